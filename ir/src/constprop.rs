@@ -0,0 +1,233 @@
+use std::collections::{HashMap, HashSet};
+
+use semantic::{consts::ConstValue, ty::Primitive};
+
+use crate::{
+    func::{Func, Module},
+    inst::{BlockId, Const, Inst, InstKind, Terminator, Value},
+};
+
+/// What is known about a value while propagating constants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Lattice {
+    /// No definition reaching the value was evaluated yet.
+    Unknown,
+    Const(Const),
+    /// The value may differ between executions.
+    Varying,
+}
+
+impl Lattice {
+    /// Returns what is known about a value that is either `self` or `other`.
+    fn meet(&self, other: &Lattice) -> Lattice {
+        match (self, other) {
+            (Lattice::Unknown, other) | (other, Lattice::Unknown) => other.clone(),
+            (Lattice::Const(a), Lattice::Const(b)) if a == b => self.clone(),
+            _ => Lattice::Varying,
+        }
+    }
+}
+
+/// Propagates constants through every function of the module, see [constprop_func].
+pub fn constprop(module: &mut Module) -> bool {
+    module
+        .funcs
+        .iter_mut()
+        .fold(false, |changed, func| constprop_func(func) | changed)
+}
+
+/// Replaces the values that are the same in every execution by constants, turns
+/// branches on constant conditions into jumps and removes the blocks and block
+/// parameters this makes redundant, returning whether anything changed.
+///
+/// Uses sparse conditional constant propagation as described by Wegman and Zadeck in
+/// "Constant Propagation with Conditional Branches", so values only computed on paths
+/// that are never taken don't keep others from being constant. Operations that would
+/// fail at runtime, like a division by zero, are left for the runtime to report. The
+/// instructions computing the replaced values are left to [crate::dce].
+pub fn constprop_func(func: &mut Func) -> bool {
+    let (values, executable) = analyze(func);
+    let mut changed = false;
+
+    for id in func.block_ids() {
+        if !executable.contains(&id) {
+            continue;
+        }
+
+        for inst in &mut func.block_mut(id).insts {
+            if let Some(result) = inst.result
+                && let Lattice::Const(value) = &values[result.index()]
+                && !matches!(inst.kind, InstKind::Const(_))
+            {
+                inst.kind = InstKind::Const(value.clone());
+                changed = true;
+            }
+        }
+
+        let block = func.block_mut(id);
+        if let Terminator::Branch {
+            cond,
+            then,
+            otherwise,
+        } = &block.term
+        {
+            let edge = match &values[cond.index()] {
+                Lattice::Const(Const::Bool(true)) => Some(then.clone()),
+                Lattice::Const(Const::Bool(false)) => Some(otherwise.clone()),
+                _ if then == otherwise => Some(then.clone()),
+                _ => None,
+            };
+            if let Some(edge) = edge {
+                block.term = Terminator::Jump(edge);
+                changed = true;
+            }
+        }
+    }
+
+    // the parameters are replaced last, since the values replacing them are unknown
+    // to the analysis.
+    let mut replacements = HashMap::new();
+    for id in func.block_ids() {
+        if !executable.contains(&id) {
+            continue;
+        }
+        for index in (0..func.block(id).params.len()).rev() {
+            let param = func.block(id).params[index];
+            if let Lattice::Const(value) = &values[param.index()] {
+                let ty = func.ty(param);
+                let constant = func.new_value(ty);
+                let inst = Inst {
+                    result: Some(constant),
+                    kind: InstKind::Const(value.clone()),
+                    span: None,
+                };
+                func.block_mut(id).insts.insert(0, inst);
+                func.remove_param(id, index);
+                replacements.insert(param, constant);
+            }
+        }
+    }
+    if !replacements.is_empty() {
+        func.replace_uses(&replacements);
+        changed = true;
+    }
+
+    // jumps removed from branches may leave parameters receiving a single value.
+    changed |= func.remove_unreachable_blocks();
+    func.remove_trivial_params() | changed
+}
+
+/// Returns what is known about each value and the blocks execution may reach.
+fn analyze(func: &Func) -> (Vec<Lattice>, HashSet<BlockId>) {
+    let mut values = vec![Lattice::Unknown; func.value_count()];
+    for &param in &func.params {
+        values[param.index()] = Lattice::Varying;
+    }
+
+    let mut executable = HashSet::from([BlockId::ENTRY]);
+    // the jumps that may be taken, by their block and their index among its jumps.
+    let mut edges = HashSet::new();
+
+    let order = func.reverse_postorder();
+    let mut predecessors = func.predecessors();
+    predecessors.iter_mut().for_each(Vec::dedup);
+
+    let update = |values: &mut Vec<Lattice>, value: Value, new: Lattice| {
+        let met = values[value.index()].meet(&new);
+        let changed = values[value.index()] != met;
+        values[value.index()] = met;
+        changed
+    };
+
+    // the lattice values only ever move from unknown to constant to varying, so
+    // iterating until nothing changes terminates.
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for &id in &order {
+            if !executable.contains(&id) {
+                continue;
+            }
+            let block = func.block(id);
+
+            for (index, &param) in block.params.iter().enumerate() {
+                let mut value = Lattice::Unknown;
+                for &pred in &predecessors[id.index()] {
+                    for (edge_index, edge) in func.block(pred).term.edges().iter().enumerate() {
+                        if edge.block == id && edges.contains(&(pred, edge_index)) {
+                            value = value.meet(&values[edge.args[index].index()]);
+                        }
+                    }
+                }
+                changed |= update(&mut values, param, value);
+            }
+
+            for inst in &block.insts {
+                if let Some(result) = inst.result {
+                    let value = evaluate(func, &inst.kind, &values, func.ty(result));
+                    changed |= update(&mut values, result, value);
+                }
+            }
+
+            let taken: &[usize] = match &block.term {
+                Terminator::Jump(_) => &[0],
+                Terminator::Branch { cond, .. } => match &values[cond.index()] {
+                    Lattice::Unknown => &[],
+                    Lattice::Const(Const::Bool(true)) => &[0],
+                    Lattice::Const(Const::Bool(false)) => &[1],
+                    _ => &[0, 1],
+                },
+                Terminator::Return(_) | Terminator::Panic { .. } | Terminator::Unreachable => &[],
+            };
+            let targets = block.term.edges();
+            for &index in taken {
+                changed |= edges.insert((id, index));
+                changed |= executable.insert(targets[index].block);
+            }
+        }
+    }
+
+    (values, executable)
+}
+
+/// Returns what is known about the result of an instruction of type `ty`.
+fn evaluate(func: &Func, kind: &InstKind, values: &[Lattice], ty: Primitive) -> Lattice {
+    let operands: Vec<_> = kind
+        .operands()
+        .iter()
+        .map(|operand| &values[operand.index()])
+        .collect();
+    if operands.contains(&&Lattice::Varying) || matches!(kind, InstKind::Call(..)) {
+        return Lattice::Varying;
+    }
+    if operands.contains(&&Lattice::Unknown) {
+        return Lattice::Unknown;
+    }
+    let constants: Vec<_> = operands
+        .into_iter()
+        .map(|operand| match operand {
+            Lattice::Const(value) => value,
+            _ => unreachable!("the operands are constant"),
+        })
+        .collect();
+
+    let folded = match kind {
+        InstKind::Const(value) => Some(value.clone()),
+        InstKind::Unary(op, _) => ConstValue::unary(*op, &constants[0].to_value(), ty)
+            .ok()
+            .flatten()
+            .map(Const::from),
+        InstKind::Binary(op, lhs, _) => {
+            let (lhs_value, rhs_value) = (constants[0].to_value(), constants[1].to_value());
+            ConstValue::binary(*op, &lhs_value, &rhs_value, func.ty(*lhs))
+                .ok()
+                .flatten()
+                .map(Const::from)
+        }
+        InstKind::Cast(_) => constants[0].cast(ty),
+        InstKind::Call(..) => None,
+    };
+
+    folded.map_or(Lattice::Varying, Lattice::Const)
+}
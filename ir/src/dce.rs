@@ -0,0 +1,124 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    func::{Func, Module},
+    inst::{BlockId, Terminator},
+};
+
+/// Removes dead code from every function of the module, see [dce_func].
+pub fn dce(module: &mut Module) -> bool {
+    module
+        .funcs
+        .iter_mut()
+        .fold(false, |changed, func| dce_func(func) | changed)
+}
+
+/// Removes the blocks execution never reaches, the pure instructions whose results
+/// are unused and the block parameters nothing uses, returning whether anything was
+/// removed.
+///
+/// Values only used to compute each other, like a counter of a loop whose result is
+/// never read, are removed as well, since only the values reaching a side effect or a
+/// terminator are kept.
+pub fn dce_func(func: &mut Func) -> bool {
+    let mut changed = func.remove_unreachable_blocks();
+
+    let mut insts = HashMap::new();
+    let mut params = HashMap::new();
+    let mut worklist = Vec::new();
+    for id in func.block_ids() {
+        let block = func.block(id);
+        for (index, &param) in block.params.iter().enumerate() {
+            params.insert(param, (id, index));
+        }
+        for (index, inst) in block.insts.iter().enumerate() {
+            if let Some(result) = inst.result {
+                insts.insert(result, (id, index));
+            }
+            if !inst.kind.is_pure(func) {
+                worklist.extend(inst.kind.operands());
+            }
+        }
+
+        // the arguments of jumps are only needed if their parameters are.
+        match &block.term {
+            Terminator::Branch { cond, .. } => worklist.push(*cond),
+            Terminator::Return(value) | Terminator::Panic { message: value, .. } => {
+                worklist.extend(value)
+            }
+            Terminator::Jump(_) | Terminator::Unreachable => {}
+        }
+    }
+
+    let predecessors = func.predecessors();
+    let mut live = HashSet::new();
+    while let Some(value) = worklist.pop() {
+        if !live.insert(value) {
+            continue;
+        }
+
+        if let Some(&(id, index)) = insts.get(&value) {
+            worklist.extend(func.block(id).insts[index].kind.operands());
+        } else if let Some(&(id, index)) = params.get(&value) {
+            for &pred in &predecessors[id.index()] {
+                let edges = func.block(pred).term.edges();
+                let args = edges.iter().filter(|edge| edge.block == id);
+                worklist.extend(args.map(|edge| edge.args[index]));
+            }
+        }
+    }
+
+    for id in func.block_ids() {
+        let pure: Vec<_> = func
+            .block(id)
+            .insts
+            .iter()
+            .map(|inst| inst.kind.is_pure(func))
+            .collect();
+        let block = func.block_mut(id);
+        let count = block.insts.len();
+        let mut pure = pure.into_iter();
+        block.insts.retain(|inst| {
+            let pure = pure.next().unwrap();
+            !pure || inst.result.is_some_and(|result| live.contains(&result))
+        });
+        changed |= block.insts.len() != count;
+
+        if id == BlockId::ENTRY {
+            continue;
+        }
+        for index in (0..func.block(id).params.len()).rev() {
+            if !live.contains(&func.block(id).params[index]) {
+                func.remove_param(id, index);
+                changed = true;
+            }
+        }
+    }
+
+    changed
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{dce::dce, parse::parse_module};
+
+    #[test]
+    fn changed() {
+        let source = "
+            fn @f(%0: i64) -> i64 {
+            bb0:
+              %1 = const i64 1
+              jump bb1(%1)
+            bb1(%2: i64):
+              ret i64 %0
+            }
+        ";
+        let mut module = parse_module(source).unwrap();
+        assert!(dce(&mut module));
+        assert_eq!(
+            module.to_string(),
+            "fn @f(%0: i64) -> i64 {\nbb0:\n  jump bb1\nbb1:\n  ret i64 %0\n}\n"
+        );
+        assert!(!dce(&mut module));
+    }
+}
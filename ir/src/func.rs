@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+
+use semantic::ty::Primitive;
+
+use crate::inst::{BlockId, Inst, Terminator, Value};
+
+/// A straight sequence of instructions, entered at its start with a value for each
+/// parameter and left by its terminator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub params: Vec<Value>,
+    pub insts: Vec<Inst>,
+    pub term: Terminator,
+}
+
+impl Default for Block {
+    fn default() -> Self {
+        Self {
+            params: Vec::new(),
+            insts: Vec::new(),
+            term: Terminator::Unreachable,
+        }
+    }
+}
+
+/// A function in SSA form, where every value is defined once and values flowing in
+/// from several predecessors are passed as block parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Func {
+    pub name: String,
+    pub params: Vec<Value>,
+    /// The type of the returned value, or [None] for functions returning `()`.
+    pub ret: Option<Primitive>,
+    /// The blocks, starting with [BlockId::ENTRY].
+    pub blocks: Vec<Block>,
+    /// The type of each value.
+    types: Vec<Primitive>,
+}
+
+impl Func {
+    pub fn new(name: impl Into<String>, ret: Option<Primitive>) -> Self {
+        Self {
+            name: name.into(),
+            params: Vec::new(),
+            ret,
+            blocks: vec![Block::default()],
+            types: Vec::new(),
+        }
+    }
+
+    /// Creates a new value of the given type, which is still to be defined.
+    pub fn new_value(&mut self, ty: Primitive) -> Value {
+        self.types.push(ty);
+        Value(self.types.len() as u32 - 1)
+    }
+
+    /// Returns the type of a value.
+    pub fn ty(&self, value: Value) -> Primitive {
+        self.types[value.index()]
+    }
+
+    /// Returns the number of values created, which is one more than the largest value.
+    pub fn value_count(&self) -> usize {
+        self.types.len()
+    }
+
+    /// Creates a new empty block ending in [Terminator::Unreachable].
+    pub fn new_block(&mut self) -> BlockId {
+        self.blocks.push(Block::default());
+        BlockId(self.blocks.len() as u32 - 1)
+    }
+
+    pub fn block(&self, id: BlockId) -> &Block {
+        &self.blocks[id.index()]
+    }
+
+    pub fn block_mut(&mut self, id: BlockId) -> &mut Block {
+        &mut self.blocks[id.index()]
+    }
+
+    /// Returns the ids of the blocks in order.
+    pub fn block_ids(&self) -> impl Iterator<Item = BlockId> + use<> {
+        (0..self.blocks.len() as u32).map(BlockId)
+    }
+
+    /// Returns the blocks jumping to each block, once per jump.
+    pub fn predecessors(&self) -> Vec<Vec<BlockId>> {
+        let mut predecessors = vec![Vec::new(); self.blocks.len()];
+        for id in self.block_ids() {
+            for edge in self.block(id).term.edges() {
+                predecessors[edge.block.index()].push(id);
+            }
+        }
+        predecessors
+    }
+
+    /// Returns the blocks reachable from the entry block in reverse postorder, where
+    /// every block comes after the blocks jumping to it, except along loops.
+    pub fn reverse_postorder(&self) -> Vec<BlockId> {
+        let mut seen = vec![false; self.blocks.len()];
+        let mut order = Vec::new();
+        let mut stack = vec![(BlockId::ENTRY, 0)];
+        seen[BlockId::ENTRY.index()] = true;
+
+        while let Some(&(id, visited)) = stack.last() {
+            let edges = self.block(id).term.edges();
+            // the successors are visited last to first, so the first one comes first.
+            match edges.len().checked_sub(visited + 1) {
+                Some(next) => {
+                    stack.last_mut().unwrap().1 += 1;
+                    let successor = edges[next].block;
+                    if !std::mem::replace(&mut seen[successor.index()], true) {
+                        stack.push((successor, 0));
+                    }
+                }
+                None => {
+                    order.push(id);
+                    stack.pop();
+                }
+            }
+        }
+
+        order.reverse();
+        order
+    }
+
+    /// Returns the immediate dominator of each block, the closest other block every path
+    /// from the entry block to it passes through. The entry block is its own immediate
+    /// dominator, and blocks execution never reaches have [None].
+    ///
+    /// Uses the algorithm by Cooper et al. in "A Simple, Fast Dominance Algorithm".
+    pub fn immediate_dominators(&self) -> Vec<Option<BlockId>> {
+        let order = self.reverse_postorder();
+        let mut rank = vec![usize::MAX; self.blocks.len()];
+        for (index, id) in order.iter().enumerate() {
+            rank[id.index()] = index;
+        }
+
+        let predecessors = self.predecessors();
+        let mut idom = vec![None; self.blocks.len()];
+        idom[BlockId::ENTRY.index()] = Some(BlockId::ENTRY);
+
+        let intersect = |idom: &[Option<BlockId>], mut a: BlockId, mut b: BlockId| {
+            while a != b {
+                while rank[a.index()] > rank[b.index()] {
+                    a = idom[a.index()].unwrap();
+                }
+                while rank[b.index()] > rank[a.index()] {
+                    b = idom[b.index()].unwrap();
+                }
+            }
+            a
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &id in order.iter().skip(1) {
+                let mut new = None;
+                for &pred in &predecessors[id.index()] {
+                    if idom[pred.index()].is_none() {
+                        continue;
+                    }
+                    new = Some(match new {
+                        None => pred,
+                        Some(other) => intersect(&idom, pred, other),
+                    });
+                }
+
+                if idom[id.index()] != new {
+                    idom[id.index()] = new;
+                    changed = true;
+                }
+            }
+        }
+
+        idom
+    }
+
+    /// Removes the blocks execution never reaches, keeping the others in order.
+    /// Returns whether any block was removed.
+    pub fn remove_unreachable_blocks(&mut self) -> bool {
+        let mut reachable = self.reverse_postorder();
+        if reachable.len() == self.blocks.len() {
+            return false;
+        }
+
+        reachable.sort();
+        self.reorder_blocks(&reachable);
+        true
+    }
+
+    /// Keeps only the given blocks in the given order, which must start with the entry
+    /// block and contain every block jumped to.
+    fn reorder_blocks(&mut self, order: &[BlockId]) {
+        let renumbered: HashMap<_, _> = order
+            .iter()
+            .enumerate()
+            .map(|(index, &id)| (id, BlockId(index as u32)))
+            .collect();
+
+        let mut blocks: Vec<_> = std::mem::take(&mut self.blocks)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.blocks = order
+            .iter()
+            .map(|id| blocks[id.index()].take().expect("blocks are kept once"))
+            .collect();
+
+        for block in &mut self.blocks {
+            for edge in block.term.edges_mut() {
+                edge.block = renumbered[&edge.block];
+            }
+        }
+    }
+
+    /// Replaces the uses of values by other values, following chains of replacements.
+    pub fn replace_uses(&mut self, replacements: &HashMap<Value, Value>) {
+        let resolve = |mut value: Value| {
+            while let Some(&replacement) = replacements.get(&value) {
+                value = replacement;
+            }
+            value
+        };
+
+        for block in &mut self.blocks {
+            for inst in &mut block.insts {
+                for operand in inst.kind.operands_mut() {
+                    *operand = resolve(*operand);
+                }
+            }
+            for operand in block.term.operands_mut() {
+                *operand = resolve(*operand);
+            }
+        }
+    }
+
+    /// Removes the block parameters receiving the same value from every jump, or
+    /// themselves along loops, replacing their uses by that value. Returns whether any
+    /// parameter was removed.
+    pub fn remove_trivial_params(&mut self) -> bool {
+        let mut replacements = HashMap::new();
+
+        loop {
+            let mut removed = false;
+            for id in self.block_ids().skip(1) {
+                let mut index = 0;
+                while index < self.block(id).params.len() {
+                    let param = self.block(id).params[index];
+                    match self.unique_arg(id, index, param, &replacements) {
+                        Some(value) => {
+                            replacements.insert(param, value);
+                            self.remove_param(id, index);
+                            removed = true;
+                        }
+                        None => index += 1,
+                    }
+                }
+            }
+
+            if !removed {
+                break;
+            }
+        }
+
+        self.replace_uses(&replacements);
+        !replacements.is_empty()
+    }
+
+    /// Returns the value passed for a block parameter if every jump to the block passes
+    /// that value or the parameter itself.
+    fn unique_arg(
+        &self,
+        block: BlockId,
+        index: usize,
+        param: Value,
+        replacements: &HashMap<Value, Value>,
+    ) -> Option<Value> {
+        let mut unique = None;
+        for edge in self.blocks.iter().flat_map(|block| block.term.edges()) {
+            if edge.block != block {
+                continue;
+            }
+
+            let mut arg = edge.args[index];
+            while let Some(&replacement) = replacements.get(&arg) {
+                arg = replacement;
+            }
+            if arg != param && unique.replace(arg).is_some_and(|unique| unique != arg) {
+                return None;
+            }
+        }
+        unique
+    }
+
+    /// Removes a block parameter along with the arguments passed for it.
+    pub(crate) fn remove_param(&mut self, id: BlockId, index: usize) {
+        self.block_mut(id).params.remove(index);
+        for block in &mut self.blocks {
+            for edge in block.term.edges_mut() {
+                if edge.block == id {
+                    edge.args.remove(index);
+                }
+            }
+        }
+    }
+
+    /// Orders the reachable blocks in reverse postorder, dropping the others, and
+    /// numbers the values in the order they are defined, which is the order the
+    /// textual IR lists them in.
+    pub fn renumber(&mut self) {
+        let order = self.reverse_postorder();
+        self.reorder_blocks(&order);
+
+        let mut renumbered = HashMap::new();
+        let mut types = Vec::new();
+        let mut define = |value: &mut Value| {
+            let new = Value(types.len() as u32);
+            types.push(self.types[value.index()]);
+            renumbered.insert(*value, new);
+            *value = new;
+        };
+
+        self.params.iter_mut().for_each(&mut define);
+        for block in &mut self.blocks {
+            block.params.iter_mut().for_each(&mut define);
+            for inst in &mut block.insts {
+                inst.result.iter_mut().for_each(&mut define);
+            }
+        }
+
+        self.types = types;
+        for block in &mut self.blocks {
+            for inst in &mut block.insts {
+                for operand in inst.kind.operands_mut() {
+                    *operand = renumbered[operand];
+                }
+            }
+            for operand in block.term.operands_mut() {
+                *operand = renumbered[operand];
+            }
+        }
+    }
+}
+
+/// The functions of a program lowered to the IR.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Module {
+    pub funcs: Vec<Func>,
+}
+
+impl Module {
+    /// Returns the function with the given name.
+    pub fn func(&self, name: &str) -> Option<&Func> {
+        self.funcs.iter().find(|func| func.name == name)
+    }
+}
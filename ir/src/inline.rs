@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use crate::{
+    func::{Func, Module},
+    inst::{BlockId, Edge, Inst, InstKind, Terminator, Value},
+};
+
+/// The number of instructions of the largest function calls are inlined to.
+const MAX_INSTS: usize = 32;
+
+/// Replaces the calls of small functions by the bodies of the functions, returning
+/// whether any call was inlined.
+///
+/// The bodies are taken from the module as it is before the pass, so a run inlines a
+/// single level of calls and recursive functions are never unrolled indefinitely.
+pub fn inline(module: &mut Module) -> bool {
+    let callees: HashMap<_, _> = module
+        .funcs
+        .iter()
+        .filter(|func| {
+            func.blocks
+                .iter()
+                .map(|block| block.insts.len())
+                .sum::<usize>()
+                <= MAX_INSTS
+        })
+        .map(|func| (func.name.clone(), func.clone()))
+        .collect();
+
+    module.funcs.iter_mut().fold(false, |changed, func| {
+        inline_calls(func, &callees) | changed
+    })
+}
+
+/// Inlines the calls of the given functions into `func`, except for calls of itself.
+fn inline_calls(func: &mut Func, callees: &HashMap<String, Func>) -> bool {
+    // the blocks copied from callees aren't searched, so calls are inlined once.
+    let mut worklist: Vec<_> = func.block_ids().collect();
+    let mut changed = false;
+
+    while let Some(id) = worklist.pop() {
+        let call = func
+            .block(id)
+            .insts
+            .iter()
+            .position(|inst| match &inst.kind {
+                InstKind::Call(name, _) => *name != func.name && callees.contains_key(name),
+                _ => false,
+            });
+        let Some(index) = call else {
+            continue;
+        };
+
+        // the instructions after the call continue in a new block, which receives
+        // the result of the call as a parameter.
+        let next = func.new_block();
+        let block = func.block_mut(id);
+        let rest = block.insts.split_off(index + 1);
+        let call = block.insts.pop().expect("the call is the last instruction");
+        let term = std::mem::replace(&mut block.term, Terminator::Unreachable);
+        let InstKind::Call(name, args) = call.kind else {
+            unreachable!("the instruction is a call");
+        };
+
+        let next_block = func.block_mut(next);
+        next_block.params.extend(call.result);
+        next_block.insts = rest;
+        next_block.term = term;
+
+        let entry = copy_body(func, &callees[&name], &args, next);
+        func.block_mut(id).term = Terminator::Jump(Edge::new(entry));
+        worklist.push(next);
+        changed = true;
+    }
+
+    // the copied blocks are placed after the block continuing the caller otherwise.
+    if changed {
+        func.renumber();
+    }
+    changed
+}
+
+/// Copies the blocks of `callee` into `func`, passing `args` for its parameters and
+/// jumping to `next` with the returned value instead of returning. Returns the copy
+/// of the entry block.
+fn copy_body(func: &mut Func, callee: &Func, args: &[Value], next: BlockId) -> BlockId {
+    let mut values: HashMap<Value, Value> = callee
+        .params
+        .iter()
+        .copied()
+        .zip(args.iter().copied())
+        .collect();
+    for block in &callee.blocks {
+        let results = block.insts.iter().filter_map(|inst| inst.result);
+        for value in block.params.iter().copied().chain(results) {
+            let copy = func.new_value(callee.ty(value));
+            values.insert(value, copy);
+        }
+    }
+
+    let blocks: Vec<_> = callee.blocks.iter().map(|_| func.new_block()).collect();
+    for (block, &copy) in callee.blocks.iter().zip(&blocks) {
+        let insts = block
+            .insts
+            .iter()
+            .map(|inst| {
+                let mut kind = inst.kind.clone();
+                for operand in kind.operands_mut() {
+                    *operand = values[operand];
+                }
+                Inst {
+                    result: inst.result.map(|result| values[&result]),
+                    kind,
+                    span: inst.span,
+                }
+            })
+            .collect();
+
+        let mut term = block.term.clone();
+        for operand in term.operands_mut() {
+            *operand = values[operand];
+        }
+        for edge in term.edges_mut() {
+            edge.block = blocks[edge.block.index()];
+        }
+        if let Terminator::Return(value) = term {
+            term = Terminator::Jump(Edge {
+                block: next,
+                args: value.into_iter().collect(),
+            });
+        }
+
+        let copy = func.block_mut(copy);
+        copy.params = block.params.iter().map(|param| values[param]).collect();
+        copy.insts = insts;
+        copy.term = term;
+    }
+
+    blocks[BlockId::ENTRY.index()]
+}
@@ -0,0 +1,344 @@
+use semantic::{consts::ConstValue, ty::Primitive};
+use syntax::{
+    ast::{BinaryOp, UnaryOp},
+    token::Span,
+};
+
+use crate::func::Func;
+
+/// Identifies a value defined by an instruction or a block parameter of a [crate::func::Func].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Value(pub u32);
+
+impl Value {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Identifies a [crate::func::Block] of a [crate::func::Func].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockId(pub u32);
+
+impl BlockId {
+    /// The block a function starts at.
+    pub const ENTRY: BlockId = BlockId(0);
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// A constant operand, whose type is the type of the value it defines.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Const {
+    /// An integer of any integer type.
+    Int(i128),
+    /// The bit representation of a float, held as an f64 for both float types.
+    Float(u64),
+    Bool(bool),
+    Str(String),
+}
+
+impl Const {
+    /// Returns the zero value of the given type, like `0` or `false`.
+    pub fn zero(ty: Primitive) -> Const {
+        match ty {
+            _ if ty.is_integer() => Const::Int(0),
+            _ if ty.is_float() => Const::Float(0f64.to_bits()),
+            Primitive::Bool => Const::Bool(false),
+            _ => Const::Str(String::new()),
+        }
+    }
+
+    /// Returns the constant as a value computed at compile time, which folds like the
+    /// constant expressions of the source.
+    pub fn to_value(&self) -> ConstValue {
+        match self {
+            Const::Int(value) => ConstValue::Int(*value),
+            Const::Float(bits) => ConstValue::Float(f64::from_bits(*bits)),
+            Const::Bool(value) => ConstValue::Bool(*value),
+            Const::Str(value) => ConstValue::Str(value.clone()),
+        }
+    }
+
+    /// Converts the constant to the given type like a [InstKind::Cast]: integers wrap
+    /// around, floats are rounded towards zero and saturate at the bounds of the
+    /// integer type, with `NaN` becoming `0`, and `bool`s become `0` or `1`.
+    pub fn cast(&self, to: Primitive) -> Option<Const> {
+        let float = |value: f64| match to {
+            Primitive::F32 => Some(Const::Float((value as f32 as f64).to_bits())),
+            _ => Some(Const::Float(value.to_bits())),
+        };
+
+        match *self {
+            Const::Int(value) if to.is_integer() => {
+                let bits = to.size() * 8;
+                let wrapped = value.rem_euclid(1 << bits);
+                match to.is_signed() && wrapped >= 1 << (bits - 1) {
+                    true => Some(Const::Int(wrapped - (1 << bits))),
+                    false => Some(Const::Int(wrapped)),
+                }
+            }
+            Const::Int(value) if to.is_float() => float(value as f64),
+            Const::Float(bits) if to.is_integer() => {
+                let (min, max) = to.bounds()?;
+                Some(Const::Int((f64::from_bits(bits) as i128).clamp(min, max)))
+            }
+            Const::Float(bits) if to.is_float() => float(f64::from_bits(bits)),
+            Const::Bool(value) => Const::Int(value.into()).cast(to),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConstValue> for Const {
+    fn from(value: ConstValue) -> Self {
+        match value {
+            ConstValue::Int(value) => Const::Int(value),
+            ConstValue::Float(value) => Const::Float(value.to_bits()),
+            ConstValue::Bool(value) => Const::Bool(value),
+            ConstValue::Str(value) => Const::Str(value),
+        }
+    }
+}
+
+/// An instruction, which defines the value `result` unless it produces `()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inst {
+    pub result: Option<Value>,
+    pub kind: InstKind,
+    /// The source the instruction was lowered from, used to locate runtime errors.
+    pub span: Option<Span>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstKind {
+    Const(Const),
+    Unary(UnaryOp, Value),
+    /// An arithmetic operation or a comparison of two values of the same type.
+    Binary(BinaryOp, Value, Value),
+    /// A conversion of a number or a `bool` to the type of the result.
+    Cast(Value),
+    /// A call of the function with the given name.
+    Call(String, Vec<Value>),
+}
+
+impl InstKind {
+    /// Returns the values the instruction uses.
+    pub fn operands(&self) -> Vec<Value> {
+        match self {
+            InstKind::Const(_) => Vec::new(),
+            InstKind::Unary(_, operand) | InstKind::Cast(operand) => vec![*operand],
+            InstKind::Binary(_, lhs, rhs) => vec![*lhs, *rhs],
+            InstKind::Call(_, args) => args.clone(),
+        }
+    }
+
+    /// Returns mutable references to the values the instruction uses.
+    pub fn operands_mut(&mut self) -> Vec<&mut Value> {
+        match self {
+            InstKind::Const(_) => Vec::new(),
+            InstKind::Unary(_, operand) | InstKind::Cast(operand) => vec![operand],
+            InstKind::Binary(_, lhs, rhs) => vec![lhs, rhs],
+            InstKind::Call(_, args) => args.iter_mut().collect(),
+        }
+    }
+
+    /// Returns whether the instruction does nothing but define its result, so it can be
+    /// removed when the result is unused. Integer arithmetic fails on overflow and
+    /// division by zero, and calls may do anything.
+    pub fn is_pure(&self, func: &Func) -> bool {
+        match self {
+            InstKind::Const(_) | InstKind::Cast(_) | InstKind::Unary(UnaryOp::Not, _) => true,
+            InstKind::Unary(UnaryOp::Neg, operand) => func.ty(*operand).is_float(),
+            InstKind::Binary(op, lhs, _) => is_comparison(*op) || func.ty(*lhs).is_float(),
+            InstKind::Call(..) => false,
+        }
+    }
+}
+
+/// Returns the name of a binary operation in the textual IR, like `add` for `+`.
+pub fn binary_mnemonic(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "add",
+        BinaryOp::Sub => "sub",
+        BinaryOp::Mul => "mul",
+        BinaryOp::Div => "div",
+        BinaryOp::Rem => "rem",
+        BinaryOp::Equal => "eq",
+        BinaryOp::Unequal => "ne",
+        BinaryOp::LessThan => "lt",
+        BinaryOp::LessEqual => "le",
+        BinaryOp::GreaterThan => "gt",
+        BinaryOp::GreaterEqual => "ge",
+    }
+}
+
+/// Returns the binary operation with the given name in the textual IR.
+pub fn binary_op(mnemonic: &str) -> Option<BinaryOp> {
+    const OPS: [BinaryOp; 11] = [
+        BinaryOp::Add,
+        BinaryOp::Sub,
+        BinaryOp::Mul,
+        BinaryOp::Div,
+        BinaryOp::Rem,
+        BinaryOp::Equal,
+        BinaryOp::Unequal,
+        BinaryOp::LessThan,
+        BinaryOp::LessEqual,
+        BinaryOp::GreaterThan,
+        BinaryOp::GreaterEqual,
+    ];
+    OPS.into_iter().find(|&op| binary_mnemonic(op) == mnemonic)
+}
+
+/// Returns whether a binary operation compares its operands, producing a `bool`.
+pub fn is_comparison(op: BinaryOp) -> bool {
+    !matches!(
+        op,
+        BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem
+    )
+}
+
+/// Returns the name of a unary operation in the textual IR, like `neg` for `-`.
+pub fn unary_mnemonic(op: UnaryOp) -> &'static str {
+    match op {
+        UnaryOp::Neg => "neg",
+        UnaryOp::Not => "not",
+    }
+}
+
+/// Returns the unary operation with the given name in the textual IR.
+pub fn unary_op(mnemonic: &str) -> Option<UnaryOp> {
+    [UnaryOp::Neg, UnaryOp::Not]
+        .into_iter()
+        .find(|&op| unary_mnemonic(op) == mnemonic)
+}
+
+/// A jump to a block, passing a value for each of its parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge {
+    pub block: BlockId,
+    pub args: Vec<Value>,
+}
+
+impl Edge {
+    pub fn new(block: BlockId) -> Self {
+        Self {
+            block,
+            args: Vec::new(),
+        }
+    }
+}
+
+/// The instruction ending a block, which decides where execution continues.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Terminator {
+    Jump(Edge),
+    /// A jump to `then` if `cond` is `true`, and to `otherwise` if it's `false`.
+    Branch {
+        cond: Value,
+        then: Edge,
+        otherwise: Edge,
+    },
+    /// A return from the function, with a value unless it returns `()`.
+    Return(Option<Value>),
+    /// Stops the program with an optional `str` message.
+    Panic {
+        message: Option<Value>,
+        span: Option<Span>,
+    },
+    /// Marks the end of a block execution never reaches.
+    Unreachable,
+}
+
+impl Terminator {
+    /// Returns the jumps to other blocks.
+    pub fn edges(&self) -> Vec<&Edge> {
+        match self {
+            Terminator::Jump(edge) => vec![edge],
+            Terminator::Branch {
+                then, otherwise, ..
+            } => vec![then, otherwise],
+            Terminator::Return(_) | Terminator::Panic { .. } | Terminator::Unreachable => {
+                Vec::new()
+            }
+        }
+    }
+
+    /// Returns mutable references to the jumps to other blocks.
+    pub fn edges_mut(&mut self) -> Vec<&mut Edge> {
+        match self {
+            Terminator::Jump(edge) => vec![edge],
+            Terminator::Branch {
+                then, otherwise, ..
+            } => vec![then, otherwise],
+            Terminator::Return(_) | Terminator::Panic { .. } | Terminator::Unreachable => {
+                Vec::new()
+            }
+        }
+    }
+
+    /// Returns the values the terminator uses, including the arguments of its jumps.
+    pub fn operands(&self) -> Vec<Value> {
+        let mut operands: Vec<_> = match self {
+            Terminator::Branch { cond, .. } => vec![*cond],
+            Terminator::Return(value) | Terminator::Panic { message: value, .. } => {
+                value.iter().copied().collect()
+            }
+            Terminator::Jump(_) | Terminator::Unreachable => Vec::new(),
+        };
+        for edge in self.edges() {
+            operands.extend(&edge.args);
+        }
+        operands
+    }
+
+    /// Returns mutable references to the values the terminator uses.
+    pub fn operands_mut(&mut self) -> Vec<&mut Value> {
+        match self {
+            Terminator::Jump(edge) => edge.args.iter_mut().collect(),
+            Terminator::Branch {
+                cond,
+                then,
+                otherwise,
+            } => std::iter::once(cond)
+                .chain(&mut then.args)
+                .chain(&mut otherwise.args)
+                .collect(),
+            Terminator::Return(value) | Terminator::Panic { message: value, .. } => {
+                value.iter_mut().collect()
+            }
+            Terminator::Unreachable => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use semantic::ty::Primitive;
+
+    use crate::inst::Const;
+
+    #[test]
+    fn casts() {
+        let float = |value: f64| Const::Float(value.to_bits());
+
+        assert_eq!(Const::Int(300).cast(Primitive::U8), Some(Const::Int(44)));
+        assert_eq!(Const::Int(200).cast(Primitive::I8), Some(Const::Int(-56)));
+        assert_eq!(
+            Const::Int(-1).cast(Primitive::U64),
+            Some(Const::Int(u64::MAX.into()))
+        );
+        assert_eq!(float(-3.9).cast(Primitive::I32), Some(Const::Int(-3)));
+        assert_eq!(
+            float(1e10).cast(Primitive::I32),
+            Some(Const::Int(i32::MAX.into()))
+        );
+        assert_eq!(float(f64::NAN).cast(Primitive::U8), Some(Const::Int(0)));
+        assert_eq!(float(0.1).cast(Primitive::F32), Some(float(0.1f32 as f64)));
+        assert_eq!(Const::Bool(true).cast(Primitive::F64), Some(float(1.0)));
+        assert_eq!(Const::Str("1".into()).cast(Primitive::I64), None);
+    }
+}
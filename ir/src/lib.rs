@@ -0,0 +1,10 @@
+pub mod constprop;
+pub mod dce;
+pub mod func;
+pub mod inline;
+pub mod inst;
+pub mod lower;
+pub mod parse;
+pub mod pass;
+pub mod print;
+pub mod verify;
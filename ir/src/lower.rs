@@ -0,0 +1,1309 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+use semantic::{
+    check::TypeTable,
+    consts::ConstValues,
+    mono::{MonoFunc, Monomorphized},
+    program::Program,
+    resolve::{DefId, DefKind, Resolutions},
+    ty::{Primitive, Type},
+};
+use syntax::{ast, diagnostics::Diagnostic, messages::message, source::FileId, token::Span};
+
+use crate::{
+    func::{Func, Module},
+    inst::{BlockId, Const, Edge, Inst, InstKind, Terminator, Value},
+    verify,
+};
+
+/// A construct of a checked program that can't be lowered to the IR yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LowerError {
+    /// What isn't supported, like `tuples`.
+    pub construct: &'static str,
+    /// The file of the imported module holding the construct, or [None] for the root
+    /// module.
+    pub file: Option<FileId>,
+    pub span: Span,
+}
+
+impl LowerError {
+    /// Returns the stable code of the error, see [syntax::explain::explain].
+    pub fn code(&self) -> &'static str {
+        "E0038"
+    }
+
+    /// Converts the error into a [Diagnostic].
+    pub fn diagnostic(&self) -> Diagnostic {
+        let code = self.code();
+        let diagnostic =
+            Diagnostic::error(message(code, &[("construct", &self.construct)]), self.span)
+                .with_code(code);
+
+        match self.file {
+            Some(file) => diagnostic.with_file(file),
+            None => diagnostic,
+        }
+    }
+}
+
+/// A checked module of a program lowered by [lower_program].
+pub struct CheckedModule<'a> {
+    /// The file of an imported module, or [None] for the root module holding `main`.
+    pub file: Option<FileId>,
+    /// The path qualifying the names of the module's functions, like `geometry.shapes`
+    /// for `geometry::shapes`, which is empty for the root module.
+    pub path: String,
+    pub module: &'a ast::Module,
+    pub resolutions: &'a Resolutions,
+    pub types: &'a TypeTable,
+    pub consts: &'a ConstValues,
+    pub instances: &'a Monomorphized,
+}
+
+/// Lowers the functions of a checked [Program] made of a single module to the IR, in
+/// the order they are declared, see [lower_program].
+pub fn lower(
+    module: &ast::Module,
+    resolutions: &Resolutions,
+    types: &TypeTable,
+    consts: &ConstValues,
+    instances: &Monomorphized,
+    program: &Program,
+) -> Result<Module, Vec<LowerError>> {
+    let root = CheckedModule {
+        file: None,
+        path: String::new(),
+        module,
+        resolutions,
+        types,
+        consts,
+        instances,
+    };
+    lower_program(&[root], program)
+}
+
+/// Lowers a checked [Program] to the IR, where `modules` holds the root module last,
+/// after the modules it imports, as loaded by the driver.
+///
+/// The functions of the root module are lowered in the order they are declared, along
+/// with the functions of imported modules they call, named after their module like
+/// `geometry.area`. Generic functions are lowered once for each of their instances,
+/// like `max_i64` and `max_f64` for `max<T>`. Values other than numbers, `bool`s and
+/// `str`s aren't lowered, and each use of them is reported.
+pub fn lower_program(
+    modules: &[CheckedModule],
+    program: &Program,
+) -> Result<Module, Vec<LowerError>> {
+    let paths: HashMap<_, _> = modules
+        .iter()
+        .filter_map(|checked| Some((checked.file?, checked.path.as_str())))
+        .collect();
+    let mut requested: HashMap<FileId, Vec<String>> = HashMap::new();
+    let mut lowered = Vec::new();
+    let mut errors = Vec::new();
+
+    // importing modules come after the modules they import, and request the functions
+    // of them they call.
+    for checked in modules.iter().rev() {
+        let resolutions = checked.resolutions;
+        let entries = match checked.file {
+            None => program
+                .items()
+                .iter()
+                .copied()
+                .filter(|&def| resolutions.def(def).kind == DefKind::Func)
+                .collect(),
+            Some(file) => {
+                let names = requested.remove(&file).unwrap_or_default();
+                checked
+                    .module
+                    .items
+                    .iter()
+                    .filter_map(|item| match item {
+                        ast::Item::Func(func) if names.contains(&func.name.text) => {
+                            resolutions.decl(func.id)
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            }
+        };
+
+        let funcs = lower_module(checked, entries, &paths, &mut requested, &mut errors);
+        lowered.push(funcs);
+    }
+
+    let lowered = Module {
+        funcs: lowered.into_iter().rev().flatten().collect(),
+    };
+
+    if errors.is_empty() {
+        verify::debug_verify(&lowered, "lowering");
+        Ok(lowered)
+    } else {
+        errors.sort_by_key(|err| (err.file, err.span.start, err.span.end));
+        errors.dedup();
+        Err(errors)
+    }
+}
+
+/// A function called by a lowered function, which is lowered as well.
+enum Callee<'a> {
+    Func(DefId),
+    Instance(&'a MonoFunc),
+    /// A function of the imported module in the given file, by name.
+    Imported(FileId, String),
+}
+
+/// Lowers the given non-generic functions of a module and the functions of it they
+/// call, adding the functions of imported modules they call to `requested`.
+fn lower_module<'a>(
+    checked: &CheckedModule<'a>,
+    entries: Vec<DefId>,
+    paths: &HashMap<FileId, &str>,
+    requested: &mut HashMap<FileId, Vec<String>>,
+    errors: &mut Vec<LowerError>,
+) -> Vec<Func> {
+    let decls: HashMap<_, _> = checked
+        .module
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ast::Item::Func(func) => Some((checked.resolutions.decl(func.id)?, func)),
+            _ => None,
+        })
+        .collect();
+
+    let mut queue: Vec<_> = entries
+        .into_iter()
+        .filter(|&def| checked.resolutions.generics(def).is_empty())
+        .map(Callee::Func)
+        .rev()
+        .collect();
+    let mut funcs = HashMap::new();
+
+    while let Some(callee) = queue.pop() {
+        let (def, within) = match callee {
+            Callee::Func(def) => (def, None),
+            Callee::Instance(mono) => (mono.instance.func, Some(mono)),
+            Callee::Imported(file, name) => {
+                requested.entry(file).or_default().push(name);
+                continue;
+            }
+        };
+        let name = match within {
+            Some(mono) => qualify(&checked.path, &mono.name),
+            None => qualify(&checked.path, &checked.resolutions.def(def).name),
+        };
+        let Some(func) = decls.get(&def) else {
+            continue;
+        };
+        if funcs.contains_key(&name) {
+            continue;
+        }
+
+        let mut lowerer = FuncLowerer {
+            exprs: &checked.module.exprs,
+            file: checked.file,
+            resolutions: checked.resolutions,
+            types: checked.types,
+            consts: checked.consts,
+            instances: checked.instances,
+            within,
+            path: &checked.path,
+            paths,
+            func: Func::new(&name, None),
+            current: BlockId::ENTRY,
+            values: HashMap::new(),
+            vars: HashMap::new(),
+            defs: HashMap::new(),
+            sealed: HashSet::from([BlockId::ENTRY]),
+            incomplete: HashMap::new(),
+            preds: HashMap::new(),
+            loops: Vec::new(),
+            callees: Vec::new(),
+            errors: Vec::new(),
+        };
+        lowerer.lower_func(def, func);
+
+        if checked.file.is_some() {
+            forget_spans(&mut lowerer.func);
+        }
+        queue.extend(lowerer.callees.into_iter().rev());
+        errors.extend(lowerer.errors);
+        funcs.insert(name, lowerer.func);
+    }
+
+    // the functions are emitted in the order they are declared, then the instances.
+    let declared = checked.module.items.iter().filter_map(|item| match item {
+        ast::Item::Func(func) => checked.resolutions.decl(func.id),
+        _ => None,
+    });
+    let declared = declared.map(|def| checked.resolutions.def(def).name.as_str());
+    let instances = checked
+        .instances
+        .funcs()
+        .iter()
+        .map(|mono| mono.name.as_str());
+
+    declared
+        .chain(instances)
+        .filter_map(|name| funcs.remove(&qualify(&checked.path, name)))
+        .collect()
+}
+
+/// Returns the name of a function of the module with the given path.
+fn qualify(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+/// Removes the spans of the function, which only point into the root module's file.
+fn forget_spans(func: &mut Func) {
+    for block in &mut func.blocks {
+        for inst in &mut block.insts {
+            inst.span = None;
+        }
+        if let Terminator::Panic { span, .. } = &mut block.term {
+            *span = None;
+        }
+    }
+}
+
+/// Returns the name of the constructs with values of a type the IR has no values of.
+fn construct(ty: &Type) -> &'static str {
+    match ty {
+        Type::Tuple(_) => "tuples",
+        Type::Ref(_) | Type::Ptr(_) => "references",
+        Type::Array(..) => "arrays",
+        Type::Optional(_) => "optional values",
+        Type::Func(..) => "function values",
+        Type::Range(_) => "ranges outside of `for` loops",
+        Type::Adt(..) => "structs and enums",
+        Type::Generic(_) => "generic functions",
+        Type::Primitive(_) | Type::Never | Type::Error => "values of this type",
+    }
+}
+
+/// A loop enclosing the lowered code.
+struct LoopTarget {
+    /// The block a `continue` jumps to.
+    next: BlockId,
+    /// The block a `break` jumps to, with a parameter for the value of a `loop`.
+    exit: BlockId,
+    value: Option<Primitive>,
+}
+
+/// Lowers a function, building SSA form for the mutable bindings while lowering as
+/// described by Braun et al. in "Simple and Efficient Construction of Static Single
+/// Assignment Form".
+struct FuncLowerer<'a> {
+    exprs: &'a ast::ExprArena,
+    file: Option<FileId>,
+    resolutions: &'a Resolutions,
+    types: &'a TypeTable,
+    consts: &'a ConstValues,
+    instances: &'a Monomorphized,
+    /// The instance of the generic function being lowered, if it is one.
+    within: Option<&'a MonoFunc>,
+    /// The path of the lowered module, see [CheckedModule::path].
+    path: &'a str,
+    /// The paths of the imported modules by file.
+    paths: &'a HashMap<FileId, &'a str>,
+    func: Func,
+    /// The block instructions are added to.
+    current: BlockId,
+    /// The values of the parameters and immutable bindings.
+    values: HashMap<DefId, Value>,
+    /// The types of the mutable bindings.
+    vars: HashMap<DefId, Primitive>,
+    /// The value each mutable binding has at the end of a block.
+    defs: HashMap<(DefId, BlockId), Value>,
+    /// The blocks whose predecessors are all known.
+    sealed: HashSet<BlockId>,
+    /// The parameters added to blocks that aren't sealed yet, still missing arguments.
+    incomplete: HashMap<BlockId, Vec<(DefId, Value)>>,
+    preds: HashMap<BlockId, Vec<BlockId>>,
+    loops: Vec<LoopTarget>,
+    /// The functions called by the lowered function.
+    callees: Vec<Callee<'a>>,
+    errors: Vec<LowerError>,
+}
+
+impl<'a> FuncLowerer<'a> {
+    fn unsupported(&mut self, construct: &'static str, span: Span) {
+        let file = self.file;
+        self.errors.push(LowerError {
+            construct,
+            file,
+            span,
+        });
+    }
+
+    /// Returns the type with the generic parameters of the lowered instance replaced.
+    fn specialize(&self, ty: &'a Type) -> Cow<'a, Type> {
+        match self.within {
+            Some(mono) => Cow::Owned(ty.substitute(&mono.substitution)),
+            None => Cow::Borrowed(ty),
+        }
+    }
+
+    fn type_of(&self, id: ast::ExprId) -> Cow<'a, Type> {
+        self.specialize(self.types.expr(id))
+    }
+
+    fn def_type(&self, def: DefId) -> Option<Cow<'a, Type>> {
+        self.types.def(def).map(|ty| self.specialize(ty))
+    }
+
+    /// Returns the IR type of values of the given type, or [None] for `()` and `!`.
+    fn lower_type(&mut self, ty: &Type, span: Span) -> Option<Primitive> {
+        match ty {
+            Type::Primitive(Primitive::Unit) | Type::Never | Type::Error => None,
+            Type::Primitive(primitive) => Some(*primitive),
+            ty if *ty == Type::UNIT => None,
+            ty => {
+                self.unsupported(construct(ty), span);
+                None
+            }
+        }
+    }
+
+    /// Returns whether the IR has no values of the type of an expression.
+    fn is_unsupported(&self, id: ast::ExprId) -> bool {
+        let ty = self.type_of(id);
+        !matches!(*ty, Type::Primitive(_) | Type::Never | Type::Error) && *ty != Type::UNIT
+    }
+
+    fn expr_type(&mut self, id: ast::ExprId) -> Option<Primitive> {
+        let span = self.exprs[id].span();
+        self.lower_type(&self.type_of(id), span)
+    }
+
+    fn emit(&mut self, kind: InstKind, ty: Option<Primitive>, span: Span) -> Option<Value> {
+        let result = ty.map(|ty| self.func.new_value(ty));
+        let inst = Inst {
+            result,
+            kind,
+            span: Some(span),
+        };
+        self.func.block_mut(self.current).insts.push(inst);
+        result
+    }
+
+    fn constant(&mut self, value: Const, ty: Primitive, span: Span) -> Value {
+        let value = self.emit(InstKind::Const(value), Some(ty), span);
+        value.expect("constants have a type")
+    }
+
+    /// Returns the value, or a zero in place of the value of an expression that never
+    /// finishes, whose use is never reached.
+    fn value_or_zero(&mut self, value: Option<Value>, ty: Primitive, span: Span) -> Value {
+        match value {
+            Some(value) => value,
+            None => self.constant(Const::zero(ty), ty, span),
+        }
+    }
+
+    /// Ends the current block and continues in a new one without predecessors, holding
+    /// the code after a `return` or `break` which is never reached.
+    fn terminate(&mut self, term: Terminator) {
+        for edge in term.edges() {
+            let preds = self.preds.entry(edge.block).or_default();
+            if !preds.contains(&self.current) {
+                preds.push(self.current);
+            }
+        }
+        self.func.block_mut(self.current).term = term;
+
+        self.current = self.func.new_block();
+        self.sealed.insert(self.current);
+    }
+
+    fn jump(&mut self, block: BlockId, value: Option<Value>) {
+        let edge = Edge {
+            block,
+            args: value.into_iter().collect(),
+        };
+        self.terminate(Terminator::Jump(edge));
+    }
+
+    fn branch(&mut self, cond: Value, then: BlockId, otherwise: BlockId) {
+        self.terminate(Terminator::Branch {
+            cond,
+            then: Edge::new(then),
+            otherwise: Edge::new(otherwise),
+        });
+    }
+
+    /// Creates a block with a parameter of the given type, if any.
+    fn new_block_with(&mut self, ty: Option<Primitive>) -> (BlockId, Option<Value>) {
+        let block = self.func.new_block();
+        let param = ty.map(|ty| self.add_param(block, ty));
+        (block, param)
+    }
+
+    fn add_param(&mut self, block: BlockId, ty: Primitive) -> Value {
+        let param = self.func.new_value(ty);
+        self.func.block_mut(block).params.push(param);
+        param
+    }
+
+    /// Marks the block as having all its predecessors, completing its parameters.
+    fn seal(&mut self, block: BlockId) {
+        for (var, _) in self.incomplete.remove(&block).unwrap_or_default() {
+            self.add_param_args(var, block);
+        }
+        self.sealed.insert(block);
+    }
+
+    fn write_var(&mut self, var: DefId, value: Value) {
+        self.defs.insert((var, self.current), value);
+    }
+
+    /// Returns the value the mutable binding has at the end of the block.
+    fn read_var(&mut self, var: DefId, block: BlockId) -> Value {
+        if let Some(&value) = self.defs.get(&(var, block)) {
+            return value;
+        }
+
+        let ty = self.vars[&var];
+        let preds = self.preds.get(&block).cloned().unwrap_or_default();
+        let value = if !self.sealed.contains(&block) {
+            let param = self.add_param(block, ty);
+            self.incomplete.entry(block).or_default().push((var, param));
+            param
+        } else if let [pred] = preds[..] {
+            self.read_var(var, pred)
+        } else if preds.is_empty() {
+            // the block is never reached, or the binding is read before it's assigned.
+            let inst = Inst {
+                result: Some(self.func.new_value(ty)),
+                kind: InstKind::Const(Const::zero(ty)),
+                span: None,
+            };
+            let zero = inst.result.unwrap();
+            self.func.block_mut(block).insts.insert(0, inst);
+            zero
+        } else {
+            let param = self.add_param(block, ty);
+            self.defs.insert((var, block), param);
+            self.add_param_args(var, block);
+            param
+        };
+
+        self.defs.insert((var, block), value);
+        value
+    }
+
+    /// Passes the value the binding has in each predecessor of the block as the
+    /// argument for the parameter.
+    fn add_param_args(&mut self, var: DefId, block: BlockId) {
+        for pred in self.preds.get(&block).cloned().unwrap_or_default() {
+            let arg = self.read_var(var, pred);
+            for edge in self.func.block_mut(pred).term.edges_mut() {
+                if edge.block == block {
+                    edge.args.push(arg);
+                }
+            }
+        }
+    }
+
+    fn lower_func(&mut self, def: DefId, func: &ast::FuncDecl) {
+        let Some(ty) = self.def_type(def) else {
+            return;
+        };
+        let Type::Func(params, ret) = &*ty else {
+            return;
+        };
+
+        for (param, ty) in func.params.iter().zip(params) {
+            let Some(def) = self.resolutions.decl(param.id) else {
+                continue;
+            };
+            match self.lower_type(ty, param.span) {
+                Some(ty) => {
+                    let value = self.func.new_value(ty);
+                    self.func.params.push(value);
+                    self.values.insert(def, value);
+                }
+                None if matches!(ty, Type::Primitive(Primitive::Unit) | Type::Never)
+                    || *ty == Type::UNIT =>
+                {
+                    self.unsupported("parameters of type `()`", param.span)
+                }
+                None => {}
+            }
+        }
+
+        let span = func
+            .return_type
+            .as_ref()
+            .map_or(func.name.span, |ty| ty.span());
+        self.func.ret = self.lower_type(ret, span);
+
+        let value = self.block(&func.body);
+        self.ret(value, func.body.span);
+
+        self.func.remove_unreachable_blocks();
+        self.func.remove_trivial_params();
+        self.func.renumber();
+    }
+
+    fn ret(&mut self, value: Option<Value>, span: Span) {
+        let value = self.func.ret.map(|ty| self.value_or_zero(value, ty, span));
+        self.terminate(Terminator::Return(value));
+    }
+
+    fn block(&mut self, block: &ast::Block) -> Option<Value> {
+        for stmt in &block.stmts {
+            self.stmt(stmt);
+        }
+        block.tail.and_then(|tail| self.expr(tail))
+    }
+
+    fn stmt(&mut self, stmt: &ast::Stmt) {
+        match stmt {
+            ast::Stmt::Let(let_stmt) => self.let_stmt(let_stmt),
+            // constants are replaced by their values where they are used.
+            ast::Stmt::Const(_) | ast::Stmt::Error(_) => {}
+            ast::Stmt::Expr(expr_stmt) => {
+                self.expr(expr_stmt.expr);
+            }
+            ast::Stmt::Return(return_stmt) => {
+                let value = return_stmt.value.and_then(|value| self.expr(value));
+                self.ret(value, return_stmt.span);
+            }
+            ast::Stmt::For(for_stmt) => self.for_stmt(for_stmt),
+            ast::Stmt::Break(break_stmt) => {
+                let value = break_stmt.value.and_then(|value| self.expr(value));
+                let Some(target) = self.loops.last() else {
+                    return;
+                };
+                let (exit, ty) = (target.exit, target.value);
+                let value = ty.map(|ty| self.value_or_zero(value, ty, break_stmt.span));
+                self.jump(exit, value);
+            }
+            ast::Stmt::Continue(_) => {
+                if let Some(target) = self.loops.last() {
+                    let next = target.next;
+                    self.jump(next, None);
+                }
+            }
+        }
+    }
+
+    fn let_stmt(&mut self, let_stmt: &ast::LetStmt) {
+        let value = let_stmt.value.and_then(|value| self.expr(value));
+        // a value of a type the IR has no values of is already reported.
+        if value.is_none()
+            && let_stmt
+                .value
+                .is_some_and(|value| self.is_unsupported(value))
+        {
+            return;
+        }
+
+        match &let_stmt.pattern {
+            ast::Pattern::Binding(binding) => {
+                let Some(def) = self.resolutions.decl(binding.id) else {
+                    return;
+                };
+                let Some(ty) = self.def_type(def) else {
+                    return;
+                };
+                let Some(ty) = self.lower_type(&ty, binding.name.span) else {
+                    return;
+                };
+
+                if self.resolutions.is_assignable(def) {
+                    self.vars.insert(def, ty);
+                    if let Some(value) = value {
+                        self.write_var(def, value);
+                    }
+                } else {
+                    let value = self.value_or_zero(value, ty, let_stmt.span);
+                    self.values.insert(def, value);
+                }
+            }
+            ast::Pattern::Wildcard(_) => {}
+            pattern => self.unsupported("destructuring `let` bindings", pattern.span()),
+        }
+    }
+
+    /// Lowers a `for` loop over a range, counting its binding up from the start.
+    fn for_stmt(&mut self, for_stmt: &ast::ForStmt) {
+        let ast::Expr::Range(range) = &self.exprs[for_stmt.iterable] else {
+            let span = self.exprs[for_stmt.iterable].span();
+            self.unsupported("`for` loops over values other than ranges", span);
+            return;
+        };
+        let Some(var) = self.resolutions.decl(for_stmt.id) else {
+            return;
+        };
+        let iterable = self.type_of(for_stmt.iterable);
+        let Type::Range(element) = &*iterable else {
+            return;
+        };
+        let Some(ty) = self.lower_type(element, range.span) else {
+            return;
+        };
+
+        let start = self.expr(range.start);
+        let start = self.value_or_zero(start, ty, range.span);
+        let end = self.expr(range.end);
+        let end = self.value_or_zero(end, ty, range.span);
+        self.vars.insert(var, ty);
+        self.write_var(var, start);
+
+        let header = self.func.new_block();
+        self.jump(header, None);
+        self.current = header;
+
+        let inclusive = range.kind == ast::RangeKind::Inclusive;
+        let op = match inclusive {
+            true => ast::BinaryOp::LessEqual,
+            false => ast::BinaryOp::LessThan,
+        };
+        let counter = self.read_var(var, header);
+        let span = for_stmt.binding.span;
+        let cond = self.emit(
+            InstKind::Binary(op, counter, end),
+            Some(Primitive::Bool),
+            span,
+        );
+
+        let body = self.func.new_block();
+        let latch = self.func.new_block();
+        let exit = self.func.new_block();
+        self.branch(cond.unwrap(), body, exit);
+        self.seal(body);
+
+        self.loops.push(LoopTarget {
+            next: latch,
+            exit,
+            value: None,
+        });
+        self.current = body;
+        self.block(&for_stmt.body);
+        self.jump(latch, None);
+        self.loops.pop();
+        self.seal(latch);
+
+        self.current = latch;
+        let counter = self.read_var(var, latch);
+        if inclusive {
+            // stop at the end instead of counting past it, which may overflow.
+            let last = self.emit(
+                InstKind::Binary(ast::BinaryOp::Equal, counter, end),
+                Some(Primitive::Bool),
+                span,
+            );
+            let step = self.func.new_block();
+            self.branch(last.unwrap(), exit, step);
+            self.seal(step);
+            self.current = step;
+        }
+        let one = self.constant(Const::Int(1), ty, span);
+        let next = self.emit(
+            InstKind::Binary(ast::BinaryOp::Add, counter, one),
+            Some(ty),
+            span,
+        );
+        self.write_var(var, next.unwrap());
+        self.jump(header, None);
+
+        self.seal(header);
+        self.seal(exit);
+        self.current = exit;
+    }
+
+    /// Lowers an expression, converting its value to the type it's implicitly
+    /// converted to where it's used.
+    fn expr(&mut self, id: ast::ExprId) -> Option<Value> {
+        let value = self.lower_expr(id)?;
+
+        let coercion = self.types.coercion(id).map(|ty| self.specialize(ty));
+        match coercion.as_deref() {
+            Some(Type::Primitive(to)) if self.func.ty(value) != *to => {
+                let span = self.exprs[id].span();
+                self.emit(InstKind::Cast(value), Some(*to), span)
+            }
+            _ => Some(value),
+        }
+    }
+
+    fn lower_expr(&mut self, id: ast::ExprId) -> Option<Value> {
+        let exprs = self.exprs;
+        let span = exprs[id].span();
+
+        match &exprs[id] {
+            ast::Expr::Identifier(_) | ast::Expr::Path(_) => self.name(id, span),
+            ast::Expr::Integer(integer) => {
+                let ty = self.expr_type(id)?;
+                Some(self.constant(Const::Int(integer.value), ty, span))
+            }
+            ast::Expr::Float(float) => {
+                let ty = self.expr_type(id)?;
+                Some(self.constant(Const::Float(float.value_bits), ty, span))
+            }
+            ast::Expr::String(string) => {
+                let value = Const::Str(string.value.clone());
+                Some(self.constant(value, Primitive::Str, span))
+            }
+            ast::Expr::Paren(paren) => self.expr(paren.expr),
+            ast::Expr::Tuple(tuple) => {
+                if !tuple.elements.is_empty() {
+                    self.unsupported("tuples", span);
+                }
+                None
+            }
+            ast::Expr::Unary(unary) => {
+                let operand = self.expr(unary.operand)?;
+                let ty = self.expr_type(id);
+                self.emit(InstKind::Unary(unary.op, operand), ty, span)
+            }
+            ast::Expr::Binary(binary) => {
+                let lhs = self.expr(binary.lhs);
+                let rhs = self.expr(binary.rhs);
+                let ty = self.expr_type(id);
+                self.emit(InstKind::Binary(binary.op, lhs?, rhs?), ty, span)
+            }
+            ast::Expr::Cast(cast) => {
+                let value = self.expr(cast.expr)?;
+                let ty = self.expr_type(id)?;
+                match self.func.ty(value) == ty {
+                    true => Some(value),
+                    false => self.emit(InstKind::Cast(value), Some(ty), span),
+                }
+            }
+            ast::Expr::Assign(assign) => {
+                let value = self.expr(assign.value);
+                let mut target = assign.target;
+                while let ast::Expr::Paren(paren) = &exprs[target] {
+                    target = paren.expr;
+                }
+
+                match self.resolutions.expr(target) {
+                    Some(var) if self.vars.contains_key(&var) => {
+                        let ty = self.vars[&var];
+                        let value = self.value_or_zero(value, ty, span);
+                        self.write_var(var, value);
+                    }
+                    Some(_) => {}
+                    None => self.unsupported("assignments to fields", exprs[target].span()),
+                }
+                None
+            }
+            ast::Expr::Range(_) => {
+                self.unsupported("ranges outside of `for` loops", span);
+                None
+            }
+            ast::Expr::Call(call) => self.call(id, call),
+            ast::Expr::Field(_) => {
+                self.unsupported("fields", span);
+                None
+            }
+            ast::Expr::Section(_) => {
+                self.unsupported("operator sections that aren't called directly", span);
+                None
+            }
+            ast::Expr::Block(block) => self.block(block),
+            ast::Expr::If(if_expr) => self.if_expr(id, if_expr),
+            ast::Expr::While(while_expr) => {
+                let header = self.func.new_block();
+                self.jump(header, None);
+                self.current = header;
+
+                let cond = self.expr(while_expr.cond);
+                let cond = self.value_or_zero(cond, Primitive::Bool, span);
+                let body = self.func.new_block();
+                let exit = self.func.new_block();
+                self.branch(cond, body, exit);
+                self.seal(body);
+
+                self.lower_loop(header, body, exit, None, &while_expr.body);
+                None
+            }
+            ast::Expr::Loop(loop_expr) => {
+                let ty = self.expr_type(id);
+                let body = self.func.new_block();
+                let (exit, value) = self.new_block_with(ty);
+                self.jump(body, None);
+
+                self.lower_loop(body, body, exit, ty, &loop_expr.body);
+                value
+            }
+            ast::Expr::Match(match_expr) => self.match_expr(id, match_expr),
+            ast::Expr::Assert(assert) => {
+                let cond = self.expr(assert.cond);
+                let cond = self.value_or_zero(cond, Primitive::Bool, span);
+                let ok = self.func.new_block();
+                let fail = self.func.new_block();
+                self.branch(cond, ok, fail);
+                self.seal(ok);
+                self.seal(fail);
+
+                self.current = fail;
+                let message = assert.message.and_then(|message| self.expr(message));
+                self.terminate(Terminator::Panic {
+                    message,
+                    span: Some(span),
+                });
+                self.current = ok;
+                None
+            }
+            ast::Expr::Panic(panic) => {
+                let message = panic.message.and_then(|message| self.expr(message));
+                self.terminate(Terminator::Panic {
+                    message,
+                    span: Some(span),
+                });
+                None
+            }
+            ast::Expr::None(_) => {
+                self.unsupported("optional values", span);
+                None
+            }
+            ast::Expr::Error(_) => None,
+        }
+    }
+
+    /// Lowers a name referring to a value.
+    fn name(&mut self, id: ast::ExprId, span: Span) -> Option<Value> {
+        let def = self.resolutions.expr(id)?;
+
+        match self.resolutions.def(def).kind {
+            DefKind::Local | DefKind::Param => match self.values.get(&def) {
+                Some(&value) => Some(value),
+                None if self.vars.contains_key(&def) => Some(self.read_var(def, self.current)),
+                None => None,
+            },
+            DefKind::Builtin => {
+                let value = self.resolutions.def(def).name == "true";
+                Some(self.constant(Const::Bool(value), Primitive::Bool, span))
+            }
+            DefKind::Const => {
+                let ty = self.expr_type(id)?;
+                let value = Const::from(self.consts.value(def)?.clone());
+                Some(self.constant(value, ty, span))
+            }
+            DefKind::Func => {
+                self.unsupported("function values", span);
+                None
+            }
+            _ => {
+                self.unsupported("structs and enums", span);
+                None
+            }
+        }
+    }
+
+    fn call(&mut self, id: ast::ExprId, call: &ast::CallExpr) -> Option<Value> {
+        let span = call.span;
+        let mut callee = call.callee;
+        while let ast::Expr::Paren(paren) = &self.exprs[callee] {
+            callee = paren.expr;
+        }
+        if let ast::Expr::Section(section) = &self.exprs[callee] {
+            return self.apply_section(id, section, call);
+        }
+        let callee = self
+            .resolutions
+            .expr(call.callee)
+            .filter(|&def| self.resolutions.def(def).kind == DefKind::Func);
+        let Some(callee) = callee else {
+            self.unsupported("calls of function values", span);
+            return None;
+        };
+        let (name, params) = match self.resolutions.file(callee) {
+            Some(file) => {
+                // only the signatures of imported functions using primitives are known.
+                let (Some(path), Some(ty)) = (self.paths.get(&file), self.def_type(callee)) else {
+                    self.unsupported(
+                        "functions of imported modules with generics, structs or enums",
+                        span,
+                    );
+                    return None;
+                };
+                let name = self.resolutions.def(callee).name.clone();
+                self.callees.push(Callee::Imported(file, name.clone()));
+                (qualify(path, &name), ty)
+            }
+            None => {
+                let ty = self.def_type(callee)?;
+                if self.resolutions.generics(callee).is_empty() {
+                    self.callees.push(Callee::Func(callee));
+                    let name = &self.resolutions.def(callee).name;
+                    (qualify(self.path, name), ty)
+                } else {
+                    // a call in an instance of a generic function calls an instance too.
+                    let mono = self.instances.callee(self.types, id, self.within)?;
+                    self.callees.push(Callee::Instance(mono));
+                    let ty = Cow::Owned(ty.substitute(&mono.substitution));
+                    (qualify(self.path, &mono.name), ty)
+                }
+            }
+        };
+        let Type::Func(params, _) = &*params else {
+            return None;
+        };
+
+        let mut args = Vec::new();
+        for (&arg, param) in call.args.iter().zip(params) {
+            let value = self.expr(arg);
+            if let Some(ty) = self.lower_type(param, self.exprs[arg].span()) {
+                args.push(self.value_or_zero(value, ty, span));
+            }
+        }
+
+        let ty = self.expr_type(id);
+        self.emit(InstKind::Call(name, args), ty, span)
+    }
+
+    /// Lowers a call of an operator section like `(+ 1)(x)` to the operator applied to
+    /// the section's operand and the argument filling its hole.
+    fn apply_section(
+        &mut self,
+        id: ast::ExprId,
+        section: &ast::SectionExpr,
+        call: &ast::CallExpr,
+    ) -> Option<Value> {
+        // the checker reported calls with another number of arguments.
+        let [arg] = call.args[..] else {
+            return None;
+        };
+
+        // the operand is evaluated with the section, before the argument.
+        let operand = self.expr(section.lhs.or(section.rhs)?);
+        let arg = self.expr(arg);
+        let (lhs, rhs) = match section.lhs {
+            Some(_) => (operand?, arg?),
+            None => (arg?, operand?),
+        };
+
+        let ty = self.expr_type(id);
+        self.emit(InstKind::Binary(section.op, lhs, rhs), ty, call.span)
+    }
+
+    fn if_expr(&mut self, id: ast::ExprId, if_expr: &ast::IfExpr) -> Option<Value> {
+        let ty = self.expr_type(id);
+        let span = if_expr.span;
+
+        let cond = self.expr(if_expr.cond);
+        let cond = self.value_or_zero(cond, Primitive::Bool, span);
+        let then = self.func.new_block();
+        let otherwise = self.func.new_block();
+        let (merge, value) = self.new_block_with(ty);
+        self.branch(cond, then, otherwise);
+        self.seal(then);
+        self.seal(otherwise);
+
+        self.current = then;
+        let then_value = self.block(&if_expr.then_block);
+        let then_value = ty.map(|ty| self.value_or_zero(then_value, ty, span));
+        self.jump(merge, then_value);
+
+        self.current = otherwise;
+        let else_value = if_expr.else_branch.and_then(|branch| self.expr(branch));
+        let else_value = ty.map(|ty| self.value_or_zero(else_value, ty, span));
+        self.jump(merge, else_value);
+
+        self.seal(merge);
+        self.current = merge;
+        value
+    }
+
+    /// Lowers the body of a loop starting at `body`, which continues at `next` and is
+    /// left through `exit`, and continues after the loop.
+    fn lower_loop(
+        &mut self,
+        next: BlockId,
+        body: BlockId,
+        exit: BlockId,
+        value: Option<Primitive>,
+        block: &ast::Block,
+    ) {
+        self.loops.push(LoopTarget { next, exit, value });
+        self.current = body;
+        self.block(block);
+        self.jump(next, None);
+        self.loops.pop();
+
+        self.seal(next);
+        self.seal(exit);
+        self.current = exit;
+    }
+
+    /// Lowers a `match` to a chain of tests of the arms in order.
+    fn match_expr(&mut self, id: ast::ExprId, match_expr: &ast::MatchExpr) -> Option<Value> {
+        let ty = self.expr_type(id);
+        let scrutinee = self.expr(match_expr.scrutinee);
+        let (merge, value) = self.new_block_with(ty);
+
+        let mut covered = false;
+        for arm in &match_expr.arms {
+            let span = arm.span;
+            if let Some(cond) = self.test(&arm.pattern, scrutinee) {
+                let matched = self.func.new_block();
+                let next = self.func.new_block();
+                self.branch(cond, matched, next);
+                self.seal(matched);
+                self.seal(next);
+
+                self.current = matched;
+                let arm_value = self.expr(arm.value);
+                let arm_value = ty.map(|ty| self.value_or_zero(arm_value, ty, span));
+                self.jump(merge, arm_value);
+                self.current = next;
+            } else {
+                // the arm matches every value, so the arms after it are never reached.
+                let arm_value = self.expr(arm.value);
+                let arm_value = ty.map(|ty| self.value_or_zero(arm_value, ty, span));
+                self.jump(merge, arm_value);
+                covered = true;
+                break;
+            }
+        }
+        if !covered {
+            // the arms cover every value, as checked by the type checker.
+            self.terminate(Terminator::Unreachable);
+        }
+
+        self.seal(merge);
+        self.current = merge;
+        value
+    }
+
+    /// Binds the pattern to the scrutinee, returning the condition of it matching, or
+    /// [None] if it matches every value.
+    fn test(&mut self, pattern: &ast::Pattern, scrutinee: Option<Value>) -> Option<Value> {
+        let span = pattern.span();
+        let constant = match pattern {
+            ast::Pattern::Wildcard(_) => return None,
+            ast::Pattern::Binding(binding) => {
+                if let Some(def) = self.resolutions.decl(binding.id) {
+                    if let Some(scrutinee) = scrutinee {
+                        self.values.insert(def, scrutinee);
+                    }
+                    return None;
+                }
+                let def = self.resolutions.node(binding.id)?;
+                Const::Bool(self.resolutions.def(def).name == "true")
+            }
+            ast::Pattern::Path(path) => {
+                let def = self.resolutions.node(path.id)?;
+                if self.resolutions.def(def).kind != DefKind::Builtin {
+                    self.unsupported("enum patterns", span);
+                    return None;
+                }
+                Const::Bool(self.resolutions.def(def).name == "true")
+            }
+            ast::Pattern::Integer(integer) => Const::Int(integer.value),
+            ast::Pattern::Float(float) => Const::Float(float.value_bits),
+            ast::Pattern::String(string) => Const::Str(string.value.clone()),
+            ast::Pattern::Tuple(tuple) => {
+                if !tuple.elements.is_empty() {
+                    self.unsupported("tuple patterns", span);
+                }
+                return None;
+            }
+            ast::Pattern::None(_) => {
+                self.unsupported("optional values", span);
+                return None;
+            }
+            ast::Pattern::TupleStruct(_) | ast::Pattern::Struct(_) => {
+                self.unsupported("enum patterns", span);
+                return None;
+            }
+        };
+
+        let scrutinee = scrutinee?;
+        let ty = self.func.ty(scrutinee);
+        let constant = self.constant(constant, ty, span);
+        let op = ast::BinaryOp::Equal;
+        self.emit(
+            InstKind::Binary(op, scrutinee, constant),
+            Some(Primitive::Bool),
+            span,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use semantic::{
+        callgraph::CallGraph, check::check, consts::eval_consts, mono::monomorphize,
+        program::build_program, resolve::resolve,
+    };
+    use syntax::{experiments::Experiments, lexer::Lexer, parser::Parser};
+
+    use crate::{
+        func::Module,
+        lower::{LowerError, lower},
+    };
+
+    fn lower_source(source: &str) -> Result<Module, Vec<LowerError>> {
+        lower_with(source, Experiments::default())
+    }
+
+    fn lower_with(source: &str, experiments: Experiments) -> Result<Module, Vec<LowerError>> {
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let mut parser = Parser::new(&tokens);
+        parser.set_experiments(experiments);
+        let (module, errors) = parser.parse_module();
+        assert_eq!(errors, []);
+
+        let resolutions = resolve(&module).resolutions;
+        let consts = eval_consts(&module, &resolutions).values;
+        let checked = check(&module, &resolutions, &consts);
+        assert_eq!(checked.errors, []);
+
+        let instances = monomorphize(&resolutions, &checked.types);
+        let call_graph = CallGraph::build(&module, &resolutions);
+        let program = build_program(&module, &resolutions, &checked.types, &call_graph).unwrap();
+        lower(
+            &module,
+            &resolutions,
+            &checked.types,
+            &consts,
+            &instances,
+            &program,
+        )
+    }
+
+    #[test]
+    fn lowering() {
+        let source = "
+            const LIMIT: i32 = 100;
+            fn sum(n: i64) -> i64 {
+                var total = 0;
+                for i in 0..n {
+                    if i % 2 == 0 { total = total + i; }
+                }
+                total
+            }
+            fn main() -> i32 {
+                var y: i32 = 1;
+                while y < LIMIT { y = y * 2; }
+                assert(sum(y) > 0, \"positive\");
+                match y { 0 => 1, n => n }
+            }
+        ";
+        let module = lower_source(source).unwrap();
+
+        assert_eq!(
+            module.func("sum").unwrap().to_string(),
+            "\
+fn @sum(%0: i64) -> i64 {
+bb0:
+  %1 = const i64 0
+  %2 = const i64 0
+  jump bb1(%2, %1)
+bb1(%3: i64, %4: i64):
+  %5 = lt i64 %3, %0
+  br %5, bb2, bb7
+bb2:
+  %6 = const i64 2
+  %7 = rem i64 %3, %6
+  %8 = const i64 0
+  %9 = eq i64 %7, %8
+  br %9, bb3, bb4
+bb3:
+  %10 = add i64 %4, %3
+  jump bb5(%10)
+bb4:
+  jump bb5(%4)
+bb5(%11: i64):
+  jump bb6
+bb6:
+  %12 = const i64 1
+  %13 = add i64 %3, %12
+  jump bb1(%13, %11)
+bb7:
+  ret i64 %4
+}
+"
+        );
+
+        // `y` is widened to the `i64` parameter of `sum`.
+        let main = module.func("main").unwrap().to_string();
+        assert!(main.contains("%2 = const i32 100\n"), "{main}");
+        assert!(main.contains("%6 = cast i32 %1 to i64\n"), "{main}");
+        assert!(main.contains("panic %"), "{main}");
+    }
+
+    #[test]
+    fn generics() {
+        let source = "
+            fn max<T>(a: T, b: T) -> T { if a > b { a } else { b } }
+            fn biggest<U>(a: U, b: U, c: U) -> U { max(max(a, b), c) }
+            fn main() -> i32 {
+                let a = biggest(1, 3, 2);
+                let b = max(0.5, 1.5);
+                if b > 1.0 { a as i32 } else { 0 }
+            }
+        ";
+        let module = lower_source(source).unwrap();
+
+        let names: Vec<_> = module.funcs.iter().map(|func| func.name.as_str()).collect();
+        assert_eq!(names, ["main", "biggest_i64", "max_f64", "max_i64"]);
+
+        let biggest = module.func("biggest_i64").unwrap().to_string();
+        assert!(biggest.starts_with("fn @biggest_i64(%0: i64, %1: i64, %2: i64) -> i64"));
+        assert!(
+            biggest.contains("= call i64 @max_i64(%0, %1)\n"),
+            "{biggest}"
+        );
+    }
+
+    #[test]
+    fn unsupported() {
+        let source = "
+            struct Point { x: i64 }
+            fn origin(p: Point) -> i64 { 0 }
+            fn main() {
+                let pair = (1, 2);
+                let f = origin;
+            }
+        ";
+        let errors = lower_source(source).unwrap_err();
+        let constructs: Vec<_> = errors.iter().map(|err| err.construct).collect();
+        assert_eq!(
+            constructs,
+            ["structs and enums", "tuples", "function values"]
+        );
+    }
+
+    #[test]
+    fn sections() {
+        let experiments = Experiments { sections: true };
+        let source = "
+            fn main() -> i32 {
+                let a = (+ 1)(2);
+                let b = ((10 - _))(a);
+                if (> 5)(b) { 1 } else { 0 }
+            }
+        ";
+        let main = lower_with(source, experiments).unwrap().to_string();
+        assert!(main.contains("%2 = add i64 %1, %0\n"), "{main}");
+        assert!(main.contains("%4 = sub i64 %3, %2\n"), "{main}");
+        assert!(main.contains("%6 = gt i64 %4, %5\n"), "{main}");
+
+        // sections used as values would need closures, which the IR doesn't have.
+        let source = "fn main() { let f = (* 2); }";
+        let errors = lower_with(source, experiments).unwrap_err();
+        assert_eq!(
+            errors[0].construct,
+            "operator sections that aren't called directly"
+        );
+        assert_eq!(errors[0].code(), "E0038");
+    }
+}
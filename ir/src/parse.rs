@@ -0,0 +1,648 @@
+use std::{collections::HashMap, fmt};
+
+use semantic::ty::Primitive;
+
+use crate::{
+    func::{Func, Module},
+    inst::{
+        BlockId, Const, Edge, Inst, InstKind, Terminator, Value, binary_op, is_comparison, unary_op,
+    },
+};
+
+/// An error in the textual IR, on a line counted from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// Something other than the described construct, like "a value" or "`:`".
+    Expected(String),
+    UnknownType(String),
+    UnknownInstruction(String),
+    /// A constant that isn't a literal of its type.
+    InvalidConstant(String),
+    UndefinedValue(u32),
+    DuplicateValue(u32),
+    UndefinedBlock(u32),
+    DuplicateBlock(u32),
+    /// A block ending without a terminator.
+    MissingTerminator(u32),
+    /// An instruction after the terminator of its block.
+    AfterTerminator,
+    /// No result for an instruction producing a value.
+    MissingResult,
+    /// A result for an instruction producing `()`, or for a terminator.
+    UnexpectedResult,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::Expected(what) => write!(f, "expected {what}"),
+            ParseErrorKind::UnknownType(name) => write!(f, "unknown type `{name}`"),
+            ParseErrorKind::UnknownInstruction(name) => write!(f, "unknown instruction `{name}`"),
+            ParseErrorKind::InvalidConstant(text) => write!(f, "invalid constant `{text}`"),
+            ParseErrorKind::UndefinedValue(value) => write!(f, "undefined value `%{value}`"),
+            ParseErrorKind::DuplicateValue(value) => {
+                write!(f, "value `%{value}` is defined twice")
+            }
+            ParseErrorKind::UndefinedBlock(block) => write!(f, "undefined block `bb{block}`"),
+            ParseErrorKind::DuplicateBlock(block) => {
+                write!(f, "block `bb{block}` is defined twice")
+            }
+            ParseErrorKind::MissingTerminator(block) => {
+                write!(f, "block `bb{block}` has no terminator")
+            }
+            ParseErrorKind::AfterTerminator => {
+                write!(f, "instruction after the terminator of the block")
+            }
+            ParseErrorKind::MissingResult => write!(f, "the instruction needs a result"),
+            ParseErrorKind::UnexpectedResult => write!(f, "the instruction produces no value"),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+/// Parses a module in the textual IR written by [crate::print], skipping empty lines
+/// and lines starting with `//`.
+///
+/// Values and blocks are numbered in the order they are defined, so printing the
+/// parsed module gives back the text if it was printed from a renumbered module.
+pub fn parse_module(source: &str) -> Result<Module, ParseError> {
+    let mut lines = source
+        .lines()
+        .enumerate()
+        .map(|(index, text)| (index + 1, text.trim()))
+        .filter(|(_, text)| !text.is_empty() && !text.starts_with("//"));
+    let end = source.lines().count() + 1;
+
+    let mut module = Module::default();
+    while let Some((line, text)) = lines.next() {
+        let func = FuncParser::header(Cursor { line, rest: text })?;
+        module.funcs.push(func.body(&mut lines, end)?);
+    }
+    Ok(module)
+}
+
+/// The rest of a line of the textual IR.
+struct Cursor<'a> {
+    line: usize,
+    rest: &'a str,
+}
+
+impl<'a> Cursor<'a> {
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            line: self.line,
+            kind,
+        }
+    }
+
+    fn expected(&self, what: &str) -> ParseError {
+        self.error(ParseErrorKind::Expected(what.to_string()))
+    }
+
+    /// Skips the given token if the line continues with it.
+    fn eat(&mut self, token: &str) -> bool {
+        match self.rest.trim_start().strip_prefix(token) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), ParseError> {
+        match self.eat(token) {
+            true => Ok(()),
+            false => Err(self.expected(&format!("`{token}`"))),
+        }
+    }
+
+    fn end(&self) -> Result<(), ParseError> {
+        match self.rest.trim().is_empty() {
+            true => Ok(()),
+            false => Err(self.expected("the end of the line")),
+        }
+    }
+
+    /// Returns the next word, like a mnemonic, a type or a function name.
+    fn word(&mut self) -> Option<&'a str> {
+        let rest = self.rest.trim_start();
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+
+        self.rest = &rest[end..];
+        Some(&rest[..end])
+    }
+
+    fn number(&mut self, prefix: &str, what: &str) -> Result<u32, ParseError> {
+        self.word()
+            .and_then(|word| word.strip_prefix(prefix))
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(|| self.expected(what))
+    }
+
+    /// Returns the number of a value like `%3`.
+    fn value(&mut self) -> Result<u32, ParseError> {
+        self.expect("%")?;
+        self.number("", "a value")
+    }
+
+    /// Returns the number of a block like `bb3`.
+    fn block(&mut self) -> Result<u32, ParseError> {
+        self.number("bb", "a block")
+    }
+
+    /// Returns a type, which is [Primitive::Unit] for `unit`.
+    fn ty(&mut self) -> Result<Primitive, ParseError> {
+        let name = self.word().ok_or_else(|| self.expected("a type"))?;
+        Primitive::from_name(name)
+            .ok_or_else(|| self.error(ParseErrorKind::UnknownType(name.to_string())))
+    }
+
+    /// Returns the values in parentheses, after the opening one.
+    fn values(&mut self) -> Result<Vec<u32>, ParseError> {
+        let mut values = Vec::new();
+        if self.eat(")") {
+            return Ok(values);
+        }
+        loop {
+            values.push(self.value()?);
+            if self.eat(")") {
+                return Ok(values);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    /// Returns the values with their types in parentheses, after the opening one.
+    fn params(&mut self) -> Result<Vec<(u32, Primitive)>, ParseError> {
+        let mut params = Vec::new();
+        if self.eat(")") {
+            return Ok(params);
+        }
+        loop {
+            let value = self.value()?;
+            self.expect(":")?;
+            params.push((value, self.ty()?));
+            if self.eat(")") {
+                return Ok(params);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    /// Returns the rest of the line.
+    fn rest(&mut self) -> &'a str {
+        std::mem::take(&mut self.rest).trim()
+    }
+}
+
+/// Parses a function, first keeping the values and blocks it uses by their numbers
+/// in the text and then replacing them by the ones defined with those numbers.
+struct FuncParser {
+    func: Func,
+    values: HashMap<u32, Value>,
+    blocks: HashMap<u32, BlockId>,
+    /// The values and blocks used with the lines they are used on.
+    value_uses: Vec<(u32, usize)>,
+    block_uses: Vec<(u32, usize)>,
+}
+
+impl FuncParser {
+    /// Parses a function header like `fn @double(%0: i64) -> i64 {`.
+    fn header(mut cursor: Cursor) -> Result<FuncParser, ParseError> {
+        cursor.expect("fn")?;
+        cursor.expect("@")?;
+        let name = cursor
+            .word()
+            .ok_or_else(|| cursor.expected("a function name"))?;
+        cursor.expect("(")?;
+        let params = cursor.params()?;
+        cursor.expect("->")?;
+        let ret = Some(cursor.ty()?).filter(|&ret| ret != Primitive::Unit);
+        cursor.expect("{")?;
+        cursor.end()?;
+
+        let mut parser = FuncParser {
+            func: Func::new(name, ret),
+            values: HashMap::new(),
+            blocks: HashMap::new(),
+            value_uses: Vec::new(),
+            block_uses: Vec::new(),
+        };
+        for (number, ty) in params {
+            let value = parser.define(number, ty, &cursor)?;
+            parser.func.params.push(value);
+        }
+        Ok(parser)
+    }
+
+    /// Parses the blocks up to the closing `}` of the function.
+    fn body<'a>(
+        mut self,
+        lines: &mut impl Iterator<Item = (usize, &'a str)>,
+        end: usize,
+    ) -> Result<Func, ParseError> {
+        // the block instructions are added to and whether it's terminated.
+        let mut current: Option<(u32, BlockId, bool)> = None;
+
+        loop {
+            let Some((line, text)) = lines.next() else {
+                let kind = ParseErrorKind::Expected("`}`".to_string());
+                return Err(ParseError { line: end, kind });
+            };
+            let mut cursor = Cursor { line, rest: text };
+
+            if text == "}" || text.starts_with("bb") {
+                if let Some((number, _, false)) = current {
+                    return Err(cursor.error(ParseErrorKind::MissingTerminator(number)));
+                }
+                if text == "}" {
+                    break;
+                }
+                current = Some(self.label(&mut cursor)?);
+                continue;
+            }
+
+            let Some((_, block, terminated)) = &mut current else {
+                return Err(cursor.expected("a block label"));
+            };
+            if *terminated {
+                return Err(cursor.error(ParseErrorKind::AfterTerminator));
+            }
+            let block = *block;
+            match self.inst(&mut cursor)? {
+                Ok(inst) => self.func.block_mut(block).insts.push(inst),
+                Err(term) => {
+                    self.func.block_mut(block).term = term;
+                    *terminated = true;
+                }
+            }
+            cursor.end()?;
+        }
+
+        self.finish()
+    }
+
+    /// Parses a block label like `bb1(%2: i64):`, starting a new block.
+    fn label(&mut self, cursor: &mut Cursor) -> Result<(u32, BlockId, bool), ParseError> {
+        let number = cursor.block()?;
+        let params = match cursor.eat("(") {
+            true => cursor.params()?,
+            false => Vec::new(),
+        };
+        cursor.expect(":")?;
+        cursor.end()?;
+
+        let id = match self.blocks.is_empty() {
+            true => BlockId::ENTRY,
+            false => self.func.new_block(),
+        };
+        if self.blocks.insert(number, id).is_some() {
+            return Err(cursor.error(ParseErrorKind::DuplicateBlock(number)));
+        }
+        for (param, ty) in params {
+            let value = self.define(param, ty, cursor)?;
+            self.func.block_mut(id).params.push(value);
+        }
+        Ok((number, id, false))
+    }
+
+    fn define(&mut self, number: u32, ty: Primitive, cursor: &Cursor) -> Result<Value, ParseError> {
+        if ty == Primitive::Unit {
+            return Err(cursor.expected("a type other than `unit`"));
+        }
+
+        let value = self.func.new_value(ty);
+        match self.values.insert(number, value) {
+            Some(_) => Err(cursor.error(ParseErrorKind::DuplicateValue(number))),
+            None => Ok(value),
+        }
+    }
+
+    /// Parses a use of a value, which is replaced by the value defined with its number
+    /// once the function is parsed.
+    fn use_value(&mut self, cursor: &mut Cursor) -> Result<Value, ParseError> {
+        let number = cursor.value()?;
+        self.value_uses.push((number, cursor.line));
+        Ok(Value(number))
+    }
+
+    fn use_values(&mut self, cursor: &mut Cursor) -> Result<Vec<Value>, ParseError> {
+        let values = cursor.values()?;
+        self.value_uses
+            .extend(values.iter().map(|&number| (number, cursor.line)));
+        Ok(values.into_iter().map(Value).collect())
+    }
+
+    /// Parses a jump to a block like `bb1(%2, %3)`.
+    fn edge(&mut self, cursor: &mut Cursor) -> Result<Edge, ParseError> {
+        let number = cursor.block()?;
+        self.block_uses.push((number, cursor.line));
+        let args = match cursor.eat("(") {
+            true => self.use_values(cursor)?,
+            false => Vec::new(),
+        };
+        Ok(Edge {
+            block: BlockId(number),
+            args,
+        })
+    }
+
+    /// Parses an instruction, or the terminator of a block.
+    fn inst(&mut self, cursor: &mut Cursor) -> Result<Result<Inst, Terminator>, ParseError> {
+        let result = match cursor.rest.starts_with('%') {
+            true => {
+                let result = cursor.value()?;
+                cursor.expect("=")?;
+                Some(result)
+            }
+            false => None,
+        };
+        let mnemonic = cursor
+            .word()
+            .ok_or_else(|| cursor.expected("an instruction"))?;
+
+        let (kind, ty) = match mnemonic {
+            "jump" | "br" | "ret" | "panic" | "unreachable" => {
+                if result.is_some() {
+                    return Err(cursor.error(ParseErrorKind::UnexpectedResult));
+                }
+                return self.term(mnemonic, cursor).map(Err);
+            }
+            "const" => {
+                let ty = cursor.ty()?;
+                let text = cursor.rest();
+                let value = constant(text, ty).ok_or_else(|| {
+                    cursor.error(ParseErrorKind::InvalidConstant(text.to_string()))
+                })?;
+                (InstKind::Const(value), ty)
+            }
+            "cast" => {
+                cursor.ty()?;
+                let operand = self.use_value(cursor)?;
+                cursor.expect("to")?;
+                (InstKind::Cast(operand), cursor.ty()?)
+            }
+            "call" => {
+                let ty = cursor.ty()?;
+                cursor.expect("@")?;
+                let callee = cursor
+                    .word()
+                    .ok_or_else(|| cursor.expected("a function name"))?;
+                cursor.expect("(")?;
+                let args = self.use_values(cursor)?;
+                (InstKind::Call(callee.to_string(), args), ty)
+            }
+            _ => {
+                if let Some(op) = unary_op(mnemonic) {
+                    let ty = cursor.ty()?;
+                    (InstKind::Unary(op, self.use_value(cursor)?), ty)
+                } else if let Some(op) = binary_op(mnemonic) {
+                    let ty = cursor.ty()?;
+                    let lhs = self.use_value(cursor)?;
+                    cursor.expect(",")?;
+                    let rhs = self.use_value(cursor)?;
+                    let ty = if is_comparison(op) {
+                        Primitive::Bool
+                    } else {
+                        ty
+                    };
+                    (InstKind::Binary(op, lhs, rhs), ty)
+                } else {
+                    let name = mnemonic.to_string();
+                    return Err(cursor.error(ParseErrorKind::UnknownInstruction(name)));
+                }
+            }
+        };
+
+        let result = match (result, ty) {
+            (None, Primitive::Unit) => None,
+            (Some(_), Primitive::Unit) => {
+                return Err(cursor.error(ParseErrorKind::UnexpectedResult));
+            }
+            (Some(number), ty) => Some(self.define(number, ty, cursor)?),
+            (None, _) => return Err(cursor.error(ParseErrorKind::MissingResult)),
+        };
+        Ok(Ok(Inst {
+            result,
+            kind,
+            span: None,
+        }))
+    }
+
+    fn term(&mut self, mnemonic: &str, cursor: &mut Cursor) -> Result<Terminator, ParseError> {
+        let term = match mnemonic {
+            "jump" => Terminator::Jump(self.edge(cursor)?),
+            "br" => {
+                let cond = self.use_value(cursor)?;
+                cursor.expect(",")?;
+                let then = self.edge(cursor)?;
+                cursor.expect(",")?;
+                let otherwise = self.edge(cursor)?;
+                Terminator::Branch {
+                    cond,
+                    then,
+                    otherwise,
+                }
+            }
+            "ret" if cursor.rest.trim().is_empty() => Terminator::Return(None),
+            "ret" => {
+                cursor.ty()?;
+                Terminator::Return(Some(self.use_value(cursor)?))
+            }
+            "panic" => {
+                let message = match cursor.rest.trim().is_empty() {
+                    true => None,
+                    false => Some(self.use_value(cursor)?),
+                };
+                Terminator::Panic {
+                    message,
+                    span: None,
+                }
+            }
+            _ => Terminator::Unreachable,
+        };
+        Ok(term)
+    }
+
+    /// Replaces the values and blocks used by the ones defined with their numbers.
+    fn finish(mut self) -> Result<Func, ParseError> {
+        let values = &self.values;
+        if let Some(&(number, line)) = self
+            .value_uses
+            .iter()
+            .find(|(n, _)| !values.contains_key(n))
+        {
+            let kind = ParseErrorKind::UndefinedValue(number);
+            return Err(ParseError { line, kind });
+        }
+        let blocks = &self.blocks;
+        if let Some(&(number, line)) = self
+            .block_uses
+            .iter()
+            .find(|(n, _)| !blocks.contains_key(n))
+        {
+            let kind = ParseErrorKind::UndefinedBlock(number);
+            return Err(ParseError { line, kind });
+        }
+
+        for block in &mut self.func.blocks {
+            for inst in &mut block.insts {
+                for operand in inst.kind.operands_mut() {
+                    *operand = self.values[&operand.0];
+                }
+            }
+            for operand in block.term.operands_mut() {
+                *operand = self.values[&operand.0];
+            }
+            for edge in block.term.edges_mut() {
+                edge.block = self.blocks[&edge.block.0];
+            }
+        }
+        Ok(self.func)
+    }
+}
+
+/// Parses a constant of the given type as printed by [crate::print].
+fn constant(text: &str, ty: Primitive) -> Option<Const> {
+    match ty {
+        _ if ty.is_integer() => text.parse().ok().map(Const::Int),
+        _ if ty.is_float() => text
+            .parse::<f64>()
+            .ok()
+            .map(|value| Const::Float(value.to_bits())),
+        Primitive::Bool => text.parse().ok().map(Const::Bool),
+        Primitive::Str => string(text).map(Const::Str),
+        _ => None,
+    }
+}
+
+/// Parses a string literal with the escapes of Rust's debug format.
+fn string(text: &str) -> Option<String> {
+    let mut chars = text.strip_prefix('"')?.strip_suffix('"')?.chars();
+    let mut string = String::new();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            string.push(c);
+            continue;
+        }
+
+        let escaped = match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            'u' => {
+                let rest = chars.as_str().strip_prefix('{')?;
+                let (code, rest) = rest.split_once('}')?;
+                chars = rest.chars();
+                char::from_u32(u32::from_str_radix(code, 16).ok()?)?
+            }
+            c @ ('\\' | '"' | '\'') => c,
+            _ => return None,
+        };
+        string.push(escaped);
+    }
+    Some(string)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse::{ParseError, ParseErrorKind, parse_module};
+
+    #[test]
+    fn round_trip() {
+        let source = "\
+fn @count(%0: i64, %1: f32) -> i64 {
+bb0:
+  %2 = const i64 -3
+  %3 = const str \"tab\\t\\\"quoted\\\" \\u{1b}\"
+  %4 = cast f32 %1 to i64
+  %5 = neg i64 %4
+  %6 = ge i64 %5, %0
+  br %6, bb1, bb2(%2)
+bb1:
+  call unit @log()
+  panic %3
+bb2(%7: i64):
+  %8 = call i64 @count(%7, %1)
+  ret i64 %8
+}
+
+fn @log() -> unit {
+bb0:
+  %0 = const f64 -0.0
+  %1 = const bool false
+  br %1, bb1, bb1
+bb1:
+  ret
+}
+";
+        let module = parse_module(source).unwrap();
+        assert_eq!(module.to_string(), source);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |source: &str| parse_module(source).unwrap_err();
+        let at = |line, kind| ParseError { line, kind };
+
+        assert_eq!(
+            error("fn @f() -> i64 {\nbb0:\n  ret i64 %1\n}"),
+            at(3, ParseErrorKind::UndefinedValue(1))
+        );
+        assert_eq!(
+            error("// comment\n\nfn @f() -> unit {\nbb0:\n  jump bb1\n}"),
+            at(5, ParseErrorKind::UndefinedBlock(1))
+        );
+        assert_eq!(
+            error("fn @f() -> unit {\nbb0:\n  %0 = const i8 1\n  %0 = const i8 2\n"),
+            at(4, ParseErrorKind::DuplicateValue(0))
+        );
+        assert_eq!(
+            error("fn @f() -> unit {\nbb0:\n  ret\n  ret\n}"),
+            at(4, ParseErrorKind::AfterTerminator)
+        );
+        assert_eq!(
+            error("fn @f() -> unit {\nbb0:\n  %0 = const i8 1\nbb1:\n  ret\n}"),
+            at(4, ParseErrorKind::MissingTerminator(0))
+        );
+        assert_eq!(
+            error("fn @f() -> unit {\nbb0:\n  %0 = const bool yes\n}"),
+            at(3, ParseErrorKind::InvalidConstant("yes".into()))
+        );
+        assert_eq!(
+            error("fn @f() -> unit {\nbb0:\n  %0 = call unit @f()\n}"),
+            at(3, ParseErrorKind::UnexpectedResult)
+        );
+        assert_eq!(
+            error("fn @f() -> unit {\nbb0:\n  shl i64 %0, %0\n}"),
+            at(3, ParseErrorKind::UnknownInstruction("shl".into()))
+        );
+        assert_eq!(
+            error("fn @f() -> unit {\nbb0:\n  ret"),
+            at(4, ParseErrorKind::Expected("`}`".into()))
+        );
+        assert_eq!(
+            error("fn @f(%0 i64) -> unit {").to_string(),
+            "line 1: expected `:`"
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use std::time::{Duration, Instant};
+
+use crate::{constprop, dce, func::Module, inline, verify};
+
+/// A transformation of the IR, which returns whether it changed the module.
+#[derive(Debug, Clone, Copy)]
+pub struct Pass {
+    pub name: &'static str,
+    pub run: fn(&mut Module) -> bool,
+}
+
+/// The passes that can be run by name, like with `--passes dce`.
+pub const PASSES: [Pass; 3] = [
+    Pass {
+        name: "constprop",
+        run: constprop::constprop,
+    },
+    Pass {
+        name: "dce",
+        run: dce::dce,
+    },
+    Pass {
+        name: "inline",
+        run: inline::inline,
+    },
+];
+
+/// The passes a [PassManager] runs unless given another pipeline, in order.
+pub const DEFAULT_PIPELINE: [&str; 3] = ["inline", "constprop", "dce"];
+
+/// Returns the pass with the given name.
+pub fn pass(name: &str) -> Option<Pass> {
+    PASSES.into_iter().find(|pass| pass.name == name)
+}
+
+/// A pass run by a [PassManager].
+#[derive(Debug, Clone, Copy)]
+pub struct PassRun {
+    pub name: &'static str,
+    pub changed: bool,
+    pub time: Duration,
+}
+
+/// Runs a pipeline of passes over the IR, verifying the module after each pass in
+/// debug builds.
+#[derive(Debug, Clone)]
+pub struct PassManager {
+    pipeline: Vec<Pass>,
+}
+
+impl Default for PassManager {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_PIPELINE
+                .iter()
+                .filter_map(|name| pass(name))
+                .collect(),
+        )
+    }
+}
+
+impl PassManager {
+    /// Creates a [PassManager] running the given passes in order.
+    pub fn new(pipeline: Vec<Pass>) -> Self {
+        Self { pipeline }
+    }
+
+    pub fn pipeline(&self) -> &[Pass] {
+        &self.pipeline
+    }
+
+    /// Runs the pipeline, calling `after` with each run and the module the pass left,
+    /// for example to print it. Returns the runs in order.
+    pub fn run(
+        &self,
+        module: &mut Module,
+        mut after: impl FnMut(&PassRun, &Module),
+    ) -> Vec<PassRun> {
+        let mut runs = Vec::new();
+
+        for pass in &self.pipeline {
+            let start = Instant::now();
+            let changed = (pass.run)(module);
+            let run = PassRun {
+                name: pass.name,
+                changed,
+                time: start.elapsed(),
+            };
+
+            verify::debug_verify(module, pass.name);
+            after(&run, module);
+            runs.push(run);
+        }
+
+        runs
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        parse::parse_module,
+        pass::{PassManager, pass},
+    };
+
+    #[test]
+    fn pipeline() {
+        let source = "
+            fn @f() -> i64 {
+            bb0:
+              %0 = const i64 2
+              %1 = neg i64 %0
+              ret i64 %0
+            }
+        ";
+        let mut module = parse_module(source).unwrap();
+        let passes = ["dce", "constprop", "dce"].map(|name| pass(name).unwrap());
+        let mut printed = Vec::new();
+        let runs = PassManager::new(passes.to_vec()).run(&mut module, |run, module| {
+            printed.push((run.name, module.to_string()))
+        });
+
+        let runs: Vec<_> = runs.iter().map(|run| (run.name, run.changed)).collect();
+        assert_eq!(runs, [("dce", false), ("constprop", true), ("dce", true)]);
+        assert_eq!(printed.len(), 3);
+        assert_eq!(printed[2].1, module.to_string());
+        assert!(!module.to_string().contains("neg"));
+    }
+}
@@ -0,0 +1,291 @@
+use std::fmt;
+
+use syntax::dot::Graph;
+
+use crate::{
+    func::{Func, Module},
+    inst::{
+        BlockId, Const, Edge, Inst, InstKind, Terminator, Value, binary_mnemonic, unary_mnemonic,
+    },
+};
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "%{}", self.0)
+    }
+}
+
+impl fmt::Display for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bb{}", self.0)
+    }
+}
+
+impl fmt::Display for Const {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Const::Int(value) => write!(f, "{value}"),
+            // the debug format always has a `.` or an exponent and reads back exactly.
+            Const::Float(bits) => write!(f, "{:?}", f64::from_bits(*bits)),
+            Const::Bool(value) => write!(f, "{value}"),
+            Const::Str(value) => write!(f, "{value:?}"),
+        }
+    }
+}
+
+impl fmt::Display for Edge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.block)?;
+        if !self.args.is_empty() {
+            write!(f, "({})", List(&self.args))?;
+        }
+        Ok(())
+    }
+}
+
+/// Values separated by commas.
+struct List<'a>(&'a [Value]);
+
+impl fmt::Display for List<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, value) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Values with their types, like the parameters of a block.
+struct Params<'a>(&'a Func, &'a [Value]);
+
+impl fmt::Display for Params<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Params(func, values) = *self;
+        for (index, &value) in values.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{value}: {}", func.ty(value).name())?;
+        }
+        Ok(())
+    }
+}
+
+impl Func {
+    fn fmt_inst(&self, inst: &Inst, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(result) = inst.result {
+            write!(f, "{result} = ")?;
+        }
+        let ty = |value: Value| self.ty(value).name();
+        let result = inst.result.map_or("unit", ty);
+
+        match &inst.kind {
+            InstKind::Const(value) => write!(f, "const {result} {value}"),
+            InstKind::Unary(op, operand) => {
+                write!(f, "{} {} {operand}", unary_mnemonic(*op), ty(*operand))
+            }
+            InstKind::Binary(op, lhs, rhs) => {
+                write!(f, "{} {} {lhs}, {rhs}", binary_mnemonic(*op), ty(*lhs))
+            }
+            InstKind::Cast(operand) => write!(f, "cast {} {operand} to {result}", ty(*operand)),
+            InstKind::Call(callee, args) => write!(f, "call {result} @{callee}({})", List(args)),
+        }
+    }
+
+    fn fmt_term(&self, term: &Terminator, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match term {
+            Terminator::Jump(edge) => write!(f, "jump {edge}"),
+            Terminator::Branch {
+                cond,
+                then,
+                otherwise,
+            } => write!(f, "br {cond}, {then}, {otherwise}"),
+            Terminator::Return(Some(value)) => write!(f, "ret {} {value}", self.ty(*value).name()),
+            Terminator::Return(None) => write!(f, "ret"),
+            Terminator::Panic {
+                message: Some(message),
+                ..
+            } => write!(f, "panic {message}"),
+            Terminator::Panic { message: None, .. } => write!(f, "panic"),
+            Terminator::Unreachable => write!(f, "unreachable"),
+        }
+    }
+}
+
+/// Prints the function in the textual IR, like `fn @double(%0: i64) -> i64 { ... }`.
+impl fmt::Display for Func {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ret = self.ret.map_or("unit", |ret| ret.name());
+        writeln!(
+            f,
+            "fn @{}({}) -> {ret} {{",
+            self.name,
+            Params(self, &self.params)
+        )?;
+
+        for id in self.block_ids() {
+            write!(f, "{}", BlockText(self, id))?;
+        }
+
+        writeln!(f, "}}")
+    }
+}
+
+/// A block of a function in the textual IR, its label followed by its instructions.
+struct BlockText<'a>(&'a Func, BlockId);
+
+impl fmt::Display for BlockText<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let BlockText(func, id) = *self;
+        let block = func.block(id);
+
+        write!(f, "{id}")?;
+        if !block.params.is_empty() {
+            write!(f, "({})", Params(func, &block.params))?;
+        }
+        writeln!(f, ":")?;
+
+        for inst in &block.insts {
+            write!(f, "  ")?;
+            func.fmt_inst(inst, f)?;
+            writeln!(f)?;
+        }
+        write!(f, "  ")?;
+        func.fmt_term(&block.term, f)?;
+        writeln!(f)
+    }
+}
+
+impl Module {
+    /// Renders the control flow graph of each function as a DOT graph, with a node for
+    /// each block listing its instructions and an edge for each jump.
+    pub fn to_dot(&self) -> String {
+        let mut graph = Graph::new("cfg");
+
+        for func in &self.funcs {
+            let nodes: Vec<_> = func
+                .block_ids()
+                .map(|id| {
+                    let text = BlockText(func, id).to_string();
+                    graph.node(format!("@{} {}", func.name, text.trim_end()), "box")
+                })
+                .collect();
+
+            for (id, block) in func.block_ids().zip(&func.blocks) {
+                let labels: &[&str] = match block.term {
+                    Terminator::Branch { .. } => &["then", "else"],
+                    _ => &[],
+                };
+                for (index, edge) in block.term.edges().into_iter().enumerate() {
+                    let to = nodes[edge.block.index()];
+                    graph.edge(nodes[id.index()], to, labels.get(index).copied());
+                }
+            }
+        }
+
+        graph.render()
+    }
+}
+
+/// Prints the functions of the module in the textual IR, separated by empty lines.
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, func) in self.funcs.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{func}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use semantic::ty::Primitive;
+    use syntax::ast::UnaryOp;
+
+    use crate::{
+        func::Func,
+        inst::{Const, Inst, InstKind, Terminator},
+        parse::parse_module,
+    };
+
+    #[test]
+    fn constants() {
+        let mut func = Func::new("consts", None);
+        let constant = |func: &mut Func, value, ty| {
+            let result = func.new_value(ty);
+            let inst = Inst {
+                result: Some(result),
+                kind: InstKind::Const(value),
+                span: None,
+            };
+            func.blocks[0].insts.push(inst);
+            result
+        };
+        constant(&mut func, Const::Float(1f64.to_bits()), Primitive::F64);
+        constant(
+            &mut func,
+            Const::Float(2.5e-20f64.to_bits()),
+            Primitive::F32,
+        );
+        constant(&mut func, Const::Str("a \"b\"\n".into()), Primitive::Str);
+        let flag = constant(&mut func, Const::Bool(true), Primitive::Bool);
+        let result = func.new_value(Primitive::Bool);
+        func.blocks[0].insts.push(Inst {
+            result: Some(result),
+            kind: InstKind::Unary(UnaryOp::Not, flag),
+            span: None,
+        });
+        func.blocks[0].term = Terminator::Return(None);
+
+        assert_eq!(
+            func.to_string(),
+            "\
+fn @consts() -> unit {
+bb0:
+  %0 = const f64 1.0
+  %1 = const f32 2.5e-20
+  %2 = const str \"a \\\"b\\\"\\n\"
+  %3 = const bool true
+  %4 = not bool %3
+  ret
+}
+"
+        );
+    }
+
+    #[test]
+    fn cfg_dot() {
+        let source = "
+            fn @f(%0: bool) -> unit {
+            bb0:
+              br %0, bb1, bb2
+            bb1:
+              jump bb2
+            bb2:
+              ret
+            }
+        ";
+        let module = parse_module(source).unwrap();
+
+        assert_eq!(
+            module.to_dot(),
+            "\
+digraph \"cfg\" {
+    node [fontname = \"monospace\"];
+    n0 [label = \"@f bb0:\\n  br %0, bb1, bb2\", shape = box];
+    n1 [label = \"@f bb1:\\n  jump bb2\", shape = box];
+    n2 [label = \"@f bb2:\\n  ret\", shape = box];
+    n0 -> n1 [label = \"then\"];
+    n0 -> n2 [label = \"else\"];
+    n1 -> n2;
+}
+"
+        );
+    }
+}
@@ -0,0 +1,697 @@
+use std::fmt;
+
+use semantic::ty::Primitive;
+use syntax::{
+    ast::{BinaryOp, UnaryOp},
+    token::Span,
+};
+
+use crate::{
+    func::{Func, Module},
+    inst::{
+        BlockId, Const, Edge, Inst, InstKind, Terminator, Value, binary_mnemonic, is_comparison,
+        unary_mnemonic,
+    },
+};
+
+/// An error in the IR, located at a part of a block of a function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError {
+    pub func: String,
+    pub block: BlockId,
+    pub location: Location,
+    /// The source the instruction was lowered from, if any.
+    pub span: Option<Span>,
+    pub kind: VerifyErrorKind,
+}
+
+/// The part of a block a [VerifyError] is located at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Params,
+    /// The instruction with the given index in the block.
+    Inst(usize),
+    Terminator,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyErrorKind {
+    /// A function without an entry block.
+    NoBlocks,
+    EntryParams,
+    JumpToEntry,
+    UndefinedBlock(BlockId),
+    UndefinedFunc(String),
+    /// A value used or defined that the function never defines or didn't create.
+    UndefinedValue(Value),
+    DuplicateDefinition(Value),
+    /// A value used where its definition doesn't dominate, so it may not be computed.
+    NotDominated(Value),
+    TypeMismatch {
+        value: Value,
+        expected: Primitive,
+        found: Primitive,
+    },
+    /// A value produced or returned where another one is expected, with [None] for `()`.
+    ResultType {
+        expected: Option<Primitive>,
+        found: Option<Primitive>,
+    },
+    InvalidOperand {
+        op: &'static str,
+        ty: Primitive,
+    },
+    /// An instruction producing a value without a result to hold it.
+    MissingResult,
+    InvalidConst(Primitive),
+    InvalidCast {
+        from: Primitive,
+        to: Primitive,
+    },
+    ArgumentCount {
+        expected: usize,
+        found: usize,
+    },
+}
+
+/// Returns the name of an optional type, where [None] stands for `()`.
+fn name(ty: Option<Primitive>) -> &'static str {
+    ty.map_or("unit", Primitive::name)
+}
+
+impl fmt::Display for VerifyErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyErrorKind::NoBlocks => write!(f, "the function has no blocks"),
+            VerifyErrorKind::EntryParams => write!(f, "the entry block has parameters"),
+            VerifyErrorKind::JumpToEntry => write!(f, "jump to the entry block"),
+            VerifyErrorKind::UndefinedBlock(block) => write!(f, "jump to undefined block {block}"),
+            VerifyErrorKind::UndefinedFunc(name) => write!(f, "call of undefined function @{name}"),
+            VerifyErrorKind::UndefinedValue(value) => write!(f, "{value} is never defined"),
+            VerifyErrorKind::DuplicateDefinition(value) => {
+                write!(f, "{value} is defined more than once")
+            }
+            VerifyErrorKind::NotDominated(value) => {
+                write!(f, "the definition of {value} doesn't dominate this use")
+            }
+            VerifyErrorKind::TypeMismatch {
+                value,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{value} has type `{}` where `{}` is expected",
+                found.name(),
+                expected.name()
+            ),
+            VerifyErrorKind::ResultType { expected, found } => write!(
+                f,
+                "produces `{}` where `{}` is expected",
+                name(*found),
+                name(*expected)
+            ),
+            VerifyErrorKind::InvalidOperand { op, ty } => {
+                write!(f, "`{op}` can't be applied to `{}`", ty.name())
+            }
+            VerifyErrorKind::MissingResult => write!(f, "the instruction has no result"),
+            VerifyErrorKind::InvalidConst(ty) => {
+                write!(f, "the constant isn't a value of type `{}`", ty.name())
+            }
+            VerifyErrorKind::InvalidCast { from, to } => {
+                write!(f, "can't cast `{}` to `{}`", from.name(), to.name())
+            }
+            VerifyErrorKind::ArgumentCount { expected, found } => {
+                write!(f, "{found} arguments passed where {expected} are expected")
+            }
+        }
+    }
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "in @{}, {} ", self.func, self.block)?;
+        match self.location {
+            Location::Params => write!(f, "parameters")?,
+            Location::Inst(index) => write!(f, "instruction {index}")?,
+            Location::Terminator => write!(f, "terminator")?,
+        }
+        if let Some(span) = self.span {
+            write!(f, " (bytes {}..{})", span.start, span.end)?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+/// Verifies every function of the module, see [verify_func].
+pub fn verify(module: &Module) -> Result<(), Vec<VerifyError>> {
+    let errors: Vec<_> = module
+        .funcs
+        .iter()
+        .flat_map(|func| verify_func(func, module))
+        .collect();
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(errors),
+    }
+}
+
+/// Verifies the module in debug builds, panicking with the errors and the module if
+/// the given pass produced invalid IR.
+pub fn debug_verify(module: &Module, pass: &str) {
+    if cfg!(debug_assertions)
+        && let Err(errors) = verify(module)
+    {
+        let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+        panic!(
+            "invalid IR after {pass}:\n{}\n\n{module}",
+            errors.join("\n")
+        );
+    }
+}
+
+/// Returns the errors in a function, which must:
+///
+/// - start with an entry block without parameters that no block jumps to,
+/// - define each value once, before each use along every path from the entry block,
+/// - use values of the types its instructions and terminators take,
+/// - pass an argument of the right type for each parameter of a block or a function.
+///
+/// Calls are checked against the functions of `module`. Uses in blocks execution
+/// never reaches aren't checked for dominance.
+pub fn verify_func(func: &Func, module: &Module) -> Vec<VerifyError> {
+    let mut verifier = Verifier {
+        func,
+        module,
+        defs: vec![None; func.value_count()],
+        errors: Vec::new(),
+        block: BlockId::ENTRY,
+        location: Location::Params,
+        span: None,
+    };
+
+    if func.blocks.is_empty() {
+        verifier.error(VerifyErrorKind::NoBlocks);
+        return verifier.errors;
+    }
+    if !func.block(BlockId::ENTRY).params.is_empty() {
+        verifier.error(VerifyErrorKind::EntryParams);
+    }
+
+    // the dominators can only be computed if every jump goes to a block.
+    let mut undefined = false;
+    for id in func.block_ids() {
+        verifier.at(id, Location::Terminator, None);
+        for edge in func.block(id).term.edges() {
+            if edge.block.index() >= func.blocks.len() {
+                verifier.error(VerifyErrorKind::UndefinedBlock(edge.block));
+                undefined = true;
+            }
+        }
+    }
+    if undefined {
+        return verifier.errors;
+    }
+
+    verifier.definitions();
+    let idom = func.immediate_dominators();
+    for id in func.block_ids() {
+        verifier.verify_block(id, &idom);
+    }
+    verifier.errors
+}
+
+struct Verifier<'a> {
+    func: &'a Func,
+    module: &'a Module,
+    /// The block each value is defined in, with the position of its definition: 0 for
+    /// parameters and `index + 1` for instructions.
+    defs: Vec<Option<(BlockId, usize)>>,
+    errors: Vec<VerifyError>,
+    /// The location errors are reported at.
+    block: BlockId,
+    location: Location,
+    span: Option<Span>,
+}
+
+impl Verifier<'_> {
+    fn at(&mut self, block: BlockId, location: Location, span: Option<Span>) {
+        self.block = block;
+        self.location = location;
+        self.span = span;
+    }
+
+    fn error(&mut self, kind: VerifyErrorKind) {
+        self.errors.push(VerifyError {
+            func: self.func.name.clone(),
+            block: self.block,
+            location: self.location,
+            span: self.span,
+            kind,
+        });
+    }
+
+    /// Records where each value is defined.
+    fn definitions(&mut self) {
+        let func = self.func;
+        for &param in &func.params {
+            self.define(param, BlockId::ENTRY, 0);
+        }
+
+        for (id, block) in func.block_ids().zip(&func.blocks) {
+            self.at(id, Location::Params, None);
+            for &param in &block.params {
+                self.define(param, id, 0);
+            }
+            for (index, inst) in block.insts.iter().enumerate() {
+                self.at(id, Location::Inst(index), inst.span);
+                if let Some(result) = inst.result {
+                    self.define(result, id, index + 1);
+                }
+            }
+        }
+    }
+
+    fn define(&mut self, value: Value, block: BlockId, position: usize) {
+        match self.defs.get_mut(value.index()) {
+            Some(def @ None) => *def = Some((block, position)),
+            Some(Some(_)) => self.error(VerifyErrorKind::DuplicateDefinition(value)),
+            None => self.error(VerifyErrorKind::UndefinedValue(value)),
+        }
+    }
+
+    fn verify_block(&mut self, id: BlockId, idom: &[Option<BlockId>]) {
+        let block = self.func.block(id);
+        let reachable = idom[id.index()].is_some();
+
+        for (index, inst) in block.insts.iter().enumerate() {
+            self.at(id, Location::Inst(index), inst.span);
+            self.verify_inst(inst, index + 1, reachable.then_some(idom));
+        }
+
+        let span = match &block.term {
+            Terminator::Panic { span, .. } => *span,
+            _ => None,
+        };
+        self.at(id, Location::Terminator, span);
+        self.verify_term(
+            &block.term,
+            block.insts.len() + 1,
+            reachable.then_some(idom),
+        );
+    }
+
+    /// Returns the type of a value used at the given position of the current block,
+    /// checking that its definition dominates the use unless `idom` is [None].
+    fn use_value(
+        &mut self,
+        value: Value,
+        position: usize,
+        idom: Option<&[Option<BlockId>]>,
+    ) -> Option<Primitive> {
+        let Some(&Some((block, def))) = self.defs.get(value.index()) else {
+            self.error(VerifyErrorKind::UndefinedValue(value));
+            return None;
+        };
+
+        let dominates = match idom {
+            Some(_) if block == self.block => def < position,
+            Some(idom) => dominates(idom, block, self.block),
+            None => true,
+        };
+        if !dominates {
+            self.error(VerifyErrorKind::NotDominated(value));
+        }
+        Some(self.func.ty(value))
+    }
+
+    /// Checks that a used value has the expected type.
+    fn expect(
+        &mut self,
+        value: Value,
+        expected: Primitive,
+        position: usize,
+        idom: Option<&[Option<BlockId>]>,
+    ) {
+        if let Some(found) = self.use_value(value, position, idom)
+            && found != expected
+        {
+            self.error(VerifyErrorKind::TypeMismatch {
+                value,
+                expected,
+                found,
+            });
+        }
+    }
+
+    fn verify_inst(&mut self, inst: &Inst, position: usize, idom: Option<&[Option<BlockId>]>) {
+        // a result the function didn't create is reported with the definitions.
+        if inst
+            .result
+            .is_some_and(|result| result.index() >= self.func.value_count())
+        {
+            return;
+        }
+        let result = inst.result.map(|result| self.func.ty(result));
+        if result.is_none() && !matches!(inst.kind, InstKind::Call(..)) {
+            self.error(VerifyErrorKind::MissingResult);
+            return;
+        }
+
+        let produced = match &inst.kind {
+            InstKind::Const(value) => {
+                let ty = result.unwrap();
+                if !fits(value, ty) {
+                    self.error(VerifyErrorKind::InvalidConst(ty));
+                }
+                result
+            }
+            InstKind::Unary(op, operand) => {
+                let ty = self.use_value(*operand, position, idom);
+                let valid = |ty: Primitive| match op {
+                    UnaryOp::Neg => ty.is_float() || ty.is_signed(),
+                    UnaryOp::Not => ty == Primitive::Bool || ty.is_integer(),
+                };
+                if let Some(ty) = ty
+                    && !valid(ty)
+                {
+                    let op = unary_mnemonic(*op);
+                    self.error(VerifyErrorKind::InvalidOperand { op, ty });
+                }
+                ty.or(result)
+            }
+            InstKind::Binary(op, lhs, rhs) => {
+                let ty = self.use_value(*lhs, position, idom);
+                if let Some(ty) = ty {
+                    self.expect(*rhs, ty, position, idom);
+                } else {
+                    self.use_value(*rhs, position, idom);
+                }
+
+                let numeric = |ty: Primitive| ty.is_integer() || ty.is_float();
+                let valid = |ty: Primitive| match op {
+                    BinaryOp::Equal | BinaryOp::Unequal => true,
+                    _ if is_comparison(*op) => numeric(ty) || ty == Primitive::Str,
+                    _ => numeric(ty),
+                };
+                if let Some(ty) = ty
+                    && !valid(ty)
+                {
+                    let op = binary_mnemonic(*op);
+                    self.error(VerifyErrorKind::InvalidOperand { op, ty });
+                }
+                match is_comparison(*op) {
+                    true => Some(Primitive::Bool),
+                    false => ty.or(result),
+                }
+            }
+            InstKind::Cast(operand) => {
+                let castable = |ty: Primitive| ty.is_integer() || ty.is_float();
+                if let (Some(from), Some(to)) = (self.use_value(*operand, position, idom), result)
+                    && !((castable(from) || from == Primitive::Bool) && castable(to))
+                {
+                    self.error(VerifyErrorKind::InvalidCast { from, to });
+                }
+                result
+            }
+            InstKind::Call(callee, args) => {
+                let Some(callee) = self.module.func(callee) else {
+                    self.error(VerifyErrorKind::UndefinedFunc(callee.clone()));
+                    return;
+                };
+                let params = param_types(callee, &callee.params);
+                self.arguments(args, &params, position, idom);
+                callee.ret
+            }
+        };
+
+        if produced != result {
+            self.error(VerifyErrorKind::ResultType {
+                expected: produced,
+                found: result,
+            });
+        }
+    }
+
+    fn verify_term(
+        &mut self,
+        term: &Terminator,
+        position: usize,
+        idom: Option<&[Option<BlockId>]>,
+    ) {
+        match term {
+            Terminator::Jump(edge) => self.edge(edge, position, idom),
+            Terminator::Branch {
+                cond,
+                then,
+                otherwise,
+            } => {
+                self.expect(*cond, Primitive::Bool, position, idom);
+                self.edge(then, position, idom);
+                self.edge(otherwise, position, idom);
+            }
+            Terminator::Return(value) => {
+                let found = match value {
+                    Some(value) => match self.use_value(*value, position, idom) {
+                        Some(ty) => Some(ty),
+                        None => return,
+                    },
+                    None => None,
+                };
+                if found != self.func.ret {
+                    self.error(VerifyErrorKind::ResultType {
+                        expected: self.func.ret,
+                        found,
+                    });
+                }
+            }
+            Terminator::Panic { message, .. } => {
+                if let Some(message) = message {
+                    self.expect(*message, Primitive::Str, position, idom);
+                }
+            }
+            Terminator::Unreachable => {}
+        }
+    }
+
+    fn edge(&mut self, edge: &Edge, position: usize, idom: Option<&[Option<BlockId>]>) {
+        if edge.block == BlockId::ENTRY {
+            self.error(VerifyErrorKind::JumpToEntry);
+        }
+        let target = self.func.block(edge.block);
+        let params = param_types(self.func, &target.params);
+        self.arguments(&edge.args, &params, position, idom);
+    }
+
+    /// Checks the arguments passed for parameters of the given types.
+    fn arguments(
+        &mut self,
+        args: &[Value],
+        params: &[Primitive],
+        position: usize,
+        idom: Option<&[Option<BlockId>]>,
+    ) {
+        if args.len() != params.len() {
+            self.error(VerifyErrorKind::ArgumentCount {
+                expected: params.len(),
+                found: args.len(),
+            });
+        }
+        for (&arg, &ty) in args.iter().zip(params) {
+            self.expect(arg, ty, position, idom);
+        }
+    }
+}
+
+/// Returns the types of the parameters of a block or a function, skipping values the
+/// function didn't create, which are reported where they are defined.
+fn param_types(func: &Func, params: &[Value]) -> Vec<Primitive> {
+    params
+        .iter()
+        .filter(|param| param.index() < func.value_count())
+        .map(|&param| func.ty(param))
+        .collect()
+}
+
+/// Returns whether `a` dominates `b`, given the immediate dominators of the blocks.
+fn dominates(idom: &[Option<BlockId>], a: BlockId, mut b: BlockId) -> bool {
+    loop {
+        if a == b {
+            return true;
+        }
+        match idom[b.index()] {
+            Some(parent) if parent != b => b = parent,
+            _ => return false,
+        }
+    }
+}
+
+/// Returns whether a constant is a value of the given type.
+fn fits(value: &Const, ty: Primitive) -> bool {
+    match value {
+        Const::Int(value) => ty
+            .bounds()
+            .is_some_and(|(min, max)| (min..=max).contains(value)),
+        Const::Float(_) => ty.is_float(),
+        Const::Bool(_) => ty == Primitive::Bool,
+        Const::Str(_) => ty == Primitive::Str,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use semantic::ty::Primitive;
+
+    use crate::{
+        inst::{BlockId, Value},
+        parse::parse_module,
+        verify::{Location, VerifyErrorKind, verify},
+    };
+
+    fn errors(source: &str) -> Vec<(BlockId, Location, VerifyErrorKind)> {
+        let module = parse_module(source).unwrap();
+        verify(&module)
+            .err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|err| (err.block, err.location, err.kind))
+            .collect()
+    }
+
+    #[test]
+    fn valid() {
+        let source = "
+            fn @max(%0: i64, %1: i64) -> i64 {
+            bb0:
+              %2 = gt i64 %0, %1
+              br %2, bb1, bb2(%1)
+            bb1:
+              jump bb2(%0)
+            bb2(%3: i64):
+              call unit @log(%3)
+              ret i64 %3
+            }
+            fn @log(%0: i64) -> unit {
+            bb0:
+              ret
+            bb1:
+              // unreachable blocks may use any value.
+              %1 = add i64 %2, %0
+              jump bb2
+            bb2:
+              %2 = const i64 1
+              unreachable
+            }
+        ";
+        assert_eq!(errors(source), []);
+    }
+
+    #[test]
+    fn invalid() {
+        let source = "
+            fn @f(%0: i64, %1: bool) -> i32 {
+            bb0:
+              br %1, bb1, bb2
+            bb1:
+              %2 = const i64 1
+              jump bb3(%2, %0)
+            bb2:
+              %3 = neg bool %1
+              %4 = add bool %1, %1
+              %5 = cast i64 %0 to str
+              %6 = call i64 @f(%0)
+              jump bb3(%2)
+            bb3(%7: i64):
+              %8 = const i8 300
+              %9 = lt i64 %7, %0
+              br %0, bb0, bb3(%7)
+            bb4:
+              ret i64 %7
+            }
+        ";
+        assert_eq!(
+            errors(source),
+            [
+                (
+                    BlockId(1),
+                    Location::Terminator,
+                    VerifyErrorKind::ArgumentCount {
+                        expected: 1,
+                        found: 2
+                    }
+                ),
+                (
+                    BlockId(2),
+                    Location::Inst(0),
+                    VerifyErrorKind::InvalidOperand {
+                        op: "neg",
+                        ty: Primitive::Bool
+                    }
+                ),
+                (
+                    BlockId(2),
+                    Location::Inst(1),
+                    VerifyErrorKind::InvalidOperand {
+                        op: "add",
+                        ty: Primitive::Bool
+                    }
+                ),
+                (
+                    BlockId(2),
+                    Location::Inst(2),
+                    VerifyErrorKind::InvalidCast {
+                        from: Primitive::I64,
+                        to: Primitive::Str
+                    }
+                ),
+                (
+                    BlockId(2),
+                    Location::Inst(3),
+                    VerifyErrorKind::ArgumentCount {
+                        expected: 2,
+                        found: 1
+                    }
+                ),
+                (
+                    BlockId(2),
+                    Location::Inst(3),
+                    VerifyErrorKind::ResultType {
+                        expected: Some(Primitive::I32),
+                        found: Some(Primitive::I64)
+                    }
+                ),
+                (
+                    BlockId(2),
+                    Location::Terminator,
+                    VerifyErrorKind::NotDominated(Value(2))
+                ),
+                (
+                    BlockId(3),
+                    Location::Inst(0),
+                    VerifyErrorKind::InvalidConst(Primitive::I8)
+                ),
+                (
+                    BlockId(3),
+                    Location::Terminator,
+                    VerifyErrorKind::TypeMismatch {
+                        value: Value(0),
+                        expected: Primitive::Bool,
+                        found: Primitive::I64
+                    }
+                ),
+                (
+                    BlockId(3),
+                    Location::Terminator,
+                    VerifyErrorKind::JumpToEntry
+                ),
+                (
+                    BlockId(4),
+                    Location::Terminator,
+                    VerifyErrorKind::ResultType {
+                        expected: Some(Primitive::I32),
+                        found: Some(Primitive::I64)
+                    }
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use std::{fs, path::Path};
+
+use ir::{
+    parse::parse_module,
+    pass::{self, Pass, PassManager},
+    verify::verify,
+};
+
+/// Checks the output against the directives of a fixture, in the style of LLVM's
+/// FileCheck:
+///
+/// - `// CHECK: text` matches the first line containing `text` after the last match.
+/// - `// CHECK-NEXT: text` matches the line right after the last match.
+/// - `// CHECK-NOT: text` fails if `text` appears between the last match and the next.
+fn file_check(fixture: &str, output: &str) -> Result<(), String> {
+    let lines: Vec<&str> = output.lines().collect();
+    // the first line not matched yet.
+    let mut next = 0;
+    let mut forbidden: Vec<&str> = Vec::new();
+
+    let check_not = |lines: &[&str], forbidden: &[&str]| {
+        for pattern in forbidden {
+            if let Some(line) = lines.iter().find(|line| line.contains(pattern)) {
+                return Err(format!("found `{pattern}` in `{line}`"));
+            }
+        }
+        Ok(())
+    };
+
+    for directive in fixture
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("//"))
+    {
+        let directive = directive.trim();
+        if let Some(pattern) = directive.strip_prefix("CHECK:") {
+            let pattern = pattern.trim();
+            let found = (next..lines.len())
+                .find(|&index| lines[index].contains(pattern))
+                .ok_or_else(|| format!("`{pattern}` not found after line {next}"))?;
+            check_not(&lines[next..found], &forbidden)?;
+            forbidden.clear();
+            next = found + 1;
+        } else if let Some(pattern) = directive.strip_prefix("CHECK-NEXT:") {
+            let pattern = pattern.trim();
+            match lines.get(next) {
+                Some(line) if line.contains(pattern) => next += 1,
+                line => {
+                    return Err(format!(
+                        "expected `{pattern}` on line {next}, found {line:?}"
+                    ));
+                }
+            }
+        } else if let Some(pattern) = directive.strip_prefix("CHECK-NOT:") {
+            forbidden.push(pattern.trim());
+        }
+    }
+
+    check_not(&lines[next..], &forbidden)
+}
+
+/// Returns the passes named by the `// RUN: dce, ...` directives of a fixture.
+fn passes(fixture: &str) -> Result<Vec<Pass>, String> {
+    fixture
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("// RUN:"))
+        .flat_map(|names| names.split(','))
+        .map(|name| pass::pass(name.trim()).ok_or(format!("unknown pass `{}`", name.trim())))
+        .collect()
+}
+
+/// Runs the fixture in the given file, which is parsed, checked to print back the
+/// same module, transformed by its passes and compared with its directives.
+fn run(path: &Path) -> Result<(), String> {
+    let fixture = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let mut module = parse_module(&fixture).map_err(|err| err.to_string())?;
+    if let Err(errors) = verify(&module) {
+        let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+        return Err(errors.join("\n"));
+    }
+
+    let printed = module.to_string();
+    let reparsed = parse_module(&printed).map_err(|err| format!("reparsing: {err}"))?;
+    if reparsed != module {
+        return Err(format!("reparsing changed the module:\n{printed}"));
+    }
+
+    PassManager::new(passes(&fixture)?).run(&mut module, |_, _| {});
+    let output = module.to_string();
+
+    file_check(&fixture, &output).map_err(|err| format!("{err} in the output:\n{output}"))
+}
+
+#[test]
+fn fixtures() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "elir"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+
+    let failures: Vec<_> = paths
+        .iter()
+        .filter_map(|path| {
+            run(path)
+                .err()
+                .map(|err| format!("{}: {err}", path.display()))
+        })
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
@@ -0,0 +1,614 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    mem,
+};
+
+use syntax::{
+    ast,
+    diagnostics::Diagnostic,
+    dot::Graph,
+    messages::message,
+    token::Span,
+    visit::{self, Visitor},
+};
+
+use crate::resolve::{DefId, DefKind, Resolutions};
+
+/// A call of a function from the body of another or the same function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Call {
+    pub callee: DefId,
+    /// The [Span] of the call expression.
+    pub span: Span,
+}
+
+/// The functions of a module along with the functions each of them calls.
+#[derive(Debug, Default)]
+pub struct CallGraph {
+    /// The functions in the order they are declared.
+    funcs: Vec<DefId>,
+    /// The calls in the body of each function, in the order they appear.
+    calls: HashMap<DefId, Vec<Call>>,
+}
+
+impl CallGraph {
+    /// Builds the [CallGraph] of the given [ast::Module] from the calls whose callee
+    /// resolves to a function. Functions used as values without being called don't
+    /// add edges.
+    pub fn build(module: &ast::Module, resolutions: &Resolutions) -> Self {
+        let mut collector = CallCollector {
+            exprs: &module.exprs,
+            resolutions,
+            function: None,
+            graph: CallGraph::default(),
+        };
+        collector.visit_module(module);
+
+        // the functions of imported modules aren't part of the graph.
+        let mut graph = collector.graph;
+        let funcs: HashSet<_> = graph.funcs.iter().copied().collect();
+        for calls in graph.calls.values_mut() {
+            calls.retain(|call| funcs.contains(&call.callee));
+        }
+
+        graph
+    }
+
+    /// Returns the functions in the order they are declared.
+    pub fn functions(&self) -> &[DefId] {
+        &self.funcs
+    }
+
+    /// Returns the calls made by the given function, in the order they appear.
+    pub fn calls(&self, caller: DefId) -> &[Call] {
+        self.calls.get(&caller).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the functions calling the given function, in the order they are declared.
+    pub fn callers(&self, callee: DefId) -> impl Iterator<Item = DefId> + '_ {
+        self.funcs
+            .iter()
+            .copied()
+            .filter(move |&caller| self.calls(caller).iter().any(|call| call.callee == callee))
+    }
+
+    /// Returns the groups of functions that call each other, either directly like a
+    /// function calling itself or through other functions of the group.
+    ///
+    /// Each group is a strongly connected component of the graph, listed in the
+    /// order its first function is declared.
+    pub fn cycles(&self) -> Vec<Vec<DefId>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            index: HashMap::new(),
+            lowlink: HashMap::new(),
+            stack: Vec::new(),
+            components: Vec::new(),
+        };
+        for &func in &self.funcs {
+            if !tarjan.index.contains_key(&func) {
+                tarjan.connect(func);
+            }
+        }
+
+        let mut cycles: Vec<_> = tarjan
+            .components
+            .into_iter()
+            .filter(|component| match component.as_slice() {
+                [func] => self.calls(*func).iter().any(|call| call.callee == *func),
+                _ => true,
+            })
+            .map(|mut component| {
+                component.sort_by_key(|func| self.position(*func));
+                component
+            })
+            .collect();
+        cycles.sort_by_key(|component| self.position(component[0]));
+
+        cycles
+    }
+
+    /// Returns the shortest chain of calls leading from the function back to itself,
+    /// starting and ending with it, or [None] if it isn't recursive.
+    pub fn cycle_through(&self, func: DefId) -> Option<Vec<DefId>> {
+        let mut previous = HashMap::new();
+        let mut queue = VecDeque::from([func]);
+
+        while let Some(caller) = queue.pop_front() {
+            for call in self.calls(caller) {
+                if call.callee == func {
+                    let mut path = vec![caller];
+                    while let Some(&prev) = previous.get(path.last().unwrap()) {
+                        path.push(prev);
+                    }
+                    path.reverse();
+                    path.push(func);
+                    return Some(path);
+                }
+
+                if call.callee != func && !previous.contains_key(&call.callee) {
+                    previous.insert(call.callee, caller);
+                    queue.push_back(call.callee);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Renders the [CallGraph] as a DOT graph, with one edge for each pair of a
+    /// caller and a callee.
+    pub fn to_dot(&self, resolutions: &Resolutions) -> String {
+        let mut graph = Graph::new("callgraph");
+
+        let nodes: HashMap<_, _> = self
+            .funcs
+            .iter()
+            .map(|&func| (func, graph.node(&resolutions.def(func).name, "box")))
+            .collect();
+
+        for &caller in &self.funcs {
+            let mut seen = HashSet::new();
+            for call in self.calls(caller) {
+                if seen.insert(call.callee) {
+                    graph.edge(nodes[&caller], nodes[&call.callee], None);
+                }
+            }
+        }
+
+        graph.render()
+    }
+
+    fn position(&self, func: DefId) -> usize {
+        self.funcs.iter().position(|&other| other == func).unwrap()
+    }
+}
+
+/// Collects the functions and the calls in their bodies.
+struct CallCollector<'ast> {
+    exprs: &'ast ast::ExprArena,
+    resolutions: &'ast Resolutions,
+    /// The function whose body is visited.
+    function: Option<DefId>,
+    graph: CallGraph,
+}
+
+impl<'ast> Visitor<'ast> for CallCollector<'ast> {
+    fn exprs(&self) -> &'ast ast::ExprArena {
+        self.exprs
+    }
+
+    fn visit_func(&mut self, func: &'ast ast::FuncDecl) {
+        let def = self
+            .resolutions
+            .decl(func.id)
+            .filter(|&def| self.resolutions.def(def).kind == DefKind::Func);
+        if let Some(def) = def {
+            self.graph.funcs.push(def);
+        }
+
+        let outer = mem::replace(&mut self.function, def);
+        visit::walk_func(self, func);
+        self.function = outer;
+    }
+
+    fn visit_expr(&mut self, id: ast::ExprId) {
+        if let ast::Expr::Call(call) = &self.exprs[id]
+            && let Some(caller) = self.function
+            && let Some(callee) = function(self.exprs, self.resolutions, call.callee)
+        {
+            self.graph.calls.entry(caller).or_default().push(Call {
+                callee,
+                span: call.span,
+            });
+        }
+
+        visit::walk_expr(self, id);
+    }
+}
+
+/// Returns the function the expression refers to, looking through parentheses.
+fn function(exprs: &ast::ExprArena, resolutions: &Resolutions, id: ast::ExprId) -> Option<DefId> {
+    match &exprs[id] {
+        ast::Expr::Identifier(_) | ast::Expr::Path(_) => resolutions
+            .expr(id)
+            .filter(|&def| resolutions.def(def).kind == DefKind::Func),
+        ast::Expr::Paren(paren) => function(exprs, resolutions, paren.expr),
+        _ => None,
+    }
+}
+
+/// Tarjan's algorithm for the strongly connected components of a [CallGraph].
+struct Tarjan<'a> {
+    graph: &'a CallGraph,
+    index: HashMap<DefId, usize>,
+    lowlink: HashMap<DefId, usize>,
+    stack: Vec<DefId>,
+    components: Vec<Vec<DefId>>,
+}
+
+impl Tarjan<'_> {
+    fn connect(&mut self, func: DefId) {
+        let index = self.index.len();
+        self.index.insert(func, index);
+        self.lowlink.insert(func, index);
+        self.stack.push(func);
+
+        for call in self.graph.calls(func) {
+            let callee = call.callee;
+            if !self.index.contains_key(&callee) {
+                self.connect(callee);
+                let low = self.lowlink[&func].min(self.lowlink[&callee]);
+                self.lowlink.insert(func, low);
+            } else if self.stack.contains(&callee) {
+                let low = self.lowlink[&func].min(self.index[&callee]);
+                self.lowlink.insert(func, low);
+            }
+        }
+
+        if self.lowlink[&func] == self.index[&func] {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                component.push(member);
+                if member == func {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+/// A warning about a function that can't return without calling itself again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecursionWarning {
+    pub name: String,
+    /// The names of the functions along the recursive calls, starting and ending
+    /// with `name`.
+    pub cycle: Vec<String>,
+    /// The [Span] of the function's name in its declaration.
+    pub span: Span,
+    /// The [Span] of the call continuing the recursion.
+    pub call: Span,
+}
+
+impl RecursionWarning {
+    /// Returns the name of the lint, used to allow or deny it.
+    pub fn name(&self) -> &'static str {
+        "unbounded_recursion"
+    }
+
+    /// Returns the stable code of the warning, see [syntax::explain::explain].
+    pub fn code(&self) -> &'static str {
+        "W0007"
+    }
+
+    /// Converts the warning into a [Diagnostic].
+    pub fn diagnostic(&self) -> Diagnostic {
+        let code = self.code();
+
+        let mut diagnostic = Diagnostic::warning(message(code, &[("name", &self.name)]), self.span)
+            .with_label(self.call, message("W0007.label", &[]));
+        if self.cycle.len() > 2 {
+            let cycle = self
+                .cycle
+                .iter()
+                .map(|name| format!("`{name}`"))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            diagnostic = diagnostic.with_note(message("W0007.note", &[("cycle", &cycle)]));
+        }
+
+        diagnostic.with_code(code).with_lint(self.name())
+    }
+}
+
+/// Finds the recursive functions without a base case, that is groups of functions
+/// calling each other where every path through each of their bodies reaches a call
+/// into the group.
+///
+/// A path leaving a function through `return`, `break`, `continue`, `panic` or a
+/// failed `assert` before such a call counts as a base case, as does a loop that
+/// may run forever without it.
+pub fn find_unbounded_recursion(
+    module: &ast::Module,
+    resolutions: &Resolutions,
+    graph: &CallGraph,
+) -> Vec<RecursionWarning> {
+    let bodies: HashMap<_, _> = module
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ast::Item::Func(func) => Some((resolutions.decl(func.id)?, &func.body)),
+            _ => None,
+        })
+        .collect();
+
+    let mut warnings = Vec::new();
+    for cycle in graph.cycles() {
+        let group: HashSet<_> = cycle.iter().copied().collect();
+        let analysis = Recursion {
+            exprs: &module.exprs,
+            resolutions,
+            group: &group,
+        };
+
+        let unbounded = cycle.iter().all(|func| {
+            bodies
+                .get(func)
+                .is_some_and(|body| analysis.block(body) == Flow::Recurses)
+        });
+        if !unbounded {
+            continue;
+        }
+
+        for &func in &cycle {
+            let def = resolutions.def(func);
+            let path = graph.cycle_through(func).unwrap_or_default();
+            let call = graph
+                .calls(func)
+                .iter()
+                .find(|call| group.contains(&call.callee))
+                .map_or(def.span, |call| call.span);
+
+            warnings.push(RecursionWarning {
+                name: def.name.clone(),
+                cycle: path
+                    .into_iter()
+                    .map(|func| resolutions.def(func).name.clone())
+                    .collect(),
+                span: def.span,
+                call,
+            });
+        }
+    }
+
+    warnings.sort_by_key(|warning| warning.span.start);
+    warnings
+}
+
+/// How control can leave a piece of code with respect to recursion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flow {
+    /// Every path reaches a call into the recursive group.
+    Recurses,
+    /// Some path may leave the function or loop before such a call.
+    Escapes,
+    /// Control continues after the code without having made such a call.
+    Continues,
+}
+
+impl Flow {
+    /// Combines the flows of alternative paths, only one of which is taken.
+    fn either(flows: impl IntoIterator<Item = Flow>) -> Flow {
+        let mut result = Flow::Recurses;
+        for flow in flows {
+            match flow {
+                Flow::Escapes => return Flow::Escapes,
+                Flow::Continues => result = Flow::Continues,
+                Flow::Recurses => {}
+            }
+        }
+        result
+    }
+
+    /// Returns the flow of code that may or may not run, like the body of a `while`.
+    fn optional(self) -> Flow {
+        match self {
+            Flow::Escapes => Flow::Escapes,
+            Flow::Recurses | Flow::Continues => Flow::Continues,
+        }
+    }
+}
+
+/// Decides whether code always recurses into a group of functions.
+struct Recursion<'a> {
+    exprs: &'a ast::ExprArena,
+    resolutions: &'a Resolutions,
+    group: &'a HashSet<DefId>,
+}
+
+impl Recursion<'_> {
+    /// Returns the flow of running the given pieces of code one after another.
+    fn sequence(&self, flows: impl IntoIterator<Item = Flow>) -> Flow {
+        flows
+            .into_iter()
+            .find(|&flow| flow != Flow::Continues)
+            .unwrap_or(Flow::Continues)
+    }
+
+    /// Returns the flow of evaluating the expressions from left to right.
+    fn exprs(&self, ids: &[ast::ExprId]) -> Flow {
+        self.sequence(ids.iter().map(|&id| self.expr(id)))
+    }
+
+    /// Returns the flow of code evaluating the value and then leaving, like a `return`.
+    fn leave(&self, value: Option<ast::ExprId>) -> Flow {
+        match value.map(|value| self.expr(value)) {
+            Some(Flow::Recurses) => Flow::Recurses,
+            _ => Flow::Escapes,
+        }
+    }
+
+    fn block(&self, block: &ast::Block) -> Flow {
+        let stmts = block.stmts.iter().map(|stmt| self.stmt(stmt));
+        let tail = block.tail.map(|tail| self.expr(tail));
+        self.sequence(stmts.chain(tail))
+    }
+
+    fn stmt(&self, stmt: &ast::Stmt) -> Flow {
+        match stmt {
+            ast::Stmt::Let(let_stmt) => match let_stmt.value {
+                Some(value) => self.expr(value),
+                None => Flow::Continues,
+            },
+            // constants are evaluated at compile time.
+            ast::Stmt::Const(_) => Flow::Continues,
+            ast::Stmt::Expr(expr_stmt) => self.expr(expr_stmt.expr),
+            ast::Stmt::Return(return_stmt) => self.leave(return_stmt.value),
+            ast::Stmt::For(for_stmt) => self.sequence([
+                self.expr(for_stmt.iterable),
+                self.block(&for_stmt.body).optional(),
+            ]),
+            ast::Stmt::Break(break_stmt) => self.leave(break_stmt.value),
+            ast::Stmt::Continue(_) | ast::Stmt::Error(_) => Flow::Escapes,
+        }
+    }
+
+    fn expr(&self, id: ast::ExprId) -> Flow {
+        match &self.exprs[id] {
+            ast::Expr::Identifier(_)
+            | ast::Expr::Path(_)
+            | ast::Expr::Integer(_)
+            | ast::Expr::Float(_)
+            | ast::Expr::String(_)
+            | ast::Expr::None(_) => Flow::Continues,
+            ast::Expr::Paren(paren) => self.expr(paren.expr),
+            ast::Expr::Tuple(tuple) => self.exprs(&tuple.elements),
+            ast::Expr::Unary(unary) => self.expr(unary.operand),
+            ast::Expr::Binary(binary) => self.exprs(&[binary.lhs, binary.rhs]),
+            ast::Expr::Cast(cast) => self.expr(cast.expr),
+            ast::Expr::Field(field) => self.expr(field.base),
+            ast::Expr::Assign(assign) => self.exprs(&[assign.target, assign.value]),
+            ast::Expr::Range(range) => self.exprs(&[range.start, range.end]),
+            ast::Expr::Call(call) => {
+                let callee = function(self.exprs, self.resolutions, call.callee);
+                let recursive = callee.is_some_and(|callee| self.group.contains(&callee));
+                let call_flow = if recursive {
+                    Flow::Recurses
+                } else {
+                    Flow::Continues
+                };
+
+                self.sequence([self.expr(call.callee), self.exprs(&call.args), call_flow])
+            }
+            ast::Expr::Section(section) => {
+                let operands: Vec<_> = section.lhs.iter().chain(&section.rhs).copied().collect();
+                self.exprs(&operands)
+            }
+            ast::Expr::Block(block) => self.block(block),
+            ast::Expr::If(if_expr) => {
+                let then_flow = self.block(&if_expr.then_block);
+                let branches = match if_expr.else_branch {
+                    Some(else_branch) => Flow::either([then_flow, self.expr(else_branch)]),
+                    None => then_flow.optional(),
+                };
+                self.sequence([self.expr(if_expr.cond), branches])
+            }
+            ast::Expr::While(while_expr) => self.sequence([
+                self.expr(while_expr.cond),
+                self.block(&while_expr.body).optional(),
+            ]),
+            // a loop that never recurses runs forever or leaves through a `break`.
+            ast::Expr::Loop(loop_expr) => match self.block(&loop_expr.body) {
+                Flow::Recurses => Flow::Recurses,
+                Flow::Escapes | Flow::Continues => Flow::Escapes,
+            },
+            ast::Expr::Match(match_expr) => {
+                let arms = Flow::either(match_expr.arms.iter().map(|arm| self.expr(arm.value)));
+                self.sequence([self.expr(match_expr.scrutinee), arms])
+            }
+            ast::Expr::Assert(assert) => match self.expr(assert.cond) {
+                Flow::Recurses => Flow::Recurses,
+                Flow::Escapes | Flow::Continues => Flow::Escapes,
+            },
+            ast::Expr::Panic(panic) => self.leave(panic.message),
+            ast::Expr::Error(_) => Flow::Escapes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use syntax::{explain::explain, lexer::Lexer, parser::Parser};
+
+    use crate::{
+        callgraph::{CallGraph, find_unbounded_recursion},
+        resolve::{DefId, resolve},
+    };
+
+    #[test]
+    fn call_graph() {
+        let source = "
+            fn even(n: u64) -> bool { if n == 0 { true } else { odd(n - 1) } }
+            fn odd(n: u64) -> bool { if n == 0 { false } else { even(n - 1) } }
+            fn fact(n: u64) -> u64 { if n == 0 { 1 } else { n * fact(n - 1) } }
+            fn main() { even(4); fact(5); fact(6); }
+        ";
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let (module, _) = Parser::new(&tokens).parse_module();
+        let resolutions = resolve(&module).resolutions;
+
+        let graph = CallGraph::build(&module, &resolutions);
+        let name = |def: DefId| resolutions.def(def).name.as_str();
+        let names = |defs: &[DefId]| defs.iter().map(|&def| name(def)).collect::<Vec<_>>();
+
+        assert_eq!(names(graph.functions()), ["even", "odd", "fact", "main"]);
+        let main = graph.functions()[3];
+        let callees: Vec<_> = graph.calls(main).iter().map(|call| call.callee).collect();
+        assert_eq!(names(&callees), ["even", "fact", "fact"]);
+        let fact = graph.functions()[2];
+        let callers: Vec<_> = graph.callers(fact).collect();
+        assert_eq!(names(&callers), ["fact", "main"]);
+
+        let cycles: Vec<_> = graph.cycles().iter().map(|cycle| names(cycle)).collect();
+        assert_eq!(cycles, [vec!["even", "odd"], vec!["fact"]]);
+        let odd = graph.functions()[1];
+        assert_eq!(
+            names(&graph.cycle_through(odd).unwrap()),
+            ["odd", "even", "odd"]
+        );
+        assert_eq!(graph.cycle_through(main), None);
+
+        let dot = graph.to_dot(&resolutions);
+        assert_eq!(dot.matches("->").count(), 5);
+
+        assert!(find_unbounded_recursion(&module, &resolutions, &graph).is_empty());
+    }
+
+    #[test]
+    fn unbounded_recursion() {
+        let source = "
+            fn forever(n: i64) -> i64 { forever(n + 1) }
+            fn ping(n: i64) { let x = n * 2; pong(x); }
+            fn pong(n: i64) { if n > 0 { ping(n); } else { ping(-n); } }
+            fn guarded(n: i64) -> i64 { if n == 0 { return 0; } guarded(n - 1) }
+            fn asserted(n: i64) { assert(n > 0); asserted(n - 1); }
+            fn looping(n: i64) { loop { looping(n); } }
+            fn main() {}
+        ";
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let (module, _) = Parser::new(&tokens).parse_module();
+        let resolutions = resolve(&module).resolutions;
+        let graph = CallGraph::build(&module, &resolutions);
+
+        let warnings = find_unbounded_recursion(&module, &resolutions, &graph);
+        let found: Vec<_> = warnings
+            .iter()
+            .map(|warning| {
+                (
+                    warning.name.as_str(),
+                    warning.cycle.join(" -> "),
+                    &source[warning.call.start..warning.call.end],
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "forever",
+                    "forever -> forever".to_string(),
+                    "forever(n + 1)"
+                ),
+                ("ping", "ping -> pong -> ping".to_string(), "pong(x)"),
+                ("pong", "pong -> ping -> pong".to_string(), "ping(n)"),
+                ("looping", "looping -> looping".to_string(), "looping(n)"),
+            ]
+        );
+
+        for warning in &warnings {
+            assert!(explain(warning.code()).is_some());
+        }
+    }
+}
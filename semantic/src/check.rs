@@ -0,0 +1,2608 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use syntax::{
+    ast,
+    diagnostics::Diagnostic,
+    limits::{Limit, Limits},
+    lints::{LiteralWarning, lint_f32_literal},
+    messages::message,
+    token::Span,
+};
+
+use crate::{
+    coerce,
+    consts::ConstValues,
+    exhaustive::Shapes,
+    resolve::{DefId, DefKind, Resolutions},
+    ty::{Primitive, Type},
+};
+
+/// The side table recording the [Type] of every expression and typed definition.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TypeTable {
+    exprs: Vec<Type>,
+    defs: HashMap<DefId, Type>,
+    /// The generic function and arguments of each call of a generic function.
+    calls: HashMap<ast::ExprId, Instance>,
+    instances: Vec<Instance>,
+    /// The types expressions are implicitly converted to where they are used.
+    coercions: HashMap<ast::ExprId, Type>,
+}
+
+impl TypeTable {
+    /// Returns the [Type] of the given expression.
+    pub fn expr(&self, id: ast::ExprId) -> &Type {
+        self.exprs.get(id.index()).unwrap_or(&Type::Error)
+    }
+
+    /// Returns the [Type] of a function, constant, parameter or local binding.
+    pub fn def(&self, id: DefId) -> Option<&Type> {
+        self.defs.get(&id)
+    }
+
+    /// Returns the generic function called by the call expression, along with the types
+    /// its generic parameters are bound to. Inside a generic function these may mention
+    /// its own type parameters.
+    pub fn call_instance(&self, call: ast::ExprId) -> Option<&Instance> {
+        self.calls.get(&call)
+    }
+
+    /// Returns the concrete instances of generic functions the module uses, in the
+    /// order they are found.
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    /// Returns the type an expression is implicitly converted to where it is used, like
+    /// `i64` for `x` in `let y: i64 = x` with `x: i32`, see [coerce::coerces].
+    pub fn coercion(&self, id: ast::ExprId) -> Option<&Type> {
+        self.coercions.get(&id)
+    }
+}
+
+/// A generic function with types for its generic parameters, like `max<i64>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Instance {
+    pub func: DefId,
+    /// The types of the generic parameters, in the order they are declared.
+    pub args: Vec<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeError {
+    pub kind: TypeErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeErrorKind {
+    /// A value of type `found` where `expected` is required, for example because of
+    /// the annotation at `origin`.
+    Mismatch {
+        expected: Type,
+        found: Type,
+        origin: Option<Span>,
+    },
+    /// An argument of type `found` passed to a parameter of the `expected` type.
+    ArgumentMismatch {
+        expected: Type,
+        found: Type,
+        callee: Callee,
+    },
+    /// A pattern that can't match values of the `expected` type.
+    PatternMismatch {
+        expected: Type,
+        pattern: String,
+    },
+    /// A binary operator applied to operands it isn't defined for.
+    InvalidOperands {
+        op: &'static str,
+        lhs: Type,
+        lhs_span: Span,
+        rhs: Type,
+        rhs_span: Span,
+    },
+    /// A unary operator applied to an operand it isn't defined for.
+    InvalidOperand {
+        op: &'static str,
+        operand: Type,
+    },
+    /// A call of a value that isn't a function.
+    NotCallable(Type),
+    InvalidCast {
+        from: Type,
+        to: Type,
+    },
+    /// A name used as a value that refers to another kind of definition.
+    NotAValue {
+        name: String,
+        kind: DefKind,
+    },
+    /// An assignment to something other than a variable.
+    InvalidAssignTarget,
+    /// An assignment to the binding `name` declared at `declared` without `var`.
+    AssignToImmutable {
+        name: String,
+        kind: DefKind,
+        declared: Span,
+    },
+    /// A `match` without arms for the values matched by the `missing` patterns.
+    NonExhaustive {
+        missing: Vec<String>,
+    },
+    /// A `for` loop over a value that isn't a range or an array.
+    NotIterable(Type),
+    /// A branch of a function body returning `expected`, declared at `origin`, that
+    /// ends without returning a value.
+    MissingReturn {
+        expected: Type,
+        origin: Option<Span>,
+    },
+    /// A call with `found` arguments of a function taking `expected` ones.
+    ArgumentCount {
+        expected: usize,
+        found: usize,
+        callee: Option<Callee>,
+    },
+    /// An access of a field the `base` type doesn't have, which has the `available` ones.
+    UnknownField {
+        base: Type,
+        field: String,
+        available: Vec<String>,
+    },
+    /// An integer literal whose value is outside of the range of its integer type.
+    LiteralOutOfRange {
+        value: i128,
+        ty: Primitive,
+    },
+    /// An error in the body of the generic function `func` that only occurs with the
+    /// generic arguments `args` it is called with at `call`.
+    Instance {
+        error: Box<TypeError>,
+        func: String,
+        args: Vec<Type>,
+        call: Span,
+    },
+    /// A [Limit] set to `value` exceeded, like by a struct containing itself.
+    LimitExceeded {
+        limit: Limit,
+        value: usize,
+    },
+}
+
+/// The function called by a call expression, if the callee names one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Callee {
+    pub name: String,
+    /// The [Span] of the function's name in its declaration.
+    pub span: Span,
+}
+
+impl Callee {
+    /// Adds a label pointing at the declaration of the callee, unless it is a built-in.
+    fn label(&self, diagnostic: Diagnostic) -> Diagnostic {
+        if self.span == Span::EOI {
+            return diagnostic;
+        }
+
+        let label = message("call.declared", &[("name", &self.name)]);
+        diagnostic.with_label(self.span, label)
+    }
+}
+
+impl TypeErrorKind {
+    /// Returns the stable code of this kind of error, see [syntax::explain::explain].
+    pub fn code(&self) -> &'static str {
+        match self {
+            TypeErrorKind::Mismatch { .. }
+            | TypeErrorKind::ArgumentMismatch { .. }
+            | TypeErrorKind::PatternMismatch { .. } => "E0015",
+            TypeErrorKind::InvalidOperands { .. } | TypeErrorKind::InvalidOperand { .. } => "E0016",
+            TypeErrorKind::NotCallable(_) => "E0017",
+            TypeErrorKind::InvalidCast { .. } => "E0018",
+            TypeErrorKind::NotAValue { .. } => "E0019",
+            TypeErrorKind::InvalidAssignTarget => "E0020",
+            TypeErrorKind::NotIterable(_) => "E0021",
+            TypeErrorKind::ArgumentCount { .. } => "E0022",
+            TypeErrorKind::MissingReturn { .. } => "E0023",
+            TypeErrorKind::UnknownField { .. } => "E0032",
+            TypeErrorKind::LiteralOutOfRange { .. } => "E0033",
+            TypeErrorKind::AssignToImmutable { .. } => "E0034",
+            TypeErrorKind::NonExhaustive { .. } => "E0035",
+            TypeErrorKind::Instance { error, .. } => error.kind.code(),
+            TypeErrorKind::LimitExceeded { .. } => "E0008",
+        }
+    }
+}
+
+impl TypeError {
+    /// Converts the error into a [Diagnostic], naming types with the given [Resolutions].
+    pub fn diagnostic(&self, resolutions: &Resolutions) -> Diagnostic {
+        let code = self.kind.code();
+        let ty = |ty: &Type| ty.display(resolutions).to_string();
+
+        let diagnostic = match &self.kind {
+            TypeErrorKind::Mismatch {
+                expected,
+                found,
+                origin,
+            } => {
+                let args = [("expected", &ty(expected)), ("found", &ty(found))];
+                let args = args.map(|(name, value)| (name, value as &dyn std::fmt::Display));
+                let diagnostic = Diagnostic::error(message(code, &args), self.span)
+                    .with_primary_label(message("E0015.label", &args));
+                let diagnostic = cast_help(diagnostic, expected, found, resolutions);
+
+                match origin {
+                    Some(origin) => diagnostic.with_label(*origin, message("E0015.origin", &args)),
+                    None => diagnostic,
+                }
+            }
+            TypeErrorKind::ArgumentMismatch {
+                expected,
+                found,
+                callee,
+            } => {
+                let args = [("expected", &ty(expected)), ("found", &ty(found))];
+                let args = args.map(|(name, value)| (name, value as &dyn std::fmt::Display));
+                let diagnostic = Diagnostic::error(message(code, &args), self.span)
+                    .with_primary_label(message("E0015.label", &args));
+
+                callee.label(cast_help(diagnostic, expected, found, resolutions))
+            }
+            TypeErrorKind::PatternMismatch { expected, pattern } => Diagnostic::error(
+                message(
+                    "E0015.pattern",
+                    &[("expected", &ty(expected)), ("pattern", pattern)],
+                ),
+                self.span,
+            ),
+            TypeErrorKind::InvalidOperands {
+                op,
+                lhs,
+                lhs_span,
+                rhs,
+                rhs_span,
+            } => Diagnostic::error(
+                message(code, &[("op", op), ("lhs", &ty(lhs)), ("rhs", &ty(rhs))]),
+                self.span,
+            )
+            .with_label(*lhs_span, format!("`{}`", ty(lhs)))
+            .with_label(*rhs_span, format!("`{}`", ty(rhs))),
+            TypeErrorKind::InvalidOperand { op, operand } => Diagnostic::error(
+                message("E0016.unary", &[("op", op), ("operand", &ty(operand))]),
+                self.span,
+            ),
+            TypeErrorKind::NotCallable(callee) => {
+                Diagnostic::error(message(code, &[("found", &ty(callee))]), self.span)
+            }
+            TypeErrorKind::InvalidCast { from, to } => Diagnostic::error(
+                message(code, &[("from", &ty(from)), ("to", &ty(to))]),
+                self.span,
+            ),
+            TypeErrorKind::NotAValue { name, kind } => Diagnostic::error(
+                message(code, &[("name", name), ("kind", &kind.description())]),
+                self.span,
+            ),
+            TypeErrorKind::InvalidAssignTarget => Diagnostic::error(message(code, &[]), self.span),
+            TypeErrorKind::AssignToImmutable {
+                name,
+                kind,
+                declared,
+            } => {
+                let diagnostic = Diagnostic::error(message(code, &[("name", name)]), self.span)
+                    .with_primary_label(message("E0034.label", &[]))
+                    .with_label(*declared, message("E0034.declared", &[("name", name)]));
+
+                match kind {
+                    DefKind::Local => diagnostic.with_help(message("E0034.help", &[])),
+                    _ => diagnostic,
+                }
+            }
+            TypeErrorKind::NonExhaustive { missing } => {
+                let missing = patterns(missing);
+                Diagnostic::error(message(code, &[("missing", &missing)]), self.span)
+                    .with_primary_label(message("E0035.label", &[("missing", &missing)]))
+                    .with_help(message("E0035.help", &[]))
+            }
+            TypeErrorKind::NotIterable(iterable) => {
+                Diagnostic::error(message(code, &[("found", &ty(iterable))]), self.span)
+            }
+            TypeErrorKind::MissingReturn { expected, origin } => {
+                let expected = ty(expected);
+                let diagnostic = Diagnostic::error(message(code, &[]), self.span)
+                    .with_primary_label(message("E0023.label", &[("expected", &expected)]));
+
+                match origin {
+                    Some(origin) => diagnostic
+                        .with_label(*origin, message("E0023.origin", &[("expected", &expected)])),
+                    None => diagnostic,
+                }
+            }
+            TypeErrorKind::ArgumentCount {
+                expected,
+                found,
+                callee,
+            } => {
+                let diagnostic = Diagnostic::error(
+                    message(code, &[("expected", expected), ("found", found)]),
+                    self.span,
+                );
+
+                match callee {
+                    Some(callee) => callee.label(diagnostic),
+                    None => diagnostic,
+                }
+            }
+            TypeErrorKind::UnknownField {
+                base,
+                field,
+                available,
+            } => {
+                let base = ty(base);
+                let diagnostic =
+                    Diagnostic::error(message(code, &[("field", field), ("ty", &base)]), self.span)
+                        .with_primary_label(message("E0032.label", &[]));
+
+                if available.is_empty() {
+                    diagnostic.with_note(message("E0032.none", &[("ty", &base)]))
+                } else {
+                    let fields: Vec<_> = available.iter().map(|name| format!("`{name}`")).collect();
+                    diagnostic.with_note(message("E0032.note", &[("fields", &fields.join(", "))]))
+                }
+            }
+            TypeErrorKind::LiteralOutOfRange { value, ty } => {
+                let (min, max) = ty.bounds().unwrap_or_default();
+                let name = ty.name();
+                let diagnostic = Diagnostic::error(message(code, &[("ty", &name)]), self.span)
+                    .with_primary_label(message("E0033.label", &[("value", value), ("ty", &name)]))
+                    .with_note(message(
+                        "E0033.note",
+                        &[("ty", &name), ("min", &min), ("max", &max)],
+                    ));
+
+                // prefers a type of the same signedness, so `300` suggests `u16` for a `u8`.
+                let fits = |primitive: &Primitive| {
+                    primitive
+                        .bounds()
+                        .is_some_and(|(min, max)| (min..=max).contains(value))
+                };
+                let larger = Primitive::all()
+                    .filter(fits)
+                    .find(|primitive| primitive.is_signed() == ty.is_signed())
+                    .or_else(|| Primitive::all().find(fits));
+
+                match larger {
+                    Some(larger) => {
+                        diagnostic.with_help(message("E0033.help", &[("ty", &larger.name())]))
+                    }
+                    None => diagnostic,
+                }
+            }
+            TypeErrorKind::Instance {
+                error,
+                func,
+                args,
+                call,
+            } => {
+                let args: Vec<_> = args.iter().map(ty).collect();
+                let instance = format!("{func}<{}>", args.join(", "));
+                error
+                    .diagnostic(resolutions)
+                    .with_label(*call, message("instance.label", &[("instance", &instance)]))
+            }
+            TypeErrorKind::LimitExceeded { limit, value } => {
+                let (message, help) = limit.exceeded(*value);
+                Diagnostic::error(message, self.span).with_help(help)
+            }
+        };
+
+        diagnostic.with_code(code)
+    }
+}
+
+/// Adds a help suggesting an explicit `as` cast to a mismatch of two numeric types which
+/// don't convert implicitly.
+fn cast_help(
+    diagnostic: Diagnostic,
+    expected: &Type,
+    found: &Type,
+    resolutions: &Resolutions,
+) -> Diagnostic {
+    if coerce::needs_cast(found, expected) {
+        let expected = expected.display(resolutions).to_string();
+        diagnostic.with_help(message("E0015.cast", &[("expected", &expected)]))
+    } else {
+        diagnostic
+    }
+}
+
+/// Lists the first few patterns, like `` `A`, `B` and 2 more ``.
+fn patterns(patterns: &[String]) -> String {
+    const SHOWN: usize = 3;
+
+    let mut list: Vec<_> = patterns
+        .iter()
+        .take(SHOWN)
+        .map(|pattern| format!("`{pattern}`"))
+        .collect();
+    let last = match patterns.len() {
+        len if len > SHOWN => format!("{} more", len - SHOWN),
+        _ => list.pop().unwrap_or_default(),
+    };
+
+    if list.is_empty() {
+        last
+    } else {
+        format!("{} and {last}", list.join(", "))
+    }
+}
+
+/// A warning about a part of the program that type checks but never makes a difference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeWarning {
+    pub kind: TypeWarningKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeWarningKind {
+    /// A match arm whose pattern only matches values the earlier arms match, like any
+    /// arm after the one at `catch_all` matching everything.
+    UnreachableArm { catch_all: Option<Span> },
+}
+
+impl TypeWarningKind {
+    /// Returns the name of the lint, used to allow or deny it.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TypeWarningKind::UnreachableArm { .. } => "unreachable_patterns",
+        }
+    }
+
+    /// Returns the stable code of this kind of warning, see [syntax::explain::explain].
+    pub fn code(&self) -> &'static str {
+        match self {
+            TypeWarningKind::UnreachableArm { .. } => "W0009",
+        }
+    }
+}
+
+impl TypeWarning {
+    /// Converts the warning into a [Diagnostic].
+    pub fn diagnostic(&self) -> Diagnostic {
+        let code = self.kind.code();
+
+        let diagnostic = match &self.kind {
+            TypeWarningKind::UnreachableArm { catch_all } => {
+                let diagnostic = Diagnostic::warning(message(code, &[]), self.span)
+                    .with_primary_label(message("W0009.label", &[]));
+
+                match catch_all {
+                    Some(span) => diagnostic.with_label(*span, message("W0009.catch-all", &[])),
+                    None => diagnostic,
+                }
+            }
+        };
+
+        diagnostic.with_code(code).with_lint(self.kind.name())
+    }
+}
+
+/// The results of [check].
+#[derive(Debug, Default)]
+pub struct Checked {
+    pub types: TypeTable,
+    pub errors: Vec<TypeError>,
+    pub warnings: Vec<TypeWarning>,
+    /// The float literals of type `f32` that lose precision, see [lint_f32_literal].
+    pub literal_warnings: Vec<LiteralWarning>,
+}
+
+/// Assigns a [Type] to every expression of the resolved [ast::Module], reporting
+/// operators applied to the wrong operands and values of the wrong type.
+///
+/// Types flow from annotations into expressions, so integer and float literals take
+/// the type expected of them and default to `i64` and `f64` otherwise.
+///
+/// Operators are allowed on the type parameters of generic functions, whose bodies are
+/// checked again for every [Instance] the module uses.
+pub fn check(module: &ast::Module, resolutions: &Resolutions, consts: &ConstValues) -> Checked {
+    check_with_limits(module, resolutions, consts, Limits::default())
+}
+
+/// Checks the module like [check], enforcing the given [Limits].
+pub fn check_with_limits(
+    module: &ast::Module,
+    resolutions: &Resolutions,
+    consts: &ConstValues,
+    limits: Limits,
+) -> Checked {
+    let mut checker = Checker {
+        exprs: &module.exprs,
+        resolutions,
+        consts,
+        limits,
+        checked: Checked {
+            types: TypeTable {
+                exprs: vec![Type::Error; module.exprs.len()],
+                ..TypeTable::default()
+            },
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            literal_warnings: Vec::new(),
+        },
+        fields: HashMap::new(),
+        depths: HashMap::new(),
+        variants: HashMap::new(),
+        funcs: HashMap::new(),
+        substitution: HashMap::new(),
+        uses: Vec::new(),
+        journal: None,
+        return_type: None,
+        loops: Vec::new(),
+    };
+
+    for (def, _) in resolutions.defs() {
+        if let Some(signature) = resolutions.signature(def) {
+            checker.checked.types.defs.insert(def, signature.clone());
+        }
+    }
+    for item in &module.items {
+        checker.collect_item(item);
+    }
+    for item in &module.items {
+        if let ast::Item::Struct(struct_decl) = item {
+            checker.check_struct_depth(struct_decl);
+        }
+    }
+
+    for item in &module.items {
+        checker.check_item(item);
+    }
+    checker.check_instances();
+
+    checker.checked
+}
+
+/// Returns whether the checked statement never finishes, like a `return` or a `let`
+/// initialized with a call of `panic`, so the statements after it never run.
+pub(crate) fn diverges(stmt: &ast::Stmt, types: &TypeTable) -> bool {
+    match stmt {
+        ast::Stmt::Return(_) | ast::Stmt::Break(_) | ast::Stmt::Continue(_) => true,
+        ast::Stmt::Let(let_stmt) => let_stmt
+            .value
+            .is_some_and(|value| *types.expr(value) == Type::Never),
+        ast::Stmt::Expr(expr_stmt) => *types.expr(expr_stmt.expr) == Type::Never,
+        ast::Stmt::Const(_) | ast::Stmt::For(_) | ast::Stmt::Error(_) => false,
+    }
+}
+
+/// The data carried by an enum variant, with the types of its fields.
+pub(crate) enum Payload {
+    Unit,
+    Tuple(Vec<Type>),
+    Struct(Vec<(String, Type)>),
+}
+
+/// A loop enclosing the checked expression.
+struct Loop {
+    /// Whether `break` may give the loop a value, which only a `loop` has.
+    has_value: bool,
+    /// The type of the values given by the `break`s seen so far.
+    ty: Option<Type>,
+    broken: bool,
+}
+
+/// The entries of the [TypeTable] overwritten while checking an [Instance], which are
+/// restored afterwards so the instance leaves the table as it was.
+#[derive(Default)]
+struct Journal {
+    exprs: Vec<(ast::ExprId, Type)>,
+    defs: Vec<(DefId, Option<Type>)>,
+    calls: Vec<(ast::ExprId, Option<Instance>)>,
+    coercions: Vec<(ast::ExprId, Option<Type>)>,
+}
+
+impl Journal {
+    fn undo(self, types: &mut TypeTable) {
+        for (id, ty) in self.exprs.into_iter().rev() {
+            types.exprs[id.index()] = ty;
+        }
+        restore(&mut types.defs, self.defs);
+        restore(&mut types.calls, self.calls);
+        restore(&mut types.coercions, self.coercions);
+    }
+}
+
+/// Puts back the values the keys had before they were overwritten, latest first.
+fn restore<K: Eq + std::hash::Hash, V>(map: &mut HashMap<K, V>, entries: Vec<(K, Option<V>)>) {
+    for (key, value) in entries.into_iter().rev() {
+        match value {
+            Some(value) => map.insert(key, value),
+            None => map.remove(&key),
+        };
+    }
+}
+
+struct Checker<'a> {
+    exprs: &'a ast::ExprArena,
+    resolutions: &'a Resolutions,
+    consts: &'a ConstValues,
+    limits: Limits,
+    checked: Checked,
+    /// The fields of each struct.
+    fields: HashMap<DefId, Vec<(String, Type)>>,
+    /// How deeply each struct nests the types of its fields, which is `None` while it
+    /// is computed or if it contains itself.
+    depths: HashMap<DefId, Option<usize>>,
+    /// The payload of each enum variant.
+    variants: HashMap<DefId, Payload>,
+    funcs: HashMap<DefId, &'a ast::FuncDecl>,
+    /// The types the generic parameters of the checked instance are bound to.
+    substitution: HashMap<DefId, Type>,
+    /// The calls of generic functions checked so far, along with their [Span].
+    uses: Vec<(Instance, Span)>,
+    /// The overwritten entries of the [TypeTable] while an [Instance] is checked.
+    journal: Option<Journal>,
+    /// The return type of the checked function, along with the [Span] declaring it.
+    return_type: Option<(Type, Option<Span>)>,
+    loops: Vec<Loop>,
+}
+
+impl<'a> Checker<'a> {
+    fn error(&mut self, kind: TypeErrorKind, span: Span) {
+        self.checked.errors.push(TypeError { kind, span });
+    }
+
+    fn mismatch(&mut self, expected: &Type, found: &Type, origin: Option<Span>, span: Span) {
+        let kind = TypeErrorKind::Mismatch {
+            expected: expected.clone(),
+            found: found.clone(),
+            origin,
+        };
+        self.error(kind, span);
+    }
+
+    /// Returns whether the expression with the type `found` can be used where `expected`
+    /// is required, recording the implicit conversion it needs, if any.
+    fn coerce(&mut self, id: ast::ExprId, found: &Type, expected: &Type) -> bool {
+        if found.fits(expected) {
+            true
+        } else if coerce::coerces(found, expected) {
+            self.set_coercion(id, expected.clone());
+            true
+        } else {
+            false
+        }
+    }
+
+    fn set_def_type(&mut self, decl: ast::NodeId, ty: Type) {
+        if let Some(def) = self.resolutions.decl(decl) {
+            let old = self.checked.types.defs.insert(def, ty);
+            if let Some(journal) = &mut self.journal {
+                journal.defs.push((def, old));
+            }
+        }
+    }
+
+    fn set_coercion(&mut self, id: ast::ExprId, ty: Type) {
+        let old = self.checked.types.coercions.insert(id, ty);
+        if let Some(journal) = &mut self.journal {
+            journal.coercions.push((id, old));
+        }
+    }
+
+    /// Converts a type as written in the source into a [Type].
+    fn lower_type(&self, ty: &ast::TypeExpr) -> Type {
+        let lower_all =
+            |types: &[ast::TypeExpr]| types.iter().map(|ty| self.lower_type(ty)).collect();
+
+        match ty {
+            ast::TypeExpr::Named(named) => {
+                let Some(def_id) = self.resolutions.node(named.id) else {
+                    return Type::Error;
+                };
+                let def = self.resolutions.def(def_id);
+
+                match def.kind {
+                    DefKind::Primitive => Primitive::from_name(&def.name)
+                        .map(Primitive::ty)
+                        .unwrap_or(Type::Error),
+                    DefKind::Struct | DefKind::Enum => Type::Adt(def_id, lower_all(&named.args)),
+                    DefKind::Generic => self
+                        .substitution
+                        .get(&def_id)
+                        .cloned()
+                        .unwrap_or(Type::Generic(def_id)),
+                    _ => Type::Error,
+                }
+            }
+            ast::TypeExpr::Ref(reference) => Type::Ref(Box::new(self.lower_type(&reference.inner))),
+            ast::TypeExpr::Ptr(pointer) => Type::Ptr(Box::new(self.lower_type(&pointer.inner))),
+            ast::TypeExpr::Optional(optional) => {
+                Type::Optional(Box::new(self.lower_type(&optional.inner)))
+            }
+            ast::TypeExpr::Array(array) => match self.consts.array_len(array.id) {
+                Some(len) => Type::Array(Box::new(self.lower_type(&array.element)), len),
+                None => Type::Error,
+            },
+            ast::TypeExpr::Tuple(tuple) => Type::Tuple(lower_all(&tuple.elements)),
+            ast::TypeExpr::Func(func) => {
+                let ret = match &func.return_type {
+                    Some(ret) => self.lower_type(ret),
+                    None => Type::UNIT,
+                };
+                Type::Func(lower_all(&func.params), Box::new(ret))
+            }
+        }
+    }
+
+    fn lower_fields(&self, fields: &[ast::Field]) -> Vec<(String, Type)> {
+        fields
+            .iter()
+            .map(|field| (field.name.text.clone(), self.lower_type(&field.ty)))
+            .collect()
+    }
+
+    /// Records the signature of an item, so it can be used before its declaration.
+    fn collect_item(&mut self, item: &'a ast::Item) {
+        match item {
+            ast::Item::Func(func) => {
+                if let Some(def) = self.resolutions.decl(func.id) {
+                    self.funcs.insert(def, func);
+                }
+
+                let params = func
+                    .params
+                    .iter()
+                    .map(|param| self.lower_type(&param.ty))
+                    .collect();
+                let ret = match &func.return_type {
+                    Some(ret) => self.lower_type(ret),
+                    None => Type::UNIT,
+                };
+                self.set_def_type(func.id, Type::Func(params, Box::new(ret)));
+            }
+            ast::Item::Const(const_decl) => {
+                let ty = self.lower_type(&const_decl.ty);
+                self.set_def_type(const_decl.id, ty);
+            }
+            ast::Item::Struct(struct_decl) => {
+                if let Some(def) = self.resolutions.decl(struct_decl.id) {
+                    let fields = self.lower_fields(&struct_decl.fields);
+                    self.fields.insert(def, fields);
+                }
+            }
+            ast::Item::Enum(enum_decl) => {
+                for variant in &enum_decl.variants {
+                    let Some(def) = self.resolutions.decl(variant.id) else {
+                        continue;
+                    };
+
+                    let payload = match &variant.payload {
+                        ast::VariantPayload::Unit => Payload::Unit,
+                        ast::VariantPayload::Tuple(types) => {
+                            Payload::Tuple(types.iter().map(|ty| self.lower_type(ty)).collect())
+                        }
+                        ast::VariantPayload::Struct(fields) => {
+                            Payload::Struct(self.lower_fields(fields))
+                        }
+                    };
+                    self.variants.insert(def, payload);
+                }
+            }
+            ast::Item::Import(_) | ast::Item::Trait(_) | ast::Item::Error(_) => {}
+        }
+    }
+
+    /// Reports a struct nesting its fields deeper than [Limits::type_recursion_depth],
+    /// like one containing itself.
+    fn check_struct_depth(&mut self, struct_decl: &ast::StructDecl) {
+        let Some(def) = self.resolutions.decl(struct_decl.id) else {
+            return;
+        };
+
+        let limit = self.limits.type_recursion_depth;
+        if self.struct_depth(def).is_none_or(|depth| depth > limit) {
+            let kind = TypeErrorKind::LimitExceeded {
+                limit: Limit::TypeRecursionDepth,
+                value: limit,
+            };
+            self.error(kind, struct_decl.name.span);
+        }
+    }
+
+    /// Returns how deeply the struct nests the types stored in it, which is `None` if
+    /// it contains itself and thus nests them forever.
+    fn struct_depth(&mut self, def: DefId) -> Option<usize> {
+        if let Some(&depth) = self.depths.get(&def) {
+            return depth;
+        }
+
+        self.depths.insert(def, None);
+        let fields = self.fields.get(&def).cloned().unwrap_or_default();
+        let mut depth = Some(1);
+        for (_, ty) in &fields {
+            depth = depth
+                .zip(self.type_depth(ty))
+                .map(|(depth, ty)| depth.max(ty + 1));
+        }
+        self.depths.insert(def, depth);
+        depth
+    }
+
+    /// Returns how deeply the type nests the types stored in it, see
+    /// [Checker::struct_depth]. References and pointers store no value.
+    fn type_depth(&mut self, ty: &Type) -> Option<usize> {
+        let nested = |checker: &mut Self, types: &[Type]| {
+            types
+                .iter()
+                .try_fold(0, |depth, ty| Some(depth.max(checker.type_depth(ty)? + 1)))
+        };
+
+        match ty {
+            Type::Tuple(types) => nested(self, types),
+            Type::Array(inner, _) | Type::Optional(inner) | Type::Range(inner) => {
+                nested(self, std::slice::from_ref(inner))
+            }
+            Type::Adt(def, args) => {
+                let args = nested(self, args)?;
+                if self.fields.contains_key(def) {
+                    Some(args.max(self.struct_depth(*def)?))
+                } else {
+                    Some(args)
+                }
+            }
+            _ => Some(0),
+        }
+    }
+
+    fn check_item(&mut self, item: &ast::Item) {
+        match item {
+            ast::Item::Func(func) => {
+                self.check_fn(&func.params, func.return_type.as_ref(), &func.body);
+            }
+            ast::Item::Const(const_decl) => {
+                let ty = self.lower_type(&const_decl.ty);
+                self.check_expr_with(const_decl.value, &ty, Some(const_decl.ty.span()));
+            }
+            ast::Item::Trait(trait_decl) => {
+                for method in &trait_decl.methods {
+                    if let Some(body) = &method.body {
+                        self.check_fn(&method.params, method.return_type.as_ref(), body);
+                    }
+                }
+            }
+            ast::Item::Import(_)
+            | ast::Item::Struct(_)
+            | ast::Item::Enum(_)
+            | ast::Item::Error(_) => {}
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        params: &[ast::Param],
+        return_type: Option<&ast::TypeExpr>,
+        body: &ast::Block,
+    ) {
+        for param in params {
+            let ty = self.lower_type(&param.ty);
+            self.set_def_type(param.id, ty);
+        }
+
+        let (ret, origin) = match return_type {
+            Some(ty) => (self.lower_type(ty), Some(ty.span())),
+            None => (Type::UNIT, None),
+        };
+        self.return_type = Some((ret.clone(), origin));
+
+        let found = self.check_block(body, Some(&ret));
+        let fits = match body.tail {
+            Some(tail) => self.coerce(tail, &found, &ret),
+            None => found.fits(&ret),
+        };
+        if fits {
+            // the body returns a value on every path.
+        } else if let Some(span) = self.missing_return(body) {
+            let kind = TypeErrorKind::MissingReturn {
+                expected: ret.clone(),
+                origin,
+            };
+            self.error(kind, span);
+        } else {
+            let span = body.tail.map_or(body.span, |tail| self.exprs[tail].span());
+            self.mismatch(&ret, &found, origin, span);
+        }
+
+        self.return_type = None;
+    }
+
+    /// Checks the body of every generic function again for each [Instance] used, starting
+    /// with the calls outside of generic functions, reporting the errors that only occur
+    /// with the concrete types.
+    ///
+    /// Instances nested deeper than [Limits::monomorphization_depth] aren't checked, nor
+    /// are those of functions instantiating themselves with ever larger types.
+    fn check_instances(&mut self) {
+        let growing = self.growing_instances();
+        let limit = self.limits.monomorphization_depth;
+        let mut queue: VecDeque<_> = self.uses.drain(..).map(|(i, call)| (i, call, 1)).collect();
+        let mut seen = HashSet::new();
+        let mut reported = HashSet::new();
+
+        while let Some((instance, call, depth)) = queue.pop_front() {
+            if instance.args.iter().any(Type::has_generics) || seen.contains(&instance) {
+                continue;
+            }
+            let Some(func) = self.funcs.get(&instance.func).copied() else {
+                continue;
+            };
+
+            let exceeded = match growing.get(&instance.func) {
+                Some(&growing) => Some(growing),
+                None => (depth > limit).then_some(call),
+            };
+            if let Some(span) = exceeded {
+                if reported.insert(span) {
+                    let kind = TypeErrorKind::LimitExceeded {
+                        limit: Limit::MonomorphizationDepth,
+                        value: limit,
+                    };
+                    self.error(kind, span);
+                }
+                continue;
+            }
+            seen.insert(instance.clone());
+
+            let generics = self.resolutions.generics(instance.func);
+            self.substitution = generics
+                .iter()
+                .copied()
+                .zip(instance.args.clone())
+                .collect();
+
+            // the instance mustn't change the results, so what it overwrites is restored.
+            let errors = std::mem::take(&mut self.checked.errors);
+            let warnings = self.checked.warnings.len();
+            let literal_warnings = self.checked.literal_warnings.len();
+            self.journal = Some(Journal::default());
+            self.check_fn(&func.params, func.return_type.as_ref(), &func.body);
+            if let Some(journal) = self.journal.take() {
+                journal.undo(&mut self.checked.types);
+            }
+            let found = std::mem::replace(&mut self.checked.errors, errors);
+            self.checked.warnings.truncate(warnings);
+            self.checked.literal_warnings.truncate(literal_warnings);
+            self.substitution.clear();
+
+            for error in found {
+                // errors that don't depend on the types are already reported.
+                if self
+                    .checked
+                    .errors
+                    .iter()
+                    .any(|other| other.span == error.span)
+                {
+                    continue;
+                }
+
+                let span = error.span;
+                let kind = TypeErrorKind::Instance {
+                    error: Box::new(error),
+                    func: func.name.text.clone(),
+                    args: instance.args.clone(),
+                    call,
+                };
+                self.error(kind, span);
+            }
+
+            let uses = self.uses.drain(..).map(|(i, call)| (i, call, depth + 1));
+            queue.extend(uses);
+            self.checked.types.instances.push(instance);
+        }
+    }
+
+    /// Returns the generic functions whose instances would instantiate each other with
+    /// ever larger types forever, like `f` calling `f((x, 1))` with `x: T`, along with
+    /// the first such call in each of them.
+    fn growing_instances(&self) -> HashMap<DefId, Span> {
+        // the type parameters passed to other type parameters by the calls in generic
+        // functions, along with whether they are passed inside a larger type.
+        let mut edges: HashMap<DefId, Vec<(DefId, bool, Span)>> = HashMap::new();
+        for (&call, instance) in &self.checked.types.calls {
+            let params = self.resolutions.generics(instance.func);
+            for (&param, arg) in params.iter().zip(&instance.args) {
+                let mut generics = Vec::new();
+                arg.collect_generics(&mut generics);
+                for generic in generics {
+                    let grows = *arg != Type::Generic(generic);
+                    let span = self.exprs[call].span();
+                    edges.entry(generic).or_default().push((param, grows, span));
+                }
+            }
+        }
+
+        let reaches = |from: DefId, to: DefId| {
+            let mut stack = vec![from];
+            let mut visited = HashSet::new();
+            while let Some(param) = stack.pop() {
+                if param == to {
+                    return true;
+                }
+                if visited.insert(param) {
+                    let next = edges.get(&param).into_iter().flatten();
+                    stack.extend(next.map(|&(next, ..)| next));
+                }
+            }
+            false
+        };
+
+        let owners: HashMap<_, _> = self
+            .funcs
+            .keys()
+            .flat_map(|&func| {
+                let generics = self.resolutions.generics(func);
+                generics.iter().map(move |&generic| (generic, func))
+            })
+            .collect();
+
+        // a larger type passed along a cycle grows each time around it.
+        let mut growing: HashMap<DefId, Span> = HashMap::new();
+        for (&from, edges) in &edges {
+            for &(to, grows, span) in edges {
+                let Some(&func) = owners.get(&from) else {
+                    continue;
+                };
+                if grows && reaches(to, from) {
+                    let first = growing.entry(func).or_insert(span);
+                    if span.start < first.start {
+                        *first = span;
+                    }
+                }
+            }
+        }
+        growing
+    }
+
+    /// Returns the [Span] of a branch of the checked block that can end without giving
+    /// it a value, like the `else` block of `if c { return 1; } else {}`.
+    fn missing_return(&self, block: &ast::Block) -> Option<Span> {
+        let diverges = block
+            .stmts
+            .iter()
+            .any(|stmt| diverges(stmt, &self.checked.types));
+
+        match block.tail {
+            _ if diverges => None,
+            Some(tail) => self.missing_return_in(tail),
+            None => Some(block.span),
+        }
+    }
+
+    /// Returns the [Span] of a branch of the given expression that can end without
+    /// giving it a value, see [Checker::missing_return].
+    fn missing_return_in(&self, id: ast::ExprId) -> Option<Span> {
+        if *self.checked.types.expr(id) == Type::Never {
+            return None;
+        }
+
+        match &self.exprs[id] {
+            ast::Expr::Block(block) => self.missing_return(block),
+            ast::Expr::If(if_expr) => match if_expr.else_branch {
+                Some(else_branch) => self
+                    .missing_return(&if_expr.then_block)
+                    .or_else(|| self.missing_return_in(else_branch)),
+                // the missing `else` branch gives no value.
+                None => Some(if_expr.span),
+            },
+            ast::Expr::Match(match_expr) => match_expr
+                .arms
+                .iter()
+                .find_map(|arm| self.missing_return_in(arm.value)),
+            _ => None,
+        }
+    }
+
+    /// Checks the statements of the block and returns its type, which is [Type::Never]
+    /// if a statement leaves it.
+    fn check_block(&mut self, block: &ast::Block, expected: Option<&Type>) -> Type {
+        let mut diverges = false;
+        for stmt in &block.stmts {
+            diverges |= self.check_stmt(stmt);
+        }
+
+        match block.tail {
+            Some(tail) if diverges => {
+                self.check_expr(tail, expected);
+                Type::Never
+            }
+            Some(tail) => self.check_expr(tail, expected),
+            None if diverges => Type::Never,
+            None => Type::UNIT,
+        }
+    }
+
+    /// Checks the statement and returns whether it always leaves the enclosing block.
+    fn check_stmt(&mut self, stmt: &ast::Stmt) -> bool {
+        match stmt {
+            ast::Stmt::Let(let_stmt) => {
+                let declared = let_stmt
+                    .ty
+                    .as_ref()
+                    .map(|ty| (self.lower_type(ty), ty.span()));
+
+                let ty = match (let_stmt.value, &declared) {
+                    (Some(value), Some((declared, origin))) => {
+                        self.check_expr_with(value, declared, Some(*origin));
+                        declared.clone()
+                    }
+                    (Some(value), None) => self.check_expr(value, None),
+                    (None, Some((declared, _))) => declared.clone(),
+                    (None, None) => Type::Error,
+                };
+
+                self.bind_pattern(&let_stmt.pattern, &ty);
+                ty == Type::Never
+            }
+            ast::Stmt::Const(const_decl) => {
+                let ty = self.lower_type(&const_decl.ty);
+                self.check_expr_with(const_decl.value, &ty, Some(const_decl.ty.span()));
+                self.set_def_type(const_decl.id, ty);
+                false
+            }
+            ast::Stmt::Expr(expr_stmt) => self.check_expr(expr_stmt.expr, None) == Type::Never,
+            ast::Stmt::Return(return_stmt) => {
+                let (expected, origin) = self.return_type.clone().unwrap_or((Type::Error, None));
+
+                match return_stmt.value {
+                    Some(value) => {
+                        self.check_expr_with(value, &expected, origin);
+                    }
+                    None if !Type::UNIT.fits(&expected) => {
+                        self.mismatch(&expected, &Type::UNIT, origin, return_stmt.span);
+                    }
+                    None => {}
+                }
+                true
+            }
+            ast::Stmt::For(for_stmt) => {
+                let iterable = self.check_expr(for_stmt.iterable, None);
+                let element = match iterable {
+                    Type::Range(element) | Type::Array(element, _) => *element,
+                    Type::Never | Type::Error => Type::Error,
+                    iterable => {
+                        let span = self.exprs[for_stmt.iterable].span();
+                        self.error(TypeErrorKind::NotIterable(iterable), span);
+                        Type::Error
+                    }
+                };
+                self.set_def_type(for_stmt.id, element);
+
+                self.with_loop(false, |checker| {
+                    checker.check_block(&for_stmt.body, Some(&Type::UNIT));
+                });
+                false
+            }
+            ast::Stmt::Break(break_stmt) => {
+                self.check_break(break_stmt);
+                true
+            }
+            ast::Stmt::Continue(_) => true,
+            ast::Stmt::Error(_) => false,
+        }
+    }
+
+    fn with_loop(&mut self, has_value: bool, f: impl FnOnce(&mut Self)) -> Loop {
+        self.loops.push(Loop {
+            has_value,
+            ty: None,
+            broken: false,
+        });
+        f(self);
+        self.loops.pop().unwrap()
+    }
+
+    fn check_break(&mut self, break_stmt: &ast::BreakStmt) {
+        let Some(value) = break_stmt.value else {
+            if let Some(current) = self.loops.last_mut() {
+                current.broken = true;
+                if current.has_value {
+                    let ty = current.ty.get_or_insert(Type::UNIT).clone();
+                    if !Type::UNIT.fits(&ty) {
+                        self.mismatch(&ty, &Type::UNIT, None, break_stmt.span);
+                    }
+                }
+            }
+            return;
+        };
+
+        let expected = match self.loops.last() {
+            Some(current) if current.has_value => current.ty.clone(),
+            // only a `loop` has a value for `break` to give.
+            Some(_) => Some(Type::UNIT),
+            None => None,
+        };
+
+        let found = match &expected {
+            Some(expected) => self.check_expr_with(value, expected, None),
+            None => self.check_expr(value, None),
+        };
+
+        if let Some(current) = self.loops.last_mut() {
+            current.broken = true;
+            if current.has_value && current.ty.as_ref().is_none_or(Type::is_flexible) {
+                current.ty = Some(found);
+            }
+        }
+    }
+
+    /// Checks the expression, expecting a value of the given type, and returns its type.
+    fn check_expr(&mut self, id: ast::ExprId, expected: Option<&Type>) -> Type {
+        let ty = self.infer_expr(id, expected);
+        let old = std::mem::replace(&mut self.checked.types.exprs[id.index()], ty.clone());
+        if let Some(journal) = &mut self.journal {
+            journal.exprs.push((id, old));
+        }
+        ty
+    }
+
+    /// Checks the expression like [Checker::check_expr], reporting a mismatch if its
+    /// type doesn't fit the `expected` one required because of `origin`.
+    fn check_expr_with(&mut self, id: ast::ExprId, expected: &Type, origin: Option<Span>) -> Type {
+        let found = self.check_expr(id, Some(expected));
+
+        if !self.coerce(id, &found, expected) {
+            let span = self.exprs[id].span();
+            self.mismatch(expected, &found, origin, span);
+        }
+
+        found
+    }
+
+    fn infer_expr(&mut self, id: ast::ExprId, expected: Option<&Type>) -> Type {
+        let exprs = self.exprs;
+
+        match &exprs[id] {
+            ast::Expr::Identifier(_) | ast::Expr::Path(_) => match self.resolutions.expr(id) {
+                Some(def) => self.value_type(def, exprs[id].span()),
+                None => Type::Error,
+            },
+            ast::Expr::Integer(integer) => {
+                let ty = match expected {
+                    Some(Type::Primitive(primitive)) if primitive.is_integer() => *primitive,
+                    _ => Primitive::DEFAULT_INTEGER,
+                };
+                self.check_range(integer, ty);
+                Type::Primitive(ty)
+            }
+            ast::Expr::Float(float) => match expected {
+                Some(ty) if ty.is_float() => {
+                    if *ty == Type::Primitive(Primitive::F32) {
+                        self.lint_f32(float);
+                    }
+                    ty.clone()
+                }
+                _ => Type::Primitive(Primitive::DEFAULT_FLOAT),
+            },
+            ast::Expr::String(_) => Type::Primitive(Primitive::Str),
+            ast::Expr::None(_) => match expected {
+                Some(Type::Optional(inner)) => Type::Optional(inner.clone()),
+                _ => Type::Optional(Box::new(Type::Error)),
+            },
+            ast::Expr::Paren(paren) => self.check_expr(paren.expr, expected),
+            ast::Expr::Tuple(tuple) => {
+                let expected = match expected {
+                    Some(Type::Tuple(types)) if types.len() == tuple.elements.len() => {
+                        Some(types.as_slice())
+                    }
+                    _ => None,
+                };
+
+                let types = tuple
+                    .elements
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &element)| {
+                        self.check_expr(element, expected.map(|types| &types[index]))
+                    })
+                    .collect();
+                Type::Tuple(types)
+            }
+            ast::Expr::Unary(unary) => {
+                let operand = self.check_expr(unary.operand, expected);
+                let valid = match unary.op {
+                    ast::UnaryOp::Neg => {
+                        operand.is_float() || operand.primitive().is_some_and(Primitive::is_signed)
+                    }
+                    ast::UnaryOp::Not => operand == Type::BOOL || operand.is_integer(),
+                } || operand.has_generics();
+
+                if !valid && !operand.is_flexible() {
+                    let op = unary.op.symbol();
+                    self.error(TypeErrorKind::InvalidOperand { op, operand }, unary.span);
+                    return Type::Error;
+                }
+                operand
+            }
+            ast::Expr::Binary(binary) => {
+                self.check_binary(binary.op, binary.lhs, binary.rhs, binary.span, expected)
+            }
+            ast::Expr::Cast(cast) => {
+                let from = self.check_expr(cast.expr, None);
+                let to = self.lower_type(&cast.ty);
+
+                if !coerce::can_cast(&from, &to) {
+                    self.error(
+                        TypeErrorKind::InvalidCast {
+                            from,
+                            to: to.clone(),
+                        },
+                        cast.span,
+                    );
+                }
+                to
+            }
+            ast::Expr::Assign(assign) => {
+                if !self.is_place(assign.target) {
+                    self.error(
+                        TypeErrorKind::InvalidAssignTarget,
+                        exprs[assign.target].span(),
+                    );
+                } else if let Some(def) = self.immutable_place(assign.target) {
+                    let def = self.resolutions.def(def);
+                    let kind = TypeErrorKind::AssignToImmutable {
+                        name: def.name.clone(),
+                        kind: def.kind,
+                        declared: def.span,
+                    };
+                    self.error(kind, assign.span);
+                }
+
+                let target = self.check_expr(assign.target, None);
+                let origin = exprs[assign.target].span();
+                self.check_expr_with(assign.value, &target, Some(origin));
+                Type::UNIT
+            }
+            ast::Expr::Range(range) => {
+                let element = match expected {
+                    Some(Type::Range(element)) => Some(&**element),
+                    _ => None,
+                };
+                let (start, end) = self.check_operands(range.start, range.end, element);
+
+                if !(start.is_flexible() || start.is_integer() && end.fits(&start)) {
+                    self.invalid_operands(
+                        "..",
+                        (range.start, start.clone()),
+                        (range.end, end),
+                        range.span,
+                    );
+                }
+                Type::Range(Box::new(start))
+            }
+            ast::Expr::Call(call) => self.check_call(id, call),
+            ast::Expr::Field(field) => self.check_field(field),
+            ast::Expr::Section(section) => {
+                let Some(operand) = section.lhs.or(section.rhs) else {
+                    return Type::Error;
+                };
+                let ty = self.check_expr(operand, None);
+
+                let result = if is_comparison(section.op) {
+                    Type::BOOL
+                } else {
+                    if !ty.is_numeric() && !ty.is_flexible() && !ty.has_generics() {
+                        let op = section.op.symbol();
+                        self.error(
+                            TypeErrorKind::InvalidOperand {
+                                op,
+                                operand: ty.clone(),
+                            },
+                            section.span,
+                        );
+                    }
+                    ty.clone()
+                };
+                Type::Func(vec![ty], Box::new(result))
+            }
+            ast::Expr::Block(block) => self.check_block(block, expected),
+            ast::Expr::If(if_expr) => {
+                self.check_expr_with(if_expr.cond, &Type::BOOL, None);
+                let then = self.check_block(&if_expr.then_block, expected);
+
+                let Some(else_branch) = if_expr.else_branch else {
+                    if !then.fits(&Type::UNIT) {
+                        let span = if_expr
+                            .then_block
+                            .tail
+                            .map_or(if_expr.then_block.span, |tail| exprs[tail].span());
+                        self.mismatch(&Type::UNIT, &then, None, span);
+                    }
+                    return Type::UNIT;
+                };
+
+                let els = self.check_expr(else_branch, expected.or(Some(&then)));
+                let origin = if_expr.then_block.tail.map(|tail| exprs[tail].span());
+                self.join(then, els, origin, exprs[else_branch].span())
+            }
+            ast::Expr::While(while_expr) => {
+                self.check_expr_with(while_expr.cond, &Type::BOOL, None);
+                self.with_loop(false, |checker| {
+                    checker.check_block(&while_expr.body, Some(&Type::UNIT));
+                });
+                Type::UNIT
+            }
+            ast::Expr::Loop(loop_expr) => {
+                let current = self.with_loop(true, |checker| {
+                    checker.check_block(&loop_expr.body, Some(&Type::UNIT));
+                });
+
+                match current {
+                    Loop { broken: false, .. } => Type::Never,
+                    Loop { ty, .. } => ty.unwrap_or(Type::UNIT),
+                }
+            }
+            ast::Expr::Match(match_expr) => {
+                let scrutinee = self.check_expr(match_expr.scrutinee, None);
+
+                let errors = self.checked.errors.len();
+                for arm in &match_expr.arms {
+                    self.bind_pattern(&arm.pattern, &scrutinee);
+                }
+                // patterns that don't fit the scrutinee would make the coverage misleading.
+                if errors == self.checked.errors.len() && !scrutinee.is_flexible() {
+                    self.check_coverage(match_expr, &scrutinee);
+                }
+
+                let mut result: Option<(Type, Span)> = None;
+                for arm in &match_expr.arms {
+                    let span = exprs[arm.value].span();
+                    let expected = expected.or(result.as_ref().map(|(ty, _)| ty));
+                    let ty = self.check_expr(arm.value, expected);
+
+                    result = Some(match result {
+                        Some((previous, origin)) => {
+                            (self.join(previous, ty, Some(origin), span), origin)
+                        }
+                        None => (ty, span),
+                    });
+                }
+
+                result.map_or(Type::Never, |(ty, _)| ty)
+            }
+            ast::Expr::Assert(assert) => {
+                self.check_expr_with(assert.cond, &Type::BOOL, None);
+                if let Some(message) = assert.message {
+                    self.check_expr_with(message, &Type::Primitive(Primitive::Str), None);
+                }
+                Type::UNIT
+            }
+            ast::Expr::Panic(panic) => {
+                if let Some(message) = panic.message {
+                    self.check_expr_with(message, &Type::Primitive(Primitive::Str), None);
+                }
+                Type::Never
+            }
+            ast::Expr::Error(_) => Type::Error,
+        }
+    }
+
+    /// Returns the type of the value a name refers to.
+    fn value_type(&mut self, def_id: DefId, span: Span) -> Type {
+        let def = self.resolutions.def(def_id);
+
+        match def.kind {
+            DefKind::Func | DefKind::Const | DefKind::Param | DefKind::Local => self
+                .checked
+                .types
+                .def(def_id)
+                .cloned()
+                .unwrap_or(Type::Error),
+            DefKind::Builtin => Type::BOOL,
+            DefKind::Variant => {
+                let adt = Type::Adt(def.parent.unwrap_or(def_id), Vec::new());
+                match self.variants.get(&def_id) {
+                    Some(Payload::Unit) => adt,
+                    Some(Payload::Tuple(types)) => Type::Func(types.clone(), Box::new(adt)),
+                    // the payloads of imported variants aren't known.
+                    None if self.resolutions.file(def_id).is_some() => Type::Error,
+                    Some(Payload::Struct(_)) | None => self.not_a_value(def_id, span),
+                }
+            }
+            DefKind::Primitive
+            | DefKind::Module
+            | DefKind::Struct
+            | DefKind::Enum
+            | DefKind::Trait
+            | DefKind::Generic => self.not_a_value(def_id, span),
+        }
+    }
+
+    /// Warns about a float literal of type `f32` that loses precision, once even if the
+    /// literal is checked again.
+    fn lint_f32(&mut self, float: &ast::FloatLiteral) {
+        let Some(warning) = lint_f32_literal(float) else {
+            return;
+        };
+        let warnings = &mut self.checked.literal_warnings;
+        if !warnings.iter().any(|other| other.span == warning.span) {
+            warnings.push(warning);
+        }
+    }
+
+    /// Reports an integer literal whose value doesn't fit in its type `ty`, rather than
+    /// letting it be truncated.
+    fn check_range(&mut self, integer: &ast::IntegerLiteral, ty: Primitive) {
+        let Some((min, max)) = ty.bounds() else {
+            return;
+        };
+
+        if !(min..=max).contains(&integer.value) {
+            let kind = TypeErrorKind::LiteralOutOfRange {
+                value: integer.value,
+                ty,
+            };
+            self.error(kind, integer.span);
+        }
+    }
+
+    /// Returns the type of the accessed struct field, looking through references to
+    /// the struct and applying its generic arguments.
+    fn check_field(&mut self, field: &ast::FieldExpr) -> Type {
+        let base = self.check_expr(field.base, None);
+        let mut ty = &base;
+        while let Type::Ref(inner) = ty {
+            ty = inner;
+        }
+
+        let fields: Vec<_> = match ty {
+            Type::Never | Type::Error => return Type::Error,
+            // the fields of imported structs aren't known.
+            Type::Adt(def, _) if self.resolutions.file(*def).is_some() => return Type::Error,
+            Type::Adt(def, args) => {
+                let generics: HashMap<_, _> = self
+                    .resolutions
+                    .generics(*def)
+                    .iter()
+                    .copied()
+                    .zip(args.iter().cloned())
+                    .collect();
+                let fields = self.fields.get(def).into_iter().flatten();
+                fields
+                    .map(|(name, ty)| (name.clone(), ty.substitute(&generics)))
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+
+        if let Some((_, ty)) = fields.iter().find(|(name, _)| *name == field.field.text) {
+            return ty.clone();
+        }
+
+        let kind = TypeErrorKind::UnknownField {
+            base: ty.clone(),
+            field: field.field.text.clone(),
+            available: fields.into_iter().map(|(name, _)| name).collect(),
+        };
+        self.error(kind, field.field.span);
+        Type::Error
+    }
+
+    fn not_a_value(&mut self, def_id: DefId, span: Span) -> Type {
+        let def = self.resolutions.def(def_id);
+        let kind = TypeErrorKind::NotAValue {
+            name: def.name.clone(),
+            kind: def.kind,
+        };
+        self.error(kind, span);
+        Type::Error
+    }
+
+    /// Returns whether the expression denotes a variable that can be assigned to.
+    fn is_place(&self, id: ast::ExprId) -> bool {
+        match &self.exprs[id] {
+            ast::Expr::Identifier(_) => self.resolutions.expr(id).is_none_or(|def| {
+                matches!(
+                    self.resolutions.def(def).kind,
+                    DefKind::Local | DefKind::Param
+                )
+            }),
+            ast::Expr::Paren(paren) => self.is_place(paren.expr),
+            ast::Expr::Error(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the binding a place expression names if it can't be assigned to, which
+    /// is the case for parameters and for bindings declared without `var`.
+    fn immutable_place(&self, id: ast::ExprId) -> Option<DefId> {
+        match &self.exprs[id] {
+            ast::Expr::Identifier(_) => self
+                .resolutions
+                .expr(id)
+                .filter(|&def| !self.resolutions.is_assignable(def)),
+            ast::Expr::Paren(paren) => self.immutable_place(paren.expr),
+            _ => None,
+        }
+    }
+
+    /// Returns the type of two branches, reporting a mismatch if they disagree.
+    fn join(&mut self, first: Type, second: Type, origin: Option<Span>, span: Span) -> Type {
+        if first.is_flexible() {
+            second
+        } else if second.fits(&first) {
+            first
+        } else if first.fits(&second) {
+            second
+        } else {
+            self.mismatch(&first, &second, origin, span);
+            first
+        }
+    }
+
+    /// Checks the operands of a binary operator, checking a literal operand after the
+    /// other one so it takes its type, like `1` in `1 + x` with `x: u8`.
+    fn check_operands(
+        &mut self,
+        lhs: ast::ExprId,
+        rhs: ast::ExprId,
+        expected: Option<&Type>,
+    ) -> (Type, Type) {
+        let is_literal = |id| matches!(self.exprs[id], ast::Expr::Integer(_) | ast::Expr::Float(_));
+
+        if is_literal(lhs) && !is_literal(rhs) {
+            let rhs_ty = self.check_expr(rhs, expected);
+            let lhs_ty = self.check_expr(lhs, Some(&rhs_ty));
+            (lhs_ty, rhs_ty)
+        } else {
+            let lhs_ty = self.check_expr(lhs, expected);
+            let rhs_ty = self.check_expr(rhs, Some(&lhs_ty));
+            (lhs_ty, rhs_ty)
+        }
+    }
+
+    fn check_binary(
+        &mut self,
+        op: ast::BinaryOp,
+        lhs: ast::ExprId,
+        rhs: ast::ExprId,
+        span: Span,
+        expected: Option<&Type>,
+    ) -> Type {
+        let comparison = is_comparison(op);
+        let (lhs_ty, rhs_ty) =
+            self.check_operands(lhs, rhs, if comparison { None } else { expected });
+        let (lhs_ty, rhs_ty) = self.promote((lhs, lhs_ty), (rhs, rhs_ty));
+
+        let same = rhs_ty.fits(&lhs_ty) && lhs_ty.fits(&rhs_ty);
+        let flexible = lhs_ty.is_flexible() || rhs_ty.is_flexible();
+        let valid = flexible
+            || same
+                && (lhs_ty.has_generics()
+                    || match op {
+                        ast::BinaryOp::Equal | ast::BinaryOp::Unequal => true,
+                        ast::BinaryOp::LessThan
+                        | ast::BinaryOp::LessEqual
+                        | ast::BinaryOp::GreaterThan
+                        | ast::BinaryOp::GreaterEqual => {
+                            lhs_ty.is_numeric() || lhs_ty == Type::Primitive(Primitive::Str)
+                        }
+                        _ => lhs_ty.is_numeric(),
+                    });
+
+        if !valid {
+            self.invalid_operands(
+                op.symbol(),
+                (lhs, lhs_ty.clone()),
+                (rhs, rhs_ty.clone()),
+                span,
+            );
+        }
+
+        match () {
+            _ if comparison => Type::BOOL,
+            _ if !valid => Type::Error,
+            _ if lhs_ty.is_flexible() => rhs_ty,
+            _ => lhs_ty,
+        }
+    }
+
+    /// Converts the narrower of two numeric operands to the type of the other one, like
+    /// `x` in `x + y` with `x: i32` and `y: i64`, returning the types of both after it.
+    fn promote(
+        &mut self,
+        (lhs, lhs_ty): (ast::ExprId, Type),
+        (rhs, rhs_ty): (ast::ExprId, Type),
+    ) -> (Type, Type) {
+        if let (Type::Primitive(left), Type::Primitive(right)) = (&lhs_ty, &rhs_ty)
+            && left != right
+        {
+            if coerce::widens(*left, *right) {
+                self.set_coercion(lhs, rhs_ty.clone());
+                return (rhs_ty.clone(), rhs_ty);
+            } else if coerce::widens(*right, *left) {
+                self.set_coercion(rhs, lhs_ty.clone());
+                return (lhs_ty.clone(), lhs_ty);
+            }
+        }
+
+        (lhs_ty, rhs_ty)
+    }
+
+    fn invalid_operands(
+        &mut self,
+        op: &'static str,
+        (lhs, lhs_ty): (ast::ExprId, Type),
+        (rhs, rhs_ty): (ast::ExprId, Type),
+        span: Span,
+    ) {
+        let kind = TypeErrorKind::InvalidOperands {
+            op,
+            lhs: lhs_ty,
+            lhs_span: self.exprs[lhs].span(),
+            rhs: rhs_ty,
+            rhs_span: self.exprs[rhs].span(),
+        };
+        self.error(kind, span);
+    }
+
+    /// Checks the arguments of a call against the parameters of the callee, inferring
+    /// the generic parameters of the callee from the arguments.
+    fn check_call(&mut self, id: ast::ExprId, call: &ast::CallExpr) -> Type {
+        let callee = self.check_expr(call.callee, None);
+
+        let (params, ret) = match callee {
+            Type::Func(params, ret) => (params, *ret),
+            callee => {
+                if !callee.is_flexible() {
+                    let span = self.exprs[call.callee].span();
+                    self.error(TypeErrorKind::NotCallable(callee), span);
+                }
+                for &arg in &call.args {
+                    self.check_expr(arg, None);
+                }
+                return Type::Error;
+            }
+        };
+
+        let named = self.resolutions.expr(call.callee).map(|def_id| {
+            let def = self.resolutions.def(def_id);
+            // the declarations of imported functions are in other files.
+            let span = if self.resolutions.file(def_id).is_some() {
+                Span::EOI
+            } else {
+                def.span
+            };
+            Callee {
+                name: def.name.clone(),
+                span,
+            }
+        });
+
+        if call.args.len() != params.len() {
+            let kind = TypeErrorKind::ArgumentCount {
+                expected: params.len(),
+                found: call.args.len(),
+                callee: named.clone(),
+            };
+            self.error(kind, call.span);
+        }
+
+        let mut generics = HashMap::new();
+        for (index, &arg) in call.args.iter().enumerate() {
+            let Some(param) = params.get(index) else {
+                self.check_expr(arg, None);
+                continue;
+            };
+
+            // generic parameters take the type of the first argument passed to them.
+            let param = param.substitute(&generics);
+            let found = self.check_expr(arg, (!param.has_generics()).then_some(&param));
+            param.infer_generics(&found, &mut generics);
+
+            if !self.coerce(arg, &found, &param.substitute(&generics)) {
+                let span = self.exprs[arg].span();
+                let kind = match &named {
+                    Some(callee) => TypeErrorKind::ArgumentMismatch {
+                        expected: param.substitute(&generics),
+                        found,
+                        callee: callee.clone(),
+                    },
+                    None => TypeErrorKind::Mismatch {
+                        expected: param.substitute(&generics),
+                        found,
+                        origin: None,
+                    },
+                };
+                self.error(kind, span);
+            }
+        }
+
+        let callee_generics = self
+            .resolutions
+            .expr(call.callee)
+            .map(|def| (def, self.resolutions.generics(def)))
+            .filter(|(_, callee_generics)| !callee_generics.is_empty());
+        let Some((func, callee_generics)) = callee_generics else {
+            let ret = ret.substitute(&generics);
+            return if ret.has_generics() { Type::Error } else { ret };
+        };
+
+        let args: Vec<_> = callee_generics
+            .iter()
+            .map(|generic| generics.get(generic).cloned().unwrap_or(Type::Error))
+            .collect();
+        // the parameters of the calling function may be bound as well, when passed on.
+        let unbound = callee_generics
+            .iter()
+            .any(|generic| !generics.contains_key(generic));
+        let instance = Instance { func, args };
+        let old = self.checked.types.calls.insert(id, instance.clone());
+        if let Some(journal) = &mut self.journal {
+            journal.calls.push((id, old));
+        }
+        self.uses.push((instance, call.span));
+
+        // the type parameters of the calling function are left as they are.
+        if unbound {
+            Type::Error
+        } else {
+            ret.substitute(&generics)
+        }
+    }
+
+    /// Checks that the pattern can match values of the given type and records the types
+    /// of the bindings it introduces.
+    fn bind_pattern(&mut self, pattern: &ast::Pattern, ty: &Type) {
+        let matches = match pattern {
+            ast::Pattern::Wildcard(_) => true,
+            // `true` and `false` resolve to their values instead of binding a name.
+            ast::Pattern::Binding(binding) if self.resolutions.node(binding.id).is_some() => {
+                *ty == Type::BOOL
+            }
+            ast::Pattern::Binding(binding) => {
+                self.set_def_type(binding.id, ty.clone());
+                true
+            }
+            ast::Pattern::None(_) => matches!(ty, Type::Optional(_)),
+            ast::Pattern::Integer(_) => ty.is_integer(),
+            ast::Pattern::Float(_) => ty.is_float(),
+            ast::Pattern::String(_) => *ty == Type::Primitive(Primitive::Str),
+            ast::Pattern::Tuple(tuple) => {
+                let types = match ty {
+                    Type::Tuple(types) if types.len() == tuple.elements.len() => Some(types),
+                    _ => None,
+                };
+                for (index, element) in tuple.elements.iter().enumerate() {
+                    let ty = types.map_or(&Type::Error, |types| &types[index]);
+                    self.bind_pattern(element, ty);
+                }
+                types.is_some()
+            }
+            ast::Pattern::Path(path) => self.matches_def(path.id, ty),
+            ast::Pattern::TupleStruct(tuple_struct) => {
+                let types = match self
+                    .resolutions
+                    .node(tuple_struct.id)
+                    .and_then(|def| self.variants.get(&def))
+                {
+                    Some(Payload::Tuple(types)) => types.clone(),
+                    _ => Vec::new(),
+                };
+                for (index, element) in tuple_struct.elements.iter().enumerate() {
+                    let ty = types.get(index).cloned().unwrap_or(Type::Error);
+                    self.bind_pattern(element, &ty);
+                }
+                self.matches_def(tuple_struct.id, ty)
+            }
+            ast::Pattern::Struct(struct_pattern) => {
+                let fields = self.resolutions.node(struct_pattern.id).and_then(|def| {
+                    match self.variants.get(&def) {
+                        Some(Payload::Struct(fields)) => Some(fields.clone()),
+                        _ => self.fields.get(&def).cloned(),
+                    }
+                });
+
+                for field in &struct_pattern.fields {
+                    let ty = fields
+                        .iter()
+                        .flatten()
+                        .find(|(name, _)| *name == field.name.text)
+                        .map_or(Type::Error, |(_, ty)| ty.clone());
+
+                    match &field.pattern {
+                        Some(pattern) => self.bind_pattern(pattern, &ty),
+                        None => self.set_def_type(field.id, ty),
+                    }
+                }
+                self.matches_def(struct_pattern.id, ty)
+            }
+        };
+
+        if !matches && !ty.is_flexible() {
+            let kind = TypeErrorKind::PatternMismatch {
+                expected: ty.clone(),
+                pattern: pattern.to_string(),
+            };
+            self.error(kind, pattern.span());
+        }
+    }
+
+    /// Reports the values of the scrutinee no arm of the `match` matches and the arms
+    /// that can't match any value.
+    fn check_coverage(&mut self, match_expr: &ast::MatchExpr, scrutinee: &Type) {
+        let shapes = Shapes {
+            resolutions: self.resolutions,
+            fields: &self.fields,
+            variants: &self.variants,
+        };
+        let patterns: Vec<_> = match_expr.arms.iter().map(|arm| &arm.pattern).collect();
+        let coverage = shapes.coverage(scrutinee, &patterns);
+
+        for index in coverage.unreachable {
+            let catch_all = patterns[..index]
+                .iter()
+                .find(|pattern| matches!(pattern, ast::Pattern::Wildcard(_)) || self.binds(pattern))
+                .map(|pattern| pattern.span());
+            self.checked.warnings.push(TypeWarning {
+                kind: TypeWarningKind::UnreachableArm { catch_all },
+                span: patterns[index].span(),
+            });
+        }
+
+        if !coverage.missing.is_empty() {
+            let kind = TypeErrorKind::NonExhaustive {
+                missing: coverage.missing,
+            };
+            self.error(kind, self.exprs[match_expr.scrutinee].span());
+        }
+    }
+
+    /// Returns whether the pattern is a name binding any value.
+    fn binds(&self, pattern: &ast::Pattern) -> bool {
+        match pattern {
+            ast::Pattern::Binding(binding) => self.resolutions.node(binding.id).is_none(),
+            _ => false,
+        }
+    }
+
+    /// Returns whether the struct or variant named by a pattern's path produces values
+    /// of the given type.
+    fn matches_def(&self, path: ast::NodeId, ty: &Type) -> bool {
+        let Some(def_id) = self.resolutions.node(path) else {
+            return true;
+        };
+        let def = self.resolutions.def(def_id);
+
+        let adt = match def.kind {
+            DefKind::Variant => def.parent,
+            DefKind::Struct => Some(def_id),
+            _ => None,
+        };
+
+        match ty {
+            Type::Adt(ty_def, _) => adt == Some(*ty_def),
+            _ => false,
+        }
+    }
+}
+
+fn is_comparison(op: ast::BinaryOp) -> bool {
+    matches!(
+        op,
+        ast::BinaryOp::Equal
+            | ast::BinaryOp::Unequal
+            | ast::BinaryOp::LessThan
+            | ast::BinaryOp::LessEqual
+            | ast::BinaryOp::GreaterThan
+            | ast::BinaryOp::GreaterEqual
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use syntax::{
+        ast, explain::explain, lexer::Lexer, limits::Limits, parser::Parser, token::Span,
+    };
+
+    use crate::{
+        check::{
+            Callee, Checked, TypeError, TypeErrorKind, TypeWarning, TypeWarningKind, check,
+            check_with_limits,
+        },
+        consts::eval_consts,
+        resolve::{DefKind, Resolutions, resolve},
+        ty::{Primitive, Type},
+    };
+
+    fn check_source(source: &str) -> (ast::Module, Resolutions, Checked) {
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let (module, errors) = Parser::new(&tokens).parse_module();
+        assert_eq!(errors, []);
+
+        let resolved = resolve(&module);
+        assert_eq!(resolved.errors, []);
+
+        let consts = eval_consts(&module, &resolved.resolutions).values;
+        let checked = check(&module, &resolved.resolutions, &consts);
+        (module, resolved.resolutions, checked)
+    }
+
+    /// Returns the type of the outermost expression starting at `offset`, displayed.
+    fn type_at(
+        module: &ast::Module,
+        resolutions: &Resolutions,
+        checked: &Checked,
+        offset: usize,
+    ) -> String {
+        let (id, _) = module
+            .exprs
+            .iter()
+            .filter(|(_, expr)| expr.span().start == offset)
+            .max_by_key(|(_, expr)| expr.span().end)
+            .unwrap();
+        checked.types.expr(id).display(resolutions).to_string()
+    }
+
+    #[test]
+    fn types() {
+        let source = "
+            enum Shape { Circle(f64), Empty }
+            fn area(shape: Shape) -> f64 {
+                match shape {
+                    Shape::Circle(r) => 3.0 * r * r,
+                    Shape::Empty => 0.0,
+                }
+            }
+            fn main(x: u8, ok: bool) {
+                let a = 1 + x;
+                let b = (a, 2.5, ok);
+                let c = loop { break x as i32; };
+                let d = area(Shape::Circle(1.0));
+                let e = 0..x;
+                let f: i32? = none;
+            }
+        ";
+        let (module, resolutions, checked) = check_source(source);
+        assert_eq!(checked.errors, []);
+
+        let at = |text: &str| type_at(&module, &resolutions, &checked, source.find(text).unwrap());
+        assert_eq!(at("3.0 * r"), "f64");
+        assert_eq!(at("1 + x"), "u8");
+        assert_eq!(at("(a, 2.5"), "(u8, f64, bool)");
+        assert_eq!(at("loop"), "i32");
+        assert_eq!(at("area(Shape"), "f64");
+        assert_eq!(at("Shape::Circle(1.0)"), "Shape");
+        assert_eq!(at("0..x"), "range<u8>");
+        assert_eq!(at("none"), "i32?");
+    }
+
+    #[test]
+    fn errors() {
+        let source = "
+            struct Point { x: f64 }
+            fn main(x: f64, ok: bool) -> i64 {
+                let a = x + ok;
+                let b: str = 5;
+                let c = -ok;
+                x(1);
+                let d = \"text\" as i64;
+                let e = Point;
+                1 = 2;
+                for i in x {}
+                let (f, g) = x;
+                let h: u8 = 0x100;
+                return 1.5;
+            }
+        ";
+        let (_, resolutions, checked) = check_source(source);
+
+        let kinds: Vec<_> = checked.errors.iter().map(|err| &err.kind).collect();
+        assert!(matches!(
+            kinds[..],
+            [
+                TypeErrorKind::InvalidOperands { op: "+", .. },
+                TypeErrorKind::Mismatch {
+                    origin: Some(_),
+                    ..
+                },
+                TypeErrorKind::InvalidOperand { op: "-", .. },
+                TypeErrorKind::NotCallable(_),
+                TypeErrorKind::InvalidCast { .. },
+                TypeErrorKind::NotAValue { .. },
+                TypeErrorKind::InvalidAssignTarget,
+                TypeErrorKind::NotIterable(_),
+                TypeErrorKind::PatternMismatch { .. },
+                TypeErrorKind::LiteralOutOfRange {
+                    value: 256,
+                    ty: Primitive::U8,
+                },
+                TypeErrorKind::Mismatch {
+                    origin: Some(_),
+                    ..
+                },
+            ]
+        ));
+
+        let diagnostic = checked.errors[9].diagnostic(&resolutions);
+        assert_eq!(diagnostic.message, "literal out of range for `u8`");
+        assert_eq!(
+            diagnostic.notes,
+            [
+                "note: `u8` holds values from 0 to 255",
+                "help: consider using `u16` instead"
+            ]
+        );
+
+        let mismatch = &checked.errors[1];
+        let TypeErrorKind::Mismatch {
+            expected,
+            found,
+            origin: Some(origin),
+        } = &mismatch.kind
+        else {
+            unreachable!();
+        };
+        assert_eq!(*expected, Type::Primitive(Primitive::Str));
+        assert_eq!(*found, Type::Primitive(Primitive::I64));
+        assert_eq!(mismatch.span.start, source.find("5;").unwrap());
+        assert_eq!(origin.start, source.find("str =").unwrap());
+
+        for err in &checked.errors {
+            let diagnostic = err.diagnostic(&resolutions);
+            assert!(explain(diagnostic.code.unwrap()).is_some());
+        }
+    }
+
+    #[test]
+    fn calls() {
+        let source = "
+            fn add(a: i64, b: i64) -> i64 { a + b }
+            fn max<T>(a: T, b: T) -> T { a }
+            fn main(x: u8) {
+                let a = add(1);
+                let b = add(1, 2.0);
+                let c = max(x, 1);
+                let d = max(x, \"s\");
+            }
+        ";
+        let (module, resolutions, checked) = check_source(source);
+        let at = source.find("max(x, 1)").unwrap();
+        assert_eq!(type_at(&module, &resolutions, &checked, at), "u8");
+
+        let span = |text: &str| Span {
+            start: source.find(text).unwrap(),
+            end: source.find(text).unwrap() + text.len(),
+        };
+        let declaration = |name: &str| Callee {
+            name: name.to_string(),
+            span: span(name),
+        };
+
+        assert_eq!(
+            checked.errors,
+            [
+                TypeError {
+                    kind: TypeErrorKind::ArgumentCount {
+                        expected: 2,
+                        found: 1,
+                        callee: Some(declaration("add")),
+                    },
+                    span: span("add(1)"),
+                },
+                TypeError {
+                    kind: TypeErrorKind::ArgumentMismatch {
+                        expected: Type::Primitive(Primitive::I64),
+                        found: Type::Primitive(Primitive::F64),
+                        callee: declaration("add"),
+                    },
+                    span: span("2.0"),
+                },
+                TypeError {
+                    kind: TypeErrorKind::ArgumentMismatch {
+                        expected: Type::Primitive(Primitive::U8),
+                        found: Type::Primitive(Primitive::Str),
+                        callee: declaration("max"),
+                    },
+                    span: span("\"s\""),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn fields() {
+        let source = "
+            struct Point { x: f64, y: f64 }
+            struct Pair<A, B> { first: A, second: B }
+            fn main(p: Point, pair: Pair<Point, i64>, r: &Point) {
+                let a = pair.first.y;
+                let b = pair.second;
+                let c = r.x;
+                let d = p.z;
+                let e = b.x;
+            }
+        ";
+        let (module, resolutions, checked) = check_source(source);
+        let at = |text: &str| {
+            let offset = source.find(text).unwrap();
+            type_at(&module, &resolutions, &checked, offset)
+        };
+        assert_eq!(at("pair.first.y"), "f64");
+        assert_eq!(at("pair.second"), "i64");
+        assert_eq!(at("r.x"), "f64");
+
+        let (point, _) = resolutions
+            .defs()
+            .find(|(_, def)| def.name == "Point")
+            .unwrap();
+        let start = source.find("p.z").unwrap() + 2;
+        assert_eq!(
+            checked.errors[0],
+            TypeError {
+                kind: TypeErrorKind::UnknownField {
+                    base: Type::Adt(point, Vec::new()),
+                    field: "z".to_string(),
+                    available: vec!["x".to_string(), "y".to_string()],
+                },
+                span: Span {
+                    start,
+                    end: start + 1
+                },
+            }
+        );
+
+        let diagnostics: Vec<_> = checked
+            .errors
+            .iter()
+            .map(|err| err.diagnostic(&resolutions))
+            .collect();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[0].notes,
+            ["note: the available fields are `x`, `y`"]
+        );
+        assert_eq!(diagnostics[1].message, "no field `x` on type `i64`");
+        assert_eq!(diagnostics[1].notes, ["note: `i64` has no fields"]);
+    }
+
+    #[test]
+    fn instances() {
+        let source = "
+            fn max<T>(a: T, b: T) -> T { if a > b { a } else { b } }
+            fn biggest<U>(a: U, b: U, c: U) -> U { max(max(a, b), c) }
+            fn main() {
+                let a = biggest(1, 2, 3);
+                let b = max(true, false);
+            }
+        ";
+        let (module, resolutions, checked) = check_source(source);
+        let at = source.find("max(max").unwrap();
+        assert_eq!(type_at(&module, &resolutions, &checked, at), "U");
+        let at = source.find("biggest(1").unwrap();
+        assert_eq!(type_at(&module, &resolutions, &checked, at), "i64");
+
+        let instances: Vec<_> = checked
+            .types
+            .instances()
+            .iter()
+            .map(|instance| {
+                let args: Vec<_> = instance
+                    .args
+                    .iter()
+                    .map(|arg| arg.display(&resolutions).to_string())
+                    .collect();
+                format!(
+                    "{}<{}>",
+                    resolutions.def(instance.func).name,
+                    args.join(", ")
+                )
+            })
+            .collect();
+        assert_eq!(instances, ["biggest<i64>", "max<bool>", "max<i64>"]);
+
+        // `>` on `bool` is only an error in the `bool` instance.
+        assert_eq!(checked.errors.len(), 1);
+        let diagnostic = checked.errors[0].diagnostic(&resolutions);
+        assert_eq!(
+            diagnostic.message,
+            "cannot apply `>` to operands of types `bool` and `bool`"
+        );
+        assert_eq!(diagnostic.code, Some("E0016"));
+        assert_eq!(
+            diagnostic.labels.last().unwrap().message,
+            "in the instance `max<bool>` used here"
+        );
+    }
+
+    #[test]
+    fn assignments() {
+        let source = "
+            fn main(n: i64) {
+                let fixed = 1;
+                var count = 0;
+                let mut total = 0;
+                let later;
+                fixed = 2;
+                (count) = 1;
+                total = count;
+                later = total;
+                n = 3;
+            }
+        ";
+        let (_, resolutions, checked) = check_source(source);
+
+        let span = |text: &str| Span {
+            start: source.find(text).unwrap(),
+            end: source.find(text).unwrap() + text.len(),
+        };
+        assert_eq!(
+            checked.errors,
+            [
+                TypeError {
+                    kind: TypeErrorKind::AssignToImmutable {
+                        name: "fixed".to_string(),
+                        kind: DefKind::Local,
+                        declared: span("fixed"),
+                    },
+                    span: span("fixed = 2"),
+                },
+                TypeError {
+                    kind: TypeErrorKind::AssignToImmutable {
+                        name: "n".to_string(),
+                        kind: DefKind::Param,
+                        declared: Span {
+                            start: source.find("n: i64").unwrap(),
+                            end: source.find("n: i64").unwrap() + 1,
+                        },
+                    },
+                    span: span("n = 3"),
+                },
+            ]
+        );
+
+        let diagnostic = checked.errors[0].diagnostic(&resolutions);
+        assert_eq!(
+            diagnostic.message,
+            "cannot assign to immutable binding `fixed`"
+        );
+        assert_eq!(
+            diagnostic.notes,
+            ["help: declare it with `var` to make it mutable"]
+        );
+    }
+
+    #[test]
+    fn missing_returns() {
+        let source = "
+            fn sign(x: i64) -> i64 {
+                if x < 0 { return -1; } else if x > 0 { return 1; } else {}
+            }
+            fn pick(x: i64) -> i64 {
+                match x { 0 => { return 1; }, _ => { let y = 2; } }
+            }
+            fn half(x: i64) -> i64 {
+                if x > 0 { return x / 2; }
+            }
+            fn all(x: i64) -> i64 {
+                if x < 0 { return -1; }
+                if x > 0 { 1 } else { panic() }
+            }
+            fn forever() -> i64 {
+                loop {}
+            }
+        ";
+        let (_, _, checked) = check_source(source);
+
+        let spans: Vec<_> = checked
+            .errors
+            .iter()
+            .map(|err| {
+                assert!(matches!(err.kind, TypeErrorKind::MissingReturn { .. }));
+                &source[err.span.start..err.span.end]
+            })
+            .collect();
+        assert_eq!(
+            spans,
+            ["{}", "{ let y = 2; }", "if x > 0 { return x / 2; }"]
+        );
+    }
+
+    #[test]
+    fn coercions() {
+        let source = "
+            fn wide(x: i64, y: f64) -> i64 { x }
+            fn main(a: i32, b: u8, c: f32, d: i64) -> i64 {
+                let e: i64 = a;
+                let f = wide(b, c);
+                let g = a + d;
+                let h: i32 = d;
+                let i: f64 = a;
+                a
+            }
+        ";
+        let (module, resolutions, checked) = check_source(source);
+
+        let at = |text: &str| source.find(text).unwrap();
+        let coercion = |text: &str| {
+            let (id, _) = module
+                .exprs
+                .iter()
+                .find(|(_, expr)| expr.span().start == at(text))
+                .unwrap();
+            let ty = checked.types.coercion(id)?;
+            Some(ty.display(&resolutions).to_string())
+        };
+        assert_eq!(coercion("a;").as_deref(), Some("i64"));
+        assert_eq!(coercion("b, c").as_deref(), Some("i64"));
+        assert_eq!(coercion("c)").as_deref(), Some("f64"));
+        assert_eq!(coercion("a + d").as_deref(), Some("i64"));
+        assert_eq!(coercion("d;").as_deref(), None);
+        assert_eq!(coercion("a\n").as_deref(), Some("i64"));
+        assert_eq!(type_at(&module, &resolutions, &checked, at("a + d")), "i64");
+
+        let helps: Vec<_> = checked
+            .errors
+            .iter()
+            .map(|err| {
+                let diagnostic = err.diagnostic(&resolutions);
+                (&source[err.span.start..err.span.end], diagnostic.notes)
+            })
+            .collect();
+        assert_eq!(
+            helps,
+            [
+                (
+                    "d",
+                    vec!["help: convert the value explicitly with `as i32`".to_string()]
+                ),
+                (
+                    "a",
+                    vec!["help: convert the value explicitly with `as f64`".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn coverage() {
+        let source = "
+            enum Shape { Circle(f64), Square(f64), Rect { w: f64, h: f64 }, Empty }
+            fn main(shape: Shape, flag: bool, pair: (bool, bool), n: i64, o: i64?) {
+                let a = match shape { Shape::Circle(r) => r, Shape::Square(s) => s };
+                let b = match flag { true => 1, false => 0, _ => 2 };
+                let c = match pair { (true, _) => 1, (_, false) => 2 };
+                let d = match n { 0 => 1, 1 => 2, 0 => 3 };
+                let e = match o { none => 0, x => x };
+                let f = match o { x => x, none => 0 };
+                let g = match shape { Shape::Rect { w: 1.0, h } => h, Shape::Empty => 0.0 };
+            }
+        ";
+        let (_, _, checked) = check_source(source);
+
+        let missing: Vec<_> = checked
+            .errors
+            .iter()
+            .map(|error| match &error.kind {
+                TypeErrorKind::NonExhaustive { missing } => (
+                    &source[error.span.start..error.span.end],
+                    missing.iter().map(String::as_str).collect::<Vec<_>>(),
+                ),
+                kind => panic!("unexpected error {kind:?}"),
+            })
+            .collect();
+        assert_eq!(
+            missing,
+            [
+                ("shape", vec!["Shape::Rect { .. }", "Shape::Empty"]),
+                ("pair", vec!["(false, true)"]),
+                ("n", vec!["_"]),
+                (
+                    "shape",
+                    vec!["Shape::Circle(_)", "Shape::Square(_)", "Shape::Rect { .. }"]
+                ),
+            ]
+        );
+        assert!(explain(checked.errors[0].kind.code()).is_some());
+        assert_eq!(
+            checked.errors[0]
+                .diagnostic(&Resolutions::default())
+                .message,
+            "non-exhaustive match: `Shape::Rect { .. }` and `Shape::Empty` not covered"
+        );
+
+        // the span of the pattern at the start of the arm.
+        let span = |arm: &str| Span {
+            start: source.find(arm).unwrap(),
+            end: source.find(arm).unwrap() + arm.find(' ').unwrap(),
+        };
+        assert_eq!(
+            checked.warnings,
+            [
+                TypeWarning {
+                    kind: TypeWarningKind::UnreachableArm { catch_all: None },
+                    span: span("_ => 2"),
+                },
+                TypeWarning {
+                    kind: TypeWarningKind::UnreachableArm { catch_all: None },
+                    span: span("0 => 3"),
+                },
+                TypeWarning {
+                    kind: TypeWarningKind::UnreachableArm {
+                        catch_all: Some(span("x => x, none")),
+                    },
+                    span: span("none => 0 }"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let source = "
+            struct List { value: i64, next: List? }
+            struct Tree { nodes: (i64, Node) }
+            struct Node { tree: [Tree; 2] }
+            struct Link { next: *Link }
+            struct Pair { a: (i64, i64), b: Link }
+        ";
+        let (_, resolutions, checked) = check_source(source);
+
+        let errors: Vec<_> = checked
+            .errors
+            .iter()
+            .map(|error| &source[error.span.start..error.span.end])
+            .collect();
+        assert_eq!(errors, ["List", "Tree", "Node"]);
+
+        let diagnostic = checked.errors[0].diagnostic(&resolutions);
+        assert_eq!(diagnostic.code, Some("E0008"));
+        assert_eq!(
+            diagnostic.message,
+            "type recursion depth limit of 64 exceeded"
+        );
+
+        // polymorphic recursion would instantiate `f` forever, with larger types each time.
+        for arg in ["(a, 1)", "(a, a)"] {
+            let source = format!(
+                "fn f<T>(a: T, n: i64) -> i64 {{ if n == 0 {{ return 0; }} return f({arg}, n - 1); }}
+                fn main() -> i32 {{ f(1, 3); return 0; }}"
+            );
+            let (_, resolutions, checked) = check_source(&source);
+
+            assert_eq!(checked.errors.len(), 1);
+            let span = checked.errors[0].span;
+            assert_eq!(&source[span.start..span.end], format!("f({arg}, n - 1)"));
+            let diagnostic = checked.errors[0].diagnostic(&resolutions);
+            assert_eq!(
+                diagnostic.message,
+                "monomorphization depth limit of 128 exceeded"
+            );
+        }
+
+        let source = "
+            fn a<T>(x: T) -> T { b(x) }
+            fn b<T>(x: T) -> T { c(x) }
+            fn c<T>(x: T) -> T { x }
+            fn main() { a(1); }
+        ";
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let (module, _) = Parser::new(&tokens).parse_module();
+        let resolutions = resolve(&module).resolutions;
+        let consts = eval_consts(&module, &resolutions).values;
+        let limits = Limits {
+            monomorphization_depth: 2,
+            ..Limits::default()
+        };
+        let checked = check_with_limits(&module, &resolutions, &consts, limits);
+
+        let errors: Vec<_> = checked
+            .errors
+            .iter()
+            .map(|error| &source[error.span.start..error.span.end])
+            .collect();
+        assert_eq!(errors, ["c(x)"]);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::ty::{Primitive, Type};
+
+/// Returns whether every value of the primitive `from` is also a value of `to`, so it
+/// is converted implicitly, like `i32` to `i64`, `u8` to `i16` or `f32` to `f64`.
+///
+/// Integers and floats never convert into each other implicitly, as most integers of
+/// a size lose precision as floats of the same size, and `bool` converts to nothing.
+pub fn widens(from: Primitive, to: Primitive) -> bool {
+    if from == to {
+        return true;
+    }
+
+    let larger = to.size() > from.size();
+    if from.is_integer() && to.is_integer() {
+        // signed values don't fit any unsigned type, unsigned ones fit larger signed ones.
+        larger && (to.is_signed() || !from.is_signed())
+    } else {
+        from.is_float() && to.is_float() && larger
+    }
+}
+
+/// Returns whether a value of type `from` can be used where `to` is required, which it
+/// may need an implicit conversion for, see [widens].
+///
+/// Besides the primitives, a `T` can be used where a `T?` is required. Values nested in
+/// other types, like the elements of a tuple, are never converted.
+pub fn coerces(from: &Type, to: &Type) -> bool {
+    from.fits(to)
+        || match (from, to) {
+            (Type::Primitive(from), Type::Primitive(to)) => widens(*from, *to),
+            (from, Type::Optional(inner)) => coerces(from, inner),
+            _ => false,
+        }
+}
+
+/// Returns whether an `as` cast converts values of type `from` to `to`, which every
+/// number and `bool` does to every number, and every type does to those it coerces to.
+pub fn can_cast(from: &Type, to: &Type) -> bool {
+    let castable = |ty: &Type| ty.is_numeric() || ty.has_generics();
+
+    coerces(from, to) || (castable(from) || *from == Type::BOOL) && castable(to)
+}
+
+/// Returns whether a number of type `found` only fits where `expected` is required with
+/// an explicit `as` cast, like an `i64` where an `i32` or an `f64` is expected.
+pub fn needs_cast(found: &Type, expected: &Type) -> bool {
+    found.is_numeric() && expected.is_numeric() && !coerces(found, expected)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        coerce::{can_cast, coerces, needs_cast, widens},
+        ty::{Primitive, Type},
+    };
+
+    #[test]
+    fn lattice() {
+        use Primitive::*;
+
+        let widening = [
+            (I8, I16),
+            (I32, I64),
+            (U8, U16),
+            (U8, I16),
+            (U32, I64),
+            (F32, F64),
+        ];
+        for (from, to) in widening {
+            assert!(widens(from, to), "{from:?} should widen to {to:?}");
+        }
+
+        let refused = [
+            (I64, I32),
+            (I8, U64),
+            (U32, I32),
+            (I32, F64),
+            (F32, I64),
+            (F64, F32),
+            (Bool, I64),
+        ];
+        for (from, to) in refused {
+            assert!(!widens(from, to), "{from:?} shouldn't widen to {to:?}");
+        }
+
+        let ty = Type::Primitive;
+        let optional = |ty: Type| Type::Optional(Box::new(ty));
+        assert!(coerces(&ty(I32), &optional(ty(I64))));
+        assert!(!coerces(
+            &Type::Tuple(vec![ty(I32)]),
+            &Type::Tuple(vec![ty(I64)])
+        ));
+
+        assert!(can_cast(&ty(F64), &ty(U8)));
+        assert!(can_cast(&ty(Bool), &ty(I32)));
+        assert!(!can_cast(&ty(I32), &ty(Bool)));
+        assert!(!can_cast(&ty(Str), &ty(I32)));
+
+        assert!(needs_cast(&ty(I64), &ty(I32)));
+        assert!(needs_cast(&ty(I32), &ty(F64)));
+        assert!(!needs_cast(&ty(I32), &ty(I64)));
+        assert!(!needs_cast(&ty(Bool), &ty(I64)));
+    }
+}
@@ -0,0 +1,718 @@
+use std::{cmp::Ordering, collections::HashMap, fmt};
+
+use syntax::{
+    ast,
+    diagnostics::Diagnostic,
+    limits::{Limit, Limits},
+    messages::message,
+    token::Span,
+    visit::{self, Visitor},
+};
+
+use crate::{
+    fold::{FoldError, FoldErrorKind},
+    resolve::{DefId, DefKind, Resolutions},
+    ty::Primitive,
+};
+
+/// A value computed at compile time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstValue {
+    Int(i128),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+}
+
+impl fmt::Display for ConstValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstValue::Int(value) => write!(f, "{value}"),
+            ConstValue::Float(value) => write!(f, "{value:?}"),
+            ConstValue::Bool(value) => write!(f, "{value}"),
+            ConstValue::Str(value) => write!(f, "{value:?}"),
+        }
+    }
+}
+
+impl ConstValue {
+    /// Applies the unary operator to a value of the type `ty`, returning `None` if it
+    /// isn't defined for the value or its result can't be expressed.
+    pub fn unary(
+        op: ast::UnaryOp,
+        operand: &ConstValue,
+        ty: Primitive,
+    ) -> Result<Option<ConstValue>, FoldErrorKind> {
+        match (op, operand) {
+            (ast::UnaryOp::Neg, ConstValue::Int(value)) => integer(op.symbol(), -value, ty),
+            (ast::UnaryOp::Neg, ConstValue::Float(value)) => Ok(float(-value, ty)),
+            (ast::UnaryOp::Not, ConstValue::Bool(value)) => Ok(Some(ConstValue::Bool(!value))),
+            (ast::UnaryOp::Not, ConstValue::Int(value)) => {
+                let Some((min, max)) = ty.bounds() else {
+                    return Ok(None);
+                };
+                // flips the bits of the value's two's complement representation.
+                let value = if min < 0 { !value } else { max - value };
+                Ok(Some(ConstValue::Int(value)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Applies the binary operator to two values, where arithmetic is evaluated in the
+    /// result type `ty`, see [ConstValue::unary].
+    pub fn binary(
+        op: ast::BinaryOp,
+        lhs: &ConstValue,
+        rhs: &ConstValue,
+        ty: Primitive,
+    ) -> Result<Option<ConstValue>, FoldErrorKind> {
+        match (lhs, rhs) {
+            (&ConstValue::Int(lhs), &ConstValue::Int(rhs)) => {
+                let value = match op {
+                    ast::BinaryOp::Add => lhs.checked_add(rhs),
+                    ast::BinaryOp::Sub => lhs.checked_sub(rhs),
+                    ast::BinaryOp::Mul => lhs.checked_mul(rhs),
+                    ast::BinaryOp::Div | ast::BinaryOp::Rem if rhs == 0 => {
+                        return Err(FoldErrorKind::DivisionByZero);
+                    }
+                    ast::BinaryOp::Div => lhs.checked_div(rhs),
+                    ast::BinaryOp::Rem => lhs.checked_rem(rhs),
+                    _ => return Ok(compare(op, lhs.cmp(&rhs))),
+                };
+
+                match value {
+                    Some(value) => integer(op.symbol(), value, ty),
+                    None => Err(FoldErrorKind::Overflow {
+                        op: op.symbol(),
+                        ty,
+                    }),
+                }
+            }
+            (&ConstValue::Float(lhs), &ConstValue::Float(rhs)) => {
+                let value = match op {
+                    ast::BinaryOp::Add => lhs + rhs,
+                    ast::BinaryOp::Sub => lhs - rhs,
+                    ast::BinaryOp::Mul => lhs * rhs,
+                    ast::BinaryOp::Div => lhs / rhs,
+                    ast::BinaryOp::Rem => lhs % rhs,
+                    _ => {
+                        return Ok(lhs
+                            .partial_cmp(&rhs)
+                            .and_then(|ordering| compare(op, ordering)));
+                    }
+                };
+                Ok(float(value, ty))
+            }
+            (ConstValue::Bool(lhs), ConstValue::Bool(rhs)) => Ok(compare_equality(op, lhs == rhs)),
+            (ConstValue::Str(lhs), ConstValue::Str(rhs)) => match op {
+                ast::BinaryOp::Equal | ast::BinaryOp::Unequal => {
+                    Ok(compare_equality(op, lhs == rhs))
+                }
+                _ => Ok(compare(op, lhs.cmp(rhs))),
+            },
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Returns the integer value if it fits the integer type `ty`.
+fn integer(
+    op: &'static str,
+    value: i128,
+    ty: Primitive,
+) -> Result<Option<ConstValue>, FoldErrorKind> {
+    let Some((min, max)) = ty.bounds() else {
+        return Ok(None);
+    };
+
+    if !(min..=max).contains(&value) {
+        return Err(FoldErrorKind::Overflow { op, ty });
+    }
+
+    Ok(Some(ConstValue::Int(value)))
+}
+
+/// Returns the float value rounded to the float type `ty`, unless it is infinite or
+/// not a number, which no literal can express.
+fn float(value: f64, ty: Primitive) -> Option<ConstValue> {
+    let value = match ty {
+        Primitive::F32 => value as f32 as f64,
+        Primitive::F64 => value,
+        _ => return None,
+    };
+
+    value.is_finite().then_some(ConstValue::Float(value))
+}
+
+/// Returns the result of a comparison whose operands are ordered like `ordering`.
+fn compare(op: ast::BinaryOp, ordering: Ordering) -> Option<ConstValue> {
+    let value = match op {
+        ast::BinaryOp::Equal => ordering.is_eq(),
+        ast::BinaryOp::Unequal => ordering.is_ne(),
+        ast::BinaryOp::LessThan => ordering.is_lt(),
+        ast::BinaryOp::LessEqual => ordering.is_le(),
+        ast::BinaryOp::GreaterThan => ordering.is_gt(),
+        ast::BinaryOp::GreaterEqual => ordering.is_ge(),
+        _ => return None,
+    };
+    Some(ConstValue::Bool(value))
+}
+
+/// Returns the result of `==` or `!=` on values that are `equal` or not.
+fn compare_equality(op: ast::BinaryOp, equal: bool) -> Option<ConstValue> {
+    match op {
+        ast::BinaryOp::Equal => Some(ConstValue::Bool(equal)),
+        ast::BinaryOp::Unequal => Some(ConstValue::Bool(!equal)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstError {
+    pub kind: ConstErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstErrorKind {
+    /// Constants whose initializers refer to each other in a cycle, starting and
+    /// ending with the reported one.
+    Cycle(Vec<String>),
+    /// An expression that can't be evaluated at compile time.
+    NotConstant,
+    /// An array length that isn't an integer between zero and the largest `u64`.
+    InvalidLength(String),
+    /// An arithmetic operation that can't be evaluated.
+    Arithmetic(FoldErrorKind),
+    /// A [Limit] set to `value` exceeded, like by evaluating too many expressions.
+    LimitExceeded { limit: Limit, value: usize },
+}
+
+impl ConstErrorKind {
+    /// Returns the stable code of this kind of error, see [syntax::explain::explain].
+    pub fn code(&self) -> &'static str {
+        match self {
+            ConstErrorKind::Cycle(_) => "E0026",
+            ConstErrorKind::NotConstant => "E0027",
+            ConstErrorKind::InvalidLength(_) => "E0028",
+            ConstErrorKind::Arithmetic(kind) => kind.code(),
+            ConstErrorKind::LimitExceeded { .. } => "E0008",
+        }
+    }
+}
+
+impl ConstError {
+    /// Converts the error into a [Diagnostic].
+    pub fn diagnostic(&self) -> Diagnostic {
+        let code = self.kind.code();
+
+        let diagnostic = match &self.kind {
+            ConstErrorKind::Cycle(names) => {
+                let cycle = names
+                    .iter()
+                    .map(|name| format!("`{name}`"))
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                Diagnostic::error(message(code, &[("name", &names[0])]), self.span)
+                    .with_note(message("E0026.note", &[("cycle", &cycle)]))
+            }
+            ConstErrorKind::NotConstant => Diagnostic::error(message(code, &[]), self.span),
+            ConstErrorKind::InvalidLength(value) => {
+                Diagnostic::error(message(code, &[("value", value)]), self.span)
+            }
+            ConstErrorKind::Arithmetic(kind) => {
+                let err = FoldError {
+                    kind: kind.clone(),
+                    span: self.span,
+                };
+                return err.diagnostic();
+            }
+            ConstErrorKind::LimitExceeded { limit, value } => {
+                let (message, help) = limit.exceeded(*value);
+                Diagnostic::error(message, self.span).with_help(help)
+            }
+        };
+
+        diagnostic.with_code(code)
+    }
+}
+
+/// The values of the constants of a module and the lengths of its array types.
+#[derive(Debug, Default, PartialEq)]
+pub struct ConstValues {
+    consts: HashMap<DefId, ConstValue>,
+    lens: HashMap<ast::NodeId, u64>,
+}
+
+impl ConstValues {
+    /// Returns the value of a constant, unless it couldn't be evaluated.
+    pub fn value(&self, def: DefId) -> Option<&ConstValue> {
+        self.consts.get(&def)
+    }
+
+    /// Returns the length of an [ast::ArrayType], unless it couldn't be evaluated.
+    pub fn array_len(&self, id: ast::NodeId) -> Option<u64> {
+        self.lens.get(&id).copied()
+    }
+}
+
+/// The results of [eval_consts].
+#[derive(Debug, Default)]
+pub struct Evaluated {
+    pub values: ConstValues,
+    pub errors: Vec<ConstError>,
+}
+
+/// Computes the initializers of the constants of the resolved [ast::Module] and the
+/// lengths of its array types, which may name constants.
+///
+/// Initializers and lengths consist of literals, operators and other constants, where
+/// integer arithmetic is evaluated in the type of the constant. Constants referring to
+/// themselves through their initializers are reported.
+pub fn eval_consts(module: &ast::Module, resolutions: &Resolutions) -> Evaluated {
+    eval_consts_with_limits(module, resolutions, Limits::default())
+}
+
+/// Evaluates the constants like [eval_consts], enforcing the given [Limits].
+pub fn eval_consts_with_limits(
+    module: &ast::Module,
+    resolutions: &Resolutions,
+    limits: Limits,
+) -> Evaluated {
+    let mut collector = ConstCollector {
+        exprs: &module.exprs,
+        resolutions,
+        consts: HashMap::new(),
+        arrays: Vec::new(),
+    };
+    collector.visit_module(module);
+
+    let mut evaluator = Evaluator {
+        exprs: &module.exprs,
+        resolutions,
+        consts: collector.consts,
+        states: HashMap::new(),
+        stack: Vec::new(),
+        limits,
+        steps: 0,
+        in_len: false,
+        evaluated: Evaluated::default(),
+    };
+
+    let mut defs: Vec<_> = evaluator.consts.keys().copied().collect();
+    defs.sort();
+    for def in defs {
+        evaluator.eval_const(def, None);
+    }
+
+    for array in collector.arrays {
+        evaluator.eval_len(array);
+    }
+
+    evaluator.evaluated
+}
+
+/// Collects the declarations of constants and the array types of a module.
+struct ConstCollector<'ast> {
+    exprs: &'ast ast::ExprArena,
+    resolutions: &'ast Resolutions,
+    consts: HashMap<DefId, &'ast ast::ConstDecl>,
+    arrays: Vec<&'ast ast::ArrayType>,
+}
+
+impl<'ast> Visitor<'ast> for ConstCollector<'ast> {
+    fn exprs(&self) -> &'ast ast::ExprArena {
+        self.exprs
+    }
+
+    fn visit_const(&mut self, const_decl: &'ast ast::ConstDecl) {
+        if let Some(def) = self.resolutions.decl(const_decl.id) {
+            self.consts.insert(def, const_decl);
+        }
+        visit::walk_const(self, const_decl);
+    }
+
+    fn visit_type(&mut self, ty: &'ast ast::TypeExpr) {
+        if let ast::TypeExpr::Array(array) = ty {
+            self.arrays.push(array);
+        }
+        visit::walk_type(self, ty);
+    }
+}
+
+/// The progress of evaluating a constant.
+enum State {
+    /// The constant's initializer is being evaluated, so reaching it again is a cycle.
+    InProgress,
+    Done,
+}
+
+struct Evaluator<'ast> {
+    exprs: &'ast ast::ExprArena,
+    resolutions: &'ast Resolutions,
+    consts: HashMap<DefId, &'ast ast::ConstDecl>,
+    states: HashMap<DefId, State>,
+    /// The constants being evaluated, outermost first.
+    stack: Vec<DefId>,
+    limits: Limits,
+    /// How many expressions were evaluated, see [Limits::const_eval_steps].
+    steps: usize,
+    /// Whether an array length is evaluated, whose errors no folding reports.
+    in_len: bool,
+    evaluated: Evaluated,
+}
+
+impl Evaluator<'_> {
+    fn error(&mut self, kind: ConstErrorKind, span: Span) {
+        self.evaluated.errors.push(ConstError { kind, span });
+    }
+
+    /// Evaluates the constant unless that happened before, where `reference` is the
+    /// [Span] of the name through which another initializer refers to it.
+    fn eval_const(&mut self, def: DefId, reference: Option<Span>) -> Option<ConstValue> {
+        match self.states.get(&def) {
+            Some(State::Done) => return self.evaluated.values.consts.get(&def).cloned(),
+            Some(State::InProgress) => {
+                let start = self.stack.iter().position(|&on_stack| on_stack == def)?;
+                let names = self.stack[start..]
+                    .iter()
+                    .chain([&def])
+                    .map(|&def| self.resolutions.def(def).name.clone())
+                    .collect();
+
+                let span = reference.unwrap_or(self.resolutions.def(def).span);
+                self.error(ConstErrorKind::Cycle(names), span);
+                return None;
+            }
+            None => {}
+        }
+
+        let const_decl = self.consts.get(&def).copied()?;
+        self.states.insert(def, State::InProgress);
+        self.stack.push(def);
+
+        let ty = self.primitive(&const_decl.ty);
+        let value = self.eval(const_decl.value, ty);
+
+        self.stack.pop();
+        self.states.insert(def, State::Done);
+
+        if let Some(value) = &value {
+            self.evaluated.values.consts.insert(def, value.clone());
+        }
+        value
+    }
+
+    fn eval_len(&mut self, array: &ast::ArrayType) {
+        self.in_len = true;
+        let value = self.eval(array.len, None);
+        self.in_len = false;
+        let Some(value) = value else {
+            return;
+        };
+
+        let len = match value {
+            ConstValue::Int(value) => u64::try_from(value).ok(),
+            _ => None,
+        };
+        match len {
+            Some(len) => {
+                self.evaluated.values.lens.insert(array.id, len);
+            }
+            None => {
+                let kind = ConstErrorKind::InvalidLength(value.to_string());
+                self.error(kind, self.exprs[array.len].span());
+            }
+        }
+    }
+
+    /// Returns the built-in type a type expression names.
+    fn primitive(&self, ty: &ast::TypeExpr) -> Option<Primitive> {
+        let ast::TypeExpr::Named(named) = ty else {
+            return None;
+        };
+
+        let def = self.resolutions.def(self.resolutions.node(named.id)?);
+        match def.kind {
+            DefKind::Primitive => Primitive::from_name(&def.name),
+            _ => None,
+        }
+    }
+
+    /// Evaluates the expression, where integers and floats have the type `ty` if it
+    /// is known. Errors are reported unless the type checker or the constant folding
+    /// reports them, like those in expressions consisting only of literals.
+    fn eval(&mut self, id: ast::ExprId, ty: Option<Primitive>) -> Option<ConstValue> {
+        let span = self.exprs[id].span();
+
+        // the error is reported once, after which nothing is evaluated anymore.
+        let limit = self.limits.const_eval_steps;
+        self.steps += 1;
+        if self.steps > limit {
+            if self.steps == limit + 1 {
+                let kind = ConstErrorKind::LimitExceeded {
+                    limit: Limit::ConstEvalSteps,
+                    value: limit,
+                };
+                self.error(kind, span);
+            }
+            return None;
+        }
+
+        match &self.exprs[id] {
+            ast::Expr::Integer(integer) => Some(ConstValue::Int(integer.value)),
+            ast::Expr::Float(float) => Some(ConstValue::Float(f64::from_bits(float.value_bits))),
+            ast::Expr::String(string) => Some(ConstValue::Str(string.value.clone())),
+            ast::Expr::Paren(paren) => self.eval(paren.expr, ty),
+            ast::Expr::Identifier(_) | ast::Expr::Path(_) => {
+                let def_id = self.resolutions.expr(id)?;
+                let def = self.resolutions.def(def_id);
+
+                match def.kind {
+                    DefKind::Const => self.eval_const(def_id, Some(span)),
+                    DefKind::Builtin => Some(ConstValue::Bool(def.name == "true")),
+                    _ => {
+                        self.error(ConstErrorKind::NotConstant, span);
+                        None
+                    }
+                }
+            }
+            ast::Expr::Unary(unary) => {
+                let operand = self.eval(unary.operand, ty)?;
+                let ty = ty.unwrap_or_else(|| default_type(&operand));
+
+                self.arithmetic(
+                    ConstValue::unary(unary.op, &operand, ty),
+                    &[unary.operand],
+                    span,
+                )
+            }
+            ast::Expr::Binary(binary) => {
+                // comparisons don't give their operands a type.
+                let operand_ty = match binary.op {
+                    ast::BinaryOp::Add
+                    | ast::BinaryOp::Sub
+                    | ast::BinaryOp::Mul
+                    | ast::BinaryOp::Div
+                    | ast::BinaryOp::Rem => ty,
+                    _ => None,
+                };
+
+                let lhs = self.eval(binary.lhs, operand_ty);
+                let rhs = self.eval(binary.rhs, operand_ty);
+                let (lhs, rhs) = lhs.zip(rhs)?;
+                let ty = operand_ty.unwrap_or_else(|| default_type(&lhs));
+
+                let result = ConstValue::binary(binary.op, &lhs, &rhs, ty);
+                self.arithmetic(result, &[binary.lhs, binary.rhs], span)
+            }
+            ast::Expr::Error(_) => None,
+            _ => {
+                self.error(ConstErrorKind::NotConstant, span);
+                None
+            }
+        }
+    }
+
+    /// Returns the value of an operation on the given operands, reporting an error
+    /// unless the operands only consist of literals, which the folding reports.
+    fn arithmetic(
+        &mut self,
+        result: Result<Option<ConstValue>, FoldErrorKind>,
+        operands: &[ast::ExprId],
+        span: Span,
+    ) -> Option<ConstValue> {
+        match result {
+            Ok(value) => value,
+            Err(kind) => {
+                if self.in_len || !operands.iter().all(|&operand| self.is_literal(operand)) {
+                    self.error(ConstErrorKind::Arithmetic(kind), span);
+                }
+                None
+            }
+        }
+    }
+
+    /// Returns whether the expression consists only of literals and operators.
+    fn is_literal(&self, id: ast::ExprId) -> bool {
+        match &self.exprs[id] {
+            ast::Expr::Integer(_) | ast::Expr::Float(_) | ast::Expr::String(_) => true,
+            ast::Expr::Identifier(_) => self
+                .resolutions
+                .expr(id)
+                .is_some_and(|def| self.resolutions.def(def).kind == DefKind::Builtin),
+            ast::Expr::Paren(paren) => self.is_literal(paren.expr),
+            ast::Expr::Unary(unary) => self.is_literal(unary.operand),
+            ast::Expr::Binary(binary) => self.is_literal(binary.lhs) && self.is_literal(binary.rhs),
+            _ => false,
+        }
+    }
+}
+
+/// Returns the type an integer or float takes without a type expected of it.
+fn default_type(value: &ConstValue) -> Primitive {
+    match value {
+        ConstValue::Float(_) => Primitive::DEFAULT_FLOAT,
+        _ => Primitive::DEFAULT_INTEGER,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use syntax::{
+        ast,
+        explain::explain,
+        lexer::Lexer,
+        limits::{Limit, Limits},
+        parser::Parser,
+        token::Span,
+    };
+
+    use crate::{
+        consts::{ConstError, ConstErrorKind, ConstValue, eval_consts, eval_consts_with_limits},
+        fold::FoldErrorKind,
+        resolve::resolve,
+        ty::Primitive,
+    };
+
+    #[test]
+    fn consts() {
+        let source = "
+            const A: i64 = B + 1;
+            const B: i64 = A * 2;
+            const SIZE: u64 = 4 * HALF;
+            const HALF: u64 = 2;
+            const BIG: u8 = 250;
+            const OVER: u8 = BIG + 10;
+            const LARGE: bool = SIZE >= 8 == true;
+            const NAME: str = \"elan\";
+            fn main(n: i64, values: [i64; SIZE], empty: [i64; 0], invalid: [i64; n],
+                    sum: [i64; (SIZE + 1) * 2], negative: [i64; HALF - 3]) {
+                const TWICE: i64 = n * 2;
+            }
+        ";
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let (module, _) = Parser::new(&tokens).parse_module();
+        let resolutions = resolve(&module).resolutions;
+        let evaluated = eval_consts(&module, &resolutions);
+
+        let value = |name: &str| {
+            let (def, _) = resolutions.defs().find(|(_, def)| def.name == name)?;
+            evaluated.values.value(def).cloned()
+        };
+        assert_eq!(value("A"), None);
+        assert_eq!(value("SIZE"), Some(ConstValue::Int(8)));
+        assert_eq!(value("OVER"), None);
+        assert_eq!(value("LARGE"), Some(ConstValue::Bool(true)));
+        assert_eq!(value("NAME"), Some(ConstValue::Str("elan".to_string())));
+
+        let ast::Item::Func(main) = &module.items[8] else {
+            unreachable!();
+        };
+        let lens: Vec<_> = main.params[1..]
+            .iter()
+            .map(|param| match &param.ty {
+                ast::TypeExpr::Array(array) => evaluated.values.array_len(array.id),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(lens, [Some(8), Some(0), None, Some(18), None]);
+
+        let span = |text: &str| {
+            let start = source.find(text).unwrap();
+            Span {
+                start,
+                end: start + text.len(),
+            }
+        };
+        assert_eq!(
+            evaluated.errors,
+            [
+                ConstError {
+                    kind: ConstErrorKind::Cycle(vec![
+                        "A".to_string(),
+                        "B".to_string(),
+                        "A".to_string(),
+                    ]),
+                    span: Span {
+                        start: span("A * 2").start,
+                        end: span("A * 2").start + 1,
+                    },
+                },
+                ConstError {
+                    kind: ConstErrorKind::Arithmetic(FoldErrorKind::Overflow {
+                        op: "+",
+                        ty: Primitive::U8,
+                    }),
+                    span: span("BIG + 10"),
+                },
+                ConstError {
+                    kind: ConstErrorKind::NotConstant,
+                    span: Span {
+                        start: span("n * 2").start,
+                        end: span("n * 2").start + 1,
+                    },
+                },
+                ConstError {
+                    kind: ConstErrorKind::NotConstant,
+                    span: Span {
+                        start: span("n]").start,
+                        end: span("n]").start + 1,
+                    },
+                },
+                ConstError {
+                    kind: ConstErrorKind::InvalidLength("-1".to_string()),
+                    span: span("HALF - 3"),
+                },
+            ]
+        );
+
+        for err in &evaluated.errors {
+            assert!(explain(err.kind.code()).is_some());
+        }
+    }
+
+    #[test]
+    fn limits() {
+        let source = "
+            const A: i64 = 1 + 2 + 3;
+            const B: i64 = A * A;
+            fn main(values: [i64; 250 + 10 - 4], huge: [i64; 9223372036854775807 + 1]) {}
+        ";
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let (module, _) = Parser::new(&tokens).parse_module();
+        let resolutions = resolve(&module).resolutions;
+
+        // literal arithmetic in array lengths is reported, since no folding reports it.
+        let evaluated = eval_consts(&module, &resolutions);
+        let kinds: Vec<_> = evaluated.errors.iter().map(|err| &err.kind).collect();
+        assert!(matches!(
+            kinds[..],
+            [ConstErrorKind::Arithmetic(FoldErrorKind::Overflow { .. })]
+        ));
+
+        let limits = Limits {
+            const_eval_steps: 6,
+            ..Limits::default()
+        };
+        let evaluated = eval_consts_with_limits(&module, &resolutions, limits);
+        assert_eq!(
+            evaluated.errors,
+            [ConstError {
+                kind: ConstErrorKind::LimitExceeded {
+                    limit: Limit::ConstEvalSteps,
+                    value: 6,
+                },
+                span: {
+                    let start = source.find("A * A").unwrap();
+                    Span {
+                        start,
+                        end: start + 1,
+                    }
+                },
+            }]
+        );
+        assert_eq!(evaluated.errors[0].diagnostic().code, Some("E0008"));
+    }
+}
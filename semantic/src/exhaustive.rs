@@ -0,0 +1,369 @@
+use std::{collections::HashMap, fmt::Write, slice};
+
+use syntax::{ast, token::Span};
+
+use crate::{
+    check::Payload,
+    resolve::{DefId, DefKind, Resolutions},
+    ty::{Primitive, Type},
+};
+
+/// How far the arms of a `match` cover the values of its scrutinee, see [Shapes::coverage].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Coverage {
+    /// Patterns like `Shape::Circle(_)` matching the values no arm matches.
+    pub missing: Vec<String>,
+    /// The indices of the arms only matching values an earlier arm already matches.
+    pub unreachable: Vec<usize>,
+}
+
+/// The kinds of values a pattern can tell apart at the top, like the variants of an enum.
+#[derive(Debug, Clone, PartialEq)]
+enum Ctor {
+    Bool(bool),
+    Integer(i128),
+    Float(u64),
+    String(String),
+    Tuple(usize),
+    None,
+    /// The present value of an optional type.
+    Some,
+    /// A struct or an enum variant.
+    Adt(DefId),
+    /// A pattern whose values aren't known, like a variant of an imported enum, which
+    /// only equals itself.
+    Opaque(Span),
+}
+
+/// A pattern reduced to the [Ctor]s it matches.
+#[derive(Debug, Clone)]
+enum Pat {
+    Wild,
+    Ctor(Ctor, Vec<Pat>),
+}
+
+/// What the coverage of patterns depends on, the variants and fields of the module's
+/// enums and structs.
+pub(crate) struct Shapes<'a> {
+    pub resolutions: &'a Resolutions,
+    pub fields: &'a HashMap<DefId, Vec<(String, Type)>>,
+    pub variants: &'a HashMap<DefId, Payload>,
+}
+
+impl Shapes<'_> {
+    /// Checks the patterns of a `match` over values of the given type, whose patterns
+    /// are known to fit it.
+    ///
+    /// Integers, floats and strings are only covered by a wildcard or a binding, as are
+    /// the values of imported enums and structs.
+    pub fn coverage(&self, ty: &Type, patterns: &[&ast::Pattern]) -> Coverage {
+        let types = slice::from_ref(ty);
+        let mut coverage = Coverage::default();
+        let mut rows = Vec::new();
+
+        for (index, pattern) in patterns.iter().enumerate() {
+            let row = vec![self.lower(pattern, ty)];
+            if self.useful(&rows, &row, types).is_none() {
+                coverage.unreachable.push(index);
+            }
+            rows.push(row);
+        }
+
+        // every missing variant is reported, not only the first one found.
+        let heads = match self.ctors(ty) {
+            Some(ctors) => ctors
+                .into_iter()
+                .map(|ctor| {
+                    let arity = self.fields(&ctor, ty).len();
+                    Pat::Ctor(ctor, vec![Pat::Wild; arity])
+                })
+                .collect(),
+            None => vec![Pat::Wild],
+        };
+        for head in heads {
+            if let Some(witness) = self.useful(&rows, &[head], types) {
+                let mut text = String::new();
+                self.display(&witness[0], &mut text);
+                coverage.missing.push(text);
+            }
+        }
+
+        coverage
+    }
+
+    /// Returns every [Ctor] of the type, or [None] if there are too many to list them,
+    /// like for integers.
+    fn ctors(&self, ty: &Type) -> Option<Vec<Ctor>> {
+        match ty {
+            Type::Primitive(Primitive::Bool) => Some(vec![Ctor::Bool(true), Ctor::Bool(false)]),
+            Type::Tuple(types) => Some(vec![Ctor::Tuple(types.len())]),
+            Type::Optional(_) => Some(vec![Ctor::None, Ctor::Some]),
+            Type::Adt(def, _) if self.resolutions.file(*def).is_some() => None,
+            Type::Adt(def, _) if self.fields.contains_key(def) => Some(vec![Ctor::Adt(*def)]),
+            Type::Adt(def, _) => Some(
+                self.resolutions
+                    .defs()
+                    .filter(|(_, variant)| variant.parent == Some(*def))
+                    .map(|(id, _)| Ctor::Adt(id))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Returns the fields of the values built by the [Ctor] at the given type, along with
+    /// their names if they have any.
+    fn fields(&self, ctor: &Ctor, ty: &Type) -> Vec<(Option<String>, Type)> {
+        match (ctor, ty) {
+            (Ctor::Tuple(_), Type::Tuple(types)) => {
+                types.iter().map(|ty| (None, ty.clone())).collect()
+            }
+            (Ctor::Some, Type::Optional(inner)) => vec![(None, (**inner).clone())],
+            (Ctor::Adt(def), Type::Adt(adt, args)) => {
+                let generics: HashMap<_, _> = self
+                    .resolutions
+                    .generics(*adt)
+                    .iter()
+                    .copied()
+                    .zip(args.iter().cloned())
+                    .collect();
+                let named = |fields: &[(String, Type)]| {
+                    fields
+                        .iter()
+                        .map(|(name, ty)| (Some(name.clone()), ty.substitute(&generics)))
+                        .collect()
+                };
+
+                match self.variants.get(def) {
+                    Some(Payload::Unit) => Vec::new(),
+                    Some(Payload::Tuple(types)) => types
+                        .iter()
+                        .map(|ty| (None, ty.substitute(&generics)))
+                        .collect(),
+                    Some(Payload::Struct(fields)) => named(fields),
+                    None => self.fields.get(def).map_or_else(Vec::new, |f| named(f)),
+                }
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Reduces a pattern matching values of the given type to a [Pat].
+    fn lower(&self, pattern: &ast::Pattern, ty: &Type) -> Pat {
+        let path_ctor = |id: ast::NodeId, span: Span| match self.resolutions.node(id) {
+            Some(def) if self.resolutions.file(def).is_none() => Ctor::Adt(def),
+            _ => Ctor::Opaque(span),
+        };
+
+        let ctor = match pattern {
+            ast::Pattern::Wildcard(_) => return Pat::Wild,
+            ast::Pattern::Binding(binding) => match self.resolutions.node(binding.id) {
+                Some(def) => Ctor::Bool(self.resolutions.def(def).name == "true"),
+                None => return Pat::Wild,
+            },
+            ast::Pattern::None(_) => Ctor::None,
+            ast::Pattern::Integer(integer) => Ctor::Integer(integer.value),
+            ast::Pattern::Float(float) => Ctor::Float(float.value_bits),
+            ast::Pattern::String(string) => Ctor::String(string.value.clone()),
+            ast::Pattern::Tuple(tuple) => Ctor::Tuple(tuple.elements.len()),
+            ast::Pattern::Path(path) => path_ctor(path.id, path.span),
+            ast::Pattern::TupleStruct(tuple_struct) => {
+                path_ctor(tuple_struct.id, tuple_struct.span)
+            }
+            ast::Pattern::Struct(struct_pattern) => {
+                path_ctor(struct_pattern.id, struct_pattern.span)
+            }
+        };
+
+        let fields = self.fields(&ctor, ty);
+        let args = match pattern {
+            ast::Pattern::Tuple(ast::TuplePattern { elements, .. })
+            | ast::Pattern::TupleStruct(ast::TupleStructPattern { elements, .. }) => fields
+                .iter()
+                .enumerate()
+                .map(|(index, (_, ty))| {
+                    elements
+                        .get(index)
+                        .map_or(Pat::Wild, |element| self.lower(element, ty))
+                })
+                .collect(),
+            ast::Pattern::Struct(struct_pattern) => fields
+                .iter()
+                .map(|(name, ty)| {
+                    let pattern = struct_pattern
+                        .fields
+                        .iter()
+                        .find(|field| Some(&field.name.text) == name.as_ref())
+                        .and_then(|field| field.pattern.as_ref());
+                    pattern.map_or(Pat::Wild, |pattern| self.lower(pattern, ty))
+                })
+                .collect(),
+            _ => vec![Pat::Wild; fields.len()],
+        };
+
+        Pat::Ctor(ctor, args)
+    }
+
+    /// Returns the patterns of a value matched by `row` but by none of `rows`, one for
+    /// each column, or [None] if the `rows` already match everything `row` does.
+    fn useful(&self, rows: &[Vec<Pat>], row: &[Pat], types: &[Type]) -> Option<Vec<Pat>> {
+        let Some((head, rest)) = row.split_first() else {
+            return rows.is_empty().then(Vec::new);
+        };
+        let ty = &types[0];
+
+        if let Pat::Ctor(ctor, args) = head {
+            let row: Vec<_> = args.iter().chain(rest).cloned().collect();
+            return self.useful_ctor(rows, ctor, &row, types);
+        }
+
+        let used: Vec<_> = rows
+            .iter()
+            .filter_map(|row| match &row[0] {
+                Pat::Ctor(ctor, _) => Some(ctor),
+                Pat::Wild => None,
+            })
+            .collect();
+
+        let ctors = self.ctors(ty);
+        if let Some(ctors) = &ctors
+            && ctors.iter().all(|ctor| used.contains(&ctor))
+        {
+            return ctors.iter().find_map(|ctor| {
+                let arity = self.fields(ctor, ty).len();
+                let row: Vec<_> = vec![Pat::Wild; arity]
+                    .into_iter()
+                    .chain(rest.to_vec())
+                    .collect();
+                self.useful_ctor(rows, ctor, &row, types)
+            });
+        }
+
+        // a value built by a constructor none of the rows start with is only matched by
+        // the rows starting with a wildcard.
+        let wild: Vec<_> = rows
+            .iter()
+            .filter(|row| matches!(row[0], Pat::Wild))
+            .map(|row| row[1..].to_vec())
+            .collect();
+        let witness = self.useful(&wild, rest, &types[1..])?;
+
+        let missing = ctors.and_then(|ctors| ctors.into_iter().find(|ctor| !used.contains(&ctor)));
+        let head = match missing {
+            Some(ctor) => {
+                let arity = self.fields(&ctor, ty).len();
+                Pat::Ctor(ctor, vec![Pat::Wild; arity])
+            }
+            None => Pat::Wild,
+        };
+        Some([head].into_iter().chain(witness).collect())
+    }
+
+    /// Returns [Shapes::useful] for a `row` whose first column was built by the [Ctor]
+    /// and replaced by the fields of the value.
+    fn useful_ctor(
+        &self,
+        rows: &[Vec<Pat>],
+        ctor: &Ctor,
+        row: &[Pat],
+        types: &[Type],
+    ) -> Option<Vec<Pat>> {
+        let fields = self.fields(ctor, &types[0]);
+        let arity = fields.len();
+
+        let rows: Vec<_> = rows
+            .iter()
+            .filter_map(|row| {
+                let args = match &row[0] {
+                    Pat::Wild => vec![Pat::Wild; arity],
+                    Pat::Ctor(other, args) if other == ctor => args.clone(),
+                    Pat::Ctor(..) => return None,
+                };
+                Some(args.into_iter().chain(row[1..].to_vec()).collect())
+            })
+            .collect();
+        let types: Vec<_> = fields
+            .into_iter()
+            .map(|(_, ty)| ty)
+            .chain(types[1..].to_vec())
+            .collect();
+
+        let mut witness = self.useful(&rows, row, &types)?;
+        let rest = witness.split_off(arity);
+        Some(
+            [Pat::Ctor(ctor.clone(), witness)]
+                .into_iter()
+                .chain(rest)
+                .collect(),
+        )
+    }
+
+    /// Writes the [Pat] as it would be written in the source.
+    fn display(&self, pat: &Pat, out: &mut String) {
+        let list = |out: &mut String, args: &[Pat]| {
+            for (index, arg) in args.iter().enumerate() {
+                if index > 0 {
+                    out.push_str(", ");
+                }
+                self.display(arg, out);
+            }
+        };
+
+        let (ctor, args) = match pat {
+            Pat::Wild => return out.push('_'),
+            Pat::Ctor(ctor, args) => (ctor, args),
+        };
+
+        let _ = match ctor {
+            Ctor::Bool(value) => write!(out, "{value}"),
+            Ctor::Integer(value) => write!(out, "{value}"),
+            Ctor::Float(bits) => write!(out, "{:?}", f64::from_bits(*bits)),
+            Ctor::String(value) => write!(out, "{value:?}"),
+            Ctor::None => write!(out, "none"),
+            Ctor::Some => return self.display(&args[0], out),
+            Ctor::Opaque(_) => write!(out, "_"),
+            Ctor::Tuple(len) => {
+                out.push('(');
+                list(out, args);
+                out.push_str(if *len == 1 { ",)" } else { ")" });
+                Ok(())
+            }
+            Ctor::Adt(def_id) => {
+                let def = self.resolutions.def(*def_id);
+                if let Some(parent) = def.parent {
+                    let _ = write!(out, "{}::", self.resolutions.def(parent).name);
+                }
+                out.push_str(&def.name);
+
+                match self.variants.get(def_id) {
+                    Some(Payload::Unit) => {}
+                    Some(Payload::Tuple(_)) => {
+                        out.push('(');
+                        list(out, args);
+                        out.push(')');
+                    }
+                    Some(Payload::Struct(fields)) => self.display_fields(fields, args, out),
+                    None if def.kind == DefKind::Struct => {
+                        let fields = self.fields.get(def_id).map_or(&[][..], Vec::as_slice);
+                        self.display_fields(fields, args, out);
+                    }
+                    None => {}
+                }
+                Ok(())
+            }
+        };
+    }
+
+    /// Writes the named fields of a struct or variant, leaving out the wildcards.
+    fn display_fields(&self, fields: &[(String, Type)], args: &[Pat], out: &mut String) {
+        out.push_str(" {");
+        for ((name, _), arg) in fields.iter().zip(args) {
+            if !matches!(arg, Pat::Wild) {
+                let _ = write!(out, " {name}: ");
+                self.display(arg, out);
+                out.push(',');
+            }
+        }
+        out.push_str(" .. }");
+    }
+}
@@ -0,0 +1,265 @@
+use syntax::{
+    ast,
+    diagnostics::Diagnostic,
+    messages::message,
+    token::Span,
+    visit::{self, MutVisitor},
+};
+
+use crate::{
+    check::TypeTable,
+    consts::ConstValue,
+    resolve::{DefId, DefKind, Resolutions},
+    ty::Primitive,
+};
+
+/// An error found while evaluating a constant expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoldError {
+    pub kind: FoldErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FoldErrorKind {
+    /// An operation whose result doesn't fit its integer type.
+    Overflow { op: &'static str, ty: Primitive },
+    /// An integer division or remainder by zero.
+    DivisionByZero,
+}
+
+impl FoldErrorKind {
+    /// Returns the stable code of this kind of error, see [syntax::explain::explain].
+    pub fn code(&self) -> &'static str {
+        match self {
+            FoldErrorKind::Overflow { .. } => "E0024",
+            FoldErrorKind::DivisionByZero => "E0025",
+        }
+    }
+}
+
+impl FoldError {
+    /// Converts the error into a [Diagnostic].
+    pub fn diagnostic(&self) -> Diagnostic {
+        let code = self.kind.code();
+
+        let diagnostic = match &self.kind {
+            FoldErrorKind::Overflow { op, ty } => {
+                let args = [("op", op as &dyn std::fmt::Display), ("ty", &ty.name())];
+                Diagnostic::error(message(code, &args), self.span)
+                    .with_primary_label(message("E0024.label", &args))
+            }
+            FoldErrorKind::DivisionByZero => Diagnostic::error(message(code, &[]), self.span),
+        };
+
+        diagnostic.with_code(code)
+    }
+}
+
+/// Replaces arithmetic, comparisons and negations of constants in the checked
+/// [ast::Module] by their results, like `2 * 3 + x` by `6 + x`.
+///
+/// Integer operations are evaluated in the type the checker gave them, where a
+/// result that doesn't fit is reported instead of wrapping around. Folded booleans
+/// become references to the `true` and `false` built-ins, recorded in `resolutions`.
+pub fn fold_constants(
+    module: &mut ast::Module,
+    resolutions: &mut Resolutions,
+    types: &TypeTable,
+) -> Vec<FoldError> {
+    let builtin = |name: &str| {
+        resolutions
+            .defs()
+            .find(|(_, def)| def.kind == DefKind::Builtin && def.name == name)
+            .map(|(id, _)| id)
+    };
+
+    let mut folder = Folder {
+        booleans: builtin("false").zip(builtin("true")),
+        resolutions,
+        types,
+        errors: Vec::new(),
+    };
+    folder.visit_module_mut(module);
+
+    folder.errors
+}
+
+struct Folder<'a> {
+    /// The `false` and `true` built-ins.
+    booleans: Option<(DefId, DefId)>,
+    resolutions: &'a mut Resolutions,
+    types: &'a TypeTable,
+    errors: Vec<FoldError>,
+}
+
+impl MutVisitor for Folder<'_> {
+    fn visit_expr_mut(&mut self, exprs: &mut ast::ExprArena, id: ast::ExprId) {
+        visit::walk_expr_mut(self, exprs, id);
+
+        let Some(ty) = self.types.expr(id).primitive() else {
+            return;
+        };
+
+        let result = match &exprs[id] {
+            ast::Expr::Unary(unary) => match self.value(exprs, unary.operand) {
+                Some(operand) => ConstValue::unary(unary.op, &operand, ty),
+                None => return,
+            },
+            ast::Expr::Binary(binary) => {
+                let lhs = self.value(exprs, binary.lhs);
+                let rhs = self.value(exprs, binary.rhs);
+                match lhs.zip(rhs) {
+                    Some((lhs, rhs)) => ConstValue::binary(binary.op, &lhs, &rhs, ty),
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+
+        let span = exprs[id].span();
+        let value = match result {
+            Ok(value) => value,
+            Err(kind) => {
+                self.errors.push(FoldError { kind, span });
+                return;
+            }
+        };
+
+        match value {
+            Some(ConstValue::Int(value)) => {
+                exprs[id] = ast::Expr::Integer(ast::IntegerLiteral { value, span });
+            }
+            Some(ConstValue::Float(value)) => {
+                let value_bits = value.to_bits();
+                exprs[id] = ast::Expr::Float(ast::FloatLiteral { value_bits, span });
+            }
+            Some(ConstValue::Bool(value)) => {
+                let Some((false_def, true_def)) = self.booleans else {
+                    return;
+                };
+
+                let text = if value { "true" } else { "false" };
+                exprs[id] = ast::Expr::Identifier(ast::Identifier {
+                    text: text.to_string(),
+                    span,
+                });
+                let def = if value { true_def } else { false_def };
+                self.resolutions.record_expr(id, def);
+            }
+            Some(ConstValue::Str(_)) | None => {}
+        }
+    }
+}
+
+impl Folder<'_> {
+    /// Returns the value of the expression if it is a literal.
+    fn value(&self, exprs: &ast::ExprArena, id: ast::ExprId) -> Option<ConstValue> {
+        match &exprs[id] {
+            ast::Expr::Integer(integer) => Some(ConstValue::Int(integer.value)),
+            ast::Expr::Float(float) => Some(ConstValue::Float(f64::from_bits(float.value_bits))),
+            ast::Expr::Identifier(_) => {
+                let (false_def, true_def) = self.booleans?;
+                match self.resolutions.expr(id)? {
+                    def if def == true_def => Some(ConstValue::Bool(true)),
+                    def if def == false_def => Some(ConstValue::Bool(false)),
+                    _ => None,
+                }
+            }
+            ast::Expr::Paren(paren) => self.value(exprs, paren.expr),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use syntax::{ast, lexer::Lexer, parser::Parser};
+
+    use crate::{
+        check::check,
+        consts::eval_consts,
+        fold::{FoldError, FoldErrorKind, fold_constants},
+        resolve::{DefKind, resolve},
+        ty::Primitive,
+    };
+
+    #[test]
+    fn fold() {
+        let source = "
+            fn main(x: i64) {
+                let a = 2 * 3 + x;
+                let b: u8 = 200 + 100;
+                let c = (1 < 2) == !false;
+                let d = 7 % 0;
+                let e: i8 = -(60 * 2);
+                let f: f32 = 0.5 * 3.0;
+            }
+        ";
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let (mut module, _) = Parser::new(&tokens).parse_module();
+        let mut resolutions = resolve(&module).resolutions;
+        let consts = eval_consts(&module, &resolutions).values;
+        let types = check(&module, &resolutions, &consts).types;
+
+        let errors = fold_constants(&mut module, &mut resolutions, &types);
+        let span = |text: &str| {
+            let start = source.find(text).unwrap();
+            syntax::token::Span {
+                start,
+                end: start + text.len(),
+            }
+        };
+        assert_eq!(
+            errors,
+            [
+                FoldError {
+                    kind: FoldErrorKind::Overflow {
+                        op: "+",
+                        ty: Primitive::U8,
+                    },
+                    span: span("200 + 100"),
+                },
+                FoldError {
+                    kind: FoldErrorKind::DivisionByZero,
+                    span: span("7 % 0"),
+                },
+            ]
+        );
+
+        let ast::Item::Func(func) = &module.items[0] else {
+            unreachable!();
+        };
+        let values: Vec<_> = func
+            .body
+            .stmts
+            .iter()
+            .map(|stmt| match stmt {
+                ast::Stmt::Let(let_stmt) => &module.exprs[let_stmt.value.unwrap()],
+                _ => unreachable!(),
+            })
+            .collect();
+
+        let ast::Expr::Binary(a) = values[0] else {
+            panic!("expected `6 + x`, found {:?}", values[0]);
+        };
+        assert!(matches!(
+            module.exprs[a.lhs],
+            ast::Expr::Integer(ast::IntegerLiteral { value: 6, .. })
+        ));
+        assert!(matches!(values[1], ast::Expr::Binary(_)));
+        assert!(matches!(values[2], ast::Expr::Identifier(ident) if ident.text == "true"));
+        let ast::Stmt::Let(c) = &func.body.stmts[2] else {
+            unreachable!();
+        };
+        let def = resolutions.expr(c.value.unwrap()).unwrap();
+        assert_eq!(resolutions.def(def).kind, DefKind::Builtin);
+        assert!(matches!(
+            values[4],
+            ast::Expr::Integer(ast::IntegerLiteral { value: -120, .. })
+        ));
+        assert!(
+            matches!(values[5], ast::Expr::Float(float) if float.value_bits == 1.5f64.to_bits())
+        );
+    }
+}
@@ -0,0 +1,14 @@
+pub mod callgraph;
+pub mod check;
+pub mod coerce;
+pub mod consts;
+mod exhaustive;
+pub mod fold;
+pub mod lints;
+pub mod mono;
+pub mod program;
+pub mod query;
+pub mod resolve;
+pub mod ty;
+pub mod unreachable;
+pub mod unused;
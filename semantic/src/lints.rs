@@ -0,0 +1,467 @@
+use std::collections::HashSet;
+
+use syntax::{
+    ast,
+    diagnostics::{Applicability, Diagnostic, LintLevel, LintLevels},
+    messages::message,
+    token::Span,
+    visit::{self, Visitor},
+};
+
+use crate::{
+    check::TypeTable,
+    query::Query,
+    resolve::{DefKind, Resolutions},
+};
+
+/// A check of a resolved and type checked module for code that is likely a mistake,
+/// run by a [LintRegistry].
+pub trait Lint {
+    /// Returns the name used to allow or deny the lint, like `redundant_parens`.
+    fn name(&self) -> &'static str;
+
+    /// Returns the level of the lint when it isn't configured.
+    fn default_level(&self) -> LintLevel {
+        LintLevel::Warn
+    }
+
+    /// Checks the module, reporting what it finds with [LintContext::report].
+    fn check(&mut self, cx: &mut LintContext<'_>);
+}
+
+/// What a [Lint] knows about the module it checks.
+pub struct LintContext<'a> {
+    pub source: &'a str,
+    pub module: &'a ast::Module,
+    pub resolutions: &'a Resolutions,
+    pub types: &'a TypeTable,
+    lint: &'static str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl LintContext<'_> {
+    /// Reports a warning of the running lint, tagged with its name.
+    pub fn report(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic.with_lint(self.lint));
+    }
+
+    /// Returns a [Query] over the checked module.
+    pub fn query(&self) -> Query<'_> {
+        Query::new(self.module, self.resolutions, self.types)
+    }
+
+    /// Returns the source text covered by the [Span].
+    pub fn text(&self, span: Span) -> String {
+        self.source
+            .chars()
+            .skip(span.start)
+            .take(span.end.saturating_sub(span.start))
+            .collect()
+    }
+}
+
+/// The [Lint]s run on every module, which tools can extend with their own.
+#[derive(Default)]
+pub struct LintRegistry {
+    lints: Vec<Box<dyn Lint>>,
+}
+
+impl LintRegistry {
+    /// Returns a registry with the lints built into the compiler.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::default();
+        registry.register(Shadowing);
+        registry.register(RedundantParens);
+        registry.register(ConstantCondition);
+        registry
+    }
+
+    /// Registers a [Lint] to run after the ones registered before, replacing the
+    /// registered lint of the same name.
+    pub fn register(&mut self, lint: impl Lint + 'static) {
+        self.lints.retain(|other| other.name() != lint.name());
+        self.lints.push(Box::new(lint));
+    }
+
+    /// Returns the names of the registered lints, in the order they run.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> {
+        self.lints.iter().map(|lint| lint.name())
+    }
+
+    /// Records the default level of every registered lint in the [LintLevels].
+    pub fn set_defaults(&self, levels: &mut LintLevels) {
+        for lint in &self.lints {
+            levels.set_default(lint.name(), lint.default_level());
+        }
+    }
+
+    /// Runs every registered lint on the module, returning their warnings.
+    pub fn run(
+        &mut self,
+        source: &str,
+        module: &ast::Module,
+        resolutions: &Resolutions,
+        types: &TypeTable,
+    ) -> Vec<Diagnostic> {
+        let mut cx = LintContext {
+            source,
+            module,
+            resolutions,
+            types,
+            lint: "",
+            diagnostics: Vec::new(),
+        };
+
+        for lint in &mut self.lints {
+            cx.lint = lint.name();
+            lint.check(&mut cx);
+        }
+
+        cx.diagnostics
+    }
+}
+
+/// Warns about bindings hiding a parameter or an earlier binding of the same function.
+pub struct Shadowing;
+
+impl Lint for Shadowing {
+    fn name(&self) -> &'static str {
+        "shadowing"
+    }
+
+    fn check(&mut self, cx: &mut LintContext<'_>) {
+        let resolutions = cx.resolutions;
+
+        for (id, def) in resolutions.defs() {
+            let Some(previous) = resolutions.shadowed(id) else {
+                continue;
+            };
+
+            let name = &def.name;
+            let previous = resolutions.def(previous).span;
+            let diagnostic = Diagnostic::warning(message("W0004", &[("name", name)]), def.span)
+                .with_label(previous, message("W0004.label", &[("name", name)]))
+                .with_code("W0004");
+            cx.report(diagnostic);
+        }
+    }
+}
+
+/// Warns about parentheses that change nothing, like those around the condition of an
+/// `if` or around a single name.
+pub struct RedundantParens;
+
+impl Lint for RedundantParens {
+    fn name(&self) -> &'static str {
+        "redundant_parens"
+    }
+
+    fn check(&mut self, cx: &mut LintContext<'_>) {
+        let mut finder = ParenFinder {
+            exprs: &cx.module.exprs,
+            found: Vec::new(),
+        };
+        finder.visit_module(cx.module);
+        finder
+            .found
+            .sort_by_key(|&id| cx.module.exprs[id].span().start);
+
+        let mut seen = HashSet::new();
+        for paren in finder.found {
+            let ast::Expr::Paren(paren_expr) = &cx.module.exprs[paren] else {
+                continue;
+            };
+            if !seen.insert(paren) {
+                continue;
+            }
+
+            let inner = cx.text(cx.module.exprs[paren_expr.expr].span());
+            let diagnostic = Diagnostic::warning(message("W0010", &[]), paren_expr.span)
+                .with_code("W0010")
+                .with_suggestion(
+                    message("W0010.suggestion", &[]),
+                    paren_expr.span,
+                    inner,
+                    Applicability::MachineApplicable,
+                );
+            cx.report(diagnostic);
+        }
+    }
+}
+
+/// Collects the parenthesized expressions whose parentheses can be removed.
+struct ParenFinder<'ast> {
+    exprs: &'ast ast::ExprArena,
+    found: Vec<ast::ExprId>,
+}
+
+impl ParenFinder<'_> {
+    /// Records the expression if it is in parentheses, which the place it is used in
+    /// doesn't need.
+    fn unneeded(&mut self, id: ast::ExprId) {
+        if let ast::Expr::Paren(paren) = &self.exprs[id]
+            && !self.exprs[paren.expr].is_block_like()
+        {
+            self.found.push(id);
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for ParenFinder<'ast> {
+    fn exprs(&self) -> &'ast ast::ExprArena {
+        self.exprs
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast ast::Stmt) {
+        match stmt {
+            ast::Stmt::Let(ast::LetStmt { value, .. })
+            | ast::Stmt::Return(ast::ReturnStmt { value, .. })
+            | ast::Stmt::Break(ast::BreakStmt { value, .. }) => {
+                if let Some(value) = *value {
+                    self.unneeded(value);
+                }
+            }
+            ast::Stmt::Expr(expr_stmt) => self.unneeded(expr_stmt.expr),
+            ast::Stmt::For(for_stmt) => self.unneeded(for_stmt.iterable),
+            ast::Stmt::Const(_) | ast::Stmt::Continue(_) | ast::Stmt::Error(_) => {}
+        }
+
+        visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, id: ast::ExprId) {
+        match &self.exprs[id] {
+            ast::Expr::If(if_expr) => self.unneeded(if_expr.cond),
+            ast::Expr::While(while_expr) => self.unneeded(while_expr.cond),
+            ast::Expr::Match(match_expr) => {
+                self.unneeded(match_expr.scrutinee);
+                for arm in &match_expr.arms {
+                    self.unneeded(arm.value);
+                }
+            }
+            ast::Expr::Assign(assign) => self.unneeded(assign.value),
+            ast::Expr::Call(call) => {
+                for &arg in &call.args {
+                    self.unneeded(arg);
+                }
+            }
+            // names and calls bind tighter than any operator, unlike numbers in `(1).x`.
+            ast::Expr::Paren(paren) => match &self.exprs[paren.expr] {
+                ast::Expr::Identifier(_)
+                | ast::Expr::Path(_)
+                | ast::Expr::String(_)
+                | ast::Expr::None(_)
+                | ast::Expr::Paren(_)
+                | ast::Expr::Tuple(_)
+                | ast::Expr::Call(_) => self.found.push(id),
+                _ => {}
+            },
+            _ => {}
+        }
+
+        visit::walk_expr(self, id);
+    }
+
+    fn visit_block(&mut self, block: &'ast ast::Block) {
+        if let Some(tail) = block.tail {
+            self.unneeded(tail);
+        }
+        visit::walk_block(self, block);
+    }
+}
+
+/// Warns about `if` and `while` conditions that are always `true` or always `false`,
+/// except for `while false`, whose body is reported as unreachable.
+pub struct ConstantCondition;
+
+impl Lint for ConstantCondition {
+    fn name(&self) -> &'static str {
+        "constant_condition"
+    }
+
+    fn check(&mut self, cx: &mut LintContext<'_>) {
+        let mut finder = ConditionFinder {
+            exprs: &cx.module.exprs,
+            resolutions: cx.resolutions,
+            found: Vec::new(),
+        };
+        finder.visit_module(cx.module);
+
+        for (span, value, is_loop) in finder.found {
+            let diagnostic = Diagnostic::warning(message("W0011", &[("value", &value)]), span)
+                .with_code("W0011");
+
+            match (is_loop, value) {
+                (true, true) => cx.report(diagnostic.with_help(message("W0011.loop", &[]))),
+                (true, false) => {}
+                (false, _) => cx.report(diagnostic),
+            }
+        }
+    }
+}
+
+/// Collects the constant conditions, along with their value and whether they belong
+/// to a loop.
+struct ConditionFinder<'ast> {
+    exprs: &'ast ast::ExprArena,
+    resolutions: &'ast Resolutions,
+    found: Vec<(Span, bool, bool)>,
+}
+
+impl ConditionFinder<'_> {
+    /// Returns the value of a condition made of `true`, `false`, `!` and parentheses.
+    fn value(&self, id: ast::ExprId) -> Option<bool> {
+        match &self.exprs[id] {
+            ast::Expr::Paren(paren) => self.value(paren.expr),
+            ast::Expr::Unary(unary) if unary.op == ast::UnaryOp::Not => {
+                self.value(unary.operand).map(|value| !value)
+            }
+            ast::Expr::Identifier(_) => {
+                let def = self.resolutions.def(self.resolutions.expr(id)?);
+                (def.kind == DefKind::Builtin).then(|| def.name == "true")
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for ConditionFinder<'ast> {
+    fn exprs(&self) -> &'ast ast::ExprArena {
+        self.exprs
+    }
+
+    fn visit_expr(&mut self, id: ast::ExprId) {
+        let condition = match &self.exprs[id] {
+            ast::Expr::If(if_expr) => Some((if_expr.cond, false)),
+            ast::Expr::While(while_expr) => Some((while_expr.cond, true)),
+            _ => None,
+        };
+
+        if let Some((cond, is_loop)) = condition
+            && let Some(value) = self.value(cond)
+        {
+            self.found.push((self.exprs[cond].span(), value, is_loop));
+        }
+
+        visit::walk_expr(self, id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use syntax::{
+        diagnostics::{LintLevel, LintLevels},
+        explain::explain,
+        lexer::Lexer,
+        parser::Parser,
+    };
+
+    use crate::{
+        check::check,
+        consts::eval_consts,
+        lints::{Lint, LintContext, LintRegistry},
+        resolve::resolve,
+    };
+
+    /// Returns the lint, code and spanned text of each warning of the registry's lints.
+    fn lint(registry: &mut LintRegistry, source: &str) -> Vec<(String, String, String)> {
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let (module, errors) = Parser::new(&tokens).parse_module();
+        assert_eq!(errors, []);
+
+        let resolutions = resolve(&module).resolutions;
+        let consts = eval_consts(&module, &resolutions).values;
+        let types = check(&module, &resolutions, &consts).types;
+
+        registry
+            .run(source, &module, &resolutions, &types)
+            .into_iter()
+            .map(|diagnostic| {
+                let span = diagnostic.primary_span;
+                let text = source.chars().skip(span.start).take(span.end - span.start);
+                (
+                    diagnostic.lint.unwrap_or_default(),
+                    diagnostic.code.unwrap_or_default().to_string(),
+                    text.collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn builtins() {
+        let source = "
+            fn f(n: i64) -> i64 {
+                let n = (n);
+                if (n > 0) { return (f(n - 1)); }
+                while true { if !false { break; } }
+                while false {}
+                let x = (n + 1) * 2;
+                (x)
+            }
+        ";
+        let found = lint(&mut LintRegistry::with_builtins(), source);
+
+        let expected = [
+            ("shadowing", "W0004", "n"),
+            ("redundant_parens", "W0010", "(n)"),
+            ("redundant_parens", "W0010", "(n > 0)"),
+            ("redundant_parens", "W0010", "(f(n - 1))"),
+            ("redundant_parens", "W0010", "(x)"),
+            ("constant_condition", "W0011", "true"),
+            ("constant_condition", "W0011", "!false"),
+        ];
+        assert_eq!(
+            found,
+            expected.map(|(lint, code, text)| (lint.into(), code.into(), text.into()))
+        );
+
+        for (_, code, _) in &found {
+            assert!(explain(code).is_some());
+        }
+    }
+
+    struct NoPanics;
+
+    impl Lint for NoPanics {
+        fn name(&self) -> &'static str {
+            "no_panics"
+        }
+
+        fn default_level(&self) -> LintLevel {
+            LintLevel::Allow
+        }
+
+        fn check(&mut self, cx: &mut LintContext<'_>) {
+            let panics: Vec<_> = cx
+                .module
+                .exprs
+                .iter()
+                .filter(|(_, expr)| matches!(expr, syntax::ast::Expr::Panic(_)))
+                .map(|(_, expr)| expr.span())
+                .collect();
+
+            for span in panics {
+                cx.report(syntax::diagnostics::Diagnostic::warning("panics", span));
+            }
+        }
+    }
+
+    #[test]
+    fn registered() {
+        let mut registry = LintRegistry::default();
+        registry.register(NoPanics);
+        registry.register(NoPanics);
+        assert_eq!(registry.names().collect::<Vec<_>>(), ["no_panics"]);
+
+        let found = lint(&mut registry, "fn main() { panic(\"no\"); }");
+        assert_eq!(
+            found,
+            [("no_panics".into(), String::new(), "panic(\"no\")".into())]
+        );
+
+        let mut levels = LintLevels::default();
+        registry.set_defaults(&mut levels);
+        assert_eq!(levels.level("no_panics"), LintLevel::Allow);
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::{HashMap, HashSet};
+
+use syntax::ast;
+
+use crate::{
+    check::{Instance, TypeTable},
+    resolve::{DefId, Resolutions},
+    ty::Type,
+};
+
+/// A generic function specialized to the types of one [Instance], which code generation
+/// emits as a function of its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonoFunc {
+    pub instance: Instance,
+    /// The name of the specialized function, like `max_i64` for `max<i64>`.
+    pub name: String,
+    /// The types the generic parameters are replaced with in the body.
+    pub substitution: HashMap<DefId, Type>,
+}
+
+/// The specialized functions of a module, see [monomorphize].
+#[derive(Debug, Default)]
+pub struct Monomorphized {
+    funcs: Vec<MonoFunc>,
+    ids: HashMap<Instance, usize>,
+}
+
+impl Monomorphized {
+    /// Returns the specialized functions in the order their instances were found.
+    pub fn funcs(&self) -> &[MonoFunc] {
+        &self.funcs
+    }
+
+    /// Returns the specialized function called by a call of a generic function, where
+    /// the call is in the body of the specialized function `within`, or outside of any
+    /// generic function if that is [None].
+    pub fn callee(
+        &self,
+        types: &TypeTable,
+        call: ast::ExprId,
+        within: Option<&MonoFunc>,
+    ) -> Option<&MonoFunc> {
+        let instance = types.call_instance(call)?;
+        let args = match within {
+            Some(within) => instance
+                .args
+                .iter()
+                .map(|arg| arg.substitute(&within.substitution))
+                .collect(),
+            None => instance.args.clone(),
+        };
+
+        let instance = Instance {
+            func: instance.func,
+            args,
+        };
+        self.ids.get(&instance).map(|&id| &self.funcs[id])
+    }
+}
+
+/// Turns every [Instance] found by [crate::check::check] into a specialized function,
+/// so a generic `fn max<T>` used with `i64` and `f64` becomes `max_i64` and `max_f64`.
+pub fn monomorphize(resolutions: &Resolutions, types: &TypeTable) -> Monomorphized {
+    let mut taken: HashSet<_> = resolutions
+        .defs()
+        .map(|(_, def)| def.name.clone())
+        .collect();
+    let mut monomorphized = Monomorphized::default();
+
+    for instance in types.instances() {
+        let mut parts = vec![resolutions.def(instance.func).name.clone()];
+        parts.extend(instance.args.iter().map(|arg| mangle(arg, resolutions)));
+
+        let base = parts.join("_");
+        let mut name = base.clone();
+        for suffix in 1.. {
+            if taken.insert(name.clone()) {
+                break;
+            }
+            name = format!("{base}_{suffix}");
+        }
+
+        let generics = resolutions.generics(instance.func).iter().copied();
+        let substitution = generics.zip(instance.args.iter().cloned()).collect();
+
+        let id = monomorphized.funcs.len();
+        monomorphized.ids.insert(instance.clone(), id);
+        monomorphized.funcs.push(MonoFunc {
+            instance: instance.clone(),
+            name,
+            substitution,
+        });
+    }
+
+    monomorphized
+}
+
+/// Returns the type as part of an identifier, where compound types are prefixed with
+/// their number of parts so different types never give the same name.
+fn mangle(ty: &Type, resolutions: &Resolutions) -> String {
+    let all = |types: &[Type]| {
+        let parts: Vec<_> = types.iter().map(|ty| mangle(ty, resolutions)).collect();
+        format!("{}_{}", types.len(), parts.join("_"))
+    };
+
+    match ty {
+        Type::Primitive(primitive) => primitive.name().to_string(),
+        Type::Tuple(types) if types.is_empty() => "unit".to_string(),
+        Type::Tuple(types) => format!("tuple{}", all(types)),
+        Type::Ref(inner) => format!("ref_{}", mangle(inner, resolutions)),
+        Type::Ptr(inner) => format!("ptr_{}", mangle(inner, resolutions)),
+        Type::Optional(inner) => format!("opt_{}", mangle(inner, resolutions)),
+        Type::Range(inner) => format!("range_{}", mangle(inner, resolutions)),
+        Type::Array(inner, len) => format!("array{len}_{}", mangle(inner, resolutions)),
+        Type::Func(params, ret) => {
+            format!("fn{}_{}", all(params), mangle(ret, resolutions))
+        }
+        Type::Adt(def, args) if args.is_empty() => resolutions.def(*def).name.clone(),
+        Type::Adt(def, args) => format!("{}{}", resolutions.def(*def).name, all(args)),
+        Type::Generic(def) => resolutions.def(*def).name.clone(),
+        Type::Never => "never".to_string(),
+        Type::Error => "error".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use syntax::{lexer::Lexer, parser::Parser};
+
+    use crate::{check::check, consts::eval_consts, mono::monomorphize, resolve::resolve};
+
+    #[test]
+    fn names() {
+        let source = "
+            fn max<T>(a: T, b: T) -> T { if a > b { a } else { b } }
+            fn biggest<U>(a: U, b: U, c: U) -> U { max(max(a, b), c) }
+            fn max_f64() {}
+            fn main(pair: (i64, bool)) {
+                let _a = max(1, 2);
+                let _b = biggest(1.0, 2.0, 3.0);
+                let _c = max(pair, pair);
+            }
+        ";
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let (module, _) = Parser::new(&tokens).parse_module();
+        let resolutions = resolve(&module).resolutions;
+        let consts = eval_consts(&module, &resolutions).values;
+        let checked = check(&module, &resolutions, &consts);
+
+        let monomorphized = monomorphize(&resolutions, &checked.types);
+        let names: Vec<_> = monomorphized
+            .funcs()
+            .iter()
+            .map(|func| func.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            ["max_i64", "biggest_f64", "max_tuple2_i64_bool", "max_f64_1"]
+        );
+
+        // the calls in `biggest` call `max<f64>` in its `f64` instance.
+        let (call, _) = module
+            .exprs
+            .iter()
+            .filter(|(_, expr)| expr.span().start == source.find("max(max").unwrap())
+            .max_by_key(|(_, expr)| expr.span().end)
+            .unwrap();
+        let biggest = &monomorphized.funcs()[1];
+        let callee = monomorphized.callee(&checked.types, call, Some(biggest));
+        assert_eq!(callee.map(|func| func.name.as_str()), Some("max_f64_1"));
+        assert_eq!(monomorphized.callee(&checked.types, call, None), None);
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::{HashMap, HashSet};
+
+use syntax::{ast, diagnostics::Diagnostic, messages::message, token::Span};
+
+use crate::{
+    callgraph::CallGraph,
+    check::TypeTable,
+    resolve::{DefId, DefKind, Resolutions},
+    ty::{Primitive, Type},
+};
+
+/// An error in the entry point of a program, the `main` function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryError {
+    pub kind: EntryErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryErrorKind {
+    /// The module declares no item named `main`.
+    Missing,
+    /// An item named `main` that isn't a function.
+    NotAFunction(DefKind),
+    /// A `main` function with generic parameters.
+    Generic,
+    /// A `main` function with parameters.
+    Params,
+    /// A `main` function returning something other than `()` or an `i32` exit code.
+    ReturnType(Type),
+}
+
+impl EntryErrorKind {
+    /// Returns the stable code of this kind of error, see [syntax::explain::explain].
+    pub fn code(&self) -> &'static str {
+        match self {
+            EntryErrorKind::Missing | EntryErrorKind::NotAFunction(_) => "E0036",
+            EntryErrorKind::Generic | EntryErrorKind::Params | EntryErrorKind::ReturnType(_) => {
+                "E0037"
+            }
+        }
+    }
+}
+
+impl EntryError {
+    /// Converts the error into a [Diagnostic], naming types with the given [Resolutions].
+    pub fn diagnostic(&self, resolutions: &Resolutions) -> Diagnostic {
+        let code = self.kind.code();
+
+        let diagnostic = match &self.kind {
+            EntryErrorKind::Missing => Diagnostic::error(message(code, &[]), self.span)
+                .with_help(message("E0036.help", &[])),
+            EntryErrorKind::NotAFunction(kind) => Diagnostic::error(message(code, &[]), self.span)
+                .with_primary_label(message("E0036.kind", &[("kind", &kind.description())])),
+            EntryErrorKind::Generic => Diagnostic::error(message(code, &[]), self.span)
+                .with_primary_label(message("E0037.generic", &[])),
+            EntryErrorKind::Params => Diagnostic::error(message(code, &[]), self.span)
+                .with_primary_label(message("E0037.params", &[])),
+            EntryErrorKind::ReturnType(found) => {
+                let found = found.display(resolutions).to_string();
+                Diagnostic::error(message(code, &[]), self.span)
+                    .with_primary_label(message("E0037.return", &[("found", &found)]))
+            }
+        };
+
+        diagnostic.with_code(code)
+    }
+}
+
+/// A checked module with a valid entry point, the form the backends run and compile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    entry: DefId,
+    /// The items of the module in the order they are declared.
+    items: Vec<DefId>,
+    names: HashMap<String, DefId>,
+    /// The functions of the module, each after the functions it calls.
+    order: Vec<DefId>,
+}
+
+impl Program {
+    /// Returns the `main` function the program starts at.
+    pub fn entry(&self) -> DefId {
+        self.entry
+    }
+
+    /// Returns whether `main` returns an `i32` the program exits with.
+    pub fn returns_code(&self, types: &TypeTable) -> bool {
+        matches!(
+            types.def(self.entry),
+            Some(Type::Func(_, ret)) if **ret == Type::Primitive(Primitive::I32)
+        )
+    }
+
+    /// Returns the functions, constants and types of the module in the order they are
+    /// declared.
+    pub fn items(&self) -> &[DefId] {
+        &self.items
+    }
+
+    /// Returns the item of the module with the given name.
+    pub fn item(&self, name: &str) -> Option<DefId> {
+        self.names.get(name).copied()
+    }
+
+    /// Returns the functions of the module ordered so that each comes after the
+    /// functions it calls, except for functions calling each other. The functions
+    /// reachable from the entry point come first.
+    pub fn order(&self) -> &[DefId] {
+        &self.order
+    }
+}
+
+/// Validates the entry point of the given checked [ast::Module], which must be a
+/// single function `fn main()` or `fn main() -> i32` without generic parameters, and
+/// builds the [Program] from it.
+///
+/// Duplicate definitions of `main` are already reported while resolving, so only the
+/// first one is validated.
+pub fn build_program(
+    module: &ast::Module,
+    resolutions: &Resolutions,
+    types: &TypeTable,
+    call_graph: &CallGraph,
+) -> Result<Program, Vec<EntryError>> {
+    let items: Vec<DefId> = module
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ast::Item::Func(func) => resolutions.decl(func.id),
+            ast::Item::Const(const_decl) => resolutions.decl(const_decl.id),
+            ast::Item::Struct(struct_decl) => resolutions.decl(struct_decl.id),
+            ast::Item::Enum(enum_decl) => resolutions.decl(enum_decl.id),
+            ast::Item::Trait(trait_decl) => resolutions.decl(trait_decl.id),
+            ast::Item::Import(_) | ast::Item::Error(_) => None,
+        })
+        .collect();
+
+    let mut names = HashMap::new();
+    for &item in &items {
+        names
+            .entry(resolutions.def(item).name.clone())
+            .or_insert(item);
+    }
+
+    let Some(&entry) = names.get("main") else {
+        return Err(vec![EntryError {
+            kind: EntryErrorKind::Missing,
+            span: Span::EOI,
+        }]);
+    };
+
+    let errors = validate_entry(module, resolutions, types, entry);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut order = Vec::new();
+    let mut visited = HashSet::new();
+    for &func in [entry].iter().chain(call_graph.functions()) {
+        postorder(call_graph, func, &mut visited, &mut order);
+    }
+
+    Ok(Program {
+        entry,
+        items,
+        names,
+        order,
+    })
+}
+
+/// Returns the errors in the signature of the `main` item.
+fn validate_entry(
+    module: &ast::Module,
+    resolutions: &Resolutions,
+    types: &TypeTable,
+    entry: DefId,
+) -> Vec<EntryError> {
+    let def = resolutions.def(entry);
+    let func = module.items.iter().find_map(|item| match item {
+        ast::Item::Func(func) if resolutions.decl(func.id) == Some(entry) => Some(func),
+        _ => None,
+    });
+    let Some(func) = func else {
+        let kind = EntryErrorKind::NotAFunction(def.kind);
+        return vec![EntryError {
+            kind,
+            span: def.span,
+        }];
+    };
+
+    let mut errors = Vec::new();
+    let mut error = |kind, span| errors.push(EntryError { kind, span });
+
+    if let (Some(first), Some(last)) = (func.generics.first(), func.generics.last()) {
+        error(EntryErrorKind::Generic, first.span.to(last.span));
+    }
+    if let (Some(first), Some(last)) = (func.params.first(), func.params.last()) {
+        error(EntryErrorKind::Params, first.span.to(last.span));
+    }
+    if let (Some(ty), Some(Type::Func(_, ret))) = (&func.return_type, types.def(entry))
+        && !matches!(
+            **ret,
+            Type::Error | Type::Primitive(Primitive::Unit | Primitive::I32)
+        )
+    {
+        error(EntryErrorKind::ReturnType((**ret).clone()), ty.span());
+    }
+
+    errors
+}
+
+/// Appends the functions reachable from `func` that aren't `visited` yet to `order`,
+/// each after the functions it calls.
+fn postorder(graph: &CallGraph, func: DefId, visited: &mut HashSet<DefId>, order: &mut Vec<DefId>) {
+    if !visited.insert(func) {
+        return;
+    }
+
+    for call in graph.calls(func) {
+        postorder(graph, call.callee, visited, order);
+    }
+    order.push(func);
+}
+
+#[cfg(test)]
+mod test {
+    use syntax::{lexer::Lexer, parser::Parser};
+
+    use crate::{
+        callgraph::CallGraph,
+        check::check,
+        consts::eval_consts,
+        program::{EntryError, EntryErrorKind, Program, build_program},
+        resolve::{DefKind, Resolutions, resolve},
+        ty::{Primitive, Type},
+    };
+
+    fn build(source: &str) -> (Resolutions, Result<Program, Vec<EntryError>>) {
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let (module, errors) = Parser::new(&tokens).parse_module();
+        assert_eq!(errors, []);
+
+        let resolutions = resolve(&module).resolutions;
+        let consts = eval_consts(&module, &resolutions).values;
+        let types = check(&module, &resolutions, &consts).types;
+        let call_graph = CallGraph::build(&module, &resolutions);
+        let program = build_program(&module, &resolutions, &types, &call_graph);
+        (resolutions, program)
+    }
+
+    #[test]
+    fn programs() {
+        let source = "
+            fn unused() -> i64 { twice(1) }
+            struct Point { x: i64 }
+            fn main() -> i32 { let p: Point? = none; even(twice(2)); 0 }
+            fn twice(x: i64) -> i64 { x * 2 }
+            fn even(x: i64) -> bool { if x == 0 { true } else { odd(x - 1) } }
+            fn odd(x: i64) -> bool { if x == 0 { false } else { even(x - 1) } }
+        ";
+        let (resolutions, program) = build(source);
+        let program = program.unwrap();
+
+        let names = |defs: &[_]| -> Vec<_> {
+            defs.iter()
+                .map(|&def| resolutions.def(def).name.as_str())
+                .collect()
+        };
+        assert_eq!(resolutions.def(program.entry()).name, "main");
+        assert_eq!(
+            names(program.items()),
+            ["unused", "Point", "main", "twice", "even", "odd"]
+        );
+        assert_eq!(
+            program.item("Point").map(|def| resolutions.def(def).kind),
+            Some(DefKind::Struct)
+        );
+        assert_eq!(
+            names(program.order()),
+            ["odd", "even", "twice", "main", "unused"]
+        );
+    }
+
+    #[test]
+    fn entry_errors() {
+        let kinds = |source: &str| -> Vec<_> {
+            let (_, program) = build(source);
+            let errors = program.unwrap_err();
+            errors
+                .into_iter()
+                .map(|err| {
+                    let text = source.get(err.span.start..err.span.end).unwrap_or("");
+                    (err.kind, text.to_string())
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            kinds("fn start() {}"),
+            [(EntryErrorKind::Missing, String::new())]
+        );
+        assert_eq!(
+            kinds("const main: i64 = 1;"),
+            [(EntryErrorKind::NotAFunction(DefKind::Const), "main".into())]
+        );
+        assert_eq!(
+            kinds("fn main<T>(x: T, y: i64) -> str { \"\" }"),
+            [
+                (EntryErrorKind::Generic, "T".into()),
+                (EntryErrorKind::Params, "x: T, y: i64".into()),
+                (
+                    EntryErrorKind::ReturnType(Type::Primitive(Primitive::Str)),
+                    "str".into()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use std::{cell::OnceCell, collections::HashMap};
+
+use syntax::{
+    ast,
+    token::Span,
+    visit::{self, Visitor},
+};
+
+use crate::{
+    check::TypeTable,
+    resolve::{DefId, Resolutions},
+    ty::Type,
+};
+
+/// A node of the syntax tree a [Query] can be asked about, either an expression or a
+/// node with a [ast::NodeId] like a declaration, a named type or a pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Node {
+    Expr(ast::ExprId),
+    Id(ast::NodeId),
+}
+
+impl From<ast::ExprId> for Node {
+    fn from(id: ast::ExprId) -> Self {
+        Node::Expr(id)
+    }
+}
+
+impl From<ast::NodeId> for Node {
+    fn from(id: ast::NodeId) -> Self {
+        Node::Id(id)
+    }
+}
+
+/// A use of a definition by a name in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reference {
+    pub node: Node,
+    /// The [Span] of the name, or of the whole path for paths like `Shape::Circle`.
+    pub span: Span,
+}
+
+/// Answers questions about a resolved and type checked module from the results of the
+/// passes, so tools don't have to run them again.
+pub struct Query<'a> {
+    module: &'a ast::Module,
+    resolutions: &'a Resolutions,
+    types: &'a TypeTable,
+    /// The uses of each definition, collected on the first [Query::references_to].
+    references: OnceCell<HashMap<DefId, Vec<Reference>>>,
+}
+
+impl<'a> Query<'a> {
+    pub fn new(
+        module: &'a ast::Module,
+        resolutions: &'a Resolutions,
+        types: &'a TypeTable,
+    ) -> Self {
+        Self {
+            module,
+            resolutions,
+            types,
+            references: OnceCell::new(),
+        }
+    }
+
+    /// Returns the [Type] of an expression or of the definition a node declares, like a
+    /// binding or a function, unless checking it failed.
+    pub fn type_of(&self, node: impl Into<Node>) -> Option<&'a Type> {
+        let ty = match node.into() {
+            Node::Expr(id) => self.types.expr(id),
+            Node::Id(id) => self.types.def(self.resolutions.decl(id)?)?,
+        };
+
+        (*ty != Type::Error).then_some(ty)
+    }
+
+    /// Returns the definition a name, path or named type refers to, or the one a
+    /// declaring node introduces.
+    pub fn definition_of(&self, node: impl Into<Node>) -> Option<DefId> {
+        match node.into() {
+            Node::Expr(id) => self.resolutions.expr(id),
+            Node::Id(id) => self
+                .resolutions
+                .node(id)
+                .or_else(|| self.resolutions.decl(id)),
+        }
+    }
+
+    /// Returns the uses of a definition in source order, leaving out its declaration.
+    pub fn references_to(&self, def: DefId) -> &[Reference] {
+        let references = self.references.get_or_init(|| {
+            let mut collector = ReferenceCollector {
+                exprs: &self.module.exprs,
+                resolutions: self.resolutions,
+                references: HashMap::new(),
+            };
+            collector.visit_module(self.module);
+
+            let mut references = collector.references;
+            for uses in references.values_mut() {
+                uses.sort_by_key(|reference| reference.span.start);
+            }
+            references
+        });
+
+        references.get(&def).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Collects the [Reference]s to every definition.
+struct ReferenceCollector<'ast> {
+    exprs: &'ast ast::ExprArena,
+    resolutions: &'ast Resolutions,
+    references: HashMap<DefId, Vec<Reference>>,
+}
+
+impl ReferenceCollector<'_> {
+    fn add(&mut self, def: Option<DefId>, node: Node, span: Span) {
+        if let Some(def) = def {
+            self.references
+                .entry(def)
+                .or_default()
+                .push(Reference { node, span });
+        }
+    }
+
+    /// Records the definition a node with a path refers to, spanning the whole path.
+    fn add_path(&mut self, id: ast::NodeId, path: &[ast::Identifier]) {
+        if let (Some(first), Some(last)) = (path.first(), path.last()) {
+            let def = self.resolutions.node(id);
+            self.add(def, Node::Id(id), first.span.to(last.span));
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for ReferenceCollector<'ast> {
+    fn exprs(&self) -> &'ast ast::ExprArena {
+        self.exprs
+    }
+
+    fn visit_expr(&mut self, id: ast::ExprId) {
+        let span = self.exprs[id].span();
+        self.add(self.resolutions.expr(id), Node::Expr(id), span);
+
+        visit::walk_expr(self, id);
+    }
+
+    fn visit_type(&mut self, ty: &'ast ast::TypeExpr) {
+        if let ast::TypeExpr::Named(named) = ty {
+            let def = self.resolutions.node(named.id);
+            self.add(def, Node::Id(named.id), named.name.span);
+        }
+
+        visit::walk_type(self, ty);
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast ast::Pattern) {
+        match pattern {
+            // `true` and `false` in patterns refer to the built-in values.
+            ast::Pattern::Binding(binding) => {
+                let def = self.resolutions.node(binding.id);
+                self.add(def, Node::Id(binding.id), binding.name.span);
+            }
+            ast::Pattern::Path(path) => self.add_path(path.id, &path.segments),
+            ast::Pattern::TupleStruct(tuple_struct) => {
+                self.add_path(tuple_struct.id, &tuple_struct.path);
+            }
+            ast::Pattern::Struct(struct_pattern) => {
+                self.add_path(struct_pattern.id, &struct_pattern.path);
+            }
+            _ => {}
+        }
+
+        visit::walk_pattern(self, pattern);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use syntax::{ast, lexer::Lexer, parser::Parser};
+
+    use crate::{check::check, consts::eval_consts, query::Query, resolve::resolve};
+
+    #[test]
+    fn queries() {
+        let source = "
+            struct Point { x: i64, y: i64 }
+            fn norm(p: Point) -> i64 { p.x * p.x + p.y * p.y }
+            fn main() {
+                let origin: Point = origin();
+                let n = norm(origin);
+                match n > 0 { true => norm(origin), _ => n };
+            }
+            fn origin() -> Point { origin() }
+        ";
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let (module, errors) = Parser::new(&tokens).parse_module();
+        assert_eq!(errors, []);
+
+        let resolutions = resolve(&module).resolutions;
+        let consts = eval_consts(&module, &resolutions).values;
+        let types = check(&module, &resolutions, &consts).types;
+        let query = Query::new(&module, &resolutions, &types);
+
+        let ast::Item::Func(norm) = &module.items[1] else {
+            panic!("expected a function");
+        };
+        let param = norm.params[0].id;
+        let param_def = query.definition_of(param).unwrap();
+        assert_eq!(resolutions.def(param_def).name, "p");
+        assert_eq!(
+            query
+                .type_of(param)
+                .unwrap()
+                .display(&resolutions)
+                .to_string(),
+            "Point"
+        );
+
+        let texts = |def| -> Vec<_> {
+            query
+                .references_to(def)
+                .iter()
+                .map(|reference| &source[reference.span.start..reference.span.end])
+                .collect()
+        };
+        assert_eq!(texts(param_def), ["p", "p", "p", "p"]);
+
+        // every use of a definition leads back to it.
+        let norm_def = query.definition_of(norm.id).unwrap();
+        let uses = query.references_to(norm_def);
+        assert_eq!(uses.len(), 2);
+        for reference in uses {
+            assert_eq!(query.definition_of(reference.node), Some(norm_def));
+        }
+
+        let point = resolutions
+            .defs()
+            .find(|(_, def)| def.name == "Point")
+            .unwrap()
+            .0;
+        assert_eq!(texts(point), ["Point", "Point", "Point"]);
+
+        let truth = query.references_to(
+            resolutions
+                .defs()
+                .find(|(_, def)| def.name == "true")
+                .unwrap()
+                .0,
+        );
+        assert_eq!(truth.len(), 1);
+        assert_eq!(query.type_of(truth[0].node), None);
+    }
+}
@@ -0,0 +1,1203 @@
+use std::collections::{HashMap, HashSet};
+
+use syntax::{
+    ast,
+    diagnostics::Diagnostic,
+    messages::message,
+    source::FileId,
+    token::Span,
+    visit::{self, Visitor},
+};
+
+use crate::{
+    check::TypeTable,
+    ty::{Primitive, Type},
+};
+
+/// The names of the built-in values, which are in scope everywhere.
+const BUILTINS: &[&str] = &["true", "false"];
+
+/// Identifies a definition found by [resolve], like a function, a type or a local binding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DefId(pub u32);
+
+impl DefId {
+    /// Returns the id as an index, e.g. into a side table of per-definition information.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DefKind {
+    /// A built-in type like `i64`.
+    Primitive,
+    /// A built-in value like `true`.
+    Builtin,
+    /// A module made available by an `import`.
+    Module,
+    Func,
+    Const,
+    Struct,
+    Enum,
+    Variant,
+    Trait,
+    /// A generic type parameter like `T` in `fn max<T>`.
+    Generic,
+    Param,
+    /// A binding introduced by a `let`, a `for` loop or a match arm.
+    Local,
+}
+
+impl DefKind {
+    /// Returns whether definitions of this kind can be used as types.
+    pub fn is_type(self) -> bool {
+        matches!(
+            self,
+            DefKind::Primitive
+                | DefKind::Struct
+                | DefKind::Enum
+                | DefKind::Trait
+                | DefKind::Generic
+        )
+    }
+
+    /// Returns how diagnostics refer to definitions of this kind.
+    pub fn description(self) -> &'static str {
+        match self {
+            DefKind::Primitive => "built-in type",
+            DefKind::Builtin => "built-in value",
+            DefKind::Module => "module",
+            DefKind::Func => "function",
+            DefKind::Const => "constant",
+            DefKind::Struct => "struct",
+            DefKind::Enum => "enum",
+            DefKind::Variant => "variant",
+            DefKind::Trait => "trait",
+            DefKind::Generic => "type parameter",
+            DefKind::Param => "parameter",
+            DefKind::Local => "local variable",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Def {
+    pub name: String,
+    pub kind: DefKind,
+    /// The [Span] of the defining name, which is [Span::EOI] for built-ins.
+    pub span: Span,
+    /// The enum a variant belongs to.
+    pub parent: Option<DefId>,
+}
+
+/// The side table recording what each name in a [ast::Module] refers to.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Resolutions {
+    defs: Vec<Def>,
+    /// The definitions of identifier and path expressions.
+    exprs: HashMap<ast::ExprId, DefId>,
+    /// The definitions of named types and path patterns.
+    nodes: HashMap<ast::NodeId, DefId>,
+    /// The definitions introduced by declaring nodes like functions and bindings.
+    decls: HashMap<ast::NodeId, DefId>,
+    /// The files declaring the items of imported modules.
+    files: HashMap<DefId, FileId>,
+    /// The types of the imported functions whose signatures are known.
+    signatures: HashMap<DefId, Type>,
+    /// The `pub` items of the module.
+    public: HashSet<DefId>,
+    /// The generic type parameters of each generic item, in order.
+    generics: HashMap<DefId, Vec<DefId>>,
+    /// The local bindings that may be assigned to.
+    assignable: HashSet<DefId>,
+    /// The parameter or earlier binding of the same function each binding hides.
+    shadowed: HashMap<DefId, DefId>,
+}
+
+impl Resolutions {
+    /// Returns the [Def] with the given [DefId].
+    pub fn def(&self, id: DefId) -> &Def {
+        &self.defs[id.index()]
+    }
+
+    /// Returns all [Def]s, including the built-ins.
+    pub fn defs(&self) -> impl Iterator<Item = (DefId, &Def)> {
+        (0..).map(DefId).zip(&self.defs)
+    }
+
+    /// Returns the definition an identifier or path expression refers to.
+    pub fn expr(&self, id: ast::ExprId) -> Option<DefId> {
+        self.exprs.get(&id).copied()
+    }
+
+    /// Records that the expression refers to the given definition, for passes that
+    /// rewrite resolved expressions.
+    pub(crate) fn record_expr(&mut self, id: ast::ExprId, def: DefId) {
+        self.exprs.insert(id, def);
+    }
+
+    /// Returns the definition a named type or a path pattern refers to, or the one
+    /// an array type names as its length.
+    pub fn node(&self, id: ast::NodeId) -> Option<DefId> {
+        self.nodes.get(&id).copied()
+    }
+
+    /// Returns the definition introduced by a declaring node, like a [ast::FuncDecl]
+    /// or an [ast::BindingPattern].
+    pub fn decl(&self, id: ast::NodeId) -> Option<DefId> {
+        self.decls.get(&id).copied()
+    }
+
+    /// Returns the file declaring an item of an imported module, or [None] if the
+    /// definition belongs to the resolved module.
+    pub fn file(&self, id: DefId) -> Option<FileId> {
+        self.files.get(&id).copied()
+    }
+
+    /// Returns the type of a function of an imported module, if its signature only uses
+    /// types every module knows, see [Exports::with_signatures].
+    pub fn signature(&self, id: DefId) -> Option<&Type> {
+        self.signatures.get(&id)
+    }
+
+    /// Returns whether the definition is an item of the resolved module marked `pub`.
+    pub fn is_public(&self, id: DefId) -> bool {
+        self.public.contains(&id)
+    }
+
+    /// Returns whether the local binding may be assigned to, because it is declared
+    /// with `var` or `let mut`, or by a `let` without a value the assignment provides.
+    pub fn is_assignable(&self, id: DefId) -> bool {
+        self.assignable.contains(&id)
+    }
+
+    /// Returns the parameter or binding of the same function hidden by a local binding
+    /// of the same name, like an outer loop variable by an inner one.
+    pub fn shadowed(&self, id: DefId) -> Option<DefId> {
+        self.shadowed.get(&id).copied()
+    }
+
+    /// Returns the generic type parameters of a function, struct or trait, like `T` in
+    /// `fn max<T>`, which is empty for other definitions.
+    pub fn generics(&self, id: DefId) -> &[DefId] {
+        self.generics.get(&id).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// An item of a module as seen by the modules importing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Export {
+    pub name: String,
+    pub kind: DefKind,
+    pub visibility: ast::Visibility,
+    /// The [Span] of the name in the declaring file.
+    pub span: Span,
+    /// The variants of an enum.
+    pub members: Vec<Export>,
+    /// The type of a function, if it is known to the importing modules.
+    pub signature: Option<Type>,
+}
+
+/// The items of a module, passed to [resolve_imports] for the modules importing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exports {
+    /// The file declaring the items.
+    pub file: FileId,
+    pub items: Vec<Export>,
+}
+
+impl Exports {
+    /// Collects the items of the given [ast::Module], including the private ones so
+    /// that uses of them can be reported as such.
+    pub fn collect(module: &ast::Module, file: FileId) -> Self {
+        let export = |name: &ast::Identifier, kind, visibility| Export {
+            name: name.text.clone(),
+            kind,
+            visibility,
+            span: name.span,
+            members: Vec::new(),
+            signature: None,
+        };
+
+        let items = module
+            .items
+            .iter()
+            .filter_map(|item| {
+                let visibility = item.visibility();
+                Some(match item {
+                    ast::Item::Func(func) => export(&func.name, DefKind::Func, visibility),
+                    ast::Item::Const(const_decl) => {
+                        export(&const_decl.name, DefKind::Const, visibility)
+                    }
+                    ast::Item::Struct(struct_decl) => {
+                        export(&struct_decl.name, DefKind::Struct, visibility)
+                    }
+                    ast::Item::Enum(enum_decl) => Export {
+                        members: enum_decl
+                            .variants
+                            .iter()
+                            .map(|variant| export(&variant.name, DefKind::Variant, visibility))
+                            .collect(),
+                        ..export(&enum_decl.name, DefKind::Enum, visibility)
+                    },
+                    ast::Item::Trait(trait_decl) => {
+                        export(&trait_decl.name, DefKind::Trait, visibility)
+                    }
+                    ast::Item::Import(_) | ast::Item::Error(_) => return None,
+                })
+            })
+            .collect();
+
+        Self { file, items }
+    }
+
+    /// Records the types of the exported functions from the checked `module`, so calls
+    /// of them are checked and compiled by the importing modules.
+    ///
+    /// Only signatures made of primitives and the types built from them are recorded,
+    /// since the structs, enums and generic parameters of a module are unknown to others.
+    pub fn with_signatures(
+        mut self,
+        module: &ast::Module,
+        resolutions: &Resolutions,
+        types: &TypeTable,
+    ) -> Self {
+        for item in &module.items {
+            let ast::Item::Func(func) = item else {
+                continue;
+            };
+            let Some(def) = resolutions.decl(func.id) else {
+                continue;
+            };
+            if !resolutions.generics(def).is_empty() {
+                continue;
+            }
+            let Some(ty) = types.def(def).filter(|ty| is_shared(ty)) else {
+                continue;
+            };
+
+            let export = self
+                .items
+                .iter_mut()
+                .find(|export| export.name == func.name.text && export.kind == DefKind::Func);
+            if let Some(export) = export {
+                export.signature = Some(ty.clone());
+            }
+        }
+
+        self
+    }
+}
+
+/// Returns whether every module knows the type, because it doesn't mention any item.
+fn is_shared(ty: &Type) -> bool {
+    match ty {
+        Type::Primitive(_) | Type::Never => true,
+        Type::Tuple(types) => types.iter().all(is_shared),
+        Type::Ref(inner)
+        | Type::Ptr(inner)
+        | Type::Array(inner, _)
+        | Type::Optional(inner)
+        | Type::Range(inner) => is_shared(inner),
+        Type::Func(params, ret) => params.iter().all(is_shared) && is_shared(ret),
+        Type::Adt(..) | Type::Generic(_) | Type::Error => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolveError {
+    pub kind: ResolveErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveErrorKind {
+    /// A name that isn't defined in any enclosing scope.
+    UnknownIdentifier(String),
+    /// A name defined twice in the same scope, where `previous` is the first definition.
+    DuplicateDefinition { name: String, previous: Span },
+    /// A path segment like `Square` in `Shape::Square` naming no member of its parent.
+    UnknownMember { parent: String, name: String },
+    /// A name used as a type that refers to another kind of definition.
+    NotAType { name: String, kind: DefKind },
+    /// An item of an imported module that isn't marked `pub`, declared at `declared`
+    /// in `file`.
+    Private {
+        name: String,
+        kind: DefKind,
+        file: FileId,
+        declared: Span,
+    },
+}
+
+impl ResolveErrorKind {
+    /// Returns the stable code of this kind of error, see [syntax::explain::explain].
+    pub fn code(&self) -> &'static str {
+        match self {
+            ResolveErrorKind::UnknownIdentifier(_) => "E0011",
+            ResolveErrorKind::DuplicateDefinition { .. } => "E0012",
+            ResolveErrorKind::UnknownMember { .. } => "E0013",
+            ResolveErrorKind::NotAType { .. } => "E0014",
+            ResolveErrorKind::Private { .. } => "E0029",
+        }
+    }
+}
+
+impl ResolveError {
+    /// Converts the error into a [Diagnostic].
+    pub fn diagnostic(&self) -> Diagnostic {
+        let code = self.kind.code();
+
+        let diagnostic = match &self.kind {
+            ResolveErrorKind::UnknownIdentifier(name) => {
+                Diagnostic::error(message(code, &[("name", name)]), self.span)
+            }
+            ResolveErrorKind::DuplicateDefinition { name, previous } => {
+                Diagnostic::error(message(code, &[("name", name)]), self.span)
+                    .with_label(*previous, message("E0012.label", &[("name", name)]))
+            }
+            ResolveErrorKind::UnknownMember { parent, name } => Diagnostic::error(
+                message(code, &[("name", name), ("parent", parent)]),
+                self.span,
+            ),
+            ResolveErrorKind::NotAType { name, kind } => Diagnostic::error(
+                message(code, &[("name", name), ("kind", &kind.description())]),
+                self.span,
+            ),
+            ResolveErrorKind::Private {
+                name,
+                kind,
+                file,
+                declared,
+            } => Diagnostic::error(
+                message(code, &[("name", name), ("kind", &kind.description())]),
+                self.span,
+            )
+            .with_file_label(*file, *declared, message("E0029.label", &[("name", name)]))
+            .with_help(message("E0029.help", &[])),
+        };
+
+        diagnostic.with_code(code)
+    }
+}
+
+/// The results of [resolve].
+#[derive(Debug, Default)]
+pub struct Resolved {
+    pub resolutions: Resolutions,
+    pub errors: Vec<ResolveError>,
+}
+
+/// Resolves every name in the given [ast::Module] to its definition.
+///
+/// Items are visible in the whole module, while local bindings are visible from the
+/// statement after theirs to the end of their block. Bindings may shadow earlier
+/// ones, which is reported as a warning within the same function.
+pub fn resolve(module: &ast::Module) -> Resolved {
+    resolve_imports(module, &HashMap::new())
+}
+
+/// Resolves the names of the given [ast::Module] like [resolve], where `imports` holds
+/// the [Exports] of the modules it imports by their path, like `["math", "trig"]`.
+///
+/// Paths like `geometry::area` refer to the items of an imported module, which have
+/// to be `pub`. Paths into modules missing from `imports` are left unresolved.
+pub fn resolve_imports(module: &ast::Module, imports: &HashMap<Vec<String>, Exports>) -> Resolved {
+    let mut resolver = Resolver {
+        exprs: &module.exprs,
+        imports,
+        resolved: Resolved::default(),
+        scopes: vec![HashMap::new()],
+        function_start: None,
+        members: HashMap::new(),
+        loaded: HashSet::new(),
+        private: HashMap::new(),
+        assignable: false,
+    };
+
+    for primitive in Primitive::all() {
+        resolver.define_builtin(primitive.name(), DefKind::Primitive);
+    }
+    for &name in BUILTINS {
+        resolver.define_builtin(name, DefKind::Builtin);
+    }
+
+    resolver.with_scope(|resolver| {
+        resolver.collect_items(module);
+        resolver.visit_module(module);
+    });
+
+    resolver.resolved
+}
+
+struct Resolver<'ast> {
+    exprs: &'ast ast::ExprArena,
+    imports: &'ast HashMap<Vec<String>, Exports>,
+    resolved: Resolved,
+    /// The scopes enclosing the visited node, innermost last, where the first one
+    /// holds the built-ins and the second one the items.
+    scopes: Vec<HashMap<String, DefId>>,
+    /// The index of the outermost scope of the visited function, if any.
+    function_start: Option<usize>,
+    /// The variants of each enum and the items of each imported module by name.
+    members: HashMap<(DefId, String), DefId>,
+    /// The imported modules whose items are known.
+    loaded: HashSet<DefId>,
+    /// The imported items that aren't `pub`, along with where they are declared.
+    private: HashMap<DefId, (FileId, Span)>,
+    /// Whether the bindings defined while visiting a pattern may be assigned to.
+    assignable: bool,
+}
+
+impl<'ast> Resolver<'ast> {
+    fn with_scope(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(HashMap::new());
+        f(self);
+        self.scopes.pop();
+    }
+
+    /// Runs `f` in a new scope holding the generics and parameters of a function.
+    fn with_function_scope(&mut self, f: impl FnOnce(&mut Self)) {
+        let outer = self.function_start.replace(self.scopes.len());
+        self.with_scope(f);
+        self.function_start = outer;
+    }
+
+    fn add_def(&mut self, def: Def, decl: Option<ast::NodeId>) -> DefId {
+        let id = DefId(self.resolved.resolutions.defs.len() as u32);
+        self.resolved.resolutions.defs.push(def);
+
+        if let Some(decl) = decl {
+            self.resolved.resolutions.decls.insert(decl, id);
+        }
+
+        id
+    }
+
+    fn define_builtin(&mut self, name: &str, kind: DefKind) {
+        let def = Def {
+            name: name.to_string(),
+            kind,
+            span: Span::EOI,
+            parent: None,
+        };
+        let id = self.add_def(def, None);
+        self.scopes[0].insert(name.to_string(), id);
+    }
+
+    /// Defines the name in the innermost scope, shadowing earlier definitions.
+    fn define(&mut self, name: &ast::Identifier, kind: DefKind, decl: Option<ast::NodeId>) {
+        let shadowed = match kind {
+            DefKind::Local => self.shadowed(name),
+            _ => None,
+        };
+
+        let def = Def {
+            name: name.text.clone(),
+            kind,
+            span: name.span,
+            parent: None,
+        };
+        let id = self.add_def(def, decl);
+        if kind == DefKind::Local && self.assignable {
+            self.resolved.resolutions.assignable.insert(id);
+        }
+        if let Some(shadowed) = shadowed {
+            self.resolved.resolutions.shadowed.insert(id, shadowed);
+        }
+
+        let scope = self.scopes.last_mut().unwrap();
+        scope.insert(name.text.clone(), id);
+    }
+
+    /// Returns the parameter or binding of the same function a new binding of the
+    /// name hides, like a loop variable named like an outer one.
+    fn shadowed(&self, name: &ast::Identifier) -> Option<DefId> {
+        let start = self.function_start?;
+
+        self.scopes[start..]
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&name.text).copied())
+            .filter(|&id| {
+                let def = self.resolved.resolutions.def(id);
+                matches!(def.kind, DefKind::Param | DefKind::Local)
+            })
+    }
+
+    /// Defines the name in the innermost scope, reporting an error if it already
+    /// has a definition there.
+    fn define_unique(&mut self, name: &ast::Identifier, kind: DefKind, decl: Option<ast::NodeId>) {
+        let scope = self.scopes.last().unwrap();
+
+        match scope.get(&name.text) {
+            Some(&previous) => self.duplicate(name, previous),
+            None => self.define(name, kind, decl),
+        }
+    }
+
+    fn duplicate(&mut self, name: &ast::Identifier, previous: DefId) {
+        let kind = ResolveErrorKind::DuplicateDefinition {
+            name: name.text.clone(),
+            previous: self.resolved.resolutions.def(previous).span,
+        };
+
+        self.resolved.errors.push(ResolveError {
+            kind,
+            span: name.span,
+        });
+    }
+
+    /// Defines the items of the module up front, so they can be used before their
+    /// declaration.
+    fn collect_items(&mut self, module: &ast::Module) {
+        for item in &module.items {
+            match item {
+                ast::Item::Import(import) => {
+                    if let Some(name) = import.alias.as_ref().or(import.path.last()) {
+                        self.define_unique(name, DefKind::Module, Some(import.id));
+                        self.collect_imported(import);
+                    }
+                }
+                ast::Item::Func(func) => {
+                    self.define_item(&func.name, DefKind::Func, func.id, func.visibility);
+                }
+                ast::Item::Const(const_decl) => self.define_item(
+                    &const_decl.name,
+                    DefKind::Const,
+                    const_decl.id,
+                    const_decl.visibility,
+                ),
+                ast::Item::Struct(struct_decl) => self.define_item(
+                    &struct_decl.name,
+                    DefKind::Struct,
+                    struct_decl.id,
+                    struct_decl.visibility,
+                ),
+                ast::Item::Enum(enum_decl) => {
+                    self.define_item(
+                        &enum_decl.name,
+                        DefKind::Enum,
+                        enum_decl.id,
+                        enum_decl.visibility,
+                    );
+                    self.collect_variants(enum_decl);
+                }
+                ast::Item::Trait(trait_decl) => self.define_item(
+                    &trait_decl.name,
+                    DefKind::Trait,
+                    trait_decl.id,
+                    trait_decl.visibility,
+                ),
+                ast::Item::Error(_) => {}
+            }
+        }
+    }
+
+    /// Defines an item like [Resolver::define_unique], remembering whether it is `pub`.
+    fn define_item(
+        &mut self,
+        name: &ast::Identifier,
+        kind: DefKind,
+        decl: ast::NodeId,
+        visibility: ast::Visibility,
+    ) {
+        self.define_unique(name, kind, Some(decl));
+
+        if visibility == ast::Visibility::Public
+            && let Some(id) = self.resolved.resolutions.decl(decl)
+        {
+            self.resolved.resolutions.public.insert(id);
+        }
+    }
+
+    /// Defines the items of an imported module as its members, if it was loaded.
+    fn collect_imported(&mut self, import: &ast::ImportDecl) {
+        let path: Vec<_> = import.path.iter().map(|seg| seg.text.clone()).collect();
+        // a duplicate import has no definition of its own to hold the items.
+        let (Some(exports), Some(module)) = (
+            self.imports.get(&path),
+            self.resolved.resolutions.decl(import.id),
+        ) else {
+            return;
+        };
+
+        self.loaded.insert(module);
+        for item in &exports.items {
+            let id = self.define_imported(item, module, exports.file);
+            for member in &item.members {
+                self.define_imported(member, id, exports.file);
+            }
+        }
+    }
+
+    fn define_imported(&mut self, export: &Export, parent: DefId, file: FileId) -> DefId {
+        let def = Def {
+            name: export.name.clone(),
+            kind: export.kind,
+            span: export.span,
+            parent: Some(parent),
+        };
+        let id = self.add_def(def, None);
+
+        self.resolved.resolutions.files.insert(id, file);
+        if let Some(signature) = &export.signature {
+            let signatures = &mut self.resolved.resolutions.signatures;
+            signatures.insert(id, signature.clone());
+        }
+        self.members.insert((parent, export.name.clone()), id);
+        if export.visibility == ast::Visibility::Private {
+            self.private.insert(id, (file, export.span));
+        }
+
+        id
+    }
+
+    fn collect_variants(&mut self, enum_decl: &ast::EnumDecl) {
+        // a duplicate enum has no definition of its own to hold the variants.
+        let Some(parent) = self.resolved.resolutions.decl(enum_decl.id) else {
+            return;
+        };
+
+        for variant in &enum_decl.variants {
+            let key = (parent, variant.name.text.clone());
+            if let Some(&previous) = self.members.get(&key) {
+                self.duplicate(&variant.name, previous);
+                continue;
+            }
+
+            let def = Def {
+                name: variant.name.text.clone(),
+                kind: DefKind::Variant,
+                span: variant.name.span,
+                parent: Some(parent),
+            };
+            let id = self.add_def(def, Some(variant.id));
+            self.members.insert(key, id);
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<DefId> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+    }
+
+    fn resolve_name(&mut self, name: &ast::Identifier) -> Option<DefId> {
+        let def = self.lookup(&name.text);
+
+        if def.is_none() {
+            self.resolved.errors.push(ResolveError {
+                kind: ResolveErrorKind::UnknownIdentifier(name.text.clone()),
+                span: name.span,
+            });
+        }
+
+        def
+    }
+
+    /// Resolves a path like `Shape::Circle`, whose later segments are members of the
+    /// definition named by the previous one.
+    fn resolve_path(&mut self, segments: &[ast::Identifier]) -> Option<DefId> {
+        let (first, rest) = segments.split_first()?;
+        let mut def = self.resolve_name(first)?;
+
+        for segment in rest {
+            let parent = self.resolved.resolutions.def(def);
+
+            // the items of modules that weren't loaded are unknown.
+            if parent.kind == DefKind::Module && !self.loaded.contains(&def) {
+                return None;
+            }
+
+            match self.members.get(&(def, segment.text.clone())) {
+                Some(&member) => {
+                    def = member;
+                    self.check_private(member, segment);
+                }
+                None => {
+                    let kind = ResolveErrorKind::UnknownMember {
+                        parent: parent.name.clone(),
+                        name: segment.text.clone(),
+                    };
+                    self.resolved.errors.push(ResolveError {
+                        kind,
+                        span: segment.span,
+                    });
+                    return None;
+                }
+            }
+        }
+
+        Some(def)
+    }
+
+    /// Reports a use of an imported item that isn't `pub`.
+    fn check_private(&mut self, def: DefId, name: &ast::Identifier) {
+        let Some(&(file, declared)) = self.private.get(&def) else {
+            return;
+        };
+
+        let kind = ResolveErrorKind::Private {
+            name: name.text.clone(),
+            kind: self.resolved.resolutions.def(def).kind,
+            file,
+            declared,
+        };
+        self.resolved.errors.push(ResolveError {
+            kind,
+            span: name.span,
+        });
+    }
+
+    /// Defines the generic type parameters of the item declared by the `owner` node.
+    fn define_generics(&mut self, owner: ast::NodeId, generics: &[ast::Identifier]) {
+        let mut defs = Vec::new();
+        for generic in generics {
+            self.define_unique(generic, DefKind::Generic, None);
+            defs.push(self.scopes.last().unwrap()[&generic.text]);
+        }
+
+        if let Some(owner) = self.resolved.resolutions.decl(owner)
+            && !defs.is_empty()
+        {
+            self.resolved.resolutions.generics.insert(owner, defs);
+        }
+    }
+
+    fn define_params(&mut self, params: &'ast [ast::Param]) {
+        for param in params {
+            self.visit_type(&param.ty);
+            self.define_unique(&param.name, DefKind::Param, Some(param.id));
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for Resolver<'ast> {
+    fn exprs(&self) -> &'ast ast::ExprArena {
+        self.exprs
+    }
+
+    // attribute arguments name lints and the like rather than values.
+    fn visit_attribute(&mut self, _attr: &'ast ast::Attribute) {}
+
+    fn visit_func(&mut self, func: &'ast ast::FuncDecl) {
+        self.with_function_scope(|resolver| {
+            resolver.define_generics(func.id, &func.generics);
+            resolver.define_params(&func.params);
+
+            if let Some(return_type) = &func.return_type {
+                resolver.visit_type(return_type);
+            }
+
+            resolver.visit_block(&func.body);
+        });
+    }
+
+    fn visit_struct(&mut self, struct_decl: &'ast ast::StructDecl) {
+        self.with_scope(|resolver| {
+            resolver.define_generics(struct_decl.id, &struct_decl.generics);
+            visit::walk_struct(resolver, struct_decl);
+        });
+    }
+
+    fn visit_trait(&mut self, trait_decl: &'ast ast::TraitDecl) {
+        self.with_scope(|resolver| {
+            resolver.define_generics(trait_decl.id, &trait_decl.generics);
+            visit::walk_trait(resolver, trait_decl);
+        });
+    }
+
+    fn visit_trait_method(&mut self, method: &'ast ast::TraitMethod) {
+        self.with_function_scope(|resolver| {
+            resolver.define_generics(method.id, &method.generics);
+
+            if let Some(span) = method.receiver {
+                let receiver = ast::Identifier {
+                    text: "self".to_string(),
+                    span,
+                };
+                resolver.define(&receiver, DefKind::Param, None);
+            }
+            resolver.define_params(&method.params);
+
+            if let Some(return_type) = &method.return_type {
+                resolver.visit_type(return_type);
+            }
+
+            if let Some(body) = &method.body {
+                resolver.visit_block(body);
+            }
+        });
+    }
+
+    fn visit_type(&mut self, ty: &'ast ast::TypeExpr) {
+        if let ast::TypeExpr::Named(named) = ty
+            && let Some(def) = self.resolve_name(&named.name)
+        {
+            let kind = self.resolved.resolutions.def(def).kind;
+            if !kind.is_type() {
+                self.resolved.errors.push(ResolveError {
+                    kind: ResolveErrorKind::NotAType {
+                        name: named.name.text.clone(),
+                        kind,
+                    },
+                    span: named.name.span,
+                });
+            }
+
+            self.resolved.resolutions.nodes.insert(named.id, def);
+        }
+
+        visit::walk_type(self, ty);
+    }
+
+    fn visit_block(&mut self, block: &'ast ast::Block) {
+        self.with_scope(|resolver| visit::walk_block(resolver, block));
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast ast::Stmt) {
+        match stmt {
+            ast::Stmt::Let(let_stmt) => {
+                // the bindings aren't in scope of their own initializer.
+                if let Some(value) = let_stmt.value {
+                    self.visit_expr(value);
+                }
+                if let Some(ty) = &let_stmt.ty {
+                    self.visit_type(ty);
+                }
+
+                self.assignable =
+                    let_stmt.mutability == ast::Mutability::Mutable || let_stmt.value.is_none();
+                self.visit_pattern(&let_stmt.pattern);
+                self.assignable = false;
+            }
+            ast::Stmt::Const(const_decl) => {
+                self.visit_const(const_decl);
+                self.define(&const_decl.name, DefKind::Const, Some(const_decl.id));
+            }
+            ast::Stmt::For(for_stmt) => {
+                self.visit_expr(for_stmt.iterable);
+                self.with_scope(|resolver| {
+                    resolver.define(&for_stmt.binding, DefKind::Local, Some(for_stmt.id));
+                    resolver.visit_block(&for_stmt.body);
+                });
+            }
+            _ => visit::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, id: ast::ExprId) {
+        let def = match &self.exprs[id] {
+            ast::Expr::Identifier(ident) => self.resolve_name(ident),
+            ast::Expr::Path(path) => self.resolve_path(&path.segments),
+            _ => return visit::walk_expr(self, id),
+        };
+
+        if let Some(def) = def {
+            self.resolved.resolutions.exprs.insert(id, def);
+        }
+    }
+
+    fn visit_match_arm(&mut self, arm: &'ast ast::MatchArm) {
+        self.with_scope(|resolver| visit::walk_match_arm(resolver, arm));
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast ast::Pattern) {
+        let (id, path) = match pattern {
+            ast::Pattern::Binding(binding) => {
+                // `true` and `false` match the boolean values instead of binding a name.
+                if let Some(def) = self.lookup(&binding.name.text)
+                    && self.resolved.resolutions.def(def).kind == DefKind::Builtin
+                {
+                    self.resolved.resolutions.nodes.insert(binding.id, def);
+                    return;
+                }
+                return self.define(&binding.name, DefKind::Local, Some(binding.id));
+            }
+            ast::Pattern::Path(path) => (path.id, &path.segments),
+            ast::Pattern::TupleStruct(tuple_struct) => (tuple_struct.id, &tuple_struct.path),
+            ast::Pattern::Struct(struct_pattern) => (struct_pattern.id, &struct_pattern.path),
+            _ => return visit::walk_pattern(self, pattern),
+        };
+
+        if let Some(def) = self.resolve_path(path) {
+            self.resolved.resolutions.nodes.insert(id, def);
+        }
+
+        if let ast::Pattern::Struct(struct_pattern) = pattern {
+            for field in &struct_pattern.fields {
+                match &field.pattern {
+                    Some(pattern) => self.visit_pattern(pattern),
+                    None => self.define(&field.name, DefKind::Local, Some(field.id)),
+                }
+            }
+        } else {
+            visit::walk_pattern(self, pattern);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use syntax::{
+        ast, explain::explain, lexer::Lexer, parser::Parser, source::FileId, token::Span,
+    };
+
+    use crate::resolve::{
+        DefKind, Exports, Resolutions, ResolveError, ResolveErrorKind, Resolved, resolve,
+        resolve_imports,
+    };
+
+    fn resolve_source(source: &str) -> (ast::Module, Resolved) {
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let (module, errors) = Parser::new(&tokens).parse_module();
+        assert_eq!(errors, []);
+
+        let resolved = resolve(&module);
+        (module, resolved)
+    }
+
+    /// Returns the name, kind and defining span of the identifier expression at `offset`.
+    fn resolved_at(
+        module: &ast::Module,
+        resolutions: &Resolutions,
+        offset: usize,
+    ) -> Option<(String, DefKind, Span)> {
+        let (id, _) = module
+            .exprs
+            .iter()
+            .find(|(_, expr)| expr.span().start == offset)?;
+        let def = resolutions.def(resolutions.expr(id)?);
+        Some((def.name.clone(), def.kind, def.span))
+    }
+
+    #[test]
+    fn names() {
+        let source = "
+            fn main() { let x = helper(1); let x = x + ONE; x }
+            fn helper(a: i64) -> i64 { a }
+            const ONE: i64 = 1;
+        ";
+        let (
+            module,
+            Resolved {
+                resolutions,
+                errors,
+                ..
+            },
+        ) = resolve_source(source);
+        assert_eq!(errors, []);
+
+        let at = |text: &str, nth: usize| source.match_indices(text).nth(nth).unwrap().0;
+        let span = |text: &str, nth: usize| Span {
+            start: at(text, nth),
+            end: at(text, nth) + text.len(),
+        };
+
+        assert_eq!(
+            resolved_at(&module, &resolutions, at("helper", 0)),
+            Some(("helper".to_string(), DefKind::Func, span("helper", 1)))
+        );
+        // the second `x` is initialized from the first one and shadows it afterwards.
+        assert_eq!(
+            resolved_at(&module, &resolutions, at("x", 2)),
+            Some(("x".to_string(), DefKind::Local, span("x", 0)))
+        );
+        assert_eq!(
+            resolved_at(&module, &resolutions, at("x", 3)),
+            Some(("x".to_string(), DefKind::Local, span("x", 1)))
+        );
+        assert_eq!(
+            resolved_at(&module, &resolutions, at("ONE", 0)),
+            Some(("ONE".to_string(), DefKind::Const, span("ONE", 1)))
+        );
+        assert_eq!(
+            resolved_at(&module, &resolutions, at("a }", 0)),
+            Some((
+                "a".to_string(),
+                DefKind::Param,
+                Span {
+                    start: at("a: i64", 0),
+                    end: at("a: i64", 0) + 1
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let source = "
+            enum Shape { Circle(f64) }
+            fn area(s: Shape) -> Helper {
+                { let inner = 1; }
+                match s { Shape::Square(r) => inner, Shape::Circle(r) => r }
+            }
+            fn area() {}
+        ";
+        let (_, Resolved { errors, .. }) = resolve_source(source);
+        for error in &errors {
+            assert!(explain(error.kind.code()).is_some());
+        }
+
+        let at = |text: &str| source.find(text).unwrap();
+        let span = |text: &str| Span {
+            start: at(text),
+            end: at(text) + text.len(),
+        };
+
+        assert_eq!(
+            errors,
+            [
+                ResolveError {
+                    kind: ResolveErrorKind::DuplicateDefinition {
+                        name: "area".to_string(),
+                        previous: span("area"),
+                    },
+                    span: Span {
+                        start: at("area()"),
+                        end: at("area()") + 4,
+                    },
+                },
+                ResolveError {
+                    kind: ResolveErrorKind::UnknownIdentifier("Helper".to_string()),
+                    span: span("Helper"),
+                },
+                ResolveError {
+                    kind: ResolveErrorKind::UnknownMember {
+                        parent: "Shape".to_string(),
+                        name: "Square".to_string(),
+                    },
+                    span: span("Square"),
+                },
+                ResolveError {
+                    kind: ResolveErrorKind::UnknownIdentifier("inner".to_string()),
+                    span: Span {
+                        start: at("inner,"),
+                        end: at("inner,") + 5,
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn shadowing() {
+        let source = "
+            fn f(n: i64) {
+                let n = n + 1;
+                { let inner = 1; }
+                let inner = 2;
+                for i in 0..n { let i = i; }
+            }
+            fn g() { let n = 1; }
+        ";
+        let (
+            _,
+            Resolved {
+                resolutions,
+                errors,
+            },
+        ) = resolve_source(source);
+        assert_eq!(errors, []);
+
+        let shadowed: Vec<_> = resolutions
+            .defs()
+            .filter_map(|(id, def)| {
+                let previous = resolutions.def(resolutions.shadowed(id)?);
+                Some((def.name.as_str(), def.span.start, previous.span.start))
+            })
+            .collect();
+
+        // `inner` and the binding in `g` don't hide anything in scope.
+        let at = |text: &str| source.find(text).unwrap();
+        assert_eq!(
+            shadowed,
+            [("n", at("n ="), at("n:")), ("i", at("i ="), at("i in"))]
+        );
+    }
+
+    #[test]
+    fn imports() {
+        let parse = |source: &str| {
+            let tokens = Lexer::new(source).collect_tokens().unwrap();
+            Parser::new(&tokens).parse_module().0
+        };
+
+        let geometry = parse("pub fn area() {} fn secret() {} pub enum Shape { Circle }");
+        let exports = Exports::collect(&geometry, FileId(1));
+
+        let source = "
+            import geometry;
+            import missing;
+            fn main() {
+                geometry::area();
+                geometry::secret();
+                geometry::volume();
+                geometry::Shape::Circle;
+                missing::anything();
+            }
+        ";
+        let module = parse(source);
+        let imports = HashMap::from([(vec!["geometry".to_string()], exports)]);
+        let Resolved {
+            resolutions,
+            errors,
+            ..
+        } = resolve_imports(&module, &imports);
+
+        let span = |text: &str| {
+            let start = source.find(text).unwrap();
+            Span {
+                start,
+                end: start + text.len(),
+            }
+        };
+        let secret = geometry.items[1].span();
+        assert_eq!(
+            errors,
+            [
+                ResolveError {
+                    kind: ResolveErrorKind::Private {
+                        name: "secret".to_string(),
+                        kind: DefKind::Func,
+                        file: FileId(1),
+                        declared: Span {
+                            start: secret.start + 3,
+                            end: secret.start + 9,
+                        },
+                    },
+                    span: span("secret"),
+                },
+                ResolveError {
+                    kind: ResolveErrorKind::UnknownMember {
+                        parent: "geometry".to_string(),
+                        name: "volume".to_string(),
+                    },
+                    span: span("volume"),
+                },
+            ]
+        );
+        assert!(explain(errors[0].kind.code()).is_some());
+
+        let at = |text: &str| {
+            let (id, _) = module
+                .exprs
+                .iter()
+                .find(|(_, expr)| expr.span().start == span(text).start)
+                .unwrap();
+            resolutions.expr(id)
+        };
+        let area = at("geometry::area").unwrap();
+        assert_eq!(resolutions.def(area).kind, DefKind::Func);
+        assert_eq!(resolutions.file(area), Some(FileId(1)));
+        let circle = at("geometry::Shape").unwrap();
+        assert_eq!(resolutions.def(circle).kind, DefKind::Variant);
+        // the items of modules that weren't loaded are unknown.
+        assert_eq!(at("missing::anything"), None);
+    }
+}
@@ -0,0 +1,398 @@
+use std::{collections::HashMap, fmt};
+
+use crate::resolve::{DefId, Resolutions};
+
+/// A built-in type like `i64` or `bool`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Primitive {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+    Bool,
+    Str,
+    /// The unit type, which can also be written as `()`.
+    Unit,
+}
+
+/// What kind of values a [Primitive] holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Signed,
+    Unsigned,
+    Float,
+    Other,
+}
+
+/// The name, size in bytes and class of every [Primitive], which is the single place
+/// the built-in types are listed. A `str` is a pointer and a length.
+const PRIMITIVES: [(Primitive, &str, u64, Class); 13] = [
+    (Primitive::I8, "i8", 1, Class::Signed),
+    (Primitive::I16, "i16", 2, Class::Signed),
+    (Primitive::I32, "i32", 4, Class::Signed),
+    (Primitive::I64, "i64", 8, Class::Signed),
+    (Primitive::U8, "u8", 1, Class::Unsigned),
+    (Primitive::U16, "u16", 2, Class::Unsigned),
+    (Primitive::U32, "u32", 4, Class::Unsigned),
+    (Primitive::U64, "u64", 8, Class::Unsigned),
+    (Primitive::F32, "f32", 4, Class::Float),
+    (Primitive::F64, "f64", 8, Class::Float),
+    (Primitive::Bool, "bool", 1, Class::Other),
+    (Primitive::Str, "str", 16, Class::Other),
+    (Primitive::Unit, "unit", 0, Class::Other),
+];
+
+impl Primitive {
+    /// The type of an integer literal that isn't expected to have another type.
+    pub const DEFAULT_INTEGER: Primitive = Primitive::I64;
+    /// The type of a float literal that isn't expected to have another type.
+    pub const DEFAULT_FLOAT: Primitive = Primitive::F64;
+
+    /// Returns all built-in types in the order they are declared.
+    pub fn all() -> impl Iterator<Item = Primitive> {
+        PRIMITIVES.iter().map(|&(primitive, ..)| primitive)
+    }
+
+    /// Returns the built-in type with the given name, like [Primitive::I64] for `i64`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        PRIMITIVES
+            .iter()
+            .find(|&&(_, other, ..)| other == name)
+            .map(|&(primitive, ..)| primitive)
+    }
+
+    fn info(self) -> (&'static str, u64, Class) {
+        let (_, name, size, class) = PRIMITIVES[self as usize];
+        (name, size, class)
+    }
+
+    /// Returns the name of the type as written in the source.
+    pub fn name(self) -> &'static str {
+        self.info().0
+    }
+
+    /// Returns the size of a value of the type in bytes.
+    pub fn size(self) -> u64 {
+        self.info().1
+    }
+
+    /// Returns the [Type] the built-in type stands for, where `unit` is [Type::UNIT].
+    pub fn ty(self) -> Type {
+        match self {
+            Primitive::Unit => Type::UNIT,
+            _ => Type::Primitive(self),
+        }
+    }
+
+    pub fn is_integer(self) -> bool {
+        matches!(self.info().2, Class::Signed | Class::Unsigned)
+    }
+
+    /// Returns whether the type is a signed integer.
+    pub fn is_signed(self) -> bool {
+        self.info().2 == Class::Signed
+    }
+
+    pub fn is_float(self) -> bool {
+        self.info().2 == Class::Float
+    }
+
+    /// Returns the smallest and the largest value of an integer type.
+    pub fn bounds(self) -> Option<(i128, i128)> {
+        let bits = self.size() * 8;
+        match self.info().2 {
+            Class::Signed => Some((-(1 << (bits - 1)), (1 << (bits - 1)) - 1)),
+            Class::Unsigned => Some((0, (1 << bits) - 1)),
+            _ => None,
+        }
+    }
+}
+
+/// The type of a value, as determined by [crate::check::check].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Type {
+    Primitive(Primitive),
+    /// A tuple type, where the empty tuple is the unit type `()`.
+    Tuple(Vec<Type>),
+    Ref(Box<Type>),
+    Ptr(Box<Type>),
+    Array(Box<Type>, u64),
+    Optional(Box<Type>),
+    /// The type of a function or a function value with the given parameter and return types.
+    Func(Vec<Type>, Box<Type>),
+    /// The range of values produced by an expression like `0..n`.
+    Range(Box<Type>),
+    /// A struct or an enum, applied to its generic arguments.
+    Adt(DefId, Vec<Type>),
+    /// A generic type parameter like `T` in `fn max<T>`.
+    Generic(DefId),
+    /// The type of expressions that never produce a value, like `return` or `panic()`,
+    /// which is compatible with every other type.
+    Never,
+    /// The type of erroneous expressions, which is compatible with every other type
+    /// so one mistake isn't reported over and over.
+    Error,
+}
+
+impl Type {
+    pub const UNIT: Type = Type::Tuple(Vec::new());
+    pub const BOOL: Type = Type::Primitive(Primitive::Bool);
+
+    /// Returns the built-in type, if this is one.
+    pub fn primitive(&self) -> Option<Primitive> {
+        match self {
+            Type::Primitive(primitive) => Some(*primitive),
+            _ => None,
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.primitive().is_some_and(Primitive::is_integer)
+    }
+
+    pub fn is_float(&self) -> bool {
+        self.primitive().is_some_and(Primitive::is_float)
+    }
+
+    /// Returns whether the type is an integer or a float.
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || self.is_float()
+    }
+
+    /// Returns whether the type is [Type::Error] or [Type::Never], which fit anywhere.
+    pub fn is_flexible(&self) -> bool {
+        matches!(self, Type::Never | Type::Error)
+    }
+
+    /// Returns whether the type mentions a generic type parameter.
+    pub fn has_generics(&self) -> bool {
+        match self {
+            Type::Generic(_) => true,
+            Type::Tuple(types) | Type::Adt(_, types) => types.iter().any(Type::has_generics),
+            Type::Ref(inner)
+            | Type::Ptr(inner)
+            | Type::Array(inner, _)
+            | Type::Optional(inner)
+            | Type::Range(inner) => inner.has_generics(),
+            Type::Func(params, ret) => params.iter().any(Type::has_generics) || ret.has_generics(),
+            Type::Primitive(_) | Type::Never | Type::Error => false,
+        }
+    }
+
+    /// Adds each generic type parameter the type mentions to `generics`.
+    pub fn collect_generics(&self, generics: &mut Vec<DefId>) {
+        match self {
+            Type::Generic(def) => generics.push(*def),
+            Type::Tuple(types) | Type::Adt(_, types) => {
+                types.iter().for_each(|ty| ty.collect_generics(generics));
+            }
+            Type::Ref(inner)
+            | Type::Ptr(inner)
+            | Type::Array(inner, _)
+            | Type::Optional(inner)
+            | Type::Range(inner) => inner.collect_generics(generics),
+            Type::Func(params, ret) => {
+                params.iter().for_each(|ty| ty.collect_generics(generics));
+                ret.collect_generics(generics);
+            }
+            Type::Primitive(_) | Type::Never | Type::Error => {}
+        }
+    }
+
+    /// Returns whether a value of this type can be used where `expected` is required,
+    /// which is also the case if it is a `T` and an optional `T?` is expected.
+    pub fn fits(&self, expected: &Type) -> bool {
+        if self.is_flexible() || expected.is_flexible() || self == expected {
+            return true;
+        }
+
+        match (self, expected) {
+            (found, Type::Optional(inner)) if found.fits(inner) => true,
+            (Type::Tuple(found), Type::Tuple(expected)) => all_fit(found, expected),
+            (Type::Adt(found_def, found), Type::Adt(expected_def, expected)) => {
+                found_def == expected_def && all_fit(found, expected)
+            }
+            (Type::Ref(found), Type::Ref(expected))
+            | (Type::Ptr(found), Type::Ptr(expected))
+            | (Type::Optional(found), Type::Optional(expected))
+            | (Type::Range(found), Type::Range(expected)) => found.fits(expected),
+            (Type::Array(found, found_len), Type::Array(expected, expected_len)) => {
+                found_len == expected_len && found.fits(expected)
+            }
+            (Type::Func(found_params, found_ret), Type::Func(expected_params, expected_ret)) => {
+                // the parameters are passed the other way.
+                all_fit(expected_params, found_params) && found_ret.fits(expected_ret)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the type with each generic type parameter replaced by its binding in
+    /// `generics`, if it has one.
+    pub fn substitute(&self, generics: &HashMap<DefId, Type>) -> Type {
+        let all = |types: &[Type]| types.iter().map(|ty| ty.substitute(generics)).collect();
+        let boxed = |ty: &Type| Box::new(ty.substitute(generics));
+
+        match self {
+            Type::Generic(def) => generics.get(def).cloned().unwrap_or(Type::Generic(*def)),
+            Type::Tuple(types) => Type::Tuple(all(types)),
+            Type::Adt(def, types) => Type::Adt(*def, all(types)),
+            Type::Ref(inner) => Type::Ref(boxed(inner)),
+            Type::Ptr(inner) => Type::Ptr(boxed(inner)),
+            Type::Array(inner, len) => Type::Array(boxed(inner), *len),
+            Type::Optional(inner) => Type::Optional(boxed(inner)),
+            Type::Range(inner) => Type::Range(boxed(inner)),
+            Type::Func(params, ret) => Type::Func(all(params), boxed(ret)),
+            Type::Primitive(_) | Type::Never | Type::Error => self.clone(),
+        }
+    }
+
+    /// Binds the generic type parameters of this type that aren't bound yet to the
+    /// corresponding parts of `found`, like `T` to `i64` for `T?` and `i64?`.
+    pub fn infer_generics(&self, found: &Type, generics: &mut HashMap<DefId, Type>) {
+        let mut all = |expected: &[Type], found: &[Type]| {
+            for (expected, found) in expected.iter().zip(found) {
+                expected.infer_generics(found, generics);
+            }
+        };
+
+        match (self, found) {
+            (Type::Generic(def), found) if !found.is_flexible() => {
+                generics.entry(*def).or_insert_with(|| found.clone());
+            }
+            (Type::Tuple(expected), Type::Tuple(found)) => all(expected, found),
+            (Type::Adt(expected_def, expected), Type::Adt(found_def, found))
+                if expected_def == found_def =>
+            {
+                all(expected, found)
+            }
+            (Type::Func(expected_params, expected_ret), Type::Func(found_params, found_ret)) => {
+                all(expected_params, found_params);
+                expected_ret.infer_generics(found_ret, generics);
+            }
+            (Type::Ref(expected), Type::Ref(found))
+            | (Type::Ptr(expected), Type::Ptr(found))
+            | (Type::Array(expected, _), Type::Array(found, _))
+            | (Type::Optional(expected), Type::Optional(found))
+            | (Type::Range(expected), Type::Range(found)) => {
+                expected.infer_generics(found, generics);
+            }
+            (Type::Optional(expected), found) => expected.infer_generics(found, generics),
+            _ => {}
+        }
+    }
+
+    /// Returns an adapter displaying the type as written in the source, naming its
+    /// definitions with the given [Resolutions].
+    pub fn display<'a>(&'a self, resolutions: &'a Resolutions) -> TypeDisplay<'a> {
+        TypeDisplay {
+            ty: self,
+            resolutions,
+        }
+    }
+}
+
+/// Returns whether each of the `found` types fits the `expected` type at its position.
+fn all_fit(found: &[Type], expected: &[Type]) -> bool {
+    found.len() == expected.len()
+        && found
+            .iter()
+            .zip(expected)
+            .all(|(found, expected)| found.fits(expected))
+}
+
+/// Displays a [Type], see [Type::display].
+pub struct TypeDisplay<'a> {
+    ty: &'a Type,
+    resolutions: &'a Resolutions,
+}
+
+impl TypeDisplay<'_> {
+    fn nested<'b>(&'b self, ty: &'b Type) -> TypeDisplay<'b> {
+        ty.display(self.resolutions)
+    }
+
+    /// Writes the given types separated by commas.
+    fn list(&self, f: &mut fmt::Formatter<'_>, types: &[Type]) -> fmt::Result {
+        for (index, ty) in types.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", self.nested(ty))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TypeDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.ty {
+            Type::Primitive(primitive) => write!(f, "{}", primitive.name()),
+            Type::Tuple(elements) => {
+                write!(f, "(")?;
+                self.list(f, elements)?;
+                if elements.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+            Type::Ref(inner) => write!(f, "&{}", self.nested(inner)),
+            Type::Ptr(inner) => write!(f, "*{}", self.nested(inner)),
+            Type::Array(element, len) => write!(f, "[{}; {len}]", self.nested(element)),
+            Type::Optional(inner) => match **inner {
+                Type::Ref(_) | Type::Ptr(_) | Type::Func(..) => {
+                    write!(f, "({})?", self.nested(inner))
+                }
+                _ => write!(f, "{}?", self.nested(inner)),
+            },
+            Type::Func(params, ret) => {
+                write!(f, "fn(")?;
+                self.list(f, params)?;
+                write!(f, ")")?;
+                match **ret {
+                    Type::Tuple(ref elements) if elements.is_empty() => Ok(()),
+                    _ => write!(f, " -> {}", self.nested(ret)),
+                }
+            }
+            Type::Range(element) => write!(f, "range<{}>", self.nested(element)),
+            Type::Adt(def, args) => {
+                write!(f, "{}", self.resolutions.def(*def).name)?;
+                if !args.is_empty() {
+                    write!(f, "<")?;
+                    self.list(f, args)?;
+                    write!(f, ">")?;
+                }
+                Ok(())
+            }
+            Type::Generic(def) => write!(f, "{}", self.resolutions.def(*def).name),
+            Type::Never => write!(f, "!"),
+            Type::Error => write!(f, "{{unknown}}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ty::{Primitive, Type};
+
+    #[test]
+    fn primitives() {
+        for primitive in Primitive::all() {
+            assert_eq!(Primitive::from_name(primitive.name()), Some(primitive));
+        }
+
+        assert_eq!(Primitive::from_name("int"), None);
+        assert_eq!(Primitive::U16.size(), 2);
+        assert!(Primitive::I32.is_signed() && !Primitive::U32.is_signed());
+        assert_eq!(Primitive::I8.bounds(), Some((-128, 127)));
+        assert_eq!(Primitive::U64.bounds(), Some((0, u64::MAX.into())));
+        assert_eq!(Primitive::F32.bounds(), None);
+        assert_eq!(Primitive::Unit.ty(), Type::UNIT);
+    }
+}
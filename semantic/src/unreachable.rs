@@ -0,0 +1,209 @@
+use syntax::{
+    ast,
+    diagnostics::Diagnostic,
+    messages::message,
+    token::Span,
+    visit::{self, Visitor},
+};
+
+use crate::{
+    check::{self, TypeTable},
+    resolve::{DefKind, Resolutions},
+};
+
+/// A warning about code that can never run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnreachableWarning {
+    pub kind: UnreachableWarningKind,
+    /// The [Span] of the code that never runs.
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnreachableWarningKind {
+    /// Statements following the one at `cause`, which never finishes.
+    AfterDiverging { cause: Span },
+    /// The body of a `while` loop whose condition at `cond` is `false`.
+    WhileFalse { cond: Span },
+}
+
+impl UnreachableWarning {
+    /// Returns the name of the lint, used to allow or deny it.
+    pub fn name(&self) -> &'static str {
+        "unreachable_code"
+    }
+
+    /// Returns the stable code of the warning, see [syntax::explain::explain].
+    pub fn code(&self) -> &'static str {
+        "W0008"
+    }
+
+    /// Converts the warning into a [Diagnostic].
+    pub fn diagnostic(&self) -> Diagnostic {
+        let code = self.code();
+        let diagnostic = match self.kind {
+            UnreachableWarningKind::AfterDiverging { cause } => {
+                Diagnostic::warning(message(code, &[]), self.span)
+                    .with_label(cause, message("W0008.label", &[]))
+            }
+            UnreachableWarningKind::WhileFalse { cond } => {
+                Diagnostic::warning(message("W0008.while", &[]), self.span)
+                    .with_label(cond, message("W0008.while.label", &[]))
+            }
+        };
+
+        diagnostic.with_code(code).with_lint(self.name())
+    }
+}
+
+/// Finds the statements after a `return`, `break`, `continue` or another statement that
+/// never finishes according to the checked [TypeTable], and the bodies of `while false`
+/// loops. Each unreachable stretch of code is reported once.
+pub fn find_unreachable(
+    module: &ast::Module,
+    resolutions: &Resolutions,
+    types: &TypeTable,
+) -> Vec<UnreachableWarning> {
+    let mut finder = UnreachableFinder {
+        exprs: &module.exprs,
+        resolutions,
+        types,
+        warnings: Vec::new(),
+    };
+    finder.visit_module(module);
+
+    finder.warnings
+}
+
+struct UnreachableFinder<'ast> {
+    exprs: &'ast ast::ExprArena,
+    resolutions: &'ast Resolutions,
+    types: &'ast TypeTable,
+    warnings: Vec<UnreachableWarning>,
+}
+
+impl UnreachableFinder<'_> {
+    /// Returns whether the expression is the built-in `false`, possibly in parentheses.
+    fn is_false(&self, id: ast::ExprId) -> bool {
+        match &self.exprs[id] {
+            ast::Expr::Paren(paren) => self.is_false(paren.expr),
+            ast::Expr::Identifier(_) => self.resolutions.expr(id).is_some_and(|def| {
+                let def = self.resolutions.def(def);
+                def.kind == DefKind::Builtin && def.name == "false"
+            }),
+            _ => false,
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for UnreachableFinder<'ast> {
+    fn exprs(&self) -> &'ast ast::ExprArena {
+        self.exprs
+    }
+
+    fn visit_block(&mut self, block: &'ast ast::Block) {
+        for (index, stmt) in block.stmts.iter().enumerate() {
+            self.visit_stmt(stmt);
+
+            if !check::diverges(stmt, self.types) {
+                continue;
+            }
+
+            let rest = block.stmts[index + 1..].iter().map(ast::Stmt::span);
+            let tail = block.tail.map(|tail| self.exprs[tail].span());
+            let mut spans = rest.chain(tail);
+
+            if let Some(first) = spans.next() {
+                let last = spans.last().unwrap_or(first);
+                self.warnings.push(UnreachableWarning {
+                    kind: UnreachableWarningKind::AfterDiverging { cause: stmt.span() },
+                    span: first.to(last),
+                });
+            }
+            return;
+        }
+
+        if let Some(tail) = block.tail {
+            self.visit_expr(tail);
+        }
+    }
+
+    fn visit_expr(&mut self, id: ast::ExprId) {
+        if let ast::Expr::While(while_expr) = &self.exprs[id]
+            && self.is_false(while_expr.cond)
+        {
+            self.warnings.push(UnreachableWarning {
+                kind: UnreachableWarningKind::WhileFalse {
+                    cond: self.exprs[while_expr.cond].span(),
+                },
+                span: while_expr.body.span,
+            });
+            return;
+        }
+
+        visit::walk_expr(self, id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use syntax::{lexer::Lexer, parser::Parser, token::Span};
+
+    use crate::{
+        check::check,
+        consts::eval_consts,
+        resolve::resolve,
+        unreachable::{UnreachableWarning, UnreachableWarningKind, find_unreachable},
+    };
+
+    #[test]
+    fn unreachable() {
+        let source = "
+            fn main(n: i64) -> i64 {
+                loop {
+                    if n > 1 { break; n; }
+                    continue;
+                }
+                while false { panic(); }
+                while (n > 0) {}
+                let x = panic(\"stop\");
+                let y = 1;
+                y
+            }
+        ";
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let (module, _) = Parser::new(&tokens).parse_module();
+        let resolutions = resolve(&module).resolutions;
+        let consts = eval_consts(&module, &resolutions).values;
+        let types = check(&module, &resolutions, &consts).types;
+
+        let span = |text: &str| Span {
+            start: source.find(text).unwrap(),
+            end: source.find(text).unwrap() + text.len(),
+        };
+        let warnings = find_unreachable(&module, &resolutions, &types);
+        assert_eq!(
+            warnings,
+            [
+                UnreachableWarning {
+                    kind: UnreachableWarningKind::AfterDiverging {
+                        cause: span("break;"),
+                    },
+                    span: span("n;"),
+                },
+                UnreachableWarning {
+                    kind: UnreachableWarningKind::WhileFalse {
+                        cond: span("false"),
+                    },
+                    span: span("{ panic(); }"),
+                },
+                UnreachableWarning {
+                    kind: UnreachableWarningKind::AfterDiverging {
+                        cause: span("let x = panic(\"stop\");"),
+                    },
+                    span: span("let y = 1;\n                y"),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use std::{collections::HashSet, mem};
+
+use syntax::{
+    ast,
+    diagnostics::{Applicability, Diagnostic},
+    messages::message,
+    token::Span,
+    visit::{self, Visitor},
+};
+
+use crate::resolve::{DefId, DefKind, Resolutions};
+
+/// The function run when a program starts, which is used without being called.
+const ENTRY_POINT: &str = "main";
+
+/// A warning about a definition that is never used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusedWarning {
+    pub kind: UnusedWarningKind,
+    /// The [Span] of the unused name in its definition.
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnusedWarningKind {
+    /// A local binding whose value is never read.
+    Variable(String),
+    /// A function that is never called outside of its own body.
+    Function(String),
+}
+
+impl UnusedWarningKind {
+    /// Returns the name of the lint, used to allow or deny it.
+    pub fn name(&self) -> &'static str {
+        match self {
+            UnusedWarningKind::Variable(_) => "unused_variables",
+            UnusedWarningKind::Function(_) => "unused_functions",
+        }
+    }
+
+    /// Returns the stable code of this kind of warning, see [syntax::explain::explain].
+    pub fn code(&self) -> &'static str {
+        match self {
+            UnusedWarningKind::Variable(_) => "W0005",
+            UnusedWarningKind::Function(_) => "W0006",
+        }
+    }
+}
+
+impl UnusedWarning {
+    /// Converts the warning into a [Diagnostic].
+    pub fn diagnostic(&self) -> Diagnostic {
+        let code = self.kind.code();
+
+        let diagnostic = match &self.kind {
+            UnusedWarningKind::Variable(name) => {
+                Diagnostic::warning(message(code, &[("name", name)]), self.span).with_suggestion(
+                    message("W0005.suggestion", &[("name", name)]),
+                    self.span,
+                    format!("_{name}"),
+                    Applicability::MaybeIncorrect,
+                )
+            }
+            UnusedWarningKind::Function(name) => {
+                Diagnostic::warning(message(code, &[("name", name)]), self.span)
+            }
+        };
+
+        diagnostic.with_code(code).with_lint(self.kind.name())
+    }
+}
+
+/// Finds the local bindings that are never read and the functions that are never
+/// called, leaving out names starting with `_`, the `main` function and functions
+/// exported with `pub`.
+///
+/// Assigning to a binding doesn't count as reading it, and a function calling itself
+/// doesn't count as calling it.
+pub fn find_unused(module: &ast::Module, resolutions: &Resolutions) -> Vec<UnusedWarning> {
+    let mut collector = UseCollector {
+        exprs: &module.exprs,
+        resolutions,
+        function: None,
+        used: HashSet::new(),
+    };
+    collector.visit_module(module);
+
+    let mut warnings: Vec<_> = resolutions
+        .defs()
+        .filter(|&(id, def)| {
+            !collector.used.contains(&id)
+                && !def.name.starts_with('_')
+                && !resolutions.is_public(id)
+                && resolutions.file(id).is_none()
+        })
+        .filter_map(|(_, def)| {
+            let kind = match def.kind {
+                DefKind::Local => UnusedWarningKind::Variable(def.name.clone()),
+                DefKind::Func if def.name != ENTRY_POINT => {
+                    UnusedWarningKind::Function(def.name.clone())
+                }
+                _ => return None,
+            };
+            Some(UnusedWarning {
+                kind,
+                span: def.span,
+            })
+        })
+        .collect();
+
+    warnings.sort_by_key(|warning| warning.span.start);
+    warnings
+}
+
+/// Collects the definitions that are read somewhere.
+struct UseCollector<'ast> {
+    exprs: &'ast ast::ExprArena,
+    resolutions: &'ast Resolutions,
+    /// The function whose body is visited.
+    function: Option<DefId>,
+    used: HashSet<DefId>,
+}
+
+impl<'ast> Visitor<'ast> for UseCollector<'ast> {
+    fn exprs(&self) -> &'ast ast::ExprArena {
+        self.exprs
+    }
+
+    fn visit_func(&mut self, func: &'ast ast::FuncDecl) {
+        let outer = mem::replace(&mut self.function, self.resolutions.decl(func.id));
+        visit::walk_func(self, func);
+        self.function = outer;
+    }
+
+    fn visit_expr(&mut self, id: ast::ExprId) {
+        match &self.exprs[id] {
+            ast::Expr::Identifier(_) | ast::Expr::Path(_) => {
+                if let Some(def) = self.resolutions.expr(id)
+                    && Some(def) != self.function
+                {
+                    self.used.insert(def);
+                }
+            }
+            // the target is written, not read.
+            ast::Expr::Assign(assign) if self.is_variable(assign.target) => {
+                self.visit_expr(assign.value);
+            }
+            _ => visit::walk_expr(self, id),
+        }
+    }
+}
+
+impl UseCollector<'_> {
+    fn is_variable(&self, id: ast::ExprId) -> bool {
+        match &self.exprs[id] {
+            ast::Expr::Identifier(_) => true,
+            ast::Expr::Paren(paren) => self.is_variable(paren.expr),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use syntax::{explain::explain, lexer::Lexer, parser::Parser};
+
+    use crate::{
+        resolve::resolve,
+        unused::{UnusedWarningKind, find_unused},
+    };
+
+    #[test]
+    fn unused() {
+        let source = "
+            fn helper() {}
+            fn fact(n: i64) -> i64 { if n == 0 { 1 } else { n * fact(n - 1) } }
+            fn _ignored() {}
+            fn used() {}
+            fn main() {
+                used();
+                let a = 1;
+                let _b = 2;
+                let c = 3;
+                c = a;
+                match a { x => {} }
+            }
+        ";
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let (module, _) = Parser::new(&tokens).parse_module();
+        let resolved = resolve(&module);
+
+        let warnings = find_unused(&module, &resolved.resolutions);
+        let found: Vec<_> = warnings
+            .iter()
+            .map(|warning| (&warning.kind, &source[warning.span.start..warning.span.end]))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (&UnusedWarningKind::Function("helper".to_string()), "helper"),
+                (&UnusedWarningKind::Function("fact".to_string()), "fact"),
+                (&UnusedWarningKind::Variable("c".to_string()), "c"),
+                (&UnusedWarningKind::Variable("x".to_string()), "x"),
+            ]
+        );
+
+        for warning in &warnings {
+            assert!(explain(warning.kind.code()).is_some());
+        }
+    }
+}
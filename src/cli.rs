@@ -1,7 +1,216 @@
 use std::path::PathBuf;
 
+use ir::pass::{self, PASSES, Pass, PassManager};
+use syntax::diagnostics::{ColorChoice, LintLevel, LintLevels};
+
 #[derive(Debug, clap::Parser)]
 #[clap(name = "elanc", about = "ELAN Compiler")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+    /// Whether to color diagnostics.
+    #[arg(long, global = true, default_value = "auto")]
+    pub color: Color,
+    /// The maximum number of errors to report, where `0` reports all of them.
+    #[arg(long, global = true, default_value_t = 20, value_name = "N")]
+    pub error_limit: usize,
+    /// The format diagnostics are written to stderr in.
+    #[arg(long, global = true, default_value = "human")]
+    pub diagnostic_format: DiagnosticFormat,
+    /// A catalog of translated diagnostic messages, with a `CODE = message` pair per line.
+    #[arg(long, global = true, value_name = "FILE")]
+    pub messages: Option<PathBuf>,
+}
+
+#[derive(Debug, clap::Subcommand)]
 pub enum Command {
-    Tokenize { file: PathBuf },
+    Tokenize {
+        file: PathBuf,
+    },
+    /// Checks the file and prints its syntax tree, failing on errors and denied lints.
+    Parse {
+        file: PathBuf,
+        /// Additionally writes the given representation next to the source file.
+        #[arg(long)]
+        emit: Option<Emit>,
+        #[command(flatten)]
+        passes: PassArgs,
+        /// Enables an unstable language feature.
+        #[arg(long)]
+        experimental: Vec<Experimental>,
+        #[command(flatten)]
+        lints: LintArgs,
+    },
+    /// Compiles the file to bytecode and runs it, exiting with the `i32` `main` returns.
+    Run {
+        file: PathBuf,
+        /// The virtual machine the program is run on.
+        #[arg(long, default_value = "register")]
+        vm: Vm,
+        #[command(flatten)]
+        passes: PassArgs,
+        /// Enables an unstable language feature.
+        #[arg(long)]
+        experimental: Vec<Experimental>,
+        #[command(flatten)]
+        lints: LintArgs,
+    },
+    /// Reports lints, optionally applying their suggested fixes to the file.
+    Lint {
+        file: PathBuf,
+        #[arg(long)]
+        fix: bool,
+        #[command(flatten)]
+        lints: LintArgs,
+    },
+    /// Applies all machine-applicable suggested fixes to the file until none are left.
+    Fix {
+        file: PathBuf,
+        #[command(flatten)]
+        lints: LintArgs,
+    },
+    /// Reports the structural changes between two versions of a file.
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+    },
+    /// Minimizes a file that makes the compiler fail into a small reproducer.
+    Reduce {
+        file: PathBuf,
+    },
+    /// Prints the extended description of a diagnostic code like `E0001`.
+    Explain {
+        code: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum Emit {
+    /// A Graphviz rendering of the syntax tree.
+    AstDot,
+    /// The lossless syntax tree, including whitespace and comments.
+    Cst,
+    /// A Graphviz rendering of which functions call which.
+    Callgraph,
+    /// The intermediate representation of the program the backends compile.
+    Ir,
+    /// A Graphviz rendering of the control flow graph of each function in the IR.
+    CfgDot,
+    /// A disassembly of the bytecode the program is compiled to for running it.
+    Bytecode,
+}
+
+/// Returns the IR pass with the given name.
+fn parse_pass(name: &str) -> Result<Pass, String> {
+    pass::pass(name).ok_or_else(|| {
+        let names: Vec<_> = PASSES.iter().map(|pass| pass.name).collect();
+        format!("expected one of {}", names.join(", "))
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Vm {
+    /// Bytecode taking its operands from a stack of values.
+    Stack,
+    /// Bytecode reading its operands from the locals of each call, which dispatches
+    /// fewer ops.
+    Register,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum Color {
+    Always,
+    Never,
+    /// Colors if stderr is a terminal.
+    Auto,
+}
+
+impl From<Color> for ColorChoice {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Always => ColorChoice::Always,
+            Color::Never => ColorChoice::Never,
+            Color::Auto => ColorChoice::Auto,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DiagnosticFormat {
+    /// Messages with source snippets, similar to rustc.
+    Human,
+    /// A SARIF 2.1.0 log for code scanning tools.
+    Sarif,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Experimental {
+    /// Operator sections like `(+ 1)` or `(_ * 2)`, which only compile when called
+    /// directly like `(+ 1)(x)`, as there are no function values yet.
+    Sections,
+}
+
+/// The passes run over the IR before it is emitted or run.
+#[derive(Debug, clap::Args)]
+pub struct PassArgs {
+    /// The passes to run in order instead of the default pipeline, or none without a
+    /// value.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        num_args = 0..=1,
+        value_name = "PASS",
+        value_parser = parse_pass
+    )]
+    passes: Option<Vec<Pass>>,
+    /// Prints the IR to stderr after each pass.
+    #[arg(long)]
+    pub print_ir_after_all: bool,
+    /// Prints how long each pass took to stderr.
+    #[arg(long)]
+    pub time_passes: bool,
+}
+
+impl PassArgs {
+    /// Returns the [PassManager] running the configured pipeline.
+    pub fn manager(&self) -> PassManager {
+        match &self.passes {
+            Some(passes) => PassManager::new(passes.clone()),
+            None => PassManager::default(),
+        }
+    }
+}
+
+/// The level of each lint, where `warnings` names all of them.
+#[derive(Debug, clap::Args)]
+pub struct LintArgs {
+    /// Reports the warnings of a lint.
+    #[arg(short = 'W', long = "warn", value_name = "LINT")]
+    warn: Vec<String>,
+    /// Turns the warnings of a lint into errors.
+    #[arg(short = 'D', long = "deny", value_name = "LINT")]
+    deny: Vec<String>,
+    /// Silences the warnings of a lint.
+    #[arg(short = 'A', long = "allow", value_name = "LINT")]
+    allow: Vec<String>,
+}
+
+impl LintArgs {
+    /// Returns the configured [LintLevels], where denying a lint takes precedence
+    /// over warning about it, which takes precedence over allowing it.
+    pub fn levels(&self) -> LintLevels {
+        let mut levels = LintLevels::default();
+
+        for (lints, level) in [
+            (&self.allow, LintLevel::Allow),
+            (&self.warn, LintLevel::Warn),
+            (&self.deny, LintLevel::Deny),
+        ] {
+            for lint in lints {
+                levels.set(lint.as_str(), level);
+            }
+        }
+
+        levels
+    }
 }
@@ -0,0 +1,239 @@
+use std::fmt;
+
+use syntax::ast::{self, pretty};
+
+/// A structural change between two versions of a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// An item that only exists in the new module.
+    Added { signature: String },
+    /// An item that only exists in the old module.
+    Removed { signature: String },
+    /// An item that was renamed without changing the rest of its signature.
+    Renamed { from: String, to: String },
+    /// An item whose signature changed between the modules.
+    Changed { old: String, new: String },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added { signature } => write!(f, "+ {signature}"),
+            Change::Removed { signature } => write!(f, "- {signature}"),
+            Change::Renamed { from, to } => write!(f, "renamed {from} to {to}"),
+            Change::Changed { old, new } => write!(f, "~ {old}\n  => {new}"),
+        }
+    }
+}
+
+/// The name and signature of an [ast::Item] that is compared across modules.
+struct ItemSignature<'a> {
+    name: &'a str,
+    /// The signature without the item's name, used to detect renames.
+    shape: String,
+    signature: String,
+}
+
+/// Compares two modules and reports the structural [Change]s between them.
+pub fn diff_modules(old: &ast::Module, new: &ast::Module) -> Vec<Change> {
+    let old_items = signatures(old);
+    let new_items = signatures(new);
+
+    let mut changes = Vec::new();
+    let mut removed = Vec::new();
+
+    for old_item in &old_items {
+        match new_items.iter().find(|item| item.name == old_item.name) {
+            Some(new_item) if new_item.signature != old_item.signature => {
+                changes.push(Change::Changed {
+                    old: old_item.signature.clone(),
+                    new: new_item.signature.clone(),
+                });
+            }
+            Some(_) => {}
+            None => removed.push(old_item),
+        }
+    }
+
+    let mut added: Vec<_> = new_items
+        .iter()
+        .filter(|new_item| old_items.iter().all(|item| item.name != new_item.name))
+        .collect();
+
+    // an item that was removed and one that was added with the same shape are
+    // reported as a rename if the match is unambiguous.
+    let removed_shapes: Vec<_> = removed.iter().map(|item| item.shape.clone()).collect();
+
+    removed.retain(|old_item| {
+        let shape_count = |shapes: &mut dyn Iterator<Item = &String>| {
+            shapes.filter(|shape| **shape == old_item.shape).count()
+        };
+
+        if shape_count(&mut removed_shapes.iter()) != 1
+            || shape_count(&mut added.iter().map(|item| &item.shape)) != 1
+        {
+            return true;
+        }
+
+        let position = added
+            .iter()
+            .position(|item| item.shape == old_item.shape)
+            .unwrap();
+        let new_item = added.remove(position);
+
+        changes.push(Change::Renamed {
+            from: old_item.name.to_string(),
+            to: new_item.name.to_string(),
+        });
+
+        false
+    });
+
+    changes.extend(removed.into_iter().map(|item| Change::Removed {
+        signature: item.signature.clone(),
+    }));
+    changes.extend(added.into_iter().map(|item| Change::Added {
+        signature: item.signature.clone(),
+    }));
+
+    changes
+}
+
+fn signatures(module: &ast::Module) -> Vec<ItemSignature<'_>> {
+    let exprs = &module.exprs;
+    module
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ast::Item::Func(func) => {
+                let params: Vec<_> = func
+                    .params
+                    .iter()
+                    .map(|param| format!("{}: {}", param.name.text, param.ty.display(exprs)))
+                    .collect();
+
+                let mut shape = format!("({})", params.join(", "));
+                if let Some(return_type) = &func.return_type {
+                    shape = format!("{shape} -> {}", return_type.display(exprs));
+                }
+
+                let generics = pretty::generics(&func.generics);
+
+                Some(ItemSignature {
+                    name: &func.name.text,
+                    signature: format!("fn {}{generics}{shape}", func.name.text),
+                    shape: format!("fn{generics}{shape}"),
+                })
+            }
+            ast::Item::Const(const_decl) => Some(ItemSignature {
+                name: &const_decl.name.text,
+                signature: format!(
+                    "const {}: {}",
+                    const_decl.name.text,
+                    const_decl.ty.display(exprs)
+                ),
+                shape: format!("const: {}", const_decl.ty.display(exprs)),
+            }),
+            ast::Item::Struct(struct_decl) => {
+                let fields: Vec<_> = struct_decl
+                    .fields
+                    .iter()
+                    .map(|field| format!("{}: {}", field.name.text, field.ty.display(exprs)))
+                    .collect();
+
+                let generics = pretty::generics(&struct_decl.generics);
+                let shape = format!("{generics} {{ {} }}", fields.join(", "));
+
+                Some(ItemSignature {
+                    name: &struct_decl.name.text,
+                    signature: format!("struct {}{shape}", struct_decl.name.text),
+                    shape: format!("struct{shape}"),
+                })
+            }
+            ast::Item::Enum(enum_decl) => {
+                let variants: Vec<_> = enum_decl
+                    .variants
+                    .iter()
+                    .map(|variant| variant.name.text.as_str())
+                    .collect();
+
+                let shape = format!("{{ {} }}", variants.join(", "));
+
+                Some(ItemSignature {
+                    name: &enum_decl.name.text,
+                    signature: format!("enum {} {shape}", enum_decl.name.text),
+                    shape: format!("enum {shape}"),
+                })
+            }
+            ast::Item::Trait(trait_decl) => {
+                let methods: Vec<_> = trait_decl
+                    .methods
+                    .iter()
+                    .map(|method| method.name.text.as_str())
+                    .collect();
+
+                let generics = pretty::generics(&trait_decl.generics);
+                let shape = format!("{generics} {{ {} }}", methods.join(", "));
+
+                Some(ItemSignature {
+                    name: &trait_decl.name.text,
+                    signature: format!("trait {}{shape}", trait_decl.name.text),
+                    shape: format!("trait{shape}"),
+                })
+            }
+            ast::Item::Import(_) | ast::Item::Error(_) => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use syntax::{ast, error::SyntaxError, lexer::Lexer, parser::Parser};
+
+    use crate::diff::{Change, diff_modules};
+
+    fn parse(source: &str) -> Result<ast::Module, SyntaxError> {
+        let tokens = Lexer::new(source).collect_tokens()?;
+        let (module, errors) = Parser::new(&tokens).parse_module();
+
+        match errors.first() {
+            Some(err) => Err(*err),
+            None => Ok(module),
+        }
+    }
+
+    #[test]
+    fn changes() -> Result<(), SyntaxError> {
+        let old = parse(
+            "fn keep() {} fn add(a: i64) -> i64 { a } fn old_name(x: f64) {} fn gone() {} enum E { A }",
+        )?;
+        let new = parse(
+            "fn keep() { 1 } fn add(a: i64, b: i64) -> i64 { a + b } fn new_name(x: f64) {} enum E { A, B } fn fresh() -> i64 { 0 }",
+        )?;
+
+        let expected = vec![
+            Change::Changed {
+                old: "fn add(a: i64) -> i64".to_string(),
+                new: "fn add(a: i64, b: i64) -> i64".to_string(),
+            },
+            Change::Changed {
+                old: "enum E { A }".to_string(),
+                new: "enum E { A, B }".to_string(),
+            },
+            Change::Renamed {
+                from: "old_name".to_string(),
+                to: "new_name".to_string(),
+            },
+            Change::Removed {
+                signature: "fn gone()".to_string(),
+            },
+            Change::Added {
+                signature: "fn fresh() -> i64".to_string(),
+            },
+        ];
+
+        assert_eq!(diff_modules(&old, &new), expected);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+
+use semantic::{
+    callgraph::{self, CallGraph, RecursionWarning},
+    check::{self, Checked, TypeError, TypeTable, TypeWarning},
+    consts::{self, ConstError, ConstValues, Evaluated},
+    fold::{self, FoldError},
+    lints::{Lint, LintRegistry},
+    mono::{self, Monomorphized},
+    program::{self, EntryError, Program},
+    query::Query,
+    resolve::{self, Exports, Resolutions, ResolveError, Resolved},
+    unreachable::{self, UnreachableWarning},
+    unused::{self, UnusedWarning},
+};
+use syntax::{
+    ast,
+    diagnostics::{Diagnostic, LintLevels},
+    error::SyntaxError,
+    experiments::Experiments,
+    lexer::Lexer,
+    limits::Limits,
+    lints::{self, LiteralWarning, Suppressions},
+    parser::Parser,
+    token::Span,
+};
+
+use crate::modules::ImportError;
+
+/// A custom pass over the syntax tree, run by the [Driver] after parsing.
+///
+/// Analyses and lints can be prototyped by implementing this trait and
+/// registering the pass with [Driver::register_ast_pass].
+pub trait AstPass {
+    /// Returns the name of the pass, used when reporting its warnings.
+    fn name(&self) -> &str;
+
+    /// Runs the pass on the given [ast::Module], which it may modify,
+    /// pushing any findings to `warnings`.
+    fn run(&mut self, module: &mut ast::Module, warnings: &mut Vec<PassWarning>);
+}
+
+/// A warning reported by an [AstPass].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassWarning {
+    pub message: String,
+    pub span: Span,
+}
+
+/// Everything produced by running the [Driver] on a source file.
+#[derive(Debug)]
+pub struct Compilation {
+    /// The parsed module, containing error nodes where `errors` were found.
+    pub module: ast::Module,
+    pub errors: Vec<SyntaxError>,
+    pub literal_warnings: Vec<LiteralWarning>,
+    /// The warnings of each [AstPass] along with the name of the pass.
+    pub pass_warnings: Vec<(String, PassWarning)>,
+    /// The imports that couldn't be loaded, see [crate::modules::load].
+    pub import_errors: Vec<ImportError>,
+    /// What the names in `module` refer to.
+    pub resolutions: Resolutions,
+    pub resolve_errors: Vec<ResolveError>,
+    /// The values of the constants in `module`.
+    pub consts: ConstValues,
+    pub const_errors: Vec<ConstError>,
+    /// The types of the expressions in `module`.
+    pub types: TypeTable,
+    pub type_errors: Vec<TypeError>,
+    pub type_warnings: Vec<TypeWarning>,
+    /// The specialized copies of the generic functions `module` uses.
+    pub instances: Monomorphized,
+    /// The warnings of the registered [Lint]s, and about unknown lints named by `@allow`
+    /// attributes.
+    pub lint_warnings: Vec<Diagnostic>,
+    pub unused_warnings: Vec<UnusedWarning>,
+    pub unreachable_warnings: Vec<UnreachableWarning>,
+    /// The functions of `module` and the functions they call.
+    pub call_graph: CallGraph,
+    pub recursion_warnings: Vec<RecursionWarning>,
+    /// The errors found while folding constants, after which `module` holds the results.
+    pub fold_errors: Vec<FoldError>,
+}
+
+impl Compilation {
+    /// Returns a [Query] over the checked `module`, answering what its names refer to
+    /// and which types its expressions have.
+    pub fn query(&self) -> Query<'_> {
+        Query::new(&self.module, &self.resolutions, &self.types)
+    }
+
+    /// Validates the entry point of the checked `module` and builds the [Program] the
+    /// backends run, see [program::build_program].
+    pub fn program(&self) -> Result<Program, Vec<EntryError>> {
+        program::build_program(
+            &self.module,
+            &self.resolutions,
+            &self.types,
+            &self.call_graph,
+        )
+    }
+
+    /// Returns all warnings and errors as [Diagnostic]s, the warnings of each [AstPass]
+    /// tagged with its name. Warnings allowed by an `@allow` attribute are left out.
+    pub fn diagnostics(&self, limits: &Limits) -> Vec<Diagnostic> {
+        let literal_warnings = self.literal_warnings.iter().map(LiteralWarning::diagnostic);
+        let pass_warnings = self.pass_warnings.iter().map(|(pass, warning)| {
+            Diagnostic::warning(format!("{} [{pass}]", warning.message), warning.span)
+                .with_lint(pass)
+        });
+        let type_warnings = self.type_warnings.iter().map(TypeWarning::diagnostic);
+        let unused_warnings = self.unused_warnings.iter().map(UnusedWarning::diagnostic);
+        let unreachable_warnings = self
+            .unreachable_warnings
+            .iter()
+            .map(UnreachableWarning::diagnostic);
+        let recursion_warnings = self
+            .recursion_warnings
+            .iter()
+            .map(RecursionWarning::diagnostic);
+        let errors = self.errors.iter().map(|err| err.diagnostic(limits));
+        let import_errors = self.import_errors.iter().map(ImportError::diagnostic);
+        let resolve_errors = self.resolve_errors.iter().map(ResolveError::diagnostic);
+        let const_errors = self.const_errors.iter().map(ConstError::diagnostic);
+        let type_errors = self
+            .type_errors
+            .iter()
+            .map(|err| err.diagnostic(&self.resolutions));
+        let fold_errors = self.fold_errors.iter().map(FoldError::diagnostic);
+        let suppressions = Suppressions::collect(&self.module);
+
+        literal_warnings
+            .chain(pass_warnings)
+            .chain(type_warnings)
+            .chain(self.lint_warnings.iter().cloned())
+            .chain(unused_warnings)
+            .chain(unreachable_warnings)
+            .chain(recursion_warnings)
+            .filter(|warning| !suppressions.suppresses(warning))
+            .chain(errors)
+            .chain(import_errors)
+            .chain(resolve_errors)
+            .chain(const_errors)
+            .chain(type_errors)
+            .chain(fold_errors)
+            .collect()
+    }
+}
+
+/// The lints reported by the stages of the compiler rather than by [Lint]s.
+const COMPILER_LINTS: &[&str] = &[
+    "leading_zeros",
+    "invalid_digits_for_base",
+    "f32_precision_loss",
+    "unreachable_patterns",
+    "unused_variables",
+    "unused_functions",
+    "unreachable_code",
+    "unbounded_recursion",
+    lints::UNKNOWN_LINTS,
+];
+
+/// Runs the compiler pipeline on source text.
+pub struct Driver {
+    limits: Limits,
+    experiments: Experiments,
+    ast_passes: Vec<Box<dyn AstPass>>,
+    lints: LintRegistry,
+}
+
+impl Driver {
+    /// Creates a new [Driver] enforcing the given [Limits].
+    pub fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            experiments: Experiments::default(),
+            ast_passes: Vec::new(),
+            lints: LintRegistry::with_builtins(),
+        }
+    }
+
+    /// Returns the [Limits] the [Driver] enforces.
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Returns the unstable language [Experiments] the [Driver] enables.
+    pub fn experiments(&self) -> Experiments {
+        self.experiments
+    }
+
+    /// Enables the given unstable language [Experiments].
+    pub fn set_experiments(&mut self, experiments: Experiments) {
+        self.experiments = experiments;
+    }
+
+    /// Registers an [AstPass] to run after parsing, after all previously registered passes.
+    pub fn register_ast_pass(&mut self, pass: impl AstPass + 'static) {
+        self.ast_passes.push(Box::new(pass));
+    }
+
+    /// Registers a [Lint] to run after type checking, see [LintRegistry::register].
+    pub fn register_lint(&mut self, lint: impl Lint + 'static) {
+        self.lints.register(lint);
+    }
+
+    /// Returns the [Lint]s the [Driver] runs.
+    pub fn lints(&self) -> &LintRegistry {
+        &self.lints
+    }
+
+    /// Returns whether the name can be given a level, because it names a lint of the
+    /// compiler, a registered [Lint] or [AstPass], or a group of lints.
+    pub fn is_known_lint(&self, name: &str) -> bool {
+        name == LintLevels::WARNINGS
+            || LintLevels::group(name).is_some()
+            || COMPILER_LINTS.contains(&name)
+            || self.lints.names().any(|lint| lint == name)
+            || self.ast_passes.iter().any(|pass| pass.name() == name)
+    }
+
+    /// Lexes and parses the given source text, runs the registered passes on it and
+    /// resolves, evaluates the constants of and type checks the resulting module,
+    /// specializes its generic functions, runs the registered lints on it, builds its
+    /// call graph and folds its constant expressions.
+    ///
+    /// The passes also run on modules with syntax errors, which contain error nodes.
+    pub fn compile(&mut self, source: &str) -> Compilation {
+        self.compile_with_imports(source, &HashMap::new())
+    }
+
+    /// Compiles the source text like [Driver::compile], resolving paths into imported
+    /// modules with their [Exports] by module path, see [resolve::resolve_imports].
+    pub fn compile_with_imports(
+        &mut self,
+        source: &str,
+        imports: &HashMap<Vec<String>, Exports>,
+    ) -> Compilation {
+        let tokens = match Lexer::new(source).collect_tokens() {
+            Ok(tokens) => tokens,
+            Err(err) => {
+                return Compilation {
+                    module: ast::Module::default(),
+                    errors: vec![err],
+                    literal_warnings: Vec::new(),
+                    pass_warnings: Vec::new(),
+                    import_errors: Vec::new(),
+                    resolutions: Resolutions::default(),
+                    resolve_errors: Vec::new(),
+                    consts: ConstValues::default(),
+                    const_errors: Vec::new(),
+                    types: TypeTable::default(),
+                    type_errors: Vec::new(),
+                    type_warnings: Vec::new(),
+                    instances: Monomorphized::default(),
+                    lint_warnings: Vec::new(),
+                    unused_warnings: Vec::new(),
+                    unreachable_warnings: Vec::new(),
+                    call_graph: CallGraph::default(),
+                    recursion_warnings: Vec::new(),
+                    fold_errors: Vec::new(),
+                };
+            }
+        };
+        let mut literal_warnings = lints::lint_literals(&tokens);
+
+        let mut parser = Parser::with_limits(&tokens, self.limits);
+        parser.set_experiments(self.experiments);
+
+        let (mut module, errors) = parser.parse_module();
+        let mut pass_warnings = Vec::new();
+
+        for pass in &mut self.ast_passes {
+            let mut warnings = Vec::new();
+            pass.run(&mut module, &mut warnings);
+
+            let name = pass.name().to_string();
+            pass_warnings.extend(warnings.into_iter().map(|warning| (name.clone(), warning)));
+        }
+
+        let Resolved {
+            mut resolutions,
+            errors: resolve_errors,
+        } = resolve::resolve_imports(&module, imports);
+        let Evaluated {
+            values: consts,
+            errors: const_errors,
+        } = consts::eval_consts_with_limits(&module, &resolutions, self.limits);
+        let Checked {
+            types,
+            errors: type_errors,
+            warnings: type_warnings,
+            literal_warnings: f32_warnings,
+        } = check::check_with_limits(&module, &resolutions, &consts, self.limits);
+        literal_warnings.extend(f32_warnings);
+        let instances = mono::monomorphize(&resolutions, &types);
+        let mut lint_warnings = self.lints.run(source, &module, &resolutions, &types);
+        let suppressions = Suppressions::collect(&module);
+        let unknown = suppressions
+            .names()
+            .iter()
+            .filter(|(name, _)| !self.is_known_lint(name))
+            .map(|(name, span)| lints::unknown_lint(name, *span));
+        lint_warnings.extend(unknown);
+        let unused_warnings = unused::find_unused(&module, &resolutions);
+        let unreachable_warnings = unreachable::find_unreachable(&module, &resolutions, &types);
+        let call_graph = CallGraph::build(&module, &resolutions);
+        let recursion_warnings =
+            callgraph::find_unbounded_recursion(&module, &resolutions, &call_graph);
+        let fold_errors = fold::fold_constants(&mut module, &mut resolutions, &types);
+
+        Compilation {
+            module,
+            errors,
+            literal_warnings,
+            pass_warnings,
+            import_errors: Vec::new(),
+            resolutions,
+            resolve_errors,
+            consts,
+            const_errors,
+            types,
+            type_errors,
+            type_warnings,
+            instances,
+            lint_warnings,
+            unused_warnings,
+            unreachable_warnings,
+            call_graph,
+            recursion_warnings,
+            fold_errors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use syntax::{ast, limits::Limits};
+
+    use crate::driver::{AstPass, Driver, PassWarning};
+
+    struct NoMainPass;
+
+    impl AstPass for NoMainPass {
+        fn name(&self) -> &str {
+            "no-main"
+        }
+
+        fn run(&mut self, module: &mut ast::Module, warnings: &mut Vec<PassWarning>) {
+            for item in &module.items {
+                if let ast::Item::Func(func) = item
+                    && func.name.text == "main"
+                {
+                    warnings.push(PassWarning {
+                        message: "found a main function".to_string(),
+                        span: func.name.span,
+                    });
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ast_pass() {
+        let mut driver = Driver::new(Limits::default());
+        driver.register_ast_pass(NoMainPass);
+
+        let compilation = driver.compile("fn helper() {} fn main() { helper(); }");
+        let warnings: Vec<_> = compilation
+            .pass_warnings
+            .iter()
+            .map(|(pass, warning)| (pass.as_str(), warning.message.as_str()))
+            .collect();
+
+        assert_eq!(warnings, [("no-main", "found a main function")]);
+
+        let diagnostics = compilation.diagnostics(driver.limits());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "found a main function [no-main]");
+        assert!(!diagnostics[0].is_error());
+    }
+
+    #[test]
+    fn unknown_lints() {
+        let mut driver = Driver::new(Limits::default());
+        driver.register_ast_pass(NoMainPass);
+
+        let input = "@allow(\"no-main\", unused, bogus) fn main() { let x = 1; }";
+        let compilation = driver.compile(input);
+        let messages: Vec<_> = compilation
+            .diagnostics(driver.limits())
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect();
+
+        assert_eq!(messages, ["unknown lint `bogus`"]);
+        assert!(driver.is_known_lint("unused_variables"));
+        assert!(!driver.is_known_lint("bogus"));
+    }
+
+    #[test]
+    fn long_operator_chain() {
+        let mut driver = Driver::new(Limits::default());
+        let chain = vec!["1"; 20_000].join(" + ");
+        let source = format!("fn main() {{ let _x = {chain}; }}");
+
+        let codes: Vec<_> = driver
+            .compile(&source)
+            .diagnostics(driver.limits())
+            .into_iter()
+            .map(|diagnostic| diagnostic.code)
+            .collect();
+        assert_eq!(codes, [Some("E0008")]);
+    }
+}
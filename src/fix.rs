@@ -0,0 +1,236 @@
+use syntax::{
+    diagnostics::{Applicability, Diagnostic, LintLevels, Suggestion},
+    token::Span,
+};
+
+use crate::driver::Driver;
+
+/// A replacement of the source text covered by a [Span].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub span: Span,
+    pub replacement: String,
+}
+
+impl From<&Suggestion> for Edit {
+    fn from(suggestion: &Suggestion) -> Self {
+        Edit {
+            span: suggestion.span,
+            replacement: suggestion.replacement.clone(),
+        }
+    }
+}
+
+/// The result of [fix], applying suggestions until none are left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixed {
+    pub source: String,
+    /// The number of applied suggestions.
+    pub applied: usize,
+    /// The number of times the source was compiled and edited.
+    pub rounds: usize,
+}
+
+/// Gives up on reaching a fixed point after this many rounds, in case fixes keep
+/// suggesting each other.
+const MAX_FIX_ROUNDS: usize = 16;
+
+/// Compiles the source and applies all [Applicability::MachineApplicable] suggestions of
+/// diagnostics that aren't allowed by the [LintLevels], repeating this on the result
+/// until there is nothing left to fix.
+///
+/// Overlapping suggestions are applied one at a time, in separate rounds.
+pub fn fix(driver: &mut Driver, levels: &LintLevels, source: &str) -> Fixed {
+    let mut fixed = Fixed {
+        source: source.to_string(),
+        applied: 0,
+        rounds: 0,
+    };
+
+    while fixed.rounds < MAX_FIX_ROUNDS {
+        let compilation = driver.compile(&fixed.source);
+
+        let diagnostics: Vec<_> = compilation
+            .diagnostics(driver.limits())
+            .into_iter()
+            .filter_map(|diagnostic| levels.apply(diagnostic))
+            .collect();
+        let edits = machine_applicable(&diagnostics);
+
+        if edits.is_empty() {
+            break;
+        }
+
+        let count = edits.len();
+        let (source, conflicts) = apply_edits(&fixed.source, edits);
+
+        if source == fixed.source {
+            break;
+        }
+
+        fixed.source = source;
+        fixed.applied += count - conflicts.len();
+        fixed.rounds += 1;
+    }
+
+    fixed
+}
+
+/// Returns the [Edit]s of the [Applicability::MachineApplicable] suggestions of the
+/// given diagnostics, including those of syntax errors.
+pub fn machine_applicable(diagnostics: &[Diagnostic]) -> Vec<Edit> {
+    diagnostics
+        .iter()
+        .flat_map(|diagnostic| &diagnostic.suggestions)
+        .filter(|suggestion| suggestion.applicability == Applicability::MachineApplicable)
+        .map(Edit::from)
+        .collect()
+}
+
+/// Applies the given [Edit]s to the source text.
+///
+/// Edits overlapping an earlier edit are not applied and are returned instead,
+/// so applying them again to the result can resolve them one at a time.
+pub fn apply_edits(source: &str, mut edits: Vec<Edit>) -> (String, Vec<Edit>) {
+    edits.sort_by_key(|edit| (edit.span.start, edit.span.end));
+
+    let mut applied = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for edit in edits {
+        match applied.last() {
+            Some(Edit { span, .. }) if edit.span.start < span.end => conflicts.push(edit),
+            _ => applied.push(edit),
+        }
+    }
+
+    // spans count chars, so map them to byte offsets before slicing.
+    let mut byte_offsets: Vec<usize> = source.char_indices().map(|(offset, _)| offset).collect();
+    byte_offsets.push(source.len());
+
+    let mut result = String::with_capacity(source.len());
+    let mut last_end = 0;
+
+    for edit in &applied {
+        result.push_str(&source[byte_offsets[last_end]..byte_offsets[edit.span.start]]);
+        result.push_str(&edit.replacement);
+        last_end = edit.span.end;
+    }
+
+    result.push_str(&source[byte_offsets[last_end]..]);
+
+    (result, conflicts)
+}
+
+#[cfg(test)]
+mod test {
+    use syntax::{
+        diagnostics::{LintLevel, LintLevels},
+        limits::Limits,
+        token::Span,
+    };
+
+    use crate::{
+        driver::Driver,
+        fix::{Edit, Fixed, apply_edits, fix, machine_applicable},
+    };
+
+    #[test]
+    fn edits() {
+        let edit = |start, end, replacement: &str| Edit {
+            span: Span { start, end },
+            replacement: replacement.to_string(),
+        };
+
+        let source = "a = 010 + ü07;";
+        let edits = vec![
+            edit(11, 12, ""),
+            edit(4, 5, ""),
+            edit(4, 7, "8"),
+            edit(14, 14, " // done"),
+        ];
+
+        let (result, conflicts) = apply_edits(source, edits);
+
+        assert_eq!(result, "a = 10 + ü7; // done");
+        assert_eq!(conflicts, vec![edit(4, 7, "8")]);
+    }
+
+    #[test]
+    fn fixed_point() {
+        let mut driver = Driver::new(Limits::default());
+        let source = "fn main() { let a = 0010; let b = 007; let c = 1 }";
+
+        let fixed = fix(&mut driver, &LintLevels::default(), source);
+
+        // the `;` missing after `c` is inserted along with the lint fixes.
+        assert_eq!(
+            fixed,
+            Fixed {
+                source: "fn main() { let a = 10; let b = 7; let c = 1; }".to_string(),
+                applied: 3,
+                rounds: 1,
+            }
+        );
+
+        let mut levels = LintLevels::default();
+        levels.set("leading_zeros", LintLevel::Allow);
+
+        let fixed = fix(&mut driver, &levels, source);
+        assert_eq!(
+            fixed.source,
+            "fn main() { let a = 0010; let b = 007; let c = 1; }"
+        );
+    }
+
+    #[test]
+    fn missing_semicolon() {
+        let mut driver = Driver::new(Limits::default());
+        let source = "fn main() {\n    let _x = 1\n}\n";
+
+        let diagnostics = driver.compile(source).diagnostics(driver.limits());
+        let (fixed, conflicts) = apply_edits(source, machine_applicable(&diagnostics));
+
+        assert_eq!(fixed, "fn main() {\n    let _x = 1;\n}\n");
+        assert_eq!(conflicts, []);
+        assert_eq!(driver.compile(&fixed).diagnostics(driver.limits()), []);
+    }
+
+    #[test]
+    fn f32_literals() {
+        let mut driver = Driver::new(Limits::default());
+        let source =
+            "fn main() { let _a: f32 = 16777217.0; let _b: f32 = 1.0e40; let _c: f32 = 0.1; }";
+
+        let compilation = driver.compile(source);
+        let warnings: Vec<_> = compilation
+            .diagnostics(driver.limits())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.code, diagnostic.primary_span))
+            .collect();
+        assert_eq!(
+            warnings,
+            [
+                (Some("W0003"), Span { start: 26, end: 36 }),
+                (Some("W0003"), Span { start: 52, end: 58 }),
+            ]
+        );
+
+        // the literal too large for an `f32` has nothing to be replaced with.
+        let fixed = fix(&mut driver, &LintLevels::default(), source);
+        assert_eq!(
+            fixed.source,
+            "fn main() { let _a: f32 = 16777216.0; let _b: f32 = 1.0e40; let _c: f32 = 0.1; }"
+        );
+        assert_eq!(fixed.applied, 1);
+
+        // the fixed literal is still a float and no longer reported.
+        let codes: Vec<_> = driver
+            .compile(&fixed.source)
+            .diagnostics(driver.limits())
+            .into_iter()
+            .map(|diagnostic| diagnostic.code)
+            .collect();
+        assert_eq!(codes, [Some("W0003")]);
+    }
+}
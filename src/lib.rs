@@ -0,0 +1,6 @@
+pub mod diff;
+pub mod driver;
+pub mod fix;
+pub mod manifest;
+pub mod modules;
+pub mod reduce;
@@ -1,29 +1,121 @@
-use std::{fs, path::PathBuf};
+use std::{fs, io, path::PathBuf, process};
 
 use clap::Parser;
-use syntax::lexer::Lexer;
+use compiler::{
+    diff,
+    driver::Driver,
+    fix,
+    manifest::Manifest,
+    modules::{self, LoadedModule},
+    reduce,
+};
+use ir::{func::Module, inst::Const};
+use syntax::{
+    ast, cst,
+    diagnostics::{self, ColorChoice, Diagnostic, EmitDiagnostic, LintLevels, TerminalEmitter},
+    experiments::Experiments,
+    explain,
+    lexer::Lexer,
+    limits::Limits,
+    lints,
+    messages::{self, Catalog},
+    sarif::SarifEmitter,
+    source::{FileId, SourceDatabase, SourceFile},
+    token::Span,
+};
+use vm::{
+    compile::{compile, compile_registers},
+    register::RegisterVm,
+    stack::StackVm,
+};
 
-use crate::cli::Command;
+use crate::cli::{Cli, Command, DiagnosticFormat, Emit, Experimental, PassArgs, Vm};
 
 mod cli;
 
 fn main() {
-    match Command::parse() {
-        Command::Tokenize { file } => tokenize_file(file),
+    let cli = Cli::parse();
+
+    if let Some(path) = &cli.messages
+        && !install_messages(path)
+    {
+        return;
+    }
+
+    let reporter = Reporter {
+        colored: ColorChoice::from(cli.color).enabled(&io::stderr()),
+        error_limit: cli.error_limit,
+        format: cli.diagnostic_format,
+    };
+
+    match cli.command {
+        Command::Tokenize { file } => tokenize_file(file, &reporter),
+        Command::Parse {
+            file,
+            emit,
+            passes,
+            experimental,
+            lints,
+        } => parse_file(
+            file,
+            emit,
+            &passes,
+            &experimental,
+            &lints.levels(),
+            &reporter,
+        ),
+        Command::Run {
+            file,
+            vm,
+            passes,
+            experimental,
+            lints,
+        } => run_file(file, vm, &passes, &experimental, &lints.levels(), &reporter),
+        Command::Lint { file, fix, lints } => lint_file(file, fix, &lints.levels(), &reporter),
+        Command::Fix { file, lints } => fix_file(file, &lints.levels(), &reporter),
+        Command::Diff { old, new } => diff_files(old, new, &reporter),
+        Command::Reduce { file } => reduce_file(file),
+        Command::Explain { code } => explain_code(&code),
+    }
+}
+
+/// Installs the message catalog at the given path, returning whether it could be loaded.
+fn install_messages(path: &PathBuf) -> bool {
+    let catalog = match fs::read_to_string(path) {
+        Ok(text) => Catalog::parse(&text).map_err(|err| err.to_string()),
+        Err(err) => Err(err.to_string()),
+    };
+
+    match catalog {
+        Ok(catalog) => messages::install(catalog).is_ok(),
+        Err(err) => {
+            eprintln!("error: failed to load message catalog");
+            eprintln!("{err}");
+            false
+        }
     }
 }
 
-fn tokenize_file(path: PathBuf) {
-    let content = match fs::read_to_string(&path) {
-        Ok(content) => content,
+/// Reads the file at the given path into the [SourceDatabase].
+fn read_file(db: &mut SourceDatabase, path: &PathBuf) -> Option<FileId> {
+    match fs::read_to_string(path) {
+        Ok(content) => Some(db.add(SourceFile::new(path.display().to_string(), content))),
         Err(err) => {
             eprintln!("error: failed to read file");
             eprintln!("{err:?}");
-            return;
+            None
         }
+    }
+}
+
+fn tokenize_file(path: PathBuf, reporter: &Reporter) {
+    let mut db = SourceDatabase::default();
+    let Some(id) = read_file(&mut db, &path) else {
+        return;
     };
+    let file = db.get(id);
 
-    let lexer = Lexer::new(&content);
+    let lexer = Lexer::new(file.text());
     let tokens = lexer.collect_tokens();
 
     match tokens {
@@ -32,6 +124,408 @@ fn tokenize_file(path: PathBuf) {
                 println!("{token:?}");
             }
         }
-        Err(err) => eprintln!("error: {err:?}"),
+        Err(err) => {
+            let diagnostic = err.diagnostic(&Limits::default()).with_file(id);
+            reporter.report(&db, vec![diagnostic]);
+        }
+    }
+}
+
+/// Checks the file at the given path along with the modules it imports, reporting
+/// their diagnostics. Returns the loaded modules, the file's last, unless there were
+/// errors.
+fn check_file(
+    path: &PathBuf,
+    experimental: &[Experimental],
+    levels: &LintLevels,
+    reporter: &Reporter,
+) -> Option<(SourceDatabase, FileId, Vec<LoadedModule>)> {
+    let manifest = match Manifest::discover(path) {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            eprintln!("error: failed to load manifest");
+            eprintln!("{err}");
+            return None;
+        }
+    };
+
+    let mut db = SourceDatabase::default();
+    let id = read_file(&mut db, path)?;
+
+    let mut driver = Driver::new(manifest.limits);
+    driver.set_experiments(Experiments {
+        sections: experimental.contains(&Experimental::Sections),
+    });
+
+    let mut levels = levels.clone();
+    driver.lints().set_defaults(&mut levels);
+
+    let modules = modules::load(&mut driver, &mut db, id, |path| {
+        fs::read_to_string(path).ok()
+    });
+
+    let diagnostics: Vec<_> = unknown_lints(&driver, &levels)
+        .into_iter()
+        .chain(modules.iter().flat_map(|module| {
+            let diagnostics = module.compilation.diagnostics(driver.limits());
+            diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.with_file(module.file))
+        }))
+        .filter_map(|diagnostic| levels.apply(diagnostic))
+        .collect();
+
+    let failed = diagnostics.iter().any(Diagnostic::is_error);
+    reporter.report(&db, diagnostics);
+
+    if failed {
+        return None;
+    }
+
+    Some((db, id, modules))
+}
+
+/// Returns the warnings about the lints configured on the command line that the
+/// [Driver] doesn't know.
+fn unknown_lints(driver: &Driver, levels: &LintLevels) -> Vec<Diagnostic> {
+    levels
+        .configured()
+        .into_iter()
+        .filter(|lint| !driver.is_known_lint(lint))
+        .map(|lint| lints::unknown_lint(lint, Span::EOI))
+        .collect()
+}
+
+fn parse_file(
+    path: PathBuf,
+    emit: Option<Emit>,
+    passes: &PassArgs,
+    experimental: &[Experimental],
+    levels: &LintLevels,
+    reporter: &Reporter,
+) {
+    let Some((db, id, loaded)) = check_file(&path, experimental, levels, reporter) else {
+        process::exit(1);
+    };
+    // the root module is compiled last, after the modules it imports.
+    let compilation = &loaded
+        .last()
+        .expect("the root module is always loaded")
+        .compilation;
+    let file = db.get(id);
+    let module = &compilation.module;
+
+    match emit {
+        Some(Emit::Callgraph) => {
+            let dot = compilation.call_graph.to_dot(&compilation.resolutions);
+            write_file(&path.with_extension("callgraph.dot"), dot);
+        }
+        Some(Emit::AstDot) => write_file(&path.with_extension("ast.dot"), ast::to_dot(module)),
+        Some(Emit::Cst) => {
+            // the source lexed without errors, otherwise compilation would have failed.
+            let tokens = Lexer::new(file.text()).collect_tokens().unwrap_or_default();
+            let tree = cst::build(file.text(), &tokens, module);
+            write_file(&path.with_extension("cst"), tree.root().dump());
+        }
+        Some(emit @ (Emit::Ir | Emit::CfgDot | Emit::Bytecode)) => {
+            match modules::lower(&loaded, &db) {
+                Ok(mut ir) => {
+                    optimize(&mut ir, passes);
+                    match emit {
+                        Emit::Ir => write_file(&path.with_extension("elir"), ir.to_string()),
+                        Emit::CfgDot => write_file(&path.with_extension("cfg.dot"), ir.to_dot()),
+                        _ => write_file(&path.with_extension("elbc"), compile(&ir).to_string()),
+                    }
+                }
+                Err(diagnostics) => {
+                    reporter.report(&db, diagnostics);
+                    process::exit(1);
+                }
+            }
+        }
+        None => println!("{module:#?}"),
+    }
+}
+
+/// Compiles the file to bytecode and runs its `main` function, exiting with the code
+/// `main` returns or `101` if the program fails.
+fn run_file(
+    path: PathBuf,
+    vm: Vm,
+    passes: &PassArgs,
+    experimental: &[Experimental],
+    levels: &LintLevels,
+    reporter: &Reporter,
+) {
+    let Some((db, id, loaded)) = check_file(&path, experimental, levels, reporter) else {
+        process::exit(1);
+    };
+
+    let mut ir = match modules::lower(&loaded, &db) {
+        Ok(ir) => ir,
+        Err(diagnostics) => {
+            reporter.report(&db, diagnostics);
+            process::exit(1);
+        }
+    };
+    optimize(&mut ir, passes);
+
+    // lowering fails for programs without a valid `main` function.
+    let result = match vm {
+        Vm::Stack => {
+            let program = compile(&ir);
+            let main = program.function("main").expect("`main` is lowered");
+            StackVm::new(&program).run(main, Vec::new())
+        }
+        Vm::Register => {
+            let program = compile_registers(&ir);
+            let main = program.function("main").expect("`main` is lowered");
+            RegisterVm::new(&program).run(main, Vec::new())
+        }
+    };
+
+    match result {
+        Ok(Some(Const::Int(code))) => process::exit(code as i32),
+        Ok(_) => {}
+        Err(err) => {
+            match err.diagnostic() {
+                Some(diagnostic) => reporter.report(&db, vec![diagnostic.with_file(id)]),
+                None => eprintln!("error: {err}"),
+            }
+            process::exit(101);
+        }
+    }
+}
+
+/// Runs the configured passes over the IR, printing the IR after each pass and their
+/// timings to stderr if requested.
+fn optimize(ir: &mut Module, args: &PassArgs) {
+    let runs = args.manager().run(ir, |run, ir| {
+        if args.print_ir_after_all {
+            eprintln!("// IR after {}\n{ir}", run.name);
+        }
+    });
+
+    if args.time_passes {
+        for run in runs {
+            let changed = if run.changed { "" } else { " (unchanged)" };
+            eprintln!("{:>12.3?}  {}{changed}", run.time, run.name);
+        }
+    }
+}
+
+fn lint_file(path: PathBuf, apply_fixes: bool, levels: &LintLevels, reporter: &Reporter) {
+    let manifest = match Manifest::discover(&path) {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            eprintln!("error: failed to load manifest");
+            eprintln!("{err}");
+            return;
+        }
+    };
+
+    let mut db = SourceDatabase::default();
+    let Some(id) = read_file(&mut db, &path) else {
+        return;
+    };
+    let file = db.get(id);
+
+    let mut driver = Driver::new(manifest.limits);
+    let mut levels = levels.clone();
+    driver.lints().set_defaults(&mut levels);
+
+    // the warnings allowed by `@allow` attributes are already left out.
+    let compiled = driver
+        .compile(file.text())
+        .diagnostics(driver.limits())
+        .into_iter()
+        .map(|diagnostic| diagnostic.with_file(id));
+    let diagnostics: Vec<_> = unknown_lints(&driver, &levels)
+        .into_iter()
+        .chain(compiled)
+        .filter_map(|diagnostic| levels.apply(diagnostic))
+        .collect();
+
+    if !apply_fixes {
+        let lints: Vec<_> = diagnostics
+            .into_iter()
+            .filter(|diagnostic| diagnostic.lint.is_some())
+            .collect();
+        let denied = lints.iter().any(Diagnostic::is_error);
+        reporter.report(&db, lints);
+        if denied {
+            process::exit(1);
+        }
+        return;
+    }
+
+    // parse recoveries like a missing `;` are fixed along with the lints.
+    let edits = fix::machine_applicable(&diagnostics);
+    let fixed_count = edits.len();
+
+    let (fixed, conflicts) = fix::apply_edits(file.text(), edits);
+    if fixed_count > 0 {
+        write_file(&path, fixed);
+    }
+
+    eprintln!(
+        "fixed {} problem(s), skipped {} conflicting fix(es)",
+        fixed_count - conflicts.len(),
+        conflicts.len()
+    );
+}
+
+fn fix_file(path: PathBuf, levels: &LintLevels, reporter: &Reporter) {
+    let manifest = match Manifest::discover(&path) {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            eprintln!("error: failed to load manifest");
+            eprintln!("{err}");
+            return;
+        }
+    };
+
+    let mut db = SourceDatabase::default();
+    let Some(id) = read_file(&mut db, &path) else {
+        return;
+    };
+
+    let mut driver = Driver::new(manifest.limits);
+    let mut levels = levels.clone();
+    driver.lints().set_defaults(&mut levels);
+
+    reporter.report(&db, unknown_lints(&driver, &levels));
+    let fixed = fix::fix(&mut driver, &levels, db.get(id).text());
+
+    if fixed.applied > 0 {
+        write_file(&path, fixed.source.clone());
+    }
+
+    eprintln!(
+        "applied {} fix(es) in {} round(s)",
+        fixed.applied, fixed.rounds
+    );
+
+    // report what is left, pointing into the fixed source.
+    let id = db.add(SourceFile::new(path.display().to_string(), fixed.source));
+    let diagnostics = driver
+        .compile(db.get(id).text())
+        .diagnostics(driver.limits())
+        .into_iter()
+        .filter_map(|diagnostic| levels.apply(diagnostic.with_file(id)))
+        .collect();
+
+    reporter.report(&db, diagnostics);
+}
+
+fn diff_files(old_path: PathBuf, new_path: PathBuf, reporter: &Reporter) {
+    let mut modules = Vec::new();
+
+    let mut db = SourceDatabase::default();
+
+    for path in [&old_path, &new_path] {
+        let Some(id) = read_file(&mut db, path) else {
+            return;
+        };
+        let file = db.get(id);
+
+        let mut driver = Driver::new(Limits::default());
+
+        let compilation = driver.compile(file.text());
+
+        if !compilation.errors.is_empty() {
+            let diagnostics = compilation
+                .errors
+                .iter()
+                .map(|err| err.diagnostic(driver.limits()).with_file(id))
+                .collect();
+            return reporter.report(&db, diagnostics);
+        }
+
+        modules.push(compilation.module);
+    }
+
+    for change in diff::diff_modules(&modules[0], &modules[1]) {
+        println!("{change}");
+    }
+}
+
+fn reduce_file(path: PathBuf) {
+    let manifest = match Manifest::discover(&path) {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            eprintln!("error: failed to load manifest");
+            eprintln!("{err}");
+            return;
+        }
+    };
+
+    let mut db = SourceDatabase::default();
+    let Some(id) = read_file(&mut db, &path) else {
+        return;
+    };
+    let file = db.get(id);
+
+    let mut driver = Driver::new(manifest.limits);
+
+    match reduce::reduce(&mut driver, file.text()) {
+        Some(reduced) => println!("{reduced}"),
+        None => eprintln!("error: the file compiles successfully, there is nothing to reduce"),
+    }
+}
+
+fn explain_code(code: &str) {
+    match explain::explain(&code.to_ascii_uppercase()) {
+        Some(explanation) => println!("{explanation}"),
+        None => eprintln!("error: `{code}` is not a known diagnostic code"),
+    }
+}
+
+fn write_file(path: &PathBuf, content: String) {
+    if let Err(err) = fs::write(path, content) {
+        eprintln!("error: failed to write file");
+        eprintln!("{err:?}");
+    }
+}
+
+/// Prints [Diagnostic]s to stderr.
+struct Reporter {
+    colored: bool,
+    /// The maximum number of errors printed, where `0` prints all of them.
+    error_limit: usize,
+    format: DiagnosticFormat,
+}
+
+impl Reporter {
+    /// Prints the given [Diagnostic]s along with the source lines they point at, leaving
+    /// out follow-on errors and the errors beyond the limit.
+    ///
+    /// SARIF logs always contain every error, as they are meant for tools.
+    fn report(&self, db: &SourceDatabase, mut diagnostics: Vec<Diagnostic>) {
+        diagnostics::dedup(&mut diagnostics);
+
+        let (mut emitter, omitted): (Box<dyn EmitDiagnostic>, _) = match self.format {
+            DiagnosticFormat::Sarif => (Box::new(SarifEmitter::new(io::stderr())), 0),
+            DiagnosticFormat::Human => {
+                let omitted = match self.error_limit {
+                    0 => 0,
+                    limit => diagnostics::limit_errors(&mut diagnostics, limit),
+                };
+                let emitter = TerminalEmitter::new(io::stderr(), self.colored);
+                (Box::new(emitter), omitted)
+            }
+        };
+
+        for diagnostic in &diagnostics {
+            emitter.emit(db, diagnostic);
+        }
+        emitter.finish(db);
+
+        if omitted > 0 {
+            eprintln!(
+                "note: {omitted} more error(s) omitted, show them with `--error-limit {}`",
+                self.error_limit + omitted
+            );
+        }
     }
 }
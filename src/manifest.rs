@@ -0,0 +1,83 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use syntax::limits::{Limit, Limits};
+
+/// The file name of the project manifest.
+pub const MANIFEST_NAME: &str = "elan.toml";
+
+#[derive(Debug, Default)]
+pub struct Manifest {
+    pub limits: Limits,
+}
+
+#[derive(Debug)]
+pub enum ManifestError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    UnknownLimit(String),
+    InvalidLimit(Limit),
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::Io(err) => write!(f, "{err}"),
+            ManifestError::Toml(err) => write!(f, "{err}"),
+            ManifestError::UnknownLimit(key) => write!(f, "unknown limit `{key}`"),
+            ManifestError::InvalidLimit(limit) => {
+                write!(f, "limit `{limit}` must be a non-negative integer")
+            }
+        }
+    }
+}
+
+impl Manifest {
+    /// Loads the [Manifest] governing the given source file, which is the first
+    /// `elan.toml` found in its directory or any of its ancestors.
+    pub fn discover(source: &Path) -> Result<Manifest, ManifestError> {
+        match find_manifest(source) {
+            Some(path) => Manifest::load(&path),
+            None => Ok(Manifest::default()),
+        }
+    }
+
+    /// Loads the [Manifest] at the given path.
+    pub fn load(path: &Path) -> Result<Manifest, ManifestError> {
+        let content = fs::read_to_string(path).map_err(ManifestError::Io)?;
+        let table = content
+            .parse::<toml::Table>()
+            .map_err(ManifestError::Toml)?;
+
+        let mut limits = Limits::default();
+
+        if let Some(limit_table) = table.get("limits").and_then(toml::Value::as_table) {
+            for (key, value) in limit_table {
+                let limit =
+                    Limit::from_key(key).ok_or_else(|| ManifestError::UnknownLimit(key.clone()))?;
+
+                let value = value
+                    .as_integer()
+                    .and_then(|value| usize::try_from(value).ok())
+                    .ok_or(ManifestError::InvalidLimit(limit))?;
+
+                limits.set(limit, value);
+            }
+        }
+
+        Ok(Manifest { limits })
+    }
+}
+
+/// Searches the directory of `source` and its ancestors for a manifest.
+fn find_manifest(source: &Path) -> Option<PathBuf> {
+    let source = source.canonicalize().ok()?;
+
+    source
+        .ancestors()
+        .skip(1)
+        .map(|dir| dir.join(MANIFEST_NAME))
+        .find(|path| path.is_file())
+}
@@ -0,0 +1,570 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use ir::{
+    func::Module,
+    lower::{self, CheckedModule, LowerError},
+};
+use semantic::{program::EntryError, resolve::Exports};
+use syntax::{
+    ast,
+    diagnostics::Diagnostic,
+    lexer::Lexer,
+    messages::message,
+    parser::Parser,
+    source::{FileId, SourceDatabase, SourceFile},
+    token::Span,
+};
+
+use crate::driver::{Compilation, Driver};
+
+/// The extension of the files holding modules.
+pub const MODULE_EXTENSION: &str = "elan";
+
+/// The file holding a directory module like `geometry/mod.elan`.
+pub const DIRECTORY_MODULE: &str = "mod";
+
+/// An error found while loading the modules a file imports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportError {
+    pub kind: ImportErrorKind,
+    /// The [Span] of the `import` declaration.
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportErrorKind {
+    /// An import naming no existing file, where `candidates` are the paths looked at.
+    NotFound {
+        module: String,
+        candidates: Vec<PathBuf>,
+    },
+    /// An import of a module that imports the importing one, directly or through
+    /// other modules.
+    Cycle {
+        module: String,
+        /// The names of the files along the cycle, starting and ending with the
+        /// imported one.
+        files: Vec<String>,
+        /// The other imports along the cycle, in the order they are followed.
+        imports: Vec<ImportSite>,
+    },
+}
+
+/// An `import` declaration in a specific file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportSite {
+    pub file: FileId,
+    pub span: Span,
+    /// The imported module path, like `math::trig`.
+    pub module: String,
+}
+
+impl ImportErrorKind {
+    /// Returns the stable code of this kind of error, see [syntax::explain::explain].
+    pub fn code(&self) -> &'static str {
+        match self {
+            ImportErrorKind::NotFound { .. } => "E0030",
+            ImportErrorKind::Cycle { .. } => "E0031",
+        }
+    }
+}
+
+impl ImportError {
+    /// Converts the error into a [Diagnostic].
+    pub fn diagnostic(&self) -> Diagnostic {
+        let code = self.kind.code();
+
+        let diagnostic = match &self.kind {
+            ImportErrorKind::NotFound { module, candidates } => {
+                let paths = candidates
+                    .iter()
+                    .map(|path| format!("`{}`", path.display()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Diagnostic::error(message(code, &[("module", module)]), self.span)
+                    .with_note(message("E0030.note", &[("paths", &paths)]))
+            }
+            ImportErrorKind::Cycle {
+                module,
+                files,
+                imports,
+            } => {
+                let cycle = files
+                    .iter()
+                    .map(|file| format!("`{file}`"))
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                let diagnostic = Diagnostic::error(message(code, &[("module", module)]), self.span)
+                    .with_note(message("E0031.note", &[("cycle", &cycle)]));
+
+                imports.iter().fold(diagnostic, |diagnostic, import| {
+                    let label = message("E0031.label", &[("module", &import.module)]);
+                    diagnostic.with_file_label(import.file, import.span, label)
+                })
+            }
+        };
+
+        diagnostic.with_code(code)
+    }
+}
+
+/// A source file of a program along with its [Compilation].
+#[derive(Debug)]
+pub struct LoadedModule {
+    pub file: FileId,
+    pub compilation: Compilation,
+}
+
+/// Lowers the program made of the loaded modules to the IR, see [lower::lower_program],
+/// returning the errors preventing it as [Diagnostic]s.
+///
+/// The functions of `src/geometry/shapes.elan` imported by `src/main.elan` are named
+/// like `geometry.shapes.square`.
+pub fn lower(modules: &[LoadedModule], db: &SourceDatabase) -> Result<Module, Vec<Diagnostic>> {
+    let root = modules.last().expect("the root module is always loaded");
+    let compilation = &root.compilation;
+    let program = compilation.program().map_err(|errors| {
+        let diagnostic = |err: &EntryError| {
+            err.diagnostic(&compilation.resolutions)
+                .with_file(root.file)
+        };
+        errors.iter().map(diagnostic).collect::<Vec<_>>()
+    })?;
+
+    let directory = Path::new(db.get(root.file).name()).parent();
+    let modules: Vec<_> = modules
+        .iter()
+        .map(|module| {
+            let (file, path) = if module.file == root.file {
+                (None, String::new())
+            } else {
+                let path = module_path(Path::new(db.get(module.file).name()), directory);
+                (Some(module.file), path)
+            };
+            CheckedModule {
+                file,
+                path,
+                module: &module.compilation.module,
+                resolutions: &module.compilation.resolutions,
+                types: &module.compilation.types,
+                consts: &module.compilation.consts,
+                instances: &module.compilation.instances,
+            }
+        })
+        .collect();
+
+    lower::lower_program(&modules, &program).map_err(|errors| {
+        let diagnostic =
+            |err: &LowerError| err.diagnostic().with_file(err.file.unwrap_or(root.file));
+        errors.iter().map(diagnostic).collect()
+    })
+}
+
+/// Returns the path of the module in the given file relative to the directory of the
+/// root file, like `geometry.shapes` for `geometry/shapes.elan` or `geometry/mod.elan`
+/// for `geometry`.
+fn module_path(file: &Path, directory: Option<&Path>) -> String {
+    let relative = directory
+        .and_then(|directory| file.strip_prefix(directory).ok())
+        .unwrap_or(file)
+        .with_extension("");
+
+    let mut segments: Vec<_> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    if segments.len() > 1 && segments.last().is_some_and(|last| last == DIRECTORY_MODULE) {
+        segments.pop();
+    }
+    segments.join(".")
+}
+
+/// Compiles the root file and every module it imports, directly or through other
+/// modules, reading the imported files with `read` into the [SourceDatabase].
+///
+/// `import math::trig;` in `src/main.elan` loads `src/math/trig.elan` or, if that
+/// doesn't exist, the directory module `src/math/trig/mod.elan`. The modules are
+/// returned in the order they were compiled, each after the modules it imports, so
+/// the root comes last.
+///
+/// Imports closing a cycle like `a -> b -> a` are reported, leaving the paths into
+/// the imported module unresolved, since its items aren't known before its own
+/// imports are loaded.
+pub fn load(
+    driver: &mut Driver,
+    db: &mut SourceDatabase,
+    root: FileId,
+    read: impl FnMut(&Path) -> Option<String>,
+) -> Vec<LoadedModule> {
+    let mut loader = Loader {
+        driver,
+        db,
+        read,
+        exports: HashMap::new(),
+        stack: Vec::new(),
+        modules: Vec::new(),
+    };
+
+    let path = PathBuf::from(loader.db.get(root).name());
+    loader.load(root, path);
+
+    loader.modules
+}
+
+struct Loader<'a, R> {
+    driver: &'a mut Driver,
+    db: &'a mut SourceDatabase,
+    read: R,
+    /// The [Exports] of each loaded file by path, which are [None] while the file's
+    /// own imports are loaded.
+    exports: HashMap<PathBuf, Option<Exports>>,
+    /// The files whose imports are loaded, each importing the next one, along with
+    /// the import that is followed.
+    stack: Vec<(PathBuf, FileId, Option<ImportSite>)>,
+    modules: Vec<LoadedModule>,
+}
+
+/// What [Loader::find] found for an import.
+enum Found {
+    Loaded(Exports),
+    /// A file whose own imports are loaded, which makes the import part of a cycle.
+    InProgress(PathBuf),
+    Missing,
+}
+
+impl<R: FnMut(&Path) -> Option<String>> Loader<'_, R> {
+    fn load(&mut self, file: FileId, path: PathBuf) -> Exports {
+        self.exports.insert(path.clone(), None);
+        self.stack.push((path.clone(), file, None));
+
+        let mut imports = HashMap::new();
+        let mut errors = Vec::new();
+
+        for import in self.imports(file) {
+            let segments: Vec<_> = import.path.iter().map(|seg| seg.text.clone()).collect();
+            let module = segments.join("::");
+            let candidates = candidates(&path, &segments);
+
+            self.stack.last_mut().unwrap().2 = Some(ImportSite {
+                file,
+                span: import.span,
+                module: module.clone(),
+            });
+
+            let kind = match self.find(&candidates) {
+                Found::Loaded(exports) => {
+                    imports.insert(segments, exports);
+                    continue;
+                }
+                Found::InProgress(imported) => self.cycle(&imported, module),
+                Found::Missing => ImportErrorKind::NotFound { module, candidates },
+            };
+            errors.push(ImportError {
+                kind,
+                span: import.span,
+            });
+        }
+
+        self.stack.pop();
+
+        let text = self.db.get(file).text();
+        let mut compilation = self.driver.compile_with_imports(text, &imports);
+        compilation.import_errors = errors;
+
+        let exports = Exports::collect(&compilation.module, file).with_signatures(
+            &compilation.module,
+            &compilation.resolutions,
+            &compilation.types,
+        );
+        self.exports.insert(path, Some(exports.clone()));
+        self.modules.push(LoadedModule { file, compilation });
+
+        exports
+    }
+
+    /// Looks for the first candidate file that exists, loading it if it wasn't loaded yet.
+    fn find(&mut self, candidates: &[PathBuf]) -> Found {
+        for path in candidates {
+            match self.exports.get(path) {
+                Some(Some(exports)) => return Found::Loaded(exports.clone()),
+                Some(None) => return Found::InProgress(path.clone()),
+                None => {}
+            }
+
+            if let Some(text) = (self.read)(path) {
+                let file = self
+                    .db
+                    .add(SourceFile::new(path.display().to_string(), text));
+                return Found::Loaded(self.load(file, path.clone()));
+            }
+        }
+
+        Found::Missing
+    }
+
+    /// Describes the cycle closed by importing the file at `imported`, whose imports
+    /// are being loaded, from the file on top of the stack.
+    fn cycle(&self, imported: &Path, module: String) -> ImportErrorKind {
+        let start = self
+            .stack
+            .iter()
+            .position(|(path, _, _)| path == imported)
+            .expect("files whose imports are loaded are on the stack");
+        let cycle = &self.stack[start..];
+
+        let files = cycle
+            .iter()
+            .chain(&cycle[..1])
+            .map(|&(_, file, _)| self.db.get(file).name().to_string())
+            .collect();
+        // the import on top of the stack is the one reported.
+        let imports = cycle[..cycle.len() - 1]
+            .iter()
+            .filter_map(|(_, _, import)| import.clone())
+            .collect();
+
+        ImportErrorKind::Cycle {
+            module,
+            files,
+            imports,
+        }
+    }
+
+    /// Returns the imports of the given file, which is parsed on its own to find them
+    /// before it is compiled.
+    fn imports(&self, file: FileId) -> Vec<ast::ImportDecl> {
+        let Ok(tokens) = Lexer::new(self.db.get(file).text()).collect_tokens() else {
+            return Vec::new();
+        };
+        let (module, _) = Parser::with_limits(&tokens, *self.driver.limits()).parse_module();
+
+        module
+            .items
+            .into_iter()
+            .filter_map(|item| match item {
+                ast::Item::Import(import) => Some(import),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Returns the paths of the files that may hold the module imported by the file at
+/// `importer`, relative to its directory.
+fn candidates(importer: &Path, segments: &[String]) -> Vec<PathBuf> {
+    let mut module = importer.parent().unwrap_or(Path::new("")).to_path_buf();
+    module.extend(segments);
+
+    vec![
+        module.with_extension(MODULE_EXTENSION),
+        module
+            .join(DIRECTORY_MODULE)
+            .with_extension(MODULE_EXTENSION),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, path::PathBuf};
+
+    use syntax::{
+        diagnostics::{Diagnostic, render_in},
+        limits::Limits,
+        source::{SourceDatabase, SourceFile},
+        token::Span,
+    };
+
+    use crate::{
+        driver::Driver,
+        modules::{ImportErrorKind, ImportSite, load, lower},
+    };
+
+    #[test]
+    fn imports() {
+        let files = HashMap::from([
+            (
+                PathBuf::from("src/geometry/mod.elan"),
+                "import shapes; pub fn area(s: f64) -> f64 { shapes::square(s) * secret() } fn secret() -> f64 { 1.0 }",
+            ),
+            (
+                PathBuf::from("src/geometry/shapes.elan"),
+                "pub fn square(s: f64) -> f64 { s * s }",
+            ),
+        ]);
+
+        let mut db = SourceDatabase::default();
+        let root = db.add(SourceFile::new(
+            "src/main.elan",
+            "import geometry;\nimport missing;\nfn main() { geometry::area(2.0); geometry::secret(); }",
+        ));
+        let mut driver = Driver::new(Limits::default());
+
+        let modules = load(&mut driver, &mut db, root, |path| {
+            files.get(path).map(|text| text.to_string())
+        });
+
+        let names: Vec<_> = modules
+            .iter()
+            .map(|module| db.get(module.file).name())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "src/geometry/shapes.elan",
+                "src/geometry/mod.elan",
+                "src/main.elan"
+            ]
+        );
+
+        // the dependencies compile cleanly, where exported functions aren't unused.
+        for module in &modules[..2] {
+            assert!(
+                module
+                    .compilation
+                    .diagnostics(&Limits::default())
+                    .is_empty()
+            );
+        }
+
+        let main = &modules[2].compilation;
+        assert_eq!(
+            main.import_errors
+                .iter()
+                .map(|err| &err.kind)
+                .collect::<Vec<_>>(),
+            [&ImportErrorKind::NotFound {
+                module: "missing".to_string(),
+                candidates: vec![
+                    PathBuf::from("src/missing.elan"),
+                    PathBuf::from("src/missing/mod.elan")
+                ],
+            }]
+        );
+
+        let diagnostics: Vec<_> = main
+            .diagnostics(&Limits::default())
+            .into_iter()
+            .map(|diagnostic| diagnostic.with_file(root))
+            .collect();
+        let codes: Vec<_> = diagnostics.iter().filter_map(|d| d.code).collect();
+        assert_eq!(codes, ["E0030", "E0029"]);
+
+        let private = diagnostics
+            .iter()
+            .find(|diagnostic| diagnostic.code == Some("E0029"))
+            .map(|diagnostic: &Diagnostic| render_in(&db, diagnostic, false).unwrap())
+            .unwrap();
+        assert!(private.contains("::: src/geometry/mod.elan:1:79"));
+    }
+
+    #[test]
+    fn lowering() {
+        let files = HashMap::from([
+            (
+                PathBuf::from("src/geometry/mod.elan"),
+                "import shapes; pub fn area(s: f64) -> f64 { shapes::square(s) } pub fn pair() -> (i64, i64) { (1, 2) }",
+            ),
+            (
+                PathBuf::from("src/geometry/shapes.elan"),
+                "pub fn square(s: f64) -> f64 { s * s }",
+            ),
+        ]);
+
+        let mut db = SourceDatabase::default();
+        let root = db.add(SourceFile::new(
+            "src/main.elan",
+            "import geometry;\nfn main() -> i32 { geometry::area(3.0) as i32 }",
+        ));
+        let mut driver = Driver::new(Limits::default());
+        let modules = load(&mut driver, &mut db, root, |path| {
+            files.get(path).map(|text| text.to_string())
+        });
+
+        // `pair` isn't called, so its tuple doesn't keep the program from compiling.
+        let ir = lower(&modules, &db).unwrap();
+        let names: Vec<_> = ir.funcs.iter().map(|func| func.name.as_str()).collect();
+        assert_eq!(names, ["geometry.shapes.square", "geometry.area", "main"]);
+        assert!(
+            ir.func("main")
+                .unwrap()
+                .to_string()
+                .contains("call f64 @geometry.area(")
+        );
+    }
+
+    #[test]
+    fn cycles() {
+        let files = HashMap::from([
+            (PathBuf::from("src/a.elan"), "import b;"),
+            (PathBuf::from("src/b.elan"), "\nimport c;"),
+            (PathBuf::from("src/c.elan"), "\n\nimport a;"),
+        ]);
+
+        let mut db = SourceDatabase::default();
+        let root = db.add(SourceFile::new("src/main.elan", "import a; import main;"));
+        let mut driver = Driver::new(Limits::default());
+
+        let modules = load(&mut driver, &mut db, root, |path| {
+            files.get(path).map(|text| text.to_string())
+        });
+
+        let errors: Vec<_> = modules
+            .iter()
+            .flat_map(|module| &module.compilation.import_errors)
+            .map(|err| &err.kind)
+            .collect();
+        let site = |name: &str, start, module: &str| ImportSite {
+            file: db.find(name).unwrap(),
+            span: Span {
+                start,
+                end: start + 9,
+            },
+            module: module.to_string(),
+        };
+        assert_eq!(
+            errors,
+            [
+                &ImportErrorKind::Cycle {
+                    module: "a".to_string(),
+                    files: vec![
+                        "src/a.elan".to_string(),
+                        "src/b.elan".to_string(),
+                        "src/c.elan".to_string(),
+                        "src/a.elan".to_string(),
+                    ],
+                    imports: vec![site("src/a.elan", 0, "b"), site("src/b.elan", 1, "c")],
+                },
+                &ImportErrorKind::Cycle {
+                    module: "main".to_string(),
+                    files: vec!["src/main.elan".to_string(), "src/main.elan".to_string()],
+                    imports: Vec::new(),
+                },
+            ]
+        );
+
+        let c = &modules[0];
+        let diagnostic = c.compilation.diagnostics(&Limits::default())[0]
+            .clone()
+            .with_file(c.file);
+        let expected = "\
+error[E0031]: importing `a` creates a cycle
+ --> src/c.elan:3:1
+  |
+3 | import a;
+  | ^^^^^^^^^
+ ::: src/a.elan:1:1
+  |
+1 | import b;
+  | --------- `b` is imported here
+ ::: src/b.elan:2:1
+  |
+2 | import c;
+  | --------- `c` is imported here
+  = note: the modules import each other as `src/a.elan` -> `src/b.elan` -> `src/c.elan` -> `src/a.elan`
+";
+        assert_eq!(render_in(&db, &diagnostic, false).unwrap(), expected);
+    }
+}
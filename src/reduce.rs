@@ -0,0 +1,379 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use syntax::{
+    ast,
+    diagnostics::Diagnostic,
+    lexer::Lexer,
+    parser::Parser,
+    token::{Span, Token, TokenKind},
+    visit::{self, Visitor},
+};
+
+use crate::driver::Driver;
+
+/// An error the compiler reported, identified by its code, message and the label of
+/// its primary [Span], but not the [Span] itself, which moves as the source shrinks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub code: Option<&'static str>,
+    pub message: String,
+    pub primary_label: Option<String>,
+}
+
+impl From<Diagnostic> for Failure {
+    fn from(diagnostic: Diagnostic) -> Self {
+        Failure {
+            code: diagnostic.code,
+            message: diagnostic.message,
+            primary_label: diagnostic.primary_label,
+        }
+    }
+}
+
+/// What happened when compiling a source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The compiler compiled the source without errors.
+    Success,
+    /// The compiler reported an error, which is the first error reported by any stage.
+    Error(Failure),
+    /// The compiler panicked, which is an internal compiler error.
+    Panic,
+}
+
+/// Compiles the given source text, returning the errors reported or [None] if the
+/// compiler panicked.
+fn errors(driver: &mut Driver, source: &str) -> Option<Vec<Failure>> {
+    let compilation = panic::catch_unwind(AssertUnwindSafe(|| driver.compile(source))).ok()?;
+
+    let errors = compilation
+        .diagnostics(driver.limits())
+        .into_iter()
+        .filter(Diagnostic::is_error)
+        .map(Failure::from)
+        .collect();
+    Some(errors)
+}
+
+/// Compiles the given source text and returns the [Outcome].
+pub fn outcome(driver: &mut Driver, source: &str) -> Outcome {
+    match errors(driver, source) {
+        Some(errors) => errors
+            .into_iter()
+            .next()
+            .map_or(Outcome::Success, Outcome::Error),
+        None => Outcome::Panic,
+    }
+}
+
+/// Returns whether compiling the source fails like the target [Outcome], by panicking
+/// or by reporting the same error among any others.
+fn reproduces(driver: &mut Driver, source: &str, target: &Outcome) -> bool {
+    match (errors(driver, source), target) {
+        (None, Outcome::Panic) => true,
+        (Some(errors), Outcome::Error(failure)) => errors.contains(failure),
+        _ => false,
+    }
+}
+
+/// Minimizes the given source text to a smaller one failing with the same [Outcome],
+/// by delta debugging over its items, then the statements of its blocks, then
+/// replacing expressions by their subexpressions, until none of them shrinks it.
+///
+/// Returns [None] if the source compiles successfully, as there is nothing to reproduce.
+pub fn reduce(driver: &mut Driver, source: &str) -> Option<String> {
+    // silence the panic messages of the many candidates that are tried.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let target = outcome(driver, source);
+    let reduced = match target {
+        Outcome::Success => None,
+        target => {
+            let mut reduced = source.to_string();
+            loop {
+                let before = reduced.clone();
+                reduced = remove_units(driver, &reduced, &target, item_units);
+                reduced = remove_units(driver, &reduced, &target, stmt_units);
+                reduced = hoist_exprs(driver, &reduced, &target);
+
+                if reduced == before {
+                    break Some(reduced);
+                }
+            }
+        }
+    };
+
+    panic::set_hook(hook);
+    reduced
+}
+
+/// The source text parsed the way the [Driver] parses it, leaving out the tokens that
+/// failed to lex.
+struct Parsed<'src> {
+    tokens: Vec<Token<'src>>,
+    module: ast::Module,
+}
+
+impl<'src> Parsed<'src> {
+    fn new(driver: &Driver, source: &'src str) -> Self {
+        let tokens: Vec<_> = Lexer::new(source).filter_map(Result::ok).collect();
+        let mut parser = Parser::with_limits(&tokens, *driver.limits());
+        parser.set_experiments(driver.experiments());
+        let (module, _) = parser.parse_module();
+
+        Parsed { tokens, module }
+    }
+}
+
+/// Returns the [Span]s of the items, including the doc comments before them.
+fn item_units(parsed: &Parsed) -> Vec<Span> {
+    parsed
+        .module
+        .items
+        .iter()
+        .map(|item| {
+            let span = item.span();
+            let first = parsed
+                .tokens
+                .partition_point(|token| token.span.start < span.start);
+            let docs = parsed.tokens[..first]
+                .iter()
+                .rev()
+                .take_while(|token| token.kind == TokenKind::DocComment)
+                .last();
+
+            docs.map_or(span, |docs| docs.span.to(span))
+        })
+        .collect()
+}
+
+/// Returns the [Span]s of the statements and tail expressions of all blocks.
+fn stmt_units(parsed: &Parsed) -> Vec<Span> {
+    struct Stmts<'a> {
+        exprs: &'a ast::ExprArena,
+        spans: Vec<Span>,
+    }
+
+    impl<'a> Visitor<'a> for Stmts<'a> {
+        fn exprs(&self) -> &'a ast::ExprArena {
+            self.exprs
+        }
+
+        fn visit_block(&mut self, block: &'a ast::Block) {
+            self.spans.extend(block.stmts.iter().map(ast::Stmt::span));
+            self.spans
+                .extend(block.tail.map(|tail| self.exprs[tail].span()));
+            visit::walk_block(self, block);
+        }
+    }
+
+    let mut stmts = Stmts {
+        exprs: &parsed.module.exprs,
+        spans: Vec::new(),
+    };
+    stmts.visit_module(&parsed.module);
+    stmts.spans
+}
+
+/// Removes as many of the units `units` finds in the source as possible while it
+/// still reproduces the target [Outcome], using [ddmin].
+fn remove_units(
+    driver: &mut Driver,
+    source: &str,
+    target: &Outcome,
+    units: fn(&Parsed) -> Vec<Span>,
+) -> String {
+    let all = units(&Parsed::new(driver, source));
+    let chars: Vec<char> = source.chars().collect();
+    let without = |kept: &[Span]| {
+        let removed: Vec<_> = all.iter().filter(|unit| !kept.contains(unit)).collect();
+        remove_spans(&chars, &removed)
+    };
+
+    let kept = ddmin(all.clone(), |kept| {
+        reproduces(driver, &without(kept), target)
+    });
+    without(&kept)
+}
+
+/// Returns the text without the [Span]s and the whitespace after them, taking the
+/// indentation and line break along for [Span]s that start a line. [Span]s within
+/// removed ones are skipped.
+fn remove_spans(chars: &[char], spans: &[&Span]) -> String {
+    let mut spans = spans.to_vec();
+    spans.sort_by_key(|span| (span.start, span.end));
+
+    let mut result = String::with_capacity(chars.len());
+    let mut last_end = 0;
+
+    for span in spans {
+        if span.start < last_end {
+            continue;
+        }
+
+        // the indentation goes too if the span starts its line.
+        let indented = chars[last_end..span.start]
+            .iter()
+            .rposition(|&ch| ch != ' ' && ch != '\t')
+            .map_or(last_end, |index| last_end + index + 1);
+        let start = match indented.checked_sub(1).map(|index| chars[index]) {
+            None | Some('\n') => indented,
+            _ => span.start,
+        };
+
+        result.extend(&chars[last_end..start]);
+        last_end = span.end;
+        while chars.get(last_end).is_some_and(|ch| ch.is_whitespace()) {
+            last_end += 1;
+            if chars[last_end - 1] == '\n' && start != span.start {
+                break;
+            }
+        }
+    }
+
+    result.extend(&chars[last_end..]);
+    result
+}
+
+/// Replaces expressions by one of their subexpressions while the source still
+/// reproduces the target [Outcome], like `f(x) + 1` by `f(x)` and then by `x`.
+fn hoist_exprs(driver: &mut Driver, source: &str, target: &Outcome) -> String {
+    let mut source = source.to_string();
+    // the expressions before the last replacement were tried already.
+    let mut from = 0;
+
+    'parse: loop {
+        let parsed = Parsed::new(driver, &source);
+        let exprs = &parsed.module.exprs;
+
+        // outer expressions come before the ones they contain.
+        let mut ids: Vec<_> = exprs
+            .iter()
+            .map(|(id, expr)| (id, expr.span()))
+            .filter(|(_, span)| span.start >= from && *span != Span::EOI)
+            .collect();
+        ids.sort_by_key(|&(_, span)| (span.start, usize::MAX - span.end));
+
+        let chars: Vec<char> = source.chars().collect();
+        for (id, span) in ids {
+            for child in visit::children(exprs, id) {
+                let child = exprs[child].span();
+                let mut candidate: String = chars[..span.start].iter().collect();
+                candidate.extend(&chars[child.start..child.end]);
+                candidate.extend(&chars[span.end..]);
+
+                if reproduces(driver, &candidate, target) {
+                    source = candidate;
+                    from = span.start;
+                    continue 'parse;
+                }
+            }
+        }
+
+        return source;
+    }
+}
+
+/// Finds a small subsequence of `units` for which `interesting` still holds,
+/// using the complement removal step of the ddmin algorithm.
+fn ddmin<T: Clone>(mut units: Vec<T>, mut interesting: impl FnMut(&[T]) -> bool) -> Vec<T> {
+    if interesting(&[]) {
+        return Vec::new();
+    }
+
+    let mut granularity = 2;
+
+    while units.len() >= 2 {
+        let chunk_size = units.len().div_ceil(granularity);
+        let mut reduced = false;
+
+        for start in (0..units.len()).step_by(chunk_size) {
+            let end = (start + chunk_size).min(units.len());
+
+            let complement: Vec<_> = units[..start]
+                .iter()
+                .chain(&units[end..])
+                .cloned()
+                .collect();
+
+            if interesting(&complement) {
+                units = complement;
+                granularity = (granularity - 1).max(2);
+                reduced = true;
+                break;
+            }
+        }
+
+        if !reduced {
+            if granularity >= units.len() {
+                break;
+            }
+
+            granularity = (granularity * 2).min(units.len());
+        }
+    }
+
+    units
+}
+#[cfg(test)]
+mod test {
+    use syntax::limits::Limits;
+
+    use crate::{
+        driver::Driver,
+        reduce::{Outcome, outcome, reduce, reproduces},
+    };
+
+    #[test]
+    fn reduce_error() {
+        let mut driver = Driver::new(Limits::default());
+
+        let source = "fn a(x: i64) -> i64 { x * 2 } enum E { A, B } fn b() { let y = 1; y = = 2; }";
+        let target = outcome(&mut driver, source);
+
+        let reduced = reduce(&mut driver, source).unwrap();
+
+        assert!(matches!(&target, Outcome::Error(failure) if failure.code == Some("E0001")));
+        assert!(reproduces(&mut driver, &reduced, &target));
+        assert_eq!(reduced, "fn b() { y = = 2; }");
+    }
+
+    #[test]
+    fn reduce_type_error() {
+        let mut driver = Driver::new(Limits::default());
+
+        let source = "/// Doubles.\nfn double(x: i64) -> i64 { x * 2 }\n\nfn main() {\n    let s = \"s\";\n    let t = s + s;\n}\n";
+        let target = outcome(&mut driver, source);
+
+        let reduced = reduce(&mut driver, source).unwrap();
+
+        assert!(matches!(&target, Outcome::Error(failure) if failure.code != Some("E0001")));
+        assert!(reproduces(&mut driver, &reduced, &target));
+        assert_eq!(
+            reduced,
+            "fn main() {\n    let s = \"s\";\n    let t = s + s;\n}\n"
+        );
+    }
+
+    #[test]
+    fn reduce_keeps_error() {
+        let mut driver = Driver::new(Limits::default());
+
+        // `let t` fails with the same code and message as `let s`, but a different label.
+        let source = "fn main() {\n    let s: i64 = \"s\";\n    let t: bool = 1;\n}\n";
+        let target = outcome(&mut driver, source);
+
+        let reduced = reduce(&mut driver, source).unwrap();
+
+        assert_eq!(outcome(&mut driver, &reduced), target);
+        assert_eq!(reduced, "fn main() {\n    let s: i64 = \"s\";\n}\n");
+    }
+
+    #[test]
+    fn reduce_success() {
+        let mut driver = Driver::new(Limits::default());
+
+        assert_eq!(reduce(&mut driver, "fn main() {}"), None);
+    }
+}
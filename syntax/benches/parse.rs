@@ -0,0 +1,48 @@
+use std::{hint::black_box, time::Instant};
+
+use syntax::{lexer::Lexer, parser::Parser};
+
+/// How often the synthetic file is repeated, giving a file of a few megabytes.
+const REPETITIONS: usize = 10_000;
+const ROUNDS: usize = 5;
+
+/// A function exercising most kinds of expressions, repeated to build the input.
+fn function(index: usize) -> String {
+    format!(
+        "fn f{index}(a: i64, b: [f64; 4]) -> i64 {{
+    let (x, _) = (a * 2 + 1, b);
+    let s: String = \"item {index}\";
+    for i in 0..a {{
+        if i % 2 == 0 {{ x = x + g(i, a as f64, -i); }} else {{ continue; }}
+    }}
+    while x > 0 {{ x = x - 1; }}
+    match x {{ 0 => 1, n => {{ n * (n - 1) }} }}
+}}
+"
+    )
+}
+
+fn main() {
+    let source: String = (0..REPETITIONS).map(function).collect();
+    let megabytes = source.len() as f64 / (1024.0 * 1024.0);
+
+    let mut best = f64::INFINITY;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+
+        let tokens = Lexer::new(&source)
+            .collect_tokens()
+            .expect("the input lexes");
+        let (module, errors) = Parser::new(&tokens).parse_module();
+        assert!(errors.is_empty(), "the input parses: {errors:?}");
+        black_box(module);
+
+        best = best.min(start.elapsed().as_secs_f64());
+    }
+
+    println!(
+        "parsed {megabytes:.1} MiB in {:.1} ms, {:.1} MiB/s",
+        best * 1000.0,
+        megabytes / best
+    );
+}
@@ -1,27 +1,1139 @@
-use crate::token::Span;
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
 
+use crate::{dot, token::Span};
+
+pub mod pretty;
+
+/// Renders the given [Module] as a Graphviz DOT graph, labeling each node with its [Span].
+pub fn to_dot(module: &Module) -> String {
+    dot::ast_to_dot(module)
+}
+
+/// The root of a parsed source file.
+#[derive(Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Module {
+    pub items: Vec<Item>,
+    /// The [Expr]s of all items, which refer to each other by [ExprId].
+    pub exprs: ExprArena,
+}
+
+/// The index of an [Expr] in an [ExprArena].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExprId(u32);
+
+impl ExprId {
+    /// Returns the id as an index, e.g. into a side table of per-expression information.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Identifies an AST node other than an [Expr], which is identified by its [ExprId].
+///
+/// The [crate::parser::Parser] numbers nodes in the order it finishes them, so a
+/// node's children have smaller ids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeId(pub u32);
+
+impl NodeId {
+    /// Returns the id as an index, e.g. into a side table of per-node information.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Stores [Expr]s in a single allocation instead of boxing each of them.
+#[derive(Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExprArena {
+    exprs: Vec<Expr>,
+}
+
+impl ExprArena {
+    /// Moves the [Expr] into the arena and returns its [ExprId].
+    pub fn alloc(&mut self, expr: Expr) -> ExprId {
+        let id = ExprId(self.exprs.len() as u32);
+        self.exprs.push(expr);
+        id
+    }
+
+    /// Returns the number of [Expr]s in the arena.
+    pub fn len(&self) -> usize {
+        self.exprs.len()
+    }
+
+    /// Returns whether the arena contains no [Expr]s.
+    pub fn is_empty(&self) -> bool {
+        self.exprs.is_empty()
+    }
+
+    /// Returns the [Expr]s in the order they were allocated, along with their [ExprId]s.
+    pub fn iter(&self) -> impl Iterator<Item = (ExprId, &Expr)> {
+        (0..).map(ExprId).zip(&self.exprs)
+    }
+}
+
+impl Index<ExprId> for ExprArena {
+    type Output = Expr;
+
+    fn index(&self, id: ExprId) -> &Expr {
+        &self.exprs[id.0 as usize]
+    }
+}
+
+impl IndexMut<ExprId> for ExprArena {
+    fn index_mut(&mut self, id: ExprId) -> &mut Expr {
+        &mut self.exprs[id.0 as usize]
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Item {
+    Import(ImportDecl),
+    Func(FuncDecl),
+    Const(ConstDecl),
+    Struct(StructDecl),
+    Enum(EnumDecl),
+    Trait(TraitDecl),
+    /// The source of an item that failed to parse.
+    Error(Span),
+}
+
+impl Item {
+    /// Returns the [Span] covering the whole item.
+    pub fn span(&self) -> Span {
+        match self {
+            Item::Import(import) => import.span,
+            Item::Func(func) => func.span,
+            Item::Const(const_decl) => const_decl.span,
+            Item::Struct(struct_decl) => struct_decl.span,
+            Item::Enum(enum_decl) => enum_decl.span,
+            Item::Trait(trait_decl) => trait_decl.span,
+            Item::Error(span) => *span,
+        }
+    }
+
+    /// Returns the text of the `///` doc comments preceding the item, which aren't
+    /// covered by its [Span].
+    pub fn docs(&self) -> Option<&str> {
+        match self {
+            Item::Import(import) => import.docs.as_deref(),
+            Item::Func(func) => func.docs.as_deref(),
+            Item::Const(const_decl) => const_decl.docs.as_deref(),
+            Item::Struct(struct_decl) => struct_decl.docs.as_deref(),
+            Item::Enum(enum_decl) => enum_decl.docs.as_deref(),
+            Item::Trait(trait_decl) => trait_decl.docs.as_deref(),
+            Item::Error(_) => None,
+        }
+    }
+
+    /// Returns the [Attribute]s preceding the item.
+    pub fn attributes(&self) -> &[Attribute] {
+        match self {
+            Item::Import(import) => &import.attributes,
+            Item::Func(func) => &func.attributes,
+            Item::Const(const_decl) => &const_decl.attributes,
+            Item::Struct(struct_decl) => &struct_decl.attributes,
+            Item::Enum(enum_decl) => &enum_decl.attributes,
+            Item::Trait(trait_decl) => &trait_decl.attributes,
+            Item::Error(_) => &[],
+        }
+    }
+
+    /// Returns whether other modules importing this one can use the item.
+    pub fn visibility(&self) -> Visibility {
+        match self {
+            Item::Func(func) => func.visibility,
+            Item::Const(const_decl) => const_decl.visibility,
+            Item::Struct(struct_decl) => struct_decl.visibility,
+            Item::Enum(enum_decl) => enum_decl.visibility,
+            Item::Trait(trait_decl) => trait_decl.visibility,
+            Item::Import(_) | Item::Error(_) => Visibility::Private,
+        }
+    }
+}
+
+/// Whether an [Item] is exported to the modules importing its own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Visibility {
+    /// Only usable within its module, the default.
+    #[default]
+    Private,
+    /// Marked `pub`, usable by importing modules like `geometry::area`.
+    Public,
+}
+
+/// Whether the bindings of a [LetStmt] can be assigned to after their declaration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mutability {
+    /// Declared with `let`, the default.
+    #[default]
+    Immutable,
+    /// Declared with `var` or `let mut`.
+    Mutable,
+}
+
+/// An attribute like `@inline` or `@deprecated("use max")` preceding an [Item].
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attribute {
+    pub id: NodeId,
+    pub name: Identifier,
+    /// The parenthesized arguments, which are empty for `@inline` and `@inline()` alike.
+    pub args: Vec<ExprId>,
+    pub span: Span,
+}
+
+/// An import like `import math::trig as t;` making another module available.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImportDecl {
+    pub id: NodeId,
+    pub docs: Option<String>,
+    pub attributes: Vec<Attribute>,
+    /// The segments of the module path, like `math` and `trig`.
+    pub path: Vec<Identifier>,
+    pub alias: Option<Identifier>,
+    pub span: Span,
+}
+
+/// A function declaration like `fn add(a: i64, b: i64) -> i64 { a + b }`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FuncDecl {
+    pub id: NodeId,
+    pub docs: Option<String>,
+    pub attributes: Vec<Attribute>,
+    pub visibility: Visibility,
+    pub name: Identifier,
+    /// The generic type parameters like `T` in `fn max<T>(a: T, b: T) -> T`.
+    pub generics: Vec<Identifier>,
+    pub params: Vec<Param>,
+    pub return_type: Option<TypeExpr>,
+    pub body: Block,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Param {
+    pub id: NodeId,
+    pub name: Identifier,
+    pub ty: TypeExpr,
+    pub span: Span,
+}
+
+/// A constant like `const MAX: i64 = 1024;`, declared as an [Item] or as a [Stmt].
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConstDecl {
+    pub id: NodeId,
+    pub docs: Option<String>,
+    pub attributes: Vec<Attribute>,
+    pub visibility: Visibility,
+    pub name: Identifier,
+    pub ty: TypeExpr,
+    pub value: ExprId,
+    pub span: Span,
+}
+
+/// A record type like `struct Box<T> { value: T }`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StructDecl {
+    pub id: NodeId,
+    pub docs: Option<String>,
+    pub attributes: Vec<Attribute>,
+    pub visibility: Visibility,
+    pub name: Identifier,
+    /// The generic type parameters like `T` in `struct Box<T>`.
+    pub generics: Vec<Identifier>,
+    pub fields: Vec<Field>,
+    pub span: Span,
+}
+
+/// A tagged union like `enum Shape { Circle(f64), Rect { w: f64, h: f64 } }`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnumDecl {
+    pub id: NodeId,
+    pub docs: Option<String>,
+    pub attributes: Vec<Attribute>,
+    pub visibility: Visibility,
+    pub name: Identifier,
+    pub variants: Vec<Variant>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Variant {
+    pub id: NodeId,
+    pub docs: Option<String>,
+    pub name: Identifier,
+    pub payload: VariantPayload,
+    pub span: Span,
+}
+
+/// The data carried by a [Variant].
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VariantPayload {
+    /// A variant without data like `None`.
+    Unit,
+    /// A variant with positional data like `Circle(f64)`.
+    Tuple(Vec<TypeExpr>),
+    /// A variant with named data like `Rect { w: f64, h: f64 }`.
+    Struct(Vec<Field>),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Field {
+    pub id: NodeId,
+    pub docs: Option<String>,
+    pub name: Identifier,
+    pub ty: TypeExpr,
+    pub span: Span,
+}
+
+/// An interface like `trait Printable { fn print(self); }` listing the methods its
+/// implementors provide.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TraitDecl {
+    pub id: NodeId,
+    pub docs: Option<String>,
+    pub attributes: Vec<Attribute>,
+    pub visibility: Visibility,
+    pub name: Identifier,
+    pub generics: Vec<Identifier>,
+    pub methods: Vec<TraitMethod>,
+    pub span: Span,
+}
+
+/// A method signature like `fn print(self);` in a [TraitDecl], optionally with a default body.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TraitMethod {
+    pub id: NodeId,
+    pub docs: Option<String>,
+    pub attributes: Vec<Attribute>,
+    pub name: Identifier,
+    pub generics: Vec<Identifier>,
+    /// The [Span] of the leading `self` parameter, if the method takes one.
+    pub receiver: Option<Span>,
+    pub params: Vec<Param>,
+    pub return_type: Option<TypeExpr>,
+    pub body: Option<Block>,
+    pub span: Span,
+}
+
+/// A type as written in the source, like `i64`, `&List<i64>` or `fn(i64) -> bool`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TypeExpr {
+    Named(NamedType),
+    Ref(RefType),
+    Ptr(PtrType),
+    Array(ArrayType),
+    Tuple(TupleType),
+    Func(FuncType),
+    Optional(OptionalType),
+}
+
+impl TypeExpr {
+    /// Returns the [Span] covering the whole type.
+    pub fn span(&self) -> Span {
+        match self {
+            TypeExpr::Named(named) => named.span,
+            TypeExpr::Ref(reference) => reference.span,
+            TypeExpr::Ptr(pointer) => pointer.span,
+            TypeExpr::Array(array) => array.span,
+            TypeExpr::Tuple(tuple) => tuple.span,
+            TypeExpr::Func(func) => func.span,
+            TypeExpr::Optional(optional) => optional.span,
+        }
+    }
+
+    /// Returns an adapter displaying the type as written in the source, printing the
+    /// lengths of its array types from the given [ExprArena].
+    pub fn display<'a>(&'a self, exprs: &'a ExprArena) -> TypeExprDisplay<'a> {
+        TypeExprDisplay { ty: self, exprs }
+    }
+}
+
+/// Displays a [TypeExpr] as written in the source, see [TypeExpr::display].
+pub struct TypeExprDisplay<'a> {
+    ty: &'a TypeExpr,
+    exprs: &'a ExprArena,
+}
+
+impl<'a> TypeExprDisplay<'a> {
+    fn nested(&self, ty: &'a TypeExpr) -> TypeExprDisplay<'a> {
+        ty.display(self.exprs)
+    }
+
+    /// Writes the given types separated by commas.
+    fn list(&self, f: &mut fmt::Formatter<'_>, types: &'a [TypeExpr]) -> fmt::Result {
+        for (index, ty) in types.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", self.nested(ty))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TypeExprDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.ty {
+            TypeExpr::Named(named) => {
+                write!(f, "{}", named.name.text)?;
+                if !named.args.is_empty() {
+                    write!(f, "<")?;
+                    self.list(f, &named.args)?;
+                    write!(f, ">")?;
+                }
+                Ok(())
+            }
+            TypeExpr::Ref(reference) => write!(f, "&{}", self.nested(&reference.inner)),
+            TypeExpr::Ptr(pointer) => write!(f, "*{}", self.nested(&pointer.inner)),
+            TypeExpr::Array(array) => write!(
+                f,
+                "[{}; {}]",
+                self.nested(&array.element),
+                pretty::expr(self.exprs, array.len)
+            ),
+            TypeExpr::Tuple(tuple) => {
+                write!(f, "(")?;
+                self.list(f, &tuple.elements)?;
+                if tuple.elements.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+            TypeExpr::Func(func) => {
+                write!(f, "fn(")?;
+                self.list(f, &func.params)?;
+                write!(f, ")")?;
+                match &func.return_type {
+                    Some(return_type) => write!(f, " -> {}", self.nested(return_type)),
+                    None => Ok(()),
+                }
+            }
+            TypeExpr::Optional(optional) => match *optional.inner {
+                // prefix and function types would otherwise take the `?` into their operand.
+                TypeExpr::Ref(_) | TypeExpr::Ptr(_) | TypeExpr::Func(_) => {
+                    write!(f, "({})?", self.nested(&optional.inner))
+                }
+                _ => write!(f, "{}?", self.nested(&optional.inner)),
+            },
+        }
+    }
+}
+
+/// A type referred to by name like `i64`, optionally applied to generic arguments like `List<i64>`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NamedType {
+    pub id: NodeId,
+    pub name: Identifier,
+    pub args: Vec<TypeExpr>,
+    pub span: Span,
+}
+
+/// A reference type like `&i64`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RefType {
+    pub id: NodeId,
+    pub inner: Box<TypeExpr>,
+    pub span: Span,
+}
+
+/// A raw pointer type like `*i64`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PtrType {
+    pub id: NodeId,
+    pub inner: Box<TypeExpr>,
+    pub span: Span,
+}
+
+/// A fixed-size array type like `[i64; 4]` or `[i64; SIZE * 2]`, whose length is
+/// evaluated at compile time.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArrayType {
+    pub id: NodeId,
+    pub element: Box<TypeExpr>,
+    pub len: ExprId,
+    pub span: Span,
+}
+
+/// A tuple type like `(i64, f64)`, where `()` is the unit type.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TupleType {
+    pub id: NodeId,
+    pub elements: Vec<TypeExpr>,
+    pub span: Span,
+}
+
+/// A function type like `fn(i64) -> bool`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FuncType {
+    pub id: NodeId,
+    pub params: Vec<TypeExpr>,
+    pub return_type: Option<Box<TypeExpr>>,
+    pub span: Span,
+}
+
+/// An optional type like `i64?`, whose values are either a `T` or `none`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OptionalType {
+    pub id: NodeId,
+    pub inner: Box<TypeExpr>,
+    pub span: Span,
+}
+
+/// A sequence of [Stmt]s enclosed in braces, whose value is the optional trailing [Expr].
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+    pub id: NodeId,
+    pub stmts: Vec<Stmt>,
+    pub tail: Option<ExprId>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Stmt {
+    Let(LetStmt),
+    Const(ConstDecl),
+    Expr(ExprStmt),
+    Return(ReturnStmt),
+    For(ForStmt),
+    Break(BreakStmt),
+    Continue(ContinueStmt),
+    /// The source of a statement that failed to parse.
+    Error(Span),
+}
+
+impl Stmt {
+    /// Returns the [Span] covering the whole statement.
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Let(let_stmt) => let_stmt.span,
+            Stmt::Const(const_decl) => const_decl.span,
+            Stmt::Expr(expr_stmt) => expr_stmt.span,
+            Stmt::Return(return_stmt) => return_stmt.span,
+            Stmt::For(for_stmt) => for_stmt.span,
+            Stmt::Break(break_stmt) => break_stmt.span,
+            Stmt::Continue(continue_stmt) => continue_stmt.span,
+            Stmt::Error(span) => *span,
+        }
+    }
+
+    /// Returns the [Attribute]s of the statement, which are empty for errors.
+    pub fn attributes(&self) -> &[Attribute] {
+        match self {
+            Stmt::Let(let_stmt) => &let_stmt.attributes,
+            Stmt::Const(const_decl) => &const_decl.attributes,
+            Stmt::Expr(expr_stmt) => &expr_stmt.attributes,
+            Stmt::Return(return_stmt) => &return_stmt.attributes,
+            Stmt::For(for_stmt) => &for_stmt.attributes,
+            Stmt::Break(break_stmt) => &break_stmt.attributes,
+            Stmt::Continue(continue_stmt) => &continue_stmt.attributes,
+            Stmt::Error(_) => &[],
+        }
+    }
+}
+
+/// A binding like `let x: i64 = 1;` introducing the names bound by `pattern` into the
+/// enclosing [Block].
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LetStmt {
+    pub id: NodeId,
+    pub attributes: Vec<Attribute>,
+    pub mutability: Mutability,
+    pub pattern: Pattern,
+    pub ty: Option<TypeExpr>,
+    pub value: Option<ExprId>,
+    pub span: Span,
+}
+
+/// An [Expr] evaluated for its side effects, terminated by a `;`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExprStmt {
+    pub id: NodeId,
+    pub attributes: Vec<Attribute>,
+    pub expr: ExprId,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReturnStmt {
+    pub id: NodeId,
+    pub attributes: Vec<Attribute>,
+    pub value: Option<ExprId>,
+    pub span: Span,
+}
+
+/// A loop like `for i in 0..10 { ... }` binding each element of the iterable in turn.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForStmt {
+    pub id: NodeId,
+    pub attributes: Vec<Attribute>,
+    pub binding: Identifier,
+    pub iterable: ExprId,
+    pub body: Block,
+    pub span: Span,
+}
+
+/// Exits the innermost loop, optionally making `value` the result of a `loop`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BreakStmt {
+    pub id: NodeId,
+    pub attributes: Vec<Attribute>,
+    pub value: Option<ExprId>,
+    pub span: Span,
+}
+
+/// Skips to the next iteration of the innermost loop.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContinueStmt {
+    pub id: NodeId,
+    pub attributes: Vec<Attribute>,
+    pub span: Span,
+}
+
+/// The shape of a value in a `let` or a match arm, binding the names it contains.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Pattern {
+    /// The `_` pattern matching anything without binding it.
+    Wildcard(Span),
+    /// The `none` pattern matching the absent value of an optional type.
+    None(Span),
+    /// A name like `x` bound to the matched value.
+    Binding(BindingPattern),
+    Integer(IntegerLiteral),
+    Float(FloatLiteral),
+    String(StringLiteral),
+    Tuple(TuplePattern),
+    /// A unit variant like `Shape::Empty`, whose path has more than one segment.
+    Path(PathPattern),
+    /// A variant or struct with positional fields like `Shape::Circle(r)`.
+    TupleStruct(TupleStructPattern),
+    /// A variant or struct with named fields like `Point { x, y: 0 }`.
+    Struct(StructPattern),
+}
+
+impl Pattern {
+    /// Returns the [Span] covering the whole pattern.
+    pub fn span(&self) -> Span {
+        match self {
+            Pattern::Wildcard(span) | Pattern::None(span) => *span,
+            Pattern::Binding(binding) => binding.name.span,
+            Pattern::Integer(integer) => integer.span,
+            Pattern::Float(float) => float.span,
+            Pattern::String(string) => string.span,
+            Pattern::Tuple(tuple) => tuple.span,
+            Pattern::Path(path) => path.span,
+            Pattern::TupleStruct(tuple_struct) => tuple_struct.span,
+            Pattern::Struct(struct_pattern) => struct_pattern.span,
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        /// Writes the given path segments separated by `::`.
+        fn path(f: &mut fmt::Formatter<'_>, segments: &[Identifier]) -> fmt::Result {
+            for (index, segment) in segments.iter().enumerate() {
+                if index > 0 {
+                    write!(f, "::")?;
+                }
+                write!(f, "{}", segment.text)?;
+            }
+            Ok(())
+        }
+
+        /// Writes the given patterns separated by commas.
+        fn list(f: &mut fmt::Formatter<'_>, patterns: &[Pattern]) -> fmt::Result {
+            for (index, pattern) in patterns.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{pattern}")?;
+            }
+            Ok(())
+        }
+
+        match self {
+            Pattern::Wildcard(_) => write!(f, "_"),
+            Pattern::None(_) => write!(f, "none"),
+            Pattern::Binding(binding) => write!(f, "{}", binding.name.text),
+            Pattern::Integer(integer) => write!(f, "{}", integer.value),
+            Pattern::Float(float) => write!(f, "{}", pretty::float_text(float)),
+            Pattern::String(string) => write!(f, "{}", pretty::string_text(string)),
+            Pattern::Tuple(tuple) => {
+                write!(f, "(")?;
+                list(f, &tuple.elements)?;
+                if tuple.elements.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+            Pattern::Path(path_pattern) => path(f, &path_pattern.segments),
+            Pattern::TupleStruct(tuple_struct) => {
+                path(f, &tuple_struct.path)?;
+                write!(f, "(")?;
+                list(f, &tuple_struct.elements)?;
+                write!(f, ")")
+            }
+            Pattern::Struct(struct_pattern) => {
+                path(f, &struct_pattern.path)?;
+                write!(f, " {{")?;
+                for (index, field) in struct_pattern.fields.iter().enumerate() {
+                    write!(f, "{}", if index > 0 { ", " } else { " " })?;
+                    match &field.pattern {
+                        Some(pattern) => write!(f, "{}: {pattern}", field.name.text)?,
+                        None => write!(f, "{}", field.name.text)?,
+                    }
+                }
+                if !struct_pattern.fields.is_empty() {
+                    write!(f, " ")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BindingPattern {
+    pub id: NodeId,
+    pub name: Identifier,
+}
+
+/// A tuple pattern like `(a, _)`, where `()` matches the unit value.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TuplePattern {
+    pub id: NodeId,
+    pub elements: Vec<Pattern>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PathPattern {
+    pub id: NodeId,
+    pub segments: Vec<Identifier>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TupleStructPattern {
+    pub id: NodeId,
+    pub path: Vec<Identifier>,
+    pub elements: Vec<Pattern>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StructPattern {
+    pub id: NodeId,
+    pub path: Vec<Identifier>,
+    pub fields: Vec<FieldPattern>,
+    pub span: Span,
+}
+
+/// A field of a [StructPattern] like `y: 0`, or `x` binding the field to its own name.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldPattern {
+    pub id: NodeId,
+    pub name: Identifier,
+    /// The pattern after the `:`, [None] for the shorthand binding the field's name.
+    pub pattern: Option<Pattern>,
+    pub span: Span,
+}
+
+/// An expression, kept small because the [ExprArena] stores them inline: children are
+/// [ExprId]s and payloads larger than a few words are boxed.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expr {
     Identifier(Identifier),
+    Path(PathExpr),
     Integer(IntegerLiteral),
     Float(FloatLiteral),
+    String(StringLiteral),
+    Paren(ParenExpr),
+    Tuple(TupleExpr),
+    Unary(UnaryExpr),
+    Binary(BinaryExpr),
+    Cast(Box<CastExpr>),
+    Assign(AssignExpr),
+    Range(RangeExpr),
+    Call(CallExpr),
+    Field(Box<FieldExpr>),
+    Section(SectionExpr),
+    Block(Box<Block>),
+    If(Box<IfExpr>),
+    While(Box<WhileExpr>),
+    Loop(Box<LoopExpr>),
+    Match(MatchExpr),
+    Assert(AssertExpr),
+    Panic(PanicExpr),
+    /// The `none` literal, the absent value of every optional type.
+    None(Span),
+    /// The source of an expression that failed to parse.
+    Error(Span),
+}
+
+impl Expr {
+    /// Returns the [Span] covering the whole expression.
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Identifier(ident) => ident.span,
+            Expr::Path(path) => path.span,
+            Expr::Integer(integer) => integer.span,
+            Expr::Float(float) => float.span,
+            Expr::String(string) => string.span,
+            Expr::Paren(paren) => paren.span,
+            Expr::Tuple(tuple) => tuple.span,
+            Expr::Unary(unary) => unary.span,
+            Expr::Binary(binary) => binary.span,
+            Expr::Cast(cast) => cast.span,
+            Expr::Assign(assign) => assign.span,
+            Expr::Range(range) => range.span,
+            Expr::Call(call) => call.span,
+            Expr::Field(field) => field.span,
+            Expr::Section(section) => section.span,
+            Expr::Block(block) => block.span,
+            Expr::If(if_expr) => if_expr.span,
+            Expr::While(while_expr) => while_expr.span,
+            Expr::Loop(loop_expr) => loop_expr.span,
+            Expr::Match(match_expr) => match_expr.span,
+            Expr::Assert(assert) => assert.span,
+            Expr::Panic(panic) => panic.span,
+            Expr::None(span) | Expr::Error(span) => *span,
+        }
+    }
+
+    /// Returns whether the expression ends in a block, which means it doesn't
+    /// need a `;` to be used as a statement.
+    pub fn is_block_like(&self) -> bool {
+        matches!(
+            self,
+            Expr::Block(_) | Expr::If(_) | Expr::While(_) | Expr::Loop(_) | Expr::Match(_)
+        )
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Identifier {
     pub text: String,
     pub span: Span,
 }
 
+/// A path to an item inside another like `Shape::Circle`, which has at least two segments.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PathExpr {
+    pub segments: Vec<Identifier>,
+    pub span: Span,
+}
+
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IntegerLiteral {
-    pub value: u64,
+    /// The value, which is negative if a `-` was folded into the literal.
+    pub value: i128,
     pub span: Span,
 }
 
+impl IntegerLiteral {
+    /// Folds the `-` at `minus_span` preceding the literal into its value.
+    pub fn negate(&mut self, minus_span: Span) {
+        self.value = -self.value;
+        self.span = minus_span.to(self.span);
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FloatLiteral {
     /// The bit representation of the f64.
     pub value_bits: u64,
     pub span: Span,
 }
+
+impl FloatLiteral {
+    /// Folds the `-` at `minus_span` preceding the literal into its value.
+    pub fn negate(&mut self, minus_span: Span) {
+        self.value_bits = (-f64::from_bits(self.value_bits)).to_bits();
+        self.span = minus_span.to(self.span);
+    }
+}
+
+/// A string literal like `"hi\n"`, whose `value` has its escapes processed.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StringLiteral {
+    pub value: String,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParenExpr {
+    pub expr: ExprId,
+    pub span: Span,
+}
+
+/// A tuple like `(1, 2.0)`, where `()` is the unit value and `(1,)` has a single element.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TupleExpr {
+    pub elements: Box<[ExprId]>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnaryOp {
+    Neg,
+    Not,
+}
+
+impl UnaryOp {
+    /// Returns the operator as written in the source.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            UnaryOp::Neg => "-",
+            UnaryOp::Not => "!",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnaryExpr {
+    pub op: UnaryOp,
+    pub operand: ExprId,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Equal,
+    Unequal,
+    LessThan,
+    LessEqual,
+    GreaterThan,
+    GreaterEqual,
+}
+
+impl BinaryOp {
+    /// Returns the operator as written in the source.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+            BinaryOp::Equal => "==",
+            BinaryOp::Unequal => "!=",
+            BinaryOp::LessThan => "<",
+            BinaryOp::LessEqual => "<=",
+            BinaryOp::GreaterThan => ">",
+            BinaryOp::GreaterEqual => ">=",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BinaryExpr {
+    pub op: BinaryOp,
+    pub lhs: ExprId,
+    pub rhs: ExprId,
+    pub span: Span,
+}
+
+/// An explicit conversion like `x as f64`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CastExpr {
+    pub expr: ExprId,
+    pub ty: TypeExpr,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AssignExpr {
+    pub target: ExprId,
+    pub value: ExprId,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RangeKind {
+    /// A range like `a..b` that excludes its end.
+    HalfOpen,
+    /// A range like `a..=b` that includes its end.
+    Inclusive,
+}
+
+/// A range of values like `0..10` or `1..=n`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RangeExpr {
+    pub kind: RangeKind,
+    pub start: ExprId,
+    pub end: ExprId,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CallExpr {
+    pub callee: ExprId,
+    pub args: Box<[ExprId]>,
+    pub span: Span,
+}
+
+/// An access of a struct field like `point.x`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldExpr {
+    pub base: ExprId,
+    pub field: Identifier,
+    pub span: Span,
+}
+
+/// An operator section like `(+ 1)` or `(_ * 2)`, which is a function of the missing operand.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SectionExpr {
+    pub op: BinaryOp,
+    /// The left operand, [None] if it is the hole.
+    pub lhs: Option<ExprId>,
+    /// The right operand, [None] if it is the hole.
+    pub rhs: Option<ExprId>,
+    pub span: Span,
+}
+
+/// A conditional `if cond { ... } else { ... }`, where `else_branch` is either
+/// an [Expr::Block] or another [Expr::If].
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IfExpr {
+    pub cond: ExprId,
+    pub then_block: Block,
+    pub else_branch: Option<ExprId>,
+    pub span: Span,
+}
+
+/// A `while cond { ... }` loop.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WhileExpr {
+    pub cond: ExprId,
+    pub body: Block,
+    pub span: Span,
+}
+
+/// An infinite `loop { ... }` whose value is given by the `break` leaving it.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LoopExpr {
+    pub body: Block,
+    pub span: Span,
+}
+
+/// A `match scrutinee { pattern => value, ... }` taking the value of the first matching arm.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchExpr {
+    pub scrutinee: ExprId,
+    pub arms: Box<[MatchArm]>,
+    pub span: Span,
+}
+
+/// An `assert(cond, message)` stopping the program with the optional message if `cond` is false.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AssertExpr {
+    pub cond: ExprId,
+    pub message: Option<ExprId>,
+    pub span: Span,
+}
+
+/// A `panic(message)` unconditionally stopping the program with the optional message.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PanicExpr {
+    pub message: Option<ExprId>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchArm {
+    pub id: NodeId,
+    pub pattern: Pattern,
+    pub value: ExprId,
+    pub span: Span,
+}
+
+#[cfg(test)]
+mod test {
+    use std::mem::size_of;
+
+    use crate::ast::Expr;
+
+    #[test]
+    fn expr_size() {
+        // every expression is stored inline in the arena, so new variants must stay small.
+        assert!(size_of::<Expr>() <= 6 * size_of::<usize>());
+    }
+}
@@ -0,0 +1,841 @@
+use crate::ast;
+
+/// Prints the given [ast::Module] as canonical ELAN source.
+///
+/// Parentheses are added wherever the tree wouldn't parse back the same way
+/// without them. Error nodes print as `<error>`, so source that failed to parse
+/// doesn't round-trip.
+pub fn print(module: &ast::Module) -> String {
+    let mut printer = Printer {
+        out: String::new(),
+        indent: 0,
+        exprs: &module.exprs,
+    };
+
+    for (index, item) in module.items.iter().enumerate() {
+        if index > 0 {
+            printer.out.push('\n');
+        }
+        printer.item(item);
+        printer.out.push('\n');
+    }
+
+    printer.out
+}
+
+/// Prints the given [ast::Expr] as canonical ELAN source, like [print].
+pub fn expr(exprs: &ast::ExprArena, id: ast::ExprId) -> String {
+    let mut printer = Printer {
+        out: String::new(),
+        indent: 0,
+        exprs,
+    };
+    printer.expr(id, ASSIGN);
+    printer.out
+}
+
+/// The precedence of assignments, the loosest binding expressions.
+const ASSIGN: u8 = 1;
+const RANGE: u8 = 2;
+const CAST: u8 = 7;
+const UNARY: u8 = 8;
+const CALL: u8 = 9;
+/// The precedence of expressions that never need parentheses.
+const ATOM: u8 = 10;
+
+/// Returns how tightly the given [ast::Expr] binds its operands, higher binds tighter.
+fn precedence(expr: &ast::Expr) -> u8 {
+    match expr {
+        ast::Expr::Assign(_) => ASSIGN,
+        ast::Expr::Range(_) => RANGE,
+        ast::Expr::Binary(binary) => binary_precedence(binary.op),
+        ast::Expr::Cast(_) => CAST,
+        ast::Expr::Unary(_) => UNARY,
+        ast::Expr::Call(_) | ast::Expr::Field(_) => CALL,
+        _ => ATOM,
+    }
+}
+
+fn binary_precedence(op: ast::BinaryOp) -> u8 {
+    match op {
+        ast::BinaryOp::Equal | ast::BinaryOp::Unequal => 3,
+        ast::BinaryOp::LessThan
+        | ast::BinaryOp::LessEqual
+        | ast::BinaryOp::GreaterThan
+        | ast::BinaryOp::GreaterEqual => 4,
+        ast::BinaryOp::Add | ast::BinaryOp::Sub => 5,
+        ast::BinaryOp::Mul | ast::BinaryOp::Div | ast::BinaryOp::Rem => 6,
+    }
+}
+
+struct Printer<'a> {
+    out: String,
+    indent: usize,
+    exprs: &'a ast::ExprArena,
+}
+
+impl Printer<'_> {
+    /// Starts a new line at the current indentation.
+    fn newline(&mut self) {
+        self.out.push('\n');
+        for _ in 0..self.indent {
+            self.out.push_str("    ");
+        }
+    }
+
+    /// Prints the doc comment lines, each on its own line.
+    fn docs(&mut self, docs: Option<&str>) {
+        for line in docs.iter().flat_map(|docs| docs.split('\n')) {
+            self.out.push_str("///");
+            if !line.is_empty() {
+                self.out.push(' ');
+                self.out.push_str(line);
+            }
+            self.newline();
+        }
+    }
+
+    /// Prints the [ast::Field]s one per line on a deeper indentation, ending on a new line.
+    fn field_lines(&mut self, fields: &[ast::Field]) {
+        self.indent += 1;
+        for field in fields {
+            self.newline();
+            self.docs(field.docs.as_deref());
+            self.out.push_str(&format!(
+                "{}: {},",
+                field.name.text,
+                field.ty.display(self.exprs)
+            ));
+        }
+        self.indent -= 1;
+
+        self.newline();
+    }
+
+    /// Prints the [ast::Attribute]s, each on its own line.
+    fn attributes(&mut self, attributes: &[ast::Attribute]) {
+        for attr in attributes {
+            self.out.push('@');
+            self.out.push_str(&attr.name.text);
+            if !attr.args.is_empty() {
+                self.out.push('(');
+                self.list(&attr.args);
+                self.out.push(')');
+            }
+            self.newline();
+        }
+    }
+
+    /// Prints a function signature like `fn max<T>(a: T, b: T) -> T`.
+    fn signature(
+        &mut self,
+        name: &ast::Identifier,
+        generic_params: &[ast::Identifier],
+        receiver: bool,
+        params: &[ast::Param],
+        return_type: Option<&ast::TypeExpr>,
+    ) {
+        let receiver = receiver.then(|| "self".to_string());
+        let params: Vec<_> = receiver
+            .into_iter()
+            .chain(
+                params
+                    .iter()
+                    .map(|param| format!("{}: {}", param.name.text, param.ty.display(self.exprs))),
+            )
+            .collect();
+
+        self.out.push_str(&format!(
+            "fn {}{}({})",
+            name.text,
+            generics(generic_params),
+            params.join(", ")
+        ));
+        if let Some(return_type) = return_type {
+            self.out
+                .push_str(&format!(" -> {}", return_type.display(self.exprs)));
+        }
+    }
+
+    fn item(&mut self, item: &ast::Item) {
+        self.docs(item.docs());
+        self.attributes(item.attributes());
+        if item.visibility() == ast::Visibility::Public {
+            self.out.push_str("pub ");
+        }
+
+        match item {
+            ast::Item::Import(import) => {
+                let path: Vec<_> = import.path.iter().map(|seg| seg.text.as_str()).collect();
+                self.out.push_str("import ");
+                self.out.push_str(&path.join("::"));
+                if let Some(alias) = &import.alias {
+                    self.out.push_str(" as ");
+                    self.out.push_str(&alias.text);
+                }
+                self.out.push(';');
+            }
+            ast::Item::Func(func) => {
+                self.signature(
+                    &func.name,
+                    &func.generics,
+                    false,
+                    &func.params,
+                    func.return_type.as_ref(),
+                );
+                self.out.push(' ');
+                self.block(&func.body);
+            }
+            ast::Item::Const(const_decl) => self.const_decl(const_decl),
+            ast::Item::Struct(struct_decl) => {
+                self.out.push_str(&format!(
+                    "struct {}{} {{",
+                    struct_decl.name.text,
+                    generics(&struct_decl.generics)
+                ));
+                if struct_decl.fields.is_empty() {
+                    self.out.push('}');
+                    return;
+                }
+
+                self.field_lines(&struct_decl.fields);
+                self.out.push('}');
+            }
+            ast::Item::Enum(enum_decl) => {
+                self.out
+                    .push_str(&format!("enum {} {{", enum_decl.name.text));
+                if enum_decl.variants.is_empty() {
+                    self.out.push('}');
+                    return;
+                }
+
+                self.indent += 1;
+                for variant in &enum_decl.variants {
+                    self.newline();
+                    self.docs(variant.docs.as_deref());
+                    self.variant(variant);
+                    self.out.push(',');
+                }
+                self.indent -= 1;
+
+                self.newline();
+                self.out.push('}');
+            }
+            ast::Item::Trait(trait_decl) => {
+                self.out.push_str(&format!(
+                    "trait {}{} {{",
+                    trait_decl.name.text,
+                    generics(&trait_decl.generics)
+                ));
+                if trait_decl.methods.is_empty() {
+                    self.out.push('}');
+                    return;
+                }
+
+                self.indent += 1;
+                for method in &trait_decl.methods {
+                    self.newline();
+                    self.docs(method.docs.as_deref());
+                    self.attributes(&method.attributes);
+                    self.signature(
+                        &method.name,
+                        &method.generics,
+                        method.receiver.is_some(),
+                        &method.params,
+                        method.return_type.as_ref(),
+                    );
+                    match &method.body {
+                        Some(body) => {
+                            self.out.push(' ');
+                            self.block(body);
+                        }
+                        None => self.out.push(';'),
+                    }
+                }
+                self.indent -= 1;
+
+                self.newline();
+                self.out.push('}');
+            }
+            ast::Item::Error(_) => self.out.push_str("<error>"),
+        }
+    }
+
+    fn const_decl(&mut self, const_decl: &ast::ConstDecl) {
+        self.out.push_str(&format!(
+            "const {}: {} = ",
+            const_decl.name.text,
+            const_decl.ty.display(self.exprs)
+        ));
+        self.expr(const_decl.value, ASSIGN);
+        self.out.push(';');
+    }
+
+    fn variant(&mut self, variant: &ast::Variant) {
+        self.out.push_str(&variant.name.text);
+
+        match &variant.payload {
+            ast::VariantPayload::Unit => {}
+            ast::VariantPayload::Tuple(types) => {
+                let types: Vec<_> = types
+                    .iter()
+                    .map(|ty| ty.display(self.exprs).to_string())
+                    .collect();
+                self.out.push_str(&format!("({})", types.join(", ")));
+            }
+            ast::VariantPayload::Struct(fields) if fields.iter().any(|f| f.docs.is_some()) => {
+                self.out.push_str(" {");
+                self.field_lines(fields);
+                self.out.push('}');
+            }
+            ast::VariantPayload::Struct(fields) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|field| format!("{}: {}", field.name.text, field.ty.display(self.exprs)))
+                    .collect();
+                self.out.push_str(&format!(" {{ {} }}", fields.join(", ")));
+            }
+        }
+    }
+
+    fn block(&mut self, block: &ast::Block) {
+        if block.stmts.is_empty() && block.tail.is_none() {
+            self.out.push_str("{}");
+            return;
+        }
+
+        self.out.push('{');
+        self.indent += 1;
+
+        for (index, stmt) in block.stmts.iter().enumerate() {
+            // the expression starting the next statement, which might continue this one.
+            let next = match block.stmts.get(index + 1) {
+                // attributes start with `@`, which can't continue an expression.
+                Some(ast::Stmt::Expr(expr_stmt)) if expr_stmt.attributes.is_empty() => {
+                    Some(expr_stmt.expr)
+                }
+                Some(_) => None,
+                None => block.tail,
+            };
+            let is_last = index + 1 == block.stmts.len() && block.tail.is_none();
+
+            self.newline();
+            self.attributes(stmt.attributes());
+            self.stmt(
+                stmt,
+                is_last || next.is_some_and(|id| self.continues_expr(id)),
+            );
+        }
+
+        if let Some(tail) = block.tail {
+            self.newline();
+            self.expr(tail, ASSIGN);
+        }
+
+        self.indent -= 1;
+        self.newline();
+        self.out.push('}');
+    }
+
+    /// Prints the [ast::Stmt], where `terminate` forces a `;` after block like expressions.
+    fn stmt(&mut self, stmt: &ast::Stmt, terminate: bool) {
+        match stmt {
+            ast::Stmt::Let(let_stmt) => {
+                // `let mut` is written the shorter way.
+                self.out.push_str(match let_stmt.mutability {
+                    ast::Mutability::Immutable => "let ",
+                    ast::Mutability::Mutable => "var ",
+                });
+                self.out.push_str(&let_stmt.pattern.to_string());
+                if let Some(ty) = &let_stmt.ty {
+                    self.out.push_str(&format!(": {}", ty.display(self.exprs)));
+                }
+                if let Some(value) = let_stmt.value {
+                    self.out.push_str(" = ");
+                    self.expr(value, ASSIGN);
+                }
+                self.out.push(';');
+            }
+            ast::Stmt::Const(const_decl) => self.const_decl(const_decl),
+            ast::Stmt::Expr(expr_stmt) => {
+                self.expr(expr_stmt.expr, ASSIGN);
+                if terminate || !self.exprs[expr_stmt.expr].is_block_like() {
+                    self.out.push(';');
+                }
+            }
+            ast::Stmt::Return(return_stmt) => {
+                self.keyword_stmt("return", return_stmt.value);
+            }
+            ast::Stmt::For(for_stmt) => {
+                self.out
+                    .push_str(&format!("for {} in ", for_stmt.binding.text));
+                self.expr(for_stmt.iterable, ASSIGN);
+                self.out.push(' ');
+                self.block(&for_stmt.body);
+            }
+            ast::Stmt::Break(break_stmt) => self.keyword_stmt("break", break_stmt.value),
+            ast::Stmt::Continue(_) => self.out.push_str("continue;"),
+            ast::Stmt::Error(_) => self.out.push_str("<error>;"),
+        }
+    }
+
+    /// Prints a statement like `return x;`, consisting of a keyword and an optional value.
+    fn keyword_stmt(&mut self, keyword: &str, value: Option<ast::ExprId>) {
+        self.out.push_str(keyword);
+        if let Some(value) = value {
+            self.out.push(' ');
+            self.expr(value, ASSIGN);
+        }
+        self.out.push(';');
+    }
+
+    /// Prints the [ast::Expr], wrapped in parentheses if it binds looser than `min`.
+    fn expr(&mut self, id: ast::ExprId, min: u8) {
+        let exprs = self.exprs;
+        let expr = &exprs[id];
+
+        let parens = precedence(expr) < min;
+        if parens {
+            self.out.push('(');
+        }
+
+        match expr {
+            ast::Expr::Identifier(ident) => self.out.push_str(&ident.text),
+            ast::Expr::Path(path) => {
+                let segments: Vec<_> = path.segments.iter().map(|seg| seg.text.as_str()).collect();
+                self.out.push_str(&segments.join("::"));
+            }
+            ast::Expr::Integer(integer) => self.out.push_str(&integer.value.to_string()),
+            ast::Expr::Float(float) => self.out.push_str(&float_text(float)),
+            ast::Expr::String(string) => self.out.push_str(&string_text(string)),
+            ast::Expr::None(_) => self.out.push_str("none"),
+            ast::Expr::Paren(paren) => {
+                self.out.push('(');
+                self.expr(paren.expr, ASSIGN);
+                self.out.push(')');
+            }
+            ast::Expr::Tuple(tuple) => {
+                self.out.push('(');
+                self.list(&tuple.elements);
+                if tuple.elements.len() == 1 {
+                    self.out.push(',');
+                }
+                self.out.push(')');
+            }
+            ast::Expr::Unary(unary) => {
+                self.out.push_str(unary.op.symbol());
+                self.expr(unary.operand, UNARY);
+            }
+            ast::Expr::Binary(binary) => {
+                let precedence = binary_precedence(binary.op);
+
+                // a type directly followed by `<` would take it as its generic arguments.
+                let lhs_min = match binary.op == ast::BinaryOp::LessThan
+                    && self.ends_with_type(binary.lhs, precedence)
+                {
+                    true => ATOM,
+                    false => precedence,
+                };
+
+                self.expr(binary.lhs, lhs_min);
+                self.out.push_str(&format!(" {} ", binary.op.symbol()));
+                self.expr(binary.rhs, precedence + 1);
+            }
+            ast::Expr::Cast(cast) => {
+                self.expr(cast.expr, CAST);
+                self.out
+                    .push_str(&format!(" as {}", cast.ty.display(exprs)));
+            }
+            ast::Expr::Assign(assign) => {
+                self.expr(assign.target, RANGE);
+                self.out.push_str(" = ");
+                self.expr(assign.value, ASSIGN);
+            }
+            ast::Expr::Range(range) => {
+                self.expr(range.start, RANGE + 1);
+                self.out.push_str(match range.kind {
+                    ast::RangeKind::HalfOpen => "..",
+                    ast::RangeKind::Inclusive => "..=",
+                });
+                self.expr(range.end, RANGE + 1);
+            }
+            ast::Expr::Call(call) => {
+                self.expr(call.callee, CALL);
+                self.out.push('(');
+                self.list(&call.args);
+                self.out.push(')');
+            }
+            ast::Expr::Field(field) => {
+                // a number followed by a `.` would be lexed as a float.
+                match &self.exprs[field.base] {
+                    ast::Expr::Integer(_) | ast::Expr::Float(_) => {
+                        self.out.push('(');
+                        self.expr(field.base, ASSIGN);
+                        self.out.push(')');
+                    }
+                    _ => self.expr(field.base, CALL),
+                }
+                self.out.push('.');
+                self.out.push_str(&field.field.text);
+            }
+            ast::Expr::Assert(assert) => {
+                self.out.push_str("assert(");
+                self.expr(assert.cond, ASSIGN);
+                if let Some(message) = assert.message {
+                    self.out.push_str(", ");
+                    self.expr(message, ASSIGN);
+                }
+                self.out.push(')');
+            }
+            ast::Expr::Panic(panic) => {
+                self.out.push_str("panic(");
+                if let Some(message) = panic.message {
+                    self.expr(message, ASSIGN);
+                }
+                self.out.push(')');
+            }
+            ast::Expr::Section(section) => {
+                let precedence = binary_precedence(section.op);
+
+                self.out.push('(');
+                match section.lhs {
+                    Some(lhs) => self.expr(lhs, precedence),
+                    None => self.out.push('_'),
+                }
+                self.out.push_str(&format!(" {} ", section.op.symbol()));
+                match section.rhs {
+                    Some(rhs) => self.expr(rhs, precedence + 1),
+                    None => self.out.push('_'),
+                }
+                self.out.push(')');
+            }
+            ast::Expr::Block(block) => self.block(block),
+            ast::Expr::If(if_expr) => {
+                self.out.push_str("if ");
+                self.expr(if_expr.cond, ASSIGN);
+                self.out.push(' ');
+                self.block(&if_expr.then_block);
+                if let Some(else_branch) = if_expr.else_branch {
+                    self.out.push_str(" else ");
+                    self.expr(else_branch, ATOM);
+                }
+            }
+            ast::Expr::While(while_expr) => {
+                self.out.push_str("while ");
+                self.expr(while_expr.cond, ASSIGN);
+                self.out.push(' ');
+                self.block(&while_expr.body);
+            }
+            ast::Expr::Loop(loop_expr) => {
+                self.out.push_str("loop ");
+                self.block(&loop_expr.body);
+            }
+            ast::Expr::Match(match_expr) => {
+                self.out.push_str("match ");
+                self.expr(match_expr.scrutinee, ASSIGN);
+                self.out.push_str(" {");
+
+                self.indent += 1;
+                for arm in &match_expr.arms {
+                    self.newline();
+                    self.out.push_str(&arm.pattern.to_string());
+                    self.out.push_str(" => ");
+                    self.expr(arm.value, ASSIGN);
+                    self.out.push(',');
+                }
+                self.indent -= 1;
+
+                if !match_expr.arms.is_empty() {
+                    self.newline();
+                }
+                self.out.push('}');
+            }
+            ast::Expr::Error(_) => self.out.push_str("<error>"),
+        }
+
+        if parens {
+            self.out.push(')');
+        }
+    }
+
+    /// Prints the given expressions separated by commas.
+    fn list(&mut self, ids: &[ast::ExprId]) {
+        for (index, &id) in ids.iter().enumerate() {
+            if index > 0 {
+                self.out.push_str(", ");
+            }
+            self.expr(id, ASSIGN);
+        }
+    }
+
+    /// Returns whether the printed expression starts with a `(` or `-`, which would
+    /// continue a block like expression printed right before it.
+    fn continues_expr(&self, id: ast::ExprId) -> bool {
+        let expr = &self.exprs[id];
+
+        let (first, min) = match expr {
+            ast::Expr::Paren(_) | ast::Expr::Tuple(_) | ast::Expr::Section(_) => return true,
+            ast::Expr::Unary(unary) => return unary.op == ast::UnaryOp::Neg,
+            ast::Expr::Integer(integer) => return integer.value < 0,
+            ast::Expr::Float(float) => return f64::from_bits(float.value_bits).is_sign_negative(),
+            ast::Expr::Binary(binary) => (binary.lhs, binary_precedence(binary.op)),
+            ast::Expr::Cast(cast) => (cast.expr, CAST),
+            ast::Expr::Assign(assign) => (assign.target, RANGE),
+            ast::Expr::Range(range) => (range.start, RANGE + 1),
+            ast::Expr::Call(call) => (call.callee, CALL),
+            ast::Expr::Field(field) => (field.base, CALL),
+            _ => return false,
+        };
+
+        precedence(&self.exprs[first]) < min || self.continues_expr(first)
+    }
+
+    /// Returns whether the expression, printed without parentheses at the given
+    /// precedence, ends with the type of a cast.
+    fn ends_with_type(&self, id: ast::ExprId, min: u8) -> bool {
+        let expr = &self.exprs[id];
+
+        if precedence(expr) < min {
+            return false;
+        }
+
+        match expr {
+            ast::Expr::Cast(_) => true,
+            ast::Expr::Unary(unary) => self.ends_with_type(unary.operand, UNARY),
+            ast::Expr::Binary(binary) => {
+                self.ends_with_type(binary.rhs, binary_precedence(binary.op) + 1)
+            }
+            ast::Expr::Assign(assign) => self.ends_with_type(assign.value, ASSIGN),
+            ast::Expr::Range(range) => self.ends_with_type(range.end, RANGE + 1),
+            _ => false,
+        }
+    }
+}
+
+/// Returns the source text of a generic parameter list like `<T, U>`, which is empty without parameters.
+pub fn generics(generics: &[ast::Identifier]) -> String {
+    if generics.is_empty() {
+        return String::new();
+    }
+
+    let names: Vec<_> = generics.iter().map(|name| name.text.as_str()).collect();
+    format!("<{}>", names.join(", "))
+}
+
+/// Returns the source text of a float literal, which always contains a `.`.
+pub(super) fn float_text(float: &ast::FloatLiteral) -> String {
+    let text = format!("{:?}", f64::from_bits(float.value_bits));
+
+    match text.find('e') {
+        Some(index) if !text[..index].contains('.') => {
+            format!("{}.0{}", &text[..index], &text[index..])
+        }
+        _ => text,
+    }
+}
+
+/// Returns the source text of a string literal, escaping what needs to be escaped.
+pub(super) fn string_text(string: &ast::StringLiteral) -> String {
+    let mut text = String::with_capacity(string.value.len() + 2);
+    text.push('"');
+
+    for ch in string.value.chars() {
+        match ch {
+            '\n' => text.push_str("\\n"),
+            '\t' => text.push_str("\\t"),
+            '\r' => text.push_str("\\r"),
+            '\0' => text.push_str("\\0"),
+            '\\' => text.push_str("\\\\"),
+            '"' => text.push_str("\\\""),
+            ch => text.push(ch),
+        }
+    }
+
+    text.push('"');
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{error::SyntaxError, experiments::Experiments, lexer::Lexer, parser::Parser};
+
+    fn parse(input: &str) -> Result<ast::Module, SyntaxError> {
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+        parser.set_experiments(Experiments { sections: true });
+
+        let (module, errors) = parser.parse_module();
+        match errors.first() {
+            Some(err) => Err(*err),
+            None => Ok(module),
+        }
+    }
+
+    #[test]
+    fn canonical() -> Result<(), SyntaxError> {
+        let input = r#"
+            import math :: trig as t;
+            pub   const MAX : i64 = (1 + 2) * 3;
+            @inline @deprecated("use max", 2) pub fn old() { }
+            enum Shape { Empty, Circle(f64), Rect { w: f64, h: f64 }, }
+            /// A pair.
+            ///
+            ///    of two values
+            struct Pair<A, B,> { first: A, // not docs
+            //// neither
+            /// The second.
+            second: B }
+            enum Tree { /// A leaf.
+            Leaf, Node { /// The left.
+            left: i64, right: i64 } }
+            trait Show<T> { fn show(self, indent: i64) -> str; @inline fn print(self,) { show(self, 0) } fn new() -> T; }
+            fn max<T>(a: T, b: T) -> T { a }
+            fn main(args: List<str>) -> i64 {
+                const N: i64 = 2; let x: f64 = 1.0e10; let o: &i64 ? ? = none; let s = "a\"b\n"; let arr: [ i64 ;( N+1 ) * 2] = x;
+                for i in 0..=10 { if i == 0 { continue; } else if !(i < 5) { break; } else { } }
+                while x { }; -y; Shape :: Circle(1.5); assert(x > 0,); assert(x, "m"); panic("p",); panic();
+                loop { }; -1.5; p . first.second(1); (1).x; -(-p).x;
+                match x { 0 => (1, 2,), (_, "s", 1.5, (a)) => a, Shape::Empty => 0, none => 0, Some(y,) => y, P { x, y: (z,), } => z, n => { n } };
+                let (a, P {}) = x; @allow(unused) let z = 1; let mut m = 1; var v; loop { } @allow("no-main", z) -2;
+                (x as i64) < 2; x = y = (+ 1) ((_ * 2))
+            }
+        "#;
+
+        let expected = r#"import math::trig as t;
+
+pub const MAX: i64 = (1 + 2) * 3;
+
+@inline
+@deprecated("use max", 2)
+pub fn old() {}
+
+enum Shape {
+    Empty,
+    Circle(f64),
+    Rect { w: f64, h: f64 },
+}
+
+/// A pair.
+///
+///    of two values
+struct Pair<A, B> {
+    first: A,
+    /// The second.
+    second: B,
+}
+
+enum Tree {
+    /// A leaf.
+    Leaf,
+    Node {
+        /// The left.
+        left: i64,
+        right: i64,
+    },
+}
+
+trait Show<T> {
+    fn show(self, indent: i64) -> str;
+    @inline
+    fn print(self) {
+        show(self, 0)
+    }
+    fn new() -> T;
+}
+
+fn max<T>(a: T, b: T) -> T {
+    a
+}
+
+fn main(args: List<str>) -> i64 {
+    const N: i64 = 2;
+    let x: f64 = 10000000000.0;
+    let o: &i64?? = none;
+    let s = "a\"b\n";
+    let arr: [i64; (N + 1) * 2] = x;
+    for i in 0..=10 {
+        if i == 0 {
+            continue;
+        } else if !(i < 5) {
+            break;
+        } else {}
+    }
+    while x {};
+    -y;
+    Shape::Circle(1.5);
+    assert(x > 0);
+    assert(x, "m");
+    panic("p");
+    panic();
+    loop {};
+    -1.5;
+    p.first.second(1);
+    (1).x;
+    -(-p).x;
+    match x {
+        0 => (1, 2),
+        (_, "s", 1.5, a) => a,
+        Shape::Empty => 0,
+        none => 0,
+        Some(y) => y,
+        P { x, y: (z,) } => z,
+        n => {
+            n
+        },
+    }
+    let (a, P {}) = x;
+    @allow(unused)
+    let z = 1;
+    var m = 1;
+    var v;
+    loop {}
+    @allow("no-main", z)
+    -2;
+    (x as i64) < 2;
+    x = y = (_ + 1)((_ * 2))
+}
+"#;
+
+        let printed = print(&parse(input)?);
+        assert_eq!(printed, expected);
+        assert_eq!(print(&parse(&printed)?), printed);
+
+        Ok(())
+    }
+
+    #[test]
+    fn precedence() {
+        let integer = |value| {
+            ast::Expr::Integer(ast::IntegerLiteral {
+                value,
+                span: crate::token::Span { start: 0, end: 0 },
+            })
+        };
+        let binary = |op, lhs, rhs| {
+            ast::Expr::Binary(ast::BinaryExpr {
+                op,
+                lhs,
+                rhs,
+                span: crate::token::Span { start: 0, end: 0 },
+            })
+        };
+
+        let mut exprs = ast::ExprArena::default();
+        let one = exprs.alloc(integer(1));
+        let two = exprs.alloc(integer(2));
+        let three = exprs.alloc(integer(3));
+        let sum = exprs.alloc(binary(ast::BinaryOp::Add, one, two));
+        let difference = exprs.alloc(binary(ast::BinaryOp::Sub, three, sum));
+        let product = exprs.alloc(binary(ast::BinaryOp::Mul, sum, difference));
+
+        let mut printer = Printer {
+            out: String::new(),
+            indent: 0,
+            exprs: &exprs,
+        };
+        printer.expr(product, ASSIGN);
+
+        assert_eq!(printer.out, "(1 + 2) * (3 - (1 + 2))");
+    }
+}
@@ -0,0 +1,503 @@
+use std::fmt::{self, Write};
+
+use crate::{
+    ast,
+    token::{Span, Token, TokenKind},
+    visit::{self, Visitor},
+};
+
+/// The kind of a [GreenNode], named after the [ast] node it covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    Module,
+    Item,
+    Attribute,
+    Param,
+    Variant,
+    TraitMethod,
+    Type,
+    Block,
+    Stmt,
+    Expr,
+    MatchArm,
+    Pattern,
+}
+
+/// The kind of a [GreenToken], which is either a lexed [Token] or trivia the lexer skips.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LeafKind {
+    Token(TokenKind),
+    Whitespace,
+    /// A `//` comment up to the end of its line, excluding the newline.
+    Comment,
+}
+
+/// A leaf of the lossless syntax tree holding its source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GreenToken {
+    pub kind: LeafKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GreenElement {
+    Node(GreenNode),
+    Token(GreenToken),
+}
+
+/// A node of the lossless syntax tree, whose tokens spell out exactly the source text
+/// it was built from, including whitespace and comments.
+///
+/// Green nodes don't know their position, see [SyntaxNode] for a positioned view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GreenNode {
+    pub kind: NodeKind,
+    pub children: Vec<GreenElement>,
+    /// The length of the node's text in chars, the unit of a [Span].
+    len: usize,
+}
+
+impl GreenElement {
+    /// Returns the length of the element's text in chars.
+    pub fn len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.len,
+            GreenElement::Token(token) => token.text.chars().count(),
+        }
+    }
+
+    /// Returns whether the element covers no text.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl GreenNode {
+    /// Creates a new [GreenNode] of the given kind from its children.
+    pub fn new(kind: NodeKind, children: Vec<GreenElement>) -> Self {
+        let len = children.iter().map(GreenElement::len).sum();
+
+        Self {
+            kind,
+            children,
+            len,
+        }
+    }
+
+    /// Returns the length of the node's text in chars.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the node covers no text.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a [SyntaxNode] viewing this node as the root of a tree starting at offset 0.
+    pub fn root(&self) -> SyntaxNode<'_> {
+        SyntaxNode {
+            green: self,
+            offset: 0,
+        }
+    }
+}
+
+impl fmt::Display for GreenNode {
+    /// Writes the source text the node was built from.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for child in &self.children {
+            match child {
+                GreenElement::Node(node) => write!(f, "{node}")?,
+                GreenElement::Token(token) => f.write_str(&token.text)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A [GreenNode] along with its position in the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntaxNode<'g> {
+    green: &'g GreenNode,
+    offset: usize,
+}
+
+/// A [GreenToken] along with its position in the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntaxToken<'g> {
+    green: &'g GreenToken,
+    offset: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxElement<'g> {
+    Node(SyntaxNode<'g>),
+    Token(SyntaxToken<'g>),
+}
+
+impl<'g> SyntaxNode<'g> {
+    pub fn kind(&self) -> NodeKind {
+        self.green.kind
+    }
+
+    pub fn green(&self) -> &'g GreenNode {
+        self.green
+    }
+
+    pub fn span(&self) -> Span {
+        Span {
+            start: self.offset,
+            end: self.offset + self.green.len,
+        }
+    }
+
+    /// Returns the child nodes and tokens, including trivia, in source order.
+    pub fn children(&self) -> impl Iterator<Item = SyntaxElement<'g>> + 'g {
+        let mut offset = self.offset;
+
+        self.green.children.iter().map(move |child| {
+            let element = match child {
+                GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode { green, offset }),
+                GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken { green, offset }),
+            };
+            offset += child.len();
+            element
+        })
+    }
+
+    /// Returns the innermost node whose [Span] contains the given offset.
+    pub fn covering_node(&self, offset: usize) -> SyntaxNode<'g> {
+        let child = self.children().find_map(|child| match child {
+            SyntaxElement::Node(node)
+                if node.span().start <= offset && offset < node.span().end =>
+            {
+                Some(node)
+            }
+            _ => None,
+        });
+
+        match child {
+            Some(child) => child.covering_node(offset),
+            None => *self,
+        }
+    }
+
+    /// Renders the tree as indented lines of kinds and spans, one line per node or token.
+    pub fn dump(&self) -> String {
+        let mut out = String::new();
+        self.dump_into(&mut out, 0);
+        out
+    }
+
+    fn dump_into(&self, out: &mut String, depth: usize) {
+        let Span { start, end } = self.span();
+        let _ = writeln!(
+            out,
+            "{:indent$}{:?}@{start}..{end}",
+            "",
+            self.kind(),
+            indent = depth * 2
+        );
+
+        for child in self.children() {
+            match child {
+                SyntaxElement::Node(node) => node.dump_into(out, depth + 1),
+                SyntaxElement::Token(token) => {
+                    let Span { start, end } = token.span();
+                    let _ = writeln!(
+                        out,
+                        "{:indent$}{:?}@{start}..{end} {:?}",
+                        "",
+                        token.kind(),
+                        token.text(),
+                        indent = (depth + 1) * 2
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl<'g> SyntaxToken<'g> {
+    pub fn kind(&self) -> LeafKind {
+        self.green.kind
+    }
+
+    pub fn text(&self) -> &'g str {
+        &self.green.text
+    }
+
+    pub fn span(&self) -> Span {
+        Span {
+            start: self.offset,
+            end: self.offset + self.green.text.chars().count(),
+        }
+    }
+
+    /// Returns whether the token is whitespace or a comment.
+    pub fn is_trivia(&self) -> bool {
+        matches!(self.kind(), LeafKind::Whitespace | LeafKind::Comment)
+    }
+}
+
+/// Builds the lossless syntax tree of `source` from its [Token]s and the [ast::Module]
+/// parsed from them.
+///
+/// The AST nodes give the tree its structure and the gaps between tokens are kept as
+/// trivia, so the tree's text is exactly `source`. Trivia between two sibling nodes
+/// belongs to their parent.
+pub fn build(source: &str, tokens: &[Token], module: &ast::Module) -> GreenNode {
+    let mut collector = SpanCollector {
+        exprs: &module.exprs,
+        nodes: Vec::new(),
+    };
+    collector.visit_module(module);
+
+    let mut nodes = collector.nodes;
+    // parents are visited before their children, so a stable sort keeps them first.
+    nodes.sort_by_key(|(_, span)| (span.start, std::cmp::Reverse(span.end)));
+
+    let mut builder = Builder {
+        chars: source.char_indices().map(|(index, _)| index).collect(),
+        source,
+        pos: 0,
+        stack: vec![(NodeKind::Module, usize::MAX, Vec::new())],
+    };
+    builder.chars.push(source.len());
+
+    let mut nodes = nodes.into_iter().peekable();
+
+    for token in tokens {
+        builder.close_until(token.span.start);
+        builder.trivia(token.span.start);
+
+        while let Some(&(kind, span)) = nodes.peek() {
+            if span.start > token.span.start {
+                break;
+            }
+
+            nodes.next();
+
+            // nodes that don't start at a token or overlap their parent can't be nested.
+            let parent_end = builder.stack.last().map_or(usize::MAX, |(_, end, _)| *end);
+            if span.start == token.span.start && span.end > span.start && span.end <= parent_end {
+                builder.stack.push((kind, span.end, Vec::new()));
+            }
+        }
+
+        builder.push(GreenElement::Token(GreenToken {
+            kind: LeafKind::Token(token.kind),
+            text: token.text.to_string(),
+        }));
+        builder.pos = token.span.end;
+    }
+
+    builder.close_until(usize::MAX - 1);
+    builder.trivia(builder.chars.len() - 1);
+
+    let (kind, _, children) = builder
+        .stack
+        .pop()
+        .expect("the module node is never closed");
+    GreenNode::new(kind, children)
+}
+
+/// Collects the kind and [Span] of every AST node in visiting order.
+struct SpanCollector<'ast> {
+    exprs: &'ast ast::ExprArena,
+    nodes: Vec<(NodeKind, Span)>,
+}
+
+impl<'ast> Visitor<'ast> for SpanCollector<'ast> {
+    fn exprs(&self) -> &'ast ast::ExprArena {
+        self.exprs
+    }
+
+    fn visit_item(&mut self, item: &'ast ast::Item) {
+        self.nodes.push((NodeKind::Item, item.span()));
+        visit::walk_item(self, item);
+    }
+
+    fn visit_attribute(&mut self, attr: &'ast ast::Attribute) {
+        self.nodes.push((NodeKind::Attribute, attr.span));
+        visit::walk_attribute(self, attr);
+    }
+
+    fn visit_param(&mut self, param: &'ast ast::Param) {
+        self.nodes.push((NodeKind::Param, param.span));
+        visit::walk_param(self, param);
+    }
+
+    fn visit_variant(&mut self, variant: &'ast ast::Variant) {
+        self.nodes.push((NodeKind::Variant, variant.span));
+        visit::walk_variant(self, variant);
+    }
+
+    fn visit_trait_method(&mut self, method: &'ast ast::TraitMethod) {
+        self.nodes.push((NodeKind::TraitMethod, method.span));
+        visit::walk_trait_method(self, method);
+    }
+
+    fn visit_type(&mut self, ty: &'ast ast::TypeExpr) {
+        self.nodes.push((NodeKind::Type, ty.span()));
+        visit::walk_type(self, ty);
+    }
+
+    fn visit_block(&mut self, block: &'ast ast::Block) {
+        self.nodes.push((NodeKind::Block, block.span));
+        visit::walk_block(self, block);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast ast::Stmt) {
+        self.nodes.push((NodeKind::Stmt, stmt.span()));
+        visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, id: ast::ExprId) {
+        self.nodes.push((NodeKind::Expr, self.exprs[id].span()));
+        visit::walk_expr(self, id);
+    }
+
+    fn visit_match_arm(&mut self, arm: &'ast ast::MatchArm) {
+        self.nodes.push((NodeKind::MatchArm, arm.span));
+        visit::walk_match_arm(self, arm);
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast ast::Pattern) {
+        self.nodes.push((NodeKind::Pattern, pattern.span()));
+        visit::walk_pattern(self, pattern);
+    }
+}
+
+/// Assembles [GreenNode]s from a stack of the nodes that are still open.
+struct Builder<'src> {
+    source: &'src str,
+    /// The byte index of each char, followed by the length of the source.
+    chars: Vec<usize>,
+    /// The position up to which the source has been added to the tree, in chars.
+    pos: usize,
+    /// The kind, end and children of each open node, the module being the bottom one.
+    stack: Vec<(NodeKind, usize, Vec<GreenElement>)>,
+}
+
+impl Builder<'_> {
+    fn push(&mut self, element: GreenElement) {
+        let (_, _, children) = self
+            .stack
+            .last_mut()
+            .expect("the module node is never closed");
+        children.push(element);
+    }
+
+    /// Closes the open nodes that end at or before the given position, except the module.
+    fn close_until(&mut self, pos: usize) {
+        while self.stack.len() > 1 && self.stack.last().is_some_and(|(_, end, _)| *end <= pos) {
+            let (kind, _, children) = self.stack.pop().unwrap();
+            self.push(GreenElement::Node(GreenNode::new(kind, children)));
+        }
+    }
+
+    /// Adds the whitespace and comments up to the given position as trivia tokens.
+    fn trivia(&mut self, end: usize) {
+        let text = &self.source[self.chars[self.pos]..self.chars[end]];
+        let mut rest = text;
+
+        while !rest.is_empty() {
+            let (kind, len) = if rest.starts_with("//") {
+                (LeafKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+            } else {
+                let len = rest
+                    .find(|ch: char| !ch.is_whitespace())
+                    .unwrap_or(rest.len());
+                (LeafKind::Whitespace, len)
+            };
+
+            self.push(GreenElement::Token(GreenToken {
+                kind,
+                text: rest[..len].to_string(),
+            }));
+            rest = &rest[len..];
+        }
+
+        self.pos = end;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        cst::{self, LeafKind, NodeKind, SyntaxElement},
+        error::SyntaxError,
+        lexer::Lexer,
+        parser::Parser,
+        token::{Span, TokenKind},
+    };
+
+    #[test]
+    fn lossless() -> Result<(), SyntaxError> {
+        let inputs = [
+            "",
+            "  // only a comment\n",
+            "/// Adds.\nfn add(a: i64, b: i64) -> i64 {\n    a + b // sum\n}\n\n",
+            "struct P<T> { x: T, y: T }  enum E { A, B(i64) }\ttrait T { fn f(self); }",
+            "fn main() { let (a, _) = (1, \"ü\"); match a { 0 => 1, n => { n } } }",
+        ];
+
+        for input in inputs {
+            let tokens = Lexer::new(input).collect_tokens()?;
+            let (module, _) = Parser::new(&tokens).parse_module();
+
+            let tree = cst::build(input, &tokens, &module);
+            assert_eq!(tree.to_string(), input);
+            assert_eq!(tree.len(), input.chars().count());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn structure() -> Result<(), SyntaxError> {
+        let input = "fn f() {\n    g(1); // call\n}";
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let (module, _) = Parser::new(&tokens).parse_module();
+
+        let tree = cst::build(input, &tokens, &module);
+        let root = tree.root();
+
+        let [SyntaxElement::Node(item)] = root.children().collect::<Vec<_>>()[..] else {
+            panic!("expected a single item node");
+        };
+        assert_eq!(item.kind(), NodeKind::Item);
+        assert_eq!(item.span(), Span { start: 0, end: 28 });
+
+        let stmt = root.covering_node(17);
+        assert_eq!(stmt.kind(), NodeKind::Stmt);
+        assert_eq!(stmt.green().to_string(), "g(1);");
+
+        let block = root.covering_node(20);
+        assert_eq!(block.kind(), NodeKind::Block);
+
+        let kinds: Vec<_> = block
+            .children()
+            .filter_map(|child| match child {
+                SyntaxElement::Token(token) => Some(token.kind()),
+                SyntaxElement::Node(_) => None,
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                LeafKind::Token(TokenKind::LBrace),
+                LeafKind::Whitespace,
+                LeafKind::Whitespace,
+                LeafKind::Comment,
+                LeafKind::Whitespace,
+                LeafKind::Token(TokenKind::RBrace),
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1079 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Write},
+    io::{self, IsTerminal},
+};
+
+use crate::{
+    messages,
+    source::{FileId, FileSpan, SourceDatabase, SourceFile},
+    token::Span,
+};
+
+/// How severe a [Diagnostic] is, an error failing the compilation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A [Span] pointed at by a [Diagnostic] along with a message explaining its relevance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    /// The file the [Span] points into, if it isn't the one of the [Diagnostic].
+    pub file: Option<FileId>,
+    pub span: Span,
+    pub message: String,
+}
+
+/// How confident a [Suggestion] is, deciding whether it may be applied automatically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Applicability {
+    /// The suggestion is definitely what was meant and can be applied by tools.
+    MachineApplicable,
+    /// The suggestion may be what was meant, but should be reviewed before applying it.
+    MaybeIncorrect,
+    /// The suggestion contains placeholders like `...` that have to be filled in.
+    HasPlaceholders,
+}
+
+/// A fix-it replacing the source text covered by a [Span].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub message: String,
+    pub span: Span,
+    /// The text replacing the [Span], where empty spans insert it and empty
+    /// replacements delete the spanned text.
+    pub replacement: String,
+    pub applicability: Applicability,
+}
+
+/// A message about the source code, reported the same way by every stage of the compiler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// A stable code identifying the kind of diagnostic, like `E0001`.
+    pub code: Option<&'static str>,
+    pub message: String,
+    /// The file all spans of the diagnostic point into, if it is known.
+    pub file: Option<FileId>,
+    pub primary_span: Span,
+    /// The message shown next to the underlined primary [Span].
+    pub primary_label: Option<String>,
+    /// The name of the lint reporting the diagnostic, whose level can be configured.
+    pub lint: Option<String>,
+    /// Further spans related to the diagnostic.
+    pub labels: Vec<Label>,
+    /// Free-form lines shown below the snippet, like `help: ...`.
+    pub notes: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    /// Creates a new [Diagnostic] with the given [Severity], message and primary [Span].
+    pub fn new(severity: Severity, message: impl Into<String>, primary_span: Span) -> Self {
+        Self {
+            severity,
+            code: None,
+            message: message.into(),
+            file: None,
+            primary_span,
+            primary_label: None,
+            lint: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn error(message: impl Into<String>, primary_span: Span) -> Self {
+        Self::new(Severity::Error, message, primary_span)
+    }
+
+    #[inline]
+    pub fn warning(message: impl Into<String>, primary_span: Span) -> Self {
+        Self::new(Severity::Warning, message, primary_span)
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_file(mut self, file: FileId) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    pub fn with_lint(mut self, lint: impl Into<String>) -> Self {
+        self.lint = Some(lint.into());
+        self
+    }
+
+    pub fn with_primary_label(mut self, message: impl Into<String>) -> Self {
+        self.primary_label = Some(message.into());
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            file: None,
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    /// Adds a label pointing into another file, like the declaration of an imported item.
+    pub fn with_file_label(mut self, file: FileId, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            file: Some(file),
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(format!("note: {}", note.into()));
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.notes.push(format!("help: {}", help.into()));
+        self
+    }
+
+    pub fn with_suggestion(
+        mut self,
+        message: impl Into<String>,
+        span: Span,
+        replacement: impl Into<String>,
+        applicability: Applicability,
+    ) -> Self {
+        self.suggestions.push(Suggestion {
+            message: message.into(),
+            span,
+            replacement: replacement.into(),
+            applicability,
+        });
+        self
+    }
+
+    /// Returns the primary [Span] along with its file, if it is known.
+    pub fn primary_file_span(&self) -> Option<FileSpan> {
+        Some(FileSpan {
+            file: self.file?,
+            span: self.primary_span,
+        })
+    }
+
+    /// Returns the file the given [Label] points into, if it is known.
+    pub fn label_file(&self, label: &Label) -> Option<FileId> {
+        label.file.or(self.file)
+    }
+
+    /// Returns whether the given [Label] points into another file than the primary [Span].
+    fn is_foreign(&self, label: &Label) -> bool {
+        self.label_file(label) != self.file
+    }
+
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+/// What to do with the warnings of a lint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintLevel {
+    /// Drops the warnings.
+    Allow,
+    /// Reports the warnings without failing the compilation.
+    Warn,
+    /// Turns the warnings into errors.
+    Deny,
+}
+
+/// The configured [LintLevel] of each lint, where unconfigured lints have their default
+/// level, which is [LintLevel::Warn] unless set with [LintLevels::set_default].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LintLevels {
+    levels: HashMap<String, LintLevel>,
+    /// The level of every lint not configured individually, set through the `warnings` group.
+    warnings: Option<LintLevel>,
+    defaults: HashMap<String, LintLevel>,
+}
+
+impl LintLevels {
+    /// The name of the group containing every lint.
+    pub const WARNINGS: &str = "warnings";
+
+    /// The groups of lints configured together, along with the lints of each group.
+    pub const GROUPS: &[(&str, &[&str])] = &[("unused", &["unused_variables", "unused_functions"])];
+
+    /// Returns the lints of the group with the given name, if there is one.
+    pub fn group(name: &str) -> Option<&'static [&'static str]> {
+        Self::GROUPS
+            .iter()
+            .find(|(group, _)| *group == name)
+            .map(|(_, lints)| *lints)
+    }
+
+    /// Sets the [LintLevel] of the given lint, of the lints of a group like `unused`,
+    /// or of all lints for [LintLevels::WARNINGS].
+    pub fn set(&mut self, lint: impl Into<String>, level: LintLevel) {
+        let lint = lint.into();
+
+        if lint == Self::WARNINGS {
+            self.warnings = Some(level);
+        } else if let Some(lints) = Self::group(&lint) {
+            for lint in lints {
+                self.levels.insert(lint.to_string(), level);
+            }
+        } else {
+            self.levels.insert(lint, level);
+        }
+    }
+
+    /// Returns the names of the lints configured individually, in order.
+    pub fn configured(&self) -> Vec<&str> {
+        let mut lints: Vec<_> = self.levels.keys().map(String::as_str).collect();
+        lints.sort();
+        lints
+    }
+
+    /// Sets the [LintLevel] of the given lint when it isn't configured.
+    pub fn set_default(&mut self, lint: impl Into<String>, level: LintLevel) {
+        self.defaults.insert(lint.into(), level);
+    }
+
+    /// Returns the [LintLevel] of the given lint.
+    ///
+    /// The `warnings` group leaves the lints that are allowed by default alone, so they
+    /// are only reported when configured individually.
+    pub fn level(&self, lint: &str) -> LintLevel {
+        if let Some(level) = self.levels.get(lint) {
+            return *level;
+        }
+
+        match self.defaults.get(lint) {
+            Some(LintLevel::Allow) => LintLevel::Allow,
+            default => self
+                .warnings
+                .or(default.copied())
+                .unwrap_or(LintLevel::Warn),
+        }
+    }
+
+    /// Applies the level of its lint to the given warning, returning `None` if it is allowed.
+    /// Other [Diagnostic]s are returned unchanged.
+    pub fn apply(&self, mut diagnostic: Diagnostic) -> Option<Diagnostic> {
+        if diagnostic.severity != Severity::Warning {
+            return Some(diagnostic);
+        }
+
+        let lint = diagnostic.lint.as_deref().unwrap_or(Self::WARNINGS);
+
+        match self.level(lint) {
+            LintLevel::Allow => None,
+            LintLevel::Warn => Some(diagnostic),
+            LintLevel::Deny => {
+                let note = messages::message("lint.denied", &[("lint", &lint)]);
+                diagnostic.severity = Severity::Error;
+                Some(diagnostic.with_note(note))
+            }
+        }
+    }
+}
+
+/// Removes [Diagnostic]s repeating an earlier one, where errors whose primary [Span]
+/// overlaps the one of an earlier error count as follow-on errors of the same problem.
+pub fn dedup(diagnostics: &mut Vec<Diagnostic>) {
+    let overlaps = |a: Span, b: Span| a == b || (a.start < b.end && b.start < a.end);
+    let mut kept: Vec<Diagnostic> = Vec::with_capacity(diagnostics.len());
+
+    for diagnostic in diagnostics.drain(..) {
+        let repeated = kept.iter().any(|earlier| {
+            earlier == &diagnostic
+                || (earlier.is_error()
+                    && diagnostic.is_error()
+                    && earlier.file == diagnostic.file
+                    && overlaps(earlier.primary_span, diagnostic.primary_span))
+        });
+
+        if !repeated {
+            kept.push(diagnostic);
+        }
+    }
+
+    *diagnostics = kept;
+}
+
+/// Drops the errors after the first `limit` ones, keeping all warnings, and returns
+/// the number of dropped errors.
+pub fn limit_errors(diagnostics: &mut Vec<Diagnostic>, limit: usize) -> usize {
+    let mut errors = 0;
+
+    diagnostics.retain(|diagnostic| {
+        if diagnostic.is_error() {
+            errors += 1;
+        }
+        !diagnostic.is_error() || errors <= limit
+    });
+
+    errors.saturating_sub(limit)
+}
+
+/// Whether to color rendered diagnostics, see [render_colored].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ColorChoice {
+    Always,
+    Never,
+    /// Colors if the output is a terminal and the `NO_COLOR` variable isn't set.
+    #[default]
+    Auto,
+}
+
+impl ColorChoice {
+    /// Returns whether to color diagnostics written to the given stream.
+    pub fn enabled(self, stream: &impl IsTerminal) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                stream.is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+            }
+        }
+    }
+}
+
+/// The ANSI escape codes of the colors used by [render_colored].
+mod style {
+    pub const BOLD: &str = "1";
+    pub const ERROR: &str = "1;31";
+    pub const WARNING: &str = "1;33";
+    pub const GUTTER: &str = "1;34";
+    pub const SUGGESTION: &str = "1;32";
+}
+
+/// Wraps text in ANSI escape codes if coloring is enabled.
+#[derive(Debug, Clone, Copy)]
+struct Painter {
+    color: bool,
+}
+
+impl Painter {
+    fn paint(self, style: &str, text: impl fmt::Display) -> String {
+        match self.color {
+            true => format!("\x1b[{style}m{text}\x1b[0m"),
+            false => text.to_string(),
+        }
+    }
+}
+
+/// Renders the given [Diagnostic] similar to rustc, showing the file position and the
+/// first line of each span with `^` underlining the primary span and `-` its labels,
+/// followed by the notes and suggestions.
+///
+/// Labels pointing into other files are left out, see [render_in].
+pub fn render(file: &SourceFile, diagnostic: &Diagnostic) -> String {
+    render_with(file, None, diagnostic, Painter { color: false })
+}
+
+/// Renders the given [Diagnostic] like [render], coloring errors red, warnings yellow
+/// and highlighting the spans.
+pub fn render_colored(file: &SourceFile, diagnostic: &Diagnostic) -> String {
+    render_with(file, None, diagnostic, Painter { color: true })
+}
+
+/// Renders the given [Diagnostic] like [render], also showing the labels pointing into
+/// other files of the [SourceDatabase] below a `:::` line naming their file.
+///
+/// Returns [None] if the [Diagnostic] isn't attributed to a file.
+pub fn render_in(db: &SourceDatabase, diagnostic: &Diagnostic, colored: bool) -> Option<String> {
+    let file = db.get(diagnostic.file?);
+    Some(render_with(
+        file,
+        Some(db),
+        diagnostic,
+        Painter { color: colored },
+    ))
+}
+
+/// A sink receiving [Diagnostic]s as they are reported, like a terminal, an editor
+/// or a test harness collecting them.
+pub trait EmitDiagnostic {
+    /// Receives a [Diagnostic], whose file is looked up in the given [SourceDatabase].
+    fn emit(&mut self, db: &SourceDatabase, diagnostic: &Diagnostic);
+
+    /// Called once all diagnostics have been emitted, for sinks writing them at once.
+    fn finish(&mut self, _db: &SourceDatabase) {}
+}
+
+/// Collects the emitted diagnostics.
+impl EmitDiagnostic for Vec<Diagnostic> {
+    fn emit(&mut self, _db: &SourceDatabase, diagnostic: &Diagnostic) {
+        self.push(diagnostic.clone());
+    }
+}
+
+/// Writes each [Diagnostic] rendered by [render] or [render_colored], or only its
+/// message if it isn't attributed to a file.
+#[derive(Debug)]
+pub struct TerminalEmitter<W> {
+    out: W,
+    colored: bool,
+}
+
+impl<W: io::Write> TerminalEmitter<W> {
+    /// Creates a new [TerminalEmitter] writing to `out`.
+    pub fn new(out: W, colored: bool) -> Self {
+        Self { out, colored }
+    }
+
+    /// Returns the writer, like a buffer the diagnostics were rendered to.
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<W: io::Write> EmitDiagnostic for TerminalEmitter<W> {
+    fn emit(&mut self, db: &SourceDatabase, diagnostic: &Diagnostic) {
+        let text = render_in(db, diagnostic, self.colored)
+            .unwrap_or_else(|| format!("{}: {}\n", diagnostic.severity, diagnostic.message));
+
+        // like `eprint!`, but failing to report a problem shouldn't become a new one.
+        let _ = self.out.write_all(text.as_bytes());
+    }
+}
+
+fn render_with(
+    file: &SourceFile,
+    db: Option<&SourceDatabase>,
+    diagnostic: &Diagnostic,
+    painter: Painter,
+) -> String {
+    let (foreign, local): (Vec<_>, Vec<_>) = diagnostic
+        .labels
+        .iter()
+        .partition(|label| diagnostic.is_foreign(label));
+    // the labels in other files are shown if the database holding them is known.
+    let foreign: Vec<_> = foreign
+        .into_iter()
+        .filter_map(|label| Some((db?.get(diagnostic.label_file(label)?), label)))
+        .collect();
+
+    let spans = std::iter::once(diagnostic.primary_span)
+        .chain(local.iter().map(|label| label.span))
+        .chain(
+            diagnostic
+                .suggestions
+                .iter()
+                .map(|suggestion| suggestion.span),
+        );
+    let foreign_lines = foreign
+        .iter()
+        .map(|(other, label)| other.line_col(label.span).line);
+    let gutter = spans
+        .map(|span| file.line_col(span).line)
+        .chain(foreign_lines)
+        .map(|line| line.to_string().len())
+        .max()
+        .unwrap_or(1);
+    let severity_style = match diagnostic.severity {
+        Severity::Warning => style::WARNING,
+        Severity::Error => style::ERROR,
+    };
+    let snippet = Snippet {
+        file,
+        gutter,
+        painter,
+    };
+
+    let mut out = String::new();
+    let severity = match diagnostic.code {
+        Some(code) => format!("{}[{code}]", diagnostic.severity),
+        None => diagnostic.severity.to_string(),
+    };
+    let _ = writeln!(
+        out,
+        "{}{}",
+        painter.paint(severity_style, severity),
+        painter.paint(style::BOLD, format_args!(": {}", diagnostic.message))
+    );
+
+    let start = file.line_col(diagnostic.primary_span);
+    let _ = writeln!(
+        out,
+        "{:gutter$}{} {}:{}:{}",
+        "",
+        painter.paint(style::GUTTER, "-->"),
+        file.name(),
+        start.line,
+        start.col
+    );
+    snippet.empty_line(&mut out);
+
+    let primary = Mark {
+        span: diagnostic.primary_span,
+        marker: '^',
+        style: severity_style,
+        message: diagnostic.primary_label.as_deref().unwrap_or_default(),
+    };
+    let labels = local.iter().map(|label| Mark {
+        span: label.span,
+        marker: '-',
+        style: style::GUTTER,
+        message: &label.message,
+    });
+    let marks: Vec<_> = std::iter::once(primary).chain(labels).collect();
+    snippet.marks(&mut out, &marks);
+
+    for (other, label) in foreign {
+        let start = other.line_col(label.span);
+        let _ = writeln!(
+            out,
+            "{:gutter$}{} {}:{}:{}",
+            "",
+            painter.paint(style::GUTTER, ":::"),
+            other.name(),
+            start.line,
+            start.col
+        );
+
+        let snippet = Snippet {
+            file: other,
+            gutter,
+            painter,
+        };
+        snippet.empty_line(&mut out);
+        let mark = Mark {
+            span: label.span,
+            marker: '-',
+            style: style::GUTTER,
+            message: &label.message,
+        };
+        snippet.marks(&mut out, &[mark]);
+    }
+
+    for note in &diagnostic.notes {
+        let _ = writeln!(
+            out,
+            "{:gutter$} {} {note}",
+            "",
+            painter.paint(style::GUTTER, "=")
+        );
+    }
+
+    for suggestion in &diagnostic.suggestions {
+        snippet.suggestion(&mut out, suggestion);
+    }
+
+    out
+}
+
+/// A [Span] underlined by [Snippet::marks].
+struct Mark<'a> {
+    span: Span,
+    marker: char,
+    style: &'static str,
+    message: &'a str,
+}
+
+/// A piece of an underline row placed at a 0-based column of the line above.
+struct Placed<'a> {
+    col: usize,
+    text: String,
+    style: &'a str,
+}
+
+/// Writes the lines of a [SourceFile] shown by [render].
+struct Snippet<'a> {
+    file: &'a SourceFile,
+    /// The width of the line numbers.
+    gutter: usize,
+    painter: Painter,
+}
+
+impl Snippet<'_> {
+    fn empty_line(&self, out: &mut String) {
+        let _ = writeln!(
+            out,
+            "{:gutter$} {}",
+            "",
+            self.painter.paint(style::GUTTER, "|"),
+            gutter = self.gutter
+        );
+    }
+
+    /// Writes the given 1-based line with its line number.
+    fn line(&self, out: &mut String, line: usize, text: &str) {
+        let number = format!("{line:>gutter$} |", gutter = self.gutter);
+        let text = text.trim_end();
+
+        let _ = match text.is_empty() {
+            true => writeln!(out, "{}", self.painter.paint(style::GUTTER, number)),
+            false => writeln!(out, "{} {text}", self.painter.paint(style::GUTTER, number)),
+        };
+    }
+
+    /// Writes a row below the given line text with the [Placed] pieces, which have to be
+    /// sorted by column. Tabs of the line are kept so the pieces line up with its text.
+    fn row(&self, out: &mut String, line_text: &str, pieces: &[Placed]) {
+        let mut row = String::new();
+        let mut col = 0;
+
+        for piece in pieces {
+            let skipped = line_text.chars().chain(std::iter::repeat(' '));
+            let padding = skipped
+                .skip(col)
+                .take(piece.col.saturating_sub(col))
+                .map(|ch| if ch == '\t' { '\t' } else { ' ' });
+
+            row.extend(padding);
+            row.push_str(&self.painter.paint(piece.style, &piece.text));
+            col = col.max(piece.col) + piece.text.chars().count();
+        }
+
+        let _ = writeln!(
+            out,
+            "{:gutter$} {} {row}",
+            "",
+            self.painter.paint(style::GUTTER, "|"),
+            gutter = self.gutter
+        );
+    }
+
+    /// Writes the first line of each [Mark]ed [Span] with the marks underlining it, where
+    /// lines are sorted and marks on the same line share it similar to rustc:
+    ///
+    /// ```text
+    /// 1 | let x: i64 = 2.0;
+    ///   |        ---   ^^^ expected `i64`
+    ///   |        |
+    ///   |        declared here
+    /// ```
+    fn marks(&self, out: &mut String, marks: &[Mark]) {
+        let mut lines: BTreeMap<usize, Vec<(usize, usize, &Mark)>> = BTreeMap::new();
+
+        for mark in marks {
+            let start = self.file.line_col(mark.span);
+            let end = self
+                .file
+                .offset_line_col(mark.span.end.max(mark.span.start));
+            let line_len = self
+                .file
+                .line_text(start.line)
+                .map_or(0, |text| text.chars().count());
+
+            // spans reaching past their first line are underlined up to its end.
+            let underline_end = match end.line == start.line {
+                true => end.col,
+                false => line_len + 1,
+            };
+            let underline_len = underline_end.saturating_sub(start.col).max(1);
+
+            let line = lines.entry(start.line).or_default();
+            line.push((start.col - 1, underline_len, mark));
+        }
+
+        let mut prev_line = None;
+
+        for (line, mut marks) in lines {
+            if prev_line.is_some_and(|prev| line > prev + 1) {
+                let _ = writeln!(out, "{}", self.painter.paint(style::GUTTER, "..."));
+            }
+            prev_line = Some(line);
+
+            let line_text = self.file.line_text(line).unwrap_or_default();
+            self.line(out, line, line_text);
+
+            marks.sort_by_key(|&(col, _, _)| col);
+
+            let mut underlines: Vec<_> = marks
+                .iter()
+                .map(|&(col, len, mark)| Placed {
+                    col,
+                    text: mark.marker.to_string().repeat(len),
+                    style: mark.style,
+                })
+                .collect();
+
+            // the message of the last mark fits right after its underline.
+            let (last, pending) = marks.split_last().expect("lines have at least one mark");
+            if !last.2.message.is_empty() {
+                let underline = underlines.last_mut().expect("every mark is underlined");
+                underline.text = format!("{} {}", underline.text, last.2.message);
+            }
+            self.row(out, line_text, &underlines);
+
+            let pending: Vec<_> = pending
+                .iter()
+                .filter(|(_, _, mark)| !mark.message.is_empty())
+                .collect();
+            let connector = |&&(col, _, mark): &&(usize, usize, &Mark)| Placed {
+                col,
+                text: "|".to_string(),
+                style: mark.style,
+            };
+
+            if !pending.is_empty() {
+                let connectors: Vec<_> = pending.iter().map(connector).collect();
+                self.row(out, line_text, &connectors);
+            }
+
+            // the other messages hang below their marks, the rightmost first.
+            for index in (0..pending.len()).rev() {
+                let &(col, _, mark) = pending[index];
+                let mut pieces: Vec<_> = pending[..index].iter().map(connector).collect();
+                pieces.push(Placed {
+                    col,
+                    text: mark.message.to_string(),
+                    style: mark.style,
+                });
+                self.row(out, line_text, &pieces);
+            }
+        }
+    }
+
+    /// Writes the message of the given [Suggestion] and, if it only touches a single line,
+    /// that line with the suggestion applied, marking inserted text with `+`, replacing
+    /// text with `~` and deleted text with `-`.
+    fn suggestion(&self, out: &mut String, suggestion: &Suggestion) {
+        let _ = writeln!(
+            out,
+            "{}",
+            self.painter
+                .paint(style::BOLD, format_args!("help: {}", suggestion.message))
+        );
+
+        let start = self.file.line_col(suggestion.span);
+        let end = self
+            .file
+            .offset_line_col(suggestion.span.end.max(suggestion.span.start));
+
+        if start.line != end.line || suggestion.replacement.contains('\n') {
+            return;
+        }
+
+        let line_text = self.file.line_text(start.line).unwrap_or_default();
+        let prefix: String = line_text.chars().take(start.col - 1).collect();
+        let suffix: String = line_text.chars().skip(end.col - 1).collect();
+
+        let (line, marker, marked_len) = match suggestion.replacement.as_str() {
+            "" => (line_text.to_string(), '-', end.col - start.col),
+            replacement => {
+                let marker = if start == end { '+' } else { '~' };
+                let line = format!("{prefix}{replacement}{suffix}");
+                (line, marker, replacement.chars().count())
+            }
+        };
+
+        self.empty_line(out);
+        self.line(out, start.line, &line);
+        let markers = Placed {
+            col: start.col - 1,
+            text: marker.to_string().repeat(marked_len),
+            style: style::SUGGESTION,
+        };
+        self.row(out, &line, &[markers]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        diagnostics::{
+            Applicability, Diagnostic, EmitDiagnostic, LintLevel, LintLevels, Severity,
+            TerminalEmitter, dedup, limit_errors, render, render_colored, render_in,
+        },
+        source::{SourceDatabase, SourceFile},
+        token::Span,
+    };
+
+    #[test]
+    fn snippet() {
+        let file = SourceFile::new("main.elan", "fn main() {\n\tlet x = $$;\n}\n");
+
+        let diagnostic = Diagnostic::error("invalid token", Span { start: 21, end: 23 })
+            .with_help("remove these characters");
+        let expected = "\
+error: invalid token
+ --> main.elan:2:10
+  |
+2 | \tlet x = $$;
+  | \t        ^^
+  = help: remove these characters
+";
+        assert_eq!(render(&file, &diagnostic), expected);
+
+        // the end of input is shown as a single caret after the last char.
+        let diagnostic = Diagnostic::error("unexpected end of input", Span::EOI);
+        let expected = "\
+error: unexpected end of input
+ --> main.elan:4:1
+  |
+4 |
+  | ^
+";
+        assert_eq!(render(&file, &diagnostic), expected);
+
+        let diagnostic = Diagnostic::warning("long", Span { start: 0, end: 24 });
+        let rendered = render(&file, &diagnostic);
+        assert!(rendered.ends_with("1 | fn main() {\n  | ^^^^^^^^^^^\n"));
+    }
+
+    #[test]
+    fn labels() {
+        let file = SourceFile::new("main.elan", "let x: i64 = 1;\n\n\nx = 2.0;\n");
+
+        let diagnostic = Diagnostic::error("mismatched types", Span { start: 22, end: 25 })
+            .with_code("E0000")
+            .with_primary_label("expected `i64`, found `f64`")
+            .with_label(Span { start: 4, end: 5 }, "declared here")
+            .with_note("floats don't convert implicitly");
+        let expected = "\
+error[E0000]: mismatched types
+ --> main.elan:4:5
+  |
+1 | let x: i64 = 1;
+  |     - declared here
+...
+4 | x = 2.0;
+  |     ^^^ expected `i64`, found `f64`
+  = note: floats don't convert implicitly
+";
+        assert_eq!(render(&file, &diagnostic), expected);
+
+        // marks on the same line share it, with the messages hanging below.
+        let file = SourceFile::new("main.elan", "\tlet x: i64 = 2.0;");
+
+        let diagnostic = Diagnostic::error("mismatched types", Span { start: 14, end: 17 })
+            .with_primary_label("found `f64`")
+            .with_label(Span { start: 8, end: 11 }, "expected due to this")
+            .with_label(Span { start: 5, end: 6 }, "declared here");
+        let expected = "\
+error: mismatched types
+ --> main.elan:1:15
+  |
+1 | \tlet x: i64 = 2.0;
+  | \t    -  ---   ^^^ found `f64`
+  | \t    |  |
+  | \t    |  expected due to this
+  | \t    declared here
+";
+        assert_eq!(render(&file, &diagnostic), expected);
+    }
+
+    #[test]
+    fn lint_levels() {
+        let warning = |lint: &str| Diagnostic::warning("w", Span::EOI).with_lint(lint);
+        let mut levels = LintLevels::default();
+
+        assert_eq!(levels.apply(warning("a")), Some(warning("a")));
+
+        levels.set("a", LintLevel::Allow);
+        levels.set("b", LintLevel::Deny);
+        assert_eq!(levels.apply(warning("a")), None);
+
+        let denied = levels.apply(warning("b")).unwrap();
+        assert_eq!(denied.severity, Severity::Error);
+        assert_eq!(denied.notes, ["note: the `b` lint is denied"]);
+
+        // the group only applies to lints without their own level.
+        levels.set(LintLevels::WARNINGS, LintLevel::Allow);
+        assert_eq!(levels.apply(warning("c")), None);
+        assert!(levels.apply(warning("b")).is_some());
+
+        // lints allowed by default stay allowed unless configured themselves.
+        levels.set_default("d", LintLevel::Allow);
+        levels.set(LintLevels::WARNINGS, LintLevel::Deny);
+        assert_eq!(levels.apply(warning("d")), None);
+        levels.set("d", LintLevel::Warn);
+        assert_eq!(levels.apply(warning("d")), Some(warning("d")));
+
+        let error = Diagnostic::error("e", Span::EOI);
+        assert_eq!(levels.apply(error.clone()), Some(error));
+    }
+
+    #[test]
+    fn lint_groups() {
+        let warning = |lint: &str| Diagnostic::warning("w", Span::EOI).with_lint(lint);
+        let mut levels = LintLevels::default();
+
+        levels.set("unused", LintLevel::Allow);
+        assert_eq!(levels.apply(warning("unused_variables")), None);
+        assert_eq!(levels.apply(warning("unused_functions")), None);
+        assert!(levels.apply(warning("leading_zeros")).is_some());
+        assert_eq!(
+            levels.configured(),
+            ["unused_functions", "unused_variables"]
+        );
+    }
+
+    #[test]
+    fn suggestions() {
+        let file = SourceFile::new("main.elan", "let x = 1\nlet y = 010;\n");
+
+        let diagnostic = Diagnostic::error("expected `;`", Span { start: 9, end: 9 })
+            .with_suggestion(
+                "add `;` here",
+                Span { start: 9, end: 9 },
+                ";",
+                Applicability::MaybeIncorrect,
+            )
+            .with_suggestion(
+                "remove these characters",
+                Span { start: 18, end: 19 },
+                "",
+                Applicability::MachineApplicable,
+            )
+            .with_suggestion(
+                "replace with `8`",
+                Span { start: 18, end: 21 },
+                "8",
+                Applicability::MachineApplicable,
+            );
+        let expected = "\
+error: expected `;`
+ --> main.elan:1:10
+  |
+1 | let x = 1
+  |          ^
+help: add `;` here
+  |
+1 | let x = 1;
+  |          +
+help: remove these characters
+  |
+2 | let y = 010;
+  |         -
+help: replace with `8`
+  |
+2 | let y = 8;
+  |         ~
+";
+        assert_eq!(render(&file, &diagnostic), expected);
+    }
+
+    #[test]
+    fn colored() {
+        let file = SourceFile::new("main.elan", "let x = 1;");
+        let diagnostic = Diagnostic::warning("unused", Span { start: 4, end: 5 });
+
+        let expected = "\
+\x1b[1;33mwarning\x1b[0m\x1b[1m: unused\x1b[0m
+ \x1b[1;34m-->\x1b[0m main.elan:1:5
+  \x1b[1;34m|\x1b[0m
+\x1b[1;34m1 |\x1b[0m let x = 1;
+  \x1b[1;34m|\x1b[0m     \x1b[1;33m^\x1b[0m
+";
+        assert_eq!(render_colored(&file, &diagnostic), expected);
+    }
+
+    #[test]
+    fn dedup_and_limit() {
+        let error = |start, end| Diagnostic::error("e", Span { start, end });
+        let warning = Diagnostic::warning("w", Span { start: 0, end: 1 });
+
+        let mut diagnostics = vec![
+            error(0, 4),
+            warning.clone(),
+            warning.clone(),
+            error(2, 6),
+            error(4, 6),
+            Diagnostic::error("other", Span::EOI),
+            Diagnostic::error("eoi", Span::EOI),
+        ];
+        dedup(&mut diagnostics);
+
+        assert_eq!(
+            diagnostics,
+            [
+                error(0, 4),
+                warning.clone(),
+                error(4, 6),
+                Diagnostic::error("other", Span::EOI),
+            ]
+        );
+
+        assert_eq!(limit_errors(&mut diagnostics, 1), 2);
+        assert_eq!(diagnostics, [error(0, 4), warning]);
+    }
+
+    #[test]
+    fn emitters() {
+        let mut db = SourceDatabase::default();
+        let file = db.add(SourceFile::new("main.elan", "let x = 1;"));
+
+        let diagnostics = [
+            Diagnostic::warning("unused", Span { start: 4, end: 5 }).with_file(file),
+            Diagnostic::error("no main function", Span::EOI),
+        ];
+
+        let mut collected = Vec::new();
+        let mut terminal = TerminalEmitter::new(Vec::new(), false);
+        for diagnostic in &diagnostics {
+            collected.emit(&db, diagnostic);
+            terminal.emit(&db, diagnostic);
+        }
+
+        assert_eq!(collected, diagnostics);
+
+        let expected = "\
+warning: unused
+ --> main.elan:1:5
+  |
+1 | let x = 1;
+  |     ^
+error: no main function
+";
+        assert_eq!(String::from_utf8(terminal.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn file_labels() {
+        let mut db = SourceDatabase::default();
+        let main = db.add(SourceFile::new(
+            "main.elan",
+            "import geometry;\ngeometry::area(1.0);",
+        ));
+        let geometry = db.add(SourceFile::new(
+            "geometry.elan",
+            "fn area(r: f64) -> f64 { r }",
+        ));
+
+        let diagnostic = Diagnostic::error("`area` is private", Span { start: 27, end: 31 })
+            .with_file(main)
+            .with_file_label(geometry, Span { start: 3, end: 7 }, "declared here")
+            .with_note("mark it `pub`");
+
+        let expected = "\
+error: `area` is private
+ --> main.elan:2:11
+  |
+2 | geometry::area(1.0);
+  |           ^^^^
+ ::: geometry.elan:1:4
+  |
+1 | fn area(r: f64) -> f64 { r }
+  |    ---- declared here
+  = note: mark it `pub`
+";
+        assert_eq!(render_in(&db, &diagnostic, false).unwrap(), expected);
+
+        // without the database, only the snippet of the primary file is shown.
+        assert!(!render(db.get(main), &diagnostic).contains("geometry.elan"));
+    }
+}
@@ -0,0 +1,569 @@
+use std::fmt::Write;
+
+use crate::{
+    ast::{self, pretty::generics},
+    token::Span,
+};
+
+/// Identifies a node inside a [Graph].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// A directed graph that renders to the Graphviz DOT language.
+#[derive(Debug)]
+pub struct Graph {
+    name: String,
+    nodes: Vec<(String, &'static str)>,
+    edges: Vec<(NodeId, NodeId, Option<String>)>,
+}
+
+impl Graph {
+    /// Creates an empty [Graph] with the given name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// Adds a node with the given label and shape, returning its [NodeId].
+    pub fn node(&mut self, label: impl Into<String>, shape: &'static str) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push((label.into(), shape));
+        id
+    }
+
+    /// Adds an edge between two nodes with an optional label.
+    pub fn edge(&mut self, from: NodeId, to: NodeId, label: Option<&str>) {
+        self.edges.push((from, to, label.map(str::to_string)));
+    }
+
+    /// Renders the [Graph] in the DOT language.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        writeln!(out, "digraph \"{}\" {{", escape(&self.name)).unwrap();
+        writeln!(out, "    node [fontname = \"monospace\"];").unwrap();
+
+        for (index, (label, shape)) in self.nodes.iter().enumerate() {
+            writeln!(
+                out,
+                "    n{index} [label = \"{}\", shape = {shape}];",
+                escape(label)
+            )
+            .unwrap();
+        }
+
+        for (from, to, label) in &self.edges {
+            match label {
+                Some(label) => writeln!(
+                    out,
+                    "    n{} -> n{} [label = \"{}\"];",
+                    from.0,
+                    to.0,
+                    escape(label)
+                ),
+                None => writeln!(out, "    n{} -> n{};", from.0, to.0),
+            }
+            .unwrap();
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Escapes a string for use inside a quoted DOT identifier.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            ch => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+/// Renders the given [ast::Module] as a DOT graph, see [ast::to_dot].
+pub(crate) fn ast_to_dot(module: &ast::Module) -> String {
+    let mut builder = AstGraph {
+        graph: Graph::new("ast"),
+        exprs: &module.exprs,
+    };
+
+    let root = builder.graph.node("Module", "box");
+    for item in &module.items {
+        let child = builder.item(item);
+        builder.graph.edge(root, child, None);
+    }
+
+    builder.graph.render()
+}
+
+struct AstGraph<'a> {
+    graph: Graph,
+    exprs: &'a ast::ExprArena,
+}
+
+impl AstGraph<'_> {
+    /// Adds a node labeled with the given text and the [Span] of the AST node it stands for.
+    fn node(&mut self, label: impl Into<String>, span: Span, shape: &'static str) -> NodeId {
+        let label = format!("{}\n{}..{}", label.into(), span.start, span.end);
+        self.graph.node(label, shape)
+    }
+
+    fn item(&mut self, item: &ast::Item) -> NodeId {
+        let node = match item {
+            ast::Item::Import(import) => {
+                let path: Vec<_> = import.path.iter().map(|seg| seg.text.as_str()).collect();
+                let label = match &import.alias {
+                    Some(alias) => format!("Import {} as {}", path.join("::"), alias.text),
+                    None => format!("Import {}", path.join("::")),
+                };
+                self.node(label, import.span, "box")
+            }
+            ast::Item::Func(func) => self.func_decl(func),
+            ast::Item::Const(const_decl) => self.const_decl(const_decl),
+            ast::Item::Struct(struct_decl) => self.struct_decl(struct_decl),
+            ast::Item::Enum(enum_decl) => self.enum_decl(enum_decl),
+            ast::Item::Trait(trait_decl) => self.trait_decl(trait_decl),
+            ast::Item::Error(span) => self.node("Error", *span, "box"),
+        };
+
+        self.attributes(node, item.attributes());
+
+        node
+    }
+
+    /// Adds the [ast::Attribute]s as children of the given node.
+    fn attributes(&mut self, node: NodeId, attributes: &[ast::Attribute]) {
+        for attr in attributes {
+            let child = self.node(format!("Attribute {}", attr.name.text), attr.span, "box");
+            for &arg in &attr.args {
+                let arg = self.expr(arg);
+                self.graph.edge(child, arg, Some("arg"));
+            }
+            self.graph.edge(node, child, Some("attribute"));
+        }
+    }
+
+    fn const_decl(&mut self, const_decl: &ast::ConstDecl) -> NodeId {
+        let label = format!(
+            "Const {}: {}",
+            const_decl.name.text,
+            const_decl.ty.display(self.exprs)
+        );
+        let node = self.node(label, const_decl.span, "box");
+        let child = self.expr(const_decl.value);
+        self.graph.edge(node, child, None);
+        node
+    }
+
+    fn struct_decl(&mut self, struct_decl: &ast::StructDecl) -> NodeId {
+        let node = self.node(
+            format!(
+                "StructDecl {}{}",
+                struct_decl.name.text,
+                generics(&struct_decl.generics)
+            ),
+            struct_decl.span,
+            "box",
+        );
+
+        for field in &struct_decl.fields {
+            let label = format!(
+                "Field {}: {}",
+                field.name.text,
+                field.ty.display(self.exprs)
+            );
+            let child = self.node(label, field.span, "box");
+            self.graph.edge(node, child, Some("field"));
+        }
+
+        node
+    }
+
+    fn enum_decl(&mut self, enum_decl: &ast::EnumDecl) -> NodeId {
+        let node = self.node(
+            format!("EnumDecl {}", enum_decl.name.text),
+            enum_decl.span,
+            "box",
+        );
+
+        for variant in &enum_decl.variants {
+            let label = match &variant.payload {
+                ast::VariantPayload::Unit => variant.name.text.clone(),
+                ast::VariantPayload::Tuple(types) => {
+                    let types: Vec<_> = types
+                        .iter()
+                        .map(|ty| ty.display(self.exprs).to_string())
+                        .collect();
+                    format!("{}({})", variant.name.text, types.join(", "))
+                }
+                ast::VariantPayload::Struct(fields) => {
+                    let fields: Vec<_> = fields
+                        .iter()
+                        .map(|field| {
+                            format!("{}: {}", field.name.text, field.ty.display(self.exprs))
+                        })
+                        .collect();
+                    format!("{} {{ {} }}", variant.name.text, fields.join(", "))
+                }
+            };
+
+            let child = self.node(label, variant.span, "box");
+            self.graph.edge(node, child, Some("variant"));
+        }
+
+        node
+    }
+
+    fn func_decl(&mut self, func: &ast::FuncDecl) -> NodeId {
+        let label = format!("FuncDecl {}{}", func.name.text, generics(&func.generics));
+        let node = self.node(label, func.span, "box");
+
+        for param in &func.params {
+            let label = format!(
+                "Param {}: {}",
+                param.name.text,
+                param.ty.display(self.exprs)
+            );
+            let child = self.node(label, param.span, "box");
+            self.graph.edge(node, child, Some("param"));
+        }
+
+        if let Some(return_type) = &func.return_type {
+            let child = self.node(
+                format!("Type {}", return_type.display(self.exprs)),
+                return_type.span(),
+                "box",
+            );
+            self.graph.edge(node, child, Some("return"));
+        }
+
+        let body = self.block(&func.body);
+        self.graph.edge(node, body, Some("body"));
+
+        node
+    }
+
+    fn trait_decl(&mut self, trait_decl: &ast::TraitDecl) -> NodeId {
+        let label = format!(
+            "TraitDecl {}{}",
+            trait_decl.name.text,
+            generics(&trait_decl.generics)
+        );
+        let node = self.node(label, trait_decl.span, "box");
+
+        for method in &trait_decl.methods {
+            let label = format!("Method {}{}", method.name.text, generics(&method.generics));
+            let child = self.node(label, method.span, "box");
+            self.attributes(child, &method.attributes);
+
+            if let Some(receiver) = method.receiver {
+                let receiver = self.node("Param self", receiver, "box");
+                self.graph.edge(child, receiver, Some("param"));
+            }
+
+            for param in &method.params {
+                let label = format!(
+                    "Param {}: {}",
+                    param.name.text,
+                    param.ty.display(self.exprs)
+                );
+                let param = self.node(label, param.span, "box");
+                self.graph.edge(child, param, Some("param"));
+            }
+
+            if let Some(return_type) = &method.return_type {
+                let return_type = self.node(
+                    format!("Type {}", return_type.display(self.exprs)),
+                    return_type.span(),
+                    "box",
+                );
+                self.graph.edge(child, return_type, Some("return"));
+            }
+
+            if let Some(body) = &method.body {
+                let body = self.block(body);
+                self.graph.edge(child, body, Some("body"));
+            }
+
+            self.graph.edge(node, child, Some("method"));
+        }
+
+        node
+    }
+
+    fn block(&mut self, block: &ast::Block) -> NodeId {
+        let node = self.node("Block", block.span, "box");
+
+        for stmt in &block.stmts {
+            let child = self.stmt(stmt);
+            self.attributes(child, stmt.attributes());
+            self.graph.edge(node, child, None);
+        }
+
+        if let Some(tail) = &block.tail {
+            let child = self.expr(*tail);
+            self.graph.edge(node, child, Some("tail"));
+        }
+
+        node
+    }
+
+    fn stmt(&mut self, stmt: &ast::Stmt) -> NodeId {
+        match stmt {
+            ast::Stmt::Let(let_stmt) => {
+                let label = match &let_stmt.ty {
+                    Some(ty) => format!("Let {}: {}", let_stmt.pattern, ty.display(self.exprs)),
+                    None => format!("Let {}", let_stmt.pattern),
+                };
+                let node = self.node(label, let_stmt.span, "box");
+                if let Some(value) = &let_stmt.value {
+                    let child = self.expr(*value);
+                    self.graph.edge(node, child, None);
+                }
+                node
+            }
+            ast::Stmt::Const(const_decl) => self.const_decl(const_decl),
+            ast::Stmt::Expr(expr_stmt) => {
+                let node = self.node("ExprStmt", expr_stmt.span, "box");
+                let child = self.expr(expr_stmt.expr);
+                self.graph.edge(node, child, None);
+                node
+            }
+            ast::Stmt::Return(return_stmt) => {
+                let node = self.node("Return", return_stmt.span, "box");
+                if let Some(value) = &return_stmt.value {
+                    let child = self.expr(*value);
+                    self.graph.edge(node, child, None);
+                }
+                node
+            }
+            ast::Stmt::For(for_stmt) => {
+                let label = format!("For {}", for_stmt.binding.text);
+                let node = self.node(label, for_stmt.span, "box");
+                let iterable = self.expr(for_stmt.iterable);
+                let body = self.block(&for_stmt.body);
+                self.graph.edge(node, iterable, Some("in"));
+                self.graph.edge(node, body, Some("body"));
+                node
+            }
+            ast::Stmt::Break(break_stmt) => {
+                let node = self.node("Break", break_stmt.span, "box");
+                if let Some(value) = &break_stmt.value {
+                    let child = self.expr(*value);
+                    self.graph.edge(node, child, None);
+                }
+                node
+            }
+            ast::Stmt::Continue(continue_stmt) => self.node("Continue", continue_stmt.span, "box"),
+            ast::Stmt::Error(span) => self.node("Error", *span, "box"),
+        }
+    }
+
+    fn expr(&mut self, id: ast::ExprId) -> NodeId {
+        let exprs = self.exprs;
+
+        let span = exprs[id].span();
+
+        match &exprs[id] {
+            ast::Expr::Identifier(ident) => self.node(&*ident.text, span, "ellipse"),
+            ast::Expr::Path(path) => {
+                let segments: Vec<_> = path.segments.iter().map(|seg| seg.text.as_str()).collect();
+                self.node(segments.join("::"), span, "ellipse")
+            }
+            ast::Expr::Integer(integer) => self.node(integer.value.to_string(), span, "ellipse"),
+            ast::Expr::Float(float) => self.node(
+                f64::from_bits(float.value_bits).to_string(),
+                span,
+                "ellipse",
+            ),
+            ast::Expr::String(string) => self.node(format!("{:?}", string.value), span, "ellipse"),
+            ast::Expr::None(_) => self.node("none", span, "ellipse"),
+            ast::Expr::Paren(paren) => {
+                let node = self.node("( )", span, "ellipse");
+                let child = self.expr(paren.expr);
+                self.graph.edge(node, child, None);
+                node
+            }
+            ast::Expr::Tuple(tuple) => {
+                let node = self.node("Tuple", span, "ellipse");
+                for element in &tuple.elements {
+                    let child = self.expr(*element);
+                    self.graph.edge(node, child, None);
+                }
+                node
+            }
+            ast::Expr::Unary(unary) => {
+                let node = self.node(format!("{:?}", unary.op), span, "ellipse");
+                let child = self.expr(unary.operand);
+                self.graph.edge(node, child, None);
+                node
+            }
+            ast::Expr::Binary(binary) => {
+                let node = self.node(format!("{:?}", binary.op), span, "ellipse");
+                let lhs = self.expr(binary.lhs);
+                let rhs = self.expr(binary.rhs);
+                self.graph.edge(node, lhs, Some("lhs"));
+                self.graph.edge(node, rhs, Some("rhs"));
+                node
+            }
+            ast::Expr::Cast(cast) => {
+                let node = self.node(format!("As {}", cast.ty.display(exprs)), span, "ellipse");
+                let child = self.expr(cast.expr);
+                self.graph.edge(node, child, None);
+                node
+            }
+            ast::Expr::Assign(assign) => {
+                let node = self.node("Assign", span, "ellipse");
+                let target = self.expr(assign.target);
+                let value = self.expr(assign.value);
+                self.graph.edge(node, target, Some("target"));
+                self.graph.edge(node, value, Some("value"));
+                node
+            }
+            ast::Expr::Range(range) => {
+                let node = self.node(format!("Range {:?}", range.kind), span, "ellipse");
+                let start = self.expr(range.start);
+                let end = self.expr(range.end);
+                self.graph.edge(node, start, Some("start"));
+                self.graph.edge(node, end, Some("end"));
+                node
+            }
+            ast::Expr::Call(call) => {
+                let node = self.node("Call", span, "ellipse");
+                let callee = self.expr(call.callee);
+                self.graph.edge(node, callee, Some("callee"));
+                for (index, arg) in call.args.iter().enumerate() {
+                    let child = self.expr(*arg);
+                    self.graph.edge(node, child, Some(&format!("arg {index}")));
+                }
+                node
+            }
+            ast::Expr::Field(field) => {
+                let node = self.node(format!(".{}", field.field.text), span, "ellipse");
+                let base = self.expr(field.base);
+                self.graph.edge(node, base, Some("base"));
+                node
+            }
+            ast::Expr::Assert(assert) => {
+                let node = self.node("Assert", span, "ellipse");
+                let cond = self.expr(assert.cond);
+                self.graph.edge(node, cond, Some("cond"));
+                if let Some(message) = assert.message {
+                    let child = self.expr(message);
+                    self.graph.edge(node, child, Some("message"));
+                }
+                node
+            }
+            ast::Expr::Panic(panic) => {
+                let node = self.node("Panic", span, "ellipse");
+                if let Some(message) = panic.message {
+                    let child = self.expr(message);
+                    self.graph.edge(node, child, Some("message"));
+                }
+                node
+            }
+            ast::Expr::Section(section) => {
+                let node = self.node(format!("Section {:?}", section.op), span, "ellipse");
+                for (operand, label) in [(&section.lhs, "lhs"), (&section.rhs, "rhs")] {
+                    let child = match operand {
+                        Some(operand) => self.expr(*operand),
+                        None => self.graph.node("_", "ellipse"),
+                    };
+                    self.graph.edge(node, child, Some(label));
+                }
+                node
+            }
+            ast::Expr::Block(block) => self.block(block),
+            ast::Expr::If(if_expr) => {
+                let node = self.node("If", span, "ellipse");
+                let cond = self.expr(if_expr.cond);
+                let then_block = self.block(&if_expr.then_block);
+                self.graph.edge(node, cond, Some("cond"));
+                self.graph.edge(node, then_block, Some("then"));
+                if let Some(else_branch) = &if_expr.else_branch {
+                    let child = self.expr(*else_branch);
+                    self.graph.edge(node, child, Some("else"));
+                }
+                node
+            }
+            ast::Expr::While(while_expr) => {
+                let node = self.node("While", span, "ellipse");
+                let cond = self.expr(while_expr.cond);
+                let body = self.block(&while_expr.body);
+                self.graph.edge(node, cond, Some("cond"));
+                self.graph.edge(node, body, Some("body"));
+                node
+            }
+            ast::Expr::Loop(loop_expr) => {
+                let node = self.node("Loop", span, "ellipse");
+                let body = self.block(&loop_expr.body);
+                self.graph.edge(node, body, Some("body"));
+                node
+            }
+            ast::Expr::Match(match_expr) => {
+                let node = self.node("Match", span, "ellipse");
+                let scrutinee = self.expr(match_expr.scrutinee);
+                self.graph.edge(node, scrutinee, Some("scrutinee"));
+                for arm in &match_expr.arms {
+                    let arm_node = self.node("Arm", arm.span, "ellipse");
+                    let label = format!("Pattern {}", arm.pattern);
+                    let pattern = self.node(label, arm.pattern.span(), "box");
+                    let value = self.expr(arm.value);
+                    self.graph.edge(arm_node, pattern, Some("pattern"));
+                    self.graph.edge(arm_node, value, Some("value"));
+                    self.graph.edge(node, arm_node, Some("arm"));
+                }
+                node
+            }
+            ast::Expr::Error(_) => self.node("Error", span, "ellipse"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ast, dot::Graph, error::SyntaxError, lexer::Lexer, parser::Parser};
+
+    #[test]
+    fn render() {
+        let mut graph = Graph::new("test");
+        let a = graph.node("say \"hi\"", "box");
+        let b = graph.node("b", "ellipse");
+        graph.edge(a, b, Some("next"));
+
+        let expected = concat!(
+            "digraph \"test\" {\n",
+            "    node [fontname = \"monospace\"];\n",
+            "    n0 [label = \"say \\\"hi\\\"\", shape = box];\n",
+            "    n1 [label = \"b\", shape = ellipse];\n",
+            "    n0 -> n1 [label = \"next\"];\n",
+            "}\n",
+        );
+
+        assert_eq!(graph.render(), expected);
+    }
+
+    #[test]
+    fn ast_spans() -> Result<(), SyntaxError> {
+        let tokens = Lexer::new("fn f() { 1 - x }").collect_tokens()?;
+        let (module, _) = Parser::new(&tokens).parse_module();
+
+        let dot = ast::to_dot(&module);
+
+        assert!(dot.contains("n1 [label = \"FuncDecl f\\n0..16\", shape = box];"));
+        assert!(dot.contains("n3 [label = \"Sub\\n9..14\", shape = ellipse];"));
+        assert!(dot.contains("n5 [label = \"x\\n13..14\", shape = ellipse];"));
+
+        Ok(())
+    }
+}
@@ -1,4 +1,9 @@
-use crate::token::{Span, TokenKind};
+use crate::{
+    diagnostics::{Applicability, Diagnostic},
+    limits::{Limit, Limits},
+    messages::message,
+    token::{Span, TokenKind},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SyntaxError {
@@ -11,6 +16,28 @@ impl SyntaxError {
         kind: SyntaxErrorKind::UnexpectedEndOfInput,
         span: Span::EOI,
     };
+
+    /// Converts the error into a [Diagnostic], using the [Limits] to describe exceeded ones.
+    pub fn diagnostic(self, limits: &Limits) -> Diagnostic {
+        let diagnostic = match self.kind {
+            SyntaxErrorKind::LimitExceeded(limit) => {
+                let (message, help) = limits.exceeded(limit);
+                Diagnostic::error(message, self.span).with_help(help)
+            }
+            SyntaxErrorKind::MissingArmArrow => Diagnostic::error(self.kind.message(), self.span)
+                .with_help(message("E0009.help", &[])),
+            SyntaxErrorKind::MissingSemicolon => Diagnostic::error(self.kind.message(), self.span)
+                .with_suggestion(
+                    message("E0010.suggestion", &[]),
+                    self.span,
+                    ";",
+                    Applicability::MachineApplicable,
+                ),
+            kind => Diagnostic::error(kind.message(), self.span),
+        };
+
+        diagnostic.with_code(self.kind.code())
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -23,4 +50,80 @@ pub enum SyntaxErrorKind {
     UnexpectedEndOfInput,
     NumberOverflow,
     InvalidNumber,
+    /// A string literal missing its closing `"`.
+    UnterminatedString,
+    /// A string literal containing an unknown escape sequence like `\q`.
+    InvalidEscape,
+    LimitExceeded(Limit),
+    /// A match arm whose pattern isn't followed by `=>`.
+    MissingArmArrow,
+    /// A statement not terminated by `;`, where the span is empty and points at
+    /// the end of the statement.
+    MissingSemicolon,
+}
+
+impl SyntaxErrorKind {
+    /// Returns the stable code of this kind of error, see [crate::explain::explain].
+    pub fn code(self) -> &'static str {
+        match self {
+            SyntaxErrorKind::UnexpectedToken { .. } => "E0001",
+            SyntaxErrorKind::InvalidNumber => "E0002",
+            SyntaxErrorKind::NumberOverflow => "E0003",
+            SyntaxErrorKind::InvalidLexicalToken => "E0004",
+            SyntaxErrorKind::UnexpectedEndOfInput => "E0005",
+            SyntaxErrorKind::UnterminatedString => "E0006",
+            SyntaxErrorKind::InvalidEscape => "E0007",
+            SyntaxErrorKind::LimitExceeded(_) => "E0008",
+            SyntaxErrorKind::MissingArmArrow => "E0009",
+            SyntaxErrorKind::MissingSemicolon => "E0010",
+        }
+    }
+
+    /// Returns the message describing this kind of error, from the installed
+    /// [crate::messages::Catalog].
+    pub fn message(self) -> String {
+        match self {
+            SyntaxErrorKind::UnexpectedToken { expected, got } => {
+                let found = got.to_string();
+                match expected {
+                    [kind] => message(
+                        "E0001",
+                        &[("expected", &kind.to_string()), ("found", &found)],
+                    ),
+                    _ => {
+                        let expected: Vec<_> = expected.iter().map(TokenKind::to_string).collect();
+                        let expected = expected.join(", ");
+                        message(
+                            "E0001.one-of",
+                            &[("expected", &expected), ("found", &found)],
+                        )
+                    }
+                }
+            }
+            SyntaxErrorKind::LimitExceeded(limit) => {
+                message("E0008", &[("limit", &limit.description())])
+            }
+            kind => message(kind.code(), &[]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{error::SyntaxErrorKind, token::TokenKind};
+
+    #[test]
+    fn unexpected_token_message() {
+        let kind = SyntaxErrorKind::UnexpectedToken {
+            expected: &[TokenKind::RBracket, TokenKind::Semicolon],
+            got: TokenKind::Identifier,
+        };
+        assert_eq!(kind.message(), "expected one of `]`, `;`, found identifier");
+
+        let kind = SyntaxErrorKind::UnexpectedToken {
+            expected: &[TokenKind::Pub],
+            got: TokenKind::At,
+        };
+        assert_eq!(kind.message(), "expected `pub`, found `@`");
+    }
 }
@@ -0,0 +1,7 @@
+/// Unstable language features that have to be opted into explicitly.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Experiments {
+    /// Operator sections like `(+ 1)` or `(_ * 2)`, which only compile when called
+    /// directly like `(+ 1)(x)`.
+    pub sections: bool,
+}
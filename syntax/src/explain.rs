@@ -0,0 +1,733 @@
+/// Returns the extended description of the given diagnostic code like `E0001`,
+/// including examples, or `None` if there is no such code.
+pub fn explain(code: &str) -> Option<&'static str> {
+    let explanation = match code {
+        "E0001" => {
+            "\
+A token appeared where the grammar doesn't allow it.
+
+Erroneous code example:
+
+    fn main() {
+        let x = 1 +;
+    }
+
+The operand after `+` is missing. The error lists the tokens that would have
+been accepted at this position."
+        }
+        "E0002" => {
+            "\
+A number literal is malformed.
+
+Erroneous code example:
+
+    let x = 0x;
+
+A prefixed literal needs at least one digit after its prefix, like `0x1f`."
+        }
+        "E0003" => {
+            "\
+An integer literal is too large to be represented.
+
+Erroneous code example:
+
+    let x = 999999999999999999999999999999999999999999;
+
+Integer literals must fit into 128 bits."
+        }
+        "E0004" => {
+            "\
+The source contains characters that don't start any token.
+
+Erroneous code example:
+
+    let x = 1 $ 2;
+
+Remove the characters or, if they are meant as text, put them in a string
+literal."
+        }
+        "E0005" => {
+            "\
+The file ended while an item, statement or expression was still incomplete.
+
+Erroneous code example:
+
+    fn main() {
+        let x = 1;
+
+Usually a closing delimiter like `}` or `)` is missing."
+        }
+        "E0006" => {
+            "\
+A string literal is missing its closing `\"`.
+
+Erroneous code example:
+
+    let greeting = \"hello;
+
+Add the closing quote at the end of the string."
+        }
+        "E0007" => {
+            "\
+A string literal contains an escape sequence that doesn't exist.
+
+Erroneous code example:
+
+    let path = \"C:\\users\";
+
+The supported escapes are `\\n`, `\\r`, `\\t`, `\\0`, `\\\\` and `\\\"`. Escape the
+backslash itself to write it literally, like `\"C:\\\\users\"`."
+        }
+        "E0008" => {
+            "\
+A compiler limit, like the maximum nesting depth of the parser, was exceeded.
+
+Erroneous code example, with the parser nesting depth limited to 4:
+
+    let x = (((((1)))));
+
+Every operator of a chain like `1 + 2 + 3` nests one level deeper as well.
+
+Simplify the code, or raise the limit in the `[limits]` table of `elan.toml`
+as the help of the error suggests.
+
+A struct containing itself, like `struct S { a: S }`, exceeds the type recursion
+depth limit however high it is. Store it behind a pointer like `*S` instead."
+        }
+        "E0009" => {
+            "\
+A match arm's pattern isn't followed by `=>`.
+
+Erroneous code example:
+
+    match x {
+        1 -> true,
+        _ => false,
+    }
+
+Separate the pattern from the arm's value with `=>`, like `1 => true`."
+        }
+        "E0010" => {
+            "\
+A statement isn't terminated by `;`.
+
+Erroneous code example:
+
+    fn main() {
+        let x = 1
+        print(x);
+    }
+
+Add the `;` after the statement. If the next line was meant to continue the
+expression, add the missing operator instead."
+        }
+        "E0011" => {
+            "\
+A name is used that isn't defined in any enclosing scope.
+
+Erroneous code example:
+
+    fn main() {
+        { let x = 1; }
+        print(x);
+    }
+
+Local bindings are only visible until the end of their block. Check the
+spelling of the name, or move the binding to a scope enclosing the use."
+        }
+        "E0012" => {
+            "\
+A name is defined more than once in the same scope.
+
+Erroneous code example:
+
+    fn area() {}
+    fn area() {}
+
+Items, parameters and generic parameters need distinct names. Local `let`
+bindings are the exception, as they may shadow earlier ones."
+        }
+        "E0013" => {
+            "\
+A path names a member its parent doesn't have.
+
+Erroneous code example:
+
+    enum Shape { Circle(f64) }
+
+    fn main() {
+        let s = Shape::Square(1.0);
+    }
+
+Check which variants the enum declares."
+        }
+        "E0014" => {
+            "\
+A name used as a type refers to something that isn't a type.
+
+Erroneous code example:
+
+    fn helper() {}
+    fn main(x: helper) {}
+
+Types are the built-in types like `i64`, structs, enums, traits and generic
+parameters."
+        }
+        "E0015" => {
+            "\
+A value has a different type than the one required where it is used.
+
+Erroneous code example:
+
+    fn main() {
+        let x: i64 = \"text\";
+    }
+
+Types are required by annotations, parameters, return types and the other
+branches of an `if` or `match`. Use a value of the required type, or convert it
+with `as` where that is allowed.
+
+Numbers convert implicitly only where no value changes: an integer to a larger
+integer that holds all its values, like `i32` to `i64` or `u8` to `i16`, and
+`f32` to `f64`. Integers and floats never convert into each other implicitly."
+        }
+        "E0016" => {
+            "\
+An operator is applied to operands it isn't defined for.
+
+Erroneous code example:
+
+    fn main(x: f64, y: bool) {
+        let z = x + y;
+    }
+
+Arithmetic needs two numbers of the same type, and ordering comparisons need
+numbers or strings. Both operands of `==` and `!=` must have the same type."
+        }
+        "E0017" => {
+            "\
+Something that isn't a function is called.
+
+Erroneous code example:
+
+    fn main(x: i64) {
+        x(1);
+    }"
+        }
+        "E0018" => {
+            "\
+An `as` cast converts between types that can't be converted.
+
+Erroneous code example:
+
+    fn main(x: str) {
+        let y = x as i64;
+    }
+
+Only numbers and `bool` can be cast to numbers."
+        }
+        "E0019" => {
+            "\
+A name used as a value refers to something that isn't a value.
+
+Erroneous code example:
+
+    struct Point { x: i64 }
+    fn main() {
+        let p = Point;
+    }
+
+Values are functions, constants, parameters, variables and enum variants
+without fields or with a tuple of fields."
+        }
+        "E0020" => {
+            "\
+The left-hand side of an assignment isn't a variable.
+
+Erroneous code example:
+
+    fn main() {
+        1 = 2;
+    }"
+        }
+        "E0021" => {
+            "\
+A `for` loop iterates over a value that isn't a range or an array.
+
+Erroneous code example:
+
+    fn main(x: i64) {
+        for i in x {}
+    }"
+        }
+        "E0022" => {
+            "\
+A function is called with more or fewer arguments than it has parameters.
+
+Erroneous code example:
+
+    fn add(a: i64, b: i64) -> i64 { a + b }
+    fn main() {
+        let x = add(1);
+    }"
+        }
+        "E0023" => {
+            "\
+A function with a return type has a branch that ends without returning a value.
+
+Erroneous code example:
+
+    fn sign(x: i64) -> i64 {
+        if x < 0 {
+            return -1;
+        }
+    }
+
+Every path through the body of a function that returns a value must end in a
+`return`, in a value at the end of a block, or in something that never ends like
+`panic()` or a `loop` without `break`."
+        }
+        "E0024" => {
+            "\
+An operation on constants gives a result outside of the range of its type.
+
+Erroneous code example:
+
+    fn main() {
+        let x: u8 = 200 + 100;
+    }
+
+Constant arithmetic is evaluated at compile time, where it doesn't wrap around.
+Use a larger type like `u16`, or change the operands."
+        }
+        "E0025" => {
+            "\
+An integer is divided by the constant zero, which has no result.
+
+Erroneous code example:
+
+    fn main() {
+        let x = 7 / 0;
+    }
+
+This also applies to the remainder operator `%`."
+        }
+        "E0026" => {
+            "\
+The initializer of a constant depends on the constant itself.
+
+Erroneous code example:
+
+    const A: i64 = B + 1;
+    const B: i64 = A * 2;
+
+The value of `A` is needed to compute `B` and the other way around, so neither
+can be computed. Replace one of the references by a value."
+        }
+        "E0027" => {
+            "\
+The initializer of a constant or an array length uses something that is only
+known when the program runs.
+
+Erroneous code example:
+
+    fn main(n: i64) {
+        const TWICE: i64 = n * 2;
+    }
+
+Constants can only be computed from literals, operators, `true`, `false` and
+other constants."
+        }
+        "E0028" => {
+            "\
+The length of an array type isn't an integer that can be a length.
+
+Erroneous code example:
+
+    const SIZE: i64 = -1;
+    fn main(values: [i64; SIZE]) {}"
+        }
+        "E0029" => {
+            "\
+An item of an imported module is used, but isn't marked `pub`.
+
+Erroneous code example:
+
+    // geometry.elan
+    fn area(r: f64) -> f64 { 3.14 * r * r }
+
+    // main.elan
+    import geometry;
+    fn main() { geometry::area(1.0); }
+
+Items are private to their module unless they are declared `pub`, like
+`pub fn area(r: f64) -> f64`."
+        }
+        "E0030" => {
+            "\
+An `import` names a module without a file.
+
+Erroneous code example:
+
+    import geometry;
+
+The module `geometry` imported by `src/main.elan` is loaded from
+`src/geometry.elan` or, for a module spanning a directory, from
+`src/geometry/mod.elan`. Paths like `math::trig` name the files
+`src/math/trig.elan` or `src/math/trig/mod.elan`."
+        }
+        "E0031" => {
+            "\
+Modules import each other in a cycle.
+
+Erroneous code example:
+
+    // a.elan
+    import b;
+
+    // b.elan
+    import a;
+
+A module's items are only known once the modules it imports are loaded, so
+modules can't depend on each other. Move the items both modules need into a
+third module imported by both."
+        }
+        "E0032" => {
+            "\
+A field is accessed on a value whose type doesn't have it.
+
+Erroneous code example:
+
+    struct Point { x: f64, y: f64 }
+    fn norm(p: Point) -> f64 { p.x * p.x + p.z * p.z }
+
+Only the fields declared by a struct can be accessed, so check the spelling
+against the struct's declaration. Enums, tuples and built-in types have no
+fields."
+        }
+        "E0033" => {
+            "\
+An integer literal doesn't fit in the integer type it is used as.
+
+Erroneous code example:
+
+    fn main() {
+        let x: u8 = 300;
+        let y: i8 = 0xff;
+    }
+
+Literals aren't truncated to their type, so `0xff` is 255 even when an `i8` is
+expected. Use a larger type like `u16`, or a value within the type's range like
+`-1` for the `i8` with all bits set."
+        }
+        "E0034" => {
+            "\
+A binding that is declared immutable is assigned to.
+
+Erroneous code example:
+
+    fn main() {
+        let count = 0;
+        count = 1;
+    }
+
+Bindings declared with `let` keep their value. Declare them with `var`, or
+`let mut`, to assign to them later. Parameters can't be assigned to either, so
+copy them into a `var` first. A `let` without a value may be assigned once it
+is given one."
+        }
+        "E0035" => {
+            "\
+A `match` doesn't cover every value of its scrutinee.
+
+Erroneous code example:
+
+    enum Shape { Circle(f64), Square(f64), Empty }
+
+    fn area(shape: Shape) -> f64 {
+        match shape {
+            Shape::Circle(r) => 3.14 * r * r,
+            Shape::Square(s) => s * s,
+        }
+    }
+
+Add an arm for each missing pattern, here `Shape::Empty`, or end the arms with
+`_` to match the remaining values. Integers, floats and strings are only fully
+covered by a `_` arm or a binding."
+        }
+        "E0036" => {
+            "\
+A program has no `main` function to start at.
+
+Erroneous code example:
+
+    const main: i64 = 0;
+
+    fn start() {}
+
+A program runs the function named `main`. Rename the function the program should
+start at to `main`, and rename any other item named `main`."
+        }
+        "E0037" => {
+            "\
+The `main` function has a signature the program can't be started with.
+
+Erroneous code example:
+
+    fn main(args: i64) -> str {
+        \"done\"
+    }
+
+`main` takes no parameters and has no generic parameters. It either returns `()`
+or an `i32`, which the program exits with."
+        }
+        "E0038" => {
+            "\
+A program uses a construct the backends can't compile yet.
+
+Erroneous code example:
+
+    fn main() {
+        let pair = (1, 2);
+    }
+
+Programs are compiled through an intermediate representation which only holds
+numbers, `bool`s and `str`s so far. Tuples, arrays, optional values, structs,
+enums, references, function values and generic functions are checked, but can't
+be compiled or run yet. Operator sections only compile when they are called
+directly, like `(+ 1)(x)`."
+        }
+        "E0039" => {
+            "\
+A running program stopped with an error.
+
+Erroneous code example:
+
+    fn half(n: i32) -> i32 {
+        n / 0
+    }
+
+    fn main() -> i32 {
+        half(10)
+    }
+
+Integer arithmetic stops the program when its result doesn't fit the type or it
+divides by zero, and so does a failed `assert` or `panic`. Too many nested
+calls, like a recursive function that never stops calling itself, overflow the
+stack. The error points at the operation that failed."
+        }
+        "W0001" => {
+            "\
+A decimal integer literal has leading zeros, which makes it look octal.
+
+Example:
+
+    let mode = 0755;
+
+This is the decimal number 755. Use `0o755` for an octal literal or remove
+the leading zeros."
+        }
+        "W0002" => {
+            "\
+A prefixed integer literal is directly followed by digits that are invalid in
+its base.
+
+Example:
+
+    let mask = 0b102;
+
+This is lexed as `0b10` followed by `2`. Remove the invalid digits or use a
+different base."
+        }
+        "W0003" => {
+            "\
+A float literal stored as an `f32` is rounded to a different value.
+
+Example:
+
+    let x: f32 = 16777217.0;
+
+The stored value is `16777216.0`. Use `f64` if the precision matters."
+        }
+        "W0004" => {
+            "\
+A binding hides a parameter or an earlier binding of the same function.
+
+Example:
+
+    fn total(n: i64) -> i64 {
+        let n = n * 2;
+        n
+    }
+
+Shadowing is allowed, but uses of `n` after the `let` silently refer to the
+new binding. Rename one of them, or allow the `shadowing` lint with
+`@allow(shadowing)` where it is deliberate."
+        }
+        "W0005" => {
+            "\
+A local binding is never read.
+
+Example:
+
+    fn main() {
+        let count = 1;
+    }
+
+Assigning to a binding doesn't count as reading it. Remove the binding, or start
+its name with an underscore like `_count` if it is meant to be unused."
+        }
+        "W0006" => {
+            "\
+A function is never called, except possibly by itself.
+
+Example:
+
+    fn helper() {}
+    fn main() {}
+
+Remove the function, start its name with an underscore, or allow the
+`unused_functions` lint with `@allow(unused_functions)` on it. The `main`
+function is never reported."
+        }
+        "W0007" => {
+            "\
+A function calls itself, directly or through other functions, on every path
+through its body, so it can never return.
+
+Example:
+
+    fn countdown(n: i64) -> i64 {
+        countdown(n - 1)
+    }
+
+Add a base case which returns without recursing, like
+`if n == 0 { return 0; }` before the call."
+        }
+        "W0008" => {
+            "\
+Code follows a statement that never finishes, or is the body of a loop that
+never runs.
+
+Example:
+
+    fn main() {
+        return;
+        cleanup();
+        while false { step(); }
+    }
+
+Statements after a `return`, `break`, `continue` or a call of `panic` are
+never reached. Remove the code, or move it before the statement that leaves."
+        }
+        "W0009" => {
+            "\
+A match arm only matches values that an earlier arm already matches, so it is
+never chosen.
+
+Example:
+
+    match flag {
+        _ => 0,
+        true => 1,
+    }
+
+Arms are tried in order. Move the more specific arm before the one matching
+everything, or remove it."
+        }
+        "W0010" => {
+            "\
+An expression is wrapped in parentheses that don't change how it is grouped.
+
+Example:
+
+    if (count > 0) {
+        return (count);
+    }
+
+Conditions, initializers and returned values never need parentheses, and
+neither do names or calls. Remove them, which `elanc fix` does automatically."
+        }
+        "W0011" => {
+            "\
+The condition of an `if` or `while` is always `true` or always `false`.
+
+Example:
+
+    while true {
+        if !false { break; }
+    }
+
+One of the branches never runs. Use `loop` for a loop that only ends with
+`break`, and remove the `if` when its branch always runs."
+        }
+        "W0012" => {
+            "\
+A lint name given to `-A`, `-W` or `-D`, or in an `@allow` attribute, names
+no lint or group of lints.
+
+Example:
+
+    @allow(unused_variable)
+    fn main() {
+        let x = 1;
+    }
+
+The misspelled name silences nothing. Use one of the names shown in the
+warnings, like `unused_variables`, a group like `unused`, or `warnings` for
+all lints."
+        }
+        _ => return None,
+    };
+
+    Some(explanation)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        error::SyntaxErrorKind,
+        explain::explain,
+        limits::Limit,
+        lints::{LiteralWarningKind, unknown_lint},
+        token::{Span, TokenKind},
+    };
+
+    #[test]
+    fn every_code_explained() {
+        let errors = [
+            SyntaxErrorKind::InvalidLexicalToken,
+            SyntaxErrorKind::UnexpectedToken {
+                expected: &[TokenKind::Semicolon],
+                got: TokenKind::Comma,
+            },
+            SyntaxErrorKind::UnexpectedEndOfInput,
+            SyntaxErrorKind::NumberOverflow,
+            SyntaxErrorKind::InvalidNumber,
+            SyntaxErrorKind::UnterminatedString,
+            SyntaxErrorKind::InvalidEscape,
+            SyntaxErrorKind::LimitExceeded(Limit::ParseNestingDepth),
+            SyntaxErrorKind::MissingArmArrow,
+            SyntaxErrorKind::MissingSemicolon,
+        ];
+        let warnings = [
+            LiteralWarningKind::LeadingZeros,
+            LiteralWarningKind::InvalidDigitsForBase { base: 2 },
+            LiteralWarningKind::F32PrecisionLoss,
+        ];
+
+        let codes: Vec<_> = errors
+            .into_iter()
+            .map(SyntaxErrorKind::code)
+            .chain(warnings.into_iter().map(LiteralWarningKind::code))
+            .chain(unknown_lint("bogus", Span::EOI).code)
+            .collect();
+
+        for (index, code) in codes.iter().enumerate() {
+            assert!(explain(code).is_some(), "{code} has no explanation");
+            assert!(!codes[..index].contains(code), "{code} is used twice");
+        }
+
+        assert_eq!(explain("E9999"), None);
+    }
+}
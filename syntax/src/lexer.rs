@@ -36,6 +36,12 @@ impl<'src> Lexer<'src> {
         self.iter.peek().copied()
     }
 
+    #[inline]
+    /// Returns the [char] after the next one in the source text without advancing.
+    fn peek_second(&self) -> Option<char> {
+        self.iter.clone().nth(1)
+    }
+
     #[inline]
     /// Consumes the next [char] in the source text.
     fn next(&mut self) {
@@ -64,10 +70,30 @@ impl<'src> Lexer<'src> {
         }
     }
 
-    /// Skips whitespace [char]s.
-    fn skip_whitespace(&mut self) {
-        while self.peek().is_some_and(char::is_whitespace) {
-            self.next();
+    /// Returns whether a `//` comment starts at the next [char], and if so whether
+    /// it is a `///` doc comment, which mustn't be followed by a fourth `/`.
+    fn peek_comment(&self) -> Option<bool> {
+        let mut chars = self.iter.clone();
+
+        if chars.next() != Some('/') || chars.next() != Some('/') {
+            return None;
+        }
+
+        Some(chars.next() == Some('/') && chars.next() != Some('/'))
+    }
+
+    /// Skips whitespace [char]s and comments, but not doc comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            match self.peek() {
+                Some(ch) if ch.is_whitespace() => self.next(),
+                Some('/') if self.peek_comment() == Some(false) => {
+                    while self.peek().is_some_and(|ch| ch != '\n') {
+                        self.next();
+                    }
+                }
+                _ => return,
+            }
         }
     }
 
@@ -93,7 +119,7 @@ impl<'src> Lexer<'src> {
         Token { kind, span, text }
     }
 
-    /// Used to lex the next [TokenKind::Identifier] [Token].
+    /// Used to lex the next [TokenKind::Identifier] or keyword [Token].
     fn next_identifier_token(&mut self) -> LexerResult<'src> {
         let start = self.pos;
         let byte_start = self.byte_pos;
@@ -105,16 +131,15 @@ impl<'src> Lexer<'src> {
             self.next();
         }
 
-        Some(Ok(self.create_token(
-            start,
-            byte_start,
-            TokenKind::Identifier,
-        )))
+        let text = &self.text[byte_start..self.byte_pos];
+        let kind = TokenKind::keyword(text).unwrap_or(TokenKind::Identifier);
+
+        Some(Ok(self.create_token(start, byte_start, kind)))
     }
 
     /// Used to lex the next [TokenKind::Integer] or [TokenKind::Float] [Token].
     fn next_number_token(&mut self) -> LexerResult<'src> {
-        const HEX_CHARS: fn(char) -> bool = |ch| matches!(ch, 'a'..='f' | 'A'..='F' | '0'..='9');
+        const HEX_CHARS: fn(char) -> bool = |ch| ch.is_ascii_hexdigit();
         const BIN_CHARS: fn(char) -> bool = |ch| matches!(ch, '0' | '1');
         const OCT_CHARS: fn(char) -> bool = |ch| matches!(ch, '0'..='7');
 
@@ -131,11 +156,13 @@ impl<'src> Lexer<'src> {
             }
         }
 
-        while self.peek().is_some_and(|ch| matches!(ch, '0'..='9')) {
+        while self.peek().is_some_and(|ch| ch.is_ascii_digit()) {
             self.next();
         }
 
-        if self.try_next('.') {
+        // a second `.` starts a range like `0..10` instead of the fraction.
+        if self.is_peek('.') && self.peek_second() != Some('.') {
+            self.next();
             return self.next_float_token(start, byte_start);
         }
 
@@ -170,7 +197,7 @@ impl<'src> Lexer<'src> {
     /// Used to lex the next [TokenKind::Float] [Token].
     fn next_float_token(&mut self, start: usize, byte_start: usize) -> LexerResult<'src> {
         let after_dot_start = self.pos;
-        while self.peek().is_some_and(|ch| matches!(ch, '0'..='9')) {
+        while self.peek().is_some_and(|ch| ch.is_ascii_digit()) {
             self.next();
         }
 
@@ -189,7 +216,7 @@ impl<'src> Lexer<'src> {
             self.try_next('-');
 
             let after_e_start = self.pos;
-            while self.peek().is_some_and(|ch| matches!(ch, '0'..='9')) {
+            while self.peek().is_some_and(|ch| ch.is_ascii_digit()) {
                 self.next();
             }
 
@@ -208,32 +235,84 @@ impl<'src> Lexer<'src> {
         Some(Ok(self.create_token(start, byte_start, TokenKind::Float)))
     }
 
+    /// Used to lex the next [TokenKind::String] [Token], keeping escapes unprocessed.
+    fn next_string_token(&mut self) -> LexerResult<'src> {
+        let start = self.pos;
+        let byte_start = self.byte_pos;
+
+        // skip the opening `"`.
+        self.next();
+
+        loop {
+            match self.peek() {
+                Some('"') => break,
+                Some('\\') => {
+                    // skip the escaped char so an escaped `"` doesn't end the literal.
+                    self.next();
+                    self.next();
+                }
+                Some(_) => self.next(),
+                None => {
+                    return Some(Err(SyntaxError {
+                        kind: SyntaxErrorKind::UnterminatedString,
+                        span: Span {
+                            start,
+                            end: self.pos,
+                        },
+                    }));
+                }
+            }
+        }
+
+        self.next();
+
+        Some(Ok(self.create_token(start, byte_start, TokenKind::String)))
+    }
+
     /// Used to lex the next [Token].
     pub fn next_token(&mut self) -> LexerResult<'src> {
-        self.skip_whitespace();
+        self.skip_trivia();
 
         let start = self.pos;
         let byte_start = self.byte_pos;
 
-        let Some(ch) = self.peek() else {
-            return None;
-        };
+        let ch = self.peek()?;
 
         Some(Ok(match ch {
             'a'..='z' | 'A'..='Z' | '_' => return self.next_identifier_token(),
             '0'..='9' => return self.next_number_token(),
+            '"' => return self.next_string_token(),
 
             '+' => self.create_simple_token(TokenKind::Plus),
-            '-' => self.create_simple_token(TokenKind::Minus),
+            '-' => {
+                self.next();
+
+                if self.try_next('>') {
+                    self.create_token(start, byte_start, TokenKind::Arrow)
+                } else {
+                    self.create_token(start, byte_start, TokenKind::Minus)
+                }
+            }
+
             '*' => self.create_simple_token(TokenKind::Asterisk),
+            '/' if self.peek_comment() == Some(true) => {
+                while self.peek().is_some_and(|ch| ch != '\n') {
+                    self.next();
+                }
+
+                self.create_token(start, byte_start, TokenKind::DocComment)
+            }
             '/' => self.create_simple_token(TokenKind::Slash),
             '%' => self.create_simple_token(TokenKind::Percent),
+            '&' => self.create_simple_token(TokenKind::Ampersand),
 
             '=' => {
                 self.next();
 
                 if self.try_next('=') {
                     self.create_token(start, byte_start, TokenKind::Equal)
+                } else if self.try_next('>') {
+                    self.create_token(start, byte_start, TokenKind::FatArrow)
                 } else {
                     self.create_token(start, byte_start, TokenKind::Assign)
                 }
@@ -269,12 +348,47 @@ impl<'src> Lexer<'src> {
                 }
             }
 
-            '.' => self.create_simple_token(TokenKind::Dot),
+            '.' => {
+                self.next();
+
+                if !self.try_next('.') {
+                    self.create_token(start, byte_start, TokenKind::Dot)
+                } else if self.try_next('=') {
+                    self.create_token(start, byte_start, TokenKind::DotDotEq)
+                } else {
+                    self.create_token(start, byte_start, TokenKind::DotDot)
+                }
+            }
+            ',' => self.create_simple_token(TokenKind::Comma),
+            '@' => self.create_simple_token(TokenKind::At),
+            '?' => self.create_simple_token(TokenKind::Question),
+            ':' => {
+                self.next();
+
+                if self.try_next(':') {
+                    self.create_token(start, byte_start, TokenKind::ColonColon)
+                } else {
+                    self.create_token(start, byte_start, TokenKind::Colon)
+                }
+            }
+
+            ';' => self.create_simple_token(TokenKind::Semicolon),
             '(' => self.create_simple_token(TokenKind::LParen),
             ')' => self.create_simple_token(TokenKind::RParen),
+            '{' => self.create_simple_token(TokenKind::LBrace),
+            '}' => self.create_simple_token(TokenKind::RBrace),
+            '[' => self.create_simple_token(TokenKind::LBracket),
+            ']' => self.create_simple_token(TokenKind::RBracket),
 
             _ => {
+                // consume the whole run of invalid characters so it's reported once.
                 self.next();
+                while self
+                    .peek()
+                    .is_some_and(|ch| !ch.is_whitespace() && !is_token_start(ch))
+                {
+                    self.next();
+                }
 
                 let span = Span {
                     start,
@@ -301,6 +415,40 @@ impl<'src> Lexer<'src> {
     }
 }
 
+/// Returns whether the given [char] can start a valid [Token].
+fn is_token_start(ch: char) -> bool {
+    matches!(
+        ch,
+        'a'..='z'
+            | 'A'..='Z'
+            | '_'
+            | '0'..='9'
+            | '"'
+            | '+'
+            | '-'
+            | '*'
+            | '/'
+            | '%'
+            | '&'
+            | '='
+            | '!'
+            | '<'
+            | '>'
+            | '.'
+            | ','
+            | '@'
+            | '?'
+            | ':'
+            | ';'
+            | '('
+            | ')'
+            | '{'
+            | '}'
+            | '['
+            | ']'
+    )
+}
+
 impl<'src> Iterator for Lexer<'src> {
     type Item = Result<Token<'src>, SyntaxError>;
 
@@ -449,9 +597,137 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn separators() -> Result<(), SyntaxError> {
+        let input = "a, b;";
+        let expected = [
+            Token {
+                kind: Identifier,
+                span: Span { start: 0, end: 1 },
+                text: "a",
+            },
+            Token {
+                kind: Comma,
+                span: Span { start: 1, end: 2 },
+                text: ",",
+            },
+            Token {
+                kind: Identifier,
+                span: Span { start: 3, end: 4 },
+                text: "b",
+            },
+            Token {
+                kind: Semicolon,
+                span: Span { start: 4, end: 5 },
+                text: ";",
+            },
+        ];
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        assert_eq!(tokens.as_slice(), expected.as_slice());
+
+        Ok(())
+    }
+
+    #[test]
+    fn keywords() -> Result<(), SyntaxError> {
+        let input = "fn return: -> {} :: [&] =>";
+        let expected = [
+            Token {
+                kind: Fn,
+                span: Span { start: 0, end: 2 },
+                text: "fn",
+            },
+            Token {
+                kind: Return,
+                span: Span { start: 3, end: 9 },
+                text: "return",
+            },
+            Token {
+                kind: Colon,
+                span: Span { start: 9, end: 10 },
+                text: ":",
+            },
+            Token {
+                kind: Arrow,
+                span: Span { start: 11, end: 13 },
+                text: "->",
+            },
+            Token {
+                kind: LBrace,
+                span: Span { start: 14, end: 15 },
+                text: "{",
+            },
+            Token {
+                kind: RBrace,
+                span: Span { start: 15, end: 16 },
+                text: "}",
+            },
+            Token {
+                kind: ColonColon,
+                span: Span { start: 17, end: 19 },
+                text: "::",
+            },
+            Token {
+                kind: LBracket,
+                span: Span { start: 20, end: 21 },
+                text: "[",
+            },
+            Token {
+                kind: Ampersand,
+                span: Span { start: 21, end: 22 },
+                text: "&",
+            },
+            Token {
+                kind: RBracket,
+                span: Span { start: 22, end: 23 },
+                text: "]",
+            },
+            Token {
+                kind: FatArrow,
+                span: Span { start: 24, end: 26 },
+                text: "=>",
+            },
+        ];
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        assert_eq!(tokens.as_slice(), expected.as_slice());
+
+        Ok(())
+    }
+
+    #[test]
+    fn comments() {
+        let input = "a // comment\n/// docs\n//// comment\nb /";
+        let expected = [
+            Token {
+                kind: Identifier,
+                span: Span { start: 0, end: 1 },
+                text: "a",
+            },
+            Token {
+                kind: DocComment,
+                span: Span { start: 13, end: 21 },
+                text: "/// docs",
+            },
+            Token {
+                kind: Identifier,
+                span: Span { start: 35, end: 36 },
+                text: "b",
+            },
+            Token {
+                kind: Slash,
+                span: Span { start: 37, end: 38 },
+                text: "/",
+            },
+        ];
+
+        assert_eq!(Lexer::new(input).collect_tokens(), Ok(expected.to_vec()));
+    }
+
     #[test]
     fn error() {
-        let input = "@";
+        let input = "$";
         let expected = Some(Err(SyntaxError {
             kind: SyntaxErrorKind::InvalidLexicalToken,
             span: Span { start: 0, end: 1 },
@@ -461,6 +737,29 @@ mod test {
         assert_eq!(lexer.next_token(), expected);
     }
 
+    #[test]
+    fn invalid_run() {
+        let input = "`#\u{1F600}$x ~";
+        let expected = [
+            Err(SyntaxError {
+                kind: SyntaxErrorKind::InvalidLexicalToken,
+                span: Span { start: 0, end: 4 },
+            }),
+            Ok(Token {
+                kind: Identifier,
+                span: Span { start: 4, end: 5 },
+                text: "x",
+            }),
+            Err(SyntaxError {
+                kind: SyntaxErrorKind::InvalidLexicalToken,
+                span: Span { start: 6, end: 7 },
+            }),
+        ];
+
+        let tokens: Vec<_> = Lexer::new(input).collect();
+        assert_eq!(tokens.as_slice(), expected.as_slice());
+    }
+
     #[test]
     fn invalid_numbers() {
         let test_cases = [
@@ -506,4 +805,61 @@ mod test {
             assert_eq!(lexer.collect_tokens(), output);
         }
     }
+
+    #[test]
+    fn strings() {
+        let input = r#""hi" "a\"b" "ü""#;
+        let expected = [
+            Token {
+                kind: String,
+                span: Span { start: 0, end: 4 },
+                text: r#""hi""#,
+            },
+            Token {
+                kind: String,
+                span: Span { start: 5, end: 11 },
+                text: r#""a\"b""#,
+            },
+            Token {
+                kind: String,
+                span: Span { start: 12, end: 15 },
+                text: r#""ü""#,
+            },
+        ];
+
+        let tokens = Lexer::new(input).collect_tokens();
+        assert_eq!(tokens.as_deref(), Ok(expected.as_slice()));
+
+        let tokens = Lexer::new(r#"x = "abc\""#).collect_tokens();
+        assert_eq!(
+            tokens,
+            Err(SyntaxError {
+                kind: SyntaxErrorKind::UnterminatedString,
+                span: Span { start: 4, end: 10 },
+            })
+        );
+    }
+
+    #[test]
+    fn ranges() -> Result<(), SyntaxError> {
+        let input = "0..10 1..=n 1.5";
+        let expected = [
+            (Integer, "0"),
+            (DotDot, ".."),
+            (Integer, "10"),
+            (Integer, "1"),
+            (DotDotEq, "..="),
+            (Identifier, "n"),
+            (Float, "1.5"),
+        ];
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let kinds: Vec<_> = tokens
+            .iter()
+            .map(|token| (token.kind, token.text))
+            .collect();
+        assert_eq!(kinds, expected);
+
+        Ok(())
+    }
 }
@@ -1,5 +1,16 @@
 pub mod ast;
+pub mod cst;
+pub mod diagnostics;
+pub mod dot;
 pub mod error;
+pub mod experiments;
+pub mod explain;
 pub mod lexer;
+pub mod limits;
+pub mod lints;
+pub mod messages;
 pub mod parser;
+pub mod sarif;
+pub mod source;
 pub mod token;
+pub mod visit;
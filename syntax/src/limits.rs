@@ -0,0 +1,121 @@
+use std::fmt;
+
+use crate::messages;
+
+/// Identifies one of the configurable compiler [Limits].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Limit {
+    /// How deeply nested constructs the parser accepts.
+    ParseNestingDepth,
+    /// How deeply nested types the type checker resolves.
+    TypeRecursionDepth,
+    /// How many nested generic instantiations are performed.
+    MonomorphizationDepth,
+    /// How many steps the compile-time evaluator may take.
+    ConstEvalSteps,
+}
+
+impl Limit {
+    /// Every [Limit] in the order they appear in the manifest.
+    pub const ALL: [Limit; 4] = [
+        Limit::ParseNestingDepth,
+        Limit::TypeRecursionDepth,
+        Limit::MonomorphizationDepth,
+        Limit::ConstEvalSteps,
+    ];
+
+    /// Returns the key of this [Limit] in the `[limits]` table of the manifest.
+    pub fn key(self) -> &'static str {
+        match self {
+            Limit::ParseNestingDepth => "parse-nesting-depth",
+            Limit::TypeRecursionDepth => "type-recursion-depth",
+            Limit::MonomorphizationDepth => "monomorphization-depth",
+            Limit::ConstEvalSteps => "const-eval-steps",
+        }
+    }
+
+    /// Looks up a [Limit] by its manifest key.
+    pub fn from_key(key: &str) -> Option<Limit> {
+        Limit::ALL.into_iter().find(|limit| limit.key() == key)
+    }
+
+    /// Returns a human readable description of this [Limit].
+    pub fn description(self) -> &'static str {
+        match self {
+            Limit::ParseNestingDepth => "parser nesting depth",
+            Limit::TypeRecursionDepth => "type recursion depth",
+            Limit::MonomorphizationDepth => "monomorphization depth",
+            Limit::ConstEvalSteps => "const evaluation step count",
+        }
+    }
+
+    /// Returns the message and help text for exceeding this [Limit] set to `value`.
+    pub fn exceeded(self, value: usize) -> (String, String) {
+        let message = messages::message(
+            "E0008.value",
+            &[("limit", &self.description()), ("value", &value)],
+        );
+        let help = messages::message(
+            "E0008.help",
+            &[
+                ("key", &self.key()),
+                ("suggested", &value.saturating_mul(2)),
+            ],
+        );
+
+        (message, help)
+    }
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.key())
+    }
+}
+
+/// All limits the compiler enforces to guard against runaway inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub parse_nesting_depth: usize,
+    pub type_recursion_depth: usize,
+    pub monomorphization_depth: usize,
+    pub const_eval_steps: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            parse_nesting_depth: 256,
+            type_recursion_depth: 64,
+            monomorphization_depth: 128,
+            const_eval_steps: 1_000_000,
+        }
+    }
+}
+
+impl Limits {
+    /// Returns the value configured for the given [Limit].
+    pub fn get(&self, limit: Limit) -> usize {
+        match limit {
+            Limit::ParseNestingDepth => self.parse_nesting_depth,
+            Limit::TypeRecursionDepth => self.type_recursion_depth,
+            Limit::MonomorphizationDepth => self.monomorphization_depth,
+            Limit::ConstEvalSteps => self.const_eval_steps,
+        }
+    }
+
+    /// Overrides the value configured for the given [Limit].
+    pub fn set(&mut self, limit: Limit, value: usize) {
+        match limit {
+            Limit::ParseNestingDepth => self.parse_nesting_depth = value,
+            Limit::TypeRecursionDepth => self.type_recursion_depth = value,
+            Limit::MonomorphizationDepth => self.monomorphization_depth = value,
+            Limit::ConstEvalSteps => self.const_eval_steps = value,
+        }
+    }
+
+    /// Returns the message and help text for exceeding the given [Limit].
+    pub fn exceeded(&self, limit: Limit) -> (String, String) {
+        limit.exceeded(self.get(limit))
+    }
+}
@@ -0,0 +1,426 @@
+use crate::{
+    ast,
+    diagnostics::{Applicability, Diagnostic, LintLevels, Severity},
+    messages::message,
+    token::{Span, Token, TokenKind},
+    visit::{self, Visitor},
+};
+
+/// A warning about a literal that is valid but likely not what was meant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiteralWarning {
+    pub kind: LiteralWarningKind,
+    pub span: Span,
+    /// The replacement text suggested for the [Span].
+    pub suggestion: Option<String>,
+}
+
+impl LiteralWarning {
+    /// Converts the warning into a [Diagnostic], suggesting its replacement as help.
+    pub fn diagnostic(&self) -> Diagnostic {
+        let diagnostic = Diagnostic::warning(self.kind.message(), self.span)
+            .with_code(self.kind.code())
+            .with_lint(self.kind.name());
+
+        let Some(suggestion) = &self.suggestion else {
+            return diagnostic;
+        };
+
+        let message = match suggestion.as_str() {
+            "" => message("lint.remove", &[]),
+            suggestion => message("lint.replace", &[("replacement", &suggestion)]),
+        };
+
+        diagnostic.with_suggestion(
+            message,
+            self.span,
+            suggestion,
+            Applicability::MachineApplicable,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiteralWarningKind {
+    /// A decimal literal with leading zeros, like `010`, which looks octal.
+    LeadingZeros,
+    /// Digits directly following a prefixed literal that are invalid for its base, like `0b102`.
+    InvalidDigitsForBase { base: u32 },
+    /// A float literal that can't be represented exactly as an `f32`.
+    F32PrecisionLoss,
+}
+
+impl LiteralWarningKind {
+    /// Returns the name of the lint reporting this kind of warning, used to configure its level.
+    pub fn name(self) -> &'static str {
+        match self {
+            LiteralWarningKind::LeadingZeros => "leading_zeros",
+            LiteralWarningKind::InvalidDigitsForBase { .. } => "invalid_digits_for_base",
+            LiteralWarningKind::F32PrecisionLoss => "f32_precision_loss",
+        }
+    }
+
+    /// Returns the stable code of this kind of warning, see [crate::explain::explain].
+    pub fn code(self) -> &'static str {
+        match self {
+            LiteralWarningKind::LeadingZeros => "W0001",
+            LiteralWarningKind::InvalidDigitsForBase { .. } => "W0002",
+            LiteralWarningKind::F32PrecisionLoss => "W0003",
+        }
+    }
+
+    /// Returns the message describing this kind of warning, from the installed
+    /// [crate::messages::Catalog].
+    pub fn message(self) -> String {
+        match self {
+            LiteralWarningKind::InvalidDigitsForBase { base } => {
+                message(self.code(), &[("base", &base)])
+            }
+            kind => message(kind.code(), &[]),
+        }
+    }
+}
+
+/// Checks the integer literals of the given [Token]s for likely mistakes.
+pub fn lint_literals(tokens: &[Token]) -> Vec<LiteralWarning> {
+    let mut warnings = Vec::new();
+
+    for (index, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Integer {
+            continue;
+        }
+
+        if let Some(warning) = lint_leading_zeros(token) {
+            warnings.push(warning);
+        }
+
+        if let Some(warning) = tokens
+            .get(index + 1)
+            .and_then(|next| lint_digits_for_base(token, next))
+        {
+            warnings.push(warning);
+        }
+    }
+
+    warnings
+}
+
+fn lint_leading_zeros(token: &Token) -> Option<LiteralWarning> {
+    let text = token.text;
+
+    if text.len() < 2 || !text.starts_with('0') || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let zeros = match text.trim_start_matches('0') {
+        // keep a single zero for literals like `000`.
+        "" => text.len() - 1,
+        trimmed => text.len() - trimmed.len(),
+    };
+
+    Some(LiteralWarning {
+        kind: LiteralWarningKind::LeadingZeros,
+        span: Span {
+            start: token.span.start,
+            end: token.span.start + zeros,
+        },
+        suggestion: Some(String::new()),
+    })
+}
+
+fn lint_digits_for_base(token: &Token, next: &Token) -> Option<LiteralWarning> {
+    let base = match token.text.get(..2)? {
+        "0x" => 16,
+        "0o" => 8,
+        "0b" => 2,
+        _ => return None,
+    };
+
+    // the digits have to follow the literal without any whitespace in between.
+    if next.span.start != token.span.end {
+        return None;
+    }
+
+    if !matches!(next.kind, TokenKind::Integer | TokenKind::Identifier)
+        || !next.text.starts_with(|ch: char| ch.is_ascii_alphanumeric())
+    {
+        return None;
+    }
+
+    let digit_count = next
+        .text
+        .chars()
+        .take_while(|ch| ch.is_ascii_alphanumeric())
+        .count();
+
+    Some(LiteralWarning {
+        kind: LiteralWarningKind::InvalidDigitsForBase { base },
+        span: Span {
+            start: next.span.start,
+            end: next.span.start + digit_count,
+        },
+        suggestion: None,
+    })
+}
+
+/// Checks whether the given [ast::FloatLiteral] keeps its value when stored as an
+/// `f32`, to be used wherever a literal is assigned to an `f32`. Literals like `0.1`
+/// whose shortest `f32` spelling reads back as the written value aren't reported.
+pub fn lint_f32_literal(literal: &ast::FloatLiteral) -> Option<LiteralWarning> {
+    let value = f64::from_bits(literal.value_bits);
+    let narrowed = value as f32;
+    let spelling = f32_literal(narrowed);
+
+    if spelling.parse::<f64>() == Ok(value) {
+        return None;
+    }
+
+    // the suggestion spells out the value stored, which a literal too large for an
+    // `f32` doesn't have.
+    Some(LiteralWarning {
+        kind: LiteralWarningKind::F32PrecisionLoss,
+        span: literal.span,
+        suggestion: narrowed.is_finite().then_some(spelling),
+    })
+}
+
+/// Spells the `f32` as the shortest float literal reading back as it, like `16777216.0`.
+fn f32_literal(value: f32) -> String {
+    let spelling = format!("{value:?}");
+
+    // the debug format leaves out the fraction of exponents like `1e20`, which float
+    // literals require.
+    match spelling.split_once('e') {
+        Some((mantissa, exponent)) if !mantissa.contains('.') => {
+            format!("{mantissa}.0e{exponent}")
+        }
+        _ => spelling,
+    }
+}
+
+/// The name of the lint warning about [unknown_lint]s.
+pub const UNKNOWN_LINTS: &str = "unknown_lints";
+
+/// Returns the warning about a lint name that names no lint or group, given on the
+/// command line or in an `@allow` attribute at the [Span].
+pub fn unknown_lint(name: &str, span: Span) -> Diagnostic {
+    Diagnostic::warning(message("W0012", &[("name", &name)]), span)
+        .with_code("W0012")
+        .with_lint(UNKNOWN_LINTS)
+}
+
+/// The regions of a module where `@allow(...)` attributes on items and statements
+/// silence lints, like `@allow(leading_zeros)` or `@allow(warnings)` for all of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Suppressions {
+    /// Each allowed lint along with the [Span] of the item or statement allowing it.
+    regions: Vec<(String, Span)>,
+    /// Each allowed lint along with the [Span] of its name in the attribute.
+    names: Vec<(String, Span)>,
+}
+
+impl Suppressions {
+    /// Collects the `@allow` attributes of the given [ast::Module], whose arguments name
+    /// lints either as identifiers or, for names like `no-main`, as strings.
+    pub fn collect(module: &ast::Module) -> Self {
+        let mut collector = SuppressionCollector {
+            exprs: &module.exprs,
+            regions: Vec::new(),
+            names: Vec::new(),
+        };
+        collector.visit_module(module);
+
+        Self {
+            regions: collector.regions,
+            names: collector.names,
+        }
+    }
+
+    /// Returns the names of the allowed lints along with their [Span]s, in order.
+    pub fn names(&self) -> &[(String, Span)] {
+        &self.names
+    }
+
+    /// Returns whether the given [Diagnostic] is a warning of a lint allowed around it.
+    pub fn suppresses(&self, diagnostic: &Diagnostic) -> bool {
+        if diagnostic.severity != Severity::Warning {
+            return false;
+        }
+
+        let lint = diagnostic.lint.as_deref().unwrap_or(LintLevels::WARNINGS);
+        let span = diagnostic.primary_span;
+
+        self.regions.iter().any(|(allowed, region)| {
+            let in_group = LintLevels::group(allowed).is_some_and(|lints| lints.contains(&lint));
+            (allowed == lint || allowed == LintLevels::WARNINGS || in_group)
+                && region.start <= span.start
+                && span.end <= region.end
+        })
+    }
+}
+
+struct SuppressionCollector<'ast> {
+    exprs: &'ast ast::ExprArena,
+    regions: Vec<(String, Span)>,
+    names: Vec<(String, Span)>,
+}
+
+impl SuppressionCollector<'_> {
+    fn allow(&mut self, attributes: &[ast::Attribute], span: Span) {
+        let allowed = attributes
+            .iter()
+            .filter(|attr| attr.name.text == "allow")
+            .flat_map(|attr| &attr.args);
+
+        for &arg in allowed {
+            let lint = match &self.exprs[arg] {
+                ast::Expr::Identifier(ident) => ident.text.clone(),
+                ast::Expr::String(literal) => literal.value.clone(),
+                _ => continue,
+            };
+
+            self.names.push((lint.clone(), self.exprs[arg].span()));
+            self.regions.push((lint, span));
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for SuppressionCollector<'ast> {
+    fn exprs(&self) -> &'ast ast::ExprArena {
+        self.exprs
+    }
+
+    fn visit_item(&mut self, item: &'ast ast::Item) {
+        self.allow(item.attributes(), item.span());
+        visit::walk_item(self, item);
+    }
+
+    fn visit_trait_method(&mut self, method: &'ast ast::TraitMethod) {
+        self.allow(&method.attributes, method.span);
+        visit::walk_trait_method(self, method);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast ast::Stmt) {
+        self.allow(stmt.attributes(), stmt.span());
+        visit::walk_stmt(self, stmt);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast,
+        diagnostics::Diagnostic,
+        error::SyntaxError,
+        lexer::Lexer,
+        lints::{
+            LiteralWarning, LiteralWarningKind, Suppressions, lint_f32_literal, lint_literals,
+        },
+        parser::Parser,
+        token::Span,
+    };
+
+    #[test]
+    fn literals() -> Result<(), SyntaxError> {
+        let input = "010 000 0 0b102 0xFG 0o7 8";
+        let expected = [
+            LiteralWarning {
+                kind: LiteralWarningKind::LeadingZeros,
+                span: Span { start: 0, end: 1 },
+                suggestion: Some(String::new()),
+            },
+            LiteralWarning {
+                kind: LiteralWarningKind::LeadingZeros,
+                span: Span { start: 4, end: 6 },
+                suggestion: Some(String::new()),
+            },
+            LiteralWarning {
+                kind: LiteralWarningKind::InvalidDigitsForBase { base: 2 },
+                span: Span { start: 14, end: 15 },
+                suggestion: None,
+            },
+            LiteralWarning {
+                kind: LiteralWarningKind::InvalidDigitsForBase { base: 16 },
+                span: Span { start: 19, end: 20 },
+                suggestion: None,
+            },
+        ];
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        assert_eq!(lint_literals(&tokens), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn f32_precision() {
+        let literal = |value: f64| ast::FloatLiteral {
+            value_bits: value.to_bits(),
+            span: Span { start: 0, end: 3 },
+        };
+
+        assert_eq!(lint_f32_literal(&literal(0.5)), None);
+        assert_eq!(lint_f32_literal(&literal(0.1)), None);
+        assert_eq!(
+            lint_f32_literal(&literal(16777217.0)).map(|warning| warning.kind),
+            Some(LiteralWarningKind::F32PrecisionLoss)
+        );
+        assert_eq!(
+            lint_f32_literal(&literal(16777217.0)).and_then(|warning| warning.suggestion),
+            Some("16777216.0".to_string())
+        );
+        assert_eq!(
+            lint_f32_literal(&literal(1.00000000001e20)).and_then(|warning| warning.suggestion),
+            Some("1.0e20".to_string())
+        );
+        assert_eq!(
+            lint_f32_literal(&literal(1.0e40)).and_then(|warning| warning.suggestion),
+            None
+        );
+    }
+
+    #[test]
+    fn suppressions() -> Result<(), SyntaxError> {
+        let input = "
+            @allow(leading_zeros) fn a() { let x = 010; }
+            fn b() { @allow(\"no-main\", warnings) let y = 010; let z = 010; }
+        ";
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let (module, errors) = Parser::new(&tokens).parse_module();
+        assert_eq!(errors, []);
+
+        let suppressions = Suppressions::collect(&module);
+        let unsuppressed: Vec<_> = lint_literals(&tokens)
+            .iter()
+            .map(LiteralWarning::diagnostic)
+            .filter(|diagnostic| !suppressions.suppresses(diagnostic))
+            .map(|diagnostic| diagnostic.primary_span.start)
+            .collect();
+
+        // only the leading zero of the last literal is reported.
+        assert_eq!(unsuppressed, [input.rfind("010; }").unwrap()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn suppression_groups() -> Result<(), SyntaxError> {
+        let input = "@allow(unused, bogus) fn a() {}";
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let (module, errors) = Parser::new(&tokens).parse_module();
+        assert_eq!(errors, []);
+
+        let suppressions = Suppressions::collect(&module);
+        let unused = Diagnostic::warning("w", Span { start: 22, end: 23 });
+        assert!(suppressions.suppresses(&unused.clone().with_lint("unused_functions")));
+        assert!(!suppressions.suppresses(&unused.with_lint("leading_zeros")));
+
+        let names: Vec<_> = suppressions.names().iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["unused", "bogus"]);
+        assert_eq!(suppressions.names()[1].1, Span { start: 15, end: 20 });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,319 @@
+use std::{collections::HashMap, fmt, sync::OnceLock};
+
+/// The English messages, keyed by diagnostic code, where the texts of helps and
+/// suggestions belonging to a code are keyed by the code and a suffix.
+const ENGLISH: &[(&str, &str)] = &[
+    ("E0001", "expected {expected}, found {found}"),
+    ("E0001.one-of", "expected one of {expected}, found {found}"),
+    ("E0002", "invalid number literal"),
+    ("E0003", "integer literal is too large"),
+    ("E0004", "invalid token"),
+    ("E0005", "unexpected end of input"),
+    ("E0006", "unterminated string literal"),
+    ("E0007", "unknown escape sequence"),
+    ("E0008", "{limit} limit exceeded"),
+    ("E0008.value", "{limit} limit of {value} exceeded"),
+    (
+        "E0008.help",
+        "raise it by setting `{key} = {suggested}` in the `[limits]` table of `elan.toml`",
+    ),
+    ("E0009", "expected `=>` after the match arm pattern"),
+    (
+        "E0009.help",
+        "separate the pattern from the arm's value with `=>`",
+    ),
+    ("E0010", "expected `;`"),
+    ("E0010.suggestion", "add `;` here"),
+    ("E0011", "unknown identifier `{name}`"),
+    ("E0012", "`{name}` is defined multiple times"),
+    ("E0012.label", "`{name}` is first defined here"),
+    ("E0013", "cannot find `{name}` in `{parent}`"),
+    ("E0014", "expected a type, found {kind} `{name}`"),
+    ("E0015", "mismatched types"),
+    ("E0015.label", "expected `{expected}`, found `{found}`"),
+    ("E0015.origin", "expected `{expected}` because of this"),
+    (
+        "E0015.cast",
+        "convert the value explicitly with `as {expected}`",
+    ),
+    (
+        "E0015.pattern",
+        "pattern `{pattern}` can't match values of type `{expected}`",
+    ),
+    (
+        "E0016",
+        "cannot apply `{op}` to operands of types `{lhs}` and `{rhs}`",
+    ),
+    (
+        "E0016.unary",
+        "cannot apply `{op}` to an operand of type `{operand}`",
+    ),
+    (
+        "E0017",
+        "expected a function, found a value of type `{found}`",
+    ),
+    ("E0018", "cannot cast `{from}` to `{to}`"),
+    ("E0019", "expected a value, found {kind} `{name}`"),
+    ("E0020", "can only assign to variables"),
+    ("E0021", "cannot iterate over a value of type `{found}`"),
+    ("E0022", "expected {expected} arguments, found {found}"),
+    ("E0023", "missing return in this branch"),
+    (
+        "E0023.label",
+        "this branch can end without returning a value of type `{expected}`",
+    ),
+    ("E0023.origin", "the function returns `{expected}`"),
+    ("E0024", "this `{op}` operation overflows `{ty}`"),
+    ("E0024.label", "the result doesn't fit in `{ty}`"),
+    ("E0025", "division by zero"),
+    (
+        "E0026",
+        "cycle detected when evaluating the constant `{name}`",
+    ),
+    (
+        "E0026.note",
+        "the initializers refer to each other: {cycle}",
+    ),
+    ("E0027", "this can't be evaluated at compile time"),
+    (
+        "E0028",
+        "array length must be a non-negative integer, found `{value}`",
+    ),
+    ("E0029", "{kind} `{name}` is private"),
+    ("E0029.label", "`{name}` is declared here without `pub`"),
+    (
+        "E0029.help",
+        "mark it `pub` to use it from the modules importing its own",
+    ),
+    ("E0030", "cannot find module `{module}`"),
+    ("E0030.note", "looked for {paths}"),
+    ("E0031", "importing `{module}` creates a cycle"),
+    ("E0031.label", "`{module}` is imported here"),
+    ("E0031.note", "the modules import each other as {cycle}"),
+    ("E0032", "no field `{field}` on type `{ty}`"),
+    ("E0032.label", "unknown field"),
+    ("E0032.note", "the available fields are {fields}"),
+    ("E0032.none", "`{ty}` has no fields"),
+    ("E0033", "literal out of range for `{ty}`"),
+    ("E0033.label", "`{value}` doesn't fit in `{ty}`"),
+    ("E0033.note", "`{ty}` holds values from {min} to {max}"),
+    ("E0033.help", "consider using `{ty}` instead"),
+    ("E0034", "cannot assign to immutable binding `{name}`"),
+    ("E0034.label", "cannot assign twice to an immutable binding"),
+    ("E0034.declared", "`{name}` is declared immutable here"),
+    ("E0034.help", "declare it with `var` to make it mutable"),
+    ("E0035", "non-exhaustive match: {missing} not covered"),
+    ("E0035.label", "{missing} not covered"),
+    (
+        "E0035.help",
+        "add an arm for each missing pattern, or a `_` arm matching the remaining values",
+    ),
+    ("E0036", "the program has no `main` function"),
+    ("E0036.kind", "`main` is a {kind}, not a function"),
+    ("E0036.help", "add a `fn main() {}` the program starts at"),
+    ("E0037", "`main` has an invalid signature"),
+    ("E0037.generic", "`main` can't have generic parameters"),
+    ("E0037.params", "`main` can't take parameters"),
+    (
+        "E0037.return",
+        "`main` must return `()` or `i32`, not `{found}`",
+    ),
+    ("E0038", "{construct} can't be compiled yet"),
+    ("E0039", "the program panicked"),
+    ("E0039.message", "the program panicked with {message}"),
+    ("E0039.overflow", "this `{op}` operation overflowed `{ty}`"),
+    ("E0039.division", "attempted to divide by zero"),
+    (
+        "E0039.stack",
+        "stack overflow: more than {limit} calls are active",
+    ),
+    (
+        "E0039.unreachable",
+        "the program reached code that should be unreachable",
+    ),
+    (
+        "W0001",
+        "leading zeros in a decimal literal; use `0o` for octal literals",
+    ),
+    (
+        "W0002",
+        "digits directly after the literal are invalid in base {base}",
+    ),
+    (
+        "W0003",
+        "float literal loses precision when stored as `f32`",
+    ),
+    (
+        "W0004",
+        "`{name}` shadows an earlier binding of this function",
+    ),
+    ("W0004.label", "`{name}` is first bound here"),
+    ("W0005", "`{name}` is never used"),
+    (
+        "W0005.suggestion",
+        "prefix it with an underscore if it is intentionally unused",
+    ),
+    ("W0006", "function `{name}` is never called"),
+    ("W0007", "function `{name}` cannot return without recursing"),
+    ("W0007.label", "the recursion continues here"),
+    ("W0007.note", "the functions call each other as {cycle}"),
+    ("W0008", "unreachable code"),
+    ("W0008.label", "any code following this never runs"),
+    ("W0008.while", "unreachable loop body"),
+    ("W0008.while.label", "the condition is always `false`"),
+    ("W0009", "unreachable match arm"),
+    (
+        "W0009.label",
+        "this pattern only matches values an earlier arm already matches",
+    ),
+    ("W0009.catch-all", "this arm matches every value"),
+    ("W0010", "unnecessary parentheses"),
+    ("W0010.suggestion", "remove these parentheses"),
+    ("W0011", "this condition is always `{value}`"),
+    (
+        "W0011.loop",
+        "use `loop` for a loop that only ends with `break`",
+    ),
+    ("W0012", "unknown lint `{name}`"),
+    ("call.declared", "`{name}` is declared here"),
+    ("instance.label", "in the instance `{instance}` used here"),
+    ("lint.remove", "remove these characters"),
+    ("lint.replace", "replace with `{replacement}`"),
+    ("lint.denied", "the `{lint}` lint is denied"),
+];
+
+/// The catalog installed with [install], used by [message].
+static INSTALLED: OnceLock<Catalog> = OnceLock::new();
+
+/// A set of translated diagnostic messages, where messages missing from it fall
+/// back to English.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Catalog {
+    messages: HashMap<String, String>,
+}
+
+/// A line of a catalog that isn't a `key = message` pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CatalogError {
+    /// The line number, starting at 1.
+    pub line: usize,
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected `key = message` on line {}", self.line)
+    }
+}
+
+impl Catalog {
+    /// Parses a catalog with one `key = message` pair per line, like `E0005 = ...`,
+    /// ignoring empty lines and comments starting with `#`.
+    pub fn parse(text: &str) -> Result<Self, CatalogError> {
+        let mut messages = HashMap::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, message)) = line.split_once('=') else {
+                return Err(CatalogError { line: index + 1 });
+            };
+            messages.insert(key.trim().to_string(), message.trim().to_string());
+        }
+
+        Ok(Self { messages })
+    }
+
+    /// Returns the message with the given key, replacing each `{name}` placeholder
+    /// with the argument of that name.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+        let template = self.messages.get(key).map(String::as_str).or_else(|| {
+            ENGLISH
+                .iter()
+                .find(|(english, _)| *english == key)
+                .map(|(_, message)| *message)
+        });
+
+        // a missing message still shouldn't hide which problem was found.
+        let Some(template) = template else {
+            return key.to_string();
+        };
+
+        let mut message = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(open) = rest.find('{') {
+            message.push_str(&rest[..open]);
+            rest = &rest[open..];
+
+            let arg = rest.find('}').and_then(|close| {
+                let name = &rest[1..close];
+                let (_, value) = args.iter().find(|(arg, _)| *arg == name)?;
+                Some((close, value))
+            });
+
+            match arg {
+                Some((close, value)) => {
+                    message.push_str(&value.to_string());
+                    rest = &rest[close + 1..];
+                }
+                None => {
+                    message.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+
+        message.push_str(rest);
+        message
+    }
+}
+
+/// Installs the [Catalog] used for all diagnostics, failing if one was installed before.
+pub fn install(catalog: Catalog) -> Result<(), Catalog> {
+    INSTALLED.set(catalog)
+}
+
+/// Returns the message with the given key from the installed [Catalog], see
+/// [Catalog::format].
+pub fn message(key: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    INSTALLED.get_or_init(Catalog::default).format(key, args)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::messages::{Catalog, CatalogError};
+
+    #[test]
+    fn catalog() {
+        let catalog = Catalog::parse(
+            "
+            # German
+            E0005 = unerwartetes Ende der Eingabe
+            W0002 = ungültige Ziffern zur Basis {base} {unknown}
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            catalog.format("E0005", &[]),
+            "unerwartetes Ende der Eingabe"
+        );
+        assert_eq!(
+            catalog.format("W0002", &[("base", &8)]),
+            "ungültige Ziffern zur Basis 8 {unknown}"
+        );
+        assert_eq!(
+            catalog.format("E0001", &[("expected", &"`;`"), ("found", &"`}`")]),
+            "expected `;`, found `}`"
+        );
+        assert_eq!(catalog.format("E9999", &[]), "E9999");
+
+        assert_eq!(
+            Catalog::parse("E0005 = ok\nmissing"),
+            Err(CatalogError { line: 2 })
+        );
+    }
+}
@@ -1,51 +1,150 @@
-use std::{iter::Peekable, num::IntErrorKind};
+use std::{mem, num::IntErrorKind};
 
 use crate::{
     ast,
     error::{SyntaxError, SyntaxErrorKind},
-    token::{Token, TokenKind},
+    experiments::Experiments,
+    limits::{Limit, Limits},
+    token::{Span, Token, TokenKind},
+    visit,
 };
 
 pub type ParserResult<T> = Result<T, SyntaxError>;
 
-pub struct Parser<'src, I>
-where
-    I: Iterator<Item = Token<'src>>,
-{
-    tokens: Peekable<I>,
+/// The [TokenKind]s an item can start with, where parsing resumes after an error in an item.
+const ITEM_SYNC: &[TokenKind] = &[
+    TokenKind::At,
+    TokenKind::Pub,
+    TokenKind::Import,
+    TokenKind::Fn,
+    TokenKind::Const,
+    TokenKind::Struct,
+    TokenKind::Enum,
+    TokenKind::Trait,
+];
+
+/// The [TokenKind]s where parsing resumes after an error in a statement.
+const STMT_SYNC: &[TokenKind] = &[
+    TokenKind::Semicolon,
+    TokenKind::RBrace,
+    TokenKind::Let,
+    TokenKind::Var,
+    TokenKind::Const,
+    TokenKind::Return,
+    TokenKind::For,
+    TokenKind::Break,
+    TokenKind::Continue,
+];
+
+/// A statement or the trailing expression of a block.
+enum StmtOrTail {
+    Stmt(Box<ast::Stmt>),
+    Tail(ast::ExprId),
+}
+
+/// The elements of a comma separated list like `(a, b,)`, see [Parser::parse_comma_separated].
+struct CommaSeparated<'src, T> {
+    items: Vec<T>,
+    /// Whether the last element was followed by a comma.
+    trailing_comma: bool,
+    /// The [Token] closing the list.
+    close_token: Token<'src>,
 }
 
-/// Rewrite this to use a Vec of tokens!!!!
+pub struct Parser<'t, 'src> {
+    tokens: &'t [Token<'src>],
+    /// The index of the next [Token] in `tokens`.
+    pos: usize,
+    limits: Limits,
+    experiments: Experiments,
+    depth: usize,
+    errors: Vec<SyntaxError>,
+    exprs: ast::ExprArena,
+    /// The depth of the tree below each [ast::Expr], indexed by [ast::ExprId].
+    expr_depths: Vec<usize>,
+    /// The number of [ast::NodeId]s handed out so far.
+    node_count: u32,
+}
 
-impl<'src, I> Parser<'src, I>
-where
-    I: Iterator<Item = Token<'src>>,
-{
+impl<'t, 'src> Parser<'t, 'src> {
     /// Constructs a new [Parser] from the given [Token]s.
-    pub fn new(tokens: I) -> Self {
-        let tokens = tokens.peekable();
+    pub fn new(tokens: &'t [Token<'src>]) -> Self {
+        Self::with_limits(tokens, Limits::default())
+    }
+
+    /// Constructs a new [Parser] from the given [Token]s that enforces the given [Limits].
+    pub fn with_limits(tokens: &'t [Token<'src>], limits: Limits) -> Self {
+        let pos = 0;
+        let experiments = Experiments::default();
+        let depth = 0;
+        let errors = Vec::new();
+        let exprs = ast::ExprArena::default();
+        let expr_depths = Vec::new();
+        let node_count = 0;
+
+        Self {
+            tokens,
+            pos,
+            limits,
+            experiments,
+            depth,
+            errors,
+            exprs,
+            expr_depths,
+            node_count,
+        }
+    }
+
+    /// Enables the given unstable language [Experiments].
+    pub fn set_experiments(&mut self, experiments: Experiments) {
+        self.experiments = experiments;
+    }
 
-        Self { tokens }
+    #[inline]
+    /// Returns the [Token] `n` positions after the next one without consuming anything.
+    fn peek_nth(&self, n: usize) -> Option<Token<'src>> {
+        self.tokens.get(self.pos + n).copied()
     }
 
     #[inline]
     /// Returns the next [Token] without consuming it.
-    fn peek(&mut self) -> Option<Token<'src>> {
-        self.tokens.peek().copied()
+    fn peek(&self) -> Option<Token<'src>> {
+        self.peek_nth(0)
     }
 
     #[inline]
     /// Consumes and returns the next [Token].
     fn next(&mut self) -> Option<Token<'src>> {
-        self.tokens.next()
+        let token = self.peek()?;
+        self.pos += 1;
+        Some(token)
+    }
+
+    #[inline]
+    /// Returns the [Span] of the last consumed [Token].
+    fn prev_span(&self) -> Span {
+        match self.pos.checked_sub(1) {
+            Some(index) => self.tokens[index].span,
+            None => Span { start: 0, end: 0 },
+        }
     }
 
     #[inline]
     /// Checks if the peek [Token] is one of the given [TokenKind]s.
-    fn is_peek(&mut self, kinds: &'static [TokenKind]) -> bool {
+    fn is_peek(&self, kinds: &'static [TokenKind]) -> bool {
         self.peek().is_some_and(|tok| kinds.contains(&tok.kind))
     }
 
+    #[inline]
+    /// Consumes and returns the next [Token] if it is one of the given [TokenKind]s.
+    fn try_next(&mut self, kinds: &'static [TokenKind]) -> Option<Token<'src>> {
+        if self.is_peek(kinds) {
+            self.next()
+        } else {
+            None
+        }
+    }
+
     /// Consumes and returns the next [Token] if it is of the given [TokenKind]s,
     /// otherwise returns a [SyntaxError].
     fn expect(&mut self, kinds: &'static [TokenKind]) -> ParserResult<Token<'src>> {
@@ -62,120 +161,3628 @@ where
         }
     }
 
-    pub fn parse_expr_atom(&mut self) -> ParserResult<ast::Expr> {
+    /// Consumes the `;` terminating a statement or item, pointing the [SyntaxError]
+    /// right after the previous [Token] where the `;` is missing. Any other [Token] is
+    /// left for recovery, so a `}` closing the block isn't lost.
+    fn expect_semicolon(&mut self) -> ParserResult<Token<'src>> {
+        const LITERALS: &[TokenKind] = &[TokenKind::Integer, TokenKind::Float, TokenKind::String];
+        const GLUED: &[TokenKind] = &[TokenKind::Identifier, TokenKind::Integer, TokenKind::Float];
+
+        let end = self.prev_span().end;
+        let after_literal = self
+            .pos
+            .checked_sub(1)
+            .is_some_and(|index| LITERALS.contains(&self.tokens[index].kind));
+
+        match self.peek() {
+            Some(tok) if tok.kind == TokenKind::Semicolon => {
+                self.next();
+                Ok(tok)
+            }
+            // a name or number right after a literal, like the `g` of `0x1g`, is more
+            // likely part of it than the start of another statement.
+            Some(tok) if after_literal && tok.span.start == end && GLUED.contains(&tok.kind) => {
+                Err(SyntaxError {
+                    kind: SyntaxErrorKind::UnexpectedToken {
+                        expected: &[TokenKind::Semicolon],
+                        got: tok.kind,
+                    },
+                    span: tok.span,
+                })
+            }
+            Some(_) => Err(SyntaxError {
+                kind: SyntaxErrorKind::MissingSemicolon,
+                span: Span { start: end, end },
+            }),
+            None => Err(SyntaxError::UNEXPECTED_EOI),
+        }
+    }
+
+    /// Runs the given production one nesting level deeper, returning a [SyntaxError]
+    /// if that exceeds the [Limit::ParseNestingDepth].
+    fn nested<T>(
+        &mut self,
+        span: Span,
+        production: impl FnOnce(&mut Self) -> ParserResult<T>,
+    ) -> ParserResult<T> {
+        if self.depth >= self.limits.parse_nesting_depth {
+            return Err(SyntaxError {
+                kind: SyntaxErrorKind::LimitExceeded(Limit::ParseNestingDepth),
+                span,
+            });
+        }
+
+        self.depth += 1;
+        let result = production(self);
+        self.depth -= 1;
+
+        result
+    }
+
+    /// Parses a comma separated list of `item`s, which may end in a trailing comma, up to
+    /// and including the `close` token. The opening token is expected to be consumed already,
+    /// its [Span] is reported if the items exceed the nesting limit.
+    fn parse_comma_separated<T>(
+        &mut self,
+        open_span: Span,
+        close: TokenKind,
+        item: fn(&mut Self) -> ParserResult<T>,
+    ) -> ParserResult<CommaSeparated<'src, T>> {
+        let separator_or_close = comma_or(close);
+
+        let mut items = Vec::new();
+        let mut trailing_comma = false;
+
+        loop {
+            if let Some(close_token) = self.try_next(&separator_or_close[1..]) {
+                return Ok(CommaSeparated {
+                    items,
+                    trailing_comma,
+                    close_token,
+                });
+            }
+
+            items.push(self.nested(open_span, item)?);
+
+            let separator_token = self.expect(separator_or_close)?;
+            trailing_comma = separator_token.kind == TokenKind::Comma;
+
+            if !trailing_comma {
+                return Ok(CommaSeparated {
+                    items,
+                    trailing_comma,
+                    close_token: separator_token,
+                });
+            }
+        }
+    }
+
+    /// Returns the arena holding the [ast::Expr]s parsed so far.
+    pub fn exprs(&self) -> &ast::ExprArena {
+        &self.exprs
+    }
+
+    #[inline]
+    /// Returns the next [ast::NodeId] for a node that is being constructed.
+    fn node_id(&mut self) -> ast::NodeId {
+        let id = ast::NodeId(self.node_count);
+        self.node_count += 1;
+        id
+    }
+
+    #[inline]
+    /// Moves the [ast::Expr] into the arena and returns its [ast::ExprId].
+    fn alloc(&mut self, expr: ast::Expr) -> ast::ExprId {
+        let id = self.exprs.alloc(expr);
+
+        let depth = visit::children(&self.exprs, id)
+            .iter()
+            .map(|child| self.expr_depths[child.index()])
+            .max();
+        self.expr_depths.push(depth.map_or(0, |depth| depth + 1));
+
+        id
+    }
+
+    /// Returns a [SyntaxError] at `span` if the tree below the [ast::Expr] is deeper
+    /// than the [Limit::ParseNestingDepth], like the one of a long chain of operators.
+    fn check_depth(&self, id: ast::ExprId, span: Span) -> ParserResult<()> {
+        if self.expr_depths[id.index()] > self.limits.parse_nesting_depth {
+            return Err(SyntaxError {
+                kind: SyntaxErrorKind::LimitExceeded(Limit::ParseNestingDepth),
+                span,
+            });
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    /// Returns the [Span] of the [ast::Expr] with the given [ast::ExprId].
+    fn expr_span(&self, id: ast::ExprId) -> Span {
+        self.exprs[id].span()
+    }
+
+    /// Returns the [SyntaxError]s the parser recovered from so far.
+    pub fn errors(&self) -> &[SyntaxError] {
+        &self.errors
+    }
+
+    /// Records the [SyntaxError] and skips [Token]s until one of the given
+    /// [TokenKind]s, where parsing can resume.
+    ///
+    /// Returns the [Span] from `start` to the last consumed [Token], which is
+    /// the source covered by the error node.
+    fn recover(&mut self, err: SyntaxError, start: Span, sync: &'static [TokenKind]) -> Span {
+        self.errors.push(err);
+
+        while self.peek().is_some() && !self.is_peek(sync) {
+            self.next();
+        }
+
+        if self.prev_span().end > start.start {
+            start.to(self.prev_span())
+        } else {
+            // nothing was consumed, so the error node is empty.
+            Span {
+                start: start.start,
+                end: start.start,
+            }
+        }
+    }
+
+    /// Parses [ast::Item]s until the end of input, returning the [ast::Module] along
+    /// with all [SyntaxError]s, whose source is covered by error nodes in the module.
+    pub fn parse_module(&mut self) -> (ast::Module, Vec<SyntaxError>) {
+        let mut items = Vec::new();
+
+        while let Some(peek_token) = self.peek() {
+            match self.parse_item() {
+                Ok(item) => items.push(item),
+                Err(err) => {
+                    let span = self.recover(err, peek_token.span, ITEM_SYNC);
+                    items.push(ast::Item::Error(span));
+                }
+            }
+        }
+
+        let exprs = mem::take(&mut self.exprs);
+        self.expr_depths.clear();
+
+        (ast::Module { items, exprs }, mem::take(&mut self.errors))
+    }
+
+    pub fn parse_item(&mut self) -> ParserResult<ast::Item> {
+        let docs = self.parse_docs();
+        let attributes = self.parse_attributes()?;
+        let pub_token = self.try_next(&[TokenKind::Pub]);
         let peek_token = self.peek().ok_or(SyntaxError::UNEXPECTED_EOI)?;
 
-        Ok(match peek_token.kind {
-            TokenKind::Identifier => ast::Expr::Identifier(self.parse_identifier()?),
-            TokenKind::Integer => ast::Expr::Integer(self.parse_integer_literal()?),
-            TokenKind::Float => ast::Expr::Float(self.parse_float_literal()?),
+        let mut item = match peek_token.kind {
+            TokenKind::Import if pub_token.is_none() => {
+                ast::Item::Import(self.parse_import_decl(docs, attributes)?)
+            }
+            TokenKind::Fn => ast::Item::Func(self.parse_func_decl(docs, attributes)?),
+            TokenKind::Const => ast::Item::Const(self.parse_const_decl(docs, attributes)?),
+            TokenKind::Struct => ast::Item::Struct(self.parse_struct_decl(docs, attributes)?),
+            TokenKind::Enum => ast::Item::Enum(self.parse_enum_decl(docs, attributes)?),
+            TokenKind::Trait => ast::Item::Trait(self.parse_trait_decl(docs, attributes)?),
 
             kind => {
+                // another attribute or `pub` would already have been parsed, and only
+                // declarations can be `pub`.
+                let expected = if pub_token.is_some() {
+                    &ITEM_SYNC[3..]
+                } else if attributes.is_empty() {
+                    ITEM_SYNC
+                } else {
+                    &ITEM_SYNC[1..]
+                };
+
                 return Err(SyntaxError {
                     kind: SyntaxErrorKind::UnexpectedToken {
-                        expected: &[TokenKind::Identifier, TokenKind::Integer, TokenKind::Float],
+                        expected,
                         got: kind,
                     },
                     span: peek_token.span,
                 });
             }
+        };
+
+        if let Some(pub_token) = pub_token {
+            make_public(&mut item, pub_token.span);
+        }
+
+        Ok(item)
+    }
+
+    /// Collects the text of consecutive `///` doc comments, stripping the slashes
+    /// and one following space from each line.
+    fn parse_docs(&mut self) -> Option<String> {
+        let mut lines = Vec::new();
+
+        while let Some(doc_token) = self.try_next(&[TokenKind::DocComment]) {
+            let line = &doc_token.text[3..];
+            lines.push(line.strip_prefix(' ').unwrap_or(line));
+        }
+
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// Parses the [ast::Attribute]s like `@inline` preceding an item.
+    fn parse_attributes(&mut self) -> ParserResult<Vec<ast::Attribute>> {
+        let mut attributes = Vec::new();
+
+        while let Some(at_token) = self.try_next(&[TokenKind::At]) {
+            let name = self.parse_identifier()?;
+            let mut span = at_token.span.to(name.span);
+            let mut args = Vec::new();
+
+            if let Some(lparen_token) = self.try_next(&[TokenKind::LParen]) {
+                let list = self.parse_comma_separated(
+                    lparen_token.span,
+                    TokenKind::RParen,
+                    Self::parse_expr,
+                )?;
+
+                args = list.items;
+                span = span.to(list.close_token.span);
+            }
+
+            attributes.push(ast::Attribute {
+                id: self.node_id(),
+                name,
+                args,
+                span,
+            });
+        }
+
+        Ok(attributes)
+    }
+
+    fn parse_import_decl(
+        &mut self,
+        docs: Option<String>,
+        attributes: Vec<ast::Attribute>,
+    ) -> ParserResult<ast::ImportDecl> {
+        let import_token = self.expect(&[TokenKind::Import])?;
+        let start = item_start(&attributes, import_token.span);
+
+        let mut path = vec![self.parse_identifier()?];
+
+        while self.try_next(&[TokenKind::ColonColon]).is_some() {
+            path.push(self.parse_identifier()?);
+        }
+
+        let alias = match self.try_next(&[TokenKind::As]) {
+            Some(_) => Some(self.parse_identifier()?),
+            None => None,
+        };
+
+        let semicolon_token = self.expect_semicolon()?;
+        let span = start.to(semicolon_token.span);
+
+        Ok(ast::ImportDecl {
+            id: self.node_id(),
+            docs,
+            attributes,
+            path,
+            alias,
+            span,
         })
     }
 
-    fn parse_identifier(&mut self) -> ParserResult<ast::Identifier> {
-        let ident_token = self.expect(&[TokenKind::Identifier])?;
+    fn parse_func_decl(
+        &mut self,
+        docs: Option<String>,
+        attributes: Vec<ast::Attribute>,
+    ) -> ParserResult<ast::FuncDecl> {
+        let fn_token = self.expect(&[TokenKind::Fn])?;
+        let start = item_start(&attributes, fn_token.span);
+        let name = self.parse_identifier()?;
+        let generics = self.parse_generic_params()?;
 
-        let text = ident_token.text.to_string();
-        let span = ident_token.span;
+        let lparen_token = self.expect(&[TokenKind::LParen])?;
+        let params = self
+            .parse_comma_separated(lparen_token.span, TokenKind::RParen, Self::parse_param)?
+            .items;
 
-        Ok(ast::Identifier { text, span })
+        let return_type = match self.try_next(&[TokenKind::Arrow]) {
+            Some(_) => Some(self.parse_type()?),
+            None => None,
+        };
+
+        if !self.is_peek(&[TokenKind::LBrace]) {
+            let expected: &'static [TokenKind] = match return_type {
+                Some(_) => &[TokenKind::LBrace],
+                None => &[TokenKind::Arrow, TokenKind::LBrace],
+            };
+
+            return Err(match self.peek() {
+                Some(tok) => SyntaxError {
+                    kind: SyntaxErrorKind::UnexpectedToken {
+                        expected,
+                        got: tok.kind,
+                    },
+                    span: tok.span,
+                },
+                None => SyntaxError::UNEXPECTED_EOI,
+            });
+        }
+
+        let body = self.parse_block()?;
+        let span = start.to(body.span);
+
+        Ok(ast::FuncDecl {
+            id: self.node_id(),
+            docs,
+            attributes,
+            visibility: ast::Visibility::Private,
+            name,
+            generics,
+            params,
+            return_type,
+            body,
+            span,
+        })
     }
 
-    fn parse_integer_literal(&mut self) -> ParserResult<ast::IntegerLiteral> {
-        let integer_token = self.expect(&[TokenKind::Integer])?;
+    fn parse_param(&mut self) -> ParserResult<ast::Param> {
+        let name = self.parse_identifier()?;
 
-        let result = match integer_token.text {
-            text if text.starts_with("0x") => u64::from_str_radix(&text[2..], 16),
-            text if text.starts_with("0o") => u64::from_str_radix(&text[2..], 8),
-            text if text.starts_with("0b") => u64::from_str_radix(&text[2..], 2),
-            text => text.parse::<u64>(),
+        self.expect(&[TokenKind::Colon])?;
+
+        let ty = self.parse_type()?;
+        let span = name.span.to(ty.span());
+
+        Ok(ast::Param {
+            id: self.node_id(),
+            name,
+            ty,
+            span,
+        })
+    }
+
+    /// Parses a [ast::ConstDecl], whose value is checked to be const-evaluable by a later pass.
+    fn parse_const_decl(
+        &mut self,
+        docs: Option<String>,
+        attributes: Vec<ast::Attribute>,
+    ) -> ParserResult<ast::ConstDecl> {
+        let const_token = self.expect(&[TokenKind::Const])?;
+        let start = item_start(&attributes, const_token.span);
+        let name = self.parse_identifier()?;
+
+        self.expect(&[TokenKind::Colon])?;
+        let ty = self.parse_type()?;
+
+        self.expect(&[TokenKind::Assign])?;
+        let value = self.parse_expr_or_error()?;
+
+        let semicolon_token = self.expect_semicolon()?;
+        let span = start.to(semicolon_token.span);
+
+        Ok(ast::ConstDecl {
+            id: self.node_id(),
+            docs,
+            attributes,
+            visibility: ast::Visibility::Private,
+            name,
+            ty,
+            value,
+            span,
+        })
+    }
+
+    /// Parses the generic type parameters like `<T, U>` following the name of an item,
+    /// where a `<` can't be a comparison, returning none if there is no `<`.
+    fn parse_generic_params(&mut self) -> ParserResult<Vec<ast::Identifier>> {
+        let Some(less_token) = self.try_next(&[TokenKind::LessThan]) else {
+            return Ok(Vec::new());
         };
 
-        let value = match result {
-            Ok(value) => value,
-            Err(err) => match err.kind() {
-                IntErrorKind::NegOverflow | IntErrorKind::PosOverflow => {
-                    return Err(SyntaxError {
-                        kind: SyntaxErrorKind::NumberOverflow,
-                        span: integer_token.span,
-                    });
-                }
-                _ => unreachable!(),
-            },
+        let list = self.parse_comma_separated(
+            less_token.span,
+            TokenKind::GreaterThan,
+            Self::parse_identifier,
+        )?;
+
+        Ok(list.items)
+    }
+
+    fn parse_struct_decl(
+        &mut self,
+        docs: Option<String>,
+        attributes: Vec<ast::Attribute>,
+    ) -> ParserResult<ast::StructDecl> {
+        let struct_token = self.expect(&[TokenKind::Struct])?;
+        let start = item_start(&attributes, struct_token.span);
+        let name = self.parse_identifier()?;
+        let generics = self.parse_generic_params()?;
+
+        let lbrace_token = self.expect(&[TokenKind::LBrace])?;
+        let list =
+            self.parse_comma_separated(lbrace_token.span, TokenKind::RBrace, Self::parse_field)?;
+
+        let fields = list.items;
+        let span = start.to(list.close_token.span);
+
+        Ok(ast::StructDecl {
+            id: self.node_id(),
+            docs,
+            attributes,
+            visibility: ast::Visibility::Private,
+            name,
+            generics,
+            fields,
+            span,
+        })
+    }
+
+    fn parse_enum_decl(
+        &mut self,
+        docs: Option<String>,
+        attributes: Vec<ast::Attribute>,
+    ) -> ParserResult<ast::EnumDecl> {
+        let enum_token = self.expect(&[TokenKind::Enum])?;
+        let start = item_start(&attributes, enum_token.span);
+        let name = self.parse_identifier()?;
+
+        let lbrace_token = self.expect(&[TokenKind::LBrace])?;
+        let list =
+            self.parse_comma_separated(lbrace_token.span, TokenKind::RBrace, Self::parse_variant)?;
+
+        let variants = list.items;
+        let span = start.to(list.close_token.span);
+
+        Ok(ast::EnumDecl {
+            id: self.node_id(),
+            docs,
+            attributes,
+            visibility: ast::Visibility::Private,
+            name,
+            variants,
+            span,
+        })
+    }
+
+    fn parse_variant(&mut self) -> ParserResult<ast::Variant> {
+        let docs = self.parse_docs();
+        let name = self.parse_identifier()?;
+
+        let (payload, span) = if let Some(lparen_token) = self.try_next(&[TokenKind::LParen]) {
+            let list =
+                self.parse_comma_separated(lparen_token.span, TokenKind::RParen, Self::parse_type)?;
+            let span = name.span.to(list.close_token.span);
+
+            (ast::VariantPayload::Tuple(list.items), span)
+        } else if let Some(lbrace_token) = self.try_next(&[TokenKind::LBrace]) {
+            let list = self.parse_comma_separated(
+                lbrace_token.span,
+                TokenKind::RBrace,
+                Self::parse_field,
+            )?;
+            let span = name.span.to(list.close_token.span);
+
+            (ast::VariantPayload::Struct(list.items), span)
+        } else {
+            (ast::VariantPayload::Unit, name.span)
         };
 
-        let span = integer_token.span;
+        Ok(ast::Variant {
+            id: self.node_id(),
+            docs,
+            name,
+            payload,
+            span,
+        })
+    }
 
-        Ok(ast::IntegerLiteral { value, span })
+    fn parse_field(&mut self) -> ParserResult<ast::Field> {
+        let docs = self.parse_docs();
+        let name = self.parse_identifier()?;
+
+        self.expect(&[TokenKind::Colon])?;
+
+        let ty = self.parse_type()?;
+        let span = name.span.to(ty.span());
+
+        Ok(ast::Field {
+            id: self.node_id(),
+            docs,
+            name,
+            ty,
+            span,
+        })
     }
 
-    fn parse_float_literal(&mut self) -> ParserResult<ast::FloatLiteral> {
-        let float_token = self.expect(&[TokenKind::Float])?;
+    fn parse_trait_decl(
+        &mut self,
+        docs: Option<String>,
+        attributes: Vec<ast::Attribute>,
+    ) -> ParserResult<ast::TraitDecl> {
+        let trait_token = self.expect(&[TokenKind::Trait])?;
+        let start = item_start(&attributes, trait_token.span);
+        let name = self.parse_identifier()?;
+        let generics = self.parse_generic_params()?;
 
-        let value = match float_token.text.parse::<f64>() {
-            Ok(value) => value,
-            Err(_) => {
-                return Err(SyntaxError {
-                    kind: SyntaxErrorKind::InvalidNumber,
-                    span: float_token.span,
+        self.expect(&[TokenKind::LBrace])?;
+
+        let mut methods = Vec::new();
+        while !self.is_peek(&[TokenKind::RBrace]) {
+            methods.push(self.parse_trait_method()?);
+        }
+
+        let rbrace_token = self.expect(&[TokenKind::RBrace])?;
+        let span = start.to(rbrace_token.span);
+
+        Ok(ast::TraitDecl {
+            id: self.node_id(),
+            docs,
+            attributes,
+            visibility: ast::Visibility::Private,
+            name,
+            generics,
+            methods,
+            span,
+        })
+    }
+
+    /// Parses a method signature like `fn print(self);` or a method with a default body.
+    fn parse_trait_method(&mut self) -> ParserResult<ast::TraitMethod> {
+        let docs = self.parse_docs();
+        let attributes = self.parse_attributes()?;
+        let fn_token = self.expect(&[TokenKind::Fn])?;
+        let start = item_start(&attributes, fn_token.span);
+        let name = self.parse_identifier()?;
+        let generics = self.parse_generic_params()?;
+
+        let lparen_token = self.expect(&[TokenKind::LParen])?;
+
+        // `self` is an ordinary identifier, so a parameter named `self: T` isn't a receiver.
+        let receiver = match (self.peek(), self.peek_nth(1)) {
+            (Some(self_token), Some(next_token))
+                if self_token.kind == TokenKind::Identifier
+                    && self_token.text == "self"
+                    && next_token.kind != TokenKind::Colon =>
+            {
+                self.next();
+                if next_token.kind == TokenKind::Comma {
+                    self.next();
+                }
+                Some(self_token.span)
+            }
+            _ => None,
+        };
+
+        let params = self
+            .parse_comma_separated(lparen_token.span, TokenKind::RParen, Self::parse_param)?
+            .items;
+
+        let return_type = match self.try_next(&[TokenKind::Arrow]) {
+            Some(_) => Some(self.parse_type()?),
+            None => None,
+        };
+
+        let (body, span) = match self.try_next(&[TokenKind::Semicolon]) {
+            Some(semicolon_token) => (None, start.to(semicolon_token.span)),
+            None if self.is_peek(&[TokenKind::LBrace]) => {
+                let body = self.parse_block()?;
+                let span = start.to(body.span);
+                (Some(body), span)
+            }
+            None => {
+                let expected: &'static [TokenKind] = match return_type {
+                    Some(_) => &[TokenKind::Semicolon, TokenKind::LBrace],
+                    None => &[TokenKind::Arrow, TokenKind::Semicolon, TokenKind::LBrace],
+                };
+
+                return Err(match self.peek() {
+                    Some(tok) => SyntaxError {
+                        kind: SyntaxErrorKind::UnexpectedToken {
+                            expected,
+                            got: tok.kind,
+                        },
+                        span: tok.span,
+                    },
+                    None => SyntaxError::UNEXPECTED_EOI,
                 });
             }
         };
 
-        let span = float_token.span;
-        let value_bits = value.to_bits();
+        Ok(ast::TraitMethod {
+            id: self.node_id(),
+            docs,
+            attributes,
+            name,
+            generics,
+            receiver,
+            params,
+            return_type,
+            body,
+            span,
+        })
+    }
 
-        Ok(ast::FloatLiteral { value_bits, span })
+    pub fn parse_type(&mut self) -> ParserResult<ast::TypeExpr> {
+        let mut ty = self.parse_type_atom()?;
+
+        // the `?` binds tighter than a `&` or `*` prefix, so `&i64?` refers to an `i64?`.
+        while let Some(question_token) = self.try_next(&[TokenKind::Question]) {
+            let span = ty.span().to(question_token.span);
+
+            ty = ast::TypeExpr::Optional(ast::OptionalType {
+                id: self.node_id(),
+                inner: Box::new(ty),
+                span,
+            });
+        }
+
+        Ok(ty)
     }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::{ast, error::SyntaxError, lexer::Lexer, parser::Parser, token::Span};
+    fn parse_type_atom(&mut self) -> ParserResult<ast::TypeExpr> {
+        let peek_token = self.peek().ok_or(SyntaxError::UNEXPECTED_EOI)?;
 
-    #[test]
-    fn expr_atom() -> Result<(), SyntaxError> {
-        let test_case = [
-            (
-                "0x12",
-                Ok(ast::Expr::Integer(ast::IntegerLiteral {
-                    value: 0x12,
-                    span: Span { start: 0, end: 4 },
-                })),
-            ),
-            (
-                "12.3e-5",
-                Ok(ast::Expr::Float(ast::FloatLiteral {
-                    value_bits: (12.3e-5f64).to_bits(),
-                    span: Span { start: 0, end: 7 },
-                })),
-            ),
-            (
-                "cents",
-                Ok(ast::Expr::Identifier(ast::Identifier {
-                    text: "cents".to_string(),
-                    span: Span { start: 0, end: 5 },
-                })),
-            ),
-        ];
+        Ok(match peek_token.kind {
+            TokenKind::Identifier => ast::TypeExpr::Named(self.parse_named_type()?),
 
-        for (input, output) in test_case {
-            let tokens = Lexer::new(input).collect_tokens()?;
-            let mut parser = Parser::new(tokens.into_iter());
+            TokenKind::Ampersand | TokenKind::Asterisk => {
+                let prefix_token = self.next().unwrap();
+                let inner = self.nested(prefix_token.span, Self::parse_type)?;
+                let span = prefix_token.span.to(inner.span());
+                let inner = Box::new(inner);
 
-            assert_eq!(parser.parse_expr_atom(), output);
-        }
+                match prefix_token.kind {
+                    TokenKind::Ampersand => ast::TypeExpr::Ref(ast::RefType {
+                        id: self.node_id(),
+                        inner,
+                        span,
+                    }),
+                    _ => ast::TypeExpr::Ptr(ast::PtrType {
+                        id: self.node_id(),
+                        inner,
+                        span,
+                    }),
+                }
+            }
 
-        Ok(())
+            TokenKind::LBracket => {
+                let lbracket_token = self.expect(&[TokenKind::LBracket])?;
+                let element = self.nested(lbracket_token.span, Self::parse_type)?;
+
+                self.expect(&[TokenKind::Semicolon])?;
+
+                let len = self.parse_expr()?;
+                let rbracket_token = self.expect(&[TokenKind::RBracket])?;
+                let span = lbracket_token.span.to(rbracket_token.span);
+
+                ast::TypeExpr::Array(ast::ArrayType {
+                    id: self.node_id(),
+                    element: Box::new(element),
+                    len,
+                    span,
+                })
+            }
+
+            TokenKind::LParen => self.parse_tuple_type()?,
+
+            TokenKind::Fn => {
+                let fn_token = self.expect(&[TokenKind::Fn])?;
+                let lparen_token = self.expect(&[TokenKind::LParen])?;
+                let list = self.parse_comma_separated(
+                    lparen_token.span,
+                    TokenKind::RParen,
+                    Self::parse_type,
+                )?;
+
+                let params = list.items;
+                let rparen_token = list.close_token;
+
+                let return_type = match self.try_next(&[TokenKind::Arrow]) {
+                    Some(arrow_token) => Some(self.nested(arrow_token.span, Self::parse_type)?),
+                    None => None,
+                };
+
+                let span = match &return_type {
+                    Some(return_type) => fn_token.span.to(return_type.span()),
+                    None => fn_token.span.to(rparen_token.span),
+                };
+
+                ast::TypeExpr::Func(ast::FuncType {
+                    id: self.node_id(),
+                    params,
+                    return_type: return_type.map(Box::new),
+                    span,
+                })
+            }
+
+            kind => {
+                return Err(SyntaxError {
+                    kind: SyntaxErrorKind::UnexpectedToken {
+                        expected: &[
+                            TokenKind::Identifier,
+                            TokenKind::Ampersand,
+                            TokenKind::Asterisk,
+                            TokenKind::LBracket,
+                            TokenKind::LParen,
+                            TokenKind::Fn,
+                        ],
+                        got: kind,
+                    },
+                    span: peek_token.span,
+                });
+            }
+        })
+    }
+
+    fn parse_named_type(&mut self) -> ParserResult<ast::NamedType> {
+        let name = self.parse_identifier()?;
+        let mut span = name.span;
+
+        let mut args = Vec::new();
+
+        if let Some(less_token) = self.try_next(&[TokenKind::LessThan]) {
+            let list = self.parse_comma_separated(
+                less_token.span,
+                TokenKind::GreaterThan,
+                Self::parse_type,
+            )?;
+
+            args = list.items;
+            span = span.to(list.close_token.span);
+        }
+
+        Ok(ast::NamedType {
+            id: self.node_id(),
+            name,
+            args,
+            span,
+        })
+    }
+
+    /// Parses a tuple type like `(i64, f64)`, where a single type without a
+    /// trailing comma like `(i64)` is only grouped and not a tuple.
+    fn parse_tuple_type(&mut self) -> ParserResult<ast::TypeExpr> {
+        let lparen_token = self.expect(&[TokenKind::LParen])?;
+        let mut list =
+            self.parse_comma_separated(lparen_token.span, TokenKind::RParen, Self::parse_type)?;
+
+        if list.items.len() == 1 && !list.trailing_comma {
+            return Ok(list.items.pop().unwrap());
+        }
+
+        let elements = list.items;
+        let span = lparen_token.span.to(list.close_token.span);
+
+        Ok(ast::TypeExpr::Tuple(ast::TupleType {
+            id: self.node_id(),
+            elements,
+            span,
+        }))
+    }
+
+    pub fn parse_block(&mut self) -> ParserResult<ast::Block> {
+        let lbrace_token = self.expect(&[TokenKind::LBrace])?;
+
+        let (stmts, tail) = self.nested(lbrace_token.span, Self::parse_stmts)?;
+        let rbrace_token = self.expect(&[TokenKind::RBrace])?;
+
+        let span = lbrace_token.span.to(rbrace_token.span);
+
+        Ok(ast::Block {
+            id: self.node_id(),
+            stmts,
+            tail,
+            span,
+        })
+    }
+
+    /// Parses statements until a closing `}` or the end of input, returning them
+    /// along with the trailing expression that is not terminated by a `;`.
+    ///
+    /// Statements with errors are skipped and their [SyntaxError]s recorded.
+    pub fn parse_stmts(&mut self) -> ParserResult<(Vec<ast::Stmt>, Option<ast::ExprId>)> {
+        let mut stmts = Vec::new();
+
+        while let Some(peek_token) = self.peek().filter(|tok| tok.kind != TokenKind::RBrace) {
+            match self.parse_stmt() {
+                Ok(StmtOrTail::Stmt(stmt)) => stmts.push(*stmt),
+                Ok(StmtOrTail::Tail(expr)) => return Ok((stmts, Some(expr))),
+                Err(err) => {
+                    self.recover(err, peek_token.span, STMT_SYNC);
+                    self.try_next(&[TokenKind::Semicolon]);
+
+                    let span = peek_token.span.to(self.prev_span());
+                    stmts.push(ast::Stmt::Error(span));
+                }
+            }
+        }
+
+        Ok((stmts, None))
+    }
+
+    fn parse_stmt(&mut self) -> ParserResult<StmtOrTail> {
+        // only a `const` keeps its docs, other statements aren't documented.
+        let docs = self.parse_docs();
+        let attributes = self.parse_attributes()?;
+        let peek_token = self.peek().ok_or(SyntaxError::UNEXPECTED_EOI)?;
+
+        let stmt = match peek_token.kind {
+            TokenKind::Let | TokenKind::Var => ast::Stmt::Let(self.parse_let_stmt(attributes)?),
+            TokenKind::Const => ast::Stmt::Const(self.parse_const_decl(docs, attributes)?),
+            TokenKind::Return => ast::Stmt::Return(self.parse_return_stmt(attributes)?),
+            TokenKind::For => ast::Stmt::For(self.parse_for_stmt(attributes)?),
+            TokenKind::Break => ast::Stmt::Break(self.parse_break_stmt(attributes)?),
+            TokenKind::Continue => ast::Stmt::Continue(self.parse_continue_stmt(attributes)?),
+            _ => {
+                let expr = self.parse_expr()?;
+
+                // attributes belong to statements, so they can't precede the tail.
+                let at_end = self.peek().is_none() || self.is_peek(&[TokenKind::RBrace]);
+                if at_end && attributes.is_empty() {
+                    return Ok(StmtOrTail::Tail(expr));
+                }
+
+                // block like expressions don't need to be terminated by a `;`.
+                let block_like = self.exprs[expr].is_block_like();
+                let start = item_start(&attributes, self.expr_span(expr));
+                let span = if block_like && !self.is_peek(&[TokenKind::Semicolon]) {
+                    start.to(self.expr_span(expr))
+                } else {
+                    let semicolon_token = self.expect_semicolon()?;
+                    start.to(semicolon_token.span)
+                };
+
+                ast::Stmt::Expr(ast::ExprStmt {
+                    id: self.node_id(),
+                    attributes,
+                    expr,
+                    span,
+                })
+            }
+        };
+
+        Ok(StmtOrTail::Stmt(Box::new(stmt)))
+    }
+
+    fn parse_let_stmt(&mut self, attributes: Vec<ast::Attribute>) -> ParserResult<ast::LetStmt> {
+        let let_token = self.expect(&[TokenKind::Let, TokenKind::Var])?;
+        let start = item_start(&attributes, let_token.span);
+
+        let mutable =
+            let_token.kind == TokenKind::Var || self.try_next(&[TokenKind::Mut]).is_some();
+        let mutability = match mutable {
+            true => ast::Mutability::Mutable,
+            false => ast::Mutability::Immutable,
+        };
+        let pattern = self.parse_pattern()?;
+
+        let ty = match self.try_next(&[TokenKind::Colon]) {
+            Some(_) => Some(self.parse_type()?),
+            None => None,
+        };
+
+        let value = match self.try_next(&[TokenKind::Assign]) {
+            Some(_) => Some(self.parse_expr_or_error()?),
+            None => None,
+        };
+
+        let semicolon_token = self.expect_semicolon()?;
+        let span = start.to(semicolon_token.span);
+
+        Ok(ast::LetStmt {
+            id: self.node_id(),
+            attributes,
+            mutability,
+            pattern,
+            ty,
+            value,
+            span,
+        })
+    }
+
+    fn parse_return_stmt(
+        &mut self,
+        attributes: Vec<ast::Attribute>,
+    ) -> ParserResult<ast::ReturnStmt> {
+        let return_token = self.expect(&[TokenKind::Return])?;
+        let start = item_start(&attributes, return_token.span);
+
+        let value = match self.is_peek(&[TokenKind::Semicolon]) {
+            true => None,
+            false => Some(self.parse_expr()?),
+        };
+
+        let semicolon_token = self.expect_semicolon()?;
+        let span = start.to(semicolon_token.span);
+
+        Ok(ast::ReturnStmt {
+            id: self.node_id(),
+            attributes,
+            value,
+            span,
+        })
+    }
+
+    fn parse_break_stmt(
+        &mut self,
+        attributes: Vec<ast::Attribute>,
+    ) -> ParserResult<ast::BreakStmt> {
+        let break_token = self.expect(&[TokenKind::Break])?;
+        let start = item_start(&attributes, break_token.span);
+
+        let value = match self.is_peek(&[TokenKind::Semicolon]) {
+            true => None,
+            false => Some(self.parse_expr()?),
+        };
+
+        let semicolon_token = self.expect_semicolon()?;
+        let span = start.to(semicolon_token.span);
+
+        Ok(ast::BreakStmt {
+            id: self.node_id(),
+            attributes,
+            value,
+            span,
+        })
+    }
+
+    fn parse_continue_stmt(
+        &mut self,
+        attributes: Vec<ast::Attribute>,
+    ) -> ParserResult<ast::ContinueStmt> {
+        let continue_token = self.expect(&[TokenKind::Continue])?;
+        let start = item_start(&attributes, continue_token.span);
+        let semicolon_token = self.expect_semicolon()?;
+
+        let span = start.to(semicolon_token.span);
+
+        Ok(ast::ContinueStmt {
+            id: self.node_id(),
+            attributes,
+            span,
+        })
+    }
+
+    fn parse_for_stmt(&mut self, attributes: Vec<ast::Attribute>) -> ParserResult<ast::ForStmt> {
+        let for_token = self.expect(&[TokenKind::For])?;
+        let start = item_start(&attributes, for_token.span);
+        let binding = self.parse_identifier()?;
+
+        self.expect(&[TokenKind::In])?;
+
+        let iterable = self.parse_expr()?;
+        let body = self.parse_block()?;
+        let span = start.to(body.span);
+
+        Ok(ast::ForStmt {
+            id: self.node_id(),
+            attributes,
+            binding,
+            iterable,
+            body,
+            span,
+        })
+    }
+
+    pub fn parse_expr(&mut self) -> ParserResult<ast::ExprId> {
+        self.parse_expr_binary(0)
+    }
+
+    /// Parses an [ast::Expr], recovering from an error in it with an [ast::Expr::Error]
+    /// that reaches up to the end of the statement.
+    fn parse_expr_or_error(&mut self) -> ParserResult<ast::ExprId> {
+        let start_token = self.peek().ok_or(SyntaxError::UNEXPECTED_EOI)?;
+
+        match self.parse_expr() {
+            Ok(expr) => Ok(expr),
+            Err(err) => {
+                let span = self.recover(err, start_token.span, STMT_SYNC);
+                Ok(self.alloc(ast::Expr::Error(span)))
+            }
+        }
+    }
+
+    /// Parses a chain of binary operators binding tighter than `min_precedence`,
+    /// as given by the [precedence] table.
+    fn parse_expr_binary(&mut self, min_precedence: u8) -> ParserResult<ast::ExprId> {
+        let mut lhs = self.parse_expr_cast()?;
+        // the precedence of the last non-associative operator, which mustn't repeat.
+        let mut non_assoc = None;
+
+        while let Some(op_token) = self.peek() {
+            let Some((precedence, assoc)) = precedence(op_token.kind) else {
+                break;
+            };
+
+            if precedence <= min_precedence || non_assoc == Some(precedence) {
+                break;
+            }
+
+            self.next();
+
+            let rhs = match assoc {
+                Assoc::Right => self.nested(op_token.span, |parser| {
+                    parser.parse_expr_binary(precedence - 1)
+                })?,
+                Assoc::Left | Assoc::None => self.parse_expr_binary(precedence)?,
+            };
+
+            if assoc == Assoc::None {
+                non_assoc = Some(precedence);
+            }
+
+            let span = self.expr_span(lhs).to(self.expr_span(rhs));
+
+            lhs = self.alloc(match op_token.kind {
+                TokenKind::Assign => ast::Expr::Assign(ast::AssignExpr {
+                    target: lhs,
+                    value: rhs,
+                    span,
+                }),
+                TokenKind::DotDot | TokenKind::DotDotEq => ast::Expr::Range(ast::RangeExpr {
+                    kind: match op_token.kind {
+                        TokenKind::DotDot => ast::RangeKind::HalfOpen,
+                        _ => ast::RangeKind::Inclusive,
+                    },
+                    start: lhs,
+                    end: rhs,
+                    span,
+                }),
+                kind => ast::Expr::Binary(ast::BinaryExpr {
+                    op: binary_op(kind).unwrap(),
+                    lhs,
+                    rhs,
+                    span,
+                }),
+            });
+            self.check_depth(lhs, op_token.span)?;
+        }
+
+        Ok(lhs)
+    }
+
+    /// Parses a chain of casts like `x as i64 as f64`.
+    ///
+    /// Like in type annotations a `<` after the type starts generic arguments,
+    /// so `x as i64 < y` has to be written as `(x as i64) < y`.
+    fn parse_expr_cast(&mut self) -> ParserResult<ast::ExprId> {
+        let mut expr = self.parse_expr_unary()?;
+
+        while let Some(as_token) = self.try_next(&[TokenKind::As]) {
+            let ty = self.parse_type()?;
+            let span = self.expr_span(expr).to(ty.span());
+
+            expr = self.alloc(ast::Expr::Cast(Box::new(ast::CastExpr { expr, ty, span })));
+            self.check_depth(expr, as_token.span)?;
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_expr_unary(&mut self) -> ParserResult<ast::ExprId> {
+        let Some(op_token) = self.try_next(&[TokenKind::Minus, TokenKind::Bang]) else {
+            return self.parse_expr_postfix();
+        };
+
+        let op = match op_token.kind {
+            TokenKind::Minus => ast::UnaryOp::Neg,
+            TokenKind::Bang => ast::UnaryOp::Not,
+            _ => unreachable!(),
+        };
+
+        let is_literal_next = self.is_peek(&[TokenKind::Integer, TokenKind::Float]);
+        let operand = self.nested(op_token.span, Self::parse_expr_unary)?;
+
+        // fold a minus applied directly to a literal, so `-128` is in the range of an `i8`.
+        if op == ast::UnaryOp::Neg && is_literal_next {
+            match &mut self.exprs[operand] {
+                ast::Expr::Integer(integer) => {
+                    integer.negate(op_token.span);
+                    return Ok(operand);
+                }
+                ast::Expr::Float(float) => {
+                    float.negate(op_token.span);
+                    return Ok(operand);
+                }
+                _ => {}
+            }
+        }
+
+        let span = op_token.span.to(self.expr_span(operand));
+
+        Ok(self.alloc(ast::Expr::Unary(ast::UnaryExpr { op, operand, span })))
+    }
+
+    fn parse_expr_postfix(&mut self) -> ParserResult<ast::ExprId> {
+        let mut expr = self.parse_expr_atom()?;
+
+        while self.is_peek(&[TokenKind::LParen, TokenKind::Dot]) {
+            if self.try_next(&[TokenKind::Dot]).is_some() {
+                let field = self.parse_identifier()?;
+                let span = self.expr_span(expr).to(field.span);
+
+                expr = self.alloc(ast::Expr::Field(Box::new(ast::FieldExpr {
+                    base: expr,
+                    field,
+                    span,
+                })));
+            } else {
+                let call = self.parse_call_expr(expr)?;
+                expr = self.alloc(ast::Expr::Call(call));
+            }
+            self.check_depth(expr, self.prev_span())?;
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_call_expr(&mut self, callee: ast::ExprId) -> ParserResult<ast::CallExpr> {
+        let lparen_token = self.expect(&[TokenKind::LParen])?;
+        let list =
+            self.parse_comma_separated(lparen_token.span, TokenKind::RParen, Self::parse_expr)?;
+
+        let args = list.items;
+        let span = self.expr_span(callee).to(list.close_token.span);
+
+        Ok(ast::CallExpr {
+            callee,
+            args: args.into(),
+            span,
+        })
+    }
+
+    pub fn parse_expr_atom(&mut self) -> ParserResult<ast::ExprId> {
+        let peek_token = self.peek().ok_or(SyntaxError::UNEXPECTED_EOI)?;
+
+        let expr = match peek_token.kind {
+            TokenKind::Identifier => self.parse_path_expr()?,
+            TokenKind::Integer => ast::Expr::Integer(self.parse_integer_literal()?),
+            TokenKind::Float => ast::Expr::Float(self.parse_float_literal()?),
+            TokenKind::String => ast::Expr::String(self.parse_string_literal()?),
+            TokenKind::None => ast::Expr::None(self.next().unwrap().span),
+            TokenKind::LParen => return self.parse_paren_expr(),
+            TokenKind::LBrace => ast::Expr::Block(Box::new(self.parse_block()?)),
+            TokenKind::If => ast::Expr::If(Box::new(self.parse_if_expr()?)),
+            TokenKind::While => ast::Expr::While(Box::new(self.parse_while_expr()?)),
+            TokenKind::Loop => ast::Expr::Loop(Box::new(self.parse_loop_expr()?)),
+            TokenKind::Match => ast::Expr::Match(self.parse_match_expr()?),
+            TokenKind::Assert => ast::Expr::Assert(self.parse_assert_expr()?),
+            TokenKind::Panic => ast::Expr::Panic(self.parse_panic_expr()?),
+
+            kind => {
+                return Err(SyntaxError {
+                    kind: SyntaxErrorKind::UnexpectedToken {
+                        expected: &[
+                            TokenKind::Identifier,
+                            TokenKind::Integer,
+                            TokenKind::Float,
+                            TokenKind::String,
+                            TokenKind::None,
+                            TokenKind::LParen,
+                            TokenKind::LBrace,
+                            TokenKind::If,
+                            TokenKind::While,
+                            TokenKind::Loop,
+                            TokenKind::Match,
+                            TokenKind::Assert,
+                            TokenKind::Panic,
+                        ],
+                        got: kind,
+                    },
+                    span: peek_token.span,
+                });
+            }
+        };
+
+        Ok(self.alloc(expr))
+    }
+
+    /// Parses an identifier, or a path like `Shape::Circle` if it is followed by `::`.
+    fn parse_path_expr(&mut self) -> ParserResult<ast::Expr> {
+        let ident = self.parse_identifier()?;
+
+        if !self.is_peek(&[TokenKind::ColonColon]) {
+            return Ok(ast::Expr::Identifier(ident));
+        }
+
+        let mut segments = vec![ident];
+
+        while self.try_next(&[TokenKind::ColonColon]).is_some() {
+            segments.push(self.parse_identifier()?);
+        }
+
+        let span = segments[0].span.to(segments[segments.len() - 1].span);
+
+        Ok(ast::Expr::Path(ast::PathExpr { segments, span }))
+    }
+
+    fn parse_assert_expr(&mut self) -> ParserResult<ast::AssertExpr> {
+        let assert_token = self.expect(&[TokenKind::Assert])?;
+        let lparen_token = self.expect(&[TokenKind::LParen])?;
+
+        let cond = self.nested(lparen_token.span, Self::parse_expr)?;
+        let message = self.parse_message_arg(lparen_token.span, true)?;
+
+        let rparen_token = self.expect(&[TokenKind::RParen])?;
+        let span = assert_token.span.to(rparen_token.span);
+
+        Ok(ast::AssertExpr {
+            cond,
+            message,
+            span,
+        })
+    }
+
+    fn parse_panic_expr(&mut self) -> ParserResult<ast::PanicExpr> {
+        let panic_token = self.expect(&[TokenKind::Panic])?;
+        let lparen_token = self.expect(&[TokenKind::LParen])?;
+
+        let message = self.parse_message_arg(lparen_token.span, false)?;
+
+        let rparen_token = self.expect(&[TokenKind::RParen])?;
+        let span = panic_token.span.to(rparen_token.span);
+
+        Ok(ast::PanicExpr { message, span })
+    }
+
+    /// Parses the optional message argument of an `assert` or `panic` up to the closing
+    /// `)`, which is preceded by a `,` if `after_arg` is set and allows a trailing one.
+    fn parse_message_arg(
+        &mut self,
+        lparen_span: Span,
+        after_arg: bool,
+    ) -> ParserResult<Option<ast::ExprId>> {
+        if after_arg && self.try_next(&[TokenKind::Comma]).is_none() {
+            return Ok(None);
+        }
+
+        if self.is_peek(&[TokenKind::RParen]) {
+            return Ok(None);
+        }
+
+        let message = self.nested(lparen_span, Self::parse_expr)?;
+        self.try_next(&[TokenKind::Comma]);
+
+        Ok(Some(message))
+    }
+
+    fn parse_if_expr(&mut self) -> ParserResult<ast::IfExpr> {
+        let if_token = self.expect(&[TokenKind::If])?;
+
+        let cond = self.parse_expr()?;
+        let then_block = self.parse_block()?;
+
+        let else_branch = match self.try_next(&[TokenKind::Else]) {
+            Some(else_token) => {
+                let branch = match self.is_peek(&[TokenKind::If]) {
+                    true => {
+                        ast::Expr::If(Box::new(self.nested(else_token.span, Self::parse_if_expr)?))
+                    }
+                    false => ast::Expr::Block(Box::new(self.parse_block()?)),
+                };
+
+                Some(self.alloc(branch))
+            }
+            None => None,
+        };
+
+        let end_span = match else_branch {
+            Some(branch) => self.expr_span(branch),
+            None => then_block.span,
+        };
+        let span = if_token.span.to(end_span);
+
+        Ok(ast::IfExpr {
+            cond,
+            then_block,
+            else_branch,
+            span,
+        })
+    }
+
+    fn parse_while_expr(&mut self) -> ParserResult<ast::WhileExpr> {
+        let while_token = self.expect(&[TokenKind::While])?;
+
+        let cond = self.parse_expr()?;
+        let body = self.parse_block()?;
+        let span = while_token.span.to(body.span);
+
+        Ok(ast::WhileExpr { cond, body, span })
+    }
+
+    fn parse_loop_expr(&mut self) -> ParserResult<ast::LoopExpr> {
+        let loop_token = self.expect(&[TokenKind::Loop])?;
+
+        let body = self.parse_block()?;
+        let span = loop_token.span.to(body.span);
+
+        Ok(ast::LoopExpr { body, span })
+    }
+
+    fn parse_match_expr(&mut self) -> ParserResult<ast::MatchExpr> {
+        let match_token = self.expect(&[TokenKind::Match])?;
+
+        let scrutinee = self.parse_expr()?;
+        let lbrace_token = self.expect(&[TokenKind::LBrace])?;
+
+        let mut arms = Vec::new();
+
+        while !self.is_peek(&[TokenKind::RBrace]) {
+            let arm = self.nested(lbrace_token.span, Self::parse_match_arm)?;
+
+            // arms ending in a block don't need a `,` to separate them.
+            let needs_comma = !self.exprs[arm.value].is_block_like();
+            arms.push(arm);
+
+            if self.try_next(&[TokenKind::Comma]).is_none() && needs_comma {
+                break;
+            }
+        }
+
+        let rbrace_token = self.expect(&[TokenKind::RBrace])?;
+        let span = match_token.span.to(rbrace_token.span);
+
+        Ok(ast::MatchExpr {
+            scrutinee,
+            arms: arms.into(),
+            span,
+        })
+    }
+
+    fn parse_match_arm(&mut self) -> ParserResult<ast::MatchArm> {
+        let pattern = self.parse_pattern()?;
+
+        match self.next() {
+            Some(tok) if tok.kind == TokenKind::FatArrow => {}
+            Some(tok) => {
+                return Err(SyntaxError {
+                    kind: SyntaxErrorKind::MissingArmArrow,
+                    span: tok.span,
+                });
+            }
+            None => return Err(SyntaxError::UNEXPECTED_EOI),
+        }
+
+        let value = self.parse_expr()?;
+        let span = pattern.span().to(self.expr_span(value));
+
+        Ok(ast::MatchArm {
+            id: self.node_id(),
+            pattern,
+            value,
+            span,
+        })
+    }
+
+    pub fn parse_pattern(&mut self) -> ParserResult<ast::Pattern> {
+        let peek_token = self.peek().ok_or(SyntaxError::UNEXPECTED_EOI)?;
+
+        Ok(match peek_token.kind {
+            TokenKind::Identifier if peek_token.text == "_" => {
+                self.next();
+                ast::Pattern::Wildcard(peek_token.span)
+            }
+            TokenKind::Identifier => self.parse_path_pattern()?,
+            TokenKind::Integer => ast::Pattern::Integer(self.parse_integer_literal()?),
+            TokenKind::Float => ast::Pattern::Float(self.parse_float_literal()?),
+            TokenKind::String => ast::Pattern::String(self.parse_string_literal()?),
+            TokenKind::None => ast::Pattern::None(self.next().unwrap().span),
+
+            TokenKind::Minus => {
+                let minus_token = self.expect(&[TokenKind::Minus])?;
+                let literal_token = self.peek().ok_or(SyntaxError::UNEXPECTED_EOI)?;
+
+                match literal_token.kind {
+                    TokenKind::Integer => {
+                        let mut integer = self.parse_integer_literal()?;
+                        integer.negate(minus_token.span);
+                        ast::Pattern::Integer(integer)
+                    }
+                    TokenKind::Float => {
+                        let mut float = self.parse_float_literal()?;
+                        float.negate(minus_token.span);
+                        ast::Pattern::Float(float)
+                    }
+                    kind => {
+                        return Err(SyntaxError {
+                            kind: SyntaxErrorKind::UnexpectedToken {
+                                expected: &[TokenKind::Integer, TokenKind::Float],
+                                got: kind,
+                            },
+                            span: literal_token.span,
+                        });
+                    }
+                }
+            }
+
+            TokenKind::LParen => {
+                let lparen_token = self.expect(&[TokenKind::LParen])?;
+                let mut list = self.parse_comma_separated(
+                    lparen_token.span,
+                    TokenKind::RParen,
+                    Self::parse_pattern,
+                )?;
+
+                // like types, a single pattern without a trailing comma is only grouped.
+                if list.items.len() == 1 && !list.trailing_comma {
+                    return Ok(list.items.pop().unwrap());
+                }
+
+                let elements = list.items;
+                let span = lparen_token.span.to(list.close_token.span);
+
+                ast::Pattern::Tuple(ast::TuplePattern {
+                    id: self.node_id(),
+                    elements,
+                    span,
+                })
+            }
+
+            kind => {
+                return Err(SyntaxError {
+                    kind: SyntaxErrorKind::UnexpectedToken {
+                        expected: &[
+                            TokenKind::Identifier,
+                            TokenKind::Integer,
+                            TokenKind::Float,
+                            TokenKind::String,
+                            TokenKind::None,
+                            TokenKind::Minus,
+                            TokenKind::LParen,
+                        ],
+                        got: kind,
+                    },
+                    span: peek_token.span,
+                });
+            }
+        })
+    }
+
+    /// Parses a pattern starting with a path like `x`, `Shape::Empty`, `Some(x)` or
+    /// `Point { x, y }`, where a lone identifier is a binding.
+    fn parse_path_pattern(&mut self) -> ParserResult<ast::Pattern> {
+        let mut path = vec![self.parse_identifier()?];
+
+        while self.try_next(&[TokenKind::ColonColon]).is_some() {
+            path.push(self.parse_identifier()?);
+        }
+
+        let start = path[0].span;
+
+        if let Some(lparen_token) = self.try_next(&[TokenKind::LParen]) {
+            let list = self.parse_comma_separated(
+                lparen_token.span,
+                TokenKind::RParen,
+                Self::parse_pattern,
+            )?;
+
+            let elements = list.items;
+            let span = start.to(list.close_token.span);
+
+            return Ok(ast::Pattern::TupleStruct(ast::TupleStructPattern {
+                id: self.node_id(),
+                path,
+                elements,
+                span,
+            }));
+        }
+
+        if let Some(lbrace_token) = self.try_next(&[TokenKind::LBrace]) {
+            let list = self.parse_comma_separated(
+                lbrace_token.span,
+                TokenKind::RBrace,
+                Self::parse_field_pattern,
+            )?;
+
+            let fields = list.items;
+            let span = start.to(list.close_token.span);
+
+            return Ok(ast::Pattern::Struct(ast::StructPattern {
+                id: self.node_id(),
+                path,
+                fields,
+                span,
+            }));
+        }
+
+        if path.len() == 1 {
+            return Ok(ast::Pattern::Binding(ast::BindingPattern {
+                id: self.node_id(),
+                name: path.pop().unwrap(),
+            }));
+        }
+
+        let span = start.to(path[path.len() - 1].span);
+
+        Ok(ast::Pattern::Path(ast::PathPattern {
+            id: self.node_id(),
+            segments: path,
+            span,
+        }))
+    }
+
+    fn parse_field_pattern(&mut self) -> ParserResult<ast::FieldPattern> {
+        let name = self.parse_identifier()?;
+
+        let (pattern, span) = match self.try_next(&[TokenKind::Colon]) {
+            Some(_) => {
+                let pattern = self.parse_pattern()?;
+                let span = name.span.to(pattern.span());
+                (Some(pattern), span)
+            }
+            None => (None, name.span),
+        };
+
+        Ok(ast::FieldPattern {
+            id: self.node_id(),
+            name,
+            pattern,
+            span,
+        })
+    }
+
+    fn parse_paren_expr(&mut self) -> ParserResult<ast::ExprId> {
+        let lparen_token = self.expect(&[TokenKind::LParen])?;
+
+        if self.experiments.sections
+            && let Some(op) = self
+                .peek()
+                .filter(|tok| tok.kind != TokenKind::Minus)
+                .and_then(|tok| binary_op(tok.kind))
+        {
+            self.next();
+
+            let rhs = self.nested(lparen_token.span, Self::parse_expr)?;
+            let rparen_token = self.expect(&[TokenKind::RParen])?;
+
+            return Ok(self.alloc(ast::Expr::Section(ast::SectionExpr {
+                op,
+                lhs: None,
+                rhs: Some(rhs),
+                span: lparen_token.span.to(rparen_token.span),
+            })));
+        }
+
+        if let Some(rparen_token) = self.try_next(&[TokenKind::RParen]) {
+            return Ok(self.alloc(ast::Expr::Tuple(ast::TupleExpr {
+                elements: Box::new([]),
+                span: lparen_token.span.to(rparen_token.span),
+            })));
+        }
+
+        let expr = self.nested(lparen_token.span, Self::parse_expr)?;
+
+        // a comma after the first element makes this a tuple instead of a grouping.
+        if self.try_next(&[TokenKind::Comma]).is_some() {
+            let list =
+                self.parse_comma_separated(lparen_token.span, TokenKind::RParen, Self::parse_expr)?;
+
+            let elements = std::iter::once(expr).chain(list.items).collect();
+            let span = lparen_token.span.to(list.close_token.span);
+
+            return Ok(self.alloc(ast::Expr::Tuple(ast::TupleExpr { elements, span })));
+        }
+
+        let rparen_token = self.expect(&[TokenKind::Comma, TokenKind::RParen])?;
+
+        let span = lparen_token.span.to(rparen_token.span);
+
+        if self.experiments.sections
+            && let ast::Expr::Binary(binary) = &self.exprs[expr]
+            && let Some(section) = section_from_holes(&self.exprs, binary, span)
+        {
+            return Ok(self.alloc(ast::Expr::Section(section)));
+        }
+
+        Ok(self.alloc(ast::Expr::Paren(ast::ParenExpr { expr, span })))
+    }
+
+    fn parse_identifier(&mut self) -> ParserResult<ast::Identifier> {
+        let ident_token = self.expect(&[TokenKind::Identifier])?;
+
+        let text = ident_token.text.to_string();
+        let span = ident_token.span;
+
+        Ok(ast::Identifier { text, span })
+    }
+
+    fn parse_integer_literal(&mut self) -> ParserResult<ast::IntegerLiteral> {
+        let integer_token = self.expect(&[TokenKind::Integer])?;
+
+        let result = match integer_token.text {
+            text if text.starts_with("0x") => u64::from_str_radix(&text[2..], 16),
+            text if text.starts_with("0o") => u64::from_str_radix(&text[2..], 8),
+            text if text.starts_with("0b") => u64::from_str_radix(&text[2..], 2),
+            text => text.parse::<u64>(),
+        };
+
+        let value = match result {
+            Ok(value) => value.into(),
+            Err(err) => match err.kind() {
+                IntErrorKind::NegOverflow | IntErrorKind::PosOverflow => {
+                    return Err(SyntaxError {
+                        kind: SyntaxErrorKind::NumberOverflow,
+                        span: integer_token.span,
+                    });
+                }
+                _ => unreachable!(),
+            },
+        };
+
+        let span = integer_token.span;
+
+        Ok(ast::IntegerLiteral { value, span })
+    }
+
+    fn parse_float_literal(&mut self) -> ParserResult<ast::FloatLiteral> {
+        let float_token = self.expect(&[TokenKind::Float])?;
+
+        let value = match float_token.text.parse::<f64>() {
+            Ok(value) => value,
+            Err(_) => {
+                return Err(SyntaxError {
+                    kind: SyntaxErrorKind::InvalidNumber,
+                    span: float_token.span,
+                });
+            }
+        };
+
+        let span = float_token.span;
+        let value_bits = value.to_bits();
+
+        Ok(ast::FloatLiteral { value_bits, span })
+    }
+
+    fn parse_string_literal(&mut self) -> ParserResult<ast::StringLiteral> {
+        let string_token = self.expect(&[TokenKind::String])?;
+        let span = string_token.span;
+
+        // strip the surrounding quotes, which the lexer guarantees to be there.
+        let text = &string_token.text[1..string_token.text.len() - 1];
+
+        let mut value = String::with_capacity(text.len());
+        // the chars are numbered like a [Span], the text starting after the opening quote.
+        let mut chars = (span.start + 1..).zip(text.chars());
+
+        while let Some((start, ch)) = chars.next() {
+            if ch != '\\' {
+                value.push(ch);
+                continue;
+            }
+
+            let escaped = chars.next();
+            let invalid_escape = SyntaxError {
+                kind: SyntaxErrorKind::InvalidEscape,
+                span: Span {
+                    start,
+                    end: escaped.map_or(start + 1, |(index, _)| index + 1),
+                },
+            };
+
+            value.push(match escaped.ok_or(invalid_escape)?.1 {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                '0' => '\0',
+                '\\' => '\\',
+                '"' => '"',
+                _ => return Err(invalid_escape),
+            });
+        }
+
+        Ok(ast::StringLiteral { value, span })
+    }
+}
+
+/// Returns the [Span] an item starts at, which is its first [ast::Attribute] if it has any.
+fn item_start(attributes: &[ast::Attribute], keyword_span: Span) -> Span {
+    attributes.first().map_or(keyword_span, |attr| attr.span)
+}
+
+/// Marks the item preceded by `pub` as [ast::Visibility::Public], making its [Span]
+/// start at the `pub` unless it starts at an attribute.
+fn make_public(item: &mut ast::Item, pub_span: Span) {
+    let (visibility, span) = match item {
+        ast::Item::Func(func) => (&mut func.visibility, &mut func.span),
+        ast::Item::Const(const_decl) => (&mut const_decl.visibility, &mut const_decl.span),
+        ast::Item::Struct(struct_decl) => (&mut struct_decl.visibility, &mut struct_decl.span),
+        ast::Item::Enum(enum_decl) => (&mut enum_decl.visibility, &mut enum_decl.span),
+        ast::Item::Trait(trait_decl) => (&mut trait_decl.visibility, &mut trait_decl.span),
+        ast::Item::Import(_) | ast::Item::Error(_) => return,
+    };
+
+    *visibility = ast::Visibility::Public;
+    span.start = span.start.min(pub_span.start);
+}
+
+/// Returns the [TokenKind]s that may follow an element of a list closed by `close`,
+/// with the comma first.
+fn comma_or(close: TokenKind) -> &'static [TokenKind] {
+    match close {
+        TokenKind::RParen => &[TokenKind::Comma, TokenKind::RParen],
+        TokenKind::RBrace => &[TokenKind::Comma, TokenKind::RBrace],
+        TokenKind::RBracket => &[TokenKind::Comma, TokenKind::RBracket],
+        TokenKind::GreaterThan => &[TokenKind::Comma, TokenKind::GreaterThan],
+        _ => unreachable!("{close:?} doesn't close a list"),
+    }
+}
+
+/// How an operator groups with operators of the same precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Assoc {
+    /// `a - b - c` groups as `(a - b) - c`.
+    Left,
+    /// `a = b = c` groups as `a = (b = c)`.
+    Right,
+    /// `a..b..c` doesn't parse.
+    None,
+}
+
+/// Returns the precedence and [Assoc] of the binary operator [TokenKind], where a
+/// higher precedence binds tighter.
+fn precedence(kind: TokenKind) -> Option<(u8, Assoc)> {
+    Some(match kind {
+        TokenKind::Assign => (1, Assoc::Right),
+        TokenKind::DotDot | TokenKind::DotDotEq => (2, Assoc::None),
+        TokenKind::Equal | TokenKind::Unequal => (3, Assoc::Left),
+        TokenKind::LessThan
+        | TokenKind::LessEqual
+        | TokenKind::GreaterThan
+        | TokenKind::GreaterEqual => (4, Assoc::Left),
+        TokenKind::Plus | TokenKind::Minus => (5, Assoc::Left),
+        TokenKind::Asterisk | TokenKind::Slash | TokenKind::Percent => (6, Assoc::Left),
+        _ => return None,
+    })
+}
+
+/// Returns the [ast::BinaryOp] for the given operator [TokenKind].
+fn binary_op(kind: TokenKind) -> Option<ast::BinaryOp> {
+    Some(match kind {
+        TokenKind::Plus => ast::BinaryOp::Add,
+        TokenKind::Minus => ast::BinaryOp::Sub,
+        TokenKind::Asterisk => ast::BinaryOp::Mul,
+        TokenKind::Slash => ast::BinaryOp::Div,
+        TokenKind::Percent => ast::BinaryOp::Rem,
+        TokenKind::Equal => ast::BinaryOp::Equal,
+        TokenKind::Unequal => ast::BinaryOp::Unequal,
+        TokenKind::LessThan => ast::BinaryOp::LessThan,
+        TokenKind::LessEqual => ast::BinaryOp::LessEqual,
+        TokenKind::GreaterThan => ast::BinaryOp::GreaterThan,
+        TokenKind::GreaterEqual => ast::BinaryOp::GreaterEqual,
+        _ => return None,
+    })
+}
+
+/// Returns the [ast::SectionExpr] for a binary expression with a `_` hole as one of its
+/// operands, like `(_ * 2)`.
+fn section_from_holes(
+    exprs: &ast::ExprArena,
+    binary: &ast::BinaryExpr,
+    span: Span,
+) -> Option<ast::SectionExpr> {
+    let is_hole = |id| matches!(&exprs[id], ast::Expr::Identifier(ident) if ident.text == "_");
+
+    let (lhs, rhs) = match (is_hole(binary.lhs), is_hole(binary.rhs)) {
+        (true, false) => (None, Some(binary.rhs)),
+        (false, true) => (Some(binary.lhs), None),
+        _ => return None,
+    };
+
+    Some(ast::SectionExpr {
+        op: binary.op,
+        lhs,
+        rhs,
+        span,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast,
+        error::{SyntaxError, SyntaxErrorKind},
+        experiments::Experiments,
+        lexer::Lexer,
+        limits::{Limit, Limits},
+        parser::Parser,
+        token::{Span, TokenKind},
+    };
+
+    #[test]
+    fn expr_atom() -> Result<(), SyntaxError> {
+        let test_case = [
+            (
+                "0x12",
+                ast::Expr::Integer(ast::IntegerLiteral {
+                    value: 0x12,
+                    span: Span { start: 0, end: 4 },
+                }),
+            ),
+            (
+                "12.3e-5",
+                ast::Expr::Float(ast::FloatLiteral {
+                    value_bits: (12.3e-5f64).to_bits(),
+                    span: Span { start: 0, end: 7 },
+                }),
+            ),
+            (
+                "cents",
+                ast::Expr::Identifier(ast::Identifier {
+                    text: "cents".to_string(),
+                    span: Span { start: 0, end: 5 },
+                }),
+            ),
+        ];
+
+        for (input, expected) in test_case {
+            let tokens = Lexer::new(input).collect_tokens()?;
+            let mut parser = Parser::new(&tokens);
+
+            let id = parser.parse_expr_atom()?;
+            assert_eq!(parser.exprs()[id], expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn nesting_limit() -> Result<(), SyntaxError> {
+        let limits = Limits {
+            parse_nesting_depth: 2,
+            ..Limits::default()
+        };
+
+        let tokens = Lexer::new("((1))").collect_tokens()?;
+        let mut parser = Parser::with_limits(&tokens, limits);
+        assert!(parser.parse_expr_atom().is_ok());
+
+        let tokens = Lexer::new("(((1)))").collect_tokens()?;
+        let mut parser = Parser::with_limits(&tokens, limits);
+        assert_eq!(
+            parser.parse_expr_atom(),
+            Err(SyntaxError {
+                kind: SyntaxErrorKind::LimitExceeded(Limit::ParseNestingDepth),
+                span: Span { start: 2, end: 3 },
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn operator_nesting_limit() -> Result<(), SyntaxError> {
+        let limits = Limits {
+            parse_nesting_depth: 3,
+            ..Limits::default()
+        };
+
+        let tokens = Lexer::new("1 + 2 + 3 + 4").collect_tokens()?;
+        let mut parser = Parser::with_limits(&tokens, limits);
+        assert!(parser.parse_expr().is_ok());
+
+        let tokens = Lexer::new("1 + 2 + 3 + 4 + 5").collect_tokens()?;
+        let mut parser = Parser::with_limits(&tokens, limits);
+        assert_eq!(
+            parser.parse_expr(),
+            Err(SyntaxError {
+                kind: SyntaxErrorKind::LimitExceeded(Limit::ParseNestingDepth),
+                span: Span { start: 14, end: 15 },
+            })
+        );
+
+        // the operators inside the parentheses count towards the depth as well.
+        let tokens = Lexer::new("(1 + 2) + (3 + 4) + 5").collect_tokens()?;
+        let mut parser = Parser::with_limits(&tokens, limits);
+        assert_eq!(
+            parser.parse_expr(),
+            Err(SyntaxError {
+                kind: SyntaxErrorKind::LimitExceeded(Limit::ParseNestingDepth),
+                span: Span { start: 18, end: 19 },
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn expr_stmts() -> Result<(), SyntaxError> {
+        let input = "do_thing(); x = 1 + 2 * y; x";
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        let (stmts, tail) = parser.parse_stmts()?;
+
+        let ident = |text: &str, start, end| {
+            ast::Expr::Identifier(ast::Identifier {
+                text: text.to_string(),
+                span: Span { start, end },
+            })
+        };
+        let integer = |value, start, end| {
+            ast::Expr::Integer(ast::IntegerLiteral {
+                value,
+                span: Span { start, end },
+            })
+        };
+
+        let mut exprs = ast::ExprArena::default();
+        let callee = exprs.alloc(ident("do_thing", 0, 8));
+        let call = exprs.alloc(ast::Expr::Call(ast::CallExpr {
+            callee,
+            args: Box::new([]),
+            span: Span { start: 0, end: 10 },
+        }));
+        let target = exprs.alloc(ident("x", 12, 13));
+        let one = exprs.alloc(integer(1, 16, 17));
+        let two = exprs.alloc(integer(2, 20, 21));
+        let y = exprs.alloc(ident("y", 24, 25));
+        let mul = exprs.alloc(ast::Expr::Binary(ast::BinaryExpr {
+            op: ast::BinaryOp::Mul,
+            lhs: two,
+            rhs: y,
+            span: Span { start: 20, end: 25 },
+        }));
+        let add = exprs.alloc(ast::Expr::Binary(ast::BinaryExpr {
+            op: ast::BinaryOp::Add,
+            lhs: one,
+            rhs: mul,
+            span: Span { start: 16, end: 25 },
+        }));
+        let assign = exprs.alloc(ast::Expr::Assign(ast::AssignExpr {
+            target,
+            value: add,
+            span: Span { start: 12, end: 25 },
+        }));
+        let expected_tail = Some(exprs.alloc(ident("x", 27, 28)));
+
+        let expected_stmts = vec![
+            ast::Stmt::Expr(ast::ExprStmt {
+                id: ast::NodeId(0),
+                attributes: Vec::new(),
+                expr: call,
+                span: Span { start: 0, end: 11 },
+            }),
+            ast::Stmt::Expr(ast::ExprStmt {
+                id: ast::NodeId(1),
+                attributes: Vec::new(),
+                expr: assign,
+                span: Span { start: 12, end: 26 },
+            }),
+        ];
+
+        assert_eq!(stmts, expected_stmts);
+        assert_eq!(tail, expected_tail);
+        assert_eq!(parser.exprs(), &exprs);
+
+        Ok(())
+    }
+
+    #[test]
+    fn missing_semicolon() -> Result<(), SyntaxError> {
+        let tokens = Lexer::new("first() second()").collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        assert_eq!(
+            parser.parse_stmts(),
+            Ok((vec![ast::Stmt::Error(Span { start: 0, end: 16 })], None))
+        );
+        assert_eq!(
+            parser.errors(),
+            [SyntaxError {
+                kind: SyntaxErrorKind::MissingSemicolon,
+                span: Span { start: 7, end: 7 },
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn missing_semicolon_before_brace() -> Result<(), SyntaxError> {
+        let input = "fn a() {\n let x = 1\n}\nfn b() {}";
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let (module, errors) = Parser::new(&tokens).parse_module();
+
+        assert_eq!(
+            errors,
+            [SyntaxError {
+                kind: SyntaxErrorKind::MissingSemicolon,
+                span: Span { start: 19, end: 19 },
+            }]
+        );
+
+        // the `}` still closes the body of `a`, so `b` is an item of its own.
+        let names: Vec<_> = module
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ast::Item::Func(func) => Some(func.name.text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, ["a", "b"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn brace_right_after_statement() -> Result<(), SyntaxError> {
+        let tokens = Lexer::new("let x = 1}").collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+        parser.parse_stmts()?;
+
+        assert_eq!(
+            parser.errors(),
+            [SyntaxError {
+                kind: SyntaxErrorKind::MissingSemicolon,
+                span: Span { start: 9, end: 9 },
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn adjacent_token_after_statement() -> Result<(), SyntaxError> {
+        let tokens = Lexer::new("let x = 0x1g;").collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+        parser.parse_stmts()?;
+
+        assert_eq!(
+            parser.errors(),
+            [SyntaxError {
+                kind: SyntaxErrorKind::UnexpectedToken {
+                    expected: &[TokenKind::Semicolon],
+                    got: TokenKind::Identifier,
+                },
+                span: Span { start: 11, end: 12 },
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn func_decl() -> Result<(), SyntaxError> {
+        let input = "fn add(a: i64, b: i64) -> i64 { a + b }";
+
+        let ident = |text: &str, start, end| ast::Identifier {
+            text: text.to_string(),
+            span: Span { start, end },
+        };
+
+        let named = |id, text: &str, start, end| {
+            ast::TypeExpr::Named(ast::NamedType {
+                id: ast::NodeId(id),
+                name: ident(text, start, end),
+                args: Vec::new(),
+                span: Span { start, end },
+            })
+        };
+
+        let mut exprs = ast::ExprArena::default();
+        let lhs = exprs.alloc(ast::Expr::Identifier(ident("a", 32, 33)));
+        let rhs = exprs.alloc(ast::Expr::Identifier(ident("b", 36, 37)));
+        let tail = exprs.alloc(ast::Expr::Binary(ast::BinaryExpr {
+            op: ast::BinaryOp::Add,
+            lhs,
+            rhs,
+            span: Span { start: 32, end: 37 },
+        }));
+
+        let expected = ast::Module {
+            items: vec![ast::Item::Func(ast::FuncDecl {
+                id: ast::NodeId(6),
+                docs: None,
+                attributes: Vec::new(),
+                visibility: ast::Visibility::Private,
+                name: ident("add", 3, 6),
+                generics: Vec::new(),
+                params: vec![
+                    ast::Param {
+                        id: ast::NodeId(1),
+                        name: ident("a", 7, 8),
+                        ty: named(0, "i64", 10, 13),
+                        span: Span { start: 7, end: 13 },
+                    },
+                    ast::Param {
+                        id: ast::NodeId(3),
+                        name: ident("b", 15, 16),
+                        ty: named(2, "i64", 18, 21),
+                        span: Span { start: 15, end: 21 },
+                    },
+                ],
+                return_type: Some(named(4, "i64", 26, 29)),
+                body: ast::Block {
+                    id: ast::NodeId(5),
+                    stmts: vec![],
+                    tail: Some(tail),
+                    span: Span { start: 30, end: 39 },
+                },
+                span: Span { start: 0, end: 39 },
+            })],
+            exprs,
+        };
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        assert_eq!(parser.parse_module(), (expected, Vec::new()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn func_decl_errors() -> Result<(), SyntaxError> {
+        let test_cases = [
+            (
+                "fn f a: i64) {}",
+                SyntaxError {
+                    kind: SyntaxErrorKind::UnexpectedToken {
+                        expected: &[TokenKind::LParen],
+                        got: TokenKind::Identifier,
+                    },
+                    span: Span { start: 5, end: 6 },
+                },
+            ),
+            (
+                "fn f(a: i64 b: i64) {}",
+                SyntaxError {
+                    kind: SyntaxErrorKind::UnexpectedToken {
+                        expected: &[TokenKind::Comma, TokenKind::RParen],
+                        got: TokenKind::Identifier,
+                    },
+                    span: Span { start: 12, end: 13 },
+                },
+            ),
+            (
+                "fn f() return",
+                SyntaxError {
+                    kind: SyntaxErrorKind::UnexpectedToken {
+                        expected: &[TokenKind::Arrow, TokenKind::LBrace],
+                        got: TokenKind::Return,
+                    },
+                    span: Span { start: 7, end: 13 },
+                },
+            ),
+            ("fn f() -> i64", SyntaxError::UNEXPECTED_EOI),
+        ];
+
+        for (input, output) in test_cases {
+            let tokens = Lexer::new(input).collect_tokens()?;
+            let mut parser = Parser::new(&tokens);
+
+            assert_eq!(parser.parse_module().1, vec![output]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn node_ids() -> Result<(), SyntaxError> {
+        let input = "fn f(a: i64) { let b = a; } fn g() {}";
+        let tokens = Lexer::new(input).collect_tokens()?;
+
+        let (module, errors) = Parser::new(&tokens).parse_module();
+        assert_eq!(errors, Vec::new());
+
+        let [ast::Item::Func(f), ast::Item::Func(g)] = &module.items[..] else {
+            panic!("expected two functions, got {:?}", module.items);
+        };
+        let [ast::Stmt::Let(let_stmt)] = &f.body.stmts[..] else {
+            panic!("expected a let statement, got {:?}", f.body.stmts);
+        };
+
+        assert!(f.params[0].id < let_stmt.id);
+        assert!(let_stmt.id < f.body.id);
+        assert!(f.body.id < f.id);
+        assert!(f.id < g.body.id);
+        assert!(g.body.id < g.id);
+
+        Ok(())
+    }
+
+    #[test]
+    fn for_stmt() -> Result<(), SyntaxError> {
+        let input = "for item in items { print(item); }";
+
+        let ident = |text: &str, start, end| ast::Identifier {
+            text: text.to_string(),
+            span: Span { start, end },
+        };
+
+        let mut exprs = ast::ExprArena::default();
+        let iterable = exprs.alloc(ast::Expr::Identifier(ident("items", 12, 17)));
+        let callee = exprs.alloc(ast::Expr::Identifier(ident("print", 20, 25)));
+        let arg = exprs.alloc(ast::Expr::Identifier(ident("item", 26, 30)));
+        let call = exprs.alloc(ast::Expr::Call(ast::CallExpr {
+            callee,
+            args: Box::new([arg]),
+            span: Span { start: 20, end: 31 },
+        }));
+
+        let expected = ast::Stmt::For(ast::ForStmt {
+            id: ast::NodeId(2),
+            attributes: Vec::new(),
+            binding: ident("item", 4, 8),
+            iterable,
+            body: ast::Block {
+                id: ast::NodeId(1),
+                stmts: vec![ast::Stmt::Expr(ast::ExprStmt {
+                    id: ast::NodeId(0),
+                    attributes: Vec::new(),
+                    expr: call,
+                    span: Span { start: 20, end: 32 },
+                })],
+                tail: None,
+                span: Span { start: 18, end: 34 },
+            },
+            span: Span { start: 0, end: 34 },
+        });
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        assert_eq!(parser.parse_stmts(), Ok((vec![expected], None)));
+        assert_eq!(parser.exprs(), &exprs);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sections() -> Result<(), SyntaxError> {
+        let integer = |value, start, end| {
+            ast::Expr::Integer(ast::IntegerLiteral {
+                value,
+                span: Span { start, end },
+            })
+        };
+
+        let test_cases = [
+            ("(+ 1)", ast::BinaryOp::Add, false, integer(1, 3, 4), 5),
+            ("(2 * _)", ast::BinaryOp::Mul, true, integer(2, 1, 2), 7),
+        ];
+
+        for (input, op, is_lhs, operand, end) in test_cases {
+            let tokens = Lexer::new(input).collect_tokens()?;
+            let mut parser = Parser::new(&tokens);
+            parser.set_experiments(Experiments { sections: true });
+
+            let id = parser.parse_expr()?;
+            let ast::Expr::Section(section) = &parser.exprs()[id] else {
+                panic!("expected a section, got {:?}", parser.exprs()[id]);
+            };
+
+            assert_eq!(section.op, op);
+            assert_eq!(section.lhs.is_some(), is_lhs);
+            assert_eq!(section.span, Span { start: 0, end });
+
+            let operand_id = section.lhs.or(section.rhs).unwrap();
+            assert_eq!(parser.exprs()[operand_id], operand);
+        }
+
+        let tokens = Lexer::new("(+ 1)").collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+        assert!(parser.parse_expr().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn loop_expr() -> Result<(), SyntaxError> {
+        let input = "loop { continue; break 1; } x";
+
+        let mut exprs = ast::ExprArena::default();
+        let one = exprs.alloc(ast::Expr::Integer(ast::IntegerLiteral {
+            value: 1,
+            span: Span { start: 23, end: 24 },
+        }));
+        let loop_expr = exprs.alloc(ast::Expr::Loop(Box::new(ast::LoopExpr {
+            body: ast::Block {
+                id: ast::NodeId(2),
+                stmts: vec![
+                    ast::Stmt::Continue(ast::ContinueStmt {
+                        id: ast::NodeId(0),
+                        attributes: Vec::new(),
+                        span: Span { start: 7, end: 16 },
+                    }),
+                    ast::Stmt::Break(ast::BreakStmt {
+                        id: ast::NodeId(1),
+                        attributes: Vec::new(),
+                        value: Some(one),
+                        span: Span { start: 17, end: 25 },
+                    }),
+                ],
+                tail: None,
+                span: Span { start: 5, end: 27 },
+            },
+            span: Span { start: 0, end: 27 },
+        })));
+        let x = exprs.alloc(ast::Expr::Identifier(ast::Identifier {
+            text: "x".to_string(),
+            span: Span { start: 28, end: 29 },
+        }));
+
+        let expected_stmts = vec![ast::Stmt::Expr(ast::ExprStmt {
+            id: ast::NodeId(3),
+            attributes: Vec::new(),
+            expr: loop_expr,
+            span: Span { start: 0, end: 27 },
+        })];
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        assert_eq!(parser.parse_stmts(), Ok((expected_stmts, Some(x))));
+        assert_eq!(parser.exprs(), &exprs);
+
+        Ok(())
+    }
+
+    #[test]
+    fn block_expr() -> Result<(), SyntaxError> {
+        let input = "{ let x: i64 = 1; if x { x } else { 0 } }";
+
+        let ident = |text: &str, start, end| ast::Identifier {
+            text: text.to_string(),
+            span: Span { start, end },
+        };
+        let integer = |value, start, end| {
+            ast::Expr::Integer(ast::IntegerLiteral {
+                value,
+                span: Span { start, end },
+            })
+        };
+        let named = |id, text: &str, start, end| {
+            ast::TypeExpr::Named(ast::NamedType {
+                id: ast::NodeId(id),
+                name: ident(text, start, end),
+                args: Vec::new(),
+                span: Span { start, end },
+            })
+        };
+        let block = |id, tail, start, end| ast::Block {
+            id: ast::NodeId(id),
+            stmts: vec![],
+            tail: Some(tail),
+            span: Span { start, end },
+        };
+
+        let mut exprs = ast::ExprArena::default();
+        let one = exprs.alloc(integer(1, 15, 16));
+        let cond = exprs.alloc(ast::Expr::Identifier(ident("x", 21, 22)));
+        let then_tail = exprs.alloc(ast::Expr::Identifier(ident("x", 25, 26)));
+        let else_tail = exprs.alloc(integer(0, 36, 37));
+        let else_branch = exprs.alloc(ast::Expr::Block(Box::new(block(4, else_tail, 34, 39))));
+        let if_expr = exprs.alloc(ast::Expr::If(Box::new(ast::IfExpr {
+            cond,
+            then_block: block(3, then_tail, 23, 28),
+            else_branch: Some(else_branch),
+            span: Span { start: 18, end: 39 },
+        })));
+        let expected = exprs.alloc(ast::Expr::Block(Box::new(ast::Block {
+            id: ast::NodeId(5),
+            stmts: vec![ast::Stmt::Let(ast::LetStmt {
+                id: ast::NodeId(2),
+                attributes: Vec::new(),
+                mutability: ast::Mutability::Immutable,
+                pattern: ast::Pattern::Binding(ast::BindingPattern {
+                    id: ast::NodeId(0),
+                    name: ident("x", 6, 7),
+                }),
+                ty: Some(named(1, "i64", 9, 12)),
+                value: Some(one),
+                span: Span { start: 2, end: 17 },
+            })],
+            tail: Some(if_expr),
+            span: Span { start: 0, end: 41 },
+        })));
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        assert_eq!(parser.parse_expr(), Ok(expected));
+        assert_eq!(parser.exprs(), &exprs);
+
+        Ok(())
+    }
+
+    #[test]
+    fn block_like_stmts() -> Result<(), SyntaxError> {
+        let input = "while x { } { } if x { } else if y { } z";
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        let (stmts, tail) = parser.parse_stmts()?;
+
+        assert_eq!(stmts.len(), 3);
+        assert!(matches!(
+            tail.map(|id| &parser.exprs()[id]),
+            Some(ast::Expr::Identifier(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn enum_decl() -> Result<(), SyntaxError> {
+        let input = "enum Shape { Empty, Circle(f64), Rect { w: f64, h: f64 }, }";
+
+        let ident = |text: &str, start, end| ast::Identifier {
+            text: text.to_string(),
+            span: Span { start, end },
+        };
+
+        let named = |id, text: &str, start, end| {
+            ast::TypeExpr::Named(ast::NamedType {
+                id: ast::NodeId(id),
+                name: ident(text, start, end),
+                args: Vec::new(),
+                span: Span { start, end },
+            })
+        };
+
+        let expected = ast::Module {
+            items: vec![ast::Item::Enum(ast::EnumDecl {
+                id: ast::NodeId(8),
+                docs: None,
+                attributes: Vec::new(),
+                visibility: ast::Visibility::Private,
+                name: ident("Shape", 5, 10),
+                variants: vec![
+                    ast::Variant {
+                        id: ast::NodeId(0),
+                        docs: None,
+                        name: ident("Empty", 13, 18),
+                        payload: ast::VariantPayload::Unit,
+                        span: Span { start: 13, end: 18 },
+                    },
+                    ast::Variant {
+                        id: ast::NodeId(2),
+                        docs: None,
+                        name: ident("Circle", 20, 26),
+                        payload: ast::VariantPayload::Tuple(vec![named(1, "f64", 27, 30)]),
+                        span: Span { start: 20, end: 31 },
+                    },
+                    ast::Variant {
+                        id: ast::NodeId(7),
+                        docs: None,
+                        name: ident("Rect", 33, 37),
+                        payload: ast::VariantPayload::Struct(vec![
+                            ast::Field {
+                                id: ast::NodeId(4),
+                                docs: None,
+                                name: ident("w", 40, 41),
+                                ty: named(3, "f64", 43, 46),
+                                span: Span { start: 40, end: 46 },
+                            },
+                            ast::Field {
+                                id: ast::NodeId(6),
+                                docs: None,
+                                name: ident("h", 48, 49),
+                                ty: named(5, "f64", 51, 54),
+                                span: Span { start: 48, end: 54 },
+                            },
+                        ]),
+                        span: Span { start: 33, end: 56 },
+                    },
+                ],
+                span: Span { start: 0, end: 59 },
+            })],
+            exprs: ast::ExprArena::default(),
+        };
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        assert_eq!(parser.parse_module(), (expected, Vec::new()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn generics() -> Result<(), SyntaxError> {
+        let input = "fn max<T>(a: T, b: T) -> T { a < b } struct Box<T, U,> { value: T, }";
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        let (module, errors) = parser.parse_module();
+        assert_eq!(errors, Vec::new());
+
+        let [ast::Item::Func(func), ast::Item::Struct(struct_decl)] = &module.items[..] else {
+            panic!("expected a function and a struct, got {:?}", module.items);
+        };
+
+        let names = |generics: &[ast::Identifier]| -> Vec<(String, Span)> {
+            generics
+                .iter()
+                .map(|name| (name.text.clone(), name.span))
+                .collect()
+        };
+
+        assert_eq!(
+            names(&func.generics),
+            [("T".to_string(), Span { start: 7, end: 8 })]
+        );
+        assert!(matches!(
+            module.exprs[func.body.tail.unwrap()],
+            ast::Expr::Binary(ast::BinaryExpr {
+                op: ast::BinaryOp::LessThan,
+                ..
+            })
+        ));
+
+        assert_eq!(
+            names(&struct_decl.generics),
+            [
+                ("T".to_string(), Span { start: 48, end: 49 }),
+                ("U".to_string(), Span { start: 51, end: 52 }),
+            ]
+        );
+        assert_eq!(struct_decl.fields.len(), 1);
+        assert_eq!(struct_decl.span, Span { start: 37, end: 68 });
+
+        Ok(())
+    }
+
+    #[test]
+    fn attributes() -> Result<(), SyntaxError> {
+        let input = "@inline @deprecated(\"use max\",) fn min() {} @test() enum E {}";
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        let (module, errors) = parser.parse_module();
+        assert_eq!(errors, Vec::new());
+
+        let attributes: Vec<_> = module
+            .items
+            .iter()
+            .map(|item| {
+                let attributes: Vec<_> = item
+                    .attributes()
+                    .iter()
+                    .map(|attr| (attr.name.text.as_str(), attr.args.len(), attr.span))
+                    .collect();
+                (attributes, item.span())
+            })
+            .collect();
+
+        assert_eq!(
+            attributes,
+            [
+                (
+                    vec![
+                        ("inline", 0, Span { start: 0, end: 7 }),
+                        ("deprecated", 1, Span { start: 8, end: 31 }),
+                    ],
+                    Span { start: 0, end: 43 },
+                ),
+                (
+                    vec![("test", 0, Span { start: 44, end: 51 })],
+                    Span { start: 44, end: 61 },
+                ),
+            ]
+        );
+
+        let input = "@inline let";
+        let tokens = Lexer::new(input).collect_tokens()?;
+
+        assert_eq!(
+            Parser::new(&tokens).parse_item(),
+            Err(SyntaxError {
+                kind: SyntaxErrorKind::UnexpectedToken {
+                    // an item can't start with another attribute.
+                    expected: &super::ITEM_SYNC[1..],
+                    got: TokenKind::Let,
+                },
+                span: Span { start: 8, end: 11 },
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn visibility() -> Result<(), SyntaxError> {
+        let input = "pub fn f() {} @inline pub const N: i64 = 1; fn g() {}";
+        let tokens = Lexer::new(input).collect_tokens()?;
+
+        let (module, errors) = Parser::new(&tokens).parse_module();
+        assert_eq!(errors, Vec::new());
+
+        let items: Vec<_> = module
+            .items
+            .iter()
+            .map(|item| (item.visibility(), item.span()))
+            .collect();
+        assert_eq!(
+            items,
+            [
+                (ast::Visibility::Public, Span { start: 0, end: 13 }),
+                (ast::Visibility::Public, Span { start: 14, end: 43 }),
+                (ast::Visibility::Private, Span { start: 44, end: 53 }),
+            ]
+        );
+
+        let input = "pub import geometry;";
+        let tokens = Lexer::new(input).collect_tokens()?;
+
+        assert_eq!(
+            Parser::new(&tokens).parse_item(),
+            Err(SyntaxError {
+                kind: SyntaxErrorKind::UnexpectedToken {
+                    // imports can't be `pub`.
+                    expected: &super::ITEM_SYNC[3..],
+                    got: TokenKind::Import,
+                },
+                span: Span { start: 4, end: 10 },
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn const_decl() -> Result<(), SyntaxError> {
+        let input = "const MAX: i64 = 1024; fn f() { const HALF: i64 = MAX / 2; }";
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        let (module, errors) = parser.parse_module();
+        assert_eq!(errors, Vec::new());
+
+        let [ast::Item::Const(max), ast::Item::Func(func)] = &module.items[..] else {
+            panic!("expected a const and a function, got {:?}", module.items);
+        };
+        assert_eq!(max.name.text, "MAX");
+        assert_eq!(max.ty.display(&module.exprs).to_string(), "i64");
+        assert_eq!(max.span, Span { start: 0, end: 22 });
+        assert!(matches!(
+            module.exprs[max.value],
+            ast::Expr::Integer(ast::IntegerLiteral { value: 1024, .. })
+        ));
+
+        let [ast::Stmt::Const(half)] = &func.body.stmts[..] else {
+            panic!("expected a const statement, got {:?}", func.body.stmts);
+        };
+        assert_eq!(half.name.text, "HALF");
+        assert_eq!(half.span, Span { start: 32, end: 58 });
+        assert!(matches!(module.exprs[half.value], ast::Expr::Binary(_)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn trait_decl() -> Result<(), SyntaxError> {
+        let input = "trait Printable { fn print(self); fn width(self, pad: i64) -> i64 { pad } fn new(self: i64); }";
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        let (module, errors) = parser.parse_module();
+        assert_eq!(errors, Vec::new());
+
+        let [ast::Item::Trait(trait_decl)] = &module.items[..] else {
+            panic!("expected a trait, got {:?}", module.items);
+        };
+        assert_eq!(trait_decl.name.text, "Printable");
+        assert_eq!(trait_decl.span, Span { start: 0, end: 94 });
+
+        let methods: Vec<_> = trait_decl
+            .methods
+            .iter()
+            .map(|method| {
+                (
+                    method.name.text.as_str(),
+                    method.receiver,
+                    method.params.len(),
+                    method.return_type.is_some(),
+                    method.body.is_some(),
+                    method.span,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            methods,
+            [
+                (
+                    "print",
+                    Some(Span { start: 27, end: 31 }),
+                    0,
+                    false,
+                    false,
+                    Span { start: 18, end: 33 },
+                ),
+                (
+                    "width",
+                    Some(Span { start: 43, end: 47 }),
+                    1,
+                    true,
+                    true,
+                    Span { start: 34, end: 73 },
+                ),
+                ("new", None, 1, false, false, Span { start: 74, end: 92 }),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn doc_comments() -> Result<(), SyntaxError> {
+        let input = "
+            // not a doc comment
+            /// Returns the larger value.
+            ///
+            ///  Ties return `a`.
+            @inline
+            fn max(a: i64, b: i64) -> i64 { /// ignored
+            a }
+            //// not a doc comment either
+            struct S { /// The field.
+            x: i64 }
+        ";
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        let (module, errors) = parser.parse_module();
+        assert_eq!(errors, Vec::new());
+
+        let docs: Vec<_> = module.items.iter().map(ast::Item::docs).collect();
+        assert_eq!(
+            docs,
+            [Some("Returns the larger value.\n\n Ties return `a`."), None]
+        );
+
+        let ast::Item::Struct(struct_decl) = &module.items[1] else {
+            panic!("expected a struct, got {:?}", module.items[1]);
+        };
+        assert_eq!(struct_decl.fields[0].docs.as_deref(), Some("The field."));
+
+        Ok(())
+    }
+
+    #[test]
+    fn import_decl() -> Result<(), SyntaxError> {
+        let input = "import math; import math::trig as t;";
+
+        let ident = |text: &str, start, end| ast::Identifier {
+            text: text.to_string(),
+            span: Span { start, end },
+        };
+
+        let expected = ast::Module {
+            items: vec![
+                ast::Item::Import(ast::ImportDecl {
+                    id: ast::NodeId(0),
+                    docs: None,
+                    attributes: Vec::new(),
+                    path: vec![ident("math", 7, 11)],
+                    alias: None,
+                    span: Span { start: 0, end: 12 },
+                }),
+                ast::Item::Import(ast::ImportDecl {
+                    id: ast::NodeId(1),
+                    docs: None,
+                    attributes: Vec::new(),
+                    path: vec![ident("math", 20, 24), ident("trig", 26, 30)],
+                    alias: Some(ident("t", 34, 35)),
+                    span: Span { start: 13, end: 36 },
+                }),
+            ],
+            exprs: ast::ExprArena::default(),
+        };
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        assert_eq!(parser.parse_module(), (expected, Vec::new()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn type_expr() -> Result<(), SyntaxError> {
+        let inputs = [
+            ("i64", "i64"),
+            ("Map<str, List<i64>,>", "Map<str, List<i64>>"),
+            ("&*i64", "&*i64"),
+            ("[ [f64; 4]; 0x10 ]", "[[f64; 4]; 16]"),
+            ("[u8; SIZE]", "[u8; SIZE]"),
+            ("[u8; (SIZE+1)*2]", "[u8; (SIZE + 1) * 2]"),
+            ("()", "()"),
+            ("(i64,)", "(i64,)"),
+            ("(i64, &f64)", "(i64, &f64)"),
+            ("(i64)", "i64"),
+            ("fn(i64, fn()) -> bool", "fn(i64, fn()) -> bool"),
+        ];
+
+        for (input, expected) in inputs {
+            let tokens = Lexer::new(input).collect_tokens()?;
+            let mut parser = Parser::new(&tokens);
+            let ty = parser.parse_type()?;
+
+            assert_eq!(ty.display(parser.exprs()).to_string(), expected);
+        }
+
+        let tokens = Lexer::new("&List<i64>").collect_tokens()?;
+        let ty = Parser::new(&tokens).parse_type()?;
+
+        assert_eq!(ty.span(), Span { start: 0, end: 10 });
+
+        let tokens = Lexer::new("[i64]").collect_tokens()?;
+        let result = Parser::new(&tokens).parse_type();
+
+        assert_eq!(
+            result.map_err(|err| err.kind),
+            Err(SyntaxErrorKind::UnexpectedToken {
+                expected: &[TokenKind::Semicolon],
+                got: TokenKind::RBracket,
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn optional_types() -> Result<(), SyntaxError> {
+        let inputs = [
+            ("i64?", "i64?"),
+            ("List<i64?>??", "List<i64?>??"),
+            ("&i64?", "&i64?"),
+            ("(&i64)?", "(&i64)?"),
+            ("(fn() -> i64)?", "(fn() -> i64)?"),
+            ("fn() -> i64?", "fn() -> i64?"),
+        ];
+
+        for (input, expected) in inputs {
+            let tokens = Lexer::new(input).collect_tokens()?;
+            let mut parser = Parser::new(&tokens);
+            let ty = parser.parse_type()?;
+
+            assert_eq!(ty.display(parser.exprs()).to_string(), expected);
+        }
+
+        let tokens = Lexer::new("&i64?").collect_tokens()?;
+        let ty = Parser::new(&tokens).parse_type()?;
+
+        let ast::TypeExpr::Ref(reference) = &ty else {
+            panic!("expected a reference type, got {ty:?}");
+        };
+        assert!(matches!(*reference.inner, ast::TypeExpr::Optional(_)));
+        assert_eq!(reference.inner.span(), Span { start: 1, end: 5 });
+
+        let tokens = Lexer::new("match x { none => none, y => y }").collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+        let id = parser.parse_expr()?;
+
+        let ast::Expr::Match(match_expr) = &parser.exprs()[id] else {
+            panic!("expected a match, got {:?}", parser.exprs()[id]);
+        };
+        assert_eq!(
+            match_expr.arms[0].pattern,
+            ast::Pattern::None(Span { start: 10, end: 14 })
+        );
+        assert_eq!(
+            parser.exprs()[match_expr.arms[0].value],
+            ast::Expr::None(Span { start: 18, end: 22 })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn patterns() -> Result<(), SyntaxError> {
+        let input = r#"(_, none, x, -1, 2.5, "s", (a), (b,), (), Shape::Empty, Some(y,), P { x, y: (1, _), })"#;
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let pattern = Parser::new(&tokens).parse_pattern()?;
+
+        let ast::Pattern::Tuple(tuple) = &pattern else {
+            panic!("expected a tuple pattern, got {pattern:?}");
+        };
+        let kinds: Vec<_> = tuple
+            .elements
+            .iter()
+            .map(|element| match element {
+                ast::Pattern::Wildcard(_) => "wildcard",
+                ast::Pattern::None(_) => "none",
+                ast::Pattern::Binding(_) => "binding",
+                ast::Pattern::Integer(_) => "integer",
+                ast::Pattern::Float(_) => "float",
+                ast::Pattern::String(_) => "string",
+                ast::Pattern::Tuple(_) => "tuple",
+                ast::Pattern::Path(_) => "path",
+                ast::Pattern::TupleStruct(_) => "tuple struct",
+                ast::Pattern::Struct(_) => "struct",
+            })
+            .collect();
+
+        assert_eq!(
+            kinds,
+            [
+                "wildcard",
+                "none",
+                "binding",
+                "integer",
+                "float",
+                "string",
+                "binding",
+                "tuple",
+                "tuple",
+                "path",
+                "tuple struct",
+                "struct",
+            ]
+        );
+        assert_eq!(pattern.span(), Span { start: 0, end: 86 });
+        assert_eq!(
+            pattern.to_string(),
+            r#"(_, none, x, -1, 2.5, "s", a, (b,), (), Shape::Empty, Some(y), P { x, y: (1, _) })"#
+        );
+
+        let input = "let (a, Point { x, y: _ }): (i64, Point) = p;";
+        let tokens = Lexer::new(input).collect_tokens()?;
+
+        let (stmts, _) = Parser::new(&tokens).parse_stmts()?;
+        let [ast::Stmt::Let(let_stmt)] = &stmts[..] else {
+            panic!("expected a let statement, got {stmts:?}");
+        };
+        assert_eq!(let_stmt.pattern.to_string(), "(a, Point { x, y: _ })");
+        assert_eq!(let_stmt.pattern.span(), Span { start: 4, end: 26 });
+
+        Ok(())
+    }
+
+    #[test]
+    fn match_expr() -> Result<(), SyntaxError> {
+        let input = "match x { 0 => 1, n => { n } 2 => n, }";
+
+        let ident = |text: &str, start, end| {
+            ast::Expr::Identifier(ast::Identifier {
+                text: text.to_string(),
+                span: Span { start, end },
+            })
+        };
+        let integer = |value, start, end| {
+            ast::Expr::Integer(ast::IntegerLiteral {
+                value,
+                span: Span { start, end },
+            })
+        };
+
+        let mut exprs = ast::ExprArena::default();
+        let scrutinee = exprs.alloc(ident("x", 6, 7));
+        let first_arm = ast::MatchArm {
+            id: ast::NodeId(0),
+            pattern: ast::Pattern::Integer(ast::IntegerLiteral {
+                value: 0,
+                span: Span { start: 10, end: 11 },
+            }),
+            value: exprs.alloc(integer(1, 15, 16)),
+            span: Span { start: 10, end: 16 },
+        };
+        let tail = exprs.alloc(ident("n", 25, 26));
+        let second_arm = ast::MatchArm {
+            id: ast::NodeId(3),
+            pattern: ast::Pattern::Binding(ast::BindingPattern {
+                id: ast::NodeId(1),
+                name: ast::Identifier {
+                    text: "n".to_string(),
+                    span: Span { start: 18, end: 19 },
+                },
+            }),
+            value: exprs.alloc(ast::Expr::Block(Box::new(ast::Block {
+                id: ast::NodeId(2),
+                stmts: Vec::new(),
+                tail: Some(tail),
+                span: Span { start: 23, end: 28 },
+            }))),
+            span: Span { start: 18, end: 28 },
+        };
+        let third_arm = ast::MatchArm {
+            id: ast::NodeId(4),
+            pattern: ast::Pattern::Integer(ast::IntegerLiteral {
+                value: 2,
+                span: Span { start: 29, end: 30 },
+            }),
+            value: exprs.alloc(ident("n", 34, 35)),
+            span: Span { start: 29, end: 35 },
+        };
+        let expected = exprs.alloc(ast::Expr::Match(ast::MatchExpr {
+            scrutinee,
+            arms: Box::new([first_arm, second_arm, third_arm]),
+            span: Span { start: 0, end: 38 },
+        }));
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        assert_eq!(parser.parse_expr(), Ok(expected));
+        assert_eq!(parser.exprs(), &exprs);
+
+        let tokens = Lexer::new("match x { 0 1 }").collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        assert_eq!(
+            parser.parse_expr(),
+            Err(SyntaxError {
+                kind: SyntaxErrorKind::MissingArmArrow,
+                span: Span { start: 12, end: 13 },
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn tuple_expr() -> Result<(), SyntaxError> {
+        let integer = |value, start, end| {
+            ast::Expr::Integer(ast::IntegerLiteral {
+                value,
+                span: Span { start, end },
+            })
+        };
+
+        let mut unit = ast::ExprArena::default();
+        unit.alloc(ast::Expr::Tuple(ast::TupleExpr {
+            elements: Box::new([]),
+            span: Span { start: 0, end: 2 },
+        }));
+
+        let mut paren = ast::ExprArena::default();
+        let expr = paren.alloc(integer(1, 1, 2));
+        paren.alloc(ast::Expr::Paren(ast::ParenExpr {
+            expr,
+            span: Span { start: 0, end: 3 },
+        }));
+
+        let mut single = ast::ExprArena::default();
+        let element = single.alloc(integer(1, 1, 2));
+        single.alloc(ast::Expr::Tuple(ast::TupleExpr {
+            elements: Box::new([element]),
+            span: Span { start: 0, end: 4 },
+        }));
+
+        let mut triple = ast::ExprArena::default();
+        let elements = vec![
+            triple.alloc(integer(1, 1, 2)),
+            triple.alloc(ast::Expr::Float(ast::FloatLiteral {
+                value_bits: 2.0f64.to_bits(),
+                span: Span { start: 4, end: 7 },
+            })),
+            triple.alloc(ast::Expr::Identifier(ast::Identifier {
+                text: "x".to_string(),
+                span: Span { start: 9, end: 10 },
+            })),
+        ];
+        triple.alloc(ast::Expr::Tuple(ast::TupleExpr {
+            elements: elements.into(),
+            span: Span { start: 0, end: 11 },
+        }));
+
+        let test_case = [
+            ("()", unit),
+            ("(1)", paren),
+            ("(1,)", single),
+            ("(1, 2.0, x)", triple),
+        ];
+
+        for (input, expected) in test_case {
+            let tokens = Lexer::new(input).collect_tokens()?;
+            let mut parser = Parser::new(&tokens);
+
+            parser.parse_expr()?;
+            assert_eq!(parser.exprs(), &expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn string_literal() -> Result<(), SyntaxError> {
+        let tokens = Lexer::new(r#""a\tb\\\"ü\n""#).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        let id = parser.parse_expr_atom()?;
+
+        assert_eq!(
+            parser.exprs()[id],
+            ast::Expr::String(ast::StringLiteral {
+                value: "a\tb\\\"ü\n".to_string(),
+                span: Span { start: 0, end: 13 },
+            })
+        );
+
+        // the span points at the escape, counting chars like the rest of the spans.
+        let tokens = Lexer::new(r#"x = "ü\n\q""#).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        assert_eq!(
+            parser.parse_expr(),
+            Err(SyntaxError {
+                kind: SyntaxErrorKind::InvalidEscape,
+                span: Span { start: 8, end: 10 },
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn variant_construction() -> Result<(), SyntaxError> {
+        let input = "match Shape::Circle(1.0) { Shape::Circle(r) => r, Shape::Empty => 0.0 }";
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        let ident = |text: &str, start, end| ast::Identifier {
+            text: text.to_string(),
+            span: Span { start, end },
+        };
+
+        let id = parser.parse_expr()?;
+        let exprs = parser.exprs();
+
+        let ast::Expr::Match(match_expr) = &exprs[id] else {
+            panic!("expected a match, got {:?}", exprs[id]);
+        };
+        let ast::Expr::Call(call) = &exprs[match_expr.scrutinee] else {
+            panic!("expected a call, got {:?}", exprs[match_expr.scrutinee]);
+        };
+
+        assert_eq!(
+            exprs[call.callee],
+            ast::Expr::Path(ast::PathExpr {
+                segments: vec![ident("Shape", 6, 11), ident("Circle", 13, 19)],
+                span: Span { start: 6, end: 19 },
+            })
+        );
+        assert_eq!(call.span, Span { start: 6, end: 24 });
+        assert!(matches!(
+            match_expr.arms[0].pattern,
+            ast::Pattern::TupleStruct(_)
+        ));
+        assert!(matches!(match_expr.arms[1].pattern, ast::Pattern::Path(_)));
+
+        let tokens = Lexer::new("Shape::").collect_tokens()?;
+        assert_eq!(
+            Parser::new(&tokens).parse_expr(),
+            Err(SyntaxError::UNEXPECTED_EOI)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn assert_and_panic() -> Result<(), SyntaxError> {
+        let tokens = Lexer::new(r#"assert(x, "bad",)"#).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        let mut exprs = ast::ExprArena::default();
+        let cond = exprs.alloc(ast::Expr::Identifier(ast::Identifier {
+            text: "x".to_string(),
+            span: Span { start: 7, end: 8 },
+        }));
+        let message = exprs.alloc(ast::Expr::String(ast::StringLiteral {
+            value: "bad".to_string(),
+            span: Span { start: 10, end: 15 },
+        }));
+        let expected = exprs.alloc(ast::Expr::Assert(ast::AssertExpr {
+            cond,
+            message: Some(message),
+            span: Span { start: 0, end: 17 },
+        }));
+
+        assert_eq!(parser.parse_expr(), Ok(expected));
+        assert_eq!(parser.exprs(), &exprs);
+
+        let tokens = Lexer::new("panic()").collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+        let id = parser.parse_expr()?;
+
+        assert_eq!(
+            parser.exprs()[id],
+            ast::Expr::Panic(ast::PanicExpr {
+                message: None,
+                span: Span { start: 0, end: 7 },
+            })
+        );
+
+        let tokens = Lexer::new(r#"assert(x, "bad", 2)"#).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        assert_eq!(
+            parser.parse_expr().map_err(|err| err.kind),
+            Err(SyntaxErrorKind::UnexpectedToken {
+                expected: &[TokenKind::RParen],
+                got: TokenKind::Integer,
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn negative_literals() -> Result<(), SyntaxError> {
+        let tokens = Lexer::new("-9223372036854775808").collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+        let id = parser.parse_expr()?;
+        assert_eq!(
+            parser.exprs()[id],
+            ast::Expr::Integer(ast::IntegerLiteral {
+                value: i64::MIN.into(),
+                span: Span { start: 0, end: 20 },
+            })
+        );
+
+        let tokens = Lexer::new("- 1.5").collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+        let id = parser.parse_expr()?;
+        assert_eq!(
+            parser.exprs()[id],
+            ast::Expr::Float(ast::FloatLiteral {
+                value_bits: (-1.5f64).to_bits(),
+                span: Span { start: 0, end: 5 },
+            })
+        );
+
+        // only the minus right before the literal is folded.
+        for input in ["--1", "-(1)", "-1(2)"] {
+            let tokens = Lexer::new(input).collect_tokens()?;
+            let mut parser = Parser::new(&tokens);
+            let id = parser.parse_expr()?;
+            assert!(
+                matches!(parser.exprs()[id], ast::Expr::Unary(_)),
+                "{input} should stay a negation"
+            );
+        }
+
+        let tokens = Lexer::new("-1").collect_tokens()?;
+        let pattern = Parser::new(&tokens).parse_pattern()?;
+        assert_eq!(
+            pattern,
+            ast::Pattern::Integer(ast::IntegerLiteral {
+                value: -1,
+                span: Span { start: 0, end: 2 },
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn cast_expr() -> Result<(), SyntaxError> {
+        let tokens = Lexer::new("-x as i64 * 2 < y").collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        let ident = |text: &str, start, end| ast::Identifier {
+            text: text.to_string(),
+            span: Span { start, end },
+        };
+
+        let mut exprs = ast::ExprArena::default();
+        let operand = exprs.alloc(ast::Expr::Identifier(ident("x", 1, 2)));
+        let neg = exprs.alloc(ast::Expr::Unary(ast::UnaryExpr {
+            op: ast::UnaryOp::Neg,
+            operand,
+            span: Span { start: 0, end: 2 },
+        }));
+        let cast = exprs.alloc(ast::Expr::Cast(Box::new(ast::CastExpr {
+            expr: neg,
+            ty: ast::TypeExpr::Named(ast::NamedType {
+                id: ast::NodeId(0),
+                name: ident("i64", 6, 9),
+                args: Vec::new(),
+                span: Span { start: 6, end: 9 },
+            }),
+            span: Span { start: 0, end: 9 },
+        })));
+        let two = exprs.alloc(ast::Expr::Integer(ast::IntegerLiteral {
+            value: 2,
+            span: Span { start: 12, end: 13 },
+        }));
+        let mul = exprs.alloc(ast::Expr::Binary(ast::BinaryExpr {
+            op: ast::BinaryOp::Mul,
+            lhs: cast,
+            rhs: two,
+            span: Span { start: 0, end: 13 },
+        }));
+        let y = exprs.alloc(ast::Expr::Identifier(ident("y", 16, 17)));
+        let expected = exprs.alloc(ast::Expr::Binary(ast::BinaryExpr {
+            op: ast::BinaryOp::LessThan,
+            lhs: mul,
+            rhs: y,
+            span: Span { start: 0, end: 17 },
+        }));
+
+        assert_eq!(parser.parse_expr(), Ok(expected));
+        assert_eq!(parser.exprs(), &exprs);
+
+        Ok(())
+    }
+
+    #[test]
+    fn range_expr() -> Result<(), SyntaxError> {
+        let tokens = Lexer::new("i = 0..=n + 1").collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        let ident = |text: &str, start, end| ast::Identifier {
+            text: text.to_string(),
+            span: Span { start, end },
+        };
+        let integer = |value, start, end| {
+            ast::Expr::Integer(ast::IntegerLiteral {
+                value,
+                span: Span { start, end },
+            })
+        };
+
+        let mut exprs = ast::ExprArena::default();
+        let target = exprs.alloc(ast::Expr::Identifier(ident("i", 0, 1)));
+        let start = exprs.alloc(integer(0, 4, 5));
+        let lhs = exprs.alloc(ast::Expr::Identifier(ident("n", 8, 9)));
+        let rhs = exprs.alloc(integer(1, 12, 13));
+        let end = exprs.alloc(ast::Expr::Binary(ast::BinaryExpr {
+            op: ast::BinaryOp::Add,
+            lhs,
+            rhs,
+            span: Span { start: 8, end: 13 },
+        }));
+        let value = exprs.alloc(ast::Expr::Range(ast::RangeExpr {
+            kind: ast::RangeKind::Inclusive,
+            start,
+            end,
+            span: Span { start: 4, end: 13 },
+        }));
+        let expected = exprs.alloc(ast::Expr::Assign(ast::AssignExpr {
+            target,
+            value,
+            span: Span { start: 0, end: 13 },
+        }));
+
+        assert_eq!(parser.parse_expr(), Ok(expected));
+        assert_eq!(parser.exprs(), &exprs);
+
+        let tokens = Lexer::new("0..1..2").collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        assert!(parser.parse_expr().is_ok());
+        assert_eq!(
+            parser.parse_expr().map_err(|err| err.kind),
+            Err(SyntaxErrorKind::UnexpectedToken {
+                expected: &[
+                    TokenKind::Identifier,
+                    TokenKind::Integer,
+                    TokenKind::Float,
+                    TokenKind::String,
+                    TokenKind::None,
+                    TokenKind::LParen,
+                    TokenKind::LBrace,
+                    TokenKind::If,
+                    TokenKind::While,
+                    TokenKind::Loop,
+                    TokenKind::Match,
+                    TokenKind::Assert,
+                    TokenKind::Panic,
+                ],
+                got: TokenKind::DotDot,
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn recovery() -> Result<(), SyntaxError> {
+        let input = "fn a() { let = 1; b(; c } enum 1 fn d() { e + }";
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        let (module, errors) = parser.parse_module();
+        let errors: Vec<_> = errors.into_iter().map(|err| err.span).collect();
+
+        assert_eq!(
+            errors,
+            [
+                Span { start: 13, end: 14 },
+                Span { start: 20, end: 21 },
+                Span { start: 31, end: 32 },
+                Span { start: 46, end: 47 },
+            ]
+        );
+
+        assert_eq!(module.items.len(), 3);
+        assert_eq!(
+            module.items[1],
+            ast::Item::Error(Span { start: 26, end: 32 })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn error_nodes() -> Result<(), SyntaxError> {
+        let tokens = Lexer::new("let x = 1 +; y(; let z = 2; z").collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        let (stmts, tail) = parser.parse_stmts()?;
+
+        assert_eq!(parser.errors().len(), 2);
+        assert_eq!(stmts.len(), 3);
+        assert!(tail.is_some());
+
+        let ast::Stmt::Let(let_stmt) = &stmts[0] else {
+            panic!("expected a let statement, got {:?}", stmts[0]);
+        };
+        assert_eq!(let_stmt.pattern.to_string(), "x");
+        assert_eq!(
+            let_stmt.value.map(|id| &parser.exprs()[id]),
+            Some(&ast::Expr::Error(Span { start: 8, end: 11 }))
+        );
+        assert_eq!(let_stmt.span, Span { start: 0, end: 12 });
+
+        assert_eq!(stmts[1], ast::Stmt::Error(Span { start: 13, end: 16 }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn node_spans() -> Result<(), SyntaxError> {
+        let input = "import a; fn f() { let x = 1; return x; } enum E { A }";
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let mut parser = Parser::new(&tokens);
+
+        let (module, errors) = parser.parse_module();
+        assert!(errors.is_empty());
+
+        let spans: Vec<_> = module.items.iter().map(ast::Item::span).collect();
+        assert_eq!(
+            spans,
+            [
+                Span { start: 0, end: 9 },
+                Span { start: 10, end: 41 },
+                Span { start: 42, end: 54 },
+            ]
+        );
+
+        let ast::Item::Func(func) = &module.items[1] else {
+            panic!("expected a function, got {:?}", module.items[1]);
+        };
+        let spans: Vec<_> = func.body.stmts.iter().map(ast::Stmt::span).collect();
+        assert_eq!(
+            spans,
+            [Span { start: 19, end: 29 }, Span { start: 30, end: 39 }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn trailing_commas() -> Result<(), SyntaxError> {
+        let input = "
+            fn f(a: Map<i64, fn(i64,),>,) { f(1, 2,); }
+            enum E { A(i64,), B { x: i64, }, }
+        ";
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let (module, errors) = Parser::new(&tokens).parse_module();
+
+        assert_eq!(errors, Vec::new());
+        assert_eq!(
+            ast::pretty::print(&module),
+            "fn f(a: Map<i64, fn(i64)>) {\n    f(1, 2);\n}\n\nenum E {\n    A(i64),\n    B { x: i64 },\n}\n"
+        );
+
+        let tokens = Lexer::new("enum E { A B }").collect_tokens()?;
+        let (_, errors) = Parser::new(&tokens).parse_module();
+
+        assert_eq!(
+            errors,
+            [SyntaxError {
+                kind: SyntaxErrorKind::UnexpectedToken {
+                    expected: &[TokenKind::Comma, TokenKind::RBrace],
+                    got: TokenKind::Identifier,
+                },
+                span: Span { start: 11, end: 12 },
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn precedence_table() -> Result<(), SyntaxError> {
+        /// Renders the expression with every operator application parenthesized.
+        fn grouped(exprs: &ast::ExprArena, id: ast::ExprId) -> String {
+            match &exprs[id] {
+                ast::Expr::Identifier(ident) => ident.text.clone(),
+                ast::Expr::Binary(binary) => format!(
+                    "({} {} {})",
+                    grouped(exprs, binary.lhs),
+                    binary.op.symbol(),
+                    grouped(exprs, binary.rhs)
+                ),
+                ast::Expr::Assign(assign) => format!(
+                    "({} = {})",
+                    grouped(exprs, assign.target),
+                    grouped(exprs, assign.value)
+                ),
+                ast::Expr::Range(range) => format!(
+                    "({}..{})",
+                    grouped(exprs, range.start),
+                    grouped(exprs, range.end)
+                ),
+                expr => panic!("unexpected expression {expr:?}"),
+            }
+        }
+
+        let test_cases = [
+            ("a - b - c", "((a - b) - c)"),
+            ("a = b = c", "(a = (b = c))"),
+            ("a + b * c == d", "((a + (b * c)) == d)"),
+            ("a < b != c % d", "((a < b) != (c % d))"),
+            ("a = b..c + d", "(a = (b..(c + d)))"),
+        ];
+
+        for (input, expected) in test_cases {
+            let tokens = Lexer::new(input).collect_tokens()?;
+            let mut parser = Parser::new(&tokens);
+
+            let id = parser.parse_expr()?;
+            assert_eq!(grouped(parser.exprs(), id), expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn deep_nesting() -> Result<(), SyntaxError> {
+        let depth = 10_000;
+
+        let inputs = [
+            format!("{}1{}", "(".repeat(depth), ")".repeat(depth)),
+            format!("{}1{}", "{".repeat(depth), "}".repeat(depth)),
+            format!("{}1", "-".repeat(depth)),
+            format!("{}1", "a = ".repeat(depth)),
+            format!("{}1{}", "if x { ".repeat(depth), " }".repeat(depth)),
+            format!("1 as {}i64", "&".repeat(depth)),
+        ];
+
+        // Debug builds use several kilobytes of stack per nesting level, so
+        // reaching the default limit needs more than the test thread's 2 MiB.
+        let parse = move || -> Result<(), SyntaxError> {
+            for input in inputs {
+                let tokens = Lexer::new(&input).collect_tokens()?;
+                let mut parser = Parser::new(&tokens);
+
+                // statements recover from the error, so it may only be recorded.
+                let result = parser.parse_expr().err();
+                let mut errors = result.iter().chain(parser.errors()).map(|err| err.kind);
+
+                assert!(errors.any(|kind| {
+                    kind == SyntaxErrorKind::LimitExceeded(Limit::ParseNestingDepth)
+                }));
+            }
+
+            Ok(())
+        };
+
+        std::thread::Builder::new()
+            .stack_size(16 * 1024 * 1024)
+            .spawn(parse)
+            .unwrap()
+            .join()
+            .unwrap()
     }
 }
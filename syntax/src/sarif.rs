@@ -0,0 +1,419 @@
+use std::{
+    fmt::{self, Write},
+    io,
+};
+
+use crate::{
+    diagnostics::{Diagnostic, EmitDiagnostic, Severity},
+    explain::explain,
+    source::{FileId, SourceDatabase},
+    token::Span,
+};
+
+/// The minimal JSON model needed to write a SARIF log.
+enum Json {
+    String(String),
+    Number(usize),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn string(text: impl Into<String>) -> Self {
+        Json::String(text.into())
+    }
+
+    /// Writes the value indented by the given level, without a trailing line break.
+    fn write(&self, out: &mut String, level: usize) -> fmt::Result {
+        let indent = "  ".repeat(level);
+
+        match self {
+            Json::String(text) => write!(out, "\"{}\"", escape(text)),
+            Json::Number(number) => write!(out, "{number}"),
+            Json::Array(items) if items.is_empty() => write!(out, "[]"),
+            Json::Array(items) => {
+                writeln!(out, "[")?;
+                for (index, item) in items.iter().enumerate() {
+                    write!(out, "{indent}  ")?;
+                    item.write(out, level + 1)?;
+                    writeln!(out, "{}", if index + 1 < items.len() { "," } else { "" })?;
+                }
+                write!(out, "{indent}]")
+            }
+            Json::Object(fields) => {
+                writeln!(out, "{{")?;
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    write!(out, "{indent}  \"{key}\": ")?;
+                    value.write(out, level + 1)?;
+                    writeln!(out, "{}", if index + 1 < fields.len() { "," } else { "" })?;
+                }
+                write!(out, "{indent}}}")
+            }
+        }
+    }
+}
+
+/// Escapes a string for use inside a quoted JSON string.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            ch if ch.is_control() => write!(escaped, "\\u{:04x}", ch as u32).unwrap(),
+            ch => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+/// Renders the given [Diagnostic]s as a SARIF 2.1.0 log, for code scanning tools.
+///
+/// Every distinct code becomes a rule described by its explanation, and labels and
+/// suggestions become related locations and fixes.
+pub fn to_sarif(db: &SourceDatabase, diagnostics: &[Diagnostic]) -> String {
+    let mut codes: Vec<_> = diagnostics.iter().filter_map(|d| d.code).collect();
+    codes.sort_unstable();
+    codes.dedup();
+
+    let rules = codes
+        .into_iter()
+        .map(|code| {
+            let mut rule = vec![("id", Json::string(code))];
+            if let Some(summary) = explain(code).and_then(|text| text.lines().next()) {
+                let text = Json::Object(vec![("text", Json::string(summary))]);
+                rule.push(("shortDescription", text));
+            }
+            Json::Object(rule)
+        })
+        .collect();
+
+    let results = diagnostics
+        .iter()
+        .map(|diagnostic| result(db, diagnostic))
+        .collect();
+
+    let log = Json::Object(vec![
+        (
+            "$schema",
+            Json::string("https://json.schemastore.org/sarif-2.1.0.json"),
+        ),
+        ("version", Json::string("2.1.0")),
+        (
+            "runs",
+            Json::Array(vec![Json::Object(vec![
+                (
+                    "tool",
+                    Json::Object(vec![(
+                        "driver",
+                        Json::Object(vec![
+                            ("name", Json::string("elanc")),
+                            ("rules", Json::Array(rules)),
+                        ]),
+                    )]),
+                ),
+                // spans count chars, so columns do as well.
+                ("columnKind", Json::string("unicodeCodePoints")),
+                ("results", Json::Array(results)),
+            ])]),
+        ),
+    ]);
+
+    let mut out = String::new();
+    log.write(&mut out, 0).unwrap();
+    out.push('\n');
+    out
+}
+
+/// Collects the emitted diagnostics and writes them as a single SARIF log once finished.
+#[derive(Debug)]
+pub struct SarifEmitter<W> {
+    out: W,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<W: io::Write> SarifEmitter<W> {
+    /// Creates a new [SarifEmitter] writing the log to `out`.
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+impl<W: io::Write> EmitDiagnostic for SarifEmitter<W> {
+    fn emit(&mut self, _db: &SourceDatabase, diagnostic: &Diagnostic) {
+        self.diagnostics.push(diagnostic.clone());
+    }
+
+    fn finish(&mut self, db: &SourceDatabase) {
+        let log = to_sarif(db, &self.diagnostics);
+        let _ = self.out.write_all(log.as_bytes());
+        self.diagnostics.clear();
+    }
+}
+
+fn result(db: &SourceDatabase, diagnostic: &Diagnostic) -> Json {
+    let level = match diagnostic.severity {
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    };
+
+    let mut text = diagnostic.message.clone();
+    for note in &diagnostic.notes {
+        text.push('\n');
+        text.push_str(note);
+    }
+
+    let mut fields = Vec::new();
+    if let Some(code) = diagnostic.code {
+        fields.push(("ruleId", Json::string(code)));
+    }
+    fields.push(("level", Json::string(level)));
+    fields.push(("message", Json::Object(vec![("text", Json::string(text))])));
+
+    // locations can't be given without knowing the file.
+    let Some(file) = diagnostic.file else {
+        return Json::Object(fields);
+    };
+
+    let location = Json::Object(vec![(
+        "physicalLocation",
+        physical_location(db, file, diagnostic.primary_span),
+    )]);
+    fields.push(("locations", Json::Array(vec![location])));
+
+    if !diagnostic.labels.is_empty() {
+        let related = diagnostic
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(index, label)| {
+                Json::Object(vec![
+                    ("id", Json::Number(index)),
+                    (
+                        "physicalLocation",
+                        physical_location(db, label.file.unwrap_or(file), label.span),
+                    ),
+                    (
+                        "message",
+                        Json::Object(vec![("text", Json::string(&label.message))]),
+                    ),
+                ])
+            })
+            .collect();
+        fields.push(("relatedLocations", Json::Array(related)));
+    }
+
+    if !diagnostic.suggestions.is_empty() {
+        let fixes = diagnostic
+            .suggestions
+            .iter()
+            .map(|suggestion| {
+                let replacement = Json::Object(vec![
+                    ("deletedRegion", region(db, file, suggestion.span)),
+                    (
+                        "insertedContent",
+                        Json::Object(vec![("text", Json::string(&suggestion.replacement))]),
+                    ),
+                ]);
+                let change = Json::Object(vec![
+                    ("artifactLocation", artifact_location(db, file)),
+                    ("replacements", Json::Array(vec![replacement])),
+                ]);
+
+                Json::Object(vec![
+                    (
+                        "description",
+                        Json::Object(vec![("text", Json::string(&suggestion.message))]),
+                    ),
+                    ("artifactChanges", Json::Array(vec![change])),
+                ])
+            })
+            .collect();
+        fields.push(("fixes", Json::Array(fixes)));
+    }
+
+    Json::Object(fields)
+}
+
+fn artifact_location(db: &SourceDatabase, file: FileId) -> Json {
+    Json::Object(vec![("uri", Json::string(db.get(file).name()))])
+}
+
+fn physical_location(db: &SourceDatabase, file: FileId, span: Span) -> Json {
+    Json::Object(vec![
+        ("artifactLocation", artifact_location(db, file)),
+        ("region", region(db, file, span)),
+    ])
+}
+
+fn region(db: &SourceDatabase, file: FileId, span: Span) -> Json {
+    let file = db.get(file);
+    let start = file.line_col(span);
+    let end = file.offset_line_col(span.end.max(span.start));
+
+    Json::Object(vec![
+        ("startLine", Json::Number(start.line)),
+        ("startColumn", Json::Number(start.col)),
+        ("endLine", Json::Number(end.line)),
+        ("endColumn", Json::Number(end.col)),
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        diagnostics::{Applicability, Diagnostic},
+        sarif::to_sarif,
+        source::{SourceDatabase, SourceFile},
+        token::Span,
+    };
+
+    #[test]
+    fn log() {
+        let mut db = SourceDatabase::default();
+        let file = db.add(SourceFile::new(
+            "src/main.elan",
+            "let x = 1\nlet y = \"ü\";",
+        ));
+
+        let diagnostics = [
+            Diagnostic::error("expected `;`", Span { start: 9, end: 9 })
+                .with_code("E0010")
+                .with_file(file)
+                .with_suggestion(
+                    "add `;` here",
+                    Span { start: 9, end: 9 },
+                    ";",
+                    Applicability::MaybeIncorrect,
+                ),
+            Diagnostic::warning("a \"quoted\" message", Span { start: 18, end: 21 })
+                .with_file(file)
+                .with_label(Span { start: 4, end: 5 }, "declared here")
+                .with_note("a note"),
+        ];
+
+        let expected = r#"{
+  "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+  "version": "2.1.0",
+  "runs": [
+    {
+      "tool": {
+        "driver": {
+          "name": "elanc",
+          "rules": [
+            {
+              "id": "E0010",
+              "shortDescription": {
+                "text": "A statement isn't terminated by `;`."
+              }
+            }
+          ]
+        }
+      },
+      "columnKind": "unicodeCodePoints",
+      "results": [
+        {
+          "ruleId": "E0010",
+          "level": "error",
+          "message": {
+            "text": "expected `;`"
+          },
+          "locations": [
+            {
+              "physicalLocation": {
+                "artifactLocation": {
+                  "uri": "src/main.elan"
+                },
+                "region": {
+                  "startLine": 1,
+                  "startColumn": 10,
+                  "endLine": 1,
+                  "endColumn": 10
+                }
+              }
+            }
+          ],
+          "fixes": [
+            {
+              "description": {
+                "text": "add `;` here"
+              },
+              "artifactChanges": [
+                {
+                  "artifactLocation": {
+                    "uri": "src/main.elan"
+                  },
+                  "replacements": [
+                    {
+                      "deletedRegion": {
+                        "startLine": 1,
+                        "startColumn": 10,
+                        "endLine": 1,
+                        "endColumn": 10
+                      },
+                      "insertedContent": {
+                        "text": ";"
+                      }
+                    }
+                  ]
+                }
+              ]
+            }
+          ]
+        },
+        {
+          "level": "warning",
+          "message": {
+            "text": "a \"quoted\" message\nnote: a note"
+          },
+          "locations": [
+            {
+              "physicalLocation": {
+                "artifactLocation": {
+                  "uri": "src/main.elan"
+                },
+                "region": {
+                  "startLine": 2,
+                  "startColumn": 9,
+                  "endLine": 2,
+                  "endColumn": 12
+                }
+              }
+            }
+          ],
+          "relatedLocations": [
+            {
+              "id": 0,
+              "physicalLocation": {
+                "artifactLocation": {
+                  "uri": "src/main.elan"
+                },
+                "region": {
+                  "startLine": 1,
+                  "startColumn": 5,
+                  "endLine": 1,
+                  "endColumn": 6
+                }
+              },
+              "message": {
+                "text": "declared here"
+              }
+            }
+          ]
+        }
+      ]
+    }
+  ]
+}
+"#;
+        assert_eq!(to_sarif(&db, &diagnostics), expected);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::token::Span;
+
+/// A 1-based line and column, counted in chars like the offsets of a [Span].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// Identifies a [SourceFile] in a [SourceDatabase].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FileId(pub u32);
+
+/// A [Span] in a specific file of a [SourceDatabase].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileSpan {
+    pub file: FileId,
+    pub span: Span,
+}
+
+/// A source file whose line starts are precomputed to turn [Span]s into lines and columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    name: String,
+    text: String,
+    /// The char and byte offset each line starts at.
+    line_starts: Vec<(usize, usize)>,
+    /// The length of the text in chars.
+    len: usize,
+}
+
+impl SourceFile {
+    /// Creates a new [SourceFile] with the given name, usually its path, and text.
+    pub fn new(name: impl Into<String>, text: impl Into<String>) -> Self {
+        let text = text.into();
+        let mut line_starts = vec![(0, 0)];
+        let mut len = 0;
+
+        for (byte, ch) in text.char_indices() {
+            len += 1;
+            if ch == '\n' {
+                line_starts.push((len, byte + 1));
+            }
+        }
+
+        Self {
+            name: name.into(),
+            text,
+            line_starts,
+            len,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the number of lines, where an empty file has a single empty line.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Returns the position the given [Span] starts at, where [Span::EOI] and other
+    /// offsets past the end are placed at the end of the file.
+    pub fn line_col(&self, span: Span) -> LineCol {
+        self.offset_line_col(span.start)
+    }
+
+    /// Returns the position of the given char offset, see [SourceFile::line_col].
+    pub fn offset_line_col(&self, offset: usize) -> LineCol {
+        let offset = offset.min(self.len);
+        let index = self.line_index(offset);
+
+        LineCol {
+            line: index + 1,
+            col: offset - self.line_starts[index].0 + 1,
+        }
+    }
+
+    /// Returns the text of the given 1-based line without its line break.
+    pub fn line_text(&self, line: usize) -> Option<&str> {
+        let &(_, start) = self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self
+            .line_starts
+            .get(line)
+            .map_or(self.text.len(), |&(_, byte)| byte);
+
+        let text = &self.text[start..end];
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        Some(text.strip_suffix('\r').unwrap_or(text))
+    }
+
+    /// Returns the source text the given [Span] covers, clamped to the end of the file.
+    pub fn snippet(&self, span: Span) -> &str {
+        let start = self.byte_offset(span.start);
+        let end = self.byte_offset(span.end).max(start);
+
+        &self.text[start..end]
+    }
+
+    /// Returns the index of the line containing the given char offset.
+    fn line_index(&self, offset: usize) -> usize {
+        self.line_starts
+            .partition_point(|&(start, _)| start <= offset)
+            .saturating_sub(1)
+    }
+
+    /// Converts the given char offset into a byte offset into the text.
+    fn byte_offset(&self, offset: usize) -> usize {
+        let offset = offset.min(self.len);
+        let (line_start, byte_start) = self.line_starts[self.line_index(offset)];
+
+        self.text[byte_start..]
+            .char_indices()
+            .nth(offset - line_start)
+            .map_or(self.text.len(), |(byte, _)| byte_start + byte)
+    }
+}
+
+/// Owns all loaded [SourceFile]s, handing out a [FileId] for each of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceDatabase {
+    files: Vec<SourceFile>,
+}
+
+impl SourceDatabase {
+    /// Adds the given [SourceFile], returning the [FileId] it can be retrieved with.
+    pub fn add(&mut self, file: SourceFile) -> FileId {
+        let id = FileId(self.files.len() as u32);
+        self.files.push(file);
+        id
+    }
+
+    /// Returns the [SourceFile] with the given [FileId].
+    ///
+    /// # Panics
+    /// If the [FileId] was handed out by another [SourceDatabase].
+    pub fn get(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0 as usize]
+    }
+
+    /// Returns the [FileId] of the first file with the given name, if one was added.
+    pub fn find(&self, name: &str) -> Option<FileId> {
+        self.files()
+            .find(|(_, file)| file.name() == name)
+            .map(|(id, _)| id)
+    }
+
+    /// Returns an iterator over all files in the order they were added.
+    pub fn files(&self) -> impl Iterator<Item = (FileId, &SourceFile)> {
+        self.files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| (FileId(index as u32), file))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        source::{FileId, LineCol, SourceDatabase, SourceFile},
+        token::Span,
+    };
+
+    #[test]
+    fn line_col() {
+        let file = SourceFile::new("main.elan", "fn main() {\r\n  let ü = 1;\n}");
+
+        assert_eq!(file.line_count(), 3);
+        assert_eq!(
+            file.line_col(Span { start: 0, end: 2 }),
+            LineCol { line: 1, col: 1 }
+        );
+        assert_eq!(
+            file.line_col(Span { start: 19, end: 20 }),
+            LineCol { line: 2, col: 7 }
+        );
+        assert_eq!(file.line_col(Span::EOI), LineCol { line: 3, col: 2 });
+
+        assert_eq!(file.line_text(1), Some("fn main() {"));
+        assert_eq!(file.line_text(2), Some("  let ü = 1;"));
+        assert_eq!(file.line_text(3), Some("}"));
+        assert_eq!(file.line_text(0), None);
+        assert_eq!(file.line_text(4), None);
+
+        assert_eq!(file.snippet(Span { start: 15, end: 21 }), "let ü ");
+        assert_eq!(file.snippet(Span::EOI), "");
+    }
+
+    #[test]
+    fn database() {
+        let mut db = SourceDatabase::default();
+
+        let main = db.add(SourceFile::new("main.elan", "import lib;"));
+        let lib = db.add(SourceFile::new("lib.elan", "fn f() {}"));
+
+        assert_ne!(main, lib);
+        assert_eq!(db.get(lib).text(), "fn f() {}");
+        assert_eq!(db.find("main.elan"), Some(main));
+        assert_eq!(db.find("other.elan"), None);
+
+        let names: Vec<_> = db.files().map(|(id, file)| (id, file.name())).collect();
+        assert_eq!(names, [(FileId(0), "main.elan"), (FileId(1), "lib.elan")]);
+    }
+}
@@ -1,4 +1,4 @@
-use std::usize;
+use std::fmt;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Token<'src> {
@@ -8,16 +8,46 @@ pub struct Token<'src> {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TokenKind {
     Identifier,
     Integer,
     Float,
+    String,
+    /// A `///` comment documenting the following item, whose text includes the slashes.
+    DocComment,
+
+    Import,
+    Pub,
+    As,
+    Fn,
+    Const,
+    Struct,
+    Enum,
+    Trait,
+    Let,
+    Var,
+    Mut,
+    Return,
+    If,
+    Else,
+    Match,
+    While,
+    For,
+    In,
+    Loop,
+    Break,
+    Continue,
+    None,
+    Assert,
+    Panic,
 
     Plus,
     Minus,
     Asterisk,
     Slash,
     Percent,
+    Ampersand,
 
     Assign,
     Bang,
@@ -29,11 +59,130 @@ pub enum TokenKind {
     GreaterEqual,
 
     Dot,
+    DotDot,
+    DotDotEq,
+    Comma,
+    At,
+    Question,
+    Colon,
+    ColonColon,
+    Semicolon,
+    Arrow,
+    FatArrow,
     LParen,
     RParen,
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+}
+
+impl TokenKind {
+    /// Returns the keyword [TokenKind] for the given identifier text, if it is one.
+    pub fn keyword(text: &str) -> Option<TokenKind> {
+        Some(match text {
+            "import" => TokenKind::Import,
+            "pub" => TokenKind::Pub,
+            "as" => TokenKind::As,
+            "fn" => TokenKind::Fn,
+            "const" => TokenKind::Const,
+            "struct" => TokenKind::Struct,
+            "enum" => TokenKind::Enum,
+            "trait" => TokenKind::Trait,
+            "let" => TokenKind::Let,
+            "var" => TokenKind::Var,
+            "mut" => TokenKind::Mut,
+            "return" => TokenKind::Return,
+            "if" => TokenKind::If,
+            "else" => TokenKind::Else,
+            "match" => TokenKind::Match,
+            "while" => TokenKind::While,
+            "for" => TokenKind::For,
+            "in" => TokenKind::In,
+            "loop" => TokenKind::Loop,
+            "break" => TokenKind::Break,
+            "continue" => TokenKind::Continue,
+            "none" => TokenKind::None,
+            "assert" => TokenKind::Assert,
+            "panic" => TokenKind::Panic,
+            _ => return None,
+        })
+    }
+}
+
+/// Describes the [TokenKind] in diagnostics, spelling out the fixed text of keywords
+/// and symbols like `` `;` `` and naming the others like `identifier`.
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            TokenKind::Identifier => return write!(f, "identifier"),
+            TokenKind::Integer => return write!(f, "integer"),
+            TokenKind::Float => return write!(f, "float"),
+            TokenKind::String => return write!(f, "string"),
+            TokenKind::DocComment => return write!(f, "doc comment"),
+            TokenKind::Import => "import",
+            TokenKind::Pub => "pub",
+            TokenKind::As => "as",
+            TokenKind::Fn => "fn",
+            TokenKind::Const => "const",
+            TokenKind::Struct => "struct",
+            TokenKind::Enum => "enum",
+            TokenKind::Trait => "trait",
+            TokenKind::Let => "let",
+            TokenKind::Var => "var",
+            TokenKind::Mut => "mut",
+            TokenKind::Return => "return",
+            TokenKind::If => "if",
+            TokenKind::Else => "else",
+            TokenKind::Match => "match",
+            TokenKind::While => "while",
+            TokenKind::For => "for",
+            TokenKind::In => "in",
+            TokenKind::Loop => "loop",
+            TokenKind::Break => "break",
+            TokenKind::Continue => "continue",
+            TokenKind::None => "none",
+            TokenKind::Assert => "assert",
+            TokenKind::Panic => "panic",
+            TokenKind::Plus => "+",
+            TokenKind::Minus => "-",
+            TokenKind::Asterisk => "*",
+            TokenKind::Slash => "/",
+            TokenKind::Percent => "%",
+            TokenKind::Ampersand => "&",
+            TokenKind::Assign => "=",
+            TokenKind::Bang => "!",
+            TokenKind::Equal => "==",
+            TokenKind::Unequal => "!=",
+            TokenKind::LessThan => "<",
+            TokenKind::LessEqual => "<=",
+            TokenKind::GreaterThan => ">",
+            TokenKind::GreaterEqual => ">=",
+            TokenKind::Dot => ".",
+            TokenKind::DotDot => "..",
+            TokenKind::DotDotEq => "..=",
+            TokenKind::Comma => ",",
+            TokenKind::At => "@",
+            TokenKind::Question => "?",
+            TokenKind::Colon => ":",
+            TokenKind::ColonColon => "::",
+            TokenKind::Semicolon => ";",
+            TokenKind::Arrow => "->",
+            TokenKind::FatArrow => "=>",
+            TokenKind::LParen => "(",
+            TokenKind::RParen => ")",
+            TokenKind::LBrace => "{",
+            TokenKind::RBrace => "}",
+            TokenKind::LBracket => "[",
+            TokenKind::RBracket => "]",
+        };
+
+        write!(f, "`{text}`")
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Span {
     pub start: usize,
     pub end: usize,
@@ -44,4 +193,12 @@ impl Span {
         start: usize::MAX,
         end: usize::MAX,
     };
+
+    /// Returns a [Span] reaching from the start of this [Span] to the end of `other`.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            start: self.start,
+            end: other.end,
+        }
+    }
 }
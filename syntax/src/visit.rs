@@ -0,0 +1,765 @@
+use std::mem;
+
+use crate::ast;
+
+/// Visits the nodes of an AST, whose [ast::Expr]s are looked up in [Visitor::exprs].
+///
+/// Each `visit_*` method defaults to the matching `walk_*` function, which visits
+/// the children of the node. Overrides call it to keep descending.
+pub trait Visitor<'ast>: Sized {
+    /// Returns the arena the visited [ast::ExprId]s refer to.
+    fn exprs(&self) -> &'ast ast::ExprArena;
+
+    fn visit_module(&mut self, module: &'ast ast::Module) {
+        walk_module(self, module);
+    }
+
+    fn visit_item(&mut self, item: &'ast ast::Item) {
+        walk_item(self, item);
+    }
+
+    fn visit_attribute(&mut self, attr: &'ast ast::Attribute) {
+        walk_attribute(self, attr);
+    }
+
+    fn visit_import(&mut self, _import: &'ast ast::ImportDecl) {}
+
+    fn visit_func(&mut self, func: &'ast ast::FuncDecl) {
+        walk_func(self, func);
+    }
+
+    fn visit_param(&mut self, param: &'ast ast::Param) {
+        walk_param(self, param);
+    }
+
+    fn visit_const(&mut self, const_decl: &'ast ast::ConstDecl) {
+        walk_const(self, const_decl);
+    }
+
+    fn visit_struct(&mut self, struct_decl: &'ast ast::StructDecl) {
+        walk_struct(self, struct_decl);
+    }
+
+    fn visit_enum(&mut self, enum_decl: &'ast ast::EnumDecl) {
+        walk_enum(self, enum_decl);
+    }
+
+    fn visit_variant(&mut self, variant: &'ast ast::Variant) {
+        walk_variant(self, variant);
+    }
+
+    fn visit_trait(&mut self, trait_decl: &'ast ast::TraitDecl) {
+        walk_trait(self, trait_decl);
+    }
+
+    fn visit_trait_method(&mut self, method: &'ast ast::TraitMethod) {
+        walk_trait_method(self, method);
+    }
+
+    fn visit_type(&mut self, ty: &'ast ast::TypeExpr) {
+        walk_type(self, ty);
+    }
+
+    fn visit_block(&mut self, block: &'ast ast::Block) {
+        walk_block(self, block);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast ast::Stmt) {
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, id: ast::ExprId) {
+        walk_expr(self, id);
+    }
+
+    fn visit_match_arm(&mut self, arm: &'ast ast::MatchArm) {
+        walk_match_arm(self, arm);
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast ast::Pattern) {
+        walk_pattern(self, pattern);
+    }
+}
+
+pub fn walk_module<'ast, V: Visitor<'ast>>(visitor: &mut V, module: &'ast ast::Module) {
+    for item in &module.items {
+        visitor.visit_item(item);
+    }
+}
+
+pub fn walk_item<'ast, V: Visitor<'ast>>(visitor: &mut V, item: &'ast ast::Item) {
+    for attr in item.attributes() {
+        visitor.visit_attribute(attr);
+    }
+
+    match item {
+        ast::Item::Import(import) => visitor.visit_import(import),
+        ast::Item::Func(func) => visitor.visit_func(func),
+        ast::Item::Const(const_decl) => visitor.visit_const(const_decl),
+        ast::Item::Struct(struct_decl) => visitor.visit_struct(struct_decl),
+        ast::Item::Enum(enum_decl) => visitor.visit_enum(enum_decl),
+        ast::Item::Trait(trait_decl) => visitor.visit_trait(trait_decl),
+        ast::Item::Error(_) => {}
+    }
+}
+
+pub fn walk_attribute<'ast, V: Visitor<'ast>>(visitor: &mut V, attr: &'ast ast::Attribute) {
+    for &arg in &attr.args {
+        visitor.visit_expr(arg);
+    }
+}
+
+pub fn walk_func<'ast, V: Visitor<'ast>>(visitor: &mut V, func: &'ast ast::FuncDecl) {
+    for param in &func.params {
+        visitor.visit_param(param);
+    }
+
+    if let Some(return_type) = &func.return_type {
+        visitor.visit_type(return_type);
+    }
+
+    visitor.visit_block(&func.body);
+}
+
+pub fn walk_param<'ast, V: Visitor<'ast>>(visitor: &mut V, param: &'ast ast::Param) {
+    visitor.visit_type(&param.ty);
+}
+
+pub fn walk_const<'ast, V: Visitor<'ast>>(visitor: &mut V, const_decl: &'ast ast::ConstDecl) {
+    visitor.visit_type(&const_decl.ty);
+    visitor.visit_expr(const_decl.value);
+}
+
+pub fn walk_struct<'ast, V: Visitor<'ast>>(visitor: &mut V, struct_decl: &'ast ast::StructDecl) {
+    for field in &struct_decl.fields {
+        visitor.visit_type(&field.ty);
+    }
+}
+
+pub fn walk_enum<'ast, V: Visitor<'ast>>(visitor: &mut V, enum_decl: &'ast ast::EnumDecl) {
+    for variant in &enum_decl.variants {
+        visitor.visit_variant(variant);
+    }
+}
+
+pub fn walk_variant<'ast, V: Visitor<'ast>>(visitor: &mut V, variant: &'ast ast::Variant) {
+    match &variant.payload {
+        ast::VariantPayload::Unit => {}
+        ast::VariantPayload::Tuple(types) => {
+            for ty in types {
+                visitor.visit_type(ty);
+            }
+        }
+        ast::VariantPayload::Struct(fields) => {
+            for field in fields {
+                visitor.visit_type(&field.ty);
+            }
+        }
+    }
+}
+
+pub fn walk_trait<'ast, V: Visitor<'ast>>(visitor: &mut V, trait_decl: &'ast ast::TraitDecl) {
+    for method in &trait_decl.methods {
+        visitor.visit_trait_method(method);
+    }
+}
+
+pub fn walk_trait_method<'ast, V: Visitor<'ast>>(visitor: &mut V, method: &'ast ast::TraitMethod) {
+    for attr in &method.attributes {
+        visitor.visit_attribute(attr);
+    }
+
+    for param in &method.params {
+        visitor.visit_param(param);
+    }
+
+    if let Some(return_type) = &method.return_type {
+        visitor.visit_type(return_type);
+    }
+
+    if let Some(body) = &method.body {
+        visitor.visit_block(body);
+    }
+}
+
+pub fn walk_type<'ast, V: Visitor<'ast>>(visitor: &mut V, ty: &'ast ast::TypeExpr) {
+    match ty {
+        ast::TypeExpr::Named(named) => {
+            for arg in &named.args {
+                visitor.visit_type(arg);
+            }
+        }
+        ast::TypeExpr::Ref(reference) => visitor.visit_type(&reference.inner),
+        ast::TypeExpr::Ptr(pointer) => visitor.visit_type(&pointer.inner),
+        ast::TypeExpr::Optional(optional) => visitor.visit_type(&optional.inner),
+        ast::TypeExpr::Array(array) => {
+            visitor.visit_type(&array.element);
+            visitor.visit_expr(array.len);
+        }
+        ast::TypeExpr::Tuple(tuple) => {
+            for element in &tuple.elements {
+                visitor.visit_type(element);
+            }
+        }
+        ast::TypeExpr::Func(func) => {
+            for param in &func.params {
+                visitor.visit_type(param);
+            }
+            if let Some(return_type) = &func.return_type {
+                visitor.visit_type(return_type);
+            }
+        }
+    }
+}
+
+pub fn walk_block<'ast, V: Visitor<'ast>>(visitor: &mut V, block: &'ast ast::Block) {
+    for stmt in &block.stmts {
+        visitor.visit_stmt(stmt);
+    }
+
+    if let Some(tail) = block.tail {
+        visitor.visit_expr(tail);
+    }
+}
+
+pub fn walk_stmt<'ast, V: Visitor<'ast>>(visitor: &mut V, stmt: &'ast ast::Stmt) {
+    for attr in stmt.attributes() {
+        visitor.visit_attribute(attr);
+    }
+
+    match stmt {
+        ast::Stmt::Let(let_stmt) => {
+            visitor.visit_pattern(&let_stmt.pattern);
+            if let Some(ty) = &let_stmt.ty {
+                visitor.visit_type(ty);
+            }
+            if let Some(value) = let_stmt.value {
+                visitor.visit_expr(value);
+            }
+        }
+        ast::Stmt::Const(const_decl) => visitor.visit_const(const_decl),
+        ast::Stmt::Expr(expr_stmt) => visitor.visit_expr(expr_stmt.expr),
+        ast::Stmt::Return(ast::ReturnStmt { value, .. })
+        | ast::Stmt::Break(ast::BreakStmt { value, .. }) => {
+            if let Some(value) = *value {
+                visitor.visit_expr(value);
+            }
+        }
+        ast::Stmt::For(for_stmt) => {
+            visitor.visit_expr(for_stmt.iterable);
+            visitor.visit_block(&for_stmt.body);
+        }
+        ast::Stmt::Continue(_) | ast::Stmt::Error(_) => {}
+    }
+}
+
+pub fn walk_expr<'ast, V: Visitor<'ast>>(visitor: &mut V, id: ast::ExprId) {
+    match &visitor.exprs()[id] {
+        ast::Expr::Identifier(_)
+        | ast::Expr::Path(_)
+        | ast::Expr::Integer(_)
+        | ast::Expr::Float(_)
+        | ast::Expr::String(_)
+        | ast::Expr::None(_)
+        | ast::Expr::Error(_) => {}
+        ast::Expr::Paren(paren) => visitor.visit_expr(paren.expr),
+        ast::Expr::Tuple(tuple) => {
+            for &element in &tuple.elements {
+                visitor.visit_expr(element);
+            }
+        }
+        ast::Expr::Unary(unary) => visitor.visit_expr(unary.operand),
+        ast::Expr::Binary(binary) => {
+            visitor.visit_expr(binary.lhs);
+            visitor.visit_expr(binary.rhs);
+        }
+        ast::Expr::Cast(cast) => {
+            visitor.visit_expr(cast.expr);
+            visitor.visit_type(&cast.ty);
+        }
+        ast::Expr::Assign(assign) => {
+            visitor.visit_expr(assign.target);
+            visitor.visit_expr(assign.value);
+        }
+        ast::Expr::Range(range) => {
+            visitor.visit_expr(range.start);
+            visitor.visit_expr(range.end);
+        }
+        ast::Expr::Call(call) => {
+            visitor.visit_expr(call.callee);
+            for &arg in &call.args {
+                visitor.visit_expr(arg);
+            }
+        }
+        ast::Expr::Field(field) => visitor.visit_expr(field.base),
+        ast::Expr::Section(section) => {
+            if let Some(lhs) = section.lhs {
+                visitor.visit_expr(lhs);
+            }
+            if let Some(rhs) = section.rhs {
+                visitor.visit_expr(rhs);
+            }
+        }
+        ast::Expr::Block(block) => visitor.visit_block(block),
+        ast::Expr::If(if_expr) => {
+            visitor.visit_expr(if_expr.cond);
+            visitor.visit_block(&if_expr.then_block);
+            if let Some(else_branch) = if_expr.else_branch {
+                visitor.visit_expr(else_branch);
+            }
+        }
+        ast::Expr::While(while_expr) => {
+            visitor.visit_expr(while_expr.cond);
+            visitor.visit_block(&while_expr.body);
+        }
+        ast::Expr::Loop(loop_expr) => visitor.visit_block(&loop_expr.body),
+        ast::Expr::Match(match_expr) => {
+            visitor.visit_expr(match_expr.scrutinee);
+            for arm in &match_expr.arms {
+                visitor.visit_match_arm(arm);
+            }
+        }
+        ast::Expr::Assert(assert) => {
+            visitor.visit_expr(assert.cond);
+            if let Some(message) = assert.message {
+                visitor.visit_expr(message);
+            }
+        }
+        ast::Expr::Panic(panic) => {
+            if let Some(message) = panic.message {
+                visitor.visit_expr(message);
+            }
+        }
+    }
+}
+
+/// Returns the direct children of the [ast::Expr], including the expressions of the
+/// statements in its blocks, in the order [walk_expr] visits them.
+pub fn children(exprs: &ast::ExprArena, id: ast::ExprId) -> Vec<ast::ExprId> {
+    struct Children<'a> {
+        exprs: &'a ast::ExprArena,
+        ids: Vec<ast::ExprId>,
+    }
+
+    impl<'a> Visitor<'a> for Children<'a> {
+        fn exprs(&self) -> &'a ast::ExprArena {
+            self.exprs
+        }
+
+        fn visit_expr(&mut self, id: ast::ExprId) {
+            self.ids.push(id);
+        }
+    }
+
+    let mut children = Children {
+        exprs,
+        ids: Vec::new(),
+    };
+    walk_expr(&mut children, id);
+    children.ids
+}
+
+pub fn walk_match_arm<'ast, V: Visitor<'ast>>(visitor: &mut V, arm: &'ast ast::MatchArm) {
+    visitor.visit_pattern(&arm.pattern);
+    visitor.visit_expr(arm.value);
+}
+
+pub fn walk_pattern<'ast, V: Visitor<'ast>>(visitor: &mut V, pattern: &'ast ast::Pattern) {
+    match pattern {
+        ast::Pattern::Tuple(ast::TuplePattern { elements, .. })
+        | ast::Pattern::TupleStruct(ast::TupleStructPattern { elements, .. }) => {
+            for element in elements {
+                visitor.visit_pattern(element);
+            }
+        }
+        ast::Pattern::Struct(struct_pattern) => {
+            for field in &struct_pattern.fields {
+                if let Some(pattern) = &field.pattern {
+                    visitor.visit_pattern(pattern);
+                }
+            }
+        }
+        ast::Pattern::Wildcard(_)
+        | ast::Pattern::None(_)
+        | ast::Pattern::Binding(_)
+        | ast::Pattern::Integer(_)
+        | ast::Pattern::Float(_)
+        | ast::Pattern::String(_)
+        | ast::Pattern::Path(_) => {}
+    }
+}
+
+/// Visits the nodes of an AST mutably, so passes can rewrite them in place.
+///
+/// [ast::Expr]s are edited through the `exprs` arena handed to every method.
+/// While the children of an [ast::Expr] are visited, the [ast::Expr] itself is
+/// moved out of the arena and its slot holds an [ast::Expr::Error].
+pub trait MutVisitor: Sized {
+    fn visit_module_mut(&mut self, module: &mut ast::Module) {
+        walk_module_mut(self, module);
+    }
+
+    fn visit_item_mut(&mut self, exprs: &mut ast::ExprArena, item: &mut ast::Item) {
+        walk_item_mut(self, exprs, item);
+    }
+
+    fn visit_func_mut(&mut self, exprs: &mut ast::ExprArena, func: &mut ast::FuncDecl) {
+        walk_func_mut(self, exprs, func);
+    }
+
+    fn visit_const_mut(&mut self, exprs: &mut ast::ExprArena, const_decl: &mut ast::ConstDecl) {
+        walk_const_mut(self, exprs, const_decl);
+    }
+
+    fn visit_type_mut(&mut self, _ty: &mut ast::TypeExpr) {}
+
+    fn visit_pattern_mut(&mut self, _pattern: &mut ast::Pattern) {}
+
+    fn visit_block_mut(&mut self, exprs: &mut ast::ExprArena, block: &mut ast::Block) {
+        walk_block_mut(self, exprs, block);
+    }
+
+    fn visit_stmt_mut(&mut self, exprs: &mut ast::ExprArena, stmt: &mut ast::Stmt) {
+        walk_stmt_mut(self, exprs, stmt);
+    }
+
+    fn visit_expr_mut(&mut self, exprs: &mut ast::ExprArena, id: ast::ExprId) {
+        walk_expr_mut(self, exprs, id);
+    }
+}
+
+pub fn walk_module_mut<V: MutVisitor>(visitor: &mut V, module: &mut ast::Module) {
+    for item in &mut module.items {
+        visitor.visit_item_mut(&mut module.exprs, item);
+    }
+}
+
+pub fn walk_item_mut<V: MutVisitor>(
+    visitor: &mut V,
+    exprs: &mut ast::ExprArena,
+    item: &mut ast::Item,
+) {
+    for attr in item.attributes() {
+        for &arg in &attr.args {
+            visitor.visit_expr_mut(exprs, arg);
+        }
+    }
+
+    match item {
+        ast::Item::Func(func) => visitor.visit_func_mut(exprs, func),
+        ast::Item::Const(const_decl) => visitor.visit_const_mut(exprs, const_decl),
+        ast::Item::Struct(struct_decl) => {
+            for field in &mut struct_decl.fields {
+                visitor.visit_type_mut(&mut field.ty);
+            }
+        }
+        ast::Item::Enum(enum_decl) => {
+            for variant in &mut enum_decl.variants {
+                match &mut variant.payload {
+                    ast::VariantPayload::Unit => {}
+                    ast::VariantPayload::Tuple(types) => {
+                        for ty in types {
+                            visitor.visit_type_mut(ty);
+                        }
+                    }
+                    ast::VariantPayload::Struct(fields) => {
+                        for field in fields {
+                            visitor.visit_type_mut(&mut field.ty);
+                        }
+                    }
+                }
+            }
+        }
+        ast::Item::Trait(trait_decl) => {
+            for method in &mut trait_decl.methods {
+                for attr in &method.attributes {
+                    for &arg in &attr.args {
+                        visitor.visit_expr_mut(exprs, arg);
+                    }
+                }
+                for param in &mut method.params {
+                    visitor.visit_type_mut(&mut param.ty);
+                }
+                if let Some(return_type) = &mut method.return_type {
+                    visitor.visit_type_mut(return_type);
+                }
+                if let Some(body) = &mut method.body {
+                    visitor.visit_block_mut(exprs, body);
+                }
+            }
+        }
+        ast::Item::Import(_) | ast::Item::Error(_) => {}
+    }
+}
+
+pub fn walk_func_mut<V: MutVisitor>(
+    visitor: &mut V,
+    exprs: &mut ast::ExprArena,
+    func: &mut ast::FuncDecl,
+) {
+    for param in &mut func.params {
+        visitor.visit_type_mut(&mut param.ty);
+    }
+
+    if let Some(return_type) = &mut func.return_type {
+        visitor.visit_type_mut(return_type);
+    }
+
+    visitor.visit_block_mut(exprs, &mut func.body);
+}
+
+pub fn walk_const_mut<V: MutVisitor>(
+    visitor: &mut V,
+    exprs: &mut ast::ExprArena,
+    const_decl: &mut ast::ConstDecl,
+) {
+    visitor.visit_type_mut(&mut const_decl.ty);
+    visitor.visit_expr_mut(exprs, const_decl.value);
+}
+
+pub fn walk_block_mut<V: MutVisitor>(
+    visitor: &mut V,
+    exprs: &mut ast::ExprArena,
+    block: &mut ast::Block,
+) {
+    for stmt in &mut block.stmts {
+        visitor.visit_stmt_mut(exprs, stmt);
+    }
+
+    if let Some(tail) = block.tail {
+        visitor.visit_expr_mut(exprs, tail);
+    }
+}
+
+pub fn walk_stmt_mut<V: MutVisitor>(
+    visitor: &mut V,
+    exprs: &mut ast::ExprArena,
+    stmt: &mut ast::Stmt,
+) {
+    for attr in stmt.attributes() {
+        for &arg in &attr.args {
+            visitor.visit_expr_mut(exprs, arg);
+        }
+    }
+
+    match stmt {
+        ast::Stmt::Let(let_stmt) => {
+            visitor.visit_pattern_mut(&mut let_stmt.pattern);
+            if let Some(ty) = &mut let_stmt.ty {
+                visitor.visit_type_mut(ty);
+            }
+            if let Some(value) = let_stmt.value {
+                visitor.visit_expr_mut(exprs, value);
+            }
+        }
+        ast::Stmt::Const(const_decl) => visitor.visit_const_mut(exprs, const_decl),
+        ast::Stmt::Expr(expr_stmt) => visitor.visit_expr_mut(exprs, expr_stmt.expr),
+        ast::Stmt::Return(ast::ReturnStmt { value, .. })
+        | ast::Stmt::Break(ast::BreakStmt { value, .. }) => {
+            if let Some(value) = *value {
+                visitor.visit_expr_mut(exprs, value);
+            }
+        }
+        ast::Stmt::For(for_stmt) => {
+            visitor.visit_expr_mut(exprs, for_stmt.iterable);
+            visitor.visit_block_mut(exprs, &mut for_stmt.body);
+        }
+        ast::Stmt::Continue(_) | ast::Stmt::Error(_) => {}
+    }
+}
+
+pub fn walk_expr_mut<V: MutVisitor>(visitor: &mut V, exprs: &mut ast::ExprArena, id: ast::ExprId) {
+    let placeholder = ast::Expr::Error(exprs[id].span());
+    let mut expr = mem::replace(&mut exprs[id], placeholder);
+
+    match &mut expr {
+        ast::Expr::Identifier(_)
+        | ast::Expr::Path(_)
+        | ast::Expr::Integer(_)
+        | ast::Expr::Float(_)
+        | ast::Expr::String(_)
+        | ast::Expr::None(_)
+        | ast::Expr::Error(_) => {}
+        ast::Expr::Paren(paren) => visitor.visit_expr_mut(exprs, paren.expr),
+        ast::Expr::Tuple(tuple) => {
+            for &element in &tuple.elements {
+                visitor.visit_expr_mut(exprs, element);
+            }
+        }
+        ast::Expr::Unary(unary) => visitor.visit_expr_mut(exprs, unary.operand),
+        ast::Expr::Binary(binary) => {
+            visitor.visit_expr_mut(exprs, binary.lhs);
+            visitor.visit_expr_mut(exprs, binary.rhs);
+        }
+        ast::Expr::Cast(cast) => {
+            visitor.visit_expr_mut(exprs, cast.expr);
+            visitor.visit_type_mut(&mut cast.ty);
+        }
+        ast::Expr::Assign(assign) => {
+            visitor.visit_expr_mut(exprs, assign.target);
+            visitor.visit_expr_mut(exprs, assign.value);
+        }
+        ast::Expr::Range(range) => {
+            visitor.visit_expr_mut(exprs, range.start);
+            visitor.visit_expr_mut(exprs, range.end);
+        }
+        ast::Expr::Call(call) => {
+            visitor.visit_expr_mut(exprs, call.callee);
+            for &arg in &call.args {
+                visitor.visit_expr_mut(exprs, arg);
+            }
+        }
+        ast::Expr::Field(field) => visitor.visit_expr_mut(exprs, field.base),
+        ast::Expr::Section(section) => {
+            if let Some(lhs) = section.lhs {
+                visitor.visit_expr_mut(exprs, lhs);
+            }
+            if let Some(rhs) = section.rhs {
+                visitor.visit_expr_mut(exprs, rhs);
+            }
+        }
+        ast::Expr::Block(block) => visitor.visit_block_mut(exprs, block),
+        ast::Expr::If(if_expr) => {
+            visitor.visit_expr_mut(exprs, if_expr.cond);
+            visitor.visit_block_mut(exprs, &mut if_expr.then_block);
+            if let Some(else_branch) = if_expr.else_branch {
+                visitor.visit_expr_mut(exprs, else_branch);
+            }
+        }
+        ast::Expr::While(while_expr) => {
+            visitor.visit_expr_mut(exprs, while_expr.cond);
+            visitor.visit_block_mut(exprs, &mut while_expr.body);
+        }
+        ast::Expr::Loop(loop_expr) => visitor.visit_block_mut(exprs, &mut loop_expr.body),
+        ast::Expr::Match(match_expr) => {
+            visitor.visit_expr_mut(exprs, match_expr.scrutinee);
+            for arm in &mut match_expr.arms {
+                visitor.visit_pattern_mut(&mut arm.pattern);
+                visitor.visit_expr_mut(exprs, arm.value);
+            }
+        }
+        ast::Expr::Assert(assert) => {
+            visitor.visit_expr_mut(exprs, assert.cond);
+            if let Some(message) = assert.message {
+                visitor.visit_expr_mut(exprs, message);
+            }
+        }
+        ast::Expr::Panic(panic) => {
+            if let Some(message) = panic.message {
+                visitor.visit_expr_mut(exprs, message);
+            }
+        }
+    }
+
+    exprs[id] = expr;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{error::SyntaxError, lexer::Lexer, parser::Parser};
+
+    /// Collects the names of all identifier expressions, bindings and named types, in visiting order.
+    struct Names<'ast> {
+        exprs: &'ast ast::ExprArena,
+        names: Vec<&'ast str>,
+    }
+
+    impl<'ast> Visitor<'ast> for Names<'ast> {
+        fn exprs(&self) -> &'ast ast::ExprArena {
+            self.exprs
+        }
+
+        fn visit_type(&mut self, ty: &'ast ast::TypeExpr) {
+            if let ast::TypeExpr::Named(named) = ty {
+                self.names.push(&named.name.text);
+            }
+            walk_type(self, ty);
+        }
+
+        fn visit_expr(&mut self, id: ast::ExprId) {
+            if let ast::Expr::Identifier(ident) = &self.exprs[id] {
+                self.names.push(&ident.text);
+            }
+            walk_expr(self, id);
+        }
+
+        fn visit_pattern(&mut self, pattern: &'ast ast::Pattern) {
+            if let ast::Pattern::Binding(binding) = pattern {
+                self.names.push(&binding.name.text);
+            }
+            walk_pattern(self, pattern);
+        }
+    }
+
+    #[test]
+    fn collect_names() -> Result<(), SyntaxError> {
+        let input = "
+            fn f(a: List<i64>) -> bool {
+                let b: f64 = a as f64;
+                for c in 0..d { e(c); }
+                match b { x => y }
+            }
+            enum E { A(&T), B { f: (U,) } }
+        ";
+
+        let tokens = Lexer::new(input).collect_tokens()?;
+        let (module, errors) = Parser::new(&tokens).parse_module();
+        assert!(errors.is_empty());
+
+        let mut visitor = Names {
+            exprs: &module.exprs,
+            names: Vec::new(),
+        };
+        visitor.visit_module(&module);
+
+        assert_eq!(
+            visitor.names,
+            [
+                "List", "i64", "bool", "b", "f64", "a", "f64", "d", "e", "c", "b", "x", "y", "T",
+                "U",
+            ]
+        );
+
+        Ok(())
+    }
+
+    /// Replaces every parenthesized expression by its inner expression.
+    struct StripParens;
+
+    impl MutVisitor for StripParens {
+        fn visit_expr_mut(&mut self, exprs: &mut ast::ExprArena, id: ast::ExprId) {
+            walk_expr_mut(self, exprs, id);
+
+            if let ast::Expr::Paren(paren) = &exprs[id] {
+                let inner = paren.expr;
+                let span = exprs[inner].span();
+                exprs[id] = mem::replace(&mut exprs[inner], ast::Expr::Error(span));
+            }
+        }
+    }
+
+    #[test]
+    fn strip_parens() -> Result<(), SyntaxError> {
+        let tokens = Lexer::new("fn f() { ((1) + (x)) * 2 }").collect_tokens()?;
+        let (mut module, errors) = Parser::new(&tokens).parse_module();
+        assert!(errors.is_empty());
+
+        StripParens.visit_module_mut(&mut module);
+
+        let ast::Item::Func(func) = &module.items[0] else {
+            panic!("expected a function, got {:?}", module.items[0]);
+        };
+        let ast::Expr::Binary(mul) = &module.exprs[func.body.tail.unwrap()] else {
+            panic!("expected a binary expression");
+        };
+        let ast::Expr::Binary(add) = &module.exprs[mul.lhs] else {
+            panic!("expected the parenthesized sum to be replaced");
+        };
+
+        assert!(matches!(module.exprs[add.lhs], ast::Expr::Integer(_)));
+        assert!(matches!(module.exprs[add.rhs], ast::Expr::Identifier(_)));
+
+        Ok(())
+    }
+}
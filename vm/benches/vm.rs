@@ -0,0 +1,148 @@
+use std::time::Instant;
+
+use ir::{func::Module, inst::Const, lower::lower, pass::PassManager};
+use semantic::{
+    callgraph::CallGraph, check::check, consts::eval_consts, mono::monomorphize,
+    program::build_program, resolve::resolve,
+};
+use syntax::{lexer::Lexer, parser::Parser};
+use vm::{
+    compile::{compile, compile_registers},
+    register::RegisterVm,
+    stack::StackVm,
+};
+
+const ROUNDS: usize = 5;
+
+/// Loop-heavy programs, each with the value its `main` returns.
+const PROGRAMS: [(&str, &str, i128); 4] = [
+    (
+        "sum",
+        "fn main() -> i32 {
+            var total: i64 = 0;
+            for i in 0..3000000 { total = total + i % 7; }
+            (total % 100) as i32
+        }",
+        94,
+    ),
+    (
+        "primes",
+        "fn is_prime(n: i64) -> bool {
+            if n < 2 { return false; }
+            var d: i64 = 2;
+            while d * d <= n {
+                if n % d == 0 { return false; }
+                d = d + 1;
+            }
+            true
+        }
+        fn main() -> i32 {
+            var count: i32 = 0;
+            for n in 0..100000 {
+                if is_prime(n) { count = count + 1; }
+            }
+            count % 256
+        }",
+        120,
+    ),
+    (
+        "fib",
+        "fn fib(n: i64) -> i64 {
+            if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+        }
+        fn main() -> i32 { (fib(25) % 256) as i32 }",
+        17,
+    ),
+    (
+        "mandelbrot",
+        "fn escapes(cx: f64, cy: f64) -> i32 {
+            var x = 0.0;
+            var y = 0.0;
+            var i: i32 = 0;
+            while i < 100 {
+                if x * x + y * y > 4.0 { return i; }
+                let t = x * x - y * y + cx;
+                y = 2.0 * x * y + cy;
+                x = t;
+                i = i + 1;
+            }
+            i
+        }
+        fn main() -> i32 {
+            var total: i32 = 0;
+            for py in 0..60 {
+                for px in 0..80 {
+                    total = total + escapes(px as f64 / 40.0 - 1.5, py as f64 / 30.0 - 1.0);
+                }
+            }
+            total % 256
+        }",
+        37,
+    ),
+];
+
+/// Lowers a program to the IR and optimizes it with the default pipeline.
+fn lower_source(source: &str) -> Module {
+    let tokens = Lexer::new(source)
+        .collect_tokens()
+        .expect("the input lexes");
+    let (module, errors) = Parser::new(&tokens).parse_module();
+    assert!(errors.is_empty(), "the input parses: {errors:?}");
+
+    let resolutions = resolve(&module).resolutions;
+    let consts = eval_consts(&module, &resolutions).values;
+    let checked = check(&module, &resolutions, &consts);
+    assert!(checked.errors.is_empty(), "the input checks");
+
+    let instances = monomorphize(&resolutions, &checked.types);
+    let call_graph = CallGraph::build(&module, &resolutions);
+    let program = build_program(&module, &resolutions, &checked.types, &call_graph)
+        .expect("the input has a `main` function");
+    let mut lowered = lower(
+        &module,
+        &resolutions,
+        &checked.types,
+        &consts,
+        &instances,
+        &program,
+    )
+    .expect("the input lowers");
+    PassManager::default().run(&mut lowered, |_, _| {});
+    lowered
+}
+
+/// Returns the fastest of [ROUNDS] runs in seconds, checking the result of each.
+fn best(expected: i128, mut run: impl FnMut() -> Option<Const>) -> f64 {
+    let mut best = f64::INFINITY;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        let result = run();
+        best = best.min(start.elapsed().as_secs_f64());
+        assert_eq!(result, Some(Const::Int(expected)));
+    }
+    best
+}
+
+fn main() {
+    for (name, source, expected) in PROGRAMS {
+        let module = lower_source(source);
+        let (program, registers) = (compile(&module), compile_registers(&module));
+        let main = program.function("main").expect("`main` is lowered");
+
+        // the number of ops compiled, not executed.
+        let stack_ops: usize = program.functions.iter().map(|f| f.code.len()).sum();
+        let register_ops: usize = registers.functions.iter().map(|f| f.code.len()).sum();
+
+        let mut stack = StackVm::new(&program);
+        let stack_time = best(expected, || stack.run(main, Vec::new()).unwrap());
+        let mut register = RegisterVm::new(&registers);
+        let register_time = best(expected, || register.run(main, Vec::new()).unwrap());
+
+        println!(
+            "{name:<10}  stack {:>8.1} ms ({stack_ops} ops)  register {:>8.1} ms ({register_ops} ops)  {:.2}x",
+            stack_time * 1000.0,
+            register_time * 1000.0,
+            stack_time / register_time
+        );
+    }
+}
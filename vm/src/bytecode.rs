@@ -0,0 +1,168 @@
+use std::fmt;
+
+use ir::inst::{Const, binary_mnemonic, unary_mnemonic};
+use semantic::ty::Primitive;
+use syntax::{
+    ast::{BinaryOp, UnaryOp},
+    token::Span,
+};
+
+/// An instruction of the bytecode, which takes its operands from a stack of values and
+/// pushes its result onto it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    /// Pushes the constant at the given index of [Program::constants].
+    Const(u32),
+    /// Pushes the value of a local.
+    Load(u32),
+    /// Pops a value into a local.
+    Store(u32),
+    /// Pops an operand of the given type and pushes the result.
+    Unary(UnaryOp, Primitive),
+    /// Pops the right and then the left operand of the given type and pushes the
+    /// result.
+    Binary(BinaryOp, Primitive),
+    /// Pops a value and pushes it converted to the given type like
+    /// [ir::inst::InstKind::Cast].
+    Cast(Primitive),
+    /// Continues at the given index of the code.
+    Jump(u32),
+    /// Pops a `bool` and continues at the given index of the code if it's `false`.
+    JumpIfFalse(u32),
+    /// Pops the arguments for the function at the given index of [Program::functions]
+    /// and calls it, pushing its result unless it returns `()`.
+    Call(u32),
+    /// Returns from the function, with a popped value unless it returns `()`.
+    Return,
+    /// Stops the program, with a popped `str` message if `true`.
+    Panic(bool),
+    Unreachable,
+}
+
+/// An instruction of the register bytecode, which reads its operands from the locals
+/// of the running call and writes its result to one, so it needs no separate ops to
+/// load and store them like an [Op].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegOp {
+    /// Sets `dst` to the constant at the given index of [Program::constants].
+    Const {
+        dst: u32,
+        index: u32,
+    },
+    Move {
+        dst: u32,
+        src: u32,
+    },
+    Unary {
+        op: UnaryOp,
+        ty: Primitive,
+        dst: u32,
+        src: u32,
+    },
+    Binary {
+        op: BinaryOp,
+        ty: Primitive,
+        dst: u32,
+        lhs: u32,
+        rhs: u32,
+    },
+    Cast {
+        ty: Primitive,
+        dst: u32,
+        src: u32,
+    },
+    Jump(u32),
+    /// Continues at `target` if the `bool` in `cond` is `false`.
+    JumpIfFalse {
+        cond: u32,
+        target: u32,
+    },
+    /// Calls the function at the given index of [Program::functions] with the
+    /// arguments in the locals starting at `args`, which become the first locals of
+    /// the call, and sets `dst` to its result unless it returns `()`.
+    Call {
+        function: u32,
+        args: u32,
+        dst: Option<u32>,
+    },
+    /// Returns from the function, with the given local unless it returns `()`.
+    Return(Option<u32>),
+    /// Stops the program, with the `str` message in the given local.
+    Panic(Option<u32>),
+    Unreachable,
+}
+
+/// A function compiled to bytecode made of [Op]s or [RegOp]s, which keeps its
+/// parameters and the values it computes in numbered locals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function<O = Op> {
+    pub name: String,
+    /// The number of parameters, which are passed in the first locals.
+    pub params: u32,
+    pub locals: u32,
+    /// Whether the function returns a value rather than `()`.
+    pub returns: bool,
+    pub code: Vec<O>,
+    /// The source each op was compiled from, used to locate runtime errors.
+    pub spans: Vec<Option<Span>>,
+}
+
+/// The functions of a program compiled to bytecode, sharing a pool of constants.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Program<O = Op> {
+    pub constants: Vec<Const>,
+    pub functions: Vec<Function<O>>,
+}
+
+impl<O> Program<O> {
+    /// Returns the index of the function with the given name.
+    pub fn function(&self, name: &str) -> Option<u32> {
+        let index = self.functions.iter().position(|func| func.name == name)?;
+        Some(index as u32)
+    }
+}
+
+impl Program {
+    fn fmt_op(&self, op: Op, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match op {
+            Op::Const(index) => write!(f, "const {index}  // {}", self.constants[index as usize]),
+            Op::Load(local) => write!(f, "load {local}"),
+            Op::Store(local) => write!(f, "store {local}"),
+            Op::Unary(op, ty) => write!(f, "{} {}", unary_mnemonic(op), ty.name()),
+            Op::Binary(op, ty) => write!(f, "{} {}", binary_mnemonic(op), ty.name()),
+            Op::Cast(ty) => write!(f, "cast {}", ty.name()),
+            Op::Jump(target) => write!(f, "jump {target}"),
+            Op::JumpIfFalse(target) => write!(f, "jump_if_false {target}"),
+            Op::Call(index) => {
+                let name = &self.functions[index as usize].name;
+                write!(f, "call {index}  // @{name}")
+            }
+            Op::Return => write!(f, "ret"),
+            Op::Panic(true) => write!(f, "panic message"),
+            Op::Panic(false) => write!(f, "panic"),
+            Op::Unreachable => write!(f, "unreachable"),
+        }
+    }
+}
+
+/// Disassembles the program, listing the ops of each function with their indices.
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, func) in self.functions.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            writeln!(
+                f,
+                "fn @{}: {} params, {} locals",
+                func.name, func.params, func.locals
+            )?;
+            for (index, &op) in func.code.iter().enumerate() {
+                write!(f, "  {index:>4}  ")?;
+                self.fmt_op(op, f)?;
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,504 @@
+use std::collections::HashMap;
+
+use ir::{
+    func::{Func, Module},
+    inst::{BlockId, Const, Edge, Inst, InstKind, Terminator, Value},
+};
+use syntax::token::Span;
+
+use crate::bytecode::{Function, Op, Program, RegOp};
+
+/// Compiles the functions of a verified module to bytecode, in the same order.
+pub fn compile(module: &Module) -> Program {
+    let indices = function_indices(module);
+    let mut constants = Constants::default();
+    let functions = module
+        .funcs
+        .iter()
+        .map(|func| FuncCompiler::new(func, &indices, &mut constants).compile())
+        .collect();
+
+    Program {
+        constants: constants.values,
+        functions,
+    }
+}
+
+/// Compiles the functions of a verified module to register bytecode, in the same
+/// order.
+pub fn compile_registers(module: &Module) -> Program<RegOp> {
+    let indices = function_indices(module);
+    let mut constants = Constants::default();
+    let functions = module
+        .funcs
+        .iter()
+        .map(|func| RegisterCompiler::new(func, &indices, &mut constants).compile())
+        .collect();
+
+    Program {
+        constants: constants.values,
+        functions,
+    }
+}
+
+fn function_indices(module: &Module) -> HashMap<&str, u32> {
+    module
+        .funcs
+        .iter()
+        .enumerate()
+        .map(|(index, func)| (func.name.as_str(), index as u32))
+        .collect()
+}
+
+/// Returns the local holding each value of a function, starting with its parameters,
+/// where the caller passes them.
+fn locals(func: &Func) -> HashMap<Value, u32> {
+    let mut values = func.params.clone();
+    for block in &func.blocks {
+        values.extend(&block.params);
+        values.extend(block.insts.iter().filter_map(|inst| inst.result));
+    }
+    (0..)
+        .zip(values)
+        .map(|(local, value)| (value, local))
+        .collect()
+}
+
+/// The constants of a program, each of which is pooled once.
+#[derive(Default)]
+struct Constants {
+    values: Vec<Const>,
+    indices: HashMap<Const, u32>,
+}
+
+impl Constants {
+    fn index(&mut self, value: &Const) -> u32 {
+        if let Some(&index) = self.indices.get(value) {
+            return index;
+        }
+        self.values.push(value.clone());
+        let index = self.values.len() as u32 - 1;
+        self.indices.insert(value.clone(), index);
+        index
+    }
+}
+
+struct FuncCompiler<'a> {
+    func: &'a Func,
+    functions: &'a HashMap<&'a str, u32>,
+    constants: &'a mut Constants,
+    /// The local holding each value.
+    locals: HashMap<Value, u32>,
+    code: Vec<Op>,
+    spans: Vec<Option<Span>>,
+    /// The index of the code each block starts at.
+    starts: Vec<u32>,
+    /// The jumps to patch with the start of the block they jump to.
+    jumps: Vec<(usize, BlockId)>,
+}
+
+impl<'a> FuncCompiler<'a> {
+    fn new(
+        func: &'a Func,
+        functions: &'a HashMap<&'a str, u32>,
+        constants: &'a mut Constants,
+    ) -> Self {
+        Self {
+            func,
+            functions,
+            constants,
+            locals: locals(func),
+            code: Vec::new(),
+            spans: Vec::new(),
+            starts: Vec::new(),
+            jumps: Vec::new(),
+        }
+    }
+
+    fn compile(mut self) -> Function {
+        for id in self.func.block_ids() {
+            self.starts.push(self.code.len() as u32);
+            let block = self.func.block(id);
+            for inst in &block.insts {
+                self.inst(inst);
+            }
+            self.term(BlockId(id.0 + 1), &block.term);
+        }
+
+        for &(index, block) in &self.jumps {
+            let start = self.starts[block.index()];
+            match &mut self.code[index] {
+                Op::Jump(target) | Op::JumpIfFalse(target) => *target = start,
+                op => unreachable!("{op:?} isn't a jump"),
+            }
+        }
+
+        Function {
+            name: self.func.name.clone(),
+            params: self.func.params.len() as u32,
+            locals: self.locals.len() as u32,
+            returns: self.func.ret.is_some(),
+            code: self.code,
+            spans: self.spans,
+        }
+    }
+
+    fn emit(&mut self, op: Op, span: Option<Span>) {
+        self.code.push(op);
+        self.spans.push(span);
+    }
+
+    fn load(&mut self, value: Value) {
+        self.emit(Op::Load(self.locals[&value]), None);
+    }
+
+    fn inst(&mut self, inst: &Inst) {
+        for operand in inst.kind.operands() {
+            self.load(operand);
+        }
+
+        let op = match &inst.kind {
+            InstKind::Const(value) => Op::Const(self.constants.index(value)),
+            InstKind::Unary(op, operand) => Op::Unary(*op, self.func.ty(*operand)),
+            InstKind::Binary(op, lhs, _) => Op::Binary(*op, self.func.ty(*lhs)),
+            InstKind::Cast(_) => {
+                let result = inst.result.expect("casts have a result");
+                Op::Cast(self.func.ty(result))
+            }
+            InstKind::Call(name, _) => Op::Call(self.functions[name.as_str()]),
+        };
+        self.emit(op, inst.span);
+
+        if let Some(result) = inst.result {
+            self.emit(Op::Store(self.locals[&result]), None);
+        }
+    }
+
+    /// Compiles the terminator of a block followed by the block `next`.
+    fn term(&mut self, next: BlockId, term: &Terminator) {
+        match term {
+            Terminator::Jump(edge) => self.edge(edge, Some(next)),
+            Terminator::Branch {
+                cond,
+                then,
+                otherwise,
+            } => {
+                self.load(*cond);
+                let branch = self.code.len();
+                self.emit(Op::JumpIfFalse(0), None);
+
+                // the arguments for `otherwise` are passed after the jump to `then`.
+                if otherwise.args.is_empty() {
+                    self.jumps.push((branch, otherwise.block));
+                    self.edge(then, Some(next));
+                } else {
+                    self.edge(then, None);
+                    self.code[branch] = Op::JumpIfFalse(self.code.len() as u32);
+                    self.edge(otherwise, Some(next));
+                }
+            }
+            Terminator::Return(value) => {
+                if let Some(value) = value {
+                    self.load(*value);
+                }
+                self.emit(Op::Return, None);
+            }
+            Terminator::Panic { message, span } => {
+                if let Some(message) = message {
+                    self.load(*message);
+                }
+                self.emit(Op::Panic(message.is_some()), *span);
+            }
+            Terminator::Unreachable => self.emit(Op::Unreachable, None),
+        }
+    }
+
+    /// Passes the arguments of a jump and jumps, unless the block jumped to is `next`
+    /// and execution falls through to it.
+    fn edge(&mut self, edge: &Edge, next: Option<BlockId>) {
+        // all arguments are pushed before any is stored, since a parameter may be
+        // passed as the argument for another one.
+        for &arg in &edge.args {
+            self.load(arg);
+        }
+        for param in self.func.block(edge.block).params.iter().rev() {
+            self.emit(Op::Store(self.locals[param]), None);
+        }
+
+        if Some(edge.block) != next {
+            self.jumps.push((self.code.len(), edge.block));
+            self.emit(Op::Jump(0), None);
+        }
+    }
+}
+
+struct RegisterCompiler<'a> {
+    func: &'a Func,
+    functions: &'a HashMap<&'a str, u32>,
+    constants: &'a mut Constants,
+    locals: HashMap<Value, u32>,
+    /// A local for breaking cycles of block arguments, like two parameters swapped
+    /// along a loop.
+    scratch: u32,
+    /// The first of the locals the arguments of calls are passed in.
+    args: u32,
+    code: Vec<RegOp>,
+    spans: Vec<Option<Span>>,
+    /// The index of the code each block starts at.
+    starts: Vec<u32>,
+    /// The jumps to patch with the start of the block they jump to.
+    jumps: Vec<(usize, BlockId)>,
+}
+
+impl<'a> RegisterCompiler<'a> {
+    fn new(
+        func: &'a Func,
+        functions: &'a HashMap<&'a str, u32>,
+        constants: &'a mut Constants,
+    ) -> Self {
+        let locals = locals(func);
+        let scratch = locals.len() as u32;
+
+        Self {
+            func,
+            functions,
+            constants,
+            locals,
+            scratch,
+            args: scratch + 1,
+            code: Vec::new(),
+            spans: Vec::new(),
+            starts: Vec::new(),
+            jumps: Vec::new(),
+        }
+    }
+
+    fn compile(mut self) -> Function<RegOp> {
+        for id in self.func.block_ids() {
+            self.starts.push(self.code.len() as u32);
+            let block = self.func.block(id);
+            for inst in &block.insts {
+                self.inst(inst);
+            }
+            self.term(BlockId(id.0 + 1), &block.term);
+        }
+
+        for &(index, block) in &self.jumps {
+            let start = self.starts[block.index()];
+            match &mut self.code[index] {
+                RegOp::Jump(target) | RegOp::JumpIfFalse { target, .. } => *target = start,
+                op => unreachable!("{op:?} isn't a jump"),
+            }
+        }
+
+        let args = self.func.blocks.iter().flat_map(|block| &block.insts);
+        let args = args.map(|inst| match &inst.kind {
+            InstKind::Call(_, args) => args.len() as u32,
+            _ => 0,
+        });
+
+        Function {
+            name: self.func.name.clone(),
+            params: self.func.params.len() as u32,
+            locals: self.args + args.max().unwrap_or(0),
+            returns: self.func.ret.is_some(),
+            code: self.code,
+            spans: self.spans,
+        }
+    }
+
+    fn emit(&mut self, op: RegOp, span: Option<Span>) {
+        self.code.push(op);
+        self.spans.push(span);
+    }
+
+    fn inst(&mut self, inst: &Inst) {
+        let local = |value: &Value| self.locals[value];
+        // only calls of functions returning `()` have no result.
+        let dst = inst.result.as_ref().map(local);
+
+        let op = match &inst.kind {
+            InstKind::Const(value) => RegOp::Const {
+                dst: dst.unwrap(),
+                index: self.constants.index(value),
+            },
+            InstKind::Unary(op, operand) => RegOp::Unary {
+                op: *op,
+                ty: self.func.ty(*operand),
+                dst: dst.unwrap(),
+                src: local(operand),
+            },
+            InstKind::Binary(op, lhs, rhs) => RegOp::Binary {
+                op: *op,
+                ty: self.func.ty(*lhs),
+                dst: dst.unwrap(),
+                lhs: local(lhs),
+                rhs: local(rhs),
+            },
+            InstKind::Cast(operand) => RegOp::Cast {
+                ty: self.func.ty(inst.result.unwrap()),
+                dst: dst.unwrap(),
+                src: local(operand),
+            },
+            InstKind::Call(name, args) => {
+                for (index, arg) in (0..).zip(args) {
+                    let (dst, src) = (self.args + index, self.locals[arg]);
+                    self.emit(RegOp::Move { dst, src }, None);
+                }
+                RegOp::Call {
+                    function: self.functions[name.as_str()],
+                    args: self.args,
+                    dst,
+                }
+            }
+        };
+        self.emit(op, inst.span);
+    }
+
+    /// Compiles the terminator of a block followed by the block `next`.
+    fn term(&mut self, next: BlockId, term: &Terminator) {
+        match term {
+            Terminator::Jump(edge) => self.edge(edge, Some(next)),
+            Terminator::Branch {
+                cond,
+                then,
+                otherwise,
+            } => {
+                let branch = self.code.len();
+                let cond = self.locals[cond];
+                self.emit(RegOp::JumpIfFalse { cond, target: 0 }, None);
+
+                // the arguments for `otherwise` are passed after the jump to `then`.
+                if otherwise.args.is_empty() {
+                    self.jumps.push((branch, otherwise.block));
+                    self.edge(then, Some(next));
+                } else {
+                    self.edge(then, None);
+                    let target = self.code.len() as u32;
+                    self.code[branch] = RegOp::JumpIfFalse { cond, target };
+                    self.edge(otherwise, Some(next));
+                }
+            }
+            Terminator::Return(value) => {
+                let value = value.map(|value| self.locals[&value]);
+                self.emit(RegOp::Return(value), None);
+            }
+            Terminator::Panic { message, span } => {
+                let message = message.map(|message| self.locals[&message]);
+                self.emit(RegOp::Panic(message), *span);
+            }
+            Terminator::Unreachable => self.emit(RegOp::Unreachable, None),
+        }
+    }
+
+    /// Moves the arguments of a jump into the parameters of the block and jumps,
+    /// unless the block jumped to is `next` and execution falls through to it.
+    fn edge(&mut self, edge: &Edge, next: Option<BlockId>) {
+        let params = &self.func.block(edge.block).params;
+        let mut moves: Vec<_> = params
+            .iter()
+            .zip(&edge.args)
+            .map(|(param, arg)| (self.locals[param], self.locals[arg]))
+            .filter(|(dst, src)| dst != src)
+            .collect();
+
+        // the parameters are set at once, so a move may only overwrite a local once
+        // no other move still reads it.
+        while !moves.is_empty() {
+            let free = (0..moves.len()).find(|&index| {
+                let dst = moves[index].0;
+                moves.iter().all(|&(_, src)| src != dst)
+            });
+            match free {
+                Some(index) => {
+                    let (dst, src) = moves.remove(index);
+                    self.emit(RegOp::Move { dst, src }, None);
+                }
+                None => {
+                    // every remaining move is part of a cycle, one of which is broken
+                    // by saving the local it overwrites first.
+                    let saved = moves[0].0;
+                    let scratch = self.scratch;
+                    self.emit(
+                        RegOp::Move {
+                            dst: scratch,
+                            src: saved,
+                        },
+                        None,
+                    );
+                    for (_, src) in &mut moves {
+                        if *src == saved {
+                            *src = scratch;
+                        }
+                    }
+                }
+            }
+        }
+
+        if Some(edge.block) != next {
+            self.jumps.push((self.code.len(), edge.block));
+            self.emit(RegOp::Jump(0), None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ir::parse::parse_module;
+
+    use crate::compile::compile;
+
+    #[test]
+    fn compiles() {
+        let source = "
+            fn @max(%0: i64, %1: i64) -> i64 {
+            bb0:
+              %2 = gt i64 %0, %1
+              br %2, bb1(%0), bb1(%1)
+            bb1(%3: i64):
+              ret i64 %3
+            }
+
+            fn @main() -> unit {
+            bb0:
+              %0 = const i64 1
+              %1 = const i64 2
+              %2 = call i64 @max(%0, %1)
+              %3 = const str \"done\"
+              panic %3
+            }
+        ";
+        let program = compile(&parse_module(source).unwrap());
+        let expected = "\
+fn @max: 2 params, 4 locals
+     0  load 0
+     1  load 1
+     2  gt i64
+     3  store 2
+     4  load 2
+     5  jump_if_false 9
+     6  load 0
+     7  store 3
+     8  jump 11
+     9  load 1
+    10  store 3
+    11  load 3
+    12  ret
+
+fn @main: 0 params, 4 locals
+     0  const 0  // 1
+     1  store 0
+     2  const 1  // 2
+     3  store 1
+     4  load 0
+     5  load 1
+     6  call 0  // @max
+     7  store 2
+     8  const 2  // \"done\"
+     9  store 3
+    10  load 3
+    11  panic message
+";
+        assert_eq!(program.to_string(), expected);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod bytecode;
+pub mod compile;
+pub mod register;
+pub mod runtime;
+pub mod stack;
@@ -0,0 +1,245 @@
+use ir::inst::Const;
+
+use crate::{
+    bytecode::{Program, RegOp},
+    runtime::{self, MAX_CALL_DEPTH, RuntimeError, RuntimeErrorKind},
+};
+
+/// A call that hasn't returned yet.
+struct Frame {
+    function: u32,
+    /// The index of the next op to execute.
+    pc: usize,
+    /// The index of the first local of the call among the registers.
+    base: usize,
+    /// The register of the caller receiving the result.
+    dst: Option<usize>,
+}
+
+/// Executes register bytecode, where each call owns a fixed number of registers
+/// starting at the arguments passed to it, which lie at the end of the registers of
+/// its caller.
+pub struct RegisterVm<'a> {
+    program: &'a Program<RegOp>,
+    registers: Vec<Const>,
+    frames: Vec<Frame>,
+}
+
+impl<'a> RegisterVm<'a> {
+    pub fn new(program: &'a Program<RegOp>) -> Self {
+        Self {
+            program,
+            registers: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Calls the function at the given index of [Program::functions] and runs it to
+    /// completion, returning its result unless it returns `()`.
+    pub fn run(&mut self, function: u32, args: Vec<Const>) -> Result<Option<Const>, RuntimeError> {
+        self.registers.clear();
+        self.frames.clear();
+        self.registers.extend(args);
+        self.enter(function, 0, None);
+        self.execute()
+    }
+
+    /// Starts a call of a function whose arguments are in the registers from `base`.
+    fn enter(&mut self, function: u32, base: usize, dst: Option<usize>) {
+        let func = &self.program.functions[function as usize];
+        // the registers are only ever grown, since every local is set before it's read.
+        let end = base + func.locals as usize;
+        if self.registers.len() < end {
+            self.registers.resize(end, Const::Int(0));
+        }
+        self.frames.push(Frame {
+            function,
+            pc: 0,
+            base,
+            dst,
+        });
+    }
+
+    fn execute(&mut self) -> Result<Option<Const>, RuntimeError> {
+        let program = self.program;
+
+        loop {
+            let frame = self.frames.last_mut().expect("a function is running");
+            let func = &program.functions[frame.function as usize];
+            let pc = frame.pc;
+            frame.pc += 1;
+            let base = frame.base;
+
+            let error = |kind| RuntimeError {
+                kind,
+                span: func.spans[pc],
+            };
+            let registers = &mut self.registers;
+            let local = |local: u32| base + local as usize;
+
+            match func.code[pc] {
+                RegOp::Const { dst, index } => {
+                    registers[local(dst)] = program.constants[index as usize].clone();
+                }
+                RegOp::Move { dst, src } => registers[local(dst)] = registers[local(src)].clone(),
+                RegOp::Unary { op, ty, dst, src } => {
+                    let result = runtime::unary(op, &registers[local(src)], ty).map_err(error)?;
+                    registers[local(dst)] = result;
+                }
+                RegOp::Binary {
+                    op,
+                    ty,
+                    dst,
+                    lhs,
+                    rhs,
+                } => {
+                    let (lhs, rhs) = (&registers[local(lhs)], &registers[local(rhs)]);
+                    let result = runtime::binary(op, lhs, rhs, ty).map_err(error)?;
+                    registers[local(dst)] = result;
+                }
+                RegOp::Cast { ty, dst, src } => {
+                    let result = registers[local(src)].cast(ty);
+                    registers[local(dst)] = result.expect("casts are verified");
+                }
+                RegOp::Jump(target) => frame.pc = target as usize,
+                RegOp::JumpIfFalse { cond, target } => {
+                    if registers[local(cond)] == Const::Bool(false) {
+                        frame.pc = target as usize;
+                    }
+                }
+                RegOp::Call {
+                    function,
+                    args,
+                    dst,
+                } => {
+                    if self.frames.len() >= MAX_CALL_DEPTH {
+                        return Err(error(RuntimeErrorKind::StackOverflow));
+                    }
+                    self.enter(function, local(args), dst.map(local));
+                }
+                RegOp::Return(value) => {
+                    let value = value.map(|value| registers[local(value)].clone());
+                    let frame = self.frames.pop().expect("a function is running");
+                    if self.frames.is_empty() {
+                        return Ok(value);
+                    }
+                    if let (Some(dst), Some(value)) = (frame.dst, value) {
+                        registers[dst] = value;
+                    }
+                }
+                RegOp::Panic(message) => {
+                    let message = message.map(|message| match &registers[local(message)] {
+                        Const::Str(message) => message.clone(),
+                        value => unreachable!("panic messages are `str`s, not {value}"),
+                    });
+                    return Err(error(RuntimeErrorKind::Panic(message)));
+                }
+                RegOp::Unreachable => return Err(error(RuntimeErrorKind::Unreachable)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ir::{inst::Const, parse::parse_module};
+
+    use crate::{
+        compile::{compile, compile_registers},
+        register::RegisterVm,
+        runtime::RuntimeErrorKind,
+        stack::StackVm,
+    };
+
+    /// Runs `@main` of the IR on both virtual machines, checking that they agree.
+    fn run(source: &str) -> Result<Option<Const>, RuntimeErrorKind> {
+        let module = parse_module(source).unwrap();
+        let (program, registers) = (compile(&module), compile_registers(&module));
+        let main = program.function("main").unwrap();
+
+        let result = RegisterVm::new(&registers).run(main, Vec::new());
+        assert_eq!(result, StackVm::new(&program).run(main, Vec::new()));
+        result.map_err(|err| err.kind)
+    }
+
+    #[test]
+    fn runs() {
+        // `@swap` passes its parameters swapped along its loop, which needs a scratch
+        // register.
+        let source = "
+            fn @fib(%0: i64) -> i64 {
+            bb0:
+              %1 = const i64 0
+              %2 = const i64 1
+              jump bb1(%1, %2, %1)
+            bb1(%3: i64, %4: i64, %5: i64):
+              %6 = lt i64 %5, %0
+              br %6, bb2, bb3
+            bb2:
+              %7 = add i64 %3, %4
+              %8 = const i64 1
+              %9 = add i64 %5, %8
+              jump bb1(%4, %7, %9)
+            bb3:
+              ret i64 %3
+            }
+
+            fn @swap(%0: i64, %1: i64, %2: i64) -> i64 {
+            bb0:
+              jump bb1(%0, %1, %2)
+            bb1(%3: i64, %4: i64, %5: i64):
+              %6 = const i64 0
+              %7 = eq i64 %5, %6
+              br %7, bb2, bb3
+            bb2:
+              %8 = const i64 10
+              %9 = mul i64 %3, %8
+              %10 = add i64 %9, %4
+              ret i64 %10
+            bb3:
+              %11 = const i64 1
+              %12 = sub i64 %5, %11
+              jump bb1(%4, %3, %12)
+            }
+
+            fn @main() -> i64 {
+            bb0:
+              %0 = const i64 50
+              %1 = call i64 @fib(%0)
+              %2 = const i64 1
+              %3 = const i64 2
+              %4 = const i64 3
+              %5 = call i64 @swap(%2, %3, %4)
+              %6 = add i64 %1, %5
+              ret i64 %6
+            }
+        ";
+        assert_eq!(run(source), Ok(Some(Const::Int(12586269025 + 21))));
+    }
+
+    #[test]
+    fn errors() {
+        let source = "
+            fn @down(%0: u8) -> u8 {
+            bb0:
+              %1 = const u8 1
+              %2 = sub u8 %0, %1
+              %3 = call u8 @down(%2)
+              ret u8 %3
+            }
+
+            fn @main() -> u8 {
+            bb0:
+              %0 = const u8 3
+              %1 = call u8 @down(%0)
+              ret u8 %1
+            }
+        ";
+        assert!(matches!(run(source), Err(RuntimeErrorKind::Arithmetic(_))));
+
+        let deep = source
+            .replace("const u8 3", "const u8 255")
+            .replace("u8", "i64");
+        assert_eq!(run(&deep), Err(RuntimeErrorKind::StackOverflow));
+    }
+}
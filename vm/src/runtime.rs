@@ -0,0 +1,183 @@
+use std::fmt;
+
+use ir::inst::Const;
+use semantic::{consts::ConstValue, fold::FoldErrorKind, ty::Primitive};
+use syntax::{
+    ast::{BinaryOp, UnaryOp},
+    diagnostics::Diagnostic,
+    messages::message,
+    token::Span,
+};
+
+/// The most calls that may be active at once before a program stops with
+/// [RuntimeErrorKind::StackOverflow].
+pub const MAX_CALL_DEPTH: usize = 4096;
+
+/// An error that stops a running program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
+    /// The source of the failing operation, if it has one.
+    pub span: Option<Span>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuntimeErrorKind {
+    /// An integer operation that overflowed or divided by zero.
+    Arithmetic(FoldErrorKind),
+    /// A call exceeding [MAX_CALL_DEPTH].
+    StackOverflow,
+    /// A `panic` with an optional message.
+    Panic(Option<String>),
+    /// Code that was compiled as unreachable, like the end of a function returning `!`.
+    Unreachable,
+}
+
+impl RuntimeError {
+    /// Returns the stable code of the error, see [syntax::explain::explain].
+    pub fn code(&self) -> &'static str {
+        "E0039"
+    }
+
+    /// Converts the error into a [Diagnostic] pointing at the failing operation, or
+    /// returns [None] if it has no source.
+    pub fn diagnostic(&self) -> Option<Diagnostic> {
+        Some(Diagnostic::error(self.to_string(), self.span?).with_code(self.code()))
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match &self.kind {
+            RuntimeErrorKind::Arithmetic(FoldErrorKind::Overflow { op, ty }) => {
+                let args = [("op", op as &dyn fmt::Display), ("ty", &ty.name())];
+                message("E0039.overflow", &args)
+            }
+            RuntimeErrorKind::Arithmetic(FoldErrorKind::DivisionByZero) => {
+                message("E0039.division", &[])
+            }
+            RuntimeErrorKind::StackOverflow => {
+                message("E0039.stack", &[("limit", &MAX_CALL_DEPTH)])
+            }
+            RuntimeErrorKind::Panic(Some(text)) => {
+                message("E0039.message", &[("message", &format!("{text:?}"))])
+            }
+            RuntimeErrorKind::Panic(None) => message("E0039", &[]),
+            RuntimeErrorKind::Unreachable => message("E0039.unreachable", &[]),
+        };
+        write!(f, "{message}")
+    }
+}
+
+/// Applies a unary operation to an operand of type `ty`, like the constant folding
+/// in [ConstValue::unary] but with floats that may become infinite or `NaN`.
+pub fn unary(op: UnaryOp, operand: &Const, ty: Primitive) -> Result<Const, RuntimeErrorKind> {
+    if let (UnaryOp::Neg, Const::Float(bits)) = (op, operand) {
+        return Ok(Const::Float((-f64::from_bits(*bits)).to_bits()));
+    }
+
+    match ConstValue::unary(op, &operand.to_value(), ty) {
+        Ok(Some(value)) => Ok(Const::from(value)),
+        Ok(None) => unreachable!("`{}` isn't defined for {operand}", op.symbol()),
+        Err(kind) => Err(RuntimeErrorKind::Arithmetic(kind)),
+    }
+}
+
+/// Applies a binary operation to operands of type `ty`, see [unary].
+pub fn binary(
+    op: BinaryOp,
+    lhs: &Const,
+    rhs: &Const,
+    ty: Primitive,
+) -> Result<Const, RuntimeErrorKind> {
+    if let (Const::Float(lhs), Const::Float(rhs)) = (lhs, rhs) {
+        let (lhs, rhs) = (f64::from_bits(*lhs), f64::from_bits(*rhs));
+        let value = match op {
+            BinaryOp::Add => lhs + rhs,
+            BinaryOp::Sub => lhs - rhs,
+            BinaryOp::Mul => lhs * rhs,
+            BinaryOp::Div => lhs / rhs,
+            BinaryOp::Rem => lhs % rhs,
+            // comparisons with `NaN` are false, except for `!=`.
+            BinaryOp::Equal => return Ok(Const::Bool(lhs == rhs)),
+            BinaryOp::Unequal => return Ok(Const::Bool(lhs != rhs)),
+            BinaryOp::LessThan => return Ok(Const::Bool(lhs < rhs)),
+            BinaryOp::LessEqual => return Ok(Const::Bool(lhs <= rhs)),
+            BinaryOp::GreaterThan => return Ok(Const::Bool(lhs > rhs)),
+            BinaryOp::GreaterEqual => return Ok(Const::Bool(lhs >= rhs)),
+        };
+        let value = match ty {
+            Primitive::F32 => value as f32 as f64,
+            _ => value,
+        };
+        return Ok(Const::Float(value.to_bits()));
+    }
+
+    match ConstValue::binary(op, &lhs.to_value(), &rhs.to_value(), ty) {
+        Ok(Some(value)) => Ok(Const::from(value)),
+        Ok(None) => unreachable!("`{}` isn't defined for {lhs} and {rhs}", op.symbol()),
+        Err(kind) => Err(RuntimeErrorKind::Arithmetic(kind)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ir::inst::Const;
+    use semantic::{fold::FoldErrorKind, ty::Primitive};
+    use syntax::ast::BinaryOp;
+
+    use crate::runtime::{RuntimeErrorKind, binary};
+
+    #[test]
+    fn arithmetic() {
+        let float = |value: f64| Const::Float(value.to_bits());
+
+        assert_eq!(
+            binary(
+                BinaryOp::Add,
+                &Const::Int(100),
+                &Const::Int(27),
+                Primitive::I8
+            ),
+            Ok(Const::Int(127))
+        );
+        assert_eq!(
+            binary(
+                BinaryOp::Add,
+                &Const::Int(100),
+                &Const::Int(28),
+                Primitive::I8
+            ),
+            Err(RuntimeErrorKind::Arithmetic(FoldErrorKind::Overflow {
+                op: "+",
+                ty: Primitive::I8
+            }))
+        );
+        assert_eq!(
+            binary(
+                BinaryOp::Rem,
+                &Const::Int(1),
+                &Const::Int(0),
+                Primitive::U32
+            ),
+            Err(RuntimeErrorKind::Arithmetic(FoldErrorKind::DivisionByZero))
+        );
+        assert_eq!(
+            binary(BinaryOp::Div, &float(1.0), &float(0.0), Primitive::F64),
+            Ok(float(f64::INFINITY))
+        );
+        assert_eq!(
+            binary(
+                BinaryOp::Unequal,
+                &float(f64::NAN),
+                &float(f64::NAN),
+                Primitive::F64
+            ),
+            Ok(Const::Bool(true))
+        );
+        assert_eq!(
+            binary(BinaryOp::Add, &float(0.1), &float(0.2), Primitive::F32),
+            Ok(float((0.1 + 0.2) as f32 as f64))
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use ir::inst::Const;
+
+use crate::{
+    bytecode::{Op, Program},
+    runtime::{self, MAX_CALL_DEPTH, RuntimeError, RuntimeErrorKind},
+};
+
+/// A call that hasn't returned yet.
+struct Frame {
+    function: u32,
+    /// The index of the next op to execute.
+    pc: usize,
+    /// The index of the first local of the call on the stack.
+    base: usize,
+}
+
+/// Executes bytecode on a stack of values, which holds the locals of each call below
+/// the operands of the ops executed in it.
+pub struct StackVm<'a> {
+    program: &'a Program,
+    stack: Vec<Const>,
+    frames: Vec<Frame>,
+}
+
+impl<'a> StackVm<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Self {
+            program,
+            stack: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Calls the function at the given index of [Program::functions] and runs it to
+    /// completion, returning its result unless it returns `()`.
+    pub fn run(&mut self, function: u32, args: Vec<Const>) -> Result<Option<Const>, RuntimeError> {
+        self.stack.clear();
+        self.frames.clear();
+        self.stack.extend(args);
+        self.enter(function);
+        self.execute()
+    }
+
+    /// Starts a call of a function whose arguments are on top of the stack.
+    fn enter(&mut self, function: u32) {
+        let func = &self.program.functions[function as usize];
+        let base = self.stack.len() - func.params as usize;
+        self.stack
+            .resize(base + func.locals as usize, Const::Int(0));
+        self.frames.push(Frame {
+            function,
+            pc: 0,
+            base,
+        });
+    }
+
+    fn execute(&mut self) -> Result<Option<Const>, RuntimeError> {
+        let program = self.program;
+
+        loop {
+            let frame = self.frames.last_mut().expect("a function is running");
+            let func = &program.functions[frame.function as usize];
+            let pc = frame.pc;
+            frame.pc += 1;
+            let base = frame.base;
+
+            let error = |kind| RuntimeError {
+                kind,
+                span: func.spans[pc],
+            };
+
+            match func.code[pc] {
+                Op::Const(index) => self.stack.push(program.constants[index as usize].clone()),
+                Op::Load(local) => {
+                    let value = self.stack[base + local as usize].clone();
+                    self.stack.push(value);
+                }
+                Op::Store(local) => self.stack[base + local as usize] = pop(&mut self.stack),
+                Op::Unary(op, ty) => {
+                    let operand = pop(&mut self.stack);
+                    let result = runtime::unary(op, &operand, ty).map_err(error)?;
+                    self.stack.push(result);
+                }
+                Op::Binary(op, ty) => {
+                    let rhs = pop(&mut self.stack);
+                    let lhs = pop(&mut self.stack);
+                    let result = runtime::binary(op, &lhs, &rhs, ty).map_err(error)?;
+                    self.stack.push(result);
+                }
+                Op::Cast(ty) => {
+                    let operand = pop(&mut self.stack);
+                    self.stack
+                        .push(operand.cast(ty).expect("casts are verified"));
+                }
+                Op::Jump(target) => frame.pc = target as usize,
+                Op::JumpIfFalse(target) => {
+                    if pop(&mut self.stack) == Const::Bool(false) {
+                        frame.pc = target as usize;
+                    }
+                }
+                Op::Call(index) => {
+                    if self.frames.len() >= MAX_CALL_DEPTH {
+                        return Err(error(RuntimeErrorKind::StackOverflow));
+                    }
+                    self.enter(index);
+                }
+                Op::Return => {
+                    let result = func.returns.then(|| pop(&mut self.stack));
+                    self.stack.truncate(base);
+                    self.frames.pop();
+                    if self.frames.is_empty() {
+                        return Ok(result);
+                    }
+                    self.stack.extend(result);
+                }
+                Op::Panic(has_message) => {
+                    let message = has_message.then(|| match pop(&mut self.stack) {
+                        Const::Str(message) => message,
+                        value => unreachable!("panic messages are `str`s, not {value}"),
+                    });
+                    return Err(error(RuntimeErrorKind::Panic(message)));
+                }
+                Op::Unreachable => return Err(error(RuntimeErrorKind::Unreachable)),
+            }
+        }
+    }
+}
+
+fn pop(stack: &mut Vec<Const>) -> Const {
+    stack.pop().expect("the operands are on the stack")
+}
+
+#[cfg(test)]
+mod test {
+    use ir::{func::Module, inst::Const, lower::lower, pass::PassManager};
+    use semantic::{
+        callgraph::CallGraph, check::check, consts::eval_consts, fold::FoldErrorKind,
+        mono::monomorphize, program::build_program, resolve::resolve, ty::Primitive,
+    };
+    use syntax::{lexer::Lexer, parser::Parser};
+
+    use crate::{
+        compile::compile,
+        runtime::{RuntimeError, RuntimeErrorKind},
+        stack::StackVm,
+    };
+
+    fn lower_source(source: &str) -> Module {
+        let tokens = Lexer::new(source).collect_tokens().unwrap();
+        let (module, errors) = Parser::new(&tokens).parse_module();
+        assert_eq!(errors, []);
+
+        let resolutions = resolve(&module).resolutions;
+        let consts = eval_consts(&module, &resolutions).values;
+        let checked = check(&module, &resolutions, &consts);
+        assert_eq!(checked.errors, []);
+
+        let instances = monomorphize(&resolutions, &checked.types);
+        let call_graph = CallGraph::build(&module, &resolutions);
+        let program = build_program(&module, &resolutions, &checked.types, &call_graph).unwrap();
+        lower(
+            &module,
+            &resolutions,
+            &checked.types,
+            &consts,
+            &instances,
+            &program,
+        )
+        .unwrap()
+    }
+
+    /// Runs `main` of the source, optimized or not, returning its result and the
+    /// source of the failing operation.
+    fn run(source: &str, optimize: bool) -> Result<Option<Const>, (RuntimeErrorKind, &str)> {
+        let mut module = lower_source(source);
+        if optimize {
+            PassManager::default().run(&mut module, |_, _| {});
+        }
+        let program = compile(&module);
+        let main = program.function("main").unwrap();
+
+        StackVm::new(&program)
+            .run(main, Vec::new())
+            .map_err(|RuntimeError { kind, span }| {
+                let span = span.unwrap();
+                (kind, &source[span.start..span.end])
+            })
+    }
+
+    #[test]
+    fn runs() {
+        let source = "
+            fn fib(n: i64) -> i64 {
+                if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+            }
+            fn main() -> i32 {
+                var total: i64 = 0;
+                for i in 0..10 { total = total + fib(i); }
+                let small: u8 = (total as u8) + 1;
+                var result: i32 = -1;
+                if 0.5 * 3.0 > 1.0 { if small == 89 { result = total as i32; } }
+                result
+            }
+        ";
+        for optimize in [false, true] {
+            assert_eq!(run(source, optimize), Ok(Some(Const::Int(88))));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let source = "
+            fn scale(x: i8) -> i8 { x * 2 }
+            fn main() -> i32 { scale(100) as i32 }
+        ";
+        let overflow = RuntimeErrorKind::Arithmetic(FoldErrorKind::Overflow {
+            op: "*",
+            ty: Primitive::I8,
+        });
+        assert_eq!(run(source, false), Err((overflow.clone(), "x * 2")));
+        // inlining keeps the source of the failing operation.
+        assert_eq!(run(source, true), Err((overflow, "x * 2")));
+
+        let source = "
+            fn div(a: i32, b: i32) -> i32 { a / b }
+            fn main() -> i32 { div(1, 0) }
+        ";
+        let division = RuntimeErrorKind::Arithmetic(FoldErrorKind::DivisionByZero);
+        assert_eq!(run(source, false), Err((division, "a / b")));
+
+        let source = "
+            fn down(n: i64) -> i64 { down(n + 1) }
+            fn main() -> i32 { down(0) as i32 }
+        ";
+        assert_eq!(
+            run(source, false),
+            Err((RuntimeErrorKind::StackOverflow, "down(n + 1)"))
+        );
+
+        let source = "fn main() { assert(1 > 2, \"math\"); }";
+        assert_eq!(
+            run(source, false),
+            Err((
+                RuntimeErrorKind::Panic(Some("math".into())),
+                "assert(1 > 2, \"math\")"
+            ))
+        );
+    }
+}